@@ -1,6 +1,8 @@
 //! Collision detection strategies.
 
-use crate::context::Rect;
+use std::cmp::Ordering;
+
+use crate::context::{DndId, Rect};
 
 /// Collision detection strategy.
 #[derive(Debug, Clone, Copy, Default)]
@@ -14,6 +16,9 @@ pub enum CollisionStrategy {
     ClosestCorners,
     /// Pointer within bounds.
     PointerWithin,
+    /// Innermost container under the pointer, then largest intersection;
+    /// see [`CollisionDetection::deepest_container`].
+    DeepestContainer,
 }
 
 /// Collision detection result.
@@ -25,6 +30,48 @@ pub struct Collision {
     pub ratio: f64,
 }
 
+/// A droppable with its place in the container hierarchy.
+#[derive(Debug, Clone)]
+pub struct DropTarget {
+    pub id: DndId,
+    pub rect: Rect,
+    /// Enclosing droppable, if nested.
+    pub parent: Option<DndId>,
+    /// Stacking order among overlapping targets; higher is on top.
+    pub z_index: i32,
+}
+
+impl DropTarget {
+    pub fn new(id: impl Into<DndId>, rect: Rect) -> Self {
+        Self {
+            id: id.into(),
+            rect,
+            parent: None,
+            z_index: 0,
+        }
+    }
+
+    pub fn with_parent(mut self, parent: impl Into<DndId>) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+}
+
+/// Droppable rects by id, for [`CollisionDetection::detect_with_index`].
+#[cfg(feature = "spatial-index")]
+pub type DroppableIndex = rsc_flow::SpatialIndex<DndId>;
+
+/// A droppable rect as the spatial index stores it.
+#[cfg(feature = "spatial-index")]
+pub(crate) fn flow_rect(rect: &Rect) -> rsc_flow::Rect {
+    rsc_flow::Rect::new(rect.x, rect.y, rect.width, rect.height)
+}
+
 /// Collision detection utilities.
 pub struct CollisionDetection;
 
@@ -53,7 +100,84 @@ impl CollisionDetection {
                     Vec::new()
                 }
             }
+            CollisionStrategy::DeepestContainer => {
+                let targets: Vec<DropTarget> = droppable_rects
+                    .iter()
+                    .map(|(id, rect)| DropTarget::new(id.clone(), *rect))
+                    .collect();
+                Self::deepest_container(active_rect, &targets, pointer)
+            }
+        }
+    }
+
+    /// Rank nested droppables, best first.
+    ///
+    /// Targets containing the pointer come first: the deepest in the
+    /// `parent` chain, then the highest `z_index`, then the smallest, so
+    /// an unlabelled inner rect still beats its container. The remaining
+    /// targets intersecting `active` follow by intersection area. Ties,
+    /// such as a pointer exactly on a border shared by two siblings, go
+    /// to the larger intersection, then the lower id.
+    pub fn deepest_container(
+        active: &Rect,
+        targets: &[DropTarget],
+        pointer: Option<(f64, f64)>,
+    ) -> Vec<Collision> {
+        let active_area = active.width * active.height;
+        let mut ranked: Vec<(bool, usize, &DropTarget, f64)> = targets
+            .iter()
+            .filter_map(|target| {
+                let under_pointer =
+                    pointer.is_some_and(|(px, py)| target.rect.contains_point(px, py));
+                let overlap = Self::intersection_area(active, &target.rect);
+                (under_pointer || overlap > 0.0)
+                    .then(|| (under_pointer, Self::depth(target, targets), target, overlap))
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            let by_pointer = b.0.cmp(&a.0);
+            let nested = if a.0 && b.0 {
+                let area = |t: &DropTarget| t.rect.width * t.rect.height;
+                b.1.cmp(&a.1)
+                    .then(b.2.z_index.cmp(&a.2.z_index))
+                    .then(area(a.2).total_cmp(&area(b.2)))
+            } else {
+                Ordering::Equal
+            };
+            by_pointer
+                .then(nested)
+                .then(b.3.total_cmp(&a.3))
+                .then(b.2.z_index.cmp(&a.2.z_index))
+                .then(a.2.id.cmp(&b.2.id))
+        });
+        ranked
+            .into_iter()
+            .map(|(under_pointer, _, target, overlap)| Collision {
+                id: target.id.clone(),
+                ratio: if under_pointer || active_area <= 0.0 {
+                    1.0
+                } else {
+                    overlap / active_area
+                },
+            })
+            .collect()
+    }
+
+    /// Number of ancestors of `target` among `targets`. A cycle stops the
+    /// count.
+    fn depth(target: &DropTarget, targets: &[DropTarget]) -> usize {
+        let mut depth = 0;
+        let mut parent = target.parent.as_deref();
+        while let Some(id) = parent
+            && depth < targets.len()
+        {
+            let Some(next) = targets.iter().find(|t| t.id == id) else {
+                break;
+            };
+            depth += 1;
+            parent = next.parent.as_deref();
         }
+        depth
     }
 
     /// Find droppables that intersect with the active rect.
@@ -169,6 +293,57 @@ impl CollisionDetection {
     }
 }
 
+#[cfg(feature = "spatial-index")]
+impl CollisionDetection {
+    /// Indexed droppables that `active` overlaps or touches, or that contain
+    /// `pointer`, in no particular order.
+    pub fn candidates(
+        index: &DroppableIndex,
+        active: &Rect,
+        pointer: Option<(f64, f64)>,
+    ) -> Vec<DndId> {
+        let mut ids = index.query_rect(&flow_rect(active));
+        if let Some((px, py)) = pointer {
+            for id in index.query_point(&rsc_flow::Position::new(px, py)) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// [`detect`](Self::detect) over the droppables in `index`. Strategies
+    /// that need overlap or the pointer only look at
+    /// [`candidates`](Self::candidates); the closest-center and
+    /// closest-corner strategies still measure every droppable.
+    pub fn detect_with_index(
+        active: &Rect,
+        index: &DroppableIndex,
+        strategy: CollisionStrategy,
+        pointer: Option<(f64, f64)>,
+    ) -> Vec<Collision> {
+        let rect = |r: &rsc_flow::Rect| {
+            Rect::new(
+                r.position.x,
+                r.position.y,
+                r.dimensions.width,
+                r.dimensions.height,
+            )
+        };
+        let droppables: Vec<(String, Rect)> = match strategy {
+            CollisionStrategy::ClosestCenter | CollisionStrategy::ClosestCorners => {
+                index.iter().map(|(id, r)| (id.clone(), rect(r))).collect()
+            }
+            _ => Self::candidates(index, active, pointer)
+                .into_iter()
+                .filter_map(|id| index.get(&id).map(|r| (id, rect(r))))
+                .collect(),
+        };
+        Self::detect(active, &droppables, strategy, pointer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,4 +366,159 @@ mod tests {
         assert_eq!(collisions.len(), 1);
         assert_eq!(collisions[0].id, "a");
     }
+
+    /// Workflow "w" holds context "c", which holds preset "p"; "s" is a
+    /// sibling of "c", overlapping it.
+    fn nested() -> Vec<DropTarget> {
+        vec![
+            DropTarget::new("w", Rect::new(0.0, 0.0, 400.0, 300.0)),
+            DropTarget::new("p", Rect::new(20.0, 20.0, 60.0, 40.0)).with_parent("c"),
+            DropTarget::new("c", Rect::new(10.0, 10.0, 200.0, 200.0)).with_parent("w"),
+            DropTarget::new("s", Rect::new(150.0, 10.0, 200.0, 200.0)).with_parent("w"),
+        ]
+    }
+
+    fn ids(collisions: &[Collision]) -> Vec<&str> {
+        collisions.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_deepest_container_wins() {
+        let targets = nested();
+        let active = Rect::new(25.0, 25.0, 10.0, 10.0);
+        let ranked = CollisionDetection::deepest_container(&active, &targets, Some((30.0, 30.0)));
+        assert_eq!(ids(&ranked), ["p", "c", "w"]);
+
+        // Without a pointer, the largest intersection decides.
+        let active = Rect::new(190.0, 100.0, 40.0, 10.0);
+        let ranked = CollisionDetection::deepest_container(&active, &targets, None);
+        // "s" and "w" cover it fully; the tie goes by id.
+        assert_eq!(ids(&ranked), ["s", "w", "c"]);
+        assert_eq!(ranked[1].ratio, 1.0);
+        assert_eq!(ranked[2].ratio, 0.5);
+    }
+
+    #[test]
+    fn test_overlapping_siblings() {
+        let mut targets = nested();
+        // Pointer in the overlap of "c" and "s": equal depth, area and z,
+        // so the larger intersection wins.
+        let active = Rect::new(170.0, 100.0, 60.0, 10.0);
+        let ranked = CollisionDetection::deepest_container(&active, &targets, Some((180.0, 105.0)));
+        assert_eq!(ids(&ranked), ["s", "c", "w"]);
+
+        // Stacking order beats overlap.
+        targets[2].z_index = 1;
+        let ranked = CollisionDetection::deepest_container(&active, &targets, Some((180.0, 105.0)));
+        assert_eq!(ids(&ranked), ["c", "s", "w"]);
+    }
+
+    #[test]
+    fn test_shared_border() {
+        let targets = [
+            DropTarget::new("left", Rect::new(0.0, 0.0, 100.0, 100.0)),
+            DropTarget::new("right", Rect::new(100.0, 0.0, 100.0, 100.0)),
+        ];
+        // Centered on the border: both contain the pointer and overlap
+        // equally, so the order is by id.
+        let active = Rect::new(90.0, 40.0, 20.0, 20.0);
+        let ranked = CollisionDetection::detect(
+            &active,
+            &[
+                ("right".to_string(), targets[1].rect),
+                ("left".to_string(), targets[0].rect),
+            ],
+            CollisionStrategy::DeepestContainer,
+            Some((100.0, 50.0)),
+        );
+        assert_eq!(ids(&ranked), ["left", "right"]);
+
+        // Leaning right, the larger overlap decides.
+        let active = Rect::new(95.0, 40.0, 20.0, 20.0);
+        let ranked = CollisionDetection::deepest_container(&active, &targets, Some((100.0, 50.0)));
+        assert_eq!(ids(&ranked), ["right", "left"]);
+    }
+
+    #[cfg(feature = "spatial-index")]
+    #[test]
+    fn test_indexed_detection_matches_scan() {
+        use crate::context::{DndContext, DroppableInfo};
+
+        // Deterministic xorshift, for repeatable random layouts.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let mut rect = |extent: f64, size: f64| {
+            Rect::new(
+                next() * extent,
+                next() * extent,
+                next() * size + 1.0,
+                next() * size + 1.0,
+            )
+        };
+
+        let droppables: Vec<(String, Rect)> = (0..300)
+            .map(|i| (format!("d{}", i), rect(3000.0, 250.0)))
+            .collect();
+        let index: DroppableIndex = droppables
+            .iter()
+            .map(|(id, r)| (id.clone(), flow_rect(r)))
+            .collect();
+        let sorted = |collisions: Vec<Collision>| {
+            let mut ids: Vec<String> = collisions.into_iter().map(|c| c.id).collect();
+            ids.sort();
+            ids
+        };
+        for _ in 0..100 {
+            let active = rect(3000.0, 120.0);
+            let pointer = Some(active.center());
+            for strategy in [
+                CollisionStrategy::RectIntersection,
+                CollisionStrategy::PointerWithin,
+                CollisionStrategy::ClosestCenter,
+                CollisionStrategy::ClosestCorners,
+            ] {
+                let scanned = CollisionDetection::detect(&active, &droppables, strategy, pointer);
+                let indexed =
+                    CollisionDetection::detect_with_index(&active, &index, strategy, pointer);
+                assert_eq!(sorted(indexed), sorted(scanned), "{:?}", strategy);
+            }
+            let strategy = CollisionStrategy::DeepestContainer;
+            let scanned = CollisionDetection::detect(&active, &droppables, strategy, pointer);
+            let indexed = CollisionDetection::detect_with_index(&active, &index, strategy, pointer);
+            assert_eq!(ids(&indexed), ids(&scanned));
+        }
+
+        // Nested droppables through the context, kept in step on updates.
+        let mut scan = DndContext::new();
+        for target in nested() {
+            let info = DroppableInfo {
+                rect: target.rect,
+                disabled: false,
+                accepts: Vec::new(),
+                data: serde_json::Value::Null,
+                parent: target.parent,
+                z_index: 0,
+            };
+            scan.register_droppable(target.id, info);
+        }
+        let mut indexed = scan.clone().with_droppable_index();
+        let active = Rect::new(25.0, 25.0, 10.0, 10.0);
+        let pointer = Some((30.0, 30.0));
+        assert_eq!(ids(&indexed.collisions(&active, pointer)), ["p", "c", "w"]);
+
+        for ctx in [&mut scan, &mut indexed] {
+            ctx.update_droppable_rect("p", Rect::new(300.0, 250.0, 20.0, 20.0));
+            ctx.unregister_droppable("s");
+        }
+        let active = Rect::new(295.0, 245.0, 10.0, 10.0);
+        let pointer = Some((305.0, 255.0));
+        let expected = scan.collisions(&active, pointer);
+        assert_eq!(ids(&indexed.collisions(&active, pointer)), ids(&expected));
+        assert_eq!(ids(&expected), ["p", "w"]);
+    }
 }
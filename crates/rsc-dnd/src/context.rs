@@ -2,12 +2,20 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::collision::{Collision, CollisionDetection, DropTarget};
+#[cfg(feature = "spatial-index")]
+use crate::collision::{DroppableIndex, flow_rect};
+use crate::overlay::{DragOverlay, DropAnimation};
+use crate::preview::{DEFAULT_PREVIEW_DEBOUNCE, DragPayload, DropPreview, PreviewRegistry};
+use crate::sensors::{Activation, ActivationConstraint, PointerKind, PointerSensor, TouchSensor};
 
 /// Unique identifier for draggable/droppable elements.
 pub type DndId = String;
 
 /// Drag and drop context state.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct DndContext {
     /// Currently active (dragging) item.
     pub active: Option<DndId>,
@@ -19,6 +27,61 @@ pub struct DndContext {
     pub droppables: HashMap<DndId, DroppableInfo>,
     /// Current drag state.
     pub state: DndState,
+    /// Preview functions by droppable id.
+    pub previews: PreviewRegistry,
+    /// Preview for the current target, for the drag overlay.
+    pub preview: Option<DropPreview>,
+    /// How long the pointer must stay over a target before its preview is
+    /// computed.
+    pub preview_debounce: Duration,
+    /// When the pointer entered the current target, while its preview is
+    /// still pending.
+    preview_pending: Option<Instant>,
+    /// Latest screen reader message, for an `aria-live` region.
+    pub aria_live_message: Option<String>,
+    /// How the drag overlay animates when the drag ends.
+    pub drop_animation: DropAnimation,
+    /// Overlay of the current drag, or of the one whose drop animation is
+    /// still running.
+    overlay: Option<DragOverlay>,
+    /// Activation constraint for mouse presses.
+    pub pointer_sensor: PointerSensor,
+    /// Activation constraint for touch presses.
+    pub touch_sensor: TouchSensor,
+    /// Press waiting for its sensor's activation constraint.
+    pending: Option<PendingActivation>,
+    /// Span covering the current drag, closed when it ends.
+    #[cfg(feature = "tracing")]
+    drag_span: Option<tracing::Span>,
+    /// Droppable rects for collision queries, when enabled.
+    #[cfg(feature = "spatial-index")]
+    droppable_index: Option<DroppableIndex>,
+}
+
+impl Default for DndContext {
+    fn default() -> Self {
+        Self {
+            active: None,
+            over: None,
+            draggables: HashMap::new(),
+            droppables: HashMap::new(),
+            state: DndState::default(),
+            previews: PreviewRegistry::default(),
+            preview: None,
+            preview_debounce: DEFAULT_PREVIEW_DEBOUNCE,
+            preview_pending: None,
+            aria_live_message: None,
+            drop_animation: DropAnimation::default(),
+            overlay: None,
+            pointer_sensor: PointerSensor::default(),
+            touch_sensor: TouchSensor::default(),
+            pending: None,
+            #[cfg(feature = "tracing")]
+            drag_span: None,
+            #[cfg(feature = "spatial-index")]
+            droppable_index: None,
+        }
+    }
 }
 
 impl DndContext {
@@ -33,7 +96,12 @@ impl DndContext {
 
     /// Register a droppable element.
     pub fn register_droppable(&mut self, id: impl Into<String>, info: DroppableInfo) {
-        self.droppables.insert(id.into(), info);
+        let id = id.into();
+        #[cfg(feature = "spatial-index")]
+        if let Some(index) = &mut self.droppable_index {
+            index.insert(id.clone(), flow_rect(&info.rect));
+        }
+        self.droppables.insert(id, info);
     }
 
     /// Unregister a draggable.
@@ -41,21 +109,145 @@ impl DndContext {
         self.draggables.remove(id);
     }
 
-    /// Unregister a droppable.
+    /// Unregister a droppable and its preview function.
     pub fn unregister_droppable(&mut self, id: &str) {
+        #[cfg(feature = "spatial-index")]
+        if let Some(index) = &mut self.droppable_index {
+            index.remove(&id.to_string());
+        }
         self.droppables.remove(id);
+        self.previews.remove(id);
+    }
+
+    /// Register a function describing what dropping on `id` would do.
+    pub fn register_preview(
+        &mut self,
+        id: impl Into<String>,
+        preview: impl Fn(&DragPayload) -> DropPreview + Send + Sync + 'static,
+    ) {
+        self.previews.insert(id, std::sync::Arc::new(preview));
     }
 
     /// Start dragging.
     pub fn start_drag(&mut self, id: impl Into<String>) -> DragEvent {
         let id = id.into();
+        #[cfg(feature = "tracing")]
+        {
+            self.drag_span = Some(tracing::info_span!(
+                "drag",
+                category = "dnd",
+                entity_id = %id,
+                over = tracing::field::Empty,
+            ));
+        }
         self.active = Some(id.clone());
         self.state = DndState::Dragging;
+        self.overlay = None;
         DragEvent::Start { id }
     }
 
+    /// Start dragging with the pointer at (`x`, `y`), showing an overlay
+    /// measured from the draggable's registered rect.
+    pub fn start_drag_at(&mut self, id: impl Into<String>, x: f64, y: f64) -> DragEvent {
+        let event = self.start_drag(id);
+        let DragEvent::Start { id } = &event else {
+            return event;
+        };
+        let info = self.draggables.get(id);
+        let origin = info.map_or(Rect::new(x, y, 0.0, 0.0), |d| d.rect);
+        self.overlay = Some(DragOverlay {
+            data: info.map(|d| d.data.clone()).unwrap_or_default(),
+            hide_source: info.is_some_and(|d| d.hide_while_dragging),
+            ..DragOverlay::new(id.clone(), origin, (x, y))
+        });
+        event
+    }
+
+    /// Constraint a press of `kind` must meet before it drags.
+    pub fn activation_constraint(&self, kind: PointerKind) -> &ActivationConstraint {
+        match kind {
+            PointerKind::Mouse => &self.pointer_sensor.activation_constraint,
+            PointerKind::Touch => &self.touch_sensor.activation_constraint,
+        }
+    }
+
+    /// Press draggable `id` at (`x`, `y`). The drag starts once the
+    /// sensor's activation constraint is met, which
+    /// [`DndContext::pointer_move`] and [`DndContext::poll_activation`]
+    /// check. Presses on disabled draggables are ignored.
+    pub fn pointer_down(
+        &mut self,
+        id: impl Into<String>,
+        kind: PointerKind,
+        x: f64,
+        y: f64,
+        now: Instant,
+    ) -> Option<DragEvent> {
+        let id = id.into();
+        if self.draggables.get(&id).is_some_and(|d| d.disabled) {
+            return None;
+        }
+        self.pending = Some(PendingActivation {
+            id,
+            kind,
+            origin: (x, y),
+            last: (x, y),
+            since: now,
+        });
+        self.poll_activation(now)
+    }
+
+    /// Move the pointer. While a press is pending this may start the drag
+    /// or, past a long press's tolerance, cancel it; during a drag it
+    /// moves the drag.
+    pub fn pointer_move(&mut self, x: f64, y: f64, now: Instant) -> Option<DragEvent> {
+        if let Some(pending) = &mut self.pending {
+            pending.last = (x, y);
+            return self.poll_activation(now);
+        }
+        self.active.is_some().then(|| self.update_drag(x, y))
+    }
+
+    /// Check the pending press at `now`, for delays that pass while the
+    /// pointer stays still.
+    pub fn poll_activation(&mut self, now: Instant) -> Option<DragEvent> {
+        let pending = self.pending.as_ref()?;
+        let (dx, dy) = (
+            pending.last.0 - pending.origin.0,
+            pending.last.1 - pending.origin.1,
+        );
+        let elapsed = now.saturating_duration_since(pending.since);
+        let constraint = self.activation_constraint(pending.kind);
+        match constraint.check((dx, dy), elapsed) {
+            Activation::Pending => None,
+            Activation::Cancel => {
+                let pending = self.pending.take()?;
+                Some(DragEvent::Cancel { id: pending.id })
+            }
+            Activation::Start => {
+                let pending = self.pending.take()?;
+                let (x, y) = pending.origin;
+                let event = self.start_drag_at(pending.id, x, y);
+                self.update_drag(pending.last.0, pending.last.1);
+                Some(event)
+            }
+        }
+    }
+
+    /// Release the pointer. Ends the drag, if one started; a press that
+    /// never activated is dropped.
+    pub fn pointer_up(&mut self) -> Option<DragEndEvent> {
+        if self.pending.take().is_some() {
+            return None;
+        }
+        self.active.is_some().then(|| self.end_drag())
+    }
+
     /// Update drag position.
     pub fn update_drag(&mut self, x: f64, y: f64) -> DragEvent {
+        if let Some(overlay) = &mut self.overlay {
+            overlay.move_to(x, y);
+        }
         DragEvent::Move {
             id: self.active.clone().unwrap_or_default(),
             x,
@@ -65,24 +257,213 @@ impl DndContext {
 
     /// Set the current drop target.
     pub fn set_over(&mut self, id: Option<String>) {
+        self.set_over_at(id, Instant::now());
+    }
+
+    /// Set the current drop target at `now`. A new target clears the
+    /// preview until [`DndContext::refresh_preview`] computes it.
+    pub fn set_over_at(&mut self, id: Option<String>, now: Instant) {
+        if self.over == id {
+            return;
+        }
+        self.preview = None;
+        self.preview_pending = id.as_ref().map(|_| now);
         self.over = id;
     }
 
-    /// End dragging.
+    /// Compute the pending preview once the target has been stable for
+    /// the debounce interval. Call on each pointer move or animation frame.
+    pub fn refresh_preview(&mut self, now: Instant) -> Option<&DropPreview> {
+        if let Some(since) = self.preview_pending
+            && now.saturating_duration_since(since) >= self.preview_debounce
+        {
+            self.compute_preview();
+        }
+        self.preview.as_ref()
+    }
+
+    fn compute_preview(&mut self) {
+        self.preview_pending = None;
+        self.preview = match (&self.active, &self.over) {
+            (Some(active), Some(over)) => self.previews.get(over).map(|preview| {
+                preview(&DragPayload {
+                    active: active.clone(),
+                    over: over.clone(),
+                    data: self
+                        .draggables
+                        .get(active)
+                        .map(|d| d.data.clone())
+                        .unwrap_or_default(),
+                    target_data: self
+                        .droppables
+                        .get(over)
+                        .map(|d| d.data.clone())
+                        .unwrap_or_default(),
+                })
+            }),
+            _ => None,
+        };
+    }
+
+    /// Whether the active item may be dropped on the current target.
+    ///
+    /// The target must be enabled, accept the item by id or by the `type`
+    /// field of its data, and not have a blocked preview. A pending
+    /// preview is computed immediately.
+    pub fn can_drop(&mut self) -> bool {
+        let (Some(active), Some(over)) = (&self.active, &self.over) else {
+            return false;
+        };
+        if let Some(target) = self.droppables.get(over) {
+            if target.disabled {
+                return false;
+            }
+            let kind = self
+                .draggables
+                .get(active)
+                .and_then(|d| d.data.get("type"))
+                .and_then(|t| t.as_str());
+            if !target.accepts.is_empty()
+                && !target
+                    .accepts
+                    .iter()
+                    .any(|a| a == active || Some(a.as_str()) == kind)
+            {
+                return false;
+            }
+        }
+        if self.preview_pending.is_some() {
+            self.compute_preview();
+        }
+        !self.preview.as_ref().is_some_and(DropPreview::is_blocked)
+    }
+
+    /// End dragging. Drops the target can't take end with no `over`.
     pub fn end_drag(&mut self) -> DragEndEvent {
+        if !self.can_drop() {
+            self.over = None;
+        }
+        let ancestors = self
+            .over
+            .as_deref()
+            .map(|over| self.ancestors(over))
+            .unwrap_or_default();
+        let target = self.over.as_ref().and_then(|id| self.droppables.get(id));
+        if let Some(overlay) = &mut self.overlay {
+            let animating = match target {
+                Some(target) => overlay.drop_on(&target.rect, &self.drop_animation),
+                None => overlay.cancel(&self.drop_animation),
+            };
+            if !animating {
+                self.overlay = None;
+            }
+        }
         let event = DragEndEvent {
             active: self.active.take(),
             over: self.over.take(),
+            ancestors,
         };
+        #[cfg(feature = "tracing")]
+        if let Some(span) = self.drag_span.take()
+            && let Some(over) = &event.over
+        {
+            span.record("over", over.as_str());
+        }
         self.state = DndState::Idle;
+        self.preview = None;
+        self.preview_pending = None;
         event
     }
 
     /// Cancel dragging.
     pub fn cancel_drag(&mut self) {
+        self.pending = None;
+        #[cfg(feature = "tracing")]
+        {
+            self.drag_span = None;
+        }
+        if let Some(overlay) = &mut self.overlay
+            && !overlay.cancel(&self.drop_animation)
+        {
+            self.overlay = None;
+        }
         self.active = None;
         self.over = None;
         self.state = DndState::Idle;
+        self.preview = None;
+        self.preview_pending = None;
+    }
+
+    /// Set the message for the `aria-live` region.
+    pub fn announce(&mut self, message: impl Into<String>) {
+        self.aria_live_message = Some(message.into());
+    }
+
+    /// Overlay to render for the current drag, or for the one whose drop
+    /// animation is still running.
+    pub fn overlay_state(&self) -> Option<&DragOverlay> {
+        self.overlay.as_ref()
+    }
+
+    /// Remove the overlay once its drop animation finished.
+    pub fn finish_overlay(&mut self) {
+        if self.state == DndState::Idle {
+            self.overlay = None;
+        }
+    }
+
+    /// Enabled droppables ranked for a drag at `active_rect` with the
+    /// pointer at `pointer`, innermost container first; see
+    /// [`CollisionDetection::deepest_container`].
+    pub fn collisions(&self, active_rect: &Rect, pointer: Option<(f64, f64)>) -> Vec<Collision> {
+        let target = |(id, info): (&DndId, &DroppableInfo)| {
+            (!info.disabled).then(|| DropTarget {
+                id: id.clone(),
+                rect: info.rect,
+                parent: info.parent.clone(),
+                z_index: info.z_index,
+            })
+        };
+        #[cfg(feature = "spatial-index")]
+        if let Some(ids) = self.indexed_candidates(active_rect, pointer) {
+            let targets: Vec<DropTarget> = ids
+                .iter()
+                .filter_map(|id| self.droppables.get_key_value(id))
+                .filter_map(target)
+                .collect();
+            return CollisionDetection::deepest_container(active_rect, &targets, pointer);
+        }
+        let targets: Vec<DropTarget> = self.droppables.iter().filter_map(target).collect();
+        CollisionDetection::deepest_container(active_rect, &targets, pointer)
+    }
+
+    /// Set the current drop target to the best collision, or none.
+    pub fn resolve_over(
+        &mut self,
+        active_rect: &Rect,
+        pointer: Option<(f64, f64)>,
+    ) -> Option<&DndId> {
+        let best = self
+            .collisions(active_rect, pointer)
+            .into_iter()
+            .next()
+            .map(|c| c.id);
+        self.set_over(best);
+        self.over.as_ref()
+    }
+
+    /// Registered droppables enclosing `id`, innermost first.
+    pub fn ancestors(&self, id: &str) -> Vec<DndId> {
+        let mut ancestors: Vec<DndId> = Vec::new();
+        let mut parent = self.droppables.get(id).and_then(|d| d.parent.as_ref());
+        while let Some(id) = parent
+            && !ancestors.contains(id)
+            && self.droppables.contains_key(id)
+        {
+            ancestors.push(id.clone());
+            parent = self.droppables[id].parent.as_ref();
+        }
+        ancestors
     }
 
     /// Check if currently dragging.
@@ -101,6 +482,65 @@ impl DndContext {
     }
 }
 
+#[cfg(feature = "spatial-index")]
+impl DndContext {
+    /// Answer [`collisions`](Self::collisions) from a spatial index of the
+    /// droppables instead of checking every one.
+    pub fn with_droppable_index(mut self) -> Self {
+        self.sync_droppable_index();
+        self
+    }
+
+    /// Rebuild the droppable index, if enabled, from `droppables`. Call
+    /// after editing `droppables` by hand.
+    pub fn sync_droppable_index(&mut self) {
+        self.droppable_index = Some(
+            self.droppables
+                .iter()
+                .map(|(id, info)| (id.clone(), flow_rect(&info.rect)))
+                .collect(),
+        );
+    }
+
+    /// Move a droppable, keeping the index in step. Returns false if there
+    /// is no such droppable.
+    pub fn update_droppable_rect(&mut self, id: &str, rect: Rect) -> bool {
+        let Some(info) = self.droppables.get_mut(id) else {
+            return false;
+        };
+        info.rect = rect;
+        if let Some(index) = &mut self.droppable_index {
+            index.update(&id.to_string(), flow_rect(&rect));
+        }
+        true
+    }
+
+    /// Droppables that could collide, with their ancestors so nesting depth
+    /// still counts; `None` without an index, or with one that has fallen
+    /// out of step with `droppables`.
+    fn indexed_candidates(
+        &self,
+        active_rect: &Rect,
+        pointer: Option<(f64, f64)>,
+    ) -> Option<Vec<DndId>> {
+        let index = self.droppable_index.as_ref()?;
+        if index.len() != self.droppables.len() {
+            return None;
+        }
+        let mut ids = CollisionDetection::candidates(index, active_rect, pointer);
+        let mut i = 0;
+        while i < ids.len() {
+            if let Some(parent) = self.droppables.get(&ids[i]).and_then(|d| d.parent.as_ref())
+                && !ids.contains(parent)
+            {
+                ids.push(parent.clone());
+            }
+            i += 1;
+        }
+        Some(ids)
+    }
+}
+
 /// Drag and drop state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DndState {
@@ -120,6 +560,9 @@ pub struct DraggableInfo {
     /// Data associated with this draggable.
     #[serde(default)]
     pub data: serde_json::Value,
+    /// Hide the element while its drag overlay shows.
+    #[serde(default)]
+    pub hide_while_dragging: bool,
 }
 
 /// Information about a droppable element.
@@ -136,10 +579,16 @@ pub struct DroppableInfo {
     /// Data associated with this droppable.
     #[serde(default)]
     pub data: serde_json::Value,
+    /// Enclosing droppable, if nested.
+    #[serde(default)]
+    pub parent: Option<DndId>,
+    /// Stacking order among overlapping droppables; higher is on top.
+    #[serde(default)]
+    pub z_index: i32,
 }
 
 /// Rectangle bounds.
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
     pub x: f64,
     pub y: f64,
@@ -168,13 +617,26 @@ impl Rect {
     }
 }
 
-/// Drag event.
+/// Drag event. `Cancel` means a press gave up before its drag started,
+/// e.g. to let the page scroll.
 #[derive(Debug, Clone)]
 pub enum DragEvent {
     Start { id: DndId },
     Move { id: DndId, x: f64, y: f64 },
     Over { id: DndId, over: DndId },
     Leave { id: DndId, left: DndId },
+    Cancel { id: DndId },
+}
+
+/// A press waiting to become a drag.
+#[derive(Debug, Clone)]
+struct PendingActivation {
+    id: DndId,
+    kind: PointerKind,
+    origin: (f64, f64),
+    /// Latest pointer position.
+    last: (f64, f64),
+    since: Instant,
 }
 
 /// Drag end event.
@@ -184,6 +646,8 @@ pub struct DragEndEvent {
     pub active: Option<DndId>,
     /// The droppable it was dropped on.
     pub over: Option<DndId>,
+    /// Containers enclosing `over`, innermost first.
+    pub ancestors: Vec<DndId>,
 }
 
 impl DragEndEvent {
@@ -213,4 +677,118 @@ mod tests {
         assert_eq!(event.over, Some("drop-zone".to_string()));
         assert!(!ctx.is_dragging());
     }
+
+    fn zone() -> DroppableInfo {
+        DroppableInfo {
+            rect: Rect::new(0.0, 0.0, 100.0, 100.0),
+            disabled: false,
+            accepts: Vec::new(),
+            data: serde_json::Value::Null,
+            parent: None,
+            z_index: 0,
+        }
+    }
+
+    #[test]
+    fn test_preview_refreshes_on_target_change() {
+        let mut ctx = DndContext::new();
+        ctx.register_droppable("a", zone());
+        ctx.register_droppable("b", zone());
+        for id in ["a", "b"] {
+            ctx.register_preview(id, move |p| {
+                DropPreview::ok(format!("Move '{}' into '{}'", p.active, id))
+            });
+        }
+        let start = Instant::now();
+        let debounce = ctx.preview_debounce;
+
+        ctx.start_drag("item");
+        ctx.set_over_at(Some("a".to_string()), start);
+        assert!(ctx.refresh_preview(start).is_none());
+        assert_eq!(
+            ctx.refresh_preview(start + debounce).unwrap().title,
+            "Move 'item' into 'a'"
+        );
+
+        // Moving to another target drops the stale preview until debounced.
+        let later = start + debounce * 2;
+        ctx.set_over_at(Some("b".to_string()), later);
+        assert!(ctx.preview.is_none());
+        assert!(ctx.refresh_preview(later + debounce / 2).is_none());
+        assert_eq!(
+            ctx.refresh_preview(later + debounce).unwrap().title,
+            "Move 'item' into 'b'"
+        );
+
+        ctx.set_over_at(None, later + debounce * 2);
+        assert!(ctx.refresh_preview(later + debounce * 4).is_none());
+    }
+
+    #[test]
+    fn test_blocked_preview_prevents_drop() {
+        let mut ctx = DndContext::new();
+        ctx.register_droppable("self", zone());
+        ctx.register_preview("self", |_| DropPreview::blocked("Can't drop onto itself"));
+        ctx.register_droppable(
+            "typed",
+            DroppableInfo {
+                accepts: vec!["context".to_string()],
+                ..zone()
+            },
+        );
+        ctx.register_draggable(
+            "item",
+            DraggableInfo {
+                rect: Rect::default(),
+                disabled: false,
+                data: serde_json::json!({"type": "preset"}),
+                hide_while_dragging: false,
+            },
+        );
+
+        // Released before the debounce elapsed: the preview is still
+        // consulted.
+        ctx.start_drag("item");
+        ctx.set_over(Some("self".to_string()));
+        assert!(!ctx.can_drop());
+        assert!(ctx.preview.as_ref().unwrap().is_blocked());
+        let event = ctx.end_drag();
+        assert!(!event.is_dropped());
+        assert!(ctx.preview.is_none());
+
+        // Accept rules reject the same way.
+        ctx.start_drag("item");
+        ctx.set_over(Some("typed".to_string()));
+        assert!(!ctx.end_drag().is_dropped());
+    }
+
+    #[test]
+    fn test_drop_reports_ancestors() {
+        let mut ctx = DndContext::new();
+        let nested = |x, size, parent: Option<&str>| DroppableInfo {
+            rect: Rect::new(x, x, size, size),
+            parent: parent.map(str::to_string),
+            ..zone()
+        };
+        ctx.register_droppable("workflow", nested(0.0, 300.0, None));
+        ctx.register_droppable("context", nested(10.0, 200.0, Some("workflow")));
+        ctx.register_droppable("preset", nested(20.0, 50.0, Some("context")));
+
+        ctx.start_drag("item");
+        let active = Rect::new(25.0, 25.0, 10.0, 10.0);
+        assert_eq!(
+            ctx.resolve_over(&active, Some((30.0, 30.0)))
+                .map(String::as_str),
+            Some("preset")
+        );
+        let event = ctx.end_drag();
+        assert_eq!(event.over.as_deref(), Some("preset"));
+        assert_eq!(event.ancestors, ["context", "workflow"]);
+
+        // Disabled containers are skipped.
+        ctx.droppables.get_mut("preset").unwrap().disabled = true;
+        ctx.start_drag("item");
+        ctx.resolve_over(&active, Some((30.0, 30.0)));
+        assert_eq!(ctx.end_drag().ancestors, ["workflow"]);
+    }
 }
@@ -17,6 +17,8 @@ pub struct Draggable {
     pub transform: Option<Transform>,
     /// Associated data.
     pub data: serde_json::Value,
+    /// Hide the element while its drag overlay shows.
+    pub hide_while_dragging: bool,
 }
 
 impl Draggable {
@@ -27,6 +29,7 @@ impl Draggable {
             disabled: false,
             transform: None,
             data: serde_json::Value::Null,
+            hide_while_dragging: false,
         }
     }
 
@@ -39,6 +42,12 @@ impl Draggable {
         self.disabled = true;
         self
     }
+
+    /// Render the drag in an overlay and hide the element meanwhile.
+    pub fn hide_while_dragging(mut self) -> Self {
+        self.hide_while_dragging = true;
+        self
+    }
 }
 
 /// Draggable state for components.
@@ -87,7 +96,7 @@ pub struct DraggableListeners {
 }
 
 /// Transform applied during drag.
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct Transform {
     pub x: f64,
     pub y: f64,
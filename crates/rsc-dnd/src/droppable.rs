@@ -13,6 +13,10 @@ pub struct Droppable {
     pub accepts: Vec<String>,
     /// Associated data.
     pub data: serde_json::Value,
+    /// Enclosing droppable, if nested.
+    pub parent: Option<DndId>,
+    /// Stacking order among overlapping droppables; higher is on top.
+    pub z_index: i32,
 }
 
 impl Droppable {
@@ -22,6 +26,8 @@ impl Droppable {
             disabled: false,
             accepts: Vec::new(),
             data: serde_json::Value::Null,
+            parent: None,
+            z_index: 0,
         }
     }
 
@@ -34,6 +40,16 @@ impl Droppable {
         self.disabled = true;
         self
     }
+
+    pub fn with_parent(mut self, parent: impl Into<String>) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
 }
 
 /// Droppable state for components.
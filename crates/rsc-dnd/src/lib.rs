@@ -30,6 +30,8 @@ mod context;
 mod draggable;
 mod droppable;
 mod error;
+mod overlay;
+mod preview;
 mod sensors;
 mod sortable;
 
@@ -38,18 +40,23 @@ pub use context::*;
 pub use draggable::*;
 pub use droppable::*;
 pub use error::*;
+pub use overlay::*;
+pub use preview::*;
 pub use sensors::*;
 pub use sortable::*;
 
 /// Re-export common types.
 pub mod prelude {
     pub use crate::{
-        CollisionDetection, CollisionStrategy,
+        CollisionDetection, CollisionStrategy, DropTarget,
         DndContext, DndState, DragEvent, DragEndEvent,
         Draggable, DraggableState,
         Droppable, DroppableState,
-        Sensor, PointerSensor, KeyboardSensor,
-        Sortable, SortableContext, SortDirection,
+        Sensor, PointerSensor, TouchSensor, KeyboardSensor, KeyboardSensorOptions, Announcements,
+        ActivationConstraint, PointerKind,
+        Sortable, SortableContext, SortableConfig, SortDirection, ItemAnimation,
         DndError, DndResult,
+        DropPreview, PreviewSeverity,
+        DragOverlay, DropAnimation,
     };
 }
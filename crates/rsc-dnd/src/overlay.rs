@@ -0,0 +1,230 @@
+//! Drag overlay.
+//!
+//! Rather than moving the dragged element, which reflows its list, the
+//! renderer draws a preview in an overlay that follows the pointer. A
+//! [`DragOverlay`] holds what that preview needs: the source measurements,
+//! the translation from them and the payload. After the drag it animates
+//! back to the source on cancel, or onto the target on a drop. It is plain
+//! state, so the RSX layer only has to render it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::{DndId, Rect};
+use crate::draggable::Transform;
+
+/// Default duration of the overlay's drop animation.
+pub const DEFAULT_DROP_ANIMATION_MS: u64 = 250;
+
+/// How the overlay animates when the drag ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DropAnimation {
+    pub duration_ms: u64,
+    /// CSS easing.
+    pub easing: String,
+    /// Slide back to the source when the drag is cancelled or rejected;
+    /// otherwise the overlay disappears at once.
+    pub return_on_cancel: bool,
+    /// Slide onto the target after a drop; otherwise the overlay
+    /// disappears at once.
+    pub snap_to_target: bool,
+}
+
+impl Default for DropAnimation {
+    fn default() -> Self {
+        Self {
+            duration_ms: DEFAULT_DROP_ANIMATION_MS,
+            easing: "ease".to_string(),
+            return_on_cancel: true,
+            snap_to_target: true,
+        }
+    }
+}
+
+impl DropAnimation {
+    /// CSS `transition` value.
+    pub fn transition(&self) -> String {
+        format!("transform {}ms {}", self.duration_ms, self.easing)
+    }
+}
+
+/// What the overlay is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayPhase {
+    /// Following the pointer.
+    Dragging,
+    /// Sliding back to the source after a cancel.
+    Returning,
+    /// Sliding onto the drop target.
+    Dropping,
+}
+
+/// Overlay of the active drag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DragOverlay {
+    /// The dragged item.
+    pub id: DndId,
+    /// The draggable's data, for rendering a custom preview.
+    pub data: serde_json::Value,
+    /// Source bounds when the drag started; the overlay keeps their size.
+    pub origin: Rect,
+    /// Pointer position when the drag started.
+    pub start: (f64, f64),
+    /// Translation from `origin`.
+    pub transform: Transform,
+    pub phase: OverlayPhase,
+    /// Hide the source element while the overlay shows.
+    pub hide_source: bool,
+    /// CSS transition while animating; none while following the pointer.
+    pub transition: Option<String>,
+}
+
+impl DragOverlay {
+    pub fn new(id: impl Into<DndId>, origin: Rect, start: (f64, f64)) -> Self {
+        Self {
+            id: id.into(),
+            data: serde_json::Value::Null,
+            origin,
+            start,
+            transform: Transform::new(0.0, 0.0),
+            phase: OverlayPhase::Dragging,
+            hide_source: false,
+            transition: None,
+        }
+    }
+
+    /// Where the pointer grabbed the source, relative to its top-left.
+    pub fn pointer_offset(&self) -> (f64, f64) {
+        (self.start.0 - self.origin.x, self.start.1 - self.origin.y)
+    }
+
+    /// Current overlay bounds.
+    pub fn rect(&self) -> Rect {
+        Rect::new(
+            self.origin.x + self.transform.x,
+            self.origin.y + self.transform.y,
+            self.origin.width,
+            self.origin.height,
+        )
+    }
+
+    /// Follow the pointer. Ignored once the drag has ended.
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        if self.phase == OverlayPhase::Dragging {
+            self.transform = Transform::new(x - self.start.0, y - self.start.1);
+        }
+    }
+
+    /// Slide back to the source. Returns false if `animation` says to
+    /// remove the overlay instead.
+    pub fn cancel(&mut self, animation: &DropAnimation) -> bool {
+        self.settle(OverlayPhase::Returning, Transform::new(0.0, 0.0), animation);
+        animation.return_on_cancel
+    }
+
+    /// Slide onto `target`, centered on it. Returns false if `animation`
+    /// says to remove the overlay instead.
+    pub fn drop_on(&mut self, target: &Rect, animation: &DropAnimation) -> bool {
+        let (tx, ty) = target.center();
+        let (ox, oy) = self.origin.center();
+        self.settle(
+            OverlayPhase::Dropping,
+            Transform::new(tx - ox, ty - oy),
+            animation,
+        );
+        animation.snap_to_target
+    }
+
+    fn settle(&mut self, phase: OverlayPhase, transform: Transform, animation: &DropAnimation) {
+        self.phase = phase;
+        self.transform = transform;
+        self.transition = Some(animation.transition());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{DndContext, DraggableInfo, DroppableInfo};
+
+    fn ctx() -> DndContext {
+        let mut ctx = DndContext::new();
+        ctx.register_draggable(
+            "card",
+            DraggableInfo {
+                rect: Rect::new(100.0, 50.0, 80.0, 40.0),
+                disabled: false,
+                data: serde_json::json!({"type": "component"}),
+                hide_while_dragging: true,
+            },
+        );
+        ctx.register_droppable(
+            "canvas",
+            DroppableInfo {
+                rect: Rect::new(300.0, 300.0, 100.0, 100.0),
+                disabled: false,
+                accepts: Vec::new(),
+                data: serde_json::Value::Null,
+                parent: None,
+                z_index: 0,
+            },
+        );
+        ctx
+    }
+
+    #[test]
+    fn test_overlay_follows_pointer() {
+        let mut ctx = ctx();
+        ctx.start_drag_at("card", 110.0, 60.0);
+        let overlay = ctx.overlay_state().unwrap();
+        assert_eq!(overlay.pointer_offset(), (10.0, 10.0));
+        assert!(overlay.hide_source);
+        assert_eq!(overlay.data["type"], "component");
+
+        ctx.update_drag(150.0, 90.0);
+        let overlay = ctx.overlay_state().unwrap();
+        assert_eq!(overlay.transform, Transform::new(40.0, 30.0));
+        assert_eq!(
+            (overlay.rect().x, overlay.rect().y, overlay.rect().width),
+            (140.0, 80.0, 80.0)
+        );
+        assert_eq!(overlay.transition, None);
+    }
+
+    #[test]
+    fn test_drop_and_cancel_end_states() {
+        let mut ctx = ctx();
+        ctx.start_drag_at("card", 110.0, 60.0);
+        ctx.update_drag(340.0, 330.0);
+        ctx.set_over(Some("canvas".to_string()));
+        assert!(ctx.end_drag().is_dropped());
+        // Centered on the target: (350, 350) from the source's (140, 70).
+        let overlay = ctx.overlay_state().unwrap();
+        assert_eq!(overlay.phase, OverlayPhase::Dropping);
+        assert_eq!(overlay.transform, Transform::new(210.0, 280.0));
+        assert_eq!(overlay.transition.as_deref(), Some("transform 250ms ease"));
+        // Later moves don't disturb the animation.
+        ctx.update_drag(0.0, 0.0);
+        assert_eq!(
+            ctx.overlay_state().unwrap().transform,
+            Transform::new(210.0, 280.0)
+        );
+        ctx.finish_overlay();
+        assert!(ctx.overlay_state().is_none());
+
+        ctx.start_drag_at("card", 110.0, 60.0);
+        ctx.update_drag(500.0, 500.0);
+        ctx.cancel_drag();
+        let overlay = ctx.overlay_state().unwrap();
+        assert_eq!(overlay.phase, OverlayPhase::Returning);
+        assert_eq!(overlay.transform, Transform::new(0.0, 0.0));
+
+        // Without the animations, the overlay goes away at once.
+        ctx.drop_animation.return_on_cancel = false;
+        ctx.start_drag_at("card", 110.0, 60.0);
+        assert!(!ctx.end_drag().is_dropped());
+        assert!(ctx.overlay_state().is_none());
+    }
+}
@@ -0,0 +1,119 @@
+//! Drop previews.
+//!
+//! A droppable can register a preview function that describes what a drop
+//! would do ("Move 'Login' into 'Auth flow'"). The context asks the
+//! droppable under the pointer once the target has been stable for the
+//! debounce interval, and a blocked preview rejects the drop.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::context::DndId;
+
+/// Default time the pointer must stay over a target before its preview
+/// is computed.
+pub const DEFAULT_PREVIEW_DEBOUNCE: Duration = Duration::from_millis(120);
+
+/// Outcome class of a drop preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewSeverity {
+    #[default]
+    Ok,
+    /// The drop is allowed but has side effects worth noticing.
+    Warning,
+    /// The drop is not allowed.
+    Blocked,
+}
+
+/// What a drop would do, for the drag overlay.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DropPreview {
+    pub title: String,
+    pub details: Vec<String>,
+    pub severity: PreviewSeverity,
+}
+
+impl DropPreview {
+    pub fn ok(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            details: Vec::new(),
+            severity: PreviewSeverity::Ok,
+        }
+    }
+
+    pub fn warning(title: impl Into<String>) -> Self {
+        Self {
+            severity: PreviewSeverity::Warning,
+            ..Self::ok(title)
+        }
+    }
+
+    pub fn blocked(title: impl Into<String>) -> Self {
+        Self {
+            severity: PreviewSeverity::Blocked,
+            ..Self::ok(title)
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.details.push(detail.into());
+        self
+    }
+
+    /// Add a detail worth noticing, raising an ok preview to a warning.
+    pub fn with_warning(mut self, detail: impl Into<String>) -> Self {
+        if self.severity == PreviewSeverity::Ok {
+            self.severity = PreviewSeverity::Warning;
+        }
+        self.with_detail(detail)
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.severity == PreviewSeverity::Blocked
+    }
+}
+
+/// The drag a preview is asked about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragPayload {
+    /// The dragged item.
+    pub active: DndId,
+    /// The droppable under the pointer.
+    pub over: DndId,
+    /// Data registered with the draggable.
+    pub data: serde_json::Value,
+    /// Data registered with the droppable.
+    pub target_data: serde_json::Value,
+}
+
+/// Preview function registered for a droppable.
+pub type PreviewFn = Arc<dyn Fn(&DragPayload) -> DropPreview + Send + Sync>;
+
+/// Preview functions by droppable id.
+#[derive(Clone, Default)]
+pub struct PreviewRegistry {
+    fns: HashMap<DndId, PreviewFn>,
+}
+
+impl PreviewRegistry {
+    pub fn insert(&mut self, id: impl Into<String>, preview: PreviewFn) {
+        self.fns.insert(id.into(), preview);
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<PreviewFn> {
+        self.fns.remove(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&PreviewFn> {
+        self.fns.get(id)
+    }
+}
+
+impl fmt::Debug for PreviewRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.fns.keys()).finish()
+    }
+}
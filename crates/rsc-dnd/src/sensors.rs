@@ -1,5 +1,23 @@
 //! Input sensors for drag detection.
 
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::context::{DndContext, DragEndEvent};
+
+/// Default distance an arrow key moves a keyboard drag.
+pub const DEFAULT_KEYBOARD_STEP: f64 = 10.0;
+
+/// Default distance Shift+arrow moves a keyboard drag.
+pub const DEFAULT_KEYBOARD_LARGE_STEP: f64 = 50.0;
+
+/// Default time a touch must be held before it drags, in milliseconds.
+pub const DEFAULT_LONG_PRESS_DELAY: u64 = 250;
+
+/// Default distance a touch may drift while held without cancelling.
+pub const DEFAULT_LONG_PRESS_TOLERANCE: f64 = 5.0;
+
 /// Sensor trait for detecting drag start.
 pub trait Sensor {
     /// Check if drag should start.
@@ -64,13 +82,55 @@ impl Sensor for PointerSensor {
     }
 }
 
+/// Touch sensor configuration.
+#[derive(Debug, Clone)]
+pub struct TouchSensor {
+    /// Activation constraint, a long press by default so that swipes
+    /// scroll.
+    pub activation_constraint: ActivationConstraint,
+}
+
+impl Default for TouchSensor {
+    fn default() -> Self {
+        Self {
+            activation_constraint: ActivationConstraint::LongPress {
+                delay: DEFAULT_LONG_PRESS_DELAY,
+                tolerance: DEFAULT_LONG_PRESS_TOLERANCE,
+            },
+        }
+    }
+}
+
+impl Sensor for TouchSensor {
+    fn should_start(&self, event: &SensorEvent) -> bool {
+        // Touches report no button, or the primary one.
+        matches!(event.event_type, SensorEventType::PointerDown)
+            && matches!(event.button, None | Some(0))
+    }
+
+    fn get_position(&self, event: &SensorEvent) -> Option<(f64, f64)> {
+        Some((event.x, event.y))
+    }
+}
+
+/// Kind of pointer pressing a draggable, picking the sensor whose
+/// activation constraint applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Mouse,
+    Touch,
+}
+
 /// Keyboard sensor for accessibility.
 #[derive(Debug, Clone, Default)]
 pub struct KeyboardSensor {
-    /// Keys that trigger drag start.
+    /// Keys that trigger drag start, and drop while dragging.
     pub start_keys: Vec<String>,
     /// Keys that move the dragged item.
     pub move_keys: MoveKeys,
+    /// Keys that cancel the drag.
+    pub cancel_keys: Vec<String>,
+    pub options: KeyboardSensorOptions,
 }
 
 impl KeyboardSensor {
@@ -78,24 +138,149 @@ impl KeyboardSensor {
         Self {
             start_keys: vec!["Enter".to_string(), " ".to_string()],
             move_keys: MoveKeys::default(),
+            cancel_keys: vec!["Escape".to_string()],
+            options: KeyboardSensorOptions::default(),
         }
     }
-}
 
-impl Sensor for KeyboardSensor {
-    fn should_start(&self, event: &SensorEvent) -> bool {
-        if let (SensorEventType::KeyDown, Some(key)) = (&event.event_type, &event.key) {
-            self.start_keys.contains(key)
+    pub fn with_options(mut self, options: KeyboardSensorOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Drive a keyboard drag of `focused` on `ctx`.
+    ///
+    /// A start key picks the item up at the center of its registered rect;
+    /// arrows move it by the step, or the large step with Shift, and
+    /// resolve the droppable under it; a start key drops it and a cancel
+    /// key puts it back. Each change is announced through
+    /// [`DndContext::announce`]. Returns the end event when the drag ends.
+    pub fn handle_key(
+        &self,
+        ctx: &mut DndContext,
+        focused: &str,
+        event: &SensorEvent,
+    ) -> Option<DragEndEvent> {
+        let (SensorEventType::KeyDown, Some(key)) = (event.event_type, &event.key) else {
+            return None;
+        };
+        let announcements = &self.options.announcements;
+        if !ctx.is_dragging() {
+            if self.start_keys.contains(key)
+                && let Some(info) = ctx.draggables.get(focused)
+                && !info.disabled
+            {
+                let (x, y) = info.rect.center();
+                ctx.start_drag_at(focused, x, y);
+                ctx.announce(announcements.drag_start(focused));
+            }
+            return None;
+        }
+        let active = ctx.active.clone().unwrap_or_default();
+
+        if self.cancel_keys.contains(key) {
+            ctx.cancel_drag();
+            ctx.announce(announcements.drag_cancel(&active));
+            return Some(DragEndEvent {
+                active: Some(active),
+                over: None,
+                ancestors: Vec::new(),
+            });
+        }
+        if self.start_keys.contains(key) {
+            let end = ctx.end_drag();
+            ctx.announce(announcements.drag_end(&active, end.over.as_deref()));
+            return Some(end);
+        }
+
+        let step = if event.modifiers.shift {
+            self.options.large_step
         } else {
-            false
+            self.options.step
+        };
+        let keys = &self.move_keys;
+        let (dx, dy) = if keys.up.contains(key) {
+            (0.0, -step)
+        } else if keys.down.contains(key) {
+            (0.0, step)
+        } else if keys.left.contains(key) {
+            (-step, 0.0)
+        } else if keys.right.contains(key) {
+            (step, 0.0)
+        } else {
+            return None;
+        };
+        let overlay = ctx.overlay_state()?;
+        let (x, y) = (
+            overlay.start.0 + overlay.transform.x + dx,
+            overlay.start.1 + overlay.transform.y + dy,
+        );
+        ctx.update_drag(x, y);
+        let rect = ctx.overlay_state()?.rect();
+        let over = ctx.over.clone();
+        if ctx.resolve_over(&rect, Some((x, y))) != over.as_ref() {
+            let over = ctx.over.clone();
+            ctx.announce(announcements.drag_over(&active, over.as_deref()));
         }
+        None
     }
+}
+
+/// Keyboard drag settings.
+#[derive(Debug, Clone)]
+pub struct KeyboardSensorOptions {
+    /// Distance an arrow key moves the item.
+    pub step: f64,
+    /// Distance Shift+arrow moves the item.
+    pub large_step: f64,
+    /// Screen reader messages.
+    pub announcements: Arc<dyn Announcements>,
+}
 
-    fn get_position(&self, _event: &SensorEvent) -> Option<(f64, f64)> {
-        None // Keyboard doesn't provide position
+impl Default for KeyboardSensorOptions {
+    fn default() -> Self {
+        Self {
+            step: DEFAULT_KEYBOARD_STEP,
+            large_step: DEFAULT_KEYBOARD_LARGE_STEP,
+            announcements: Arc::new(DefaultAnnouncements),
+        }
     }
 }
 
+/// Screen reader messages for a drag, given the draggable and droppable
+/// ids. Override the ones to word differently, e.g. with entity names.
+pub trait Announcements: Debug + Send + Sync {
+    fn drag_start(&self, active: &str) -> String {
+        format!("Picked up draggable item {active}.")
+    }
+
+    fn drag_over(&self, active: &str, over: Option<&str>) -> String {
+        match over {
+            Some(over) => format!("Draggable item {active} was moved over droppable area {over}."),
+            None => format!("Draggable item {active} is no longer over a droppable area."),
+        }
+    }
+
+    fn drag_end(&self, active: &str, over: Option<&str>) -> String {
+        match over {
+            Some(over) => {
+                format!("Draggable item {active} was dropped over droppable area {over}.")
+            }
+            None => format!("Draggable item {active} was dropped."),
+        }
+    }
+
+    fn drag_cancel(&self, active: &str) -> String {
+        format!("Dragging was cancelled. Draggable item {active} was dropped.")
+    }
+}
+
+/// The default English messages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultAnnouncements;
+
+impl Announcements for DefaultAnnouncements {}
+
 /// Keys for moving items.
 #[derive(Debug, Clone)]
 pub struct MoveKeys {
@@ -117,7 +302,7 @@ impl Default for MoveKeys {
 }
 
 /// Activation constraint for starting drag.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ActivationConstraint {
     /// Minimum distance before drag starts.
     Distance(f64),
@@ -125,4 +310,236 @@ pub enum ActivationConstraint {
     Delay(u64),
     /// Both distance and delay.
     DistanceAndDelay { distance: f64, delay: u64 },
+    /// Hold for `delay` milliseconds. Moving more than `tolerance`
+    /// vertically first cancels the press, leaving the gesture to
+    /// scrolling; sideways drift doesn't.
+    LongPress { delay: u64, tolerance: f64 },
+}
+
+/// State of a press under an [`ActivationConstraint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activation {
+    /// Not yet decided.
+    Pending,
+    /// The drag should start.
+    Start,
+    /// The press is not a drag.
+    Cancel,
+}
+
+impl ActivationConstraint {
+    /// Check a press that has moved by `(dx, dy)` since it began `elapsed`
+    /// ago.
+    pub fn check(&self, (dx, dy): (f64, f64), elapsed: Duration) -> Activation {
+        let distance = dx.hypot(dy);
+        let held = |delay: u64| elapsed >= Duration::from_millis(delay);
+        let start = match *self {
+            ActivationConstraint::Distance(min) => distance >= min,
+            ActivationConstraint::Delay(delay) => held(delay),
+            ActivationConstraint::DistanceAndDelay {
+                distance: min,
+                delay,
+            } => distance >= min && held(delay),
+            ActivationConstraint::LongPress { delay, tolerance } => {
+                if held(delay) {
+                    true
+                } else if dy.abs() > tolerance {
+                    return Activation::Cancel;
+                } else {
+                    false
+                }
+            }
+        };
+        if start {
+            Activation::Start
+        } else {
+            Activation::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{DragEvent, DraggableInfo, DroppableInfo, Rect};
+
+    fn key(key: &str, shift: bool) -> SensorEvent {
+        SensorEvent {
+            event_type: SensorEventType::KeyDown,
+            x: 0.0,
+            y: 0.0,
+            button: None,
+            key: Some(key.to_string()),
+            modifiers: Modifiers {
+                shift,
+                ..Modifiers::default()
+            },
+        }
+    }
+
+    fn ctx() -> DndContext {
+        let mut ctx = DndContext::new();
+        ctx.register_draggable(
+            "card",
+            DraggableInfo {
+                rect: Rect::new(0.0, 0.0, 40.0, 20.0),
+                disabled: false,
+                data: serde_json::Value::Null,
+                hide_while_dragging: false,
+            },
+        );
+        ctx.register_droppable(
+            "zone",
+            DroppableInfo {
+                rect: Rect::new(0.0, 50.0, 100.0, 100.0),
+                disabled: false,
+                accepts: Vec::new(),
+                data: serde_json::Value::Null,
+                parent: None,
+                z_index: 0,
+            },
+        );
+        ctx
+    }
+
+    #[derive(Debug)]
+    struct Terse;
+
+    impl Announcements for Terse {
+        fn drag_start(&self, active: &str) -> String {
+            format!("Moving {active}")
+        }
+    }
+
+    #[test]
+    fn test_keyboard_drag_session() {
+        let mut ctx = ctx();
+        let sensor = KeyboardSensor::new().with_options(KeyboardSensorOptions {
+            step: 5.0,
+            large_step: 40.0,
+            announcements: Arc::new(Terse),
+        });
+        let mut messages = Vec::new();
+        let mut press = |ctx: &mut DndContext, name: &str, shift: bool| {
+            let end = sensor.handle_key(ctx, "card", &key(name, shift));
+            messages.extend(ctx.aria_live_message.take());
+            end
+        };
+        press(&mut ctx, "Enter", false);
+        press(&mut ctx, "ArrowRight", false);
+        press(&mut ctx, "ArrowDown", true);
+        press(&mut ctx, "ArrowDown", false);
+        // From the center (20, 10): 5 right, then 40 and 5 down.
+        let rect = ctx.overlay_state().unwrap().rect();
+        assert_eq!((rect.x, rect.y), (5.0, 45.0));
+        assert!(press(&mut ctx, " ", false).unwrap().is_dropped());
+
+        assert_eq!(
+            messages,
+            [
+                "Moving card",
+                "Draggable item card was moved over droppable area zone.",
+                "Draggable item card was dropped over droppable area zone.",
+            ]
+        );
+        assert!(!ctx.is_dragging());
+    }
+
+    #[test]
+    fn test_escape_restores_position() {
+        let mut ctx = ctx();
+        let sensor = KeyboardSensor::new();
+        sensor.handle_key(&mut ctx, "card", &key(" ", false));
+        sensor.handle_key(&mut ctx, "card", &key("ArrowDown", true));
+        assert_eq!(ctx.over.as_deref(), Some("zone"));
+        assert_eq!(ctx.overlay_state().unwrap().rect().y, 50.0);
+
+        let end = sensor.handle_key(&mut ctx, "card", &key("Escape", false));
+        assert!(!end.unwrap().is_dropped());
+        assert_eq!(
+            ctx.aria_live_message.as_deref(),
+            Some("Dragging was cancelled. Draggable item card was dropped.")
+        );
+        assert_eq!(
+            ctx.overlay_state().unwrap().rect(),
+            ctx.draggables["card"].rect
+        );
+        assert!(!ctx.is_dragging() && ctx.over.is_none());
+    }
+
+    /// Touch press on the card at (10, 10), held for 200ms with 8px
+    /// tolerance.
+    fn press(ctx: &mut DndContext, now: std::time::Instant) {
+        ctx.touch_sensor.activation_constraint = ActivationConstraint::LongPress {
+            delay: 200,
+            tolerance: 8.0,
+        };
+        let event = ctx.pointer_down("card", PointerKind::Touch, 10.0, 10.0, now);
+        assert!(event.is_none() && !ctx.is_dragging());
+    }
+
+    #[test]
+    fn test_long_press_starts_after_delay() {
+        let mut ctx = ctx();
+        let start = std::time::Instant::now();
+        press(&mut ctx, start);
+        // Vertical drift within the tolerance keeps the press alive, and
+        // sideways drift doesn't count against it.
+        assert!(ctx.pointer_move(30.0, 15.0, start + ms(100)).is_none());
+        assert!(ctx.poll_activation(start + ms(150)).is_none());
+        assert!(matches!(
+            ctx.poll_activation(start + ms(200)),
+            Some(DragEvent::Start { id }) if id == "card"
+        ));
+        assert!(ctx.is_dragging_id("card"));
+        let overlay = ctx.overlay_state().unwrap();
+        assert_eq!(overlay.start, (10.0, 10.0));
+        assert_eq!(overlay.transform.x, 20.0);
+
+        // Once dragging, the tolerance no longer applies.
+        assert!(matches!(
+            ctx.pointer_move(100.0, 10.0, start + ms(250)),
+            Some(DragEvent::Move { .. })
+        ));
+        assert_eq!(ctx.pointer_up().unwrap().active.as_deref(), Some("card"));
+    }
+
+    #[test]
+    fn test_early_move_yields_to_scroll() {
+        let mut ctx = ctx();
+        let start = std::time::Instant::now();
+        press(&mut ctx, start);
+        assert!(matches!(
+            ctx.pointer_move(10.0, 30.0, start + ms(50)),
+            Some(DragEvent::Cancel { id }) if id == "card"
+        ));
+        // Holding on afterwards doesn't start a drag.
+        assert!(ctx.poll_activation(start + ms(500)).is_none());
+        assert!(ctx.pointer_move(10.0, 31.0, start + ms(500)).is_none());
+        assert!(!ctx.is_dragging());
+        assert!(ctx.pointer_up().is_none());
+    }
+
+    #[test]
+    fn test_lift_before_delay_is_a_tap() {
+        let mut ctx = ctx();
+        let start = std::time::Instant::now();
+        press(&mut ctx, start);
+        assert!(ctx.poll_activation(start + ms(199)).is_none());
+        assert!(ctx.pointer_up().is_none());
+        assert!(ctx.poll_activation(start + ms(300)).is_none());
+        assert!(!ctx.is_dragging() && ctx.overlay_state().is_none());
+
+        // Mouse presses keep using the distance threshold.
+        let down = ctx.pointer_down("card", PointerKind::Mouse, 0.0, 0.0, start);
+        assert!(down.is_none());
+        assert!(matches!(
+            ctx.pointer_move(12.0, 0.0, start),
+            Some(DragEvent::Start { .. })
+        ));
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
 }
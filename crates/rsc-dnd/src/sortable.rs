@@ -1,8 +1,15 @@
 //! Sortable list functionality.
+//!
+//! Besides reordering, a [`SortableContext`] computes FLIP animations for a
+//! reorder: given the order before and after and the item rects measured
+//! before, each item that moves gets the translate that puts it back where
+//! it was, to be transitioned to zero.
+
+use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::context::DndId;
+use crate::context::{DndId, Rect};
 use crate::draggable::Transform;
 
 /// Sortable context for list reordering.
@@ -18,6 +25,8 @@ pub struct SortableContext {
     pub active_index: Option<usize>,
     /// Current over index.
     pub over_index: Option<usize>,
+    /// Animation timing.
+    pub config: SortableConfig,
 }
 
 impl SortableContext {
@@ -28,9 +37,15 @@ impl SortableContext {
             active_id: None,
             active_index: None,
             over_index: None,
+            config: SortableConfig::default(),
         }
     }
 
+    pub fn with_config(mut self, config: SortableConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     pub fn with_direction(mut self, direction: SortDirection) -> Self {
         self.direction = direction;
         self
@@ -99,6 +114,194 @@ impl SortableContext {
             _ => Some(current_index),
         }
     }
+
+    /// FLIP animations for going from `previous` to `next`, with `rects`
+    /// measured in the `previous` layout.
+    ///
+    /// Lists stack items along the sort direction, so variable sizes shift
+    /// their neighbors by the right amount; grids move items between the
+    /// previous slots. The active item animates from its measured (dragged)
+    /// rect into its slot with the drop timing. Items only in `next` enter,
+    /// from their rect if one was measured, e.g. in another list; items
+    /// only in `previous` exit. Items that stay put are left out.
+    pub fn animations_for(
+        &self,
+        previous: &[DndId],
+        next: &[DndId],
+        rects: &HashMap<DndId, Rect>,
+    ) -> Vec<ItemAnimation> {
+        let active = self.active_id.as_deref();
+        let slots = match self.direction {
+            SortDirection::Grid => previous.iter().map(|id| rects.get(id).copied()).collect(),
+            SortDirection::Vertical | SortDirection::Horizontal => {
+                self.stack(previous, next, rects)
+            }
+        };
+
+        let mut animations = Vec::new();
+        for (index, id) in next.iter().enumerate() {
+            let (Some(Some(slot)), Some(rect)) = (slots.get(index), rects.get(id)) else {
+                continue;
+            };
+            let kind = if Some(id.as_str()) == active {
+                AnimationKind::Drop
+            } else if previous.contains(id) {
+                AnimationKind::Move
+            } else {
+                AnimationKind::Enter
+            };
+            let (x, y) = (rect.x - slot.x, rect.y - slot.y);
+            if kind == AnimationKind::Move && x == 0.0 && y == 0.0 {
+                continue;
+            }
+            animations.push(self.animation(id, kind, Transform::new(x, y)));
+        }
+        for id in previous.iter().filter(|id| !next.contains(id)) {
+            animations.push(self.animation(id, AnimationKind::Exit, Transform::new(0.0, 0.0)));
+        }
+        animations
+    }
+
+    /// Slot rects for `next`, stacked from the start of the `previous`
+    /// list with its first gap. Items without a rect are sized like the
+    /// average item. The active item's rect only counts for its size,
+    /// since it sits under the pointer.
+    fn stack(
+        &self,
+        previous: &[DndId],
+        next: &[DndId],
+        rects: &HashMap<DndId, Rect>,
+    ) -> Vec<Option<Rect>> {
+        let horizontal = self.direction == SortDirection::Horizontal;
+        // (start, size) along the sort direction, and the cross start.
+        let along = |r: &Rect| {
+            if horizontal {
+                (r.x, r.width)
+            } else {
+                (r.y, r.height)
+            }
+        };
+        let cross = |r: &Rect| if horizontal { r.y } else { r.x };
+        let is_active = |id: &str| Some(id) == self.active_id.as_deref();
+
+        // Items in place, with their index in `previous`.
+        let placed: Vec<(usize, &Rect)> = previous
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| !is_active(id))
+            .filter_map(|(i, id)| Some((i, rects.get(id)?)))
+            .collect();
+        let Some(&(first_index, first)) = placed.first() else {
+            return vec![None; next.len()];
+        };
+        let gap = match placed.get(1) {
+            Some((_, second)) => (along(second).0 - along(first).0 - along(first).1).max(0.0),
+            None => 0.0,
+        };
+        let average = placed.iter().map(|(_, r)| along(r).1).sum::<f64>() / placed.len() as f64;
+        let size = |id: &str| rects.get(id).map_or(average, |r| along(r).1);
+        let list_cross = placed
+            .iter()
+            .map(|(_, r)| cross(r))
+            .fold(f64::INFINITY, f64::min);
+
+        // Back up over the items before the first one in place.
+        let mut cursor = along(first).0
+            - previous[..first_index]
+                .iter()
+                .map(|id| size(id) + gap)
+                .sum::<f64>();
+        next.iter()
+            .map(|id| {
+                // Items staying in the list keep their cross position;
+                // the dragged and entering ones go in line.
+                let cross = match rects.get(id) {
+                    Some(r) if !is_active(id) && previous.contains(id) => cross(r),
+                    _ => list_cross,
+                };
+                let start = cursor;
+                cursor += size(id) + gap;
+                Some(if horizontal {
+                    Rect::new(start, cross, 0.0, 0.0)
+                } else {
+                    Rect::new(cross, start, 0.0, 0.0)
+                })
+            })
+            .collect()
+    }
+
+    fn animation(&self, id: &str, kind: AnimationKind, from: Transform) -> ItemAnimation {
+        let (duration_ms, easing) = match kind {
+            AnimationKind::Drop => (self.config.drop_duration_ms, &self.config.drop_easing),
+            _ => (self.config.duration_ms, &self.config.easing),
+        };
+        ItemAnimation {
+            id: id.to_string(),
+            kind,
+            from,
+            duration_ms,
+            easing: easing.clone(),
+        }
+    }
+}
+
+/// Sortable animation timing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SortableConfig {
+    /// Duration of items shifting, entering and leaving.
+    pub duration_ms: u64,
+    /// CSS easing of items shifting, entering and leaving.
+    pub easing: String,
+    /// Duration of the dragged item settling into its slot.
+    pub drop_duration_ms: u64,
+    /// CSS easing of the dragged item settling into its slot.
+    pub drop_easing: String,
+}
+
+impl Default for SortableConfig {
+    fn default() -> Self {
+        Self {
+            duration_ms: 200,
+            easing: "ease".to_string(),
+            drop_duration_ms: 250,
+            drop_easing: "cubic-bezier(0.18, 0.67, 0.6, 1.22)".to_string(),
+        }
+    }
+}
+
+/// Why an item animates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnimationKind {
+    /// Shifted by the reorder.
+    Move,
+    /// The dragged item settling into its slot.
+    Drop,
+    /// New to the list.
+    Enter,
+    /// Leaving the list; the offset is zero.
+    Exit,
+}
+
+/// FLIP animation of one item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemAnimation {
+    pub id: DndId,
+    pub kind: AnimationKind,
+    /// Translate from the new slot back to where the item was; apply it
+    /// without a transition, then transition to zero.
+    pub from: Transform,
+    pub duration_ms: u64,
+    pub easing: String,
+}
+
+impl ItemAnimation {
+    /// CSS `transition` value.
+    pub fn transition(&self) -> String {
+        format!("transform {}ms {}", self.duration_ms, self.easing)
+    }
 }
 
 /// Sort direction.
@@ -170,4 +373,106 @@ mod tests {
         assert_eq!(result.from, 1);
         assert_eq!(result.to, 3);
     }
+
+    fn ids(ids: &[&str]) -> Vec<DndId> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn offsets(animations: &[ItemAnimation]) -> Vec<(&str, AnimationKind, f64, f64)> {
+        animations
+            .iter()
+            .map(|a| (a.id.as_str(), a.kind, a.from.x, a.from.y))
+            .collect()
+    }
+
+    #[test]
+    fn test_swap_adjacent_items() {
+        use AnimationKind::*;
+        // Heights 50, 30 and 40, 10 apart.
+        let rects = HashMap::from([
+            ("a".to_string(), Rect::new(0.0, 0.0, 200.0, 50.0)),
+            ("b".to_string(), Rect::new(0.0, 60.0, 200.0, 30.0)),
+            ("c".to_string(), Rect::new(0.0, 100.0, 200.0, 40.0)),
+        ]);
+        let ctx = SortableContext::new(ids(&["a", "b", "c"]));
+        let animations = ctx.animations_for(&ctx.items, &ids(&["b", "a", "c"]), &rects);
+        // "b" moves up to 0, "a" down to 40; "c" stays.
+        assert_eq!(
+            offsets(&animations),
+            [("b", Move, 0.0, 60.0), ("a", Move, 0.0, -40.0)]
+        );
+        assert_eq!(animations[0].transition(), "transform 200ms ease");
+
+        // The same swap in a row.
+        let rects: HashMap<DndId, Rect> = rects
+            .into_iter()
+            .map(|(id, r)| (id, Rect::new(r.y, 0.0, r.height, 20.0)))
+            .collect();
+        let ctx = ctx.with_direction(SortDirection::Horizontal);
+        let animations = ctx.animations_for(&ctx.items, &ids(&["b", "a", "c"]), &rects);
+        assert_eq!(
+            offsets(&animations),
+            [("b", Move, 60.0, 0.0), ("a", Move, -40.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_move_first_to_end_with_drop() {
+        use AnimationKind::*;
+        let rects = HashMap::from([
+            // Dragged past the end of the list, a little to the right.
+            ("a".to_string(), Rect::new(15.0, 170.0, 200.0, 40.0)),
+            ("b".to_string(), Rect::new(0.0, 50.0, 200.0, 40.0)),
+            ("c".to_string(), Rect::new(0.0, 100.0, 200.0, 40.0)),
+        ]);
+        let mut ctx = SortableContext::new(ids(&["a", "b", "c"]));
+        ctx.start("a");
+        ctx.move_to("c");
+        let next = ids(&["b", "c", "a"]);
+        let animations = ctx.animations_for(&ctx.items, &next, &rects);
+        assert_eq!(
+            offsets(&animations),
+            [
+                ("b", Move, 0.0, 50.0),
+                ("c", Move, 0.0, 50.0),
+                ("a", Drop, 15.0, 70.0),
+            ]
+        );
+        assert_eq!(animations[2].duration_ms, 250);
+
+        // Without a drag, "a" just moves.
+        let rects = HashMap::from([
+            ("a".to_string(), Rect::new(0.0, 0.0, 200.0, 40.0)),
+            ("b".to_string(), Rect::new(0.0, 50.0, 200.0, 40.0)),
+            ("c".to_string(), Rect::new(0.0, 100.0, 200.0, 40.0)),
+        ]);
+        let ctx = SortableContext::new(ids(&["a", "b", "c"]));
+        assert_eq!(
+            offsets(&ctx.animations_for(&ctx.items, &next, &rects)),
+            [
+                ("b", Move, 0.0, 50.0),
+                ("c", Move, 0.0, 50.0),
+                ("a", Move, 0.0, -100.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_items_entering_and_leaving() {
+        use AnimationKind::*;
+        let rects = HashMap::from([
+            ("a".to_string(), Rect::new(0.0, 0.0, 100.0, 40.0)),
+            ("b".to_string(), Rect::new(0.0, 50.0, 100.0, 40.0)),
+            // Measured in another list.
+            ("x".to_string(), Rect::new(300.0, 20.0, 100.0, 40.0)),
+        ]);
+        let ctx = SortableContext::new(ids(&["a", "b"]));
+        let animations = ctx.animations_for(&ctx.items, &ids(&["x", "b", "new"]), &rects);
+        // "x" comes in line at the top; "b" is already in the second slot
+        // and "new" has no rect to animate from.
+        assert_eq!(
+            offsets(&animations),
+            [("x", Enter, 300.0, 20.0), ("a", Exit, 0.0, 0.0)]
+        );
+    }
 }
@@ -1,14 +1,24 @@
 //! Flow canvas state and configuration.
 
+use std::sync::Arc;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::connection::ConnectionValidator;
 use crate::edge::{Edge, EdgeId};
+use crate::interaction::InteractionConfig;
 use crate::layout::{HierarchicalLayout, LayoutConfig};
 use crate::node::{Node, NodeId};
-use crate::position::{Dimensions, Rect};
+use crate::position::{Dimensions, Position, Rect};
+use crate::snap::SnapConfig;
+use crate::spatial::SpatialIndex;
+use crate::theme::FlowTheme;
 use crate::viewport::Viewport;
 
+/// Distance between the labels of edges joining the same two nodes.
+pub const PARALLEL_LABEL_SPACING: f64 = 24.0;
+
 /// Flow canvas state.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
@@ -32,6 +42,16 @@ pub struct FlowCanvas<N = (), E = ()> {
     /// Canvas configuration.
     #[serde(default)]
     pub config: FlowCanvasConfig,
+    /// Node with keyboard focus.
+    #[serde(skip)]
+    pub focused_node: Option<NodeId>,
+    /// Node rects for hit testing.
+    #[serde(skip)]
+    pub(crate) spatial: SpatialIndex,
+    /// Nodes handed out by [`get_node_mut`](Self::get_node_mut) since the
+    /// last sync, whose indexed rect may be out of date.
+    #[serde(skip)]
+    pub(crate) spatial_stale: Vec<NodeId>,
 }
 
 impl<N, E> Default for FlowCanvas<N, E> {
@@ -50,6 +70,9 @@ impl<N, E> FlowCanvas<N, E> {
             selected_nodes: Vec::new(),
             selected_edges: Vec::new(),
             config: FlowCanvasConfig::default(),
+            focused_node: None,
+            spatial: SpatialIndex::new(),
+            spatial_stale: Vec::new(),
         }
     }
 
@@ -62,12 +85,15 @@ impl<N, E> FlowCanvas<N, E> {
         for edge in edges {
             canvas.edges.insert(edge.id.clone(), edge);
         }
+        canvas.sync_spatial_index();
         canvas
     }
 
     /// Add a node.
     pub fn add_node(&mut self, node: Node<N>) {
-        self.nodes.insert(node.id.clone(), node);
+        let id = node.id.clone();
+        self.nodes.insert(id.clone(), node);
+        self.reindex_node(&id);
     }
 
     /// Remove a node and its connected edges.
@@ -77,8 +103,15 @@ impl<N, E> FlowCanvas<N, E> {
             .retain(|_, edge| edge.source != id && edge.target != id);
         // Remove from selection
         self.selected_nodes.retain(|n| n != id);
+        // Move focus to the nearest remaining node
+        if self.focused_node.as_deref() == Some(id) {
+            self.focused_node = self.nearest_node(id).cloned();
+        }
         // Remove node
-        self.nodes.shift_remove(id)
+        self.spatial_stale.retain(|n| n != id);
+        let node = self.nodes.shift_remove(id);
+        self.reindex_node(id);
+        node
     }
 
     /// Add an edge.
@@ -97,9 +130,25 @@ impl<N, E> FlowCanvas<N, E> {
         self.nodes.get(id)
     }
 
-    /// Get a mutable node by ID.
+    /// Get a mutable node by ID. Hit testing re-checks the node's rect
+    /// until the next [`sync_spatial_index`](Self::sync_spatial_index).
     pub fn get_node_mut(&mut self, id: &str) -> Option<&mut Node<N>> {
-        self.nodes.get_mut(id)
+        let node = self.nodes.get_mut(id)?;
+        if !self.spatial_stale.iter().any(|n| n == id) {
+            self.spatial_stale.push(id.to_string());
+        }
+        Some(node)
+    }
+
+    /// Move a node, keeping hit testing in step. Returns false if there is
+    /// no such node.
+    pub fn move_node(&mut self, id: &str, position: Position) -> bool {
+        let Some(node) = self.nodes.get_mut(id) else {
+            return false;
+        };
+        node.position = position;
+        self.reindex_node(id);
+        true
     }
 
     /// Get an edge by ID.
@@ -131,6 +180,69 @@ impl<N, E> FlowCanvas<N, E> {
             .collect()
     }
 
+    /// Start and end of an edge drawn as a straight line: the edge's
+    /// handle anchors where set, otherwise the node centers.
+    pub fn edge_endpoints(&self, id: &str) -> Option<(Position, Position)> {
+        let edge = self.edges.get(id)?;
+        let anchor = |node_id: &str, handle: &Option<String>| {
+            let node = self.nodes.get(node_id)?;
+            handle
+                .as_deref()
+                .and_then(|h| node.handle_anchor(h))
+                .or_else(|| node.bounds().map(|b| b.center()))
+                .or(Some(node.position))
+        };
+        Some((
+            anchor(&edge.source, &edge.source_handle)?,
+            anchor(&edge.target, &edge.target_handle)?,
+        ))
+    }
+
+    /// Label positions of labelled edges, in edge order.
+    ///
+    /// Edges joining the same two nodes, in either direction, have their
+    /// labels fanned out across the line between the nodes so they don't
+    /// sit on top of each other.
+    pub fn label_positions(&self) -> IndexMap<EdgeId, Position> {
+        let mut groups: IndexMap<(&str, &str), Vec<&Edge<E>>> = IndexMap::new();
+        for edge in self.edges.values().filter(|e| e.label.is_some()) {
+            let (a, b) = (edge.source.as_str(), edge.target.as_str());
+            groups.entry((a.min(b), a.max(b))).or_default().push(edge);
+        }
+        let mut positions = IndexMap::new();
+        for ((first, _), edges) in groups {
+            let count = edges.len();
+            for (i, edge) in edges.into_iter().enumerate() {
+                let Some((start, end)) = self.edge_endpoints(&edge.id) else {
+                    continue;
+                };
+                let mut position = edge.label_position(start, end);
+                if count > 1 {
+                    // Normal of the pair's line, the same for both directions.
+                    let (from, to) = if edge.source == first {
+                        (start, end)
+                    } else {
+                        (end, start)
+                    };
+                    let length = from.distance_to(&to);
+                    let (nx, ny) = if length > 0.0 {
+                        (-(to.y - from.y) / length, (to.x - from.x) / length)
+                    } else {
+                        (0.0, 1.0)
+                    };
+                    let shift = (i as f64 - (count - 1) as f64 / 2.0) * PARALLEL_LABEL_SPACING;
+                    position.x += nx * shift;
+                    position.y += ny * shift;
+                }
+                positions.insert(edge.id.clone(), position);
+            }
+        }
+        self.edges
+            .keys()
+            .filter_map(|id| positions.swap_remove(id).map(|p| (id.clone(), p)))
+            .collect()
+    }
+
     /// Select a node.
     pub fn select_node(&mut self, id: &str, multi: bool) {
         if !multi {
@@ -178,12 +290,47 @@ impl<N, E> FlowCanvas<N, E> {
         Some(Rect::new(min_x, min_y, max_x - min_x, max_y - min_y))
     }
 
+    /// Union of all node rects. Nodes without dimensions count as points.
+    pub fn content_bounds(&self) -> Option<Rect> {
+        self.nodes
+            .values()
+            .map(|node| {
+                node.bounds()
+                    .unwrap_or(Rect::new(node.position.x, node.position.y, 0.0, 0.0))
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+
     /// Fit viewport to content.
     pub fn fit_view(&mut self, padding: f64, canvas_size: Dimensions) {
-        if let Some(bounds) = self.get_bounds() {
-            self.viewport.fit_to_bounds(&bounds, padding, &canvas_size);
+        if let Some(bounds) = self.content_bounds() {
+            self.viewport.fit_bounds(bounds, padding, canvas_size);
         }
     }
+
+    /// Center the viewport on a node, optionally changing the zoom.
+    /// Returns false if there is no such node.
+    pub fn center_on_node(&mut self, id: &str, canvas_size: Dimensions, zoom: Option<f64>) -> bool {
+        let Some(node) = self.nodes.get(id) else {
+            return false;
+        };
+        let center = node
+            .bounds()
+            .map_or(node.position, |bounds| bounds.center());
+        self.viewport.center_on(center, canvas_size, zoom);
+        true
+    }
+
+    /// Fit the viewport to one node. Returns false if there is no such node.
+    pub fn zoom_to_node(&mut self, id: &str, padding: f64, canvas_size: Dimensions) -> bool {
+        let Some(node) = self.nodes.get(id) else {
+            return false;
+        };
+        let dims = node.dimensions.unwrap_or(Dimensions::new(150.0, 50.0));
+        let bounds = Rect::new(node.position.x, node.position.y, dims.width, dims.height);
+        self.viewport.fit_to_bounds(&bounds, padding, &canvas_size);
+        true
+    }
 }
 
 impl<N: Clone, E: Clone> FlowCanvas<N, E> {
@@ -202,6 +349,7 @@ impl<N: Clone, E: Clone> FlowCanvas<N, E> {
                 existing.dimensions = node.dimensions;
             }
         }
+        self.reindex_all();
     }
 }
 
@@ -217,6 +365,9 @@ pub struct FlowCanvasConfig {
     /// Snap to grid.
     #[serde(default)]
     pub snap_to_grid: bool,
+    /// Grid and guide snapping of dropped nodes.
+    #[serde(default)]
+    pub snap: SnapConfig,
     /// Show minimap.
     #[serde(default)]
     pub show_minimap: bool,
@@ -232,6 +383,15 @@ pub struct FlowCanvasConfig {
     /// Allow edge creation by dragging.
     #[serde(default = "default_true")]
     pub edges_updatable: bool,
+    /// Checks connections made with [`FlowCanvas::connect`]. Not saved.
+    #[serde(skip)]
+    pub connection_validator: Option<Arc<dyn ConnectionValidator>>,
+    /// Gesture bindings, drag activation and zoom stepping.
+    #[serde(default)]
+    pub interaction: InteractionConfig,
+    /// Canvas colors.
+    #[serde(default)]
+    pub theme: FlowTheme,
 }
 
 fn default_true() -> bool {
@@ -248,11 +408,28 @@ impl Default for FlowCanvasConfig {
             show_grid: true,
             grid_size: default_grid_size(),
             snap_to_grid: false,
+            snap: SnapConfig::default(),
             show_minimap: false,
             show_controls: true,
             connection_line_type: crate::edge::EdgeType::default(),
             deletable: true,
             edges_updatable: true,
+            connection_validator: None,
+            interaction: InteractionConfig::default(),
+            theme: FlowTheme::default(),
+        }
+    }
+}
+
+impl FlowCanvasConfig {
+    /// Snapping in effect: `snap`, or a `grid_size` grid when only
+    /// `snap_to_grid` is set.
+    pub fn snap_config(&self) -> SnapConfig {
+        match self.snap.grid {
+            None if self.snap_to_grid => {
+                self.snap.clone().with_grid(self.grid_size, self.grid_size)
+            }
+            _ => self.snap.clone(),
         }
     }
 }
@@ -281,4 +458,88 @@ mod tests {
         assert_eq!(canvas.nodes.len(), 1);
         assert_eq!(canvas.edges.len(), 0); // Edge removed too
     }
+
+    #[test]
+    fn test_edge_endpoints() {
+        let mut canvas: FlowCanvas<(), ()> = FlowCanvas::new();
+        canvas.add_node(
+            Node::new("a", NodeType::Default, Position::new(0.0, 0.0))
+                .with_dimensions(Dimensions::new(100.0, 40.0))
+                .with_handle("out", crate::node::HandleSide::Bottom),
+        );
+        canvas.add_node(Node::new("b", NodeType::Default, Position::new(0.0, 200.0)));
+        canvas.add_edge(Edge::new("e1", "a", "b"));
+        let mut handled = Edge::new("e2", "a", "b");
+        handled.source_handle = Some("out".to_string());
+        canvas.add_edge(handled);
+
+        assert_eq!(
+            canvas.edge_endpoints("e1"),
+            Some((Position::new(50.0, 20.0), Position::new(0.0, 200.0)))
+        );
+        assert_eq!(
+            canvas.edge_endpoints("e2").map(|(start, _)| start),
+            Some(Position::new(50.0, 40.0))
+        );
+        assert_eq!(canvas.edge_endpoints("missing"), None);
+    }
+
+    #[test]
+    fn test_content_bounds_and_centering() {
+        let mut canvas: FlowCanvas<(), ()> = FlowCanvas::new();
+        assert_eq!(canvas.content_bounds(), None);
+        canvas.add_node(
+            Node::new("a", NodeType::Default, Position::new(0.0, 0.0))
+                .with_dimensions(Dimensions::new(100.0, 40.0)),
+        );
+        canvas.add_node(
+            Node::new("b", NodeType::Default, Position::new(300.0, 200.0))
+                .with_dimensions(Dimensions::new(50.0, 50.0)),
+        );
+        canvas.add_node(Node::new(
+            "c",
+            NodeType::Default,
+            Position::new(-20.0, 500.0),
+        ));
+        assert_eq!(
+            canvas.content_bounds(),
+            Some(Rect::new(-20.0, 0.0, 370.0, 500.0))
+        );
+
+        let screen = Dimensions::new(800.0, 600.0);
+        assert!(canvas.center_on_node("b", screen, Some(2.0)));
+        let transform = canvas.viewport.transform;
+        assert_eq!(
+            (transform.x, transform.y, transform.zoom),
+            (-250.0, -150.0, 2.0)
+        );
+        assert!(!canvas.center_on_node("missing", screen, None));
+
+        canvas.fit_view(0.0, Dimensions::new(740.0, 1000.0));
+        assert_eq!(canvas.viewport.transform.zoom, 2.0);
+    }
+
+    #[test]
+    fn test_parallel_edge_labels_fan_out() {
+        let mut canvas: FlowCanvas<(), ()> = FlowCanvas::new();
+        canvas.add_node(Node::new("a", NodeType::Default, Position::new(0.0, 0.0)));
+        canvas.add_node(Node::new("b", NodeType::Default, Position::new(200.0, 0.0)));
+        canvas.add_node(Node::new("c", NodeType::Default, Position::new(0.0, 200.0)));
+        canvas.add_edge(Edge::new("e1", "a", "b").with_label("Next"));
+        canvas.add_edge(Edge::new("e2", "b", "a").with_label("Back"));
+        canvas.add_edge(Edge::new("e3", "a", "b").with_label("Skip"));
+        canvas.add_edge(Edge::new("e4", "a", "c").with_label("Help"));
+        canvas.add_edge(Edge::new("e5", "a", "c"));
+
+        let positions = canvas.label_positions();
+        assert_eq!(
+            positions.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["e1", "e2", "e3", "e4"]
+        );
+        assert_eq!(positions["e1"], Position::new(100.0, -24.0));
+        assert_eq!(positions["e2"], Position::new(100.0, 0.0));
+        assert_eq!(positions["e3"], Position::new(100.0, 24.0));
+        // A lone labelled edge stays on its line.
+        assert_eq!(positions["e4"], Position::new(0.0, 100.0));
+    }
 }
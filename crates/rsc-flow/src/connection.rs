@@ -0,0 +1,341 @@
+//! Validation of new connections.
+//!
+//! Before an edge drawn by the user is added, the canvas asks its
+//! [`ConnectionValidator`] whether the connection is allowed. Validators
+//! see the graph through [`FlowGraph`], so they work with any node and
+//! edge data.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::canvas::FlowCanvas;
+use crate::edge::Edge;
+use crate::error::{FlowError, FlowResult};
+use crate::node::NodeId;
+
+/// A connection about to be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionCandidate {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub source_handle: Option<String>,
+    pub target_handle: Option<String>,
+}
+
+impl ConnectionCandidate {
+    pub fn new(source: impl Into<NodeId>, target: impl Into<NodeId>) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            source_handle: None,
+            target_handle: None,
+        }
+    }
+
+    /// The connection `edge` would make.
+    pub fn from_edge<E>(edge: &Edge<E>) -> Self {
+        Self {
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            source_handle: edge.source_handle.clone(),
+            target_handle: edge.target_handle.clone(),
+        }
+    }
+}
+
+/// Outcome of validating a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionVerdict {
+    Allow,
+    /// Allowed, with a warning for the user.
+    AllowWithWarning(String),
+    /// Rejected, with the reason to show the user.
+    Deny(String),
+}
+
+impl ConnectionVerdict {
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, ConnectionVerdict::Deny(_))
+    }
+}
+
+/// An existing edge, as seen by validators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphEdge<'a> {
+    pub id: &'a str,
+    pub source: &'a str,
+    pub target: &'a str,
+    pub source_handle: Option<&'a str>,
+    pub target_handle: Option<&'a str>,
+}
+
+/// Structure of a canvas without its node and edge data.
+#[derive(Debug, Clone, Default)]
+pub struct FlowGraph<'a> {
+    pub nodes: Vec<&'a str>,
+    pub edges: Vec<GraphEdge<'a>>,
+}
+
+impl<'a> FlowGraph<'a> {
+    pub fn from_canvas<N, E>(canvas: &'a FlowCanvas<N, E>) -> Self {
+        Self {
+            nodes: canvas.nodes.keys().map(String::as_str).collect(),
+            edges: canvas
+                .edges
+                .values()
+                .map(|e| GraphEdge {
+                    id: &e.id,
+                    source: &e.source,
+                    target: &e.target,
+                    source_handle: e.source_handle.as_deref(),
+                    target_handle: e.target_handle.as_deref(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn contains_node(&self, id: &str) -> bool {
+        self.nodes.contains(&id)
+    }
+
+    /// Edges leaving `node`.
+    pub fn out_degree(&self, node: &str) -> usize {
+        self.edges.iter().filter(|e| e.source == node).count()
+    }
+}
+
+/// Decides whether a connection may be made.
+pub trait ConnectionValidator: Debug + Send + Sync {
+    fn validate(&self, connection: &ConnectionCandidate, graph: &FlowGraph) -> ConnectionVerdict;
+}
+
+/// Denies edges from a node to itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoSelfLoops;
+
+impl ConnectionValidator for NoSelfLoops {
+    fn validate(&self, connection: &ConnectionCandidate, _: &FlowGraph) -> ConnectionVerdict {
+        if connection.source == connection.target {
+            ConnectionVerdict::Deny("A node can't connect to itself".to_string())
+        } else {
+            ConnectionVerdict::Allow
+        }
+    }
+}
+
+/// Denies a second edge between the same handles of the same two nodes.
+/// Edges in the opposite direction are allowed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDuplicateEdges;
+
+impl ConnectionValidator for NoDuplicateEdges {
+    fn validate(&self, connection: &ConnectionCandidate, graph: &FlowGraph) -> ConnectionVerdict {
+        let duplicate = graph.edges.iter().find(|e| {
+            e.source == connection.source
+                && e.target == connection.target
+                && e.source_handle == connection.source_handle.as_deref()
+                && e.target_handle == connection.target_handle.as_deref()
+        });
+        match duplicate {
+            Some(edge) => ConnectionVerdict::Deny(format!(
+                "'{}' and '{}' are already connected by '{}'",
+                connection.source, connection.target, edge.id
+            )),
+            None => ConnectionVerdict::Allow,
+        }
+    }
+}
+
+/// Denies edges from a node that already has this many outgoing edges.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxOutDegree(pub usize);
+
+impl ConnectionValidator for MaxOutDegree {
+    fn validate(&self, connection: &ConnectionCandidate, graph: &FlowGraph) -> ConnectionVerdict {
+        if graph.out_degree(&connection.source) >= self.0 {
+            ConnectionVerdict::Deny(format!(
+                "'{}' can't have more than {} outgoing connections",
+                connection.source, self.0
+            ))
+        } else {
+            ConnectionVerdict::Allow
+        }
+    }
+}
+
+/// Runs validators in order. The first denial wins; otherwise the first
+/// warning is kept.
+#[derive(Debug, Default)]
+pub struct ValidatorChain(pub Vec<Box<dyn ConnectionValidator>>);
+
+impl ValidatorChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, validator: impl ConnectionValidator + 'static) -> Self {
+        self.0.push(Box::new(validator));
+        self
+    }
+}
+
+impl ConnectionValidator for ValidatorChain {
+    fn validate(&self, connection: &ConnectionCandidate, graph: &FlowGraph) -> ConnectionVerdict {
+        let mut verdict = ConnectionVerdict::Allow;
+        for validator in &self.0 {
+            match validator.validate(connection, graph) {
+                deny @ ConnectionVerdict::Deny(_) => return deny,
+                warning @ ConnectionVerdict::AllowWithWarning(_)
+                    if verdict == ConnectionVerdict::Allow =>
+                {
+                    verdict = warning;
+                }
+                _ => {}
+            }
+        }
+        verdict
+    }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Whether `connection` may be made. Both nodes must exist and be
+    /// connectable; the rest is up to
+    /// [`FlowCanvasConfig::connection_validator`](crate::FlowCanvasConfig::connection_validator).
+    pub fn validate_connection(&self, connection: &ConnectionCandidate) -> ConnectionVerdict {
+        for id in [&connection.source, &connection.target] {
+            match self.nodes.get(id) {
+                None => return ConnectionVerdict::Deny(format!("Node '{}' not found", id)),
+                Some(node) if !node.connectable => {
+                    return ConnectionVerdict::Deny(format!("Node '{}' can't be connected", id));
+                }
+                Some(_) => {}
+            }
+        }
+        match &self.config.connection_validator {
+            Some(validator) => validator.validate(connection, &FlowGraph::from_canvas(self)),
+            None => ConnectionVerdict::Allow,
+        }
+    }
+
+    /// Add `edge` if its connection is allowed, returning the verdict. A
+    /// denial is returned as [`FlowError::InvalidConnection`] carrying the
+    /// reason.
+    pub fn connect(&mut self, edge: Edge<E>) -> FlowResult<ConnectionVerdict> {
+        let verdict = self.validate_connection(&ConnectionCandidate::from_edge(&edge));
+        if let ConnectionVerdict::Deny(reason) = verdict {
+            return Err(FlowError::invalid_connection(reason));
+        }
+        self.add_edge(edge);
+        Ok(verdict)
+    }
+
+    /// Set the validator consulted by [`connect`](Self::connect).
+    pub fn set_connection_validator(&mut self, validator: Box<dyn ConnectionValidator>) {
+        self.config.connection_validator = Some(Arc::from(validator));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Node, NodeType};
+    use crate::position::Position;
+
+    fn canvas() -> FlowCanvas {
+        let mut canvas = FlowCanvas::new();
+        for id in ["a", "b", "c"] {
+            canvas.add_node(Node::new(id, NodeType::Default, Position::zero()));
+        }
+        canvas.add_edge(Edge::new("e1", "a", "b"));
+        canvas
+    }
+
+    fn verdict(
+        validator: &dyn ConnectionValidator,
+        source: &str,
+        target: &str,
+    ) -> ConnectionVerdict {
+        let canvas = canvas();
+        let graph = FlowGraph::from_canvas(&canvas);
+        validator.validate(&ConnectionCandidate::new(source, target), &graph)
+    }
+
+    #[derive(Debug)]
+    struct Warn(&'static str);
+
+    impl ConnectionValidator for Warn {
+        fn validate(&self, _: &ConnectionCandidate, _: &FlowGraph) -> ConnectionVerdict {
+            ConnectionVerdict::AllowWithWarning(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn test_builtin_validators() {
+        assert!(!verdict(&NoSelfLoops, "a", "a").is_allowed());
+        assert_eq!(verdict(&NoSelfLoops, "a", "b"), ConnectionVerdict::Allow);
+
+        assert_eq!(
+            verdict(&NoDuplicateEdges, "a", "b"),
+            ConnectionVerdict::Deny("'a' and 'b' are already connected by 'e1'".to_string())
+        );
+        assert!(verdict(&NoDuplicateEdges, "b", "a").is_allowed());
+        let canvas = canvas();
+        let handled = ConnectionCandidate {
+            source_handle: Some("out".to_string()),
+            ..ConnectionCandidate::new("a", "b")
+        };
+        let graph = FlowGraph::from_canvas(&canvas);
+        assert!(NoDuplicateEdges.validate(&handled, &graph).is_allowed());
+
+        assert!(!verdict(&MaxOutDegree(1), "a", "c").is_allowed());
+        assert!(verdict(&MaxOutDegree(1), "b", "c").is_allowed());
+        assert!(verdict(&MaxOutDegree(2), "a", "c").is_allowed());
+    }
+
+    #[test]
+    fn test_chain_order() {
+        let chain = ValidatorChain::new()
+            .with(Warn("first"))
+            .with(NoSelfLoops)
+            .with(Warn("second"))
+            .with(MaxOutDegree(1));
+        // A denial wins over an earlier warning; the first denial is reported.
+        assert_eq!(
+            verdict(&chain, "a", "a"),
+            ConnectionVerdict::Deny("A node can't connect to itself".to_string())
+        );
+        assert_eq!(
+            verdict(&chain, "b", "c"),
+            ConnectionVerdict::AllowWithWarning("first".to_string())
+        );
+        assert_eq!(
+            verdict(&ValidatorChain::new(), "a", "a"),
+            ConnectionVerdict::Allow
+        );
+    }
+
+    #[test]
+    fn test_connect() {
+        let mut canvas = canvas();
+        canvas.nodes["c"].connectable = false;
+        assert!(canvas.connect(Edge::new("e2", "a", "a")).is_ok());
+
+        canvas.set_connection_validator(Box::new(
+            ValidatorChain::new()
+                .with(NoSelfLoops)
+                .with(NoDuplicateEdges),
+        ));
+        let error = canvas.connect(Edge::new("e3", "b", "b")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid connection: A node can't connect to itself"
+        );
+        assert!(canvas.connect(Edge::new("e4", "b", "c")).is_err());
+        assert!(canvas.connect(Edge::new("e5", "b", "missing")).is_err());
+        assert_eq!(
+            canvas.connect(Edge::new("e6", "b", "a")).unwrap(),
+            ConnectionVerdict::Allow
+        );
+        assert_eq!(canvas.edges.len(), 3);
+    }
+}
@@ -0,0 +1,161 @@
+//! Viewport culling.
+//!
+//! Large canvases render only what the viewport shows. Nodes come from the
+//! spatial index; edges are tested by the bounding box of the path they
+//! draw, so an edge between two off-screen nodes that crosses the viewport
+//! still counts.
+
+use crate::canvas::FlowCanvas;
+use crate::edge::EdgeId;
+use crate::focus::rect;
+use crate::node::NodeId;
+use crate::position::{Dimensions, Rect};
+use crate::spatial::rects_touch;
+use crate::viewport::Viewport;
+
+/// Nodes and edges to render, in canvas order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VisibleSet {
+    pub nodes: Vec<NodeId>,
+    pub edges: Vec<EdgeId>,
+}
+
+impl VisibleSet {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    pub fn contains_node(&self, id: &str) -> bool {
+        self.nodes.iter().any(|n| n == id)
+    }
+
+    pub fn contains_edge(&self, id: &str) -> bool {
+        self.edges.iter().any(|e| e == id)
+    }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Bounding box of an edge's drawn path between its nodes' rects.
+    /// Nodes without dimensions count as the default node size.
+    pub fn edge_bounds(&self, id: &str) -> Option<Rect> {
+        let edge = self.edges.get(id)?;
+        let source = self.nodes.get(&edge.source)?;
+        let target = self.nodes.get(&edge.target)?;
+        Some(edge.path_bounds(rect(source), rect(target)))
+    }
+
+    /// Nodes and edges touching what `viewport` shows of a `canvas_size`
+    /// screen area, grown by `overscan` screen pixels on every side so
+    /// elements are ready just before they scroll in.
+    pub fn visible_elements(
+        &self,
+        viewport: &Viewport,
+        canvas_size: Dimensions,
+        overscan: f64,
+    ) -> VisibleSet {
+        let visible = viewport.visible_rect(canvas_size);
+        let margin = overscan.max(0.0) / viewport.transform.zoom;
+        let area = Rect::new(
+            visible.position.x - margin,
+            visible.position.y - margin,
+            visible.dimensions.width + 2.0 * margin,
+            visible.dimensions.height + 2.0 * margin,
+        );
+        VisibleSet {
+            nodes: self.nodes_in_rect(&area),
+            edges: self
+                .edges
+                .keys()
+                .filter(|id| self.edge_bounds(id).is_some_and(|b| rects_touch(&b, &area)))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edge::Edge;
+    use crate::node::{Node, NodeType};
+    use crate::position::Position;
+    use crate::viewport::ViewportTransform;
+
+    /// 100x40 nodes around an 800x600 screen at zoom 1: "in" on screen,
+    /// "left" and "right" beyond either side, "far" and "corner" below.
+    fn canvas() -> FlowCanvas<(), ()> {
+        let mut canvas = FlowCanvas::new();
+        for (id, x, y) in [
+            ("in", 300.0, 200.0),
+            ("left", -500.0, 280.0),
+            ("right", 1100.0, 280.0),
+            ("far", 300.0, 2000.0),
+            ("corner", 1100.0, 2000.0),
+        ] {
+            canvas.add_node(
+                Node::new(id, NodeType::Default, Position::new(x, y))
+                    .with_dimensions(Dimensions::new(100.0, 40.0)),
+            );
+        }
+        canvas.add_edge(Edge::new("in-far", "in", "far"));
+        // Both ends off screen, the line crosses it.
+        canvas.add_edge(Edge::new("left-right", "left", "right"));
+        // A vertical line at x = 1150.
+        canvas.add_edge(Edge::new("right-corner", "right", "corner"));
+        canvas
+    }
+
+    const SCREEN: Dimensions = Dimensions {
+        width: 800.0,
+        height: 600.0,
+    };
+
+    fn panned(x: f64, zoom: f64) -> Viewport {
+        Viewport {
+            transform: ViewportTransform::new(x, 0.0, zoom),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_visible_set_at_the_viewport_edges() {
+        let canvas = canvas();
+        let visible = canvas.visible_elements(&Viewport::default(), SCREEN, 0.0);
+        assert_eq!(visible.nodes, ["in"]);
+        assert_eq!(visible.edges, ["in-far", "left-right"]);
+        assert_eq!(
+            canvas.edge_bounds("left-right"),
+            Some(Rect::new(-400.0, 300.0, 1500.0, 0.0))
+        );
+
+        // Panned so "left" ends exactly on the left screen edge.
+        let visible = canvas.visible_elements(&panned(400.0, 1.0), SCREEN, 0.0);
+        assert_eq!(visible.nodes, ["in", "left"]);
+        // One unit further and it is gone.
+        let visible = canvas.visible_elements(&panned(399.0, 1.0), SCREEN, 0.0);
+        assert_eq!(visible.nodes, ["in"]);
+        assert!(!visible.contains_edge("right-corner"));
+    }
+
+    #[test]
+    fn test_overscan_in_screen_pixels() {
+        let canvas = canvas();
+        let viewport = Viewport::default();
+        // "right" starts 300 units past the right screen edge.
+        let visible = canvas.visible_elements(&viewport, SCREEN, 299.0);
+        assert!(!visible.contains_node("right"));
+        let visible = canvas.visible_elements(&viewport, SCREEN, 300.0);
+        assert_eq!(visible.nodes, ["in", "right"]);
+        assert!(!visible.contains_edge("right-corner"));
+        let visible = canvas.visible_elements(&viewport, SCREEN, 350.0);
+        assert!(visible.contains_edge("right-corner"));
+
+        // At zoom 2 the screen shows x 800..1200; "in" ends 400 units to
+        // the left, which is 800 screen pixels.
+        let zoomed = panned(-1600.0, 2.0);
+        let visible = canvas.visible_elements(&zoomed, SCREEN, 700.0);
+        assert_eq!(visible.nodes, ["right"]);
+        let visible = canvas.visible_elements(&zoomed, SCREEN, 800.0);
+        assert_eq!(visible.nodes, ["in", "right"]);
+    }
+}
@@ -4,7 +4,8 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::node::NodeId;
+use crate::node::{HandleSide, NodeId};
+use crate::position::{Position, Rect};
 
 /// Unique edge identifier.
 pub type EdgeId = String;
@@ -48,6 +49,9 @@ pub struct Edge<T = ()> {
     /// Inline styles.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub style: IndexMap<String, String>,
+    /// Geometry of step routing; defaults depend on the edge type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_options: Option<StepOptions>,
 }
 
 impl<T> Edge<T> {
@@ -67,6 +71,7 @@ impl<T> Edge<T> {
             z_index: 0,
             class_names: Vec::new(),
             style: IndexMap::new(),
+            step_options: None,
         }
     }
 
@@ -109,6 +114,391 @@ impl<T> Edge<T> {
         self.target_handle = Some(target_handle.into());
         self
     }
+
+    /// Set step routing geometry.
+    pub fn with_step_options(mut self, options: StepOptions) -> Self {
+        self.step_options = Some(options);
+        self
+    }
+
+    /// SVG path data from the source node's bounds to the target's.
+    ///
+    /// Both ends sit on the facing sides of the two rects, picked from
+    /// their relative position. `Step` and `SmoothStep` edges are routed
+    /// orthogonally; `SmoothStep` rounds the elbows unless its options
+    /// say otherwise.
+    pub fn path(&self, source_rect: Rect, target_rect: Rect) -> String {
+        let (source_side, target_side) = facing_sides(&source_rect, &target_rect);
+        let start = side_midpoint(&source_rect, source_side);
+        let end = side_midpoint(&target_rect, target_side);
+        match self.edge_type {
+            EdgeType::Step | EdgeType::SmoothStep => {
+                let options = self.route_options();
+                let points =
+                    step_route(source_rect, source_side, target_rect, target_side, &options);
+                step_path(&points, options.corner_radius)
+            }
+            EdgeType::Bezier => {
+                let (c1, c2) = bezier_controls(start, source_side, end, target_side);
+                format!(
+                    "M {} {} C {} {} {} {} {} {}",
+                    start.x, start.y, c1.x, c1.y, c2.x, c2.y, end.x, end.y
+                )
+            }
+            _ => format!("M {} {} L {} {}", start.x, start.y, end.x, end.y),
+        }
+    }
+
+    /// Bounding box of the path [`path`](Self::path) draws. Bezier edges
+    /// are bounded by their control points, so their box may be loose.
+    pub fn path_bounds(&self, source_rect: Rect, target_rect: Rect) -> Rect {
+        let (source_side, target_side) = facing_sides(&source_rect, &target_rect);
+        let start = side_midpoint(&source_rect, source_side);
+        let end = side_midpoint(&target_rect, target_side);
+        let points = match self.edge_type {
+            EdgeType::Step | EdgeType::SmoothStep => step_route(
+                source_rect,
+                source_side,
+                target_rect,
+                target_side,
+                &self.route_options(),
+            ),
+            EdgeType::Bezier => {
+                let (c1, c2) = bezier_controls(start, source_side, end, target_side);
+                vec![start, c1, c2, end]
+            }
+            _ => vec![start, end],
+        };
+        points
+            .into_iter()
+            .map(|p| Rect::new(p.x, p.y, 0.0, 0.0))
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default()
+    }
+
+    /// Step routing of this edge: its own options, or the defaults of its
+    /// edge type.
+    fn route_options(&self) -> StepOptions {
+        self.step_options.unwrap_or(match self.edge_type {
+            EdgeType::SmoothStep => StepOptions::smooth(),
+            _ => StepOptions::default(),
+        })
+    }
+
+    /// Where the label sits for an edge drawn from `source` to `target`.
+    ///
+    /// The point is taken at the label's `position` along the same curve
+    /// or route the edge type draws, then moved by the label's offset. An
+    /// edge without a label gets the unshifted midpoint.
+    pub fn label_position(&self, source: Position, target: Position) -> Position {
+        let (t, offset) = self.label.as_ref().map_or((0.5, Position::zero()), |l| {
+            (
+                l.position.clamp(0.0, 1.0),
+                Position::new(l.offset_x, l.offset_y),
+            )
+        });
+        let (dx, dy) = (target.x - source.x, target.y - source.y);
+        let (source_side, target_side) = if dx.abs() >= dy.abs() {
+            if dx >= 0.0 {
+                (HandleSide::Right, HandleSide::Left)
+            } else {
+                (HandleSide::Left, HandleSide::Right)
+            }
+        } else if dy >= 0.0 {
+            (HandleSide::Bottom, HandleSide::Top)
+        } else {
+            (HandleSide::Top, HandleSide::Bottom)
+        };
+        let point = match self.edge_type {
+            EdgeType::Step | EdgeType::SmoothStep => {
+                let options = self.step_options.unwrap_or_default();
+                let points = step_route(
+                    Rect::new(source.x, source.y, 0.0, 0.0),
+                    source_side,
+                    Rect::new(target.x, target.y, 0.0, 0.0),
+                    target_side,
+                    &options,
+                );
+                point_along(&points, t)
+            }
+            EdgeType::Bezier => {
+                let (c1, c2) = bezier_controls(source, source_side, target, target_side);
+                let u = 1.0 - t;
+                let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                Position::new(
+                    a * source.x + b * c1.x + c * c2.x + d * target.x,
+                    a * source.y + b * c1.y + c * c2.y + d * target.y,
+                )
+            }
+            _ => source.lerp(&target, t),
+        };
+        Position::new(point.x + offset.x, point.y + offset.y)
+    }
+}
+
+/// Control points of a bezier edge: each end pulls straight out of its
+/// side by half the distance between the ends.
+fn bezier_controls(
+    start: Position,
+    source_side: HandleSide,
+    end: Position,
+    target_side: HandleSide,
+) -> (Position, Position) {
+    let offset = (start.distance_to(&end) / 2.0).max(25.0);
+    (
+        offset_point(start, source_side, offset),
+        offset_point(end, target_side, offset),
+    )
+}
+
+/// Point at fraction `t` of the total length of a polyline.
+fn point_along(points: &[Position], t: f64) -> Position {
+    let total: f64 = points.windows(2).map(|w| w[0].distance_to(&w[1])).sum();
+    let mut remaining = total * t;
+    for w in points.windows(2) {
+        let length = w[0].distance_to(&w[1]);
+        if remaining <= length && length > 0.0 {
+            return w[0].lerp(&w[1], remaining / length);
+        }
+        remaining -= length;
+    }
+    points.last().copied().unwrap_or_default()
+}
+
+/// Geometry of orthogonal (step) edges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StepOptions {
+    /// Radius of rounded elbows; 0 gives sharp corners.
+    #[serde(default)]
+    pub corner_radius: f64,
+    /// Length of the first and last segments, so the edge leaves and
+    /// enters perpendicular to the node side.
+    #[serde(default = "default_min_segment")]
+    pub min_segment: f64,
+}
+
+fn default_min_segment() -> f64 {
+    20.0
+}
+
+impl Default for StepOptions {
+    fn default() -> Self {
+        Self {
+            corner_radius: 0.0,
+            min_segment: default_min_segment(),
+        }
+    }
+}
+
+impl StepOptions {
+    /// Options used for `SmoothStep` edges.
+    pub fn smooth() -> Self {
+        Self {
+            corner_radius: 8.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_corner_radius(mut self, radius: f64) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
+    pub fn with_min_segment(mut self, length: f64) -> Self {
+        self.min_segment = length;
+        self
+    }
+}
+
+/// Sides of `source` and `target` that face each other, along the axis
+/// with the larger gap between the rects.
+pub fn facing_sides(source: &Rect, target: &Rect) -> (HandleSide, HandleSide) {
+    let (s, t) = (source, target);
+    let gap_x = (t.position.x - (s.position.x + s.dimensions.width))
+        .max(s.position.x - (t.position.x + t.dimensions.width));
+    let gap_y = (t.position.y - (s.position.y + s.dimensions.height))
+        .max(s.position.y - (t.position.y + t.dimensions.height));
+    let (sc, tc) = (s.center(), t.center());
+    if gap_x >= gap_y {
+        if tc.x >= sc.x {
+            (HandleSide::Right, HandleSide::Left)
+        } else {
+            (HandleSide::Left, HandleSide::Right)
+        }
+    } else if tc.y >= sc.y {
+        (HandleSide::Bottom, HandleSide::Top)
+    } else {
+        (HandleSide::Top, HandleSide::Bottom)
+    }
+}
+
+fn side_midpoint(rect: &Rect, side: HandleSide) -> Position {
+    let c = rect.center();
+    match side {
+        HandleSide::Top => Position::new(c.x, rect.position.y),
+        HandleSide::Bottom => Position::new(c.x, rect.position.y + rect.dimensions.height),
+        HandleSide::Left => Position::new(rect.position.x, c.y),
+        HandleSide::Right => Position::new(rect.position.x + rect.dimensions.width, c.y),
+    }
+}
+
+fn offset_point(point: Position, side: HandleSide, distance: f64) -> Position {
+    match side {
+        HandleSide::Top => Position::new(point.x, point.y - distance),
+        HandleSide::Bottom => Position::new(point.x, point.y + distance),
+        HandleSide::Left => Position::new(point.x - distance, point.y),
+        HandleSide::Right => Position::new(point.x + distance, point.y),
+    }
+}
+
+/// Corner points of an orthogonal route from the middle of `source_side`
+/// to the middle of `target_side`, both ends included.
+///
+/// The route is computed in a frame where the source exits to the right
+/// and mapped back, so every side combination shares one set of cases.
+pub fn step_route(
+    source: Rect,
+    source_side: HandleSide,
+    target: Rect,
+    target_side: HandleSide,
+    options: &StepOptions,
+) -> Vec<Position> {
+    let transpose = matches!(source_side, HandleSide::Top | HandleSide::Bottom);
+    let mirror = matches!(source_side, HandleSide::Left | HandleSide::Top);
+    let to_frame = |p: Position| {
+        let p = if transpose {
+            Position::new(p.y, p.x)
+        } else {
+            p
+        };
+        if mirror { Position::new(-p.x, p.y) } else { p }
+    };
+    let from_frame = |p: Position| {
+        let p = if mirror { Position::new(-p.x, p.y) } else { p };
+        if transpose {
+            Position::new(p.y, p.x)
+        } else {
+            p
+        }
+    };
+    let side_in_frame = |side: HandleSide| {
+        let side = if transpose {
+            match side {
+                HandleSide::Top => HandleSide::Left,
+                HandleSide::Bottom => HandleSide::Right,
+                HandleSide::Left => HandleSide::Top,
+                HandleSide::Right => HandleSide::Bottom,
+            }
+        } else {
+            side
+        };
+        match side {
+            HandleSide::Left if mirror => HandleSide::Right,
+            HandleSide::Right if mirror => HandleSide::Left,
+            side => side,
+        }
+    };
+    // Vertical extent of a rect in the frame.
+    let span = |rect: &Rect| {
+        let a = to_frame(rect.position);
+        let b = to_frame(Position::new(
+            rect.position.x + rect.dimensions.width,
+            rect.position.y + rect.dimensions.height,
+        ));
+        (a.y.min(b.y), a.y.max(b.y))
+    };
+
+    let min = options.min_segment;
+    let p0 = to_frame(side_midpoint(&source, source_side));
+    let p3 = to_frame(side_midpoint(&target, target_side));
+    let s1 = Position::new(p0.x + min, p0.y);
+    let points = match side_in_frame(target_side) {
+        HandleSide::Left if p3.x - p0.x >= 2.0 * min || (p3.y == p0.y && p3.x > p0.x) => {
+            let mx = (p0.x + p3.x) / 2.0;
+            vec![p0, Position::new(mx, p0.y), Position::new(mx, p3.y), p3]
+        }
+        HandleSide::Left => {
+            // Target is behind the source: go around between or past the rects.
+            let t1 = Position::new(p3.x - min, p3.y);
+            let ((s_top, s_bottom), (t_top, t_bottom)) = (span(&source), span(&target));
+            let my = if t_top >= s_bottom {
+                (s_bottom + t_top) / 2.0
+            } else if s_top >= t_bottom {
+                (t_bottom + s_top) / 2.0
+            } else {
+                s_bottom.max(t_bottom) + min
+            };
+            vec![
+                p0,
+                s1,
+                Position::new(s1.x, my),
+                Position::new(t1.x, my),
+                t1,
+                p3,
+            ]
+        }
+        HandleSide::Right => {
+            let x = s1.x.max(p3.x + min);
+            vec![p0, Position::new(x, p0.y), Position::new(x, p3.y), p3]
+        }
+        side => {
+            let dy = if side == HandleSide::Top { -min } else { min };
+            let t1 = Position::new(p3.x, p3.y + dy);
+            let clear = if dy < 0.0 { p0.y <= t1.y } else { p0.y >= t1.y };
+            if p3.x >= s1.x && clear {
+                vec![p0, Position::new(p3.x, p0.y), p3]
+            } else {
+                vec![p0, s1, Position::new(s1.x, t1.y), t1, p3]
+            }
+        }
+    };
+    simplify(points.into_iter().map(from_frame).collect())
+}
+
+/// Drop repeated points and points in the middle of a straight run.
+fn simplify(points: Vec<Position>) -> Vec<Position> {
+    let mut out: Vec<Position> = Vec::with_capacity(points.len());
+    for p in points {
+        if out.last() == Some(&p) {
+            continue;
+        }
+        if out.len() >= 2 {
+            let (a, b) = (out[out.len() - 2], out[out.len() - 1]);
+            if (a.x == b.x && b.x == p.x) || (a.y == b.y && b.y == p.y) {
+                out.pop();
+            }
+        }
+        out.push(p);
+    }
+    out
+}
+
+/// SVG path through `points`, rounding each elbow with a quadratic curve
+/// of up to `radius`, limited to half the shorter adjoining segment.
+pub fn step_path(points: &[Position], radius: f64) -> String {
+    let Some(first) = points.first() else {
+        return String::new();
+    };
+    let mut d = format!("M {} {}", first.x, first.y);
+    for i in 1..points.len() {
+        let p = points[i];
+        match points.get(i + 1) {
+            Some(next) if radius > 0.0 => {
+                let prev = points[i - 1];
+                let r = radius
+                    .min(prev.distance_to(&p) / 2.0)
+                    .min(p.distance_to(next) / 2.0);
+                let before = p.lerp(&prev, r / prev.distance_to(&p));
+                let after = p.lerp(next, r / p.distance_to(next));
+                d.push_str(&format!(
+                    " L {} {} Q {} {} {} {}",
+                    before.x, before.y, p.x, p.y, after.x, after.y
+                ));
+            }
+            _ => d.push_str(&format!(" L {} {}", p.x, p.y)),
+        }
+    }
+    d
 }
 
 /// Edge rendering type.
@@ -144,6 +534,9 @@ pub struct EdgeLabel {
     /// Y offset.
     #[serde(default)]
     pub offset_y: f64,
+    /// Space between the text and the edge of its background box.
+    #[serde(default = "default_label_padding")]
+    pub background_padding: f64,
     /// CSS class names.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub class_names: Vec<String>,
@@ -153,6 +546,31 @@ fn default_label_position() -> f64 {
     0.5
 }
 
+fn default_label_padding() -> f64 {
+    4.0
+}
+
+/// Named label positions along an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelAlign {
+    /// Near the source, clear of its node.
+    Start,
+    Middle,
+    /// Near the target, clear of its node.
+    End,
+}
+
+impl LabelAlign {
+    /// Position along the edge (0.0 to 1.0).
+    pub fn position(self) -> f64 {
+        match self {
+            LabelAlign::Start => 0.2,
+            LabelAlign::Middle => 0.5,
+            LabelAlign::End => 0.8,
+        }
+    }
+}
+
 impl EdgeLabel {
     pub fn new(text: impl Into<String>) -> Self {
         Self {
@@ -160,9 +578,32 @@ impl EdgeLabel {
             position: 0.5,
             offset_x: 0.0,
             offset_y: 0.0,
+            background_padding: default_label_padding(),
             class_names: Vec::new(),
         }
     }
+
+    /// Set the position along the edge, clamped to 0.0–1.0.
+    pub fn with_position(mut self, position: f64) -> Self {
+        self.position = position.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_align(self, align: LabelAlign) -> Self {
+        self.with_position(align.position())
+    }
+
+    /// Set the offset from the point on the path.
+    pub fn with_offset(mut self, x: f64, y: f64) -> Self {
+        self.offset_x = x;
+        self.offset_y = y;
+        self
+    }
+
+    pub fn with_background_padding(mut self, padding: f64) -> Self {
+        self.background_padding = padding;
+        self
+    }
 }
 
 /// Common edge data structure.
@@ -174,6 +615,9 @@ pub struct EdgeData {
     /// Edge weight/priority.
     #[serde(default)]
     pub weight: f64,
+    /// Layer the edge belongs to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
     /// Additional metadata.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub metadata: IndexMap<String, serde_json::Value>,
@@ -194,4 +638,114 @@ mod tests {
         assert_eq!(edge.target, "node2");
         assert!(edge.animated);
     }
+
+    #[test]
+    fn test_label_position() {
+        let (a, b) = (Position::new(0.0, 0.0), Position::new(200.0, 100.0));
+        let edge: Edge = Edge::new("e1", "a", "b").with_label("Go");
+        assert_eq!(edge.label_position(a, b), Position::new(100.0, 50.0));
+
+        let mut edge = edge.with_type(EdgeType::Step);
+        assert_eq!(edge.label_position(a, b), Position::new(100.0, 50.0));
+        edge.label = Some(
+            EdgeLabel::new("Go")
+                .with_align(LabelAlign::Start)
+                .with_offset(0.0, -10.0),
+        );
+        // 20% of the 300-unit route is on its first segment.
+        assert_eq!(edge.label_position(a, b), Position::new(60.0, -10.0));
+
+        let mut edge = edge.with_type(EdgeType::Bezier);
+        edge.label = Some(EdgeLabel::new("Go").with_align(LabelAlign::End));
+        let p = edge.label_position(a, b);
+        assert!(p.x > 100.0 && p.x < 200.0 && p.y > 80.0 && p.y < 100.0);
+    }
+
+    #[test]
+    fn test_step_path_left_to_right() {
+        let edge: Edge = Edge::new("e1", "a", "b").with_type(EdgeType::Step);
+        let (source, target) = (
+            Rect::new(0.0, 0.0, 100.0, 40.0),
+            Rect::new(200.0, 60.0, 100.0, 40.0),
+        );
+        assert_eq!(
+            edge.path(source, target),
+            "M 100 20 L 150 20 L 150 80 L 200 80"
+        );
+
+        let edge = edge.with_step_options(StepOptions::default().with_corner_radius(8.0));
+        assert_eq!(
+            edge.path(source, target),
+            "M 100 20 L 142 20 Q 150 20 150 28 L 150 72 Q 150 80 158 80 L 200 80"
+        );
+        // Aligned rows route straight across.
+        assert_eq!(
+            edge.path(source, Rect::new(200.0, 0.0, 100.0, 40.0)),
+            "M 100 20 L 200 20"
+        );
+    }
+
+    #[test]
+    fn test_step_path_top_to_bottom() {
+        let edge: Edge = Edge::new("e1", "a", "b").with_type(EdgeType::Step);
+        let (source, target) = (
+            Rect::new(0.0, 0.0, 100.0, 40.0),
+            Rect::new(60.0, 120.0, 100.0, 40.0),
+        );
+        assert_eq!(
+            facing_sides(&source, &target),
+            (HandleSide::Bottom, HandleSide::Top)
+        );
+        assert_eq!(
+            edge.path(source, target),
+            "M 50 40 L 50 80 L 110 80 L 110 120"
+        );
+        // Corners are rounded by at most half the shorter segment.
+        let edge = edge.with_step_options(StepOptions::default().with_corner_radius(100.0));
+        assert_eq!(
+            edge.path(source, target),
+            "M 50 40 L 50 60 Q 50 80 70 80 L 90 80 Q 110 80 110 100 L 110 120"
+        );
+    }
+
+    #[test]
+    fn test_step_path_backwards() {
+        let edge: Edge = Edge::new("e1", "a", "b").with_type(EdgeType::Step);
+        let (source, target) = (
+            Rect::new(200.0, 0.0, 100.0, 40.0),
+            Rect::new(0.0, 60.0, 100.0, 40.0),
+        );
+        // Picked sides face each other, so the route mirrors the forward case.
+        assert_eq!(
+            edge.path(source, target),
+            "M 200 20 L 150 20 L 150 80 L 100 80"
+        );
+
+        // Forced left-to-right sides wrap around through the gap, leaving and
+        // entering each node perpendicular to its side.
+        let points = step_route(
+            source,
+            HandleSide::Right,
+            target,
+            HandleSide::Left,
+            &StepOptions::default(),
+        );
+        assert_eq!(
+            step_path(&points, 0.0),
+            "M 300 20 L 320 20 L 320 50 L -20 50 L -20 80 L 0 80"
+        );
+
+        // Overlapping rows go around below both nodes.
+        let points = step_route(
+            source,
+            HandleSide::Right,
+            Rect::new(0.0, 10.0, 100.0, 40.0),
+            HandleSide::Left,
+            &StepOptions::default(),
+        );
+        assert_eq!(
+            step_path(&points, 0.0),
+            "M 300 20 L 320 20 L 320 70 L -20 70 L -20 30 L 0 30"
+        );
+    }
 }
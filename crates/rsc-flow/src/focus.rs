@@ -0,0 +1,409 @@
+//! Keyboard focus for canvas nodes.
+//!
+//! Nodes are reachable in reading order (top-to-bottom, left-to-right,
+//! groups before their children) and by arrow keys, which move to the
+//! nearest node in that direction, preferring nodes in line. Accessibility attributes for each node
+//! are exposed through [`AriaAttributes`] for the renderer.
+
+use crate::canvas::FlowCanvas;
+use crate::node::{Node, NodeId};
+use crate::position::{Dimensions, Position, Rect};
+
+/// Size assumed for nodes without dimensions.
+const DEFAULT_NODE_SIZE: Dimensions = Dimensions {
+    width: 150.0,
+    height: 50.0,
+};
+
+/// Arrow key direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Accessibility attributes for a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AriaAttributes {
+    /// "group" for nodes with children, otherwise "button".
+    pub role: &'static str,
+    pub label: String,
+    /// 1-based position in the tab order.
+    pub pos_in_set: usize,
+    pub set_size: usize,
+    pub selected: bool,
+    pub focused: bool,
+}
+
+fn center<N>(node: &Node<N>) -> Position {
+    let dims = node.dimensions.unwrap_or(DEFAULT_NODE_SIZE);
+    Position::new(
+        node.position.x + dims.width / 2.0,
+        node.position.y + dims.height / 2.0,
+    )
+}
+
+pub(crate) fn rect<N>(node: &Node<N>) -> Rect {
+    Rect {
+        position: node.position,
+        dimensions: node.dimensions.unwrap_or(DEFAULT_NODE_SIZE),
+    }
+}
+
+/// Nearest node to `from` in `direction`, among `nodes`.
+///
+/// Only nodes whose center lies past `from`'s edge in that direction
+/// count, so the result is never behind or beside it. Nodes in the same row or
+/// column (overlapping `from` across the axis) win over the rest; within
+/// each group, distance off the axis counts double.
+pub fn find_node_in_direction<N>(
+    from: &str,
+    direction: FocusDirection,
+    nodes: &[Node<N>],
+) -> Option<NodeId> {
+    nearest_in_direction(from, direction, nodes.iter())
+}
+
+fn nearest_in_direction<'a, N: 'a>(
+    from: &str,
+    direction: FocusDirection,
+    nodes: impl Iterator<Item = &'a Node<N>> + Clone,
+) -> Option<NodeId> {
+    let origin = rect(nodes.clone().find(|n| n.id == from)?);
+    let start = origin.center();
+    let overlaps = |a: f64, a_len: f64, b: f64, b_len: f64| a < b + b_len && b < a + a_len;
+    nodes
+        .filter(|n| n.id != from)
+        .filter_map(|n| {
+            let other = rect(n);
+            let c = other.center();
+            let (along, across, aligned) = match direction {
+                FocusDirection::Up | FocusDirection::Down => (
+                    c.y - start.y,
+                    c.x - start.x,
+                    overlaps(
+                        origin.position.x,
+                        origin.dimensions.width,
+                        other.position.x,
+                        other.dimensions.width,
+                    ),
+                ),
+                FocusDirection::Left | FocusDirection::Right => (
+                    c.x - start.x,
+                    c.y - start.y,
+                    overlaps(
+                        origin.position.y,
+                        origin.dimensions.height,
+                        other.position.y,
+                        other.dimensions.height,
+                    ),
+                ),
+            };
+            let (along, beyond) = match direction {
+                FocusDirection::Up => (-along, c.y < origin.position.y),
+                FocusDirection::Down => (along, c.y > origin.position.y + origin.dimensions.height),
+                FocusDirection::Left => (-along, c.x < origin.position.x),
+                FocusDirection::Right => (along, c.x > origin.position.x + origin.dimensions.width),
+            };
+            beyond.then(|| (!aligned, along + across.abs() * 2.0, &n.id))
+        })
+        .min_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+        .map(|(_, _, id)| id.clone())
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Node ids in tab order.
+    ///
+    /// Siblings are sorted by position (top, then left, then insertion
+    /// order); each group is followed by its children.
+    pub fn tab_order(&self) -> Vec<NodeId> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let roots: Vec<&Node<N>> = self
+            .nodes
+            .values()
+            .filter(|n| {
+                n.parent_id
+                    .as_ref()
+                    .is_none_or(|p| !self.nodes.contains_key(p))
+            })
+            .collect();
+        self.push_in_reading_order(roots, &mut order);
+        order
+    }
+
+    fn push_in_reading_order<'a>(
+        &'a self,
+        mut siblings: Vec<&'a Node<N>>,
+        order: &mut Vec<NodeId>,
+    ) {
+        siblings.sort_by(|a, b| {
+            a.position
+                .y
+                .total_cmp(&b.position.y)
+                .then(a.position.x.total_cmp(&b.position.x))
+        });
+        for node in siblings {
+            if order.contains(&node.id) {
+                continue;
+            }
+            order.push(node.id.clone());
+            let children: Vec<&Node<N>> = self
+                .nodes
+                .values()
+                .filter(|n| n.parent_id.as_deref() == Some(node.id.as_str()))
+                .collect();
+            self.push_in_reading_order(children, order);
+        }
+    }
+
+    /// Focus a node by id; unknown ids clear focus.
+    pub fn set_focus(&mut self, id: Option<&str>) {
+        self.focused_node = id
+            .filter(|id| self.nodes.contains_key(*id))
+            .map(str::to_string);
+    }
+
+    /// Focus the first node in tab order.
+    pub fn focus_first(&mut self) -> Option<&NodeId> {
+        self.focused_node = self.tab_order().into_iter().next();
+        self.focused_node.as_ref()
+    }
+
+    /// Move focus forward in tab order, wrapping around.
+    pub fn focus_next(&mut self) -> Option<&NodeId> {
+        self.step_focus(1)
+    }
+
+    /// Move focus backward in tab order, wrapping around.
+    pub fn focus_prev(&mut self) -> Option<&NodeId> {
+        self.step_focus(-1)
+    }
+
+    fn step_focus(&mut self, step: isize) -> Option<&NodeId> {
+        let order = self.tab_order();
+        if order.is_empty() {
+            self.focused_node = None;
+            return None;
+        }
+        let len = order.len() as isize;
+        let next = match self
+            .focused_node
+            .as_ref()
+            .and_then(|id| order.iter().position(|o| o == id))
+        {
+            Some(i) => (i as isize + step).rem_euclid(len),
+            None if step > 0 => 0,
+            None => len - 1,
+        };
+        self.focused_node = Some(order[next as usize].clone());
+        self.focused_node.as_ref()
+    }
+
+    /// Move focus to the nearest node in a direction, per
+    /// [`find_node_in_direction`]. Focus is unchanged if there is no
+    /// candidate.
+    pub fn focus_direction(&mut self, direction: FocusDirection) -> Option<&NodeId> {
+        let Some(from) = self
+            .focused_node
+            .as_ref()
+            .filter(|id| self.nodes.contains_key(*id))
+        else {
+            return self.focus_first();
+        };
+        if let Some(best) = nearest_in_direction(from, direction, self.nodes.values()) {
+            self.focused_node = Some(best);
+        }
+        self.focused_node.as_ref()
+    }
+
+    /// Select the focused node (Enter). Returns its id so the caller can
+    /// open the inspector.
+    pub fn activate_focused(&mut self) -> Option<NodeId> {
+        let id = self.focused_node.clone()?;
+        self.select_node(&id, false);
+        Some(id)
+    }
+
+    /// Nearest other node to `id`, by center distance.
+    pub fn nearest_node(&self, id: &str) -> Option<&NodeId> {
+        let from = center(self.nodes.get(id)?);
+        self.nodes
+            .values()
+            .filter(|n| n.id != id)
+            .min_by(|a, b| {
+                from.distance_to(&center(a))
+                    .total_cmp(&from.distance_to(&center(b)))
+            })
+            .map(|n| &n.id)
+    }
+
+    /// Accessibility attributes for a node.
+    pub fn aria_attributes(
+        &self,
+        id: &str,
+        label: impl Fn(&Node<N>) -> String,
+    ) -> Option<AriaAttributes> {
+        let node = self.nodes.get(id)?;
+        let order = self.tab_order();
+        let has_children = self
+            .nodes
+            .values()
+            .any(|n| n.parent_id.as_deref() == Some(id));
+        Some(AriaAttributes {
+            role: if has_children { "group" } else { "button" },
+            label: label(node),
+            pos_in_set: order.iter().position(|o| o == id)? + 1,
+            set_size: order.len(),
+            selected: node.selected,
+            focused: self.focused_node.as_deref() == Some(id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{NodeData, NodeType};
+
+    fn node(id: &str, x: f64, y: f64) -> Node<NodeData> {
+        Node::new(id, NodeType::Default, Position::new(x, y))
+            .with_dimensions(Dimensions::new(100.0, 40.0))
+            .with_data(NodeData::new(id.to_uppercase()))
+    }
+
+    /// Two rows; "g" is a group containing "g1" and "g2".
+    fn canvas() -> FlowCanvas<NodeData> {
+        FlowCanvas::from_elements(
+            vec![
+                node("b", 300.0, 0.0),
+                node("g2", 320.0, 250.0).with_parent("g"),
+                node("a", 0.0, 0.0),
+                node("g", 0.0, 200.0),
+                node("g1", 20.0, 250.0).with_parent("g"),
+                node("c", 600.0, 200.0),
+            ],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_tab_order() {
+        let mut canvas = canvas();
+        assert_eq!(canvas.tab_order(), ["a", "b", "g", "g1", "g2", "c"]);
+
+        assert_eq!(canvas.focus_next().unwrap(), "a");
+        canvas.set_focus(Some("g2"));
+        assert_eq!(canvas.focus_next().unwrap(), "c");
+        assert_eq!(canvas.focus_next().unwrap(), "a");
+        assert_eq!(canvas.focus_prev().unwrap(), "c");
+
+        let aria = canvas
+            .aria_attributes("g", |n| n.data.as_ref().unwrap().label.clone())
+            .unwrap();
+        assert_eq!(aria.role, "group");
+        assert_eq!(aria.label, "G");
+        assert_eq!((aria.pos_in_set, aria.set_size), (3, 6));
+        assert!(!aria.focused);
+    }
+
+    #[test]
+    fn test_directional_navigation() {
+        let mut canvas = canvas();
+        canvas.set_focus(Some("a"));
+
+        assert_eq!(canvas.focus_direction(FocusDirection::Right).unwrap(), "b");
+        assert_eq!(canvas.focus_direction(FocusDirection::Down).unwrap(), "g2");
+        assert_eq!(canvas.focus_direction(FocusDirection::Left).unwrap(), "g1");
+        assert_eq!(canvas.focus_direction(FocusDirection::Up).unwrap(), "g");
+        // Nothing further left: focus stays.
+        assert_eq!(canvas.focus_direction(FocusDirection::Left).unwrap(), "g");
+
+        assert_eq!(canvas.activate_focused().as_deref(), Some("g"));
+        assert_eq!(canvas.selected_nodes, ["g"]);
+    }
+
+    #[test]
+    fn test_grid_navigation_never_stuck_or_backwards() {
+        // 3x3 grid, ids "r{row}c{col}", up to 18 off the lines.
+        let nodes: Vec<Node<NodeData>> = (0..3)
+            .flat_map(|row| {
+                (0..3).map(move |col| {
+                    let jitter = ((row * 3 + col) % 4) as f64 * 6.0;
+                    node(
+                        &format!("r{row}c{col}"),
+                        col as f64 * 200.0 + jitter,
+                        row as f64 * 120.0 - jitter,
+                    )
+                })
+            })
+            .collect();
+        let moves = [
+            (FocusDirection::Up, -1, 0),
+            (FocusDirection::Down, 1, 0),
+            (FocusDirection::Left, 0, -1),
+            (FocusDirection::Right, 0, 1),
+        ];
+        for row in 0..3i32 {
+            for col in 0..3i32 {
+                let from = format!("r{row}c{col}");
+                for (direction, dr, dc) in moves {
+                    let (r, c) = (row + dr, col + dc);
+                    let expected =
+                        ((0..3).contains(&r) && (0..3).contains(&c)).then(|| format!("r{r}c{c}"));
+                    assert_eq!(
+                        find_node_in_direction(&from, direction, &nodes),
+                        expected,
+                        "{from} {direction:?}"
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            find_node_in_direction("missing", FocusDirection::Up, &nodes),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prefers_alignment_over_distance() {
+        let nodes = [
+            node("a", 0.0, 0.0),
+            // In the same row, far away.
+            node("far", 500.0, 20.0),
+            // Closer, but below the row.
+            node("near", 150.0, 60.0),
+        ];
+        assert_eq!(
+            find_node_in_direction("a", FocusDirection::Right, &nodes).as_deref(),
+            Some("far")
+        );
+        assert_eq!(
+            find_node_in_direction("far", FocusDirection::Left, &nodes).as_deref(),
+            Some("a")
+        );
+        // Without an aligned candidate, the nearest one still counts.
+        assert_eq!(
+            find_node_in_direction("a", FocusDirection::Down, &nodes).as_deref(),
+            Some("near")
+        );
+    }
+
+    #[test]
+    fn test_focus_follows_deletion() {
+        let mut canvas = canvas();
+        canvas.set_focus(Some("b"));
+
+        canvas.remove_node("b");
+        assert_eq!(canvas.focused_node.as_deref(), Some("g2"));
+
+        canvas.remove_node("c");
+        assert_eq!(canvas.focused_node.as_deref(), Some("g2"));
+
+        for id in ["a", "g", "g1", "g2"] {
+            canvas.remove_node(id);
+        }
+        assert_eq!(canvas.focused_node, None);
+    }
+}
@@ -0,0 +1,355 @@
+//! Canvas gesture bindings and interaction presets.
+//!
+//! A [`GestureController`] maps input gestures to canvas actions through
+//! an [`InteractionConfig`]. Presets bundle the usual tables; rebinding a
+//! controller swaps its table in place, so a running canvas picks up the
+//! change on the next event.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::viewport::Viewport;
+
+/// Input gesture on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Gesture {
+    /// Mouse wheel or two-finger scroll.
+    Scroll,
+    /// Scroll with Ctrl or Cmd held; trackpad pinches arrive this way too.
+    CtrlScroll,
+    /// Scroll with Shift held.
+    ShiftScroll,
+    /// Primary-button drag on the empty background.
+    BackgroundDrag,
+    /// Drag while holding Space.
+    SpaceDrag,
+    /// Middle-button drag.
+    MiddleDrag,
+}
+
+impl Gesture {
+    pub fn all() -> &'static [Gesture] {
+        &[
+            Gesture::Scroll,
+            Gesture::CtrlScroll,
+            Gesture::ShiftScroll,
+            Gesture::BackgroundDrag,
+            Gesture::SpaceDrag,
+            Gesture::MiddleDrag,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Gesture::Scroll => "Scroll",
+            Gesture::CtrlScroll => "Ctrl + scroll",
+            Gesture::ShiftScroll => "Shift + scroll",
+            Gesture::BackgroundDrag => "Drag background",
+            Gesture::SpaceDrag => "Space + drag",
+            Gesture::MiddleDrag => "Middle-button drag",
+        }
+    }
+}
+
+/// What a gesture does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CanvasAction {
+    /// Move the viewport with the gesture.
+    Pan,
+    /// Move the viewport sideways only.
+    PanHorizontal,
+    /// Zoom around the pointer.
+    Zoom,
+    /// Start a selection box.
+    Select,
+    /// Ignore the gesture.
+    #[default]
+    None,
+}
+
+impl CanvasAction {
+    pub fn all() -> &'static [CanvasAction] {
+        &[
+            CanvasAction::Pan,
+            CanvasAction::PanHorizontal,
+            CanvasAction::Zoom,
+            CanvasAction::Select,
+            CanvasAction::None,
+        ]
+    }
+
+    /// Stable string form, matching the serialized name.
+    pub fn key(&self) -> &'static str {
+        match self {
+            CanvasAction::Pan => "pan",
+            CanvasAction::PanHorizontal => "pan-horizontal",
+            CanvasAction::Zoom => "zoom",
+            CanvasAction::Select => "select",
+            CanvasAction::None => "none",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|a| a.key() == key)
+    }
+}
+
+/// Named bundle of gesture bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InteractionPreset {
+    /// Scroll zooms, dragging the background pans.
+    #[default]
+    Default,
+    /// Scroll pans, Ctrl + scroll zooms, Space + drag pans.
+    FigmaLike,
+    /// Like Figma, tuned for trackpads: finer zoom steps and a larger drag
+    /// threshold so taps don't move nodes.
+    Trackpad,
+    /// Bindings chosen one by one.
+    Custom,
+}
+
+impl InteractionPreset {
+    pub fn all() -> &'static [InteractionPreset] {
+        &[
+            InteractionPreset::Default,
+            InteractionPreset::FigmaLike,
+            InteractionPreset::Trackpad,
+            InteractionPreset::Custom,
+        ]
+    }
+
+    /// Stable string form, matching the serialized name.
+    pub fn key(&self) -> &'static str {
+        match self {
+            InteractionPreset::Default => "default",
+            InteractionPreset::FigmaLike => "figma-like",
+            InteractionPreset::Trackpad => "trackpad",
+            InteractionPreset::Custom => "custom",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|p| p.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InteractionPreset::Default => "Default",
+            InteractionPreset::FigmaLike => "Figma-like",
+            InteractionPreset::Trackpad => "Trackpad-optimized",
+            InteractionPreset::Custom => "Custom",
+        }
+    }
+}
+
+/// Gesture bindings, drag activation and zoom stepping for a canvas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InteractionConfig {
+    /// Preset the bindings came from; `Custom` once any is changed.
+    #[serde(default)]
+    pub preset: InteractionPreset,
+    /// Action per gesture; unbound gestures do nothing.
+    #[serde(default)]
+    pub bindings: IndexMap<Gesture, CanvasAction>,
+    /// Pointer travel in screen pixels before a node drag starts.
+    #[serde(default = "default_drag_threshold")]
+    pub drag_threshold: f64,
+    /// Hold time before a touch drag starts, in milliseconds.
+    #[serde(default)]
+    pub drag_delay_ms: u64,
+    /// Zoom factor applied per wheel notch.
+    #[serde(default = "default_zoom_step")]
+    pub zoom_step: f64,
+}
+
+fn default_drag_threshold() -> f64 {
+    5.0
+}
+
+fn default_zoom_step() -> f64 {
+    1.2
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self::preset(InteractionPreset::Default)
+    }
+}
+
+impl InteractionConfig {
+    /// The bindings of a preset. `Custom` starts from the default table.
+    pub fn preset(preset: InteractionPreset) -> Self {
+        use CanvasAction::*;
+        let (table, drag_threshold, drag_delay_ms, zoom_step) = match preset {
+            InteractionPreset::Default | InteractionPreset::Custom => {
+                ([Zoom, Zoom, PanHorizontal, Pan, Pan, Pan], 5.0, 0, 1.2)
+            }
+            InteractionPreset::FigmaLike => {
+                ([Pan, Zoom, PanHorizontal, Select, Pan, Pan], 3.0, 0, 1.1)
+            }
+            InteractionPreset::Trackpad => {
+                ([Pan, Zoom, PanHorizontal, Select, Pan, Pan], 8.0, 120, 1.05)
+            }
+        };
+        Self {
+            preset,
+            bindings: Gesture::all().iter().copied().zip(table).collect(),
+            drag_threshold,
+            drag_delay_ms,
+            zoom_step,
+        }
+    }
+
+    /// Bind a gesture, switching to the custom preset.
+    pub fn with_binding(mut self, gesture: Gesture, action: CanvasAction) -> Self {
+        self.bindings.insert(gesture, action);
+        self.preset = InteractionPreset::Custom;
+        self
+    }
+
+    pub fn action(&self, gesture: Gesture) -> CanvasAction {
+        self.bindings.get(&gesture).copied().unwrap_or_default()
+    }
+}
+
+/// Routes canvas gestures to actions.
+#[derive(Debug, Clone, Default)]
+pub struct GestureController {
+    config: InteractionConfig,
+    /// Bumped on every rebind, so views can tell their cached table is stale.
+    generation: u64,
+}
+
+impl GestureController {
+    pub fn new(config: InteractionConfig) -> Self {
+        Self {
+            config,
+            generation: 0,
+        }
+    }
+
+    pub fn config(&self) -> &InteractionConfig {
+        &self.config
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Swap in new bindings; takes effect on the next gesture.
+    pub fn rebind(&mut self, config: InteractionConfig) {
+        if config != self.config {
+            self.config = config;
+            self.generation += 1;
+        }
+    }
+
+    pub fn action(&self, gesture: Gesture) -> CanvasAction {
+        self.config.action(gesture)
+    }
+
+    /// Apply a scroll gesture to the viewport. Returns what it did.
+    pub fn scroll(
+        &self,
+        gesture: Gesture,
+        dx: f64,
+        dy: f64,
+        viewport: &mut Viewport,
+    ) -> CanvasAction {
+        let action = self.action(gesture);
+        match action {
+            CanvasAction::Pan => viewport.pan(-dx, -dy),
+            CanvasAction::PanHorizontal => viewport.pan(-(dx + dy), 0.0),
+            CanvasAction::Zoom if viewport.zoom_enabled => {
+                if dy < 0.0 {
+                    viewport.zoom_in(self.config.zoom_step);
+                } else if dy > 0.0 {
+                    viewport.zoom_out(self.config.zoom_step);
+                }
+            }
+            _ => {}
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(preset: InteractionPreset) -> Vec<(Gesture, CanvasAction)> {
+        InteractionConfig::preset(preset)
+            .bindings
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_preset_tables() {
+        use CanvasAction::*;
+        use Gesture::*;
+        assert_eq!(
+            table(InteractionPreset::Default),
+            [
+                (Scroll, Zoom),
+                (CtrlScroll, Zoom),
+                (ShiftScroll, PanHorizontal),
+                (BackgroundDrag, Pan),
+                (SpaceDrag, Pan),
+                (MiddleDrag, Pan),
+            ]
+        );
+        let figma = [
+            (Scroll, Pan),
+            (CtrlScroll, Zoom),
+            (ShiftScroll, PanHorizontal),
+            (BackgroundDrag, Select),
+            (SpaceDrag, Pan),
+            (MiddleDrag, Pan),
+        ];
+        assert_eq!(table(InteractionPreset::FigmaLike), figma);
+        assert_eq!(table(InteractionPreset::Trackpad), figma);
+
+        let trackpad = InteractionConfig::preset(InteractionPreset::Trackpad);
+        assert_eq!(trackpad.drag_threshold, 8.0);
+        assert_eq!(trackpad.zoom_step, 1.05);
+        assert_eq!(
+            table(InteractionPreset::Custom),
+            table(InteractionPreset::Default)
+        );
+
+        let custom = InteractionConfig::default().with_binding(Scroll, None);
+        assert_eq!(custom.preset, InteractionPreset::Custom);
+        assert_eq!(custom.action(Scroll), None);
+    }
+
+    #[test]
+    fn test_rebind_mid_session() {
+        let mut viewport = Viewport::default();
+        let mut controller = GestureController::default();
+        assert_eq!(
+            controller.scroll(Gesture::Scroll, 0.0, -100.0, &mut viewport),
+            CanvasAction::Zoom
+        );
+        assert_eq!(viewport.transform.zoom, 1.2);
+
+        controller.rebind(InteractionConfig::preset(InteractionPreset::FigmaLike));
+        assert_eq!(controller.generation(), 1);
+        controller.scroll(Gesture::Scroll, 10.0, -100.0, &mut viewport);
+        assert_eq!(viewport.transform.zoom, 1.2);
+        assert_eq!((viewport.transform.x, viewport.transform.y), (-10.0, 100.0));
+
+        // Same table again is not a rebind.
+        controller.rebind(InteractionConfig::preset(InteractionPreset::FigmaLike));
+        assert_eq!(controller.generation(), 1);
+
+        let json = serde_json::to_string(controller.config()).unwrap();
+        assert!(json.contains("\"background-drag\":\"select\""));
+        let back: InteractionConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(&back, controller.config());
+    }
+}
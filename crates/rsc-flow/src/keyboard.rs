@@ -0,0 +1,330 @@
+//! Keyboard control of canvas nodes.
+//!
+//! A [`CanvasKeyboardController`] turns key presses into canvas changes:
+//! arrows move the focus and selection to the nearest node in that
+//! direction, Tab cycles in tab order, Shift+arrows nudge the focused node,
+//! Enter starts renaming it, Delete removes it and Escape clears the
+//! selection. Focus and selection are
+//! applied directly; edits are returned as a [`KeyboardCommand`] so the
+//! caller can record them in its history.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::FlowCanvas;
+use crate::focus::FocusDirection;
+use crate::node::NodeId;
+use crate::position::Position;
+
+/// Default distance a nudge moves a node without a snap grid.
+pub const DEFAULT_NUDGE_STEP: f64 = 10.0;
+
+/// A key the canvas handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanvasKey {
+    Arrow(FocusDirection),
+    Tab,
+    Enter,
+    Delete,
+    Escape,
+}
+
+impl CanvasKey {
+    /// Parse a DOM `KeyboardEvent.key` value.
+    pub fn from_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "ArrowUp" => CanvasKey::Arrow(FocusDirection::Up),
+            "ArrowDown" => CanvasKey::Arrow(FocusDirection::Down),
+            "ArrowLeft" => CanvasKey::Arrow(FocusDirection::Left),
+            "ArrowRight" => CanvasKey::Arrow(FocusDirection::Right),
+            "Tab" => CanvasKey::Tab,
+            "Enter" => CanvasKey::Enter,
+            "Delete" | "Backspace" => CanvasKey::Delete,
+            "Escape" => CanvasKey::Escape,
+            _ => return None,
+        })
+    }
+}
+
+/// What a key press asks the caller to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyboardCommand {
+    /// Not handled; let the key through.
+    None,
+    /// Focus and selection moved to this node.
+    Focus(NodeId),
+    /// Move the node to `to`.
+    Nudge { id: NodeId, to: Position },
+    /// Show the rename field for the node.
+    Rename(NodeId),
+    /// Hide the rename field without renaming.
+    CancelRename(NodeId),
+    /// Remove the node.
+    Delete(NodeId),
+}
+
+/// Keyboard settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyboardConfig {
+    /// Distance a Shift+arrow nudge moves a node. With a snap grid, nudges
+    /// move to the next grid line instead.
+    pub nudge_step: f64,
+}
+
+impl Default for KeyboardConfig {
+    fn default() -> Self {
+        Self {
+            nudge_step: DEFAULT_NUDGE_STEP,
+        }
+    }
+}
+
+/// Keyboard state of a canvas.
+#[derive(Debug, Clone, Default)]
+pub struct CanvasKeyboardController {
+    pub config: KeyboardConfig,
+    /// Node whose rename field is open. While it is, only Escape is
+    /// handled; every other key belongs to the field.
+    renaming: Option<NodeId>,
+}
+
+impl CanvasKeyboardController {
+    pub fn new(config: KeyboardConfig) -> Self {
+        Self {
+            config,
+            renaming: None,
+        }
+    }
+
+    pub fn renaming(&self) -> Option<&NodeId> {
+        self.renaming.as_ref()
+    }
+
+    /// Close the rename field, once the rename was applied. Returns the
+    /// node that was being renamed.
+    pub fn finish_rename(&mut self) -> Option<NodeId> {
+        self.renaming.take()
+    }
+
+    /// Handle a key press on `canvas`.
+    pub fn handle_key<N, E>(
+        &mut self,
+        canvas: &mut FlowCanvas<N, E>,
+        key: CanvasKey,
+        shift: bool,
+    ) -> KeyboardCommand {
+        if let Some(id) = &self.renaming {
+            if key != CanvasKey::Escape {
+                return KeyboardCommand::None;
+            }
+            let id = id.clone();
+            self.renaming = None;
+            return KeyboardCommand::CancelRename(id);
+        }
+        // Focus may point at a node removed since.
+        canvas.set_focus(canvas.focused_node.clone().as_deref());
+
+        match key {
+            CanvasKey::Arrow(direction) if shift => {
+                let Some(id) = canvas.focused_node.clone() else {
+                    return KeyboardCommand::None;
+                };
+                let to = self.nudge_target(canvas, &id, direction);
+                KeyboardCommand::Nudge { id, to }
+            }
+            CanvasKey::Arrow(direction) => select(canvas, |c| c.focus_direction(direction)),
+            CanvasKey::Tab if shift => select(canvas, |c| c.focus_prev()),
+            CanvasKey::Tab => select(canvas, |c| c.focus_next()),
+            CanvasKey::Enter => match canvas.focused_node.clone() {
+                Some(id) => {
+                    self.renaming = Some(id.clone());
+                    KeyboardCommand::Rename(id)
+                }
+                None => KeyboardCommand::None,
+            },
+            CanvasKey::Delete => match &canvas.focused_node {
+                Some(id) => KeyboardCommand::Delete(id.clone()),
+                None => KeyboardCommand::None,
+            },
+            CanvasKey::Escape => {
+                canvas.clear_selection();
+                canvas.focused_node = None;
+                KeyboardCommand::None
+            }
+        }
+    }
+
+    /// Where a nudge in `direction` moves node `id`: one step, or to the
+    /// next grid line in that direction when the canvas snaps to a grid.
+    fn nudge_target<N, E>(
+        &self,
+        canvas: &FlowCanvas<N, E>,
+        id: &str,
+        direction: FocusDirection,
+    ) -> Position {
+        let mut position = canvas.nodes[id].position;
+        let grid = canvas.config.snap_config().grid;
+        let (value, cell, forward) = match direction {
+            FocusDirection::Up => (&mut position.y, grid.map(|g| g.1), false),
+            FocusDirection::Down => (&mut position.y, grid.map(|g| g.1), true),
+            FocusDirection::Left => (&mut position.x, grid.map(|g| g.0), false),
+            FocusDirection::Right => (&mut position.x, grid.map(|g| g.0), true),
+        };
+        *value = match cell {
+            Some(cell) if cell > 0.0 && forward => ((*value / cell).floor() + 1.0) * cell,
+            Some(cell) if cell > 0.0 => ((*value / cell).ceil() - 1.0) * cell,
+            _ if forward => *value + self.config.nudge_step,
+            _ => *value - self.config.nudge_step,
+        };
+        position
+    }
+}
+
+/// Move focus with `step` and select the focused node alone.
+fn select<N, E>(
+    canvas: &mut FlowCanvas<N, E>,
+    step: impl FnOnce(&mut FlowCanvas<N, E>) -> Option<&NodeId>,
+) -> KeyboardCommand {
+    let Some(id) = step(canvas).cloned() else {
+        return KeyboardCommand::None;
+    };
+    canvas.select_node(&id, false);
+    KeyboardCommand::Focus(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Node, NodeType};
+    use crate::position::Dimensions;
+
+    /// Two rows of two 100x40 nodes.
+    fn canvas() -> FlowCanvas {
+        let mut canvas = FlowCanvas::new();
+        for (id, x, y) in [
+            ("a", 0.0, 0.0),
+            ("b", 200.0, 0.0),
+            ("c", 0.0, 100.0),
+            ("d", 200.0, 103.0),
+        ] {
+            canvas.add_node(
+                Node::new(id, NodeType::Default, Position::new(x, y))
+                    .with_dimensions(Dimensions::new(100.0, 40.0)),
+            );
+        }
+        canvas
+    }
+
+    fn press(
+        keys: &mut CanvasKeyboardController,
+        canvas: &mut FlowCanvas,
+        key: &str,
+        shift: bool,
+    ) -> KeyboardCommand {
+        keys.handle_key(canvas, CanvasKey::from_key(key).unwrap(), shift)
+    }
+
+    #[test]
+    fn test_navigation_selects() {
+        let mut canvas = canvas();
+        let mut keys = CanvasKeyboardController::default();
+
+        // The first arrow focuses the first node.
+        let focus = |id: &str| KeyboardCommand::Focus(id.to_string());
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowDown", false),
+            focus("a")
+        );
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowDown", false),
+            focus("c")
+        );
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowRight", false),
+            focus("d")
+        );
+        assert_eq!(canvas.selected_nodes, ["d"]);
+        // At the edge, focus stays.
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowRight", false),
+            focus("d")
+        );
+
+        assert_eq!(press(&mut keys, &mut canvas, "Tab", false), focus("a"));
+        assert_eq!(press(&mut keys, &mut canvas, "Tab", true), focus("d"));
+
+        assert_eq!(
+            press(&mut keys, &mut canvas, "Escape", false),
+            KeyboardCommand::None
+        );
+        assert!(canvas.selected_nodes.is_empty() && canvas.focused_node.is_none());
+        assert_eq!(
+            press(&mut keys, &mut canvas, "Delete", false),
+            KeyboardCommand::None
+        );
+    }
+
+    #[test]
+    fn test_nudge_honors_grid() {
+        let mut canvas = canvas();
+        let mut keys = CanvasKeyboardController::new(KeyboardConfig { nudge_step: 5.0 });
+        canvas.set_focus(Some("d"));
+
+        let nudge = |x, y| KeyboardCommand::Nudge {
+            id: "d".to_string(),
+            to: Position::new(x, y),
+        };
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowLeft", true),
+            nudge(195.0, 103.0)
+        );
+
+        // With a grid, nudges go to the next line, even from off the grid.
+        canvas.config.snap_to_grid = true;
+        canvas.config.grid_size = 20.0;
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowDown", true),
+            nudge(200.0, 120.0)
+        );
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowUp", true),
+            nudge(200.0, 100.0)
+        );
+        assert_eq!(
+            press(&mut keys, &mut canvas, "ArrowRight", true),
+            nudge(220.0, 103.0)
+        );
+        // Nudges don't move the node themselves.
+        assert_eq!(canvas.nodes["d"].position, Position::new(200.0, 103.0));
+    }
+
+    #[test]
+    fn test_rename_owns_keys() {
+        let mut canvas = canvas();
+        let mut keys = CanvasKeyboardController::default();
+        canvas.set_focus(Some("b"));
+
+        let rename = press(&mut keys, &mut canvas, "Enter", false);
+        assert_eq!(rename, KeyboardCommand::Rename("b".to_string()));
+        assert_eq!(
+            press(&mut keys, &mut canvas, "Delete", false),
+            KeyboardCommand::None
+        );
+        assert_eq!(
+            press(&mut keys, &mut canvas, "Escape", false),
+            KeyboardCommand::CancelRename("b".to_string())
+        );
+        assert_eq!(
+            press(&mut keys, &mut canvas, "Delete", false),
+            KeyboardCommand::Delete("b".to_string())
+        );
+
+        // A removed node is no longer focused.
+        canvas.focused_node = Some("gone".to_string());
+        assert_eq!(
+            press(&mut keys, &mut canvas, "Enter", false),
+            KeyboardCommand::None
+        );
+        assert_eq!(keys.finish_rename(), None);
+    }
+}
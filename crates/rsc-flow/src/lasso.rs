@@ -0,0 +1,355 @@
+//! Freeform lasso selection.
+//!
+//! Pointer positions are collected into a polygon in canvas coordinates,
+//! so the lasso keeps its shape while the viewport zooms or pans. The
+//! polygon is simplified as it grows so hit testing stays cheap on long
+//! drags.
+
+use crate::canvas::FlowCanvas;
+use crate::node::{Node, NodeId};
+use crate::position::{Dimensions, Position, Rect};
+use crate::viewport::Viewport;
+
+/// Size assumed for nodes without dimensions.
+pub(crate) const DEFAULT_NODE_SIZE: Dimensions = Dimensions {
+    width: 150.0,
+    height: 50.0,
+};
+
+/// Default upper bound on lasso polygon points.
+pub const DEFAULT_LASSO_MAX_POINTS: usize = 64;
+
+/// How the nodes under a selection gesture combine with the current selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    /// Replace the selection.
+    #[default]
+    Replace,
+    /// Add to the selection (Shift).
+    Add,
+    /// Remove from the selection (Alt).
+    Subtract,
+}
+
+impl SelectionMode {
+    /// Mode for the held modifier keys; Alt wins over Shift.
+    pub fn from_modifiers(shift: bool, alt: bool) -> Self {
+        match (shift, alt) {
+            (_, true) => SelectionMode::Subtract,
+            (true, false) => SelectionMode::Add,
+            (false, false) => SelectionMode::Replace,
+        }
+    }
+}
+
+/// Which part of a node must fall inside the lasso.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LassoHit {
+    /// The node's center.
+    #[default]
+    Center,
+    /// The node's whole rect.
+    Contained,
+}
+
+/// An in-progress lasso gesture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lasso {
+    /// Polygon points in canvas coordinates.
+    points: Vec<Position>,
+    /// Upper bound on `points`; the points that change the shape least
+    /// are dropped first.
+    pub max_points: usize,
+    pub hit: LassoHit,
+}
+
+impl Default for Lasso {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lasso {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            max_points: DEFAULT_LASSO_MAX_POINTS,
+            hit: LassoHit::default(),
+        }
+    }
+
+    /// Set the point limit (at least 3).
+    pub fn with_max_points(mut self, max_points: usize) -> Self {
+        self.max_points = max_points.max(3);
+        self
+    }
+
+    pub fn with_hit(mut self, hit: LassoHit) -> Self {
+        self.hit = hit;
+        self
+    }
+
+    /// Add a point in canvas coordinates.
+    pub fn push(&mut self, point: Position) {
+        if self.points.last() == Some(&point) {
+            return;
+        }
+        self.points.push(point);
+        simplify(&mut self.points, self.max_points);
+    }
+
+    /// Add a pointer position in screen coordinates.
+    pub fn push_screen(&mut self, point: Position, viewport: &Viewport) {
+        self.push(viewport.screen_to_canvas(point));
+    }
+
+    /// Polygon in canvas coordinates, for hit testing and rendering.
+    pub fn points(&self) -> &[Position] {
+        &self.points
+    }
+
+    /// Polygon in screen coordinates, for drawing the outline.
+    pub fn screen_points(&self, viewport: &Viewport) -> Vec<Position> {
+        self.points
+            .iter()
+            .map(|p| viewport.canvas_to_screen(*p))
+            .collect()
+    }
+
+    /// SVG path data for the closed outline, in canvas coordinates.
+    pub fn to_svg_path(&self) -> String {
+        let mut path = String::new();
+        for (i, p) in self.points.iter().enumerate() {
+            let cmd = if i == 0 { 'M' } else { 'L' };
+            path.push_str(&format!("{}{:.1},{:.1} ", cmd, p.x, p.y));
+        }
+        if !self.points.is_empty() {
+            path.push('Z');
+        }
+        path
+    }
+
+    /// Whether a point lies inside the polygon (even-odd rule).
+    pub fn contains(&self, point: &Position) -> bool {
+        polygon_contains(&self.points, point)
+    }
+
+    /// Whether a whole rect lies inside the polygon.
+    pub fn contains_rect(&self, rect: &Rect) -> bool {
+        let corners = rect_corners(rect);
+        if !corners.iter().all(|c| self.contains(c)) {
+            return false;
+        }
+        // Corners inside is not enough for concave polygons: an edge may
+        // still cut through the rect.
+        let n = self.points.len();
+        (0..n).all(|i| {
+            let (a, b) = (self.points[i], self.points[(i + 1) % n]);
+            (0..4).all(|j| !segments_cross(a, b, corners[j], corners[(j + 1) % 4]))
+        })
+    }
+
+    /// Whether a node is selected by this lasso.
+    pub fn hits<N>(&self, node: &Node<N>) -> bool {
+        let rect = Rect {
+            position: node.position,
+            dimensions: node.dimensions.unwrap_or(DEFAULT_NODE_SIZE),
+        };
+        match self.hit {
+            LassoHit::Center => self.contains(&rect.center()),
+            LassoHit::Contained => self.contains_rect(&rect),
+        }
+    }
+}
+
+fn rect_corners(rect: &Rect) -> [Position; 4] {
+    let (x, y) = (rect.position.x, rect.position.y);
+    let (w, h) = (rect.dimensions.width, rect.dimensions.height);
+    [
+        Position::new(x, y),
+        Position::new(x + w, y),
+        Position::new(x + w, y + h),
+        Position::new(x, y + h),
+    ]
+}
+
+fn polygon_contains(points: &[Position], point: &Position) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn cross(o: Position, a: Position, b: Position) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Whether two segments properly cross (touching endpoints do not count).
+fn segments_cross(a: Position, b: Position, c: Position, d: Position) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Drop points until at most `max` remain, each time removing the point
+/// whose triangle with its neighbours has the smallest area. The first and
+/// last points are kept so the outline stays anchored to the pointer.
+fn simplify(points: &mut Vec<Position>, max: usize) {
+    while points.len() > max.max(3) {
+        let Some(index) = (1..points.len() - 1).min_by(|&a, &b| {
+            let area = |i: usize| cross(points[i - 1], points[i], points[i + 1]).abs();
+            area(a).total_cmp(&area(b))
+        }) else {
+            return;
+        };
+        points.remove(index);
+    }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Ids of the nodes selected by a lasso, in canvas order.
+    pub fn nodes_in_lasso(&self, lasso: &Lasso) -> Vec<NodeId> {
+        self.lasso_candidates(lasso)
+            .into_iter()
+            .filter(|id| self.nodes.get(id).is_some_and(|n| lasso.hits(n)))
+            .collect()
+    }
+
+    /// Apply a lasso to the node selection. Returns the ids it hit.
+    pub fn select_lasso(&mut self, lasso: &Lasso, mode: SelectionMode) -> Vec<NodeId> {
+        let hit = self.nodes_in_lasso(lasso);
+        self.apply_selection(&hit, mode);
+        hit
+    }
+
+    /// Combine `ids` with the node selection according to `mode`.
+    pub fn apply_selection(&mut self, ids: &[NodeId], mode: SelectionMode) {
+        match mode {
+            SelectionMode::Replace => {
+                self.clear_selection();
+                for id in ids {
+                    self.select_node(id, true);
+                }
+            }
+            SelectionMode::Add => {
+                for id in ids {
+                    self.select_node(id, true);
+                }
+            }
+            SelectionMode::Subtract => {
+                for id in ids {
+                    if let Some(node) = self.nodes.get_mut(id) {
+                        node.selected = false;
+                    }
+                }
+                self.selected_nodes.retain(|n| !ids.contains(n));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::NodeType;
+    use crate::viewport::ViewportTransform;
+
+    fn lasso(points: &[(f64, f64)]) -> Lasso {
+        let mut lasso = Lasso::new();
+        for (x, y) in points {
+            lasso.push(Position::new(*x, *y));
+        }
+        lasso
+    }
+
+    /// A "U" shape: two arms joined along the bottom, open at the top.
+    fn u_shape() -> Lasso {
+        lasso(&[
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 200.0),
+            (200.0, 200.0),
+            (200.0, 0.0),
+            (300.0, 0.0),
+            (300.0, 300.0),
+            (0.0, 300.0),
+        ])
+    }
+
+    #[test]
+    fn test_concave_polygon() {
+        let u = u_shape();
+        assert!(u.contains(&Position::new(50.0, 100.0)));
+        assert!(u.contains(&Position::new(250.0, 100.0)));
+        assert!(u.contains(&Position::new(150.0, 250.0)));
+        // Inside the notch, within the bounding box.
+        assert!(!u.contains(&Position::new(150.0, 100.0)));
+
+        // All four corners are inside, but the notch cuts through the rect.
+        let spanning = Rect::new(50.0, 180.0, 200.0, 100.0);
+        assert!(rect_corners(&spanning).iter().all(|c| u.contains(c)));
+        assert!(!u.contains_rect(&spanning));
+        assert!(u.contains_rect(&Rect::new(20.0, 20.0, 60.0, 60.0)));
+    }
+
+    #[test]
+    fn test_simplification_bound() {
+        let mut lasso = Lasso::new().with_max_points(16);
+        for i in 0..500 {
+            let t = i as f64 / 500.0 * std::f64::consts::TAU;
+            lasso.push(Position::new(100.0 * t.cos(), 100.0 * t.sin()));
+            assert!(lasso.points().len() <= 16);
+        }
+        assert_eq!(lasso.points().len(), 16);
+        assert!(lasso.contains(&Position::new(0.0, 0.0)));
+        assert!(lasso.contains(&Position::new(80.0, 0.0)));
+        assert!(!lasso.contains(&Position::new(120.0, 0.0)));
+    }
+
+    #[test]
+    fn test_select_with_modifiers_under_zoom() {
+        let node = |id: &str, x: f64, y: f64| {
+            Node::new(id, NodeType::Default, Position::new(x, y))
+                .with_dimensions(Dimensions::new(40.0, 40.0))
+        };
+        let mut canvas: FlowCanvas = FlowCanvas::from_elements(
+            vec![node("a", 0.0, 0.0), node("b", 100.0, 100.0), node("c", 200.0, 200.0)],
+            vec![],
+        );
+        canvas.viewport.transform = ViewportTransform::new(50.0, 20.0, 2.0);
+
+        // A diagonal band around a and b, drawn in screen space.
+        let mut band = Lasso::new();
+        for (x, y) in [(-20.0, 0.0), (20.0, -20.0), (180.0, 140.0), (140.0, 180.0)] {
+            let screen = canvas.viewport.canvas_to_screen(Position::new(x, y));
+            band.push_screen(screen, &canvas.viewport);
+        }
+        assert!((band.points()[2].x - 180.0).abs() < 1e-9);
+
+        canvas.select_node("c", false);
+        assert_eq!(canvas.select_lasso(&band, SelectionMode::Replace), ["a", "b"]);
+        assert_eq!(canvas.selected_nodes, ["a", "b"]);
+
+        canvas.select_node("c", true);
+        let only_b = lasso(&[(90.0, 90.0), (160.0, 90.0), (160.0, 160.0), (90.0, 160.0)]);
+        canvas.select_lasso(&only_b, SelectionMode::from_modifiers(false, true));
+        assert_eq!(canvas.selected_nodes, ["a", "c"]);
+        assert!(!canvas.get_node("b").unwrap().selected);
+
+        canvas.select_lasso(&only_b, SelectionMode::from_modifiers(true, false));
+        assert_eq!(canvas.selected_nodes, ["a", "c", "b"]);
+    }
+}
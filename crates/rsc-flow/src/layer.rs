@@ -0,0 +1,267 @@
+//! Edge layers.
+//!
+//! Edges can be assigned to a named layer ("happy path", "error handling")
+//! through [`EdgeData::layer`], and whole layers hidden at once. Hiding a
+//! layer can also hide the nodes that are only reachable through it.
+
+use std::collections::{HashSet, VecDeque};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::FlowCanvas;
+use crate::edge::{Edge, EdgeData};
+use crate::node::NodeId;
+
+/// A named class of edges.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeLayer {
+    pub name: String,
+    /// Stroke color for the layer's edges and its panel swatch.
+    pub color: String,
+    /// Whether the layer is shown when the user hasn't toggled it.
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+impl EdgeLayer {
+    pub fn new(name: impl Into<String>, color: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            color: color.into(),
+            visible: true,
+        }
+    }
+
+    /// Start hidden until toggled on.
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+}
+
+/// Layers known to a document, in panel order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LayerRegistry {
+    pub layers: IndexMap<String, EdgeLayer>,
+}
+
+impl LayerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a layer.
+    pub fn insert(&mut self, layer: EdgeLayer) {
+        self.layers.insert(layer.name.clone(), layer);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<EdgeLayer> {
+        self.layers.shift_remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EdgeLayer> {
+        self.layers.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EdgeLayer> {
+        self.layers.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Default visibility of a layer. Unknown layers are visible.
+    pub fn visible_by_default(&self, name: &str) -> bool {
+        self.layers.get(name).is_none_or(|l| l.visible)
+    }
+}
+
+impl Edge<EdgeData> {
+    /// Whether the edge is shown when the `hidden` layers are off.
+    /// Unlayered edges are always shown.
+    pub fn is_visible_with(&self, hidden: &HashSet<String>) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|d| d.layer.as_ref())
+            .is_none_or(|layer| !hidden.contains(layer))
+    }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Nodes without incoming edges, where reachability starts.
+    pub fn root_nodes(&self) -> Vec<&NodeId> {
+        let targets: HashSet<&str> = self.edges.values().map(|e| e.target.as_str()).collect();
+        self.nodes
+            .keys()
+            .filter(|id| !targets.contains(id.as_str()))
+            .collect()
+    }
+
+    /// Nodes reachable from the roots over edges accepted by `follow`.
+    fn reachable(&self, follow: impl Fn(&Edge<E>) -> bool) -> HashSet<NodeId> {
+        let mut seen: HashSet<NodeId> = self.root_nodes().into_iter().cloned().collect();
+        let mut queue: VecDeque<NodeId> = seen.iter().cloned().collect();
+        while let Some(id) = queue.pop_front() {
+            for edge in self.edges.values() {
+                if edge.source == id && follow(edge) && seen.insert(edge.target.clone()) {
+                    queue.push_back(edge.target.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Nodes no root can reach, e.g. members of a cycle nothing points into.
+    /// Every edge counts, hidden or not.
+    pub fn unreachable_nodes(&self) -> Vec<NodeId> {
+        let reachable = self.reachable(|_| true);
+        self.nodes
+            .keys()
+            .filter(|id| !reachable.contains(*id))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<N> FlowCanvas<N, EdgeData> {
+    /// Edges shown when the `hidden` layers are off.
+    pub fn visible_edges(&self, hidden: &HashSet<String>) -> Vec<&Edge<EdgeData>> {
+        self.edges
+            .values()
+            .filter(|e| e.is_visible_with(hidden))
+            .collect()
+    }
+
+    /// Nodes that are reachable, but only through edges on hidden layers.
+    pub fn nodes_hidden_by_layers(&self, hidden: &HashSet<String>) -> Vec<NodeId> {
+        let all = self.reachable(|_| true);
+        let visible = self.reachable(|e| e.is_visible_with(hidden));
+        self.nodes
+            .keys()
+            .filter(|id| all.contains(*id) && !visible.contains(*id))
+            .cloned()
+            .collect()
+    }
+
+    /// Copy of the canvas without the hidden layers' edges, for rendering
+    /// and export. With `hide_nodes`, nodes only reachable through those
+    /// edges are dropped too.
+    pub fn with_layers_hidden(&self, hidden: &HashSet<String>, hide_nodes: bool) -> Self
+    where
+        N: Clone,
+    {
+        let mut canvas = self.clone();
+        if hide_nodes {
+            for id in self.nodes_hidden_by_layers(hidden) {
+                canvas.remove_node(&id);
+            }
+        }
+        canvas.edges.retain(|_, e| e.is_visible_with(hidden));
+        let edges = &canvas.edges;
+        canvas.selected_edges.retain(|id| edges.contains_key(id));
+        canvas
+    }
+
+    /// Move a layer's edges to no layer. Returns how many were moved.
+    pub fn clear_layer(&mut self, name: &str) -> usize {
+        let mut moved = 0;
+        for data in self.edges.values_mut().filter_map(|e| e.data.as_mut()) {
+            if data.layer.as_deref() == Some(name) {
+                data.layer = None;
+                moved += 1;
+            }
+        }
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Node, NodeType};
+    use crate::position::Position;
+
+    fn edge(id: &str, source: &str, target: &str, layer: Option<&str>) -> Edge<EdgeData> {
+        Edge::new(id, source, target).with_data(EdgeData {
+            layer: layer.map(str::to_string),
+            ..Default::default()
+        })
+    }
+
+    /// start -> form, then form -> done on the happy layer and
+    /// form -> retry on the error layer; retry -> fix is unlayered.
+    fn canvas() -> FlowCanvas<(), EdgeData> {
+        let nodes = ["start", "form", "done", "retry", "fix"]
+            .into_iter()
+            .map(|id| Node::new(id, NodeType::Default, Position::zero()))
+            .collect();
+        FlowCanvas::from_elements(
+            nodes,
+            vec![
+                edge("e1", "start", "form", None),
+                edge("e2", "form", "done", Some("happy")),
+                edge("e3", "form", "retry", Some("error")),
+                edge("e4", "retry", "fix", None),
+            ],
+        )
+    }
+
+    fn hidden(layers: &[&str]) -> HashSet<String> {
+        layers.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_hidden_layer_reachability() {
+        let mut canvas = canvas();
+        assert!(canvas.nodes_hidden_by_layers(&hidden(&[])).is_empty());
+
+        // Hiding "error" hides retry and everything only reachable from it.
+        let error = hidden(&["error"]);
+        assert_eq!(canvas.visible_edges(&error).len(), 3);
+        assert_eq!(canvas.nodes_hidden_by_layers(&error), ["retry", "fix"]);
+        // Validation looks at every edge, so nothing is unreachable.
+        assert!(canvas.unreachable_nodes().is_empty());
+
+        // A second path on a visible layer keeps retry shown.
+        canvas.add_edge(edge("e5", "done", "retry", Some("admin")));
+        assert!(canvas.nodes_hidden_by_layers(&error).is_empty());
+        assert_eq!(
+            canvas.nodes_hidden_by_layers(&hidden(&["error", "admin"])),
+            ["retry", "fix"]
+        );
+
+        let filtered = canvas.with_layers_hidden(&hidden(&["error", "admin"]), true);
+        assert_eq!(filtered.nodes.len(), 3);
+        assert_eq!(filtered.edges.keys().collect::<Vec<_>>(), ["e1", "e2"]);
+        let edges_only = canvas.with_layers_hidden(&error, false);
+        assert_eq!(edges_only.nodes.len(), 5);
+        assert_eq!(edges_only.edges.len(), 4);
+    }
+
+    #[test]
+    fn test_clear_layer_and_registry() {
+        let mut canvas = canvas();
+        canvas.add_edge(edge("e5", "start", "retry", Some("error")));
+        let mut registry = LayerRegistry::new();
+        registry.insert(EdgeLayer::new("error", "#ef4444"));
+        registry.insert(EdgeLayer::new("admin", "#a855f7").hidden());
+        assert!(!registry.visible_by_default("admin"));
+        assert!(registry.visible_by_default("unknown"));
+
+        registry.remove("error");
+        assert_eq!(canvas.clear_layer("error"), 2);
+        // Unlayered edges can't be hidden.
+        assert_eq!(canvas.visible_edges(&hidden(&["error"])).len(), 5);
+        assert_eq!(
+            registry.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(),
+            ["admin"]
+        );
+    }
+}
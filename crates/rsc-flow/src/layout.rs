@@ -1,10 +1,13 @@
 //! Layout algorithms for automatic node positioning.
 
+use std::collections::{HashMap, HashSet, VecDeque};
+
 use serde::{Deserialize, Serialize};
 
-use crate::edge::Edge;
-use crate::node::{Node, NodeId};
+use crate::edge::{Edge, EdgeId};
+use crate::node::{HandleSide, Node, NodeId};
 use crate::position::{Dimensions, Position};
+use crate::trace::trace_span;
 
 /// Layout direction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -21,6 +24,20 @@ pub enum LayoutDirection {
     RightToLeft,
 }
 
+/// How layered layout ranks graphs that contain cycles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum CycleHandling {
+    /// Find back edges with a depth-first search and reverse them while
+    /// ranking, so every other edge points to a later rank. Nodes sit on
+    /// the longest path from a root.
+    #[default]
+    ReverseBackEdges,
+    /// Rank by breadth-first distance from the roots; edges closing a
+    /// cycle are skipped where the search first meets them.
+    BreadthFirst,
+}
+
 /// Layout configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayoutConfig {
@@ -39,6 +56,9 @@ pub struct LayoutConfig {
     /// Default node dimensions.
     #[serde(default = "default_node_dimensions")]
     pub default_node_dimensions: Dimensions,
+    /// Ranking strategy for cyclic graphs.
+    #[serde(default)]
+    pub cycle_handling: CycleHandling,
 }
 
 fn default_node_sep() -> f64 {
@@ -57,6 +77,41 @@ fn default_node_dimensions() -> Dimensions {
     Dimensions::new(150.0, 50.0)
 }
 
+impl LayoutDirection {
+    /// Whether ranks advance along the y axis.
+    pub fn is_vertical(&self) -> bool {
+        matches!(
+            self,
+            LayoutDirection::TopToBottom | LayoutDirection::BottomToTop
+        )
+    }
+
+    /// Default sides for edges without handles, as `(source, target)`.
+    pub fn default_sides(&self) -> (HandleSide, HandleSide) {
+        match self {
+            LayoutDirection::TopToBottom => (HandleSide::Bottom, HandleSide::Top),
+            LayoutDirection::BottomToTop => (HandleSide::Top, HandleSide::Bottom),
+            LayoutDirection::LeftToRight => (HandleSide::Right, HandleSide::Left),
+            LayoutDirection::RightToLeft => (HandleSide::Left, HandleSide::Right),
+        }
+    }
+}
+
+/// Laid-out endpoints of an edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeAnchor {
+    pub edge_id: EdgeId,
+    /// Source attachment point.
+    pub source: Position,
+    pub source_side: HandleSide,
+    /// Target attachment point.
+    pub target: Position,
+    pub target_side: HandleSide,
+    /// The edge points back to an earlier rank. Without handles it leaves
+    /// and enters on the opposite sides to a forward edge.
+    pub back_edge: bool,
+}
+
 impl Default for LayoutConfig {
     fn default() -> Self {
         Self {
@@ -65,6 +120,7 @@ impl Default for LayoutConfig {
             rank_sep: default_rank_sep(),
             margin: default_margin(),
             default_node_dimensions: default_node_dimensions(),
+            cycle_handling: CycleHandling::default(),
         }
     }
 }
@@ -81,68 +137,31 @@ impl HierarchicalLayout {
     }
 
     /// Apply layout to nodes.
-    pub fn apply<T: Clone, E>(&self, nodes: &mut [Node<T>], edges: &[Edge<E>]) {
+    ///
+    /// Nodes with handles keep their port constraints: children are ordered
+    /// within a rank by the side and order of the handle they hang off, and
+    /// the returned anchors sit at the exact handle offsets.
+    pub fn apply<T: Clone, E>(&self, nodes: &mut [Node<T>], edges: &[Edge<E>]) -> Vec<EdgeAnchor> {
         if nodes.is_empty() {
-            return;
+            return Vec::new();
         }
+        trace_span!("layout", "layout", nodes = nodes.len(), edges = edges.len());
 
-        // Build adjacency list
-        let mut children: std::collections::HashMap<NodeId, Vec<NodeId>> =
-            std::collections::HashMap::new();
-        let mut has_parent: std::collections::HashSet<NodeId> = std::collections::HashSet::new();
-
-        for edge in edges {
-            children
-                .entry(edge.source.clone())
-                .or_default()
-                .push(edge.target.clone());
-            has_parent.insert(edge.target.clone());
-        }
+        let ranks = match self.config.cycle_handling {
+            CycleHandling::ReverseBackEdges => longest_path_ranks(nodes, edges),
+            CycleHandling::BreadthFirst => breadth_first_ranks(nodes, edges),
+        };
 
-        // Find root nodes (no incoming edges)
-        let roots: Vec<NodeId> = nodes
-            .iter()
-            .filter(|n| !has_parent.contains(&n.id))
-            .map(|n| n.id.clone())
-            .collect();
-
-        // Assign ranks (BFS from roots)
-        let mut ranks: std::collections::HashMap<NodeId, usize> = std::collections::HashMap::new();
-        let mut queue: std::collections::VecDeque<(NodeId, usize)> =
-            std::collections::VecDeque::new();
-
-        for root in &roots {
-            queue.push_back((root.clone(), 0));
-        }
-
-        // Handle disconnected nodes
+        // Group nodes by rank, in node order
+        let mut rank_groups: std::collections::BTreeMap<usize, Vec<NodeId>> =
+            std::collections::BTreeMap::new();
         for node in nodes.iter() {
-            if !roots.contains(&node.id) && !has_parent.contains(&node.id) {
-                queue.push_back((node.id.clone(), 0));
-            }
-        }
-
-        while let Some((node_id, rank)) = queue.pop_front() {
-            if ranks.contains_key(&node_id) {
-                continue;
-            }
-            ranks.insert(node_id.clone(), rank);
-
-            if let Some(child_ids) = children.get(&node_id) {
-                for child_id in child_ids {
-                    if !ranks.contains_key(child_id) {
-                        queue.push_back((child_id.clone(), rank + 1));
-                    }
-                }
+            if let Some(rank) = ranks.get(&node.id) {
+                rank_groups.entry(*rank).or_default().push(node.id.clone());
             }
         }
 
-        // Group nodes by rank
-        let mut rank_groups: std::collections::BTreeMap<usize, Vec<NodeId>> =
-            std::collections::BTreeMap::new();
-        for (node_id, rank) in &ranks {
-            rank_groups.entry(*rank).or_default().push(node_id.clone());
-        }
+        self.order_ranks(&mut rank_groups, nodes, edges);
 
         // Position nodes
         let node_width = self.config.default_node_dimensions.width;
@@ -178,7 +197,267 @@ impl HierarchicalLayout {
                 }
             }
         }
+
+        self.edge_anchors(nodes, edges, &ranks)
+    }
+
+    /// Crossing minimization: order each rank by the barycenter of its
+    /// parents, offset by the position of the source handle on the parent.
+    fn order_ranks<T, E>(
+        &self,
+        rank_groups: &mut std::collections::BTreeMap<usize, Vec<NodeId>>,
+        nodes: &[Node<T>],
+        edges: &[Edge<E>],
+    ) {
+        let by_id: std::collections::HashMap<&str, &Node<T>> =
+            nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+        let ranks: Vec<usize> = rank_groups.keys().copied().collect();
+
+        for pair in ranks.windows(2) {
+            let previous: std::collections::HashMap<&str, usize> = rank_groups[&pair[0]]
+                .iter()
+                .enumerate()
+                .map(|(i, id)| (id.as_str(), i))
+                .collect();
+
+            let group = &rank_groups[&pair[1]];
+            let mut keyed: Vec<(f64, NodeId)> = group
+                .iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    let positions: Vec<f64> = edges
+                        .iter()
+                        .filter(|e| &e.target == id)
+                        .filter_map(|e| {
+                            let index = previous.get(e.source.as_str())?;
+                            let source = by_id.get(e.source.as_str())?;
+                            Some(*index as f64 + self.port_fraction(source, e.source_handle.as_deref()))
+                        })
+                        .collect();
+                    let key = if positions.is_empty() {
+                        i as f64 + 0.5
+                    } else {
+                        positions.iter().sum::<f64>() / positions.len() as f64
+                    };
+                    (key, id.clone())
+                })
+                .collect();
+
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            rank_groups.insert(pair[1], keyed.into_iter().map(|(_, id)| id).collect());
+        }
+    }
+
+    /// Position of a source handle across the rank axis, from 0.0 (first
+    /// within the rank) to 1.0 (last). Nodes without the handle use the center.
+    fn port_fraction<T>(&self, node: &Node<T>, handle: Option<&str>) -> f64 {
+        let Some(handle) = handle.and_then(|h| node.handle(h)) else {
+            return 0.5;
+        };
+        let vertical = self.config.direction.is_vertical();
+        match handle.side {
+            HandleSide::Left if vertical => 0.0,
+            HandleSide::Right if vertical => 1.0,
+            HandleSide::Top if !vertical => 0.0,
+            HandleSide::Bottom if !vertical => 1.0,
+            _ => node.handle_offset(handle),
+        }
+    }
+
+    /// Attachment points for every edge whose endpoints were laid out.
+    /// Self-loops leave and re-enter the same side, across from the rank
+    /// axis.
+    fn edge_anchors<T, E>(
+        &self,
+        nodes: &[Node<T>],
+        edges: &[Edge<E>],
+        ranks: &HashMap<NodeId, usize>,
+    ) -> Vec<EdgeAnchor> {
+        let (default_source, default_target) = self.config.direction.default_sides();
+        let loop_side = if self.config.direction.is_vertical() {
+            HandleSide::Right
+        } else {
+            HandleSide::Bottom
+        };
+        let anchor = |node: &Node<T>, handle: Option<&str>, side, offset| {
+            match handle.and_then(|h| node.handle(h)) {
+                Some(h) => Some((node.side_anchor(h.side, node.handle_offset(h))?, h.side)),
+                None => Some((node.side_anchor(side, offset)?, side)),
+            }
+        };
+
+        edges
+            .iter()
+            .filter_map(|edge| {
+                let source = nodes.iter().find(|n| n.id == edge.source)?;
+                let target = nodes.iter().find(|n| n.id == edge.target)?;
+                let back_edge = ranks.get(&edge.target) < ranks.get(&edge.source);
+                let ((source_side, source_offset), (target_side, target_offset)) =
+                    if edge.source == edge.target {
+                        ((loop_side, 0.3), (loop_side, 0.7))
+                    } else if back_edge {
+                        ((default_target, 0.5), (default_source, 0.5))
+                    } else {
+                        ((default_source, 0.5), (default_target, 0.5))
+                    };
+                let (source_point, source_side) = anchor(
+                    source,
+                    edge.source_handle.as_deref(),
+                    source_side,
+                    source_offset,
+                )?;
+                let (target_point, target_side) = anchor(
+                    target,
+                    edge.target_handle.as_deref(),
+                    target_side,
+                    target_offset,
+                )?;
+                Some(EdgeAnchor {
+                    edge_id: edge.id.clone(),
+                    source: source_point,
+                    source_side,
+                    target: target_point,
+                    target_side,
+                    back_edge,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Edges between laid-out nodes as index pairs, without self-loops.
+fn index_edges<T, E>(nodes: &[Node<T>], edges: &[Edge<E>]) -> Vec<(usize, usize)> {
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), i))
+        .collect();
+    edges
+        .iter()
+        .filter_map(|e| {
+            Some((
+                *index.get(e.source.as_str())?,
+                *index.get(e.target.as_str())?,
+            ))
+        })
+        .filter(|(s, t)| s != t)
+        .collect()
+}
+
+/// Edges that close a cycle, found by depth-first search from the roots
+/// and then from any node still unvisited, in node order.
+fn back_edges(count: usize, edges: &[(usize, usize)]) -> HashSet<usize> {
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut has_parent = vec![false; count];
+    for (i, (s, t)) in edges.iter().enumerate() {
+        out[*s].push(i);
+        has_parent[*t] = true;
+    }
+
+    // 0 = unvisited, 1 = on the stack, 2 = done.
+    let mut state = vec![0u8; count];
+    let mut back = HashSet::new();
+    let starts = (0..count)
+        .filter(|&n| !has_parent[n])
+        .chain((0..count).filter(|&n| has_parent[n]));
+    for start in starts {
+        if state[start] != 0 {
+            continue;
+        }
+        state[start] = 1;
+        let mut stack = vec![(start, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let Some(&edge) = out[*node].get(*next) else {
+                state[*node] = 2;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            let target = edges[edge].1;
+            match state[target] {
+                0 => {
+                    state[target] = 1;
+                    stack.push((target, 0));
+                }
+                1 => {
+                    back.insert(edge);
+                }
+                _ => {}
+            }
+        }
+    }
+    back
+}
+
+/// Longest-path ranks with back edges reversed, so the graph is acyclic.
+fn longest_path_ranks<T, E>(nodes: &[Node<T>], edges: &[Edge<E>]) -> HashMap<NodeId, usize> {
+    let edges = index_edges(nodes, edges);
+    let back = back_edges(nodes.len(), &edges);
+    let mut out: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    let mut in_degree = vec![0usize; nodes.len()];
+    for (i, &(s, t)) in edges.iter().enumerate() {
+        let (s, t) = if back.contains(&i) { (t, s) } else { (s, t) };
+        out[s].push(t);
+        in_degree[t] += 1;
+    }
+
+    let mut rank = vec![0usize; nodes.len()];
+    let mut ready: VecDeque<usize> = (0..nodes.len()).filter(|&n| in_degree[n] == 0).collect();
+    while let Some(node) = ready.pop_front() {
+        for &target in &out[node] {
+            rank[target] = rank[target].max(rank[node] + 1);
+            in_degree[target] -= 1;
+            if in_degree[target] == 0 {
+                ready.push_back(target);
+            }
+        }
     }
+
+    nodes
+        .iter()
+        .zip(rank)
+        .map(|(node, rank)| (node.id.clone(), rank))
+        .collect()
+}
+
+/// Breadth-first distance from the roots. Nodes only reachable through a
+/// cycle start a rank-0 walk of their own.
+fn breadth_first_ranks<T, E>(nodes: &[Node<T>], edges: &[Edge<E>]) -> HashMap<NodeId, usize> {
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut has_parent: HashSet<&str> = HashSet::new();
+    for edge in edges {
+        children
+            .entry(edge.source.as_str())
+            .or_default()
+            .push(edge.target.as_str());
+        has_parent.insert(edge.target.as_str());
+    }
+
+    let mut ranks: HashMap<NodeId, usize> = HashMap::new();
+    let mut queue: VecDeque<(&str, usize)> = nodes
+        .iter()
+        .filter(|n| !has_parent.contains(n.id.as_str()))
+        .map(|n| (n.id.as_str(), 0))
+        .collect();
+    let mut unvisited = nodes.iter().map(|n| n.id.as_str());
+    loop {
+        while let Some((node_id, rank)) = queue.pop_front() {
+            if ranks.contains_key(node_id) {
+                continue;
+            }
+            ranks.insert(node_id.to_string(), rank);
+            for child_id in children.get(node_id).into_iter().flatten() {
+                if !ranks.contains_key(*child_id) {
+                    queue.push_back((child_id, rank + 1));
+                }
+            }
+        }
+        match unvisited.find(|id| !ranks.contains_key(*id)) {
+            Some(id) => queue.push_back((id, 0)),
+            None => break,
+        }
+    }
+    ranks
 }
 
 #[cfg(test)]
@@ -208,4 +487,174 @@ mod tests {
 
         assert!(n2.position.y > n1.position.y);
     }
+
+    #[test]
+    fn test_port_constraints_order_children() {
+        // The success target is declared first, so without port constraints
+        // it would sit left of the error target and the edges would cross.
+        let mut nodes: Vec<Node<()>> = vec![
+            Node::new("decision", NodeType::Default, Position::zero())
+                .with_handle("success", HandleSide::Right)
+                .with_handle("error", HandleSide::Bottom),
+            Node::new("on_success", NodeType::Default, Position::zero()),
+            Node::new("on_error", NodeType::Default, Position::zero()),
+            Node::new("other", NodeType::Default, Position::zero()),
+        ];
+        let mut success: Edge<()> = Edge::new("s", "decision", "on_success");
+        success.source_handle = Some("success".to_string());
+        let mut error: Edge<()> = Edge::new("e", "decision", "on_error");
+        error.source_handle = Some("error".to_string());
+        let edges = vec![success, error, Edge::new("o", "decision", "other")];
+
+        let layout = HierarchicalLayout::new(LayoutConfig::default());
+        let anchors = layout.apply(&mut nodes, &edges);
+
+        let node = |id: &str| nodes.iter().find(|n| n.id == id).unwrap();
+        let decision = node("decision");
+        assert!(node("on_success").position.x > node("on_error").position.x);
+
+        let dims = LayoutConfig::default().default_node_dimensions;
+        let anchor = |id: &str| anchors.iter().find(|a| a.edge_id == id).unwrap();
+
+        let s = anchor("s");
+        assert_eq!(s.source_side, HandleSide::Right);
+        assert_eq!(
+            s.source,
+            Position::new(
+                decision.position.x + dims.width,
+                decision.position.y + dims.height / 2.0
+            )
+        );
+        assert_eq!(s.target_side, HandleSide::Top);
+
+        let e = anchor("e");
+        assert_eq!(e.source_side, HandleSide::Bottom);
+        assert_eq!(
+            e.source,
+            Position::new(
+                decision.position.x + dims.width / 2.0,
+                decision.position.y + dims.height
+            )
+        );
+    }
+
+    fn layout(ids: &[&str], pairs: &[(&str, &str)]) -> (Vec<Node<()>>, Vec<EdgeAnchor>) {
+        let mut nodes: Vec<Node<()>> = ids
+            .iter()
+            .map(|id| Node::new(*id, NodeType::Default, Position::zero()))
+            .collect();
+        let edges: Vec<Edge<()>> = pairs
+            .iter()
+            .map(|(s, t)| Edge::new(format!("{}-{}", s, t), *s, *t))
+            .collect();
+        let anchors = HierarchicalLayout::new(LayoutConfig::default()).apply(&mut nodes, &edges);
+        (nodes, anchors)
+    }
+
+    fn assert_no_overlap(nodes: &[Node<()>]) {
+        let rect = |n: &Node<()>| {
+            let d = n.dimensions.unwrap();
+            (
+                n.position.x,
+                n.position.y,
+                n.position.x + d.width,
+                n.position.y + d.height,
+            )
+        };
+        for (i, a) in nodes.iter().enumerate() {
+            for b in &nodes[i + 1..] {
+                let (ax0, ay0, ax1, ay1) = rect(a);
+                let (bx0, by0, bx1, by1) = rect(b);
+                let overlap = ax0 < bx1 && bx0 < ax1 && ay0 < by1 && by0 < ay1;
+                assert!(!overlap, "{} overlaps {}", a.id, b.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_three_node_cycle() {
+        let (nodes, anchors) = layout(
+            &["dashboard", "list", "detail"],
+            &[
+                ("dashboard", "list"),
+                ("list", "detail"),
+                ("detail", "dashboard"),
+            ],
+        );
+        assert_no_overlap(&nodes);
+        let y = |id: &str| nodes.iter().find(|n| n.id == id).unwrap().position.y;
+        assert!(y("dashboard") < y("list") && y("list") < y("detail"));
+
+        let back = anchors
+            .iter()
+            .find(|a| a.edge_id == "detail-dashboard")
+            .unwrap();
+        assert!(back.back_edge);
+        assert_eq!(
+            (back.source_side, back.target_side),
+            (HandleSide::Top, HandleSide::Bottom)
+        );
+        assert_eq!(anchors.iter().filter(|a| a.back_edge).count(), 1);
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let (nodes, anchors) = layout(&["a", "b"], &[("a", "a"), ("a", "b")]);
+        assert_no_overlap(&nodes);
+        let a = &nodes[0];
+        assert!(nodes[1].position.y > a.position.y);
+
+        let dims = LayoutConfig::default().default_node_dimensions;
+        let anchor = anchors.iter().find(|x| x.edge_id == "a-a").unwrap();
+        assert!(!anchor.back_edge);
+        assert_eq!(
+            (anchor.source_side, anchor.target_side),
+            (HandleSide::Right, HandleSide::Right)
+        );
+        assert_eq!(anchor.source.x, a.position.x + dims.width);
+        assert!(anchor.source.y < anchor.target.y);
+    }
+
+    #[test]
+    fn test_two_independent_cycles() {
+        let ids = ["r", "a1", "a2", "a3", "b1", "b2", "b3", "b4", "leaf"];
+        let pairs = [
+            ("r", "a1"),
+            ("a1", "a2"),
+            ("a2", "a3"),
+            ("a3", "a1"),
+            ("r", "b1"),
+            ("b1", "b2"),
+            ("b2", "b3"),
+            ("b3", "b4"),
+            ("b4", "b2"),
+            ("b3", "leaf"),
+            ("r", "leaf"),
+        ];
+        let (nodes, anchors) = layout(&ids, &pairs);
+        assert_no_overlap(&nodes);
+        let back: Vec<_> = anchors
+            .iter()
+            .filter(|a| a.back_edge)
+            .map(|a| a.edge_id.as_str())
+            .collect();
+        assert_eq!(back, ["a3-a1", "b4-b2"]);
+        // Longest path puts the leaf below b3, not next to the root.
+        let y = |id: &str| nodes.iter().find(|n| n.id == id).unwrap().position.y;
+        assert!(y("leaf") > y("b3"));
+
+        // Breadth-first ranking still lays out without overlaps.
+        let mut nodes = nodes;
+        let edges: Vec<Edge<()>> = pairs
+            .iter()
+            .map(|(s, t)| Edge::new(format!("{}-{}", s, t), *s, *t))
+            .collect();
+        let config = LayoutConfig {
+            cycle_handling: CycleHandling::BreadthFirst,
+            direction: LayoutDirection::LeftToRight,
+            ..Default::default()
+        };
+        HierarchicalLayout::new(config).apply(&mut nodes, &edges);
+        assert_no_overlap(&nodes);
+    }
 }
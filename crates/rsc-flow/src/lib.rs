@@ -27,19 +27,44 @@
 //! ```
 
 mod canvas;
+mod connection;
+mod culling;
 mod edge;
 mod error;
+mod focus;
+mod interaction;
+mod keyboard;
+mod lasso;
+mod layer;
 mod layout;
+mod minimap;
 mod node;
 mod position;
+mod ruler;
+mod snap;
+mod spatial;
+mod theme;
+mod trace;
 mod viewport;
 
 pub use canvas::*;
+pub use connection::*;
+pub use culling::*;
 pub use edge::*;
 pub use error::*;
+pub use focus::*;
+pub use interaction::*;
+pub use keyboard::*;
+pub use lasso::*;
+pub use layer::*;
 pub use layout::*;
+pub use minimap::*;
 pub use node::*;
 pub use position::*;
+pub use ruler::*;
+pub use snap::*;
+pub use spatial::*;
+pub use theme::*;
 pub use viewport::*;
 
 /// Re-export common types
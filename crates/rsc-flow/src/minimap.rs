@@ -0,0 +1,341 @@
+//! Minimap geometry.
+//!
+//! A [`Minimap`] scales the node bounds into a fixed-size box, keeping the
+//! aspect ratio and centering the content (letterboxing). It yields the
+//! node and viewport rectangles to draw, in minimap pixels, and maps
+//! clicks back to the canvas. Moving one node only rescales that node
+//! unless it changes the content bounds.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::canvas::FlowCanvas;
+use crate::lasso::DEFAULT_NODE_SIZE;
+use crate::node::NodeId;
+use crate::position::{Dimensions, Position, Rect};
+use crate::viewport::{Viewport, ViewportTransform};
+
+/// Default gap between the content and the minimap edge, in pixels.
+pub const DEFAULT_MINIMAP_PADDING: f64 = 8.0;
+
+/// Mapping from canvas to minimap coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mapping {
+    /// Top-left of the content bounds, in canvas units.
+    origin: Position,
+    scale: f64,
+    /// Top-left of the scaled content, in minimap pixels.
+    offset: Position,
+}
+
+impl Mapping {
+    fn project(self, point: Position) -> Position {
+        Position::new(
+            (point.x - self.origin.x) * self.scale + self.offset.x,
+            (point.y - self.origin.y) * self.scale + self.offset.y,
+        )
+    }
+
+    fn unproject(self, point: Position) -> Position {
+        Position::new(
+            (point.x - self.offset.x) / self.scale + self.origin.x,
+            (point.y - self.offset.y) / self.scale + self.origin.y,
+        )
+    }
+
+    fn rect(self, rect: &Rect) -> Rect {
+        Rect {
+            position: self.project(rect.position),
+            dimensions: Dimensions::new(
+                rect.dimensions.width * self.scale,
+                rect.dimensions.height * self.scale,
+            ),
+        }
+    }
+}
+
+/// What to draw, in minimap pixels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MinimapView {
+    /// Scaled node rectangles, in node order.
+    pub nodes: Vec<(NodeId, Rect)>,
+    /// Visible part of the canvas. May reach past the minimap edges.
+    pub viewport: Option<Rect>,
+}
+
+/// Minimap of a set of node rectangles.
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    /// Minimap size, in pixels.
+    pub size: Dimensions,
+    /// Gap between the content and the minimap edge, in pixels.
+    pub padding: f64,
+    /// Node rectangles, in canvas units.
+    rects: IndexMap<NodeId, Rect>,
+    /// Node rectangles, in minimap pixels.
+    scaled: IndexMap<NodeId, Rect>,
+    bounds: Option<Rect>,
+    mapping: Option<Mapping>,
+    /// Nodes rescaled by the last update.
+    pub last_rescaled: usize,
+}
+
+impl Minimap {
+    pub fn new(size: Dimensions) -> Self {
+        Self {
+            size,
+            padding: DEFAULT_MINIMAP_PADDING,
+            rects: IndexMap::new(),
+            scaled: IndexMap::new(),
+            bounds: None,
+            mapping: None,
+            last_rescaled: 0,
+        }
+    }
+
+    pub fn with_padding(mut self, padding: f64) -> Self {
+        self.padding = padding;
+        self.rescale();
+        self
+    }
+
+    /// Replace all node rectangles.
+    pub fn set_nodes(&mut self, rects: impl IntoIterator<Item = (NodeId, Rect)>) {
+        self.rects = rects.into_iter().collect();
+        self.rescale();
+    }
+
+    /// Move or add one node. Only that node is rescaled unless the
+    /// content bounds change.
+    pub fn update_node(&mut self, id: &str, rect: Rect) {
+        let old = self.rects.insert(id.to_string(), rect);
+        let inside = |r: &Rect| {
+            self.bounds.is_some_and(|b| {
+                r.position.x > b.position.x
+                    && r.position.y > b.position.y
+                    && r.position.x + r.dimensions.width < b.position.x + b.dimensions.width
+                    && r.position.y + r.dimensions.height < b.position.y + b.dimensions.height
+            })
+        };
+        // Bounds can only shrink if the old rect touched them.
+        if old.is_some_and(|o| inside(&o))
+            && inside(&rect)
+            && let Some(mapping) = self.mapping
+        {
+            self.scaled.insert(id.to_string(), mapping.rect(&rect));
+            self.last_rescaled = 1;
+        } else {
+            self.rescale();
+        }
+    }
+
+    /// Remove a node.
+    pub fn remove_node(&mut self, id: &str) {
+        if self.rects.shift_remove(id).is_some() {
+            self.rescale();
+        }
+    }
+
+    fn rescale(&mut self) {
+        self.bounds = self.rects.values().copied().reduce(|a, b| a.union(&b));
+        self.mapping = self.bounds.map(|bounds| {
+            let available = Dimensions::new(
+                (self.size.width - self.padding * 2.0).max(0.0),
+                (self.size.height - self.padding * 2.0).max(0.0),
+            );
+            let fit = |available: f64, size: f64| {
+                if size > 0.0 {
+                    available / size
+                } else {
+                    f64::INFINITY
+                }
+            };
+            let scale = fit(available.width, bounds.dimensions.width)
+                .min(fit(available.height, bounds.dimensions.height));
+            // Content without extent, e.g. one zero-sized node.
+            let scale = if scale.is_finite() { scale } else { 1.0 };
+            Mapping {
+                origin: bounds.position,
+                scale,
+                offset: Position::new(
+                    self.padding + (available.width - bounds.dimensions.width * scale) / 2.0,
+                    self.padding + (available.height - bounds.dimensions.height * scale) / 2.0,
+                ),
+            }
+        });
+        self.scaled = match self.mapping {
+            Some(mapping) => self
+                .rects
+                .iter()
+                .map(|(id, rect)| (id.clone(), mapping.rect(rect)))
+                .collect(),
+            None => IndexMap::new(),
+        };
+        self.last_rescaled = self.scaled.len();
+    }
+
+    /// Scaled node rectangles.
+    pub fn nodes(&self) -> &IndexMap<NodeId, Rect> {
+        &self.scaled
+    }
+
+    /// Minimap pixels per canvas unit; `None` without nodes.
+    pub fn scale(&self) -> Option<f64> {
+        self.mapping.map(|m| m.scale)
+    }
+
+    /// A canvas point in minimap pixels.
+    pub fn to_minimap(&self, point: Position) -> Option<Position> {
+        self.mapping.map(|m| m.project(point))
+    }
+
+    /// A minimap point in canvas units.
+    pub fn to_canvas(&self, point: Position) -> Option<Position> {
+        self.mapping.map(|m| m.unproject(point))
+    }
+
+    /// Visible part of a `canvas_size` canvas, in minimap pixels.
+    pub fn viewport_rect(&self, viewport: &Viewport, canvas_size: Dimensions) -> Option<Rect> {
+        let mapping = self.mapping?;
+        Some(mapping.rect(&viewport.visible_rect(canvas_size)))
+    }
+
+    /// Everything to draw. Empty without nodes.
+    pub fn view(&self, viewport: &Viewport, canvas_size: Dimensions) -> MinimapView {
+        MinimapView {
+            nodes: self
+                .scaled
+                .iter()
+                .map(|(id, rect)| (id.clone(), *rect))
+                .collect(),
+            viewport: self.viewport_rect(viewport, canvas_size),
+        }
+    }
+
+    /// Transform centering the main canvas on the point clicked in the
+    /// minimap, keeping the zoom. Unchanged without nodes.
+    pub fn pan_to(
+        &self,
+        minimap_click: Position,
+        viewport: &Viewport,
+        canvas_size: Dimensions,
+    ) -> ViewportTransform {
+        let Some(mapping) = self.mapping else {
+            return viewport.transform;
+        };
+        let target = mapping.unproject(minimap_click);
+        let zoom = viewport.transform.zoom;
+        ViewportTransform {
+            x: canvas_size.width / 2.0 - target.x * zoom,
+            y: canvas_size.height / 2.0 - target.y * zoom,
+            zoom,
+        }
+    }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Minimap of the current nodes. Nodes without dimensions count as the
+    /// default node size.
+    pub fn minimap(&self, size: Dimensions) -> Minimap {
+        let mut minimap = Minimap::new(size);
+        minimap.set_nodes(self.nodes.values().map(|node| {
+            let rect = Rect {
+                position: node.position,
+                dimensions: node.dimensions.unwrap_or(DEFAULT_NODE_SIZE),
+            };
+            (node.id.clone(), rect)
+        }));
+        minimap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Content 400x100 at (100, 100) in a 200x200 minimap without padding:
+    /// scale 0.5, letterboxed 75px from the top.
+    fn minimap() -> Minimap {
+        let mut minimap = Minimap::new(Dimensions::square(200.0)).with_padding(0.0);
+        minimap.set_nodes([
+            ("a".to_string(), Rect::new(100.0, 100.0, 100.0, 50.0)),
+            ("b".to_string(), Rect::new(400.0, 150.0, 100.0, 50.0)),
+            ("c".to_string(), Rect::new(250.0, 120.0, 20.0, 20.0)),
+        ]);
+        minimap
+    }
+
+    #[test]
+    fn test_mapping_both_directions() {
+        let minimap = minimap();
+        assert_eq!(minimap.scale(), Some(0.5));
+        assert_eq!(minimap.nodes()["a"], Rect::new(0.0, 75.0, 50.0, 25.0));
+        assert_eq!(minimap.nodes()["b"], Rect::new(150.0, 100.0, 50.0, 25.0));
+        assert_eq!(
+            minimap.to_minimap(Position::new(300.0, 150.0)),
+            Some(Position::new(100.0, 100.0))
+        );
+        assert_eq!(
+            minimap.to_canvas(Position::new(100.0, 100.0)),
+            Some(Position::new(300.0, 150.0))
+        );
+
+        // An 800x600 canvas at 2x zoom panned to show (50, 25)..(450, 325).
+        let mut viewport = Viewport {
+            transform: ViewportTransform {
+                x: -100.0,
+                y: -50.0,
+                zoom: 2.0,
+            },
+            ..Default::default()
+        };
+        let canvas = Dimensions::new(800.0, 600.0);
+        assert_eq!(
+            minimap.viewport_rect(&viewport, canvas),
+            Some(Rect::new(-25.0, 37.5, 200.0, 150.0))
+        );
+
+        // Clicking the middle of the minimap centers the content.
+        let transform = minimap.pan_to(Position::new(100.0, 100.0), &viewport, canvas);
+        viewport.transform = transform;
+        assert_eq!(transform.zoom, 2.0);
+        assert_eq!(
+            viewport.screen_to_canvas(Position::new(400.0, 300.0)),
+            Position::new(300.0, 150.0)
+        );
+    }
+
+    #[test]
+    fn test_incremental_update() {
+        let mut minimap = minimap();
+        minimap.update_node("c", Rect::new(260.0, 130.0, 20.0, 20.0));
+        assert_eq!(minimap.last_rescaled, 1);
+        assert_eq!(minimap.nodes()["c"], Rect::new(80.0, 90.0, 10.0, 10.0));
+
+        // Moving a node that spans the bounds rescales everything.
+        minimap.update_node("b", Rect::new(200.0, 150.0, 100.0, 50.0));
+        assert_eq!(minimap.last_rescaled, 3);
+        assert_eq!(minimap.scale(), Some(1.0));
+        assert_eq!(minimap.nodes(), minimap.clone().with_padding(0.0).nodes());
+    }
+
+    #[test]
+    fn test_empty_minimap() {
+        let mut minimap = Minimap::new(Dimensions::square(200.0));
+        let viewport = Viewport::default();
+        let canvas = Dimensions::new(800.0, 600.0);
+        let view = minimap.view(&viewport, canvas);
+        assert!(view.nodes.is_empty() && view.viewport.is_none());
+        assert_eq!(
+            minimap.pan_to(Position::new(10.0, 10.0), &viewport, canvas),
+            viewport.transform
+        );
+
+        // A single zero-sized node has no extent to scale.
+        minimap.set_nodes([("a".to_string(), Rect::new(5.0, 5.0, 0.0, 0.0))]);
+        assert_eq!(minimap.scale(), Some(1.0));
+        assert_eq!(minimap.nodes()["a"].position, Position::new(100.0, 100.0));
+        minimap.remove_node("a");
+        assert!(minimap.nodes().is_empty());
+    }
+}
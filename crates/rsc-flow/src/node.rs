@@ -45,6 +45,9 @@ pub struct Node<T = ()> {
     /// Inline styles.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub style: IndexMap<String, String>,
+    /// Named connection handles with fixed side and order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handles: Vec<Handle>,
 }
 
 fn default_true() -> bool {
@@ -67,6 +70,7 @@ impl<T> Node<T> {
             z_index: 0,
             class_names: Vec::new(),
             style: IndexMap::new(),
+            handles: Vec::new(),
         }
     }
 
@@ -93,6 +97,17 @@ impl<T> Node<T> {
         self
     }
 
+    /// Add a handle after any existing handles on the same side.
+    pub fn with_handle(mut self, id: impl Into<String>, side: HandleSide) -> Self {
+        let order = self.handles.iter().filter(|h| h.side == side).count();
+        self.handles.push(Handle {
+            id: id.into(),
+            side,
+            order,
+        });
+        self
+    }
+
     /// Get the bounding rect.
     pub fn bounds(&self) -> Option<crate::position::Rect> {
         self.dimensions.map(|d| crate::position::Rect {
@@ -100,6 +115,69 @@ impl<T> Node<T> {
             dimensions: d,
         })
     }
+
+    /// Find a handle by id.
+    pub fn handle(&self, id: &str) -> Option<&Handle> {
+        self.handles.iter().find(|h| h.id == id)
+    }
+
+    /// Offset of a handle along its side (0.0 to 1.0), spacing handles on
+    /// the same side evenly in their fixed order.
+    pub fn handle_offset(&self, handle: &Handle) -> f64 {
+        let mut on_side: Vec<&Handle> = self
+            .handles
+            .iter()
+            .filter(|h| h.side == handle.side)
+            .collect();
+        on_side.sort_by_key(|h| h.order);
+        let index = on_side
+            .iter()
+            .position(|h| h.id == handle.id)
+            .unwrap_or(0);
+        (index + 1) as f64 / (on_side.len() + 1) as f64
+    }
+
+    /// Point on a side of the node, `offset` (0.0 to 1.0) along it.
+    ///
+    /// Returns `None` if the node has no dimensions.
+    pub fn side_anchor(&self, side: HandleSide, offset: f64) -> Option<Position> {
+        let d = self.dimensions?;
+        let Position { x, y } = self.position;
+        Some(match side {
+            HandleSide::Top => Position::new(x + d.width * offset, y),
+            HandleSide::Bottom => Position::new(x + d.width * offset, y + d.height),
+            HandleSide::Left => Position::new(x, y + d.height * offset),
+            HandleSide::Right => Position::new(x + d.width, y + d.height * offset),
+        })
+    }
+
+    /// Anchor point of a named handle in canvas coordinates.
+    pub fn handle_anchor(&self, id: &str) -> Option<Position> {
+        let handle = self.handle(id)?;
+        self.side_anchor(handle.side, self.handle_offset(handle))
+    }
+}
+
+/// Side of a node a handle sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HandleSide {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Named connection point with a fixed side and order within that side.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handle {
+    /// Handle identifier, referenced by `Edge::source_handle`/`target_handle`.
+    pub id: String,
+    /// Side the handle is fixed to.
+    pub side: HandleSide,
+    /// Order within the side (top-to-bottom or left-to-right).
+    #[serde(default)]
+    pub order: usize,
 }
 
 impl<T: Default> Default for Node<T> {
@@ -109,10 +187,11 @@ impl<T: Default> Default for Node<T> {
 }
 
 /// Node type determines rendering behavior.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum NodeType {
     /// Default node rendering.
+    #[default]
     Default,
     /// Input node (source).
     Input,
@@ -124,12 +203,6 @@ pub enum NodeType {
     Custom(String),
 }
 
-impl Default for NodeType {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
 /// Common node data structure.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NodeData {
@@ -193,4 +266,20 @@ mod tests {
         assert_eq!(node.id, "test");
         assert_eq!(node.data.as_ref().unwrap().label, "Test Node");
     }
+
+    #[test]
+    fn test_handle_anchors() {
+        let node: Node<()> = Node::new("n", NodeType::Default, Position::new(10.0, 20.0))
+            .with_dimensions(Dimensions::new(100.0, 60.0))
+            .with_handle("a", HandleSide::Bottom)
+            .with_handle("b", HandleSide::Bottom)
+            .with_handle("c", HandleSide::Right);
+
+        assert_eq!(node.handle("b").unwrap().order, 1);
+        let a = node.handle_anchor("a").unwrap();
+        assert!((a.x - (10.0 + 100.0 / 3.0)).abs() < 1e-9);
+        assert_eq!(a.y, 80.0);
+        assert_eq!(node.handle_anchor("c"), Some(Position::new(110.0, 50.0)));
+        assert_eq!(node.handle_anchor("missing"), None);
+    }
 }
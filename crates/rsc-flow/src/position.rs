@@ -30,6 +30,17 @@ impl Position {
             y: self.y + (other.y - self.y) * t,
         }
     }
+
+    /// Point on the segment from `a` to `b` closest to this one.
+    pub fn closest_on_segment(&self, a: &Position, b: &Position) -> Position {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let length_sq = dx * dx + dy * dy;
+        if length_sq == 0.0 {
+            return *a;
+        }
+        let t = ((self.x - a.x) * dx + (self.y - a.y) * dy) / length_sq;
+        a.lerp(b, t.clamp(0.0, 1.0))
+    }
 }
 
 /// Dimensions (width/height).
@@ -88,6 +99,17 @@ impl Rect {
             && point.y <= self.position.y + self.dimensions.height
     }
 
+    /// Smallest rect containing both.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let left = self.position.x.min(other.position.x);
+        let top = self.position.y.min(other.position.y);
+        let right = (self.position.x + self.dimensions.width)
+            .max(other.position.x + other.dimensions.width);
+        let bottom = (self.position.y + self.dimensions.height)
+            .max(other.position.y + other.dimensions.height);
+        Rect::new(left, top, right - left, bottom - top)
+    }
+
     pub fn intersects(&self, other: &Rect) -> bool {
         self.position.x < other.position.x + other.dimensions.width
             && self.position.x + self.dimensions.width > other.position.x
@@ -107,6 +129,17 @@ mod tests {
         assert!((a.distance_to(&b) - 5.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_closest_on_segment() {
+        let a = Position::new(0.0, 0.0);
+        let b = Position::new(100.0, 0.0);
+        let closest = |x, y| Position::new(x, y).closest_on_segment(&a, &b);
+        assert_eq!(closest(40.0, 25.0), Position::new(40.0, 0.0));
+        assert_eq!(closest(-30.0, 5.0), a);
+        assert_eq!(closest(130.0, -5.0), b);
+        assert_eq!(Position::new(5.0, 5.0).closest_on_segment(&a, &a), a);
+    }
+
     #[test]
     fn test_rect_contains() {
         let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
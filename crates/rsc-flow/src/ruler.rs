@@ -0,0 +1,320 @@
+//! Canvas rulers and measurement tool.
+//!
+//! Both work in world (canvas) coordinates and go through the viewport
+//! transform, so they stay correct under zoom and pan.
+
+use crate::canvas::FlowCanvas;
+use crate::position::{Position, Rect};
+use crate::viewport::Viewport;
+
+/// Ruler axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Top ruler, measuring x.
+    Horizontal,
+    /// Left ruler, measuring y.
+    Vertical,
+}
+
+/// A ruler tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    /// Position in world units.
+    pub world: f64,
+    /// Position along the ruler in screen pixels.
+    pub screen: f64,
+    pub major: bool,
+    /// Label for major ticks.
+    pub label: Option<String>,
+}
+
+/// Smallest "nice" step (1, 2 or 5 × 10^n) that is at least `raw`.
+pub fn nice_step(raw: f64) -> f64 {
+    if !raw.is_finite() || raw <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= raw * (1.0 - 1e-9))
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Ticks for a ruler `length_px` long.
+///
+/// Minor ticks are at least `target_px_spacing` apart on screen; major
+/// ticks fall on the next nice step at five times that and carry labels.
+pub fn ruler_ticks(
+    viewport: &Viewport,
+    axis: Axis,
+    length_px: f64,
+    target_px_spacing: f64,
+) -> Vec<Tick> {
+    let zoom = viewport.transform.zoom;
+    if zoom <= 0.0 || length_px <= 0.0 || target_px_spacing <= 0.0 {
+        return Vec::new();
+    }
+    let offset = match axis {
+        Axis::Horizontal => viewport.transform.x,
+        Axis::Vertical => viewport.transform.y,
+    };
+
+    let step = nice_step(target_px_spacing / zoom);
+    let major_step = nice_step(step * 5.0);
+    let major_every = (major_step / step).round() as i64;
+    let decimals = (-major_step.log10().floor()).max(0.0) as usize;
+
+    let first = ((0.0 - offset) / zoom / step).ceil() as i64;
+    let last = ((length_px - offset) / zoom / step).floor() as i64;
+
+    (first..=last)
+        .map(|i| {
+            let world = i as f64 * step;
+            let major = i.rem_euclid(major_every) == 0;
+            Tick {
+                world,
+                screen: world * zoom + offset,
+                major,
+                label: major.then(|| format!("{:.*}", decimals, world)),
+            }
+        })
+        .collect()
+}
+
+/// Result of a measurement, in world units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub start: Position,
+    pub end: Position,
+    pub dx: f64,
+    pub dy: f64,
+    pub distance: f64,
+}
+
+impl Measurement {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self {
+            start,
+            end,
+            dx: end.x - start.x,
+            dy: end.y - start.y,
+            distance: start.distance_to(&end),
+        }
+    }
+}
+
+/// Measurement tool state.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MeasureState {
+    #[default]
+    Idle,
+    /// First point placed.
+    Anchored(Position),
+    /// Both points placed.
+    Done(Measurement),
+}
+
+/// Two-click measurement tool with snapping to node edges and centers.
+#[derive(Debug, Clone)]
+pub struct MeasureTool {
+    pub state: MeasureState,
+    /// Snap distance in screen pixels.
+    pub snap_radius_px: f64,
+}
+
+impl Default for MeasureTool {
+    fn default() -> Self {
+        Self::new(8.0)
+    }
+}
+
+impl MeasureTool {
+    pub fn new(snap_radius_px: f64) -> Self {
+        Self {
+            state: MeasureState::Idle,
+            snap_radius_px,
+        }
+    }
+
+    /// Place a point at a screen position.
+    ///
+    /// The first click anchors, the second completes the measurement and a
+    /// third starts a new one. Returns the snapped world point.
+    pub fn click<N, E>(&mut self, screen: Position, canvas: &FlowCanvas<N, E>) -> Position {
+        let point = self.snap(screen, canvas);
+        self.state = match self.state {
+            MeasureState::Anchored(start) => MeasureState::Done(Measurement::new(start, point)),
+            MeasureState::Idle | MeasureState::Done(_) => MeasureState::Anchored(point),
+        };
+        point
+    }
+
+    /// Live measurement while the second point is being placed.
+    pub fn preview<N, E>(
+        &self,
+        screen: Position,
+        canvas: &FlowCanvas<N, E>,
+    ) -> Option<Measurement> {
+        match self.state {
+            MeasureState::Anchored(start) => {
+                Some(Measurement::new(start, self.snap(screen, canvas)))
+            }
+            MeasureState::Done(m) => Some(m),
+            MeasureState::Idle => None,
+        }
+    }
+
+    /// Completed measurement, if any.
+    pub fn measurement(&self) -> Option<Measurement> {
+        match self.state {
+            MeasureState::Done(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = MeasureState::Idle;
+    }
+
+    /// World point for a screen position, snapped per axis to the nearest
+    /// edge or center of a node within the snap radius.
+    pub fn snap<N, E>(&self, screen: Position, canvas: &FlowCanvas<N, E>) -> Position {
+        let world = canvas.viewport.screen_to_canvas(screen);
+        let radius = self.snap_radius_px / canvas.viewport.transform.zoom;
+        let bounds: Vec<Rect> = canvas.nodes.values().filter_map(|n| n.bounds()).collect();
+        snap_point(world, &bounds, radius)
+    }
+}
+
+/// Snap a world point to the edges and centers of nearby rects.
+pub fn snap_point(point: Position, bounds: &[Rect], radius: f64) -> Position {
+    let near = bounds.iter().filter(|r| {
+        Rect::new(
+            r.position.x - radius,
+            r.position.y - radius,
+            r.dimensions.width + radius * 2.0,
+            r.dimensions.height + radius * 2.0,
+        )
+        .contains(&point)
+    });
+
+    let mut best_x: Option<f64> = None;
+    let mut best_y: Option<f64> = None;
+    for rect in near {
+        let center = rect.center();
+        let xs = [
+            rect.position.x,
+            center.x,
+            rect.position.x + rect.dimensions.width,
+        ];
+        let ys = [
+            rect.position.y,
+            center.y,
+            rect.position.y + rect.dimensions.height,
+        ];
+        for x in xs {
+            if (x - point.x).abs() <= radius
+                && best_x.is_none_or(|b| (x - point.x).abs() < (b - point.x).abs())
+            {
+                best_x = Some(x);
+            }
+        }
+        for y in ys {
+            if (y - point.y).abs() <= radius
+                && best_y.is_none_or(|b| (y - point.y).abs() < (b - point.y).abs())
+            {
+                best_y = Some(y);
+            }
+        }
+    }
+
+    Position::new(best_x.unwrap_or(point.x), best_y.unwrap_or(point.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Node, NodeType};
+    use crate::position::Dimensions;
+    use crate::viewport::ViewportTransform;
+
+    fn viewport(x: f64, zoom: f64) -> Viewport {
+        Viewport {
+            transform: ViewportTransform::new(x, 0.0, zoom),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tick_steps_across_zoom() {
+        let ticks = ruler_ticks(&viewport(0.0, 1.0), Axis::Horizontal, 200.0, 10.0);
+        assert_eq!(ticks[1].world - ticks[0].world, 10.0);
+        assert_eq!(ticks[5].label.as_deref(), Some("50"));
+        assert!(!ticks[4].major);
+
+        let zoomed_out = ruler_ticks(&viewport(0.0, 0.25), Axis::Horizontal, 200.0, 10.0);
+        assert_eq!(zoomed_out[1].world - zoomed_out[0].world, 50.0);
+        assert!(!zoomed_out[2].major);
+        assert_eq!(zoomed_out[10].label.as_deref(), Some("500"));
+
+        let zoomed_in = ruler_ticks(&viewport(0.0, 4.0), Axis::Vertical, 200.0, 10.0);
+        assert_eq!(zoomed_in[1].world - zoomed_in[0].world, 5.0);
+        assert_eq!(zoomed_in[1].screen, 20.0);
+
+        let fine = ruler_ticks(&viewport(0.0, 40.0), Axis::Horizontal, 200.0, 10.0);
+        assert!((fine[1].world - 0.5).abs() < 1e-9);
+        assert_eq!(fine[0].label.as_deref(), Some("0"));
+        assert_eq!(nice_step(0.3), 0.5);
+        assert_eq!(nice_step(120.0), 200.0);
+    }
+
+    #[test]
+    fn test_ticks_follow_pan() {
+        let ticks = ruler_ticks(&viewport(-35.0, 2.0), Axis::Horizontal, 100.0, 10.0);
+
+        // Visible world range is 17.5..67.5 with a step of 5.
+        assert_eq!(ticks[0].world, 20.0);
+        assert_eq!(ticks[0].screen, 5.0);
+        assert_eq!(ticks.last().unwrap().world, 65.0);
+        assert!(ticks.iter().all(|t| t.major == (t.world % 50.0 == 0.0)));
+    }
+
+    #[test]
+    fn test_measure_snaps_to_nodes() {
+        let mut canvas: FlowCanvas = FlowCanvas::new();
+        canvas.add_node(
+            Node::new("a", NodeType::Default, Position::new(100.0, 100.0))
+                .with_dimensions(Dimensions::new(100.0, 50.0)),
+        );
+        canvas.viewport = viewport(0.0, 2.0);
+        let mut tool = MeasureTool::new(8.0);
+
+        // Screen (203, 253) is world (101.5, 126.5): snaps to the left edge
+        // and vertical center.
+        let start = tool.click(Position::new(203.0, 253.0), &canvas);
+        assert_eq!(start, Position::new(100.0, 125.0));
+
+        // World (160, 300) is far from the node: no snap.
+        assert_eq!(
+            tool.preview(Position::new(320.0, 600.0), &canvas)
+                .unwrap()
+                .end,
+            Position::new(160.0, 300.0)
+        );
+
+        // 6px past the right edge on screen is 3 world units: still snaps.
+        tool.click(Position::new(406.0, 300.0), &canvas);
+        let m = tool.measurement().unwrap();
+        assert_eq!(m.end, Position::new(200.0, 150.0));
+        assert_eq!((m.dx, m.dy), (100.0, 25.0));
+        assert!((m.distance - 103.0776).abs() < 1e-3);
+
+        canvas.viewport = viewport(0.0, 0.5);
+        tool.click(Position::new(105.0, 63.0), &canvas);
+        assert_eq!(
+            tool.state,
+            MeasureState::Anchored(Position::new(200.0, 125.0))
+        );
+    }
+}
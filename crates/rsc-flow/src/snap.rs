@@ -0,0 +1,299 @@
+//! Snapping of dragged nodes.
+//!
+//! While a node is dragged, [`compute_snap`] aligns its edges or center
+//! with nearby nodes and returns the guide lines to draw; an axis without
+//! a guide is rounded to the snap grid. The canvas applies the result on
+//! drop.
+
+use serde::{Deserialize, Serialize};
+
+use crate::canvas::FlowCanvas;
+use crate::lasso::DEFAULT_NODE_SIZE;
+use crate::position::{Position, Rect};
+
+/// Default guide snapping distance, in screen pixels.
+pub const DEFAULT_SNAP_TOLERANCE: f64 = 6.0;
+
+/// Snapping configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapConfig {
+    /// Grid cell width and height node positions are rounded to.
+    pub grid: Option<(f64, f64)>,
+    /// Align with the edges and centers of other nodes.
+    pub guides: bool,
+    /// Largest distance a guide pulls the node. [`compute_snap`] takes it
+    /// in world units; the canvas passes screen pixels divided by zoom.
+    pub tolerance: f64,
+}
+
+impl Default for SnapConfig {
+    fn default() -> Self {
+        Self {
+            grid: None,
+            guides: true,
+            tolerance: DEFAULT_SNAP_TOLERANCE,
+        }
+    }
+}
+
+impl SnapConfig {
+    pub fn with_grid(mut self, width: f64, height: f64) -> Self {
+        self.grid = Some((width, height));
+        self
+    }
+
+    pub fn with_guides(mut self, guides: bool) -> Self {
+        self.guides = guides;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+/// Orientation of a guide line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideAxis {
+    /// A vertical line at x, from snapping horizontally.
+    Vertical,
+    /// A horizontal line at y, from snapping vertically.
+    Horizontal,
+}
+
+/// A guide line to draw while dragging, in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapGuide {
+    pub axis: GuideAxis,
+    /// x of a vertical guide, y of a horizontal one.
+    pub at: f64,
+    /// Extent along the line, covering the dragged node and every node
+    /// aligned with it.
+    pub from: f64,
+    pub to: f64,
+}
+
+/// Snapped position of the dragged node and the guides that caused it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapResult {
+    pub position: Position,
+    pub guides: Vec<SnapGuide>,
+}
+
+/// Left, center and right (or top, middle and bottom) of a rect.
+fn lines(rect: &Rect, axis: GuideAxis) -> [f64; 3] {
+    let (start, size) = match axis {
+        GuideAxis::Vertical => (rect.position.x, rect.dimensions.width),
+        GuideAxis::Horizontal => (rect.position.y, rect.dimensions.height),
+    };
+    [start, start + size / 2.0, start + size]
+}
+
+/// Extent of a rect across a guide of `axis`.
+fn span(rect: &Rect, axis: GuideAxis) -> (f64, f64) {
+    match axis {
+        GuideAxis::Vertical => (rect.position.y, rect.position.y + rect.dimensions.height),
+        GuideAxis::Horizontal => (rect.position.x, rect.position.x + rect.dimensions.width),
+    }
+}
+
+/// Closest alignment on one axis within `tolerance`: the offset to move
+/// by and the line aligned to. Ties keep the first candidate.
+fn closest(dragged: &Rect, others: &[Rect], axis: GuideAxis, tolerance: f64) -> Option<(f64, f64)> {
+    let mut best: Option<(f64, f64)> = None;
+    for other in others {
+        for target in lines(other, axis) {
+            for line in lines(dragged, axis) {
+                let offset = target - line;
+                let closer = best.is_none_or(|(b, _)| offset.abs() < b.abs());
+                if offset.abs() <= tolerance && closer {
+                    best = Some((offset, target));
+                }
+            }
+        }
+    }
+    best
+}
+
+fn round_to(value: f64, step: f64) -> f64 {
+    if step > 0.0 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+/// Snap `dragged` against `others`. Each axis snaps to the closest guide
+/// within the tolerance, or else to the grid.
+pub fn compute_snap(dragged: Rect, others: &[Rect], config: &SnapConfig) -> SnapResult {
+    let mut position = dragged.position;
+    let mut aligned = Vec::new();
+    for axis in [GuideAxis::Vertical, GuideAxis::Horizontal] {
+        let found = config
+            .guides
+            .then(|| closest(&dragged, others, axis, config.tolerance))
+            .flatten();
+        let coordinate = match axis {
+            GuideAxis::Vertical => &mut position.x,
+            GuideAxis::Horizontal => &mut position.y,
+        };
+        match (found, config.grid) {
+            (Some((offset, at)), _) => {
+                *coordinate += offset;
+                aligned.push((axis, at));
+            }
+            (None, Some((width, height))) => {
+                let step = match axis {
+                    GuideAxis::Vertical => width,
+                    GuideAxis::Horizontal => height,
+                };
+                *coordinate = round_to(*coordinate, step);
+            }
+            (None, None) => {}
+        }
+    }
+
+    let snapped = Rect {
+        position,
+        dimensions: dragged.dimensions,
+    };
+    let guides = aligned
+        .into_iter()
+        .map(|(axis, at)| {
+            let (mut from, mut to) = span(&snapped, axis);
+            for other in others {
+                if lines(other, axis).iter().any(|l| (l - at).abs() < 1e-9) {
+                    let (start, end) = span(other, axis);
+                    from = from.min(start);
+                    to = to.max(end);
+                }
+            }
+            SnapGuide { axis, at, from, to }
+        })
+        .collect();
+    SnapResult { position, guides }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Where node `id` would land if dropped at `position`, snapped per
+    /// [`FlowCanvasConfig::snap`](crate::FlowCanvasConfig::snap). Guides
+    /// use the other nodes; nodes without dimensions count as the default
+    /// node size.
+    pub fn snap_node(&self, id: &str, position: Position) -> Option<SnapResult> {
+        let node = self.nodes.get(id)?;
+        let rect = |position: Position, dimensions: Option<_>| Rect {
+            position,
+            dimensions: dimensions.unwrap_or(DEFAULT_NODE_SIZE),
+        };
+        let others: Vec<Rect> = self
+            .nodes
+            .values()
+            .filter(|n| n.id != id)
+            .map(|n| rect(n.position, n.dimensions))
+            .collect();
+        let mut config = self.config.snap_config();
+        config.tolerance /= self.viewport.transform.zoom;
+        Some(compute_snap(
+            rect(position, node.dimensions),
+            &others,
+            &config,
+        ))
+    }
+
+    /// Drop node `id` at `position`, snapped. Returns the snap result, or
+    /// `None` if there is no such node.
+    pub fn drop_node(&mut self, id: &str, position: Position) -> Option<SnapResult> {
+        let result = self.snap_node(id, position)?;
+        self.move_node(id, result.position);
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Node, NodeType};
+    use crate::position::Dimensions;
+
+    fn guides_only(tolerance: f64) -> SnapConfig {
+        SnapConfig::default().with_tolerance(tolerance)
+    }
+
+    #[test]
+    fn test_snaps_both_axes_at_once() {
+        let other = Rect::new(100.0, 100.0, 100.0, 40.0);
+        // Left edge 3 off the other's right edge, top 2 off its bottom.
+        let dragged = Rect::new(203.0, 142.0, 50.0, 20.0);
+        let result = compute_snap(dragged, &[other], &guides_only(5.0));
+
+        assert_eq!(result.position, Position::new(200.0, 140.0));
+        assert_eq!(
+            result.guides,
+            [
+                SnapGuide {
+                    axis: GuideAxis::Vertical,
+                    at: 200.0,
+                    from: 100.0,
+                    to: 160.0,
+                },
+                SnapGuide {
+                    axis: GuideAxis::Horizontal,
+                    at: 140.0,
+                    from: 100.0,
+                    to: 250.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prefers_closer_guide_and_falls_back_to_grid() {
+        let far = Rect::new(0.0, 0.0, 40.0, 40.0);
+        let near = Rect::new(0.0, 300.0, 43.0, 40.0);
+        // Right edge at 45: 5 from `far`'s right edge, 2 from `near`'s.
+        let dragged = Rect::new(25.0, 150.0, 20.0, 20.0);
+        let config = guides_only(6.0).with_grid(8.0, 8.0);
+        let result = compute_snap(dragged, &[far, near], &config);
+
+        assert_eq!(result.position.x, 23.0);
+        // No node within reach vertically: rounded to the grid instead.
+        assert_eq!(result.position.y, 152.0);
+        assert_eq!(result.guides.len(), 1);
+        assert_eq!((result.guides[0].from, result.guides[0].to), (152.0, 340.0));
+
+        let grid_only = compute_snap(dragged, &[near], &config.with_guides(false));
+        assert_eq!(grid_only.position, Position::new(24.0, 152.0));
+        assert!(grid_only.guides.is_empty());
+        let untouched = compute_snap(dragged, &[], &SnapConfig::default());
+        assert_eq!(untouched.position, dragged.position);
+    }
+
+    #[test]
+    fn test_drop_node_scales_tolerance_with_zoom() {
+        let mut canvas: FlowCanvas<(), ()> = FlowCanvas::new();
+        let size = Dimensions::new(100.0, 40.0);
+        canvas.add_node(
+            Node::new("a", NodeType::Default, Position::new(0.0, 0.0)).with_dimensions(size),
+        );
+        canvas.add_node(
+            Node::new("b", NodeType::Default, Position::new(300.0, 300.0)).with_dimensions(size),
+        );
+        canvas.config.snap = SnapConfig::default().with_tolerance(6.0);
+
+        // 4 world units is 8 screen pixels at 2x: out of reach.
+        canvas.viewport.transform.zoom = 2.0;
+        let result = canvas.snap_node("b", Position::new(4.0, 200.0)).unwrap();
+        assert_eq!(result.position.x, 4.0);
+
+        canvas.viewport.transform.zoom = 1.0;
+        let result = canvas.drop_node("b", Position::new(4.0, 200.0)).unwrap();
+        assert_eq!(result.position.x, 0.0);
+        assert_eq!(
+            canvas.get_node("b").unwrap().position,
+            Position::new(0.0, 200.0)
+        );
+        assert!(canvas.drop_node("missing", Position::zero()).is_none());
+    }
+}
@@ -0,0 +1,595 @@
+//! Spatial index for hit testing on large canvases.
+//!
+//! A loose quadtree over the bounds of the indexed rects: each quad accepts
+//! rects whose center lies in it and that fit within its bounds grown by
+//! half on every side, so rects straddling a split line still sink to a
+//! quad of their own size. Rects outside the root are kept in an overflow
+//! list that every query scans. Moves update the tree in place, and the
+//! tree is rebuilt once enough of it has churned or overflowed that
+//! in-place updates stop paying off.
+
+use std::hash::Hash;
+
+use indexmap::IndexMap;
+
+use crate::canvas::FlowCanvas;
+use crate::focus::rect;
+use crate::lasso::{Lasso, SelectionMode};
+use crate::node::NodeId;
+use crate::position::{Position, Rect};
+
+/// Rects a quad holds before it splits.
+const QUAD_CAPACITY: usize = 8;
+
+/// Depth below which quads no longer split.
+const MAX_DEPTH: u8 = 12;
+
+/// Churn and overflow below this never trigger a rebuild.
+const MIN_REBUILD_CHURN: usize = 64;
+
+/// Where an indexed rect is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Quad(usize),
+    Overflow,
+}
+
+#[derive(Debug, Clone)]
+struct Quad<K> {
+    /// Tight bounds; children split them in four.
+    bounds: Rect,
+    /// `bounds` grown by half on every side; holds the quad's rects.
+    loose: Rect,
+    depth: u8,
+    items: Vec<K>,
+    children: Option<[usize; 4]>,
+}
+
+impl<K> Quad<K> {
+    /// Child of `children` whose tight bounds hold `point`.
+    fn child_at(&self, point: Position) -> Option<usize> {
+        let children = self.children?;
+        let center = self.bounds.center();
+        let right = usize::from(point.x >= center.x);
+        let below = usize::from(point.y >= center.y);
+        Some(children[below * 2 + right])
+    }
+
+    fn new(bounds: Rect, depth: u8) -> Self {
+        let (w, h) = (bounds.dimensions.width, bounds.dimensions.height);
+        Self {
+            bounds,
+            loose: Rect::new(
+                bounds.position.x - w / 2.0,
+                bounds.position.y - h / 2.0,
+                w * 2.0,
+                h * 2.0,
+            ),
+            depth,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+}
+
+/// Whether `inner` lies entirely within `outer`, edges included.
+fn encloses(outer: &Rect, inner: &Rect) -> bool {
+    outer.position.x <= inner.position.x
+        && outer.position.y <= inner.position.y
+        && inner.position.x + inner.dimensions.width <= outer.position.x + outer.dimensions.width
+        && inner.position.y + inner.dimensions.height <= outer.position.y + outer.dimensions.height
+}
+
+/// Whether two rects overlap or touch. Unlike [`Rect::intersects`], a
+/// zero-sized rect on the border of another counts.
+pub fn rects_touch(a: &Rect, b: &Rect) -> bool {
+    a.position.x <= b.position.x + b.dimensions.width
+        && b.position.x <= a.position.x + a.dimensions.width
+        && a.position.y <= b.position.y + b.dimensions.height
+        && b.position.y <= a.position.y + a.dimensions.height
+}
+
+/// Quadtree of rects keyed by id, answering point and rect queries
+/// without scanning every rect.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<K = NodeId> {
+    /// Every indexed rect with where it is stored.
+    entries: IndexMap<K, (Rect, Slot)>,
+    /// Quads; the root is at index 0 when the tree is not empty.
+    quads: Vec<Quad<K>>,
+    overflow: Vec<K>,
+    /// Inserts, moves and removals since the last rebuild.
+    churn: usize,
+    rebuilds: u64,
+}
+
+impl<K> Default for SpatialIndex<K> {
+    fn default() -> Self {
+        Self {
+            entries: IndexMap::new(),
+            quads: Vec::new(),
+            overflow: Vec::new(),
+            churn: 0,
+            rebuilds: 0,
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> FromIterator<(K, Rect)> for SpatialIndex<K> {
+    fn from_iter<I: IntoIterator<Item = (K, Rect)>>(iter: I) -> Self {
+        let mut index = Self::new();
+        for (id, rect) in iter {
+            index.entries.insert(id, (rect, Slot::Overflow));
+        }
+        index.rebuild();
+        index
+    }
+}
+
+impl<K: Clone + Eq + Hash> SpatialIndex<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Indexed rect of `id`.
+    pub fn get(&self, id: &K) -> Option<&Rect> {
+        self.entries.get(id).map(|(rect, _)| rect)
+    }
+
+    /// Indexed ids with their rects, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &Rect)> {
+        self.entries.iter().map(|(id, (rect, _))| (id, rect))
+    }
+
+    /// Number of times the tree was rebuilt.
+    pub fn rebuilds(&self) -> u64 {
+        self.rebuilds
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.quads.clear();
+        self.overflow.clear();
+        self.churn = 0;
+    }
+
+    /// Index `rect` under `id`, replacing any rect it had.
+    pub fn insert(&mut self, id: K, rect: Rect) {
+        self.detach(&id);
+        let slot = self.place(id.clone(), rect);
+        self.entries.insert(id, (rect, slot));
+        self.churned();
+    }
+
+    /// Move `id` to `rect`. Returns false, changing nothing, if `id` is not
+    /// indexed.
+    pub fn update(&mut self, id: &K, rect: Rect) -> bool {
+        match self.entries.get(id) {
+            Some((current, _)) if *current == rect => true,
+            Some(_) => {
+                self.insert(id.clone(), rect);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop indexing `id`, returning its rect.
+    pub fn remove(&mut self, id: &K) -> Option<Rect> {
+        let rect = self.detach(id)?;
+        self.entries.swap_remove(id);
+        self.churned();
+        Some(rect)
+    }
+
+    /// Ids whose rect contains `point`, edges included, in no particular
+    /// order.
+    pub fn query_point(&self, point: &Position) -> Vec<K> {
+        self.search(|bounds| bounds.contains(point), &mut 0)
+    }
+
+    /// Ids whose rect overlaps or touches `area`, in no particular order.
+    pub fn query_rect(&self, area: &Rect) -> Vec<K> {
+        self.search(|bounds| rects_touch(bounds, area), &mut 0)
+    }
+
+    /// Rebuild the tree around the current rects.
+    pub fn rebuild(&mut self) {
+        self.quads.clear();
+        self.overflow.clear();
+        self.churn = 0;
+        self.rebuilds += 1;
+        let bounds = self
+            .entries
+            .values()
+            .map(|(rect, _)| *rect)
+            .filter(|rect| rect.position.x.is_finite() && rect.position.y.is_finite())
+            .reduce(|a, b| a.union(&b));
+        if let Some(bounds) = bounds {
+            self.quads.push(Quad::new(bounds, 0));
+        }
+        let entries: Vec<(K, Rect)> = self
+            .entries
+            .iter()
+            .map(|(id, (rect, _))| (id.clone(), *rect))
+            .collect();
+        for (id, rect) in entries {
+            let slot = self.place(id.clone(), rect);
+            if let Some(entry) = self.entries.get_mut(&id) {
+                entry.1 = slot;
+            }
+        }
+    }
+
+    /// Rebuild when in-place updates have left the tree lopsided: many
+    /// rects outside the root, or more churn than twice the rect count.
+    fn churned(&mut self) {
+        self.churn += 1;
+        let len = self.entries.len();
+        if self.overflow.len() > MIN_REBUILD_CHURN.max(len / 4)
+            || self.churn > MIN_REBUILD_CHURN.max(len * 2)
+        {
+            self.rebuild();
+        }
+    }
+
+    /// Take `id` out of the tree, keeping its entry.
+    fn detach(&mut self, id: &K) -> Option<Rect> {
+        let (rect, slot) = *self.entries.get(id)?;
+        let items = match slot {
+            Slot::Quad(quad) => &mut self.quads[quad].items,
+            Slot::Overflow => &mut self.overflow,
+        };
+        if let Some(i) = items.iter().position(|item| item == id) {
+            items.swap_remove(i);
+        }
+        Some(rect)
+    }
+
+    /// Store `id` in the deepest quad that takes `rect`, splitting a full
+    /// leaf on the way.
+    fn place(&mut self, id: K, rect: Rect) -> Slot {
+        if self.quads.is_empty() || !encloses(&self.quads[0].loose, &rect) {
+            self.overflow.push(id);
+            return Slot::Overflow;
+        }
+        let center = rect.center();
+        let mut quad = 0;
+        loop {
+            if let Some(child) = self.quads[quad].child_at(center) {
+                if !encloses(&self.quads[child].loose, &rect) {
+                    break;
+                }
+                quad = child;
+            } else if self.quads[quad].items.len() >= QUAD_CAPACITY
+                && self.quads[quad].depth < MAX_DEPTH
+            {
+                self.split(quad);
+            } else {
+                break;
+            }
+        }
+        self.quads[quad].items.push(id);
+        Slot::Quad(quad)
+    }
+
+    /// Give a leaf four children and move down the rects that fit one.
+    fn split(&mut self, quad: usize) {
+        let Quad { bounds, depth, .. } = self.quads[quad];
+        let (w, h) = (
+            bounds.dimensions.width / 2.0,
+            bounds.dimensions.height / 2.0,
+        );
+        let (x, y) = (bounds.position.x, bounds.position.y);
+        let first = self.quads.len();
+        for (cx, cy) in [(x, y), (x + w, y), (x, y + h), (x + w, y + h)] {
+            self.quads
+                .push(Quad::new(Rect::new(cx, cy, w, h), depth + 1));
+        }
+        self.quads[quad].children = Some([first, first + 1, first + 2, first + 3]);
+
+        let items = std::mem::take(&mut self.quads[quad].items);
+        for id in items {
+            let rect = self.entries[&id].0;
+            let target = self.quads[quad]
+                .child_at(rect.center())
+                .filter(|&c| encloses(&self.quads[c].loose, &rect))
+                .unwrap_or(quad);
+            self.quads[target].items.push(id.clone());
+            if let Some(entry) = self.entries.get_mut(&id) {
+                entry.1 = Slot::Quad(target);
+            }
+        }
+    }
+
+    /// Ids whose rect passes `hit`, visiting only quads whose loose bounds
+    /// pass it. `examined` counts the rects tested.
+    fn search(&self, hit: impl Fn(&Rect) -> bool, examined: &mut usize) -> Vec<K> {
+        let mut found = Vec::new();
+        let mut test = |id: &K| {
+            *examined += 1;
+            if hit(&self.entries[id].0) {
+                found.push(id.clone());
+            }
+        };
+        self.overflow.iter().for_each(&mut test);
+        let mut stack: Vec<usize> = if self.quads.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(quad) = stack.pop() {
+            let quad = &self.quads[quad];
+            if !hit(&quad.loose) {
+                continue;
+            }
+            quad.items.iter().for_each(&mut test);
+            stack.extend(quad.children.into_iter().flatten());
+        }
+        found
+    }
+}
+
+impl<N, E> FlowCanvas<N, E> {
+    /// Whether the spatial index matches `nodes`. Nodes edited through
+    /// [`get_node_mut`](Self::get_node_mut) are still waiting to be
+    /// re-indexed but are checked directly, so they do not count.
+    fn spatial_in_sync(&self) -> bool {
+        self.spatial.len() == self.nodes.len()
+    }
+
+    /// Re-index nodes edited since the last sync, or every node if `nodes`
+    /// was changed directly. Call after editing `nodes` by hand.
+    pub fn sync_spatial_index(&mut self) {
+        if !self.spatial_in_sync() {
+            self.reindex_all();
+            return;
+        }
+        for id in std::mem::take(&mut self.spatial_stale) {
+            self.reindex_node(&id);
+        }
+    }
+
+    /// Index every node from scratch.
+    pub(crate) fn reindex_all(&mut self) {
+        self.spatial = self
+            .nodes
+            .values()
+            .map(|n| (n.id.clone(), rect(n)))
+            .collect();
+        self.spatial_stale.clear();
+    }
+
+    /// Re-index one node after its position or dimensions changed.
+    pub(crate) fn reindex_node(&mut self, id: &str) {
+        match self.nodes.get(id) {
+            Some(node) => self.spatial.insert(id.to_string(), rect(node)),
+            None => {
+                self.spatial.remove(&id.to_string());
+            }
+        }
+    }
+
+    /// Ids of the nodes `hit` accepts by rect, in canvas order. Nodes
+    /// without dimensions count as the default node size.
+    fn nodes_where(
+        &self,
+        query: impl Fn(&SpatialIndex) -> Vec<NodeId>,
+        hit: impl Fn(&Rect) -> bool,
+    ) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = if self.spatial_in_sync() {
+            let mut ids: Vec<NodeId> = query(&self.spatial)
+                .into_iter()
+                .filter(|id| !self.spatial_stale.contains(id))
+                .collect();
+            ids.extend(
+                self.spatial_stale
+                    .iter()
+                    .filter(|id| self.nodes.get(*id).is_some_and(|n| hit(&rect(n))))
+                    .cloned(),
+            );
+            ids
+        } else {
+            self.nodes
+                .values()
+                .filter(|n| hit(&rect(n)))
+                .map(|n| n.id.clone())
+                .collect()
+        };
+        ids.sort_by_key(|id| self.nodes.get_index_of(id));
+        ids
+    }
+
+    /// Nodes under `point`, in canvas order; the last one is drawn on top.
+    pub fn nodes_at(&self, point: &Position) -> Vec<NodeId> {
+        self.nodes_where(|index| index.query_point(point), |r| r.contains(point))
+    }
+
+    /// Topmost node under `point`, for hover and click.
+    pub fn node_at(&self, point: &Position) -> Option<NodeId> {
+        self.nodes_at(point).pop()
+    }
+
+    /// Nodes whose rect overlaps or touches `area`, in canvas order.
+    pub fn nodes_in_rect(&self, area: &Rect) -> Vec<NodeId> {
+        self.nodes_where(|index| index.query_rect(area), |r| rects_touch(r, area))
+    }
+
+    /// Apply a rubber-band rect to the node selection. Returns the ids it
+    /// hit.
+    pub fn select_rect(&mut self, area: &Rect, mode: SelectionMode) -> Vec<NodeId> {
+        let hit = self.nodes_in_rect(area);
+        self.apply_selection(&hit, mode);
+        hit
+    }
+
+    /// Nodes that could be hit by `lasso`: those touching its bounding box.
+    pub(crate) fn lasso_candidates(&self, lasso: &Lasso) -> Vec<NodeId> {
+        let Some(bounds) = lasso
+            .points()
+            .iter()
+            .map(|p| Rect::new(p.x, p.y, 0.0, 0.0))
+            .reduce(|a, b| a.union(&b))
+        else {
+            return Vec::new();
+        };
+        self.nodes_in_rect(&bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::{Node, NodeType};
+    use crate::position::Dimensions;
+
+    /// Deterministic xorshift generator for repeatable random rects.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        fn rect(&mut self, extent: f64, max_size: f64) -> Rect {
+            Rect::new(
+                self.next() * extent,
+                self.next() * extent,
+                self.next() * max_size,
+                self.next() * max_size,
+            )
+        }
+    }
+
+    fn sorted(mut ids: Vec<usize>) -> Vec<usize> {
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn test_queries_match_brute_force() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for round in 0..20 {
+            let count = 10 + round * 40;
+            let mut rects: Vec<Rect> = (0..count).map(|_| rng.rect(2000.0, 200.0)).collect();
+            let mut index: SpatialIndex<usize> = rects.iter().copied().enumerate().collect();
+
+            // Moves, some far outside the original bounds, and removals.
+            for _ in 0..count {
+                let i = (rng.next() * count as f64) as usize;
+                rects[i] = rng.rect(4000.0, 200.0);
+                index.update(&i, rects[i]);
+            }
+            let removed = (rng.next() * count as f64) as usize;
+            index.remove(&removed);
+
+            for _ in 0..50 {
+                let point = Position::new(rng.next() * 4000.0, rng.next() * 4000.0);
+                let area = rng.rect(4000.0, 600.0);
+                let live = || (0..count).filter(|i| *i != removed);
+                let expected: Vec<usize> = live().filter(|i| rects[*i].contains(&point)).collect();
+                assert_eq!(sorted(index.query_point(&point)), expected);
+                let expected: Vec<usize> =
+                    live().filter(|i| rects_touch(&rects[*i], &area)).collect();
+                assert_eq!(sorted(index.query_rect(&area)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_cost_is_sub_linear() {
+        let mut rng = Rng(42);
+        let mut examined_per_query = Vec::new();
+        for count in [500, 2_000, 8_000, 32_000] {
+            // Same density at every size, so a query hits about as many rects.
+            let extent = (count as f64).sqrt() * 100.0;
+            let index: SpatialIndex<usize> =
+                (0..count).map(|i| (i, rng.rect(extent, 150.0))).collect();
+            let mut examined = 0;
+            for _ in 0..200 {
+                let point = Position::new(rng.next() * extent, rng.next() * extent);
+                index.search(|bounds| bounds.contains(&point), &mut examined);
+            }
+            examined_per_query.push(examined as f64 / 200.0);
+        }
+        // 64 times the rects costs far less than 64 times the work.
+        let growth = examined_per_query[3] / examined_per_query[0];
+        assert!(growth < 4.0, "{:?}", examined_per_query);
+        assert!(
+            examined_per_query[3] < 32_000.0 / 100.0,
+            "{:?}",
+            examined_per_query
+        );
+    }
+
+    #[test]
+    fn test_rebuilds_after_churn() {
+        let mut index: SpatialIndex<usize> = (0..100)
+            .map(|i| (i, Rect::new(i as f64 * 10.0, 0.0, 5.0, 5.0)))
+            .collect();
+        assert_eq!(index.rebuilds(), 1);
+        // Moving everything far away overflows the root and forces a rebuild.
+        for i in 0..100 {
+            index.update(&i, Rect::new(50_000.0 + i as f64, 50_000.0, 5.0, 5.0));
+        }
+        assert!(index.rebuilds() > 1);
+        assert!(index.overflow.len() < 100);
+        assert_eq!(
+            index.query_point(&Position::new(50_002.0, 50_002.0)).len(),
+            3
+        );
+        // Updating to the same rect is not churn.
+        let rebuilds = index.rebuilds();
+        for _ in 0..1_000 {
+            index.update(&7, *index.get(&7).unwrap());
+        }
+        assert_eq!(index.rebuilds(), rebuilds);
+    }
+
+    #[test]
+    fn test_canvas_hit_testing_follows_moves() {
+        let mut canvas: FlowCanvas<(), ()> = FlowCanvas::new();
+        for (id, x) in [("a", 0.0), ("b", 50.0), ("c", 400.0)] {
+            canvas.add_node(
+                Node::new(id, NodeType::Default, Position::new(x, 0.0))
+                    .with_dimensions(Dimensions::new(100.0, 40.0)),
+            );
+        }
+        let point = Position::new(75.0, 20.0);
+        assert_eq!(canvas.nodes_at(&point), ["a", "b"]);
+        assert_eq!(canvas.node_at(&point).as_deref(), Some("b"));
+
+        // Edited through get_node_mut: seen before and after syncing.
+        canvas.get_node_mut("c").unwrap().position = Position::new(60.0, 10.0);
+        assert_eq!(canvas.nodes_at(&point), ["a", "b", "c"]);
+        canvas.sync_spatial_index();
+        assert_eq!(canvas.node_at(&point).as_deref(), Some("c"));
+
+        canvas.drop_node("a", Position::new(500.0, 500.0));
+        let band = Rect::new(-10.0, -10.0, 90.0, 30.0);
+        assert_eq!(
+            canvas.select_rect(&band, SelectionMode::Replace),
+            ["b", "c"]
+        );
+        assert_eq!(canvas.selected_nodes, ["b", "c"]);
+
+        // Nodes inserted directly fall back to a scan until synced.
+        let node = Node::new("d", NodeType::Default, Position::new(0.0, 0.0));
+        canvas.nodes.insert("d".to_string(), node);
+        assert_eq!(canvas.nodes_in_rect(&band), ["b", "c", "d"]);
+        canvas.remove_node("b");
+        canvas.sync_spatial_index();
+        assert_eq!(canvas.nodes_in_rect(&band), ["c", "d"]);
+    }
+}
@@ -0,0 +1,47 @@
+//! Canvas colors.
+
+use serde::{Deserialize, Serialize};
+
+/// Colors the canvas is drawn with, as CSS color values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowTheme {
+    pub background: String,
+    pub grid: String,
+    pub node_fill: String,
+    pub node_stroke: String,
+    pub node_text: String,
+    pub edge: String,
+    /// Outline of selected nodes and edges.
+    pub selection: String,
+}
+
+impl Default for FlowTheme {
+    fn default() -> Self {
+        Self {
+            background: "#f8fafc".to_string(),
+            grid: "#e2e8f0".to_string(),
+            node_fill: "#ffffff".to_string(),
+            node_stroke: "#334155".to_string(),
+            node_text: "#0f172a".to_string(),
+            edge: "#94a3b8".to_string(),
+            selection: "#3b82f6".to_string(),
+        }
+    }
+}
+
+impl FlowTheme {
+    /// The theme with every color passed through `f`, e.g. to preview it
+    /// under a color vision deficiency.
+    pub fn map_colors(&self, f: impl Fn(&str) -> String) -> Self {
+        Self {
+            background: f(&self.background),
+            grid: f(&self.grid),
+            node_fill: f(&self.node_fill),
+            node_stroke: f(&self.node_stroke),
+            node_text: f(&self.node_text),
+            edge: f(&self.edge),
+            selection: f(&self.selection),
+        }
+    }
+}
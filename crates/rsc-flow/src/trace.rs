@@ -0,0 +1,13 @@
+//! Instrumentation behind the `tracing` feature.
+
+/// Enter a span until the end of the enclosing block. Every span carries a
+/// `category` field used to group timings. Without the `tracing` feature
+/// this expands to nothing and its fields are not evaluated.
+macro_rules! trace_span {
+    ($category:literal, $name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name, category = $category $(, $($fields)*)?).entered();
+    };
+}
+
+pub(crate) use trace_span;
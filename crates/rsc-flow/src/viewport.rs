@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::position::Position;
+use crate::position::{Dimensions, Position, Rect};
 
 /// Viewport state for the canvas.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,17 +74,58 @@ impl Viewport {
     }
 
     /// Fit to bounds.
-    pub fn fit_to_bounds(&mut self, bounds: &crate::position::Rect, padding: f64, canvas_size: &crate::position::Dimensions) {
-        let scale_x = (canvas_size.width - padding * 2.0) / bounds.dimensions.width;
-        let scale_y = (canvas_size.height - padding * 2.0) / bounds.dimensions.height;
-        let zoom = scale_x.min(scale_y).min(self.max_zoom).max(self.min_zoom);
+    pub fn fit_to_bounds(&mut self, bounds: &Rect, padding: f64, canvas_size: &Dimensions) {
+        self.fit_bounds(*bounds, padding, *canvas_size);
+    }
 
-        let center = bounds.center();
+    /// Zoom and pan so `bounds` fits inside `canvas` with `padding` on
+    /// every side, centered. The zoom is clamped to `min_zoom..=max_zoom`;
+    /// a zero-sized side doesn't limit it.
+    pub fn fit_bounds(
+        &mut self,
+        bounds: Rect,
+        padding: f64,
+        canvas: Dimensions,
+    ) -> ViewportTransform {
+        let scale = |available: f64, size: f64| {
+            if size > 0.0 {
+                available / size
+            } else {
+                f64::INFINITY
+            }
+        };
+        let scale_x = scale(canvas.width - padding * 2.0, bounds.dimensions.width);
+        let scale_y = scale(canvas.height - padding * 2.0, bounds.dimensions.height);
+        self.center_on(bounds.center(), canvas, Some(scale_x.min(scale_y)))
+    }
+
+    /// Pan so `point` is in the middle of `canvas`, optionally changing
+    /// the zoom (clamped to `min_zoom..=max_zoom`).
+    pub fn center_on(
+        &mut self,
+        point: Position,
+        canvas: Dimensions,
+        zoom: Option<f64>,
+    ) -> ViewportTransform {
+        let zoom = zoom.map_or(self.transform.zoom, |z| {
+            z.min(self.max_zoom).max(self.min_zoom)
+        });
         self.transform = ViewportTransform {
-            x: canvas_size.width / 2.0 - center.x * zoom,
-            y: canvas_size.height / 2.0 - center.y * zoom,
+            x: canvas.width / 2.0 - point.x * zoom,
+            y: canvas.height / 2.0 - point.y * zoom,
             zoom,
         };
+        self.transform
+    }
+
+    /// Part of the canvas a `canvas_size` screen area shows, in canvas
+    /// coordinates.
+    pub fn visible_rect(&self, canvas_size: Dimensions) -> Rect {
+        let zoom = self.transform.zoom;
+        Rect {
+            position: self.screen_to_canvas(Position::zero()),
+            dimensions: Dimensions::new(canvas_size.width / zoom, canvas_size.height / zoom),
+        }
     }
 
     /// Convert screen coordinates to canvas coordinates.
@@ -105,7 +146,7 @@ impl Viewport {
 }
 
 /// Viewport transform (pan and zoom).
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ViewportTransform {
     /// X translation.
     pub x: f64,
@@ -148,6 +189,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_coordinate_conversion() {
         let mut viewport = Viewport::default();
         viewport.transform = ViewportTransform::new(100.0, 50.0, 2.0);
@@ -159,4 +201,62 @@ mod tests {
         assert!((back.x - screen.x).abs() < 0.001);
         assert!((back.y - screen.y).abs() < 0.001);
     }
+
+    #[test]
+    fn test_fit_bounds() {
+        let canvas = Dimensions::new(1000.0, 600.0);
+        let mut viewport = Viewport::default();
+
+        // Wide and short: width limits the zoom.
+        let wide = Rect::new(-200.0, 40.0, 1600.0, 100.0);
+        assert_eq!(
+            viewport.fit_bounds(wide, 100.0, canvas),
+            ViewportTransform::new(200.0, 255.0, 0.5)
+        );
+        // Tall and narrow: height limits the zoom.
+        let tall = Rect::new(0.0, 0.0, 100.0, 1600.0);
+        assert_eq!(
+            viewport.fit_bounds(tall, 100.0, canvas),
+            ViewportTransform::new(487.5, 100.0, 0.25)
+        );
+        assert_eq!(
+            viewport.transform,
+            ViewportTransform::new(487.5, 100.0, 0.25)
+        );
+
+        // Clamped at both ends.
+        assert_eq!(
+            viewport.fit_bounds(Rect::new(0.0, 0.0, 10.0, 10.0), 100.0, canvas),
+            ViewportTransform::new(480.0, 280.0, 4.0)
+        );
+        assert_eq!(
+            viewport.fit_bounds(Rect::new(0.0, 0.0, 100000.0, 100000.0), 100.0, canvas),
+            ViewportTransform::new(-4500.0, -4700.0, 0.1)
+        );
+        assert_eq!(
+            viewport
+                .fit_bounds(Rect::new(10.0, 10.0, 0.0, 0.0), 100.0, canvas)
+                .zoom,
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_center_on() {
+        let canvas = Dimensions::new(1000.0, 600.0);
+        let mut viewport = Viewport::default();
+        let point = Position::new(100.0, 100.0);
+        assert_eq!(
+            viewport.center_on(point, canvas, Some(2.0)),
+            ViewportTransform::new(300.0, 100.0, 2.0)
+        );
+        // Keeps the zoom when none is given.
+        assert_eq!(
+            viewport.center_on(Position::new(0.0, 0.0), canvas, None),
+            ViewportTransform::new(500.0, 300.0, 2.0)
+        );
+        assert_eq!(viewport.center_on(point, canvas, Some(10.0)).zoom, 4.0);
+        let centered = viewport.canvas_to_screen(point);
+        assert_eq!(centered, Position::new(500.0, 300.0));
+    }
 }
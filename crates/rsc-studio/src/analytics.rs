@@ -0,0 +1,233 @@
+//! Usage analytics overlays for the flow canvas.
+//!
+//! Traversal counts are kept beside the design (per workflow in the store),
+//! never in edge data, so imported analytics don't leak into exports.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Quantile buckets of the overlay drawn on the canvas and in exports.
+pub const DEFAULT_WEIGHT_BUCKETS: usize = 4;
+
+/// Stroke color ramp from low to high traffic.
+const WEIGHT_COLORS: [&str; 5] = ["#cbd5e1", "#93c5fd", "#60a5fa", "#2563eb", "#1e3a8a"];
+
+/// Stroke width for the lowest bucket.
+const MIN_STROKE_WIDTH: f64 = 1.0;
+
+/// Stroke width for the highest bucket.
+const MAX_STROKE_WIDTH: f64 = 6.0;
+
+/// Traversal weights keyed by edge id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WeightMap {
+    /// Accumulated weight per edge.
+    #[serde(default)]
+    pub weights: IndexMap<String, f64>,
+    /// Imported rows that could not be matched to an edge.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmatched: Vec<UnmatchedRow>,
+}
+
+impl WeightMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add weight to an edge.
+    pub fn add(&mut self, edge_id: impl Into<String>, weight: f64) {
+        *self.weights.entry(edge_id.into()).or_insert(0.0) += weight;
+    }
+
+    pub fn get(&self, edge_id: &str) -> Option<f64> {
+        self.weights.get(edge_id).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+}
+
+/// A row from an analytics import that matched no edge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnmatchedRow {
+    /// 1-based line number in the source file.
+    pub line: usize,
+    /// Source slug as written in the row.
+    pub source: String,
+    /// Target slug as written in the row.
+    pub target: String,
+    /// Why the row was not applied.
+    pub reason: String,
+}
+
+/// Visual style for a weighted edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeWeightStyle {
+    /// Quantile bucket (0 = lowest traffic).
+    pub bucket: usize,
+    pub stroke_width: f64,
+    pub color: &'static str,
+}
+
+/// Legend entry describing one quantile bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightLegendEntry {
+    pub bucket: usize,
+    /// Smallest weight in the bucket.
+    pub min: f64,
+    /// Largest weight in the bucket.
+    pub max: f64,
+    pub stroke_width: f64,
+    pub color: &'static str,
+    /// Display label, e.g. "12 – 40".
+    pub label: String,
+}
+
+/// Weight overlay mapping edge weights to quantile-based stroke styles.
+#[derive(Debug, Clone)]
+pub struct WeightOverlay {
+    buckets: usize,
+    styles: IndexMap<String, EdgeWeightStyle>,
+    legend: Vec<WeightLegendEntry>,
+}
+
+impl WeightOverlay {
+    /// Bucket the weights into `buckets` quantiles (at least one).
+    pub fn new(weights: &WeightMap, buckets: usize) -> Self {
+        let buckets = buckets.max(1);
+        let mut sorted: Vec<f64> = weights.weights.values().copied().collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let mut styles = IndexMap::new();
+        let mut ranges: Vec<Option<(f64, f64)>> = vec![None; buckets];
+        for (edge_id, weight) in &weights.weights {
+            let bucket = quantile_bucket(&sorted, *weight, buckets);
+            styles.insert(edge_id.clone(), bucket_style(bucket, buckets));
+            let range = ranges[bucket].get_or_insert((*weight, *weight));
+            range.0 = range.0.min(*weight);
+            range.1 = range.1.max(*weight);
+        }
+
+        let legend = ranges
+            .into_iter()
+            .enumerate()
+            .filter_map(|(bucket, range)| {
+                let (min, max) = range?;
+                let style = bucket_style(bucket, buckets);
+                Some(WeightLegendEntry {
+                    bucket,
+                    min,
+                    max,
+                    stroke_width: style.stroke_width,
+                    color: style.color,
+                    label: if min == max {
+                        format_weight(min)
+                    } else {
+                        format!("{} – {}", format_weight(min), format_weight(max))
+                    },
+                })
+            })
+            .collect();
+
+        Self {
+            buckets,
+            styles,
+            legend,
+        }
+    }
+
+    pub fn buckets(&self) -> usize {
+        self.buckets
+    }
+
+    /// Style for an edge, if it has a weight.
+    pub fn style_for(&self, edge_id: &str) -> Option<&EdgeWeightStyle> {
+        self.styles.get(edge_id)
+    }
+
+    /// Legend entries for non-empty buckets, lowest first.
+    pub fn legend(&self) -> &[WeightLegendEntry] {
+        &self.legend
+    }
+}
+
+/// Bucket index of a value: its rank among the sorted weights, scaled to the bucket count.
+fn quantile_bucket(sorted: &[f64], value: f64, buckets: usize) -> usize {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = sorted.partition_point(|w| *w < value);
+    (rank * buckets / sorted.len()).min(buckets - 1)
+}
+
+fn bucket_style(bucket: usize, buckets: usize) -> EdgeWeightStyle {
+    let t = if buckets > 1 {
+        bucket as f64 / (buckets - 1) as f64
+    } else {
+        1.0
+    };
+    let color_index = (t * (WEIGHT_COLORS.len() - 1) as f64).round() as usize;
+    EdgeWeightStyle {
+        bucket,
+        stroke_width: MIN_STROKE_WIDTH + (MAX_STROKE_WIDTH - MIN_STROKE_WIDTH) * t,
+        color: WEIGHT_COLORS[color_index],
+    }
+}
+
+fn format_weight(weight: f64) -> String {
+    if weight.fract() == 0.0 {
+        format!("{}", weight as i64)
+    } else {
+        format!("{:.2}", weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantile_bucketing() {
+        let mut weights = WeightMap::new();
+        for (i, w) in [1.0, 2.0, 3.0, 4.0, 10.0, 20.0, 30.0, 40.0]
+            .iter()
+            .enumerate()
+        {
+            weights.add(format!("e{}", i), *w);
+        }
+
+        let overlay = WeightOverlay::new(&weights, 4);
+
+        assert_eq!(overlay.style_for("e0").unwrap().bucket, 0);
+        assert_eq!(overlay.style_for("e1").unwrap().bucket, 0);
+        assert_eq!(overlay.style_for("e2").unwrap().bucket, 1);
+        assert_eq!(overlay.style_for("e5").unwrap().bucket, 2);
+        assert_eq!(overlay.style_for("e7").unwrap().bucket, 3);
+        assert!(overlay.style_for("missing").is_none());
+
+        let low = overlay.style_for("e0").unwrap();
+        let high = overlay.style_for("e7").unwrap();
+        assert_eq!(low.stroke_width, MIN_STROKE_WIDTH);
+        assert_eq!(high.stroke_width, MAX_STROKE_WIDTH);
+        assert_ne!(low.color, high.color);
+
+        let legend = overlay.legend();
+        assert_eq!(legend.len(), 4);
+        assert_eq!(legend[0].label, "1 – 2");
+        assert_eq!(legend[3].label, "30 – 40");
+    }
+
+    #[test]
+    fn test_equal_weights_share_a_bucket() {
+        let mut weights = WeightMap::new();
+        weights.add("a", 5.0);
+        weights.add("b", 5.0);
+        weights.add("c", 5.0);
+
+        let overlay = WeightOverlay::new(&weights, 3);
+
+        assert_eq!(overlay.legend().len(), 1);
+        assert_eq!(overlay.legend()[0].label, "5");
+    }
+}
@@ -0,0 +1,259 @@
+//! Binary assets dropped or pasted into the studio.
+//!
+//! Assets are keyed by a hash of their content, so dropping the same
+//! screenshot twice stores it once. Only images are accepted; their
+//! intrinsic size is read from the file header.
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+/// Largest asset accepted by default, in bytes.
+pub const DEFAULT_MAX_ASSET_BYTES: usize = 10 * 1024 * 1024;
+
+/// Asset error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum AssetError {
+    /// The file isn't an image.
+    #[error("'{name}' is not an image ({mime})")]
+    NotAnImage { name: String, mime: String },
+
+    /// The file claims to be an image but its size can't be read.
+    #[error("'{0}' is not a readable PNG, GIF or JPEG image")]
+    Unreadable(String),
+
+    /// The file is over the size limit.
+    #[error("'{name}' is {size} bytes; the limit is {max}")]
+    TooLarge {
+        name: String,
+        size: usize,
+        max: usize,
+    },
+}
+
+/// Intrinsic image size in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A stored asset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Asset {
+    /// Content-derived id, e.g. `asset-3f2a…`.
+    pub id: String,
+    /// File name as dropped.
+    pub name: String,
+    pub mime: String,
+    pub size: ImageSize,
+    pub bytes: Vec<u8>,
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike the std hasher.
+pub(crate) fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Width and height from a PNG, GIF or JPEG header.
+pub fn image_size(bytes: &[u8]) -> Option<ImageSize> {
+    let be16 = |i: usize| Some(u16::from_be_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+    let le16 = |i: usize| Some(u16::from_le_bytes([*bytes.get(i)?, *bytes.get(i + 1)?]) as u32);
+
+    let size = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ImageSize {
+            width: be32(16)?,
+            height: be32(20)?,
+        }
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        ImageSize {
+            width: le16(6)?,
+            height: le16(8)?,
+        }
+    } else if bytes.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments to the first start-of-frame marker.
+        let mut i = 2;
+        loop {
+            if *bytes.get(i)? != 0xff {
+                return None;
+            }
+            let marker = *bytes.get(i + 1)?;
+            let is_frame = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_frame {
+                break ImageSize {
+                    width: be16(i + 7)?,
+                    height: be16(i + 5)?,
+                };
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    } else {
+        return None;
+    };
+    (size.width > 0 && size.height > 0).then_some(size)
+}
+
+/// Content-addressed image store.
+#[derive(Debug, Clone)]
+pub struct AssetRegistry {
+    assets: IndexMap<String, Asset>,
+    /// Largest accepted file, in bytes.
+    pub max_bytes: usize,
+}
+
+impl Default for AssetRegistry {
+    fn default() -> Self {
+        Self {
+            assets: IndexMap::new(),
+            max_bytes: DEFAULT_MAX_ASSET_BYTES,
+        }
+    }
+}
+
+impl AssetRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Store an image, or return the stored copy if the same bytes were
+    /// added before (under any name).
+    pub fn add_image(
+        &mut self,
+        name: &str,
+        mime: &str,
+        bytes: Vec<u8>,
+    ) -> Result<&Asset, AssetError> {
+        if !mime.starts_with("image/") {
+            return Err(AssetError::NotAnImage {
+                name: name.to_string(),
+                mime: mime.to_string(),
+            });
+        }
+        if bytes.len() > self.max_bytes {
+            return Err(AssetError::TooLarge {
+                name: name.to_string(),
+                size: bytes.len(),
+                max: self.max_bytes,
+            });
+        }
+        let size = image_size(&bytes).ok_or_else(|| AssetError::Unreadable(name.to_string()))?;
+
+        // Probe past hash collisions with different content.
+        let hash = content_hash(&bytes);
+        let mut id = format!("asset-{:016x}", hash);
+        let mut probe = 1;
+        while let Some(existing) = self.assets.get(&id) {
+            if existing.bytes == bytes {
+                return Ok(&self.assets[&id]);
+            }
+            id = format!("asset-{:016x}-{}", hash, probe);
+            probe += 1;
+        }
+
+        let asset = Asset {
+            id: id.clone(),
+            name: name.to_string(),
+            mime: mime.to_string(),
+            size,
+            bytes,
+        };
+        Ok(self.assets.entry(id).or_insert(asset))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Asset> {
+        self.assets.get(id)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Asset> {
+        self.assets.shift_remove(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Bytes held by all assets.
+    pub fn total_bytes(&self) -> usize {
+        self.assets.values().map(|a| a.bytes.len()).sum()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Header-only PNG of the given size.
+    pub(crate) fn png(width: u32, height: u32, seed: u8) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        bytes.extend(width.to_be_bytes());
+        bytes.extend(height.to_be_bytes());
+        bytes.push(seed);
+        bytes
+    }
+
+    #[test]
+    fn test_image_size() {
+        assert_eq!(
+            image_size(&png(640, 480, 0)),
+            Some(ImageSize {
+                width: 640,
+                height: 480
+            })
+        );
+        let gif = b"GIF89a\x20\x03\x58\x02".to_vec();
+        assert_eq!(image_size(&gif).unwrap().height, 600);
+        // SOI, an APP0 segment, then SOF0 with height 300 and width 400.
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x01,
+            0x2c, 0x01, 0x90,
+        ];
+        assert_eq!(
+            image_size(&jpeg),
+            Some(ImageSize {
+                width: 400,
+                height: 300
+            })
+        );
+        assert_eq!(image_size(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_dedupe_by_content() {
+        let mut registry = AssetRegistry::new();
+        let first = registry
+            .add_image("shot.png", "image/png", png(10, 10, 1))
+            .unwrap()
+            .id
+            .clone();
+        let again = registry
+            .add_image("copy.png", "image/png", png(10, 10, 1))
+            .unwrap();
+        assert_eq!(again.id, first);
+        assert_eq!(again.name, "shot.png");
+        registry
+            .add_image("other.png", "image/png", png(10, 10, 2))
+            .unwrap();
+        assert_eq!(registry.len(), 2);
+
+        let mut small = AssetRegistry::new().with_max_bytes(8);
+        assert!(matches!(
+            small.add_image("shot.png", "image/png", png(10, 10, 1)),
+            Err(AssetError::TooLarge { size: 25, .. })
+        ));
+        assert!(matches!(
+            registry.add_image("notes.txt", "text/plain", b"hi".to_vec()),
+            Err(AssetError::NotAnImage { .. })
+        ));
+    }
+}
@@ -1,7 +1,16 @@
 //! Studio configuration.
 
+pub mod layered;
+
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::designer::css::OutputOrdering;
+use crate::preview::{DevicePreset, PreviewFrame, builtin_devices};
+use crate::store::limits::StoreLimits;
+
+pub use layered::{ConfigError, ConfigSource, ConfigWarning, LayeredConfig};
+
 /// Studio configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StudioConfig {
@@ -16,6 +25,24 @@ pub struct StudioConfig {
     /// Features enabled.
     #[serde(default)]
     pub features: Features,
+    /// External status badge styles.
+    #[serde(default)]
+    pub statuses: StatusConfig,
+    /// Recorded as `modified_by` on entities changed in this session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Entity count and memory limits for the store.
+    #[serde(default)]
+    pub limits: StoreLimits,
+    /// Preview device presets.
+    #[serde(default)]
+    pub preview: PreviewConfig,
+    /// Component scaffolding.
+    #[serde(default)]
+    pub scaffold: ScaffoldConfig,
+    /// Order of tokens and components in generated CSS and exports.
+    #[serde(default, skip_serializing_if = "OutputOrdering::is_default")]
+    pub output_ordering: OutputOrdering,
 }
 
 fn default_version() -> String {
@@ -29,6 +56,12 @@ impl Default for StudioConfig {
             version: default_version(),
             theme: ThemeRef::default(),
             features: Features::default(),
+            statuses: StatusConfig::default(),
+            author: None,
+            limits: StoreLimits::default(),
+            preview: PreviewConfig::default(),
+            scaffold: ScaffoldConfig::default(),
+            output_ordering: OutputOrdering::default(),
         }
     }
 }
@@ -85,6 +118,9 @@ pub struct Features {
     /// Enable code generation.
     #[serde(default = "default_true")]
     pub codegen: bool,
+    /// Panels switched on or off by id; unlisted panels are on.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub panels: IndexMap<String, bool>,
 }
 
 fn default_true() -> bool {
@@ -100,6 +136,123 @@ impl Default for Features {
             live_preview: true,
             yaml_export: true,
             codegen: true,
+            panels: IndexMap::new(),
+        }
+    }
+}
+
+impl Features {
+    /// Whether the panel `id` is switched on.
+    pub fn panel_enabled(&self, id: &str) -> bool {
+        self.panels.get(id).copied().unwrap_or(true)
+    }
+}
+
+/// Default staleness limit for external statuses: one week.
+const DEFAULT_STALE_AFTER_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Status badge configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusConfig {
+    /// Icon and color per status name.
+    #[serde(default)]
+    pub styles: IndexMap<String, StatusStyle>,
+    /// Statuses older than this are shown as stale.
+    #[serde(default = "default_stale_after_secs")]
+    pub stale_after_secs: u64,
+}
+
+fn default_stale_after_secs() -> u64 {
+    DEFAULT_STALE_AFTER_SECS
+}
+
+impl Default for StatusConfig {
+    fn default() -> Self {
+        Self {
+            styles: IndexMap::new(),
+            stale_after_secs: DEFAULT_STALE_AFTER_SECS,
+        }
+    }
+}
+
+impl StatusConfig {
+    /// Style for a status, falling back to a neutral badge.
+    pub fn style_for(&self, status: &str) -> StatusStyle {
+        self.styles
+            .get(status)
+            .cloned()
+            .unwrap_or_else(StatusStyle::fallback)
+    }
+
+    /// Whether a status updated at `updated_at` is stale at `now` (Unix seconds).
+    pub fn is_stale(&self, updated_at: u64, now: u64) -> bool {
+        now.saturating_sub(updated_at) > self.stale_after_secs
+    }
+}
+
+/// Badge icon and color.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusStyle {
+    pub icon: String,
+    pub color: String,
+}
+
+impl StatusStyle {
+    pub fn new(icon: impl Into<String>, color: impl Into<String>) -> Self {
+        Self {
+            icon: icon.into(),
+            color: color.into(),
+        }
+    }
+
+    pub fn fallback() -> Self {
+        Self::new("circle", "#64748b")
+    }
+}
+
+/// Component scaffolding configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaffoldConfig {
+    /// Directory of user templates, relative to the project.
+    #[serde(default = "default_templates_dir")]
+    pub templates_dir: String,
+}
+
+fn default_templates_dir() -> String {
+    "templates".to_string()
+}
+
+impl Default for ScaffoldConfig {
+    fn default() -> Self {
+        Self {
+            templates_dir: default_templates_dir(),
         }
     }
 }
+
+/// Preview panel configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreviewConfig {
+    /// Extra device presets by id; an id of a built-in preset replaces it.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub devices: IndexMap<String, DevicePreset>,
+}
+
+impl PreviewConfig {
+    /// Built-in presets followed by configured ones.
+    pub fn devices(&self) -> IndexMap<String, DevicePreset> {
+        let mut devices = builtin_devices();
+        devices.extend(self.devices.clone());
+        devices
+    }
+
+    /// A device frame for a preset id.
+    pub fn frame(&self, id: &str) -> Option<PreviewFrame> {
+        let preset = self
+            .devices
+            .get(id)
+            .cloned()
+            .or_else(|| builtin_devices().shift_remove(id))?;
+        Some(PreviewFrame::device(id, preset))
+    }
+}
@@ -0,0 +1,418 @@
+//! Layered configuration.
+//!
+//! Sources are merged in order, each overriding earlier ones key by key,
+//! e.g. defaults, then the user's config, then the project's. Keys are
+//! dotted paths (`theme.overrides.primary`); the layer that supplied each
+//! effective value is kept for [`LayeredConfig::origin_of`].
+
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::StudioConfig;
+
+/// Name of the [`ConfigSource::Default`] layer.
+pub const DEFAULT_LAYER: &str = "default";
+
+/// Renamed keys, old prefix to new. Old keys still load, with a notice.
+const DEPRECATED_KEYS: [(&str, &str); 3] = [
+    ("features.scaffold", "features.component_scaffold"),
+    ("statuses.stale_after", "statuses.stale_after_secs"),
+    ("scaffold.templates", "scaffold.templates_dir"),
+];
+
+/// One configuration layer.
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    /// The built-in [`StudioConfig::default`].
+    Default,
+    /// A TOML document.
+    Toml { name: String, text: String },
+    /// A JSON document.
+    Json { name: String, text: String },
+    /// Values by dotted key, e.g. from the command line.
+    Values {
+        name: String,
+        values: IndexMap<String, Value>,
+    },
+}
+
+impl ConfigSource {
+    pub fn toml(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self::Toml {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+
+    pub fn json(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self::Json {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+
+    pub fn values(name: impl Into<String>, values: IndexMap<String, Value>) -> Self {
+        Self::Values {
+            name: name.into(),
+            values,
+        }
+    }
+
+    /// Layer name reported by [`LayeredConfig::origin_of`].
+    pub fn name(&self) -> &str {
+        match self {
+            ConfigSource::Default => DEFAULT_LAYER,
+            ConfigSource::Toml { name, .. }
+            | ConfigSource::Json { name, .. }
+            | ConfigSource::Values { name, .. } => name,
+        }
+    }
+
+    /// The layer's values by dotted key.
+    fn leaves(&self) -> Result<Vec<(String, Value)>, ConfigError> {
+        let layer = || self.name().to_string();
+        let value = match self {
+            ConfigSource::Default => {
+                serde_json::to_value(StudioConfig::default()).map_err(|source| {
+                    ConfigError::Json {
+                        layer: layer(),
+                        source,
+                    }
+                })?
+            }
+            ConfigSource::Toml { text, .. } => {
+                toml::from_str(text).map_err(|source| ConfigError::Toml {
+                    layer: layer(),
+                    source,
+                })?
+            }
+            ConfigSource::Json { text, .. } => {
+                serde_json::from_str(text).map_err(|source| ConfigError::Json {
+                    layer: layer(),
+                    source,
+                })?
+            }
+            ConfigSource::Values { values, .. } => {
+                let mut leaves = Vec::new();
+                for (key, value) in values {
+                    flatten(key, value, &mut leaves);
+                }
+                return Ok(leaves);
+            }
+        };
+        let mut leaves = Vec::new();
+        match value {
+            Value::Object(map) => {
+                for (key, value) in &map {
+                    flatten(key, value, &mut leaves);
+                }
+            }
+            _ => return Err(ConfigError::NotATable(layer())),
+        }
+        Ok(leaves)
+    }
+}
+
+/// Configuration loading error types.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// A TOML layer could not be parsed.
+    #[error("{layer}: {source}")]
+    Toml {
+        layer: String,
+        source: toml::de::Error,
+    },
+
+    /// A JSON layer could not be parsed.
+    #[error("{layer}: {source}")]
+    Json {
+        layer: String,
+        source: serde_json::Error,
+    },
+
+    /// A layer's top level is not a table of keys.
+    #[error("{0}: expected a table of keys")]
+    NotATable(String),
+
+    /// The merged values don't form a valid configuration.
+    #[error("Invalid configuration: {0}")]
+    Invalid(serde_json::Error),
+}
+
+/// Something worth telling the user about a layer that still loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigWarning {
+    /// The key isn't a configuration setting and was ignored.
+    UnknownKey { layer: String, key: String },
+    /// The key was renamed; its value was applied to `replacement`.
+    Deprecated {
+        layer: String,
+        key: String,
+        replacement: String,
+    },
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigWarning::UnknownKey { layer, key } => {
+                write!(f, "{}: unknown key \"{}\" ignored", layer, key)
+            }
+            ConfigWarning::Deprecated {
+                layer,
+                key,
+                replacement,
+            } => write!(
+                f,
+                "{}: \"{}\" is deprecated, use \"{}\"",
+                layer, key, replacement
+            ),
+        }
+    }
+}
+
+/// A merged configuration and where its values came from.
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// The effective configuration.
+    pub config: StudioConfig,
+    /// Layer names, in merge order.
+    pub layers: Vec<String>,
+    /// Unknown and deprecated keys, in layer order.
+    pub warnings: Vec<ConfigWarning>,
+    /// Dotted key to the index of the layer that set it last.
+    origins: IndexMap<String, usize>,
+}
+
+impl StudioConfig {
+    /// Merge `sources` in order; later layers override earlier ones key by
+    /// key. Keys no layer sets take their serde defaults.
+    pub fn load_layered(sources: &[ConfigSource]) -> Result<LayeredConfig, ConfigError> {
+        let mut merged = Value::Object(Map::new());
+        let mut origins = IndexMap::new();
+        let mut warnings = Vec::new();
+        let mut layer_leaves = Vec::new();
+        for (index, source) in sources.iter().enumerate() {
+            let mut leaves = source.leaves()?;
+            for (key, _) in &mut leaves {
+                if let Some(replacement) = replacement_for(key) {
+                    warnings.push(ConfigWarning::Deprecated {
+                        layer: source.name().to_string(),
+                        key: std::mem::replace(key, replacement.clone()),
+                        replacement,
+                    });
+                }
+            }
+            for (key, value) in &leaves {
+                set_path(&mut merged, key, value.clone());
+                origins.insert(key.clone(), index);
+            }
+            layer_leaves.push(leaves);
+        }
+
+        let config: StudioConfig = serde_json::from_value(merged).map_err(ConfigError::Invalid)?;
+        let effective = serde_json::to_value(&config).map_err(ConfigError::Invalid)?;
+        for (source, leaves) in sources.iter().zip(layer_leaves) {
+            for (key, value) in leaves {
+                let empty = value.is_null() || value.as_object().is_some_and(Map::is_empty);
+                if !empty && get_path(&effective, &key).is_none() {
+                    origins.shift_remove(&key);
+                    warnings.push(ConfigWarning::UnknownKey {
+                        layer: source.name().to_string(),
+                        key,
+                    });
+                }
+            }
+        }
+
+        Ok(LayeredConfig {
+            config,
+            layers: sources.iter().map(|s| s.name().to_string()).collect(),
+            warnings,
+            origins,
+        })
+    }
+}
+
+impl LayeredConfig {
+    /// Name of the layer that supplied the effective value of `key`. For
+    /// a section, the last layer that set anything in it. `None` when no
+    /// layer set it and the serde default applies.
+    pub fn origin_of(&self, key: &str) -> Option<&str> {
+        let section = format!("{}.", key);
+        let index = self
+            .origins
+            .iter()
+            .filter(|(k, _)| *k == key || k.starts_with(&section))
+            .map(|(_, index)| *index)
+            .max()?;
+        Some(&self.layers[index])
+    }
+
+    /// The effective configuration as TOML, for debugging.
+    pub fn to_effective_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(&self.config)
+    }
+}
+
+/// Push the leaves of `value` under `key`. Empty tables are leaves.
+fn flatten(key: &str, value: &Value, leaves: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (child, value) in map {
+                flatten(&format!("{}.{}", key, child), value, leaves);
+            }
+        }
+        _ => leaves.push((key.to_string(), value.clone())),
+    }
+}
+
+/// The new name of a deprecated key.
+fn replacement_for(key: &str) -> Option<String> {
+    DEPRECATED_KEYS.iter().find_map(|(old, new)| {
+        let rest = key.strip_prefix(old)?;
+        (rest.is_empty() || rest.starts_with('.')).then(|| format!("{}{}", new, rest))
+    })
+}
+
+fn set_path(root: &mut Value, key: &str, value: Value) {
+    let mut node = root;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        let map = node.as_object_mut().expect("just made an object");
+        if parts.peek().is_none() {
+            map.insert(part.to_string(), value);
+            return;
+        }
+        node = map.entry(part).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+fn get_path<'a>(root: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(root, |node, part| node.get(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layers() -> Vec<ConfigSource> {
+        let user = r##"
+            name = "Scratch"
+            author = "ana"
+
+            [theme.overrides]
+            primary = "#ff5500"
+            text = "#111111"
+
+            [features]
+            codegen = false
+            scaffold = false
+
+            [features.panels]
+            problems = false
+        "##;
+        let project = r##"{
+            "name": "Checkout",
+            "theme": {"overrides": {"primary": "#0055ff"}},
+            "features": {"panels": {"minimap": false}},
+            "statuses": {"stale_after_secs": 60},
+            "canvas": {"snap_grid": 8}
+        }"##;
+        vec![
+            ConfigSource::Default,
+            ConfigSource::toml("user", user),
+            ConfigSource::json("project", project),
+            ConfigSource::values(
+                "cli",
+                IndexMap::from([("scaffold.templates_dir".to_string(), "tpl".into())]),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_layers_merge_field_by_field() {
+        let layered = StudioConfig::load_layered(&layers()).unwrap();
+        let config = &layered.config;
+        assert_eq!(config.name, "Checkout");
+        assert_eq!(config.author.as_deref(), Some("ana"));
+        // The project overrides one override and keeps the user's other.
+        assert_eq!(config.theme.overrides.primary.as_deref(), Some("#0055ff"));
+        assert_eq!(config.theme.overrides.text.as_deref(), Some("#111111"));
+        assert_eq!(config.features.panels.len(), 2);
+        assert!(!config.features.codegen);
+        assert!(!config.features.component_scaffold);
+        assert!(config.features.css_designer);
+        assert_eq!(config.statuses.stale_after_secs, 60);
+        assert_eq!(config.scaffold.templates_dir, "tpl");
+
+        assert_eq!(
+            layered.warnings,
+            [
+                ConfigWarning::Deprecated {
+                    layer: "user".to_string(),
+                    key: "features.scaffold".to_string(),
+                    replacement: "features.component_scaffold".to_string(),
+                },
+                ConfigWarning::UnknownKey {
+                    layer: "project".to_string(),
+                    key: "canvas.snap_grid".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            layered.warnings[0].to_string(),
+            "user: \"features.scaffold\" is deprecated, use \"features.component_scaffold\""
+        );
+    }
+
+    #[test]
+    fn test_origin_of() {
+        let layered = StudioConfig::load_layered(&layers()).unwrap();
+        assert_eq!(
+            layered.origin_of("theme.overrides.primary"),
+            Some("project")
+        );
+        assert_eq!(layered.origin_of("theme.overrides.text"), Some("user"));
+        assert_eq!(
+            layered.origin_of("features.component_scaffold"),
+            Some("user")
+        );
+        assert_eq!(layered.origin_of("features.yaml_export"), Some("default"));
+        assert_eq!(layered.origin_of("scaffold"), Some("cli"));
+        assert_eq!(layered.origin_of("canvas.snap_grid"), None);
+        assert_eq!(layered.origin_of("features.scaffold"), None);
+
+        // Without the default layer, untouched keys have no origin.
+        let layered =
+            StudioConfig::load_layered(&[ConfigSource::toml("user", "name = \"Bare\"")]).unwrap();
+        assert_eq!(layered.origin_of("name"), Some("user"));
+        assert_eq!(layered.origin_of("version"), None);
+        assert_eq!(layered.config.version, "1.0.0");
+    }
+
+    #[test]
+    fn test_errors_and_effective_toml() {
+        let error = StudioConfig::load_layered(&[
+            ConfigSource::Default,
+            ConfigSource::toml("user", "[features]\ncodegen = \"no\""),
+        ])
+        .unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid(_)));
+        let error =
+            StudioConfig::load_layered(&[ConfigSource::json("project", "[1, 2]")]).unwrap_err();
+        assert_eq!(error.to_string(), "project: expected a table of keys");
+
+        let layered = StudioConfig::load_layered(&layers()).unwrap();
+        let toml = layered.to_effective_toml().unwrap();
+        assert!(toml.contains("name = \"Checkout\""));
+        let reparsed: StudioConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(reparsed.theme.overrides.primary.as_deref(), Some("#0055ff"));
+    }
+}
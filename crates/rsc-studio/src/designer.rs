@@ -1,7 +1,16 @@
 //! Designer modules.
 
 pub mod css;
+pub mod drop_preview;
+pub mod edge_tooltip;
+pub mod file_drop;
+pub mod flow_history;
+pub mod flow_style;
+pub mod keyboard;
+pub mod layers;
 pub mod navigation;
+pub mod presentation;
+pub mod value;
 
 pub use css::CssDesigner;
 pub use navigation::NavigationDesigner;
@@ -1,20 +1,99 @@
 //! Visual CSS designer.
 //! Allows visual editing of design tokens and styles.
 
+pub mod adoption;
+pub mod annotate;
+pub mod batch;
+pub mod cache;
+pub mod category;
+pub mod color;
+pub mod contrast;
+pub mod dark;
+pub mod diff;
+pub mod dimension;
+pub mod docs;
+pub mod edit;
+pub mod experiment;
+pub mod extract;
+pub mod graph;
+pub mod inherit;
+pub mod lint;
+pub mod ordering;
+pub mod parse;
+pub mod output;
+pub mod rename;
+pub mod scale;
+pub mod simulation;
+pub mod suggest;
+pub mod theme;
+pub mod transition;
+pub mod usage;
+pub mod variant;
+pub mod viewport;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::designer::value::{expect_color, expect_integer, expect_length};
+use crate::history::History;
+use crate::perf::trace_span;
+use crate::timestamp::Timestamps;
+
+pub use adoption::{AdoptionReport, FileAdoption, ReplacementSuggestion, adoption_report};
+pub use annotate::{AnnotationKind, CssAnnotation, CssAnnotator, ValueType, annotate_css};
+pub use batch::{NumericOp, TransformError, TransformReport};
+pub use cache::{CssCache, CssCacheStats};
+pub use category::{
+    CategoryListItem, CategoryListView, CategoryPresentation, CategoryPresentations,
+};
+pub use color::ColorBlindness;
+pub use contrast::{ContrastError, ContrastPair, ContrastReport, ContrastResult};
+pub use dark::{DarkDerivation, DarkModeReport, DeriveStrategy};
+pub use diff::{AdaptiveHalf, ChangedToken, TokenDiff, TokenDiffSummary, TokenEntry};
+pub use dimension::{
+    DimensionError, DimensionField, DimensionValue, FieldState, LengthUnit, RawReason, UnitPolicy,
+};
+pub use docs::{DeprecatedUse, TokenDocs};
+pub use edit::{TokenEdit, diff_tokens};
+pub use experiment::{ExperimentError, TokenExperiments};
+pub use extract::{ExtractOptions, ExtractResult, TokenMode};
+pub use graph::{TokenGraphView, tokens_to_flow};
+pub use inherit::ComponentStyleError;
+pub use lint::{ComponentLint, LintScope, StyleLint, StyleLintKind};
+pub use ordering::{OutputOrdering, natural_cmp};
+pub use output::{CssNode, CssOutputOptions};
+pub use parse::CssParseError;
+pub use rename::{RenameReport, TokenRenameError};
+pub use scale::{ColorParseError, ScaleError, generate_scale};
+pub use simulation::{DegradedContrast, simulate_color, simulate_flow_theme};
+pub use suggest::{Suggestion, SuggestionKind, SuggestContext, SuggestTarget, suggest_values};
+pub use theme::{DEFAULT_THEME, ThemeError, ThemeManager};
+pub use transition::{TransitionEntry, TransitionSpec, TransitionTime};
+pub use usage::UnusedReport;
+pub use variant::{GeneratedVariant, SizeStep, ToneMapping, VariantReport, VariantSpec};
+pub use viewport::{PreviewViewport, simulate_width};
+
 /// CSS Designer state.
 #[derive(Debug, Clone, Default)]
 pub struct CssDesigner {
-    /// Design tokens being edited.
+    /// Tokens of the active theme, being edited.
     pub tokens: DesignTokens,
+    /// All themes; the active one is synced from `tokens` on switch.
+    pub themes: ThemeManager,
     /// Selected token category.
     pub selected_category: TokenCategory,
     /// Selected token path (e.g., "colors.primary").
     pub selected_token: Option<String>,
     /// Preview mode.
     pub preview_mode: PreviewMode,
+    /// Undo/redo history of token edits.
+    pub history: History<Vec<TokenEdit>>,
+    /// Where dark values of adaptive tokens go in generated CSS.
+    pub dark_mode: DarkModeStrategy,
+    /// Simulated preview width; `None` fills the panel.
+    pub viewport: Option<PreviewViewport>,
+    /// Write token descriptions as comments in generated CSS.
+    pub token_comments: bool,
 }
 
 impl CssDesigner {
@@ -22,116 +101,168 @@ impl CssDesigner {
         Self::default()
     }
 
-    /// Load tokens from a design file.
+    /// Load tokens from a design file, starting a fresh history.
     pub fn load_tokens(&mut self, tokens: DesignTokens) {
         self.tokens = tokens;
+        self.history.clear();
+    }
+
+    /// Merge imported tokens into the current set instead of replacing it.
+    /// The merge undoes as one step.
+    ///
+    /// The selected token is cleared if the merge removed it.
+    pub fn merge_tokens(&mut self, incoming: DesignTokens, strategy: MergeStrategy) -> MergeReport {
+        let before = self.tokens.clone();
+        let report = self.tokens.merge(incoming, strategy);
+        self.record("Import tokens", &before);
+        if let Some(selected) = &self.selected_token
+            && report.removed.contains(selected)
+        {
+            self.selected_token = None;
+        }
+        report
     }
 
     /// Get a token value.
     pub fn get_token(&self, path: &str) -> Option<&TokenValue> {
-        let parts: Vec<&str> = path.split('.').collect();
-        if parts.len() < 2 {
-            return None;
-        }
+        let (prefix, name) = path.rsplit_once('.')?;
+        self.tokens
+            .categories()
+            .into_iter()
+            .find(|(p, _)| *p == prefix)
+            .and_then(|(_, map)| map.get(name))
+    }
 
-        match parts[0] {
-            "colors" => self.tokens.colors.get(parts[1]).map(|v| v),
-            "spacing" => self.tokens.spacing.get(parts[1]),
-            "radius" => self.tokens.radius.get(parts[1]),
-            "shadows" => self.tokens.shadows.get(parts[1]),
-            "typography" => {
-                if parts.len() < 3 {
-                    return None;
-                }
-                match parts[1] {
-                    "fonts" => self.tokens.typography.fonts.get(parts[2]),
-                    "sizes" => self.tokens.typography.sizes.get(parts[2]),
-                    "weights" => self.tokens.typography.weights.get(parts[2]),
-                    _ => None,
-                }
-            }
-            _ => None,
-        }
+    /// Generate CSS variables from tokens.
+    ///
+    /// Adaptive tokens put their light value in `:root` and their dark
+    /// value in the block chosen by `dark_mode`. Scale tokens produce one
+    /// variable per step.
+    pub fn generate_css(&self) -> String {
+        tokens_css(&self.tokens, &self.dark_mode, ":root", self.token_comments)
     }
 
-    /// Set a token value.
-    pub fn set_token(&mut self, path: &str, value: TokenValue) {
-        let parts: Vec<&str> = path.split('.').collect();
-        if parts.len() < 2 {
-            return;
-        }
+    /// [`generate_css`](Self::generate_css) with tokens and scale steps in
+    /// `ordering`, usually the store's `output_ordering`.
+    pub fn generate_css_ordered(&self, ordering: &OutputOrdering) -> String {
+        tokens_css(
+            &self.tokens.ordered(ordering),
+            &self.dark_mode,
+            ":root",
+            self.token_comments,
+        )
+    }
 
-        match parts[0] {
-            "colors" => {
-                self.tokens.colors.insert(parts[1].to_string(), value);
-            }
-            "spacing" => {
-                self.tokens.spacing.insert(parts[1].to_string(), value);
-            }
-            "radius" => {
-                self.tokens.radius.insert(parts[1].to_string(), value);
-            }
-            "shadows" => {
-                self.tokens.shadows.insert(parts[1].to_string(), value);
-            }
-            _ => {}
-        }
+    /// Set where dark values go in generated CSS.
+    pub fn with_dark_mode(mut self, dark_mode: DarkModeStrategy) -> Self {
+        self.dark_mode = dark_mode;
+        self
     }
 
-    /// Generate CSS variables from tokens.
-    pub fn generate_css(&self) -> String {
-        let mut css = String::from(":root {\n");
+    /// Write token descriptions as comments in generated CSS.
+    pub fn with_token_comments(mut self, comments: bool) -> Self {
+        self.token_comments = comments;
+        self
+    }
+}
 
-        // Colors
-        for (name, value) in &self.tokens.colors {
-            if let TokenValue::Simple(v) = value {
-                css.push_str(&format!("  --color-{}: {};\n", name, v));
-            }
-        }
+/// CSS variables for `tokens`, declared under `scope`. With `comments`,
+/// each described token is preceded by its description.
+fn tokens_css(
+    tokens: &DesignTokens,
+    dark_mode: &DarkModeStrategy,
+    scope: &str,
+    comments: bool,
+) -> String {
+    trace_span!(
+        "css",
+        "generate_css",
+        count = tokens.categories().iter().map(|(_, t)| t.len()).sum::<usize>()
+    );
+    let chunks: Vec<(String, String)> = tokens
+        .categories()
+        .into_iter()
+        .map(|(prefix, map)| category_css(prefix, map, &tokens.docs, comments))
+        .collect();
+    assemble_tokens_css(&chunks, dark_mode, scope)
+}
 
-        // Spacing
-        for (name, value) in &self.tokens.spacing {
-            if let TokenValue::Simple(v) = value {
-                css.push_str(&format!("  --spacing-{}: {};\n", name, v));
-            }
+/// Declarations of one token category, split into the light block and the
+/// dark block.
+fn category_css(
+    prefix: &str,
+    tokens: &IndexMap<String, TokenValue>,
+    docs: &IndexMap<String, TokenDocs>,
+    comments: bool,
+) -> (String, String) {
+    let mut css = String::new();
+    let mut dark = String::new();
+    for (name, value) in tokens {
+        let path = format!("{}.{}", prefix, name);
+        let Some(var) = path_to_css_var(&path) else {
+            continue;
+        };
+        if comments && let Some(description) = docs.get(&path).and_then(|d| d.description.as_ref())
+        {
+            css.push_str(&format!("  /* {} */\n", description.replace("*/", "* /")));
         }
-
-        // Radius
-        for (name, value) in &self.tokens.radius {
-            if let TokenValue::Simple(v) = value {
-                css.push_str(&format!("  --radius-{}: {};\n", name, v));
+        match value {
+            TokenValue::Simple(v) => css.push_str(&format!("  {}: {};\n", var, v)),
+            TokenValue::Adaptive { light, dark: d } => {
+                css.push_str(&format!("  {}: {};\n", var, light));
+                dark.push_str(&format!("  {}: {};\n", var, d));
             }
-        }
-
-        // Shadows
-        for (name, value) in &self.tokens.shadows {
-            if let TokenValue::Simple(v) = value {
-                css.push_str(&format!("  --shadow-{}: {};\n", name, v));
+            TokenValue::Scale(steps) => {
+                for (step, v) in steps {
+                    css.push_str(&format!("  {}-{}: {};\n", var, step, v));
+                }
             }
         }
+    }
+    (css, dark)
+}
 
-        css.push_str("}\n");
-        css
+/// Join category chunks, in order, into the `scope` block and its dark block.
+fn assemble_tokens_css<'a>(
+    chunks: impl IntoIterator<Item = &'a (String, String)>,
+    dark_mode: &DarkModeStrategy,
+    scope: &str,
+) -> String {
+    let mut css = format!("{} {{\n", scope);
+    let mut dark = String::new();
+    for (light, d) in chunks {
+        css.push_str(light);
+        dark.push_str(d);
     }
+    css.push_str("}\n");
+    if !dark.is_empty() {
+        css.push('\n');
+        css.push_str(&dark_mode.wrap(&dark, scope));
+    }
+    css
 }
 
 /// Design tokens structure.
+///
+/// Serialized through `docs::TokenFile`, which writes each token's docs
+/// inline with its value.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "docs::TokenFile", into = "docs::TokenFile")]
 pub struct DesignTokens {
-    #[serde(default)]
     pub colors: IndexMap<String, TokenValue>,
-    #[serde(default)]
     pub spacing: IndexMap<String, TokenValue>,
-    #[serde(default)]
     pub radius: IndexMap<String, TokenValue>,
-    #[serde(default)]
     pub shadows: IndexMap<String, TokenValue>,
-    #[serde(default)]
     pub typography: TypographyTokens,
-    #[serde(default)]
     pub transitions: IndexMap<String, TokenValue>,
-    #[serde(default)]
     pub z_index: IndexMap<String, TokenValue>,
+    /// Created/modified times per token path.
+    pub metadata: IndexMap<String, Timestamps>,
+    /// Description, deprecation and tags per token path.
+    pub docs: IndexMap<String, TokenDocs>,
+    /// Foreground/background pairs checked for contrast by `validate`.
+    /// Empty means the default pairs.
+    pub contrast_pairs: Vec<ContrastPair>,
 }
 
 impl DesignTokens {
@@ -157,37 +288,10 @@ impl DesignTokens {
 
     /// Get all token paths.
     pub fn all_paths(&self) -> Vec<String> {
-        let mut paths = Vec::new();
-
-        for name in self.colors.keys() {
-            paths.push(format!("colors.{}", name));
-        }
-        for name in self.spacing.keys() {
-            paths.push(format!("spacing.{}", name));
-        }
-        for name in self.radius.keys() {
-            paths.push(format!("radius.{}", name));
-        }
-        for name in self.shadows.keys() {
-            paths.push(format!("shadows.{}", name));
-        }
-        for name in self.typography.fonts.keys() {
-            paths.push(format!("typography.fonts.{}", name));
-        }
-        for name in self.typography.sizes.keys() {
-            paths.push(format!("typography.sizes.{}", name));
-        }
-        for name in self.typography.weights.keys() {
-            paths.push(format!("typography.weights.{}", name));
-        }
-        for name in self.transitions.keys() {
-            paths.push(format!("transitions.{}", name));
-        }
-        for name in self.z_index.keys() {
-            paths.push(format!("z-index.{}", name));
-        }
-
-        paths
+        self.categories()
+            .into_iter()
+            .flat_map(|(prefix, map)| map.keys().map(move |name| format!("{}.{}", prefix, name)))
+            .collect()
     }
 
     /// Validate all tokens.
@@ -215,6 +319,13 @@ impl DesignTokens {
             }
         }
 
+        // Validate font sizes
+        for (name, value) in &self.typography.sizes {
+            if let Some(err) = validate_dimension(name, value, "typography.sizes") {
+                errors.push(err);
+            }
+        }
+
         // Validate z-index
         for (name, value) in &self.z_index {
             if let Some(err) = validate_number(name, value, "z-index") {
@@ -222,6 +333,9 @@ impl DesignTokens {
             }
         }
 
+        errors.extend(self.contrast_warnings());
+        errors.extend(self.deprecation_warnings());
+
         errors
     }
 
@@ -312,6 +426,235 @@ impl DesignTokens {
             + self.transitions.len()
             + self.z_index.len()
     }
+
+    /// Token maps paired with their path prefix, in category order.
+    pub fn categories(&self) -> [(&'static str, &IndexMap<String, TokenValue>); 9] {
+        [
+            ("colors", &self.colors),
+            ("spacing", &self.spacing),
+            ("radius", &self.radius),
+            ("shadows", &self.shadows),
+            ("typography.fonts", &self.typography.fonts),
+            ("typography.sizes", &self.typography.sizes),
+            ("typography.weights", &self.typography.weights),
+            ("transitions", &self.transitions),
+            ("z-index", &self.z_index),
+        ]
+    }
+
+    /// Mutable token maps paired with their path prefix, in category order.
+    pub fn categories_mut(&mut self) -> [(&'static str, &mut IndexMap<String, TokenValue>); 9] {
+        [
+            ("colors", &mut self.colors),
+            ("spacing", &mut self.spacing),
+            ("radius", &mut self.radius),
+            ("shadows", &mut self.shadows),
+            ("typography.fonts", &mut self.typography.fonts),
+            ("typography.sizes", &mut self.typography.sizes),
+            ("typography.weights", &mut self.typography.weights),
+            ("transitions", &mut self.transitions),
+            ("z-index", &mut self.z_index),
+        ]
+    }
+
+    /// Merge another token set into this one.
+    pub fn merge(&mut self, incoming: DesignTokens, strategy: MergeStrategy) -> MergeReport {
+        let mut report = MergeReport::default();
+        let mut incoming = incoming;
+
+        for ((prefix, target), (_, source)) in self
+            .categories_mut()
+            .into_iter()
+            .zip(incoming.categories_mut())
+        {
+            let source = std::mem::take(source);
+
+            if strategy == MergeStrategy::Replace {
+                for name in target.keys().filter(|name| !source.contains_key(*name)) {
+                    report.removed.push(format!("{}.{}", prefix, name));
+                }
+                target.retain(|name, _| source.contains_key(name));
+            }
+
+            for (name, value) in source {
+                let path = format!("{}.{}", prefix, name);
+                match target.get_mut(&name) {
+                    None => {
+                        target.insert(name, value);
+                        report.added.push(path);
+                    }
+                    Some(existing) if *existing == value => {
+                        report.unchanged.push(path);
+                    }
+                    Some(_) if strategy == MergeStrategy::PreferExisting => {
+                        report.skipped.push(path);
+                    }
+                    Some(existing) => {
+                        *existing = value;
+                        report.overwritten.push(path);
+                    }
+                }
+            }
+        }
+
+        for path in &report.removed {
+            self.docs.shift_remove(path);
+        }
+        for path in report
+            .added
+            .iter()
+            .chain(&report.overwritten)
+            .chain(&report.unchanged)
+        {
+            if let Some(docs) = incoming.docs.shift_remove(path) {
+                self.docs.insert(path.clone(), docs);
+            }
+        }
+
+        report
+    }
+
+    /// CSS custom properties for every token, in category order.
+    ///
+    /// Adaptive tokens resolve to their dark value when `dark` is set; scale
+    /// tokens produce one property per step.
+    pub fn css_variables(&self, dark: bool) -> Vec<(String, String)> {
+        let mut vars = Vec::new();
+        for (prefix, tokens) in self.categories() {
+            for (name, value) in tokens {
+                let Some(var) = path_to_css_var(&format!("{}.{}", prefix, name)) else {
+                    continue;
+                };
+                match value {
+                    TokenValue::Simple(v) => vars.push((var, v.clone())),
+                    TokenValue::Adaptive { light, dark: d } => {
+                        vars.push((var, if dark { d.clone() } else { light.clone() }))
+                    }
+                    TokenValue::Scale(steps) => {
+                        for (step, v) in steps {
+                            vars.push((format!("{}-{}", var, step), v.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        vars
+    }
+}
+
+/// Named token themes, e.g. "light", "dark" or a brand variant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeSet {
+    #[serde(default)]
+    pub themes: IndexMap<String, DesignTokens>,
+    /// Theme shown first; falls back to the first theme.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+impl ThemeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a theme.
+    pub fn with_theme(mut self, name: impl Into<String>, tokens: DesignTokens) -> Self {
+        self.themes.insert(name.into(), tokens);
+        self
+    }
+
+    /// Build "light" and "dark" themes by resolving adaptive tokens.
+    pub fn from_adaptive(tokens: &DesignTokens) -> Self {
+        let resolve = |dark: bool| {
+            let mut resolved = tokens.clone();
+            for (_, map) in resolved.categories_mut() {
+                for value in map.values_mut() {
+                    if let TokenValue::Adaptive { light, dark: d } = value {
+                        *value = TokenValue::Simple(if dark { d.clone() } else { light.clone() });
+                    }
+                }
+            }
+            resolved
+        };
+        Self::new()
+            .with_theme("light", resolve(false))
+            .with_theme("dark", resolve(true))
+    }
+
+    /// Name of the default theme.
+    pub fn default_theme(&self) -> Option<&str> {
+        self.default
+            .as_deref()
+            .filter(|name| self.themes.contains_key(*name))
+            .or_else(|| self.themes.keys().next().map(String::as_str))
+    }
+}
+
+/// How conflicting tokens are resolved when merging token sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MergeStrategy {
+    /// Discard all existing tokens and take the incoming set.
+    Replace,
+    /// Keep existing values; only add tokens that don't exist yet.
+    #[default]
+    PreferExisting,
+    /// Overwrite conflicting tokens but keep local-only tokens.
+    PreferIncoming,
+}
+
+/// Outcome of a token merge, as token paths.
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    /// Tokens that did not exist before.
+    pub added: Vec<String>,
+    /// Existing tokens whose value was replaced.
+    pub overwritten: Vec<String>,
+    /// Conflicting tokens left untouched.
+    pub skipped: Vec<String>,
+    /// Incoming tokens identical to the existing value.
+    pub unchanged: Vec<String>,
+    /// Existing tokens dropped by [`MergeStrategy::Replace`].
+    pub removed: Vec<String>,
+}
+
+impl MergeReport {
+    /// Whether the merge changed anything.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.overwritten.is_empty() && self.removed.is_empty()
+    }
+
+    /// The part of the report for one category, e.g. `typography.sizes`.
+    pub fn for_category(&self, prefix: &str) -> MergeReport {
+        let filter = |paths: &Vec<String>| -> Vec<String> {
+            paths
+                .iter()
+                .filter(|p| {
+                    p.strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with('.'))
+                })
+                .cloned()
+                .collect()
+        };
+        MergeReport {
+            added: filter(&self.added),
+            overwritten: filter(&self.overwritten),
+            skipped: filter(&self.skipped),
+            unchanged: filter(&self.unchanged),
+            removed: filter(&self.removed),
+        }
+    }
+
+    /// Per-category reports for the categories the merge touched.
+    pub fn by_category(&self) -> IndexMap<&'static str, MergeReport> {
+        DesignTokens::default()
+            .categories()
+            .into_iter()
+            .map(|(prefix, _)| (prefix, self.for_category(prefix)))
+            .filter(|(_, report)| {
+                !(report.is_empty() && report.skipped.is_empty() && report.unchanged.is_empty())
+            })
+            .collect()
+    }
 }
 
 /// Token import error.
@@ -337,6 +680,24 @@ pub enum ValidationSeverity {
     Info,
 }
 
+/// Convert a token path to its CSS variable name (e.g. "colors.primary" to "--color-primary").
+pub fn path_to_css_var(path: &str) -> Option<String> {
+    let (var, name) = [
+        ("colors.", "color"),
+        ("spacing.", "spacing"),
+        ("radius.", "radius"),
+        ("shadows.", "shadow"),
+        ("typography.fonts.", "font-family"),
+        ("typography.sizes.", "font-size"),
+        ("typography.weights.", "font-weight"),
+        ("transitions.", "transition"),
+        ("z-index.", "z"),
+    ]
+    .iter()
+    .find_map(|(prefix, var)| path.strip_prefix(prefix).map(|name| (*var, name)))?;
+    Some(format!("--{}-{}", var, name))
+}
+
 /// Convert CSS variable name to token path.
 fn css_var_to_path(var_name: &str) -> String {
     let parts: Vec<&str> = var_name.splitn(2, '-').collect();
@@ -371,31 +732,7 @@ fn css_var_to_path(var_name: &str) -> String {
 
 /// Validate a color token.
 fn validate_color(name: &str, value: &TokenValue) -> Option<TokenValidationError> {
-    let validate_color_value = |v: &str| -> Option<String> {
-        let v = v.trim();
-        // Check for common color formats
-        if v.starts_with('#') {
-            // Hex color
-            let hex = &v[1..];
-            if hex.len() != 3 && hex.len() != 6 && hex.len() != 8 {
-                return Some("Invalid hex color format".to_string());
-            }
-            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
-                return Some("Invalid hex characters in color".to_string());
-            }
-        } else if v.starts_with("rgb") || v.starts_with("hsl") {
-            // RGB/HSL - basic check for parentheses
-            if !v.contains('(') || !v.contains(')') {
-                return Some("Invalid color function syntax".to_string());
-            }
-        } else if v.starts_with("var(") {
-            // CSS variable reference - valid
-        } else if v.is_empty() {
-            return Some("Color value is empty".to_string());
-        }
-        // Named colors and other formats are accepted
-        None
-    };
+    let validate_color_value = |v: &str| expect_color(v).err().map(|e| e.to_string());
 
     match value {
         TokenValue::Simple(v) => {
@@ -410,15 +747,15 @@ fn validate_color(name: &str, value: &TokenValue) -> Option<TokenValidationError
         TokenValue::Adaptive { light, dark } => {
             if let Some(msg) = validate_color_value(light) {
                 return Some(TokenValidationError {
-                    path: format!("colors.{} (light)", name),
-                    message: msg,
+                    path: format!("colors.{}", name),
+                    message: format!("{} (light)", msg),
                     severity: ValidationSeverity::Warning,
                 });
             }
             if let Some(msg) = validate_color_value(dark) {
                 return Some(TokenValidationError {
-                    path: format!("colors.{} (dark)", name),
-                    message: msg,
+                    path: format!("colors.{}", name),
+                    message: format!("{} (dark)", msg),
                     severity: ValidationSeverity::Warning,
                 });
             }
@@ -427,8 +764,8 @@ fn validate_color(name: &str, value: &TokenValue) -> Option<TokenValidationError
             for (key, v) in scale {
                 if let Some(msg) = validate_color_value(v) {
                     return Some(TokenValidationError {
-                        path: format!("colors.{}.{}", name, key),
-                        message: msg,
+                        path: format!("colors.{}", name),
+                        message: format!("{} (step {})", msg, key),
                         severity: ValidationSeverity::Warning,
                     });
                 }
@@ -438,58 +775,32 @@ fn validate_color(name: &str, value: &TokenValue) -> Option<TokenValidationError
     None
 }
 
-/// Validate a dimension token (spacing, radius).
+/// Validate a dimension token (spacing, radius, font sizes).
 fn validate_dimension(name: &str, value: &TokenValue, category: &str) -> Option<TokenValidationError> {
-    let validate_dim = |v: &str| -> Option<String> {
-        let v = v.trim();
-        if v.is_empty() {
-            return Some("Value is empty".to_string());
-        }
-        if v.starts_with("var(") {
-            return None; // CSS variable reference
-        }
-        // Check for valid CSS units
-        let units = ["px", "rem", "em", "%", "vh", "vw", "vmin", "vmax", "ch", "ex"];
-        let has_unit = units.iter().any(|u| v.ends_with(u));
-        let is_zero = v == "0";
-        let is_calc = v.starts_with("calc(");
-
-        if !has_unit && !is_zero && !is_calc {
-            return Some("Missing or invalid CSS unit".to_string());
-        }
-        None
-    };
-
-    if let TokenValue::Simple(v) = value {
-        if let Some(msg) = validate_dim(v) {
-            return Some(TokenValidationError {
-                path: format!("{}.{}", category, name),
-                message: msg,
-                severity: ValidationSeverity::Warning,
-            });
-        }
+    let validate_dim = |v: &str| expect_length(v).err().map(|e| e.to_string());
+
+    if let TokenValue::Simple(v) = value
+        && let Some(msg) = validate_dim(v)
+    {
+        return Some(TokenValidationError {
+            path: format!("{}.{}", category, name),
+            message: msg,
+            severity: ValidationSeverity::Warning,
+        });
     }
     None
 }
 
 /// Validate a numeric token (z-index).
 fn validate_number(name: &str, value: &TokenValue, category: &str) -> Option<TokenValidationError> {
-    if let TokenValue::Simple(v) = value {
-        let v = v.trim();
-        if v.is_empty() {
-            return Some(TokenValidationError {
-                path: format!("{}.{}", category, name),
-                message: "Value is empty".to_string(),
-                severity: ValidationSeverity::Warning,
-            });
-        }
-        if !v.starts_with("var(") && v.parse::<i32>().is_err() {
-            return Some(TokenValidationError {
-                path: format!("{}.{}", category, name),
-                message: "Expected numeric value".to_string(),
-                severity: ValidationSeverity::Warning,
-            });
-        }
+    if let TokenValue::Simple(v) = value
+        && let Err(e) = expect_integer(v)
+    {
+        return Some(TokenValidationError {
+            path: format!("{}.{}", category, name),
+            message: e.to_string(),
+            severity: ValidationSeverity::Warning,
+        });
     }
     None
 }
@@ -512,19 +823,15 @@ impl TokenUsageTracker {
     }
 
     /// Mark tokens from CSS variable references in a string.
+    ///
+    /// Fallbacks are skipped, so `var(--spacing-md, 8px)` marks `spacing.md`.
     pub fn mark_from_css(&mut self, css: &str) {
-        let mut remaining = css;
-        while let Some(start) = remaining.find("var(--") {
-            let after_var = &remaining[start + 6..];
-            if let Some(end) = after_var.find(')') {
-                let var_name = &after_var[..end];
+        for (_, _, name) in extract::var_calls(css) {
+            if let Some(var_name) = name.strip_prefix("--") {
                 let path = css_var_to_path(var_name);
                 if !path.is_empty() {
                     self.mark_used(&path);
                 }
-                remaining = &after_var[end..];
-            } else {
-                break;
             }
         }
     }
@@ -551,7 +858,7 @@ pub struct TypographyTokens {
 }
 
 /// Token value (simple or structured).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum TokenValue {
     Simple(String),
@@ -587,6 +894,47 @@ pub enum PreviewMode {
     Both,
 }
 
+/// How generated CSS switches to dark values.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DarkModeStrategy {
+    /// Follow the OS setting with `@media (prefers-color-scheme: dark)`.
+    #[default]
+    MediaQuery,
+    /// Apply under a selector the app toggles, e.g. `.dark`.
+    Selector(String),
+}
+
+impl DarkModeStrategy {
+    /// Class-based dark mode, e.g. `DarkModeStrategy::class("dark")` for `.dark`.
+    pub fn class(name: &str) -> Self {
+        Self::Selector(format!(".{}", name.trim_start_matches('.')))
+    }
+
+    /// Wrap variable declarations in the dark-mode block for `scope`.
+    /// A selector strategy is combined with a theme scope on the same
+    /// element, e.g. `[data-theme="brand"].dark`.
+    fn wrap(&self, declarations: &str, scope: &str) -> String {
+        match self {
+            DarkModeStrategy::MediaQuery => {
+                let indented: String = declarations
+                    .lines()
+                    .map(|l| format!("  {}\n", l))
+                    .collect();
+                format!(
+                    "@media (prefers-color-scheme: dark) {{\n  {} {{\n{}  }}\n}}\n",
+                    scope, indented
+                )
+            }
+            DarkModeStrategy::Selector(selector) if scope == ":root" => {
+                format!("{} {{\n{}}}\n", selector, declarations)
+            }
+            DarkModeStrategy::Selector(selector) => {
+                format!("{}{} {{\n{}}}\n", scope, selector, declarations)
+            }
+        }
+    }
+}
+
 /// Component state variant.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum StateVariant {
@@ -678,7 +1026,7 @@ impl Breakpoint {
 }
 
 /// Component style definition.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComponentStyle {
     /// Base styles (applied to all states and breakpoints).
     #[serde(default)]
@@ -689,6 +1037,84 @@ pub struct ComponentStyle {
     /// Breakpoint-specific overrides.
     #[serde(default)]
     pub breakpoints: IndexMap<Breakpoint, StyleProperties>,
+    /// Listed in the component catalog export; off for internal components.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub include_in_catalog: bool,
+    /// Component whose style this one builds on. Its rules apply first and
+    /// this style's properties override them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Set on styles made by [`ComponentStyles::generate_variants`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated: Option<GeneratedVariant>,
+    /// Created/modified times.
+    #[serde(flatten)]
+    pub timestamps: Timestamps,
+}
+
+impl Default for ComponentStyle {
+    fn default() -> Self {
+        Self {
+            base: StyleProperties::default(),
+            states: IndexMap::new(),
+            breakpoints: IndexMap::new(),
+            include_in_catalog: true,
+            extends: None,
+            generated: None,
+            timestamps: Timestamps::default(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+impl ComponentStyle {
+    /// Generate the rules for one component, selected by class name.
+    pub fn to_css(&self, name: &str) -> String {
+        let mut css = String::new();
+
+        // Base styles
+        let base_css = self.base.to_css();
+        if !base_css.is_empty() {
+            css.push_str(&format!(".{} {{\n{}}}\n\n", name, base_css));
+        }
+
+        // State variants
+        for (state, props) in &self.states {
+            let state_css = props.to_css();
+            if !state_css.is_empty() {
+                css.push_str(&format!(
+                    ".{}{} {{\n{}}}\n\n",
+                    name,
+                    state.css_selector(),
+                    state_css
+                ));
+            }
+        }
+
+        // Breakpoint overrides
+        for (breakpoint, props) in &self.breakpoints {
+            let bp_css = props.to_css();
+            if !bp_css.is_empty()
+                && let Some(min_width) = breakpoint.min_width()
+            {
+                css.push_str(&format!(
+                    "@media (min-width: {}px) {{\n  .{} {{\n{}}}\n}}\n\n",
+                    min_width,
+                    name,
+                    bp_css.lines().map(|l| format!("  {}", l)).collect::<Vec<_>>().join("\n")
+                ));
+            }
+        }
+
+        css
+    }
 }
 
 /// CSS style properties.
@@ -787,6 +1213,9 @@ pub struct StyleProperties {
     pub cursor: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transition: Option<String>,
+    /// Structured transitions; when set, compiled into `transition`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transitions: Option<TransitionSpec>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transform: Option<String>,
 
@@ -811,9 +1240,66 @@ pub struct StyleProperties {
     pub overflow_x: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub overflow_y: Option<String>,
+
+    /// Properties outside [`Self::PROPERTY_NAMES`], kept verbatim.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub custom: IndexMap<String, String>,
 }
 
 impl StyleProperties {
+    /// CSS names of all supported properties.
+    pub const PROPERTY_NAMES: &'static [&'static str] = &[
+        "display",
+        "flex-direction",
+        "align-items",
+        "justify-content",
+        "gap",
+        "width",
+        "height",
+        "min-width",
+        "min-height",
+        "max-width",
+        "max-height",
+        "padding",
+        "padding-top",
+        "padding-right",
+        "padding-bottom",
+        "padding-left",
+        "margin",
+        "margin-top",
+        "margin-right",
+        "margin-bottom",
+        "margin-left",
+        "color",
+        "background",
+        "background-color",
+        "border",
+        "border-width",
+        "border-style",
+        "border-color",
+        "border-radius",
+        "box-shadow",
+        "font-family",
+        "font-size",
+        "font-weight",
+        "line-height",
+        "text-align",
+        "text-decoration",
+        "opacity",
+        "cursor",
+        "transition",
+        "transform",
+        "position",
+        "top",
+        "right",
+        "bottom",
+        "left",
+        "z-index",
+        "overflow",
+        "overflow-x",
+        "overflow-y",
+    ];
+
     /// Convert to CSS string.
     pub fn to_css(&self) -> String {
         let mut css = String::new();
@@ -879,7 +1365,12 @@ impl StyleProperties {
         // Effects
         add_prop!(opacity, "opacity");
         add_prop!(cursor, "cursor");
-        add_prop!(transition, "transition");
+        match &self.transitions {
+            Some(spec) if !spec.is_empty() => {
+                css.push_str(&format!("  transition: {};\n", spec.to_css()));
+            }
+            _ => add_prop!(transition, "transition"),
+        }
         add_prop!(transform, "transform");
 
         // Position
@@ -895,9 +1386,32 @@ impl StyleProperties {
         add_prop!(overflow_x, "overflow-x");
         add_prop!(overflow_y, "overflow-y");
 
+        for (name, value) in &self.custom {
+            css.push_str(&format!("  {}: {};\n", name, value));
+        }
+
         css
     }
 
+    /// These properties with any unset ones taken from `base`.
+    pub fn merged_over(&self, base: &StyleProperties) -> StyleProperties {
+        let mut merged = self.clone();
+        for name in Self::PROPERTY_NAMES {
+            if merged.get(name).is_none() {
+                merged.set(name, base.get(name).cloned());
+            }
+        }
+        if merged.transitions.is_none() {
+            merged.transitions = base.transitions.clone();
+        }
+        for (name, value) in &base.custom {
+            if !merged.custom.contains_key(name) {
+                merged.custom.insert(name.clone(), value.clone());
+            }
+        }
+        merged
+    }
+
     /// Get a property value by name.
     pub fn get(&self, name: &str) -> Option<&String> {
         match name {
@@ -1100,43 +1614,13 @@ impl ComponentStyles {
         self.styles.insert(component, style);
     }
 
-    /// Generate CSS for all component styles.
+    /// Generate CSS for all component styles, with `extends` applied.
     pub fn generate_css(&self) -> String {
         let mut css = String::new();
 
-        for (name, style) in &self.styles {
-            // Base styles
-            let base_css = style.base.to_css();
-            if !base_css.is_empty() {
-                css.push_str(&format!(".{} {{\n{}}}\n\n", name, base_css));
-            }
-
-            // State variants
-            for (state, props) in &style.states {
-                let state_css = props.to_css();
-                if !state_css.is_empty() {
-                    css.push_str(&format!(
-                        ".{}{} {{\n{}}}\n\n",
-                        name,
-                        state.css_selector(),
-                        state_css
-                    ));
-                }
-            }
-
-            // Breakpoint overrides
-            for (breakpoint, props) in &style.breakpoints {
-                let bp_css = props.to_css();
-                if !bp_css.is_empty() {
-                    if let Some(min_width) = breakpoint.min_width() {
-                        css.push_str(&format!(
-                            "@media (min-width: {}px) {{\n  .{} {{\n{}}}\n}}\n\n",
-                            min_width,
-                            name,
-                            bp_css.lines().map(|l| format!("  {}", l)).collect::<Vec<_>>().join("\n")
-                        ));
-                    }
-                }
+        for name in self.styles.keys() {
+            if let Some(style) = self.resolve(name) {
+                css.push_str(&style.to_css(name));
             }
         }
 
@@ -1165,6 +1649,110 @@ mod tests {
         assert!(css.contains("--spacing-md: 1rem"));
     }
 
+    fn mixed_tokens() -> DesignTokens {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#3b82f6".to_string()),
+        );
+        tokens.colors.insert(
+            "surface".to_string(),
+            TokenValue::Adaptive {
+                light: "#ffffff".to_string(),
+                dark: "#0f172a".to_string(),
+            },
+        );
+        let mut blue = IndexMap::new();
+        blue.insert("50".to_string(), "#eff6ff".to_string());
+        blue.insert("100".to_string(), "#dbeafe".to_string());
+        tokens.colors.insert("blue".to_string(), TokenValue::Scale(blue));
+        tokens.spacing.insert(
+            "md".to_string(),
+            TokenValue::Simple("1rem".to_string()),
+        );
+        tokens.shadows.insert(
+            "card".to_string(),
+            TokenValue::Adaptive {
+                light: "0 1px 2px #0002".to_string(),
+                dark: "none".to_string(),
+            },
+        );
+        tokens
+    }
+
+    #[test]
+    fn test_css_generation_media_query() {
+        let mut designer = CssDesigner::new();
+        designer.load_tokens(mixed_tokens());
+
+        assert_eq!(
+            designer.generate_css(),
+            r#":root {
+  --color-primary: #3b82f6;
+  --color-surface: #ffffff;
+  --color-blue-50: #eff6ff;
+  --color-blue-100: #dbeafe;
+  --spacing-md: 1rem;
+  --shadow-card: 0 1px 2px #0002;
+}
+
+@media (prefers-color-scheme: dark) {
+  :root {
+    --color-surface: #0f172a;
+    --shadow-card: none;
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_css_generation_class_strategy() {
+        let mut designer = CssDesigner::new().with_dark_mode(DarkModeStrategy::class("dark"));
+        designer.load_tokens(mixed_tokens());
+
+        assert_eq!(
+            designer.generate_css(),
+            r#":root {
+  --color-primary: #3b82f6;
+  --color-surface: #ffffff;
+  --color-blue-50: #eff6ff;
+  --color-blue-100: #dbeafe;
+  --spacing-md: 1rem;
+  --shadow-card: 0 1px 2px #0002;
+}
+
+.dark {
+  --color-surface: #0f172a;
+  --shadow-card: none;
+}
+"#
+        );
+
+        // Without adaptive tokens there is no dark block.
+        designer.tokens.colors.shift_remove("surface");
+        designer.tokens.shadows.clear();
+        assert!(!designer.generate_css().contains(".dark"));
+    }
+
+    #[test]
+    fn test_css_generation_token_comments() {
+        let mut designer = CssDesigner::new();
+        designer.load_tokens(mixed_tokens());
+        designer.tokens.docs.insert(
+            "colors.blue".to_string(),
+            TokenDocs::default().with_description("Info hues */ tints"),
+        );
+        assert!(!designer.generate_css().contains("/*"));
+
+        let css = designer.with_token_comments(true).generate_css();
+        assert!(css.contains(concat!(
+            "  --color-surface: #ffffff;\n",
+            "  /* Info hues * / tints */\n",
+            "  --color-blue-50: #eff6ff;\n",
+        )));
+    }
+
     #[test]
     fn test_designer_default_state() {
         let designer = CssDesigner::new();
@@ -1319,6 +1907,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(unused_variables)]
     fn test_token_validation_valid_hex() {
         let tokens = DesignTokens::default();
         let mut designer = CssDesigner::new();
@@ -1360,6 +1949,45 @@ mod tests {
         assert!(paths.contains(&"radius.md".to_string()));
     }
 
+    #[test]
+    fn test_every_category_editable_and_emitted() {
+        let mut designer = CssDesigner::new();
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        designer.set_token("typography.fonts.body", simple("Inter, sans-serif"));
+        designer.set_token("typography.sizes.lg", simple("1.25"));
+        designer.set_token("typography.weights.bold", simple("700"));
+        designer.set_token("transitions.fast", simple("150ms ease"));
+        designer.set_token("z-index.modal", simple("1000"));
+        designer.set_token(
+            "colors.brand",
+            TokenValue::Adaptive {
+                light: "#fff".to_string(),
+                dark: "#12".to_string(),
+            },
+        );
+        assert_eq!(designer.tokens.count(), 6);
+
+        let css = designer.generate_css();
+        for var in [
+            "--font-family-body: Inter, sans-serif;",
+            "--font-size-lg: 1.25;",
+            "--font-weight-bold: 700;",
+            "--transition-fast: 150ms ease;",
+            "--z-modal: 1000;",
+        ] {
+            assert!(css.contains(var), "missing {}", var);
+        }
+
+        // Every reported path is one the designer can edit.
+        let paths = designer.tokens.all_paths();
+        assert_eq!(paths.len(), 6);
+        let errors = designer.tokens.validate();
+        let reported: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(reported, ["colors.brand", "typography.sizes.lg"]);
+        assert!(reported.iter().all(|p| paths.iter().any(|q| q == p)));
+        assert!(errors[0].message.ends_with("(dark)"));
+    }
+
     #[test]
     fn test_token_count() {
         let mut tokens = DesignTokens::default();
@@ -1481,4 +2109,91 @@ mod tests {
         assert!(unused.contains(&"colors.unused".to_string()));
         assert!(!unused.contains(&"colors.used".to_string()));
     }
+
+    fn overlapping() -> (DesignTokens, DesignTokens) {
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        let mut local = DesignTokens::default();
+        local.colors.insert("primary".to_string(), simple("#111"));
+        local.colors.insert("local".to_string(), simple("#222"));
+        local.typography.sizes.insert("base".to_string(), simple("1rem"));
+        local.transitions.insert("fast".to_string(), simple("100ms"));
+        local.z_index.insert("modal".to_string(), simple("100"));
+
+        let mut shared = DesignTokens::default();
+        shared.colors.insert("primary".to_string(), simple("#3b82f6"));
+        shared.colors.insert("accent".to_string(), simple("#f59e0b"));
+        shared.typography.sizes.insert("base".to_string(), simple("1rem"));
+        shared.typography.weights.insert("bold".to_string(), simple("700"));
+        shared.transitions.insert("fast".to_string(), simple("150ms"));
+        shared.z_index.insert("toast".to_string(), simple("200"));
+        (local, shared)
+    }
+
+    #[test]
+    fn test_merge_prefer_existing() {
+        let (local, shared) = overlapping();
+        let mut designer = CssDesigner::new();
+        designer.load_tokens(local);
+
+        let report = designer.merge_tokens(shared, MergeStrategy::PreferExisting);
+
+        assert_eq!(
+            report.added,
+            ["colors.accent", "typography.weights.bold", "z-index.toast"]
+        );
+        assert_eq!(report.skipped, ["colors.primary", "transitions.fast"]);
+        assert_eq!(report.unchanged, ["typography.sizes.base"]);
+        assert!(report.overwritten.is_empty());
+        assert_eq!(
+            designer.get_token("colors.primary"),
+            Some(&TokenValue::Simple("#111".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_merge_prefer_incoming() {
+        let (local, shared) = overlapping();
+        let mut designer = CssDesigner::new();
+        designer.load_tokens(local);
+
+        let report = designer.merge_tokens(shared, MergeStrategy::PreferIncoming);
+
+        // Identical values are not overwrites.
+        assert_eq!(report.overwritten, ["colors.primary", "transitions.fast"]);
+        assert_eq!(report.unchanged, ["typography.sizes.base"]);
+        assert!(report.removed.is_empty());
+        assert!(designer.tokens.colors.contains_key("local"));
+        assert!(designer.tokens.z_index.contains_key("modal"));
+
+        let by_category = report.by_category();
+        let categories: Vec<&str> = by_category.keys().copied().collect();
+        assert_eq!(
+            categories,
+            [
+                "colors",
+                "typography.sizes",
+                "typography.weights",
+                "transitions",
+                "z-index"
+            ]
+        );
+        assert_eq!(by_category["colors"].added, ["colors.accent"]);
+        assert_eq!(by_category["z-index"].added, ["z-index.toast"]);
+    }
+
+    #[test]
+    fn test_merge_replace() {
+        let (local, shared) = overlapping();
+        let mut designer = CssDesigner::new();
+        designer.load_tokens(local);
+        designer.selected_token = Some("colors.local".to_string());
+
+        let report = designer.merge_tokens(shared, MergeStrategy::Replace);
+
+        assert_eq!(report.removed, ["colors.local", "z-index.modal"]);
+        assert_eq!(report.overwritten, ["colors.primary", "transitions.fast"]);
+        assert!(!designer.tokens.colors.contains_key("local"));
+        assert_eq!(designer.selected_token, None);
+        assert_eq!(designer.tokens.all_paths().len(), 6);
+    }
 }
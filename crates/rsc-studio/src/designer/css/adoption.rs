@@ -0,0 +1,461 @@
+//! Token adoption report.
+//!
+//! Scans app stylesheets for hardcoded colors and dimensions, counts them
+//! against `var(...)` token usages, and suggests tokens to replace the most
+//! frequent literals with.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::DesignTokens;
+use super::annotate::color_to_hex;
+use super::extract::{inline_vars, var_calls};
+
+/// Largest RGB distance at which two colors still count as a match.
+pub const NEAR_COLOR_DISTANCE: f64 = 4.0;
+
+/// Number of suggestions kept in a report.
+pub const TOP_SUGGESTIONS: usize = 10;
+
+/// Root font size used to compare `rem` with `px`.
+const ROOT_FONT_SIZE: f64 = 16.0;
+
+/// Counts for one scanned file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileAdoption {
+    pub path: String,
+    /// `var(--...)` references.
+    pub tokenized: usize,
+    /// Literal colors and dimensions.
+    pub hardcoded: usize,
+}
+
+/// A token that could replace a hardcoded value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplacementSuggestion {
+    /// Normalized literal, e.g. "#3b82f6" or "16px".
+    pub value: String,
+    pub occurrences: usize,
+    /// Suggested variable, e.g. "--color-primary".
+    pub token: String,
+    /// False for near-identical colors.
+    pub exact: bool,
+    /// Files the value appears in.
+    pub files: Vec<String>,
+}
+
+/// Adoption report across scanned files.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdoptionReport {
+    pub files: Vec<FileAdoption>,
+    pub tokenized: usize,
+    pub hardcoded: usize,
+    /// Top replacements, most frequent first.
+    pub suggestions: Vec<ReplacementSuggestion>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    /// Lowercase hex.
+    Color(String),
+    Dimension(String),
+}
+
+impl Literal {
+    fn key(&self) -> &str {
+        match self {
+            Literal::Color(s) | Literal::Dimension(s) => s,
+        }
+    }
+}
+
+/// Build an adoption report from `(path, source)` pairs of CSS or SCSS.
+pub fn adoption_report<P, S>(files: &[(P, S)], tokens: &DesignTokens) -> AdoptionReport
+where
+    P: AsRef<str>,
+    S: AsRef<str>,
+{
+    let raw: IndexMap<String, String> = tokens.css_variables(false).into_iter().collect();
+    let resolved: Vec<(String, String)> = raw
+        .iter()
+        .map(|(var, value)| {
+            (
+                var.clone(),
+                inline_vars(value, &raw, &mut vec![var.clone()]),
+            )
+        })
+        .collect();
+
+    let mut report = AdoptionReport {
+        files: Vec::new(),
+        tokenized: 0,
+        hardcoded: 0,
+        suggestions: Vec::new(),
+    };
+    // Literal key to (literal, occurrences, files).
+    let mut literals: IndexMap<String, (Literal, usize, Vec<String>)> = IndexMap::new();
+
+    for (path, source) in files {
+        let path = path.as_ref();
+        let mut file = FileAdoption {
+            path: path.to_string(),
+            tokenized: 0,
+            hardcoded: 0,
+        };
+        for value in declaration_values(source.as_ref()) {
+            let (tokenized, found) = scan_value(value);
+            file.tokenized += tokenized;
+            file.hardcoded += found.len();
+            for literal in found {
+                let entry = literals
+                    .entry(literal.key().to_string())
+                    .or_insert_with(|| (literal, 0, Vec::new()));
+                entry.1 += 1;
+                if !entry.2.iter().any(|f| f == path) {
+                    entry.2.push(path.to_string());
+                }
+            }
+        }
+        report.tokenized += file.tokenized;
+        report.hardcoded += file.hardcoded;
+        report.files.push(file);
+    }
+
+    let mut suggestions: Vec<ReplacementSuggestion> = literals
+        .into_iter()
+        .filter_map(|(key, (literal, occurrences, files))| {
+            let (token, exact) = match_token(&literal, &resolved)?;
+            Some(ReplacementSuggestion {
+                value: key,
+                occurrences,
+                token,
+                exact,
+                files,
+            })
+        })
+        .collect();
+    // Stable sort keeps first-seen order among ties.
+    suggestions.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then(b.exact.cmp(&a.exact))
+    });
+    suggestions.truncate(TOP_SUGGESTIONS);
+    report.suggestions = suggestions;
+    report
+}
+
+impl AdoptionReport {
+    /// Share of values that use tokens, from 0.0 to 1.0.
+    pub fn adoption_rate(&self) -> f64 {
+        rate(self.tokenized, self.hardcoded)
+    }
+
+    /// Export to JSON string.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Markdown summary for CI comments.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("## Token adoption\n\n");
+        md.push_str(&format!(
+            "**{:.1}%** of values use tokens ({} tokenized, {} hardcoded).\n\n",
+            self.adoption_rate() * 100.0,
+            self.tokenized,
+            self.hardcoded
+        ));
+
+        md.push_str("| File | Tokenized | Hardcoded | Adoption |\n");
+        md.push_str("|---|---:|---:|---:|\n");
+        for file in &self.files {
+            md.push_str(&format!(
+                "| {} | {} | {} | {:.1}% |\n",
+                file.path,
+                file.tokenized,
+                file.hardcoded,
+                rate(file.tokenized, file.hardcoded) * 100.0
+            ));
+        }
+
+        if !self.suggestions.is_empty() {
+            md.push_str("\n### Suggested replacements\n\n");
+            md.push_str("| Value | Occurrences | Token |\n");
+            md.push_str("|---|---:|---|\n");
+            for s in &self.suggestions {
+                md.push_str(&format!(
+                    "| `{}` | {} | `var({})`{} |\n",
+                    s.value,
+                    s.occurrences,
+                    s.token,
+                    if s.exact { "" } else { " (near match)" }
+                ));
+            }
+        }
+        md
+    }
+}
+
+fn rate(tokenized: usize, hardcoded: usize) -> f64 {
+    let total = tokenized + hardcoded;
+    if total == 0 {
+        1.0
+    } else {
+        tokenized as f64 / total as f64
+    }
+}
+
+/// Values of ordinary declarations, skipping selectors, comments and
+/// custom property definitions.
+fn declaration_values(source: &str) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut start = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                let end = source[i + 2..]
+                    .find("*/")
+                    .map_or(source.len(), |e| i + 4 + e);
+                while chars.peek().is_some_and(|(j, _)| *j < end) {
+                    chars.next();
+                }
+                start = end;
+            }
+            '/' if matches!(chars.peek(), Some((_, '/')))
+                && (i == 0 || !source[..i].ends_with(':')) =>
+            {
+                let end = source[i..].find('\n').map_or(source.len(), |e| i + e);
+                while chars.peek().is_some_and(|(j, _)| *j < end) {
+                    chars.next();
+                }
+                start = end;
+            }
+            '{' => start = i + 1,
+            ';' | '}' => {
+                let decl = source[start..i].trim();
+                if let Some((name, value)) = decl.split_once(':')
+                    && !name.trim().starts_with("--")
+                    && !name.trim().starts_with('$')
+                {
+                    values.push(value);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    values
+}
+
+/// Count token references and collect literals outside them.
+fn scan_value(value: &str) -> (usize, Vec<Literal>) {
+    let calls = var_calls(value);
+    let tokenized = calls.iter().filter(|(_, _, n)| n.starts_with("--")).count();
+    let in_call = |i: usize| calls.iter().any(|(s, e, _)| i >= *s && i < *e);
+
+    let bytes = value.as_bytes();
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if in_call(i) {
+            i += 1;
+            continue;
+        }
+        let boundary = i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'-');
+        let rest = &value[i..];
+
+        if bytes[i] == b'#' {
+            let len = rest[1..]
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len() - 1);
+            if let Some(hex) = color_to_hex(&rest[..=len]) {
+                literals.push(Literal::Color(hex));
+            }
+            i += len + 1;
+            continue;
+        }
+
+        if boundary
+            && ["rgb(", "rgba(", "hsl(", "hsla("]
+                .iter()
+                .any(|f| rest.starts_with(f))
+        {
+            let end = rest.find(')').map_or(rest.len(), |e| e + 1);
+            if !(i..i + end).any(in_call)
+                && let Some(hex) = color_to_hex(&rest[..end])
+            {
+                literals.push(Literal::Color(hex));
+            }
+            i += end;
+            continue;
+        }
+
+        if boundary && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+            let num_len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let unit_len = rest[num_len..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len() - num_len);
+            let unit = &rest[num_len..num_len + unit_len];
+            if matches!(unit, "px" | "rem" | "em")
+                && rest[..num_len].parse::<f64>().is_ok_and(|n| n != 0.0)
+            {
+                literals.push(Literal::Dimension(rest[..num_len + unit_len].to_string()));
+            }
+            i += num_len + unit_len;
+            continue;
+        }
+
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    (tokenized, literals)
+}
+
+/// Best token for a literal: (variable, exact).
+fn match_token(literal: &Literal, tokens: &[(String, String)]) -> Option<(String, bool)> {
+    match literal {
+        Literal::Color(hex) => {
+            let target = rgba(hex)?;
+            tokens
+                .iter()
+                .filter_map(|(var, value)| {
+                    let candidate = rgba(&color_to_hex(value)?)?;
+                    if candidate.3 != target.3 {
+                        return None;
+                    }
+                    let distance = ((candidate.0 - target.0).powi(2)
+                        + (candidate.1 - target.1).powi(2)
+                        + (candidate.2 - target.2).powi(2))
+                    .sqrt();
+                    (distance <= NEAR_COLOR_DISTANCE).then_some((distance, var))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(distance, var)| (var.clone(), distance == 0.0))
+        }
+        Literal::Dimension(dim) => {
+            let target = to_px(dim);
+            tokens
+                .iter()
+                .find(|(_, value)| {
+                    let value = value.trim();
+                    value == dim
+                        || target
+                            .zip(to_px(value))
+                            .is_some_and(|(a, b)| (a - b).abs() < 1e-6)
+                })
+                .map(|(var, _)| (var.clone(), true))
+        }
+    }
+}
+
+/// Channels of a `#rrggbb` or `#rrggbbaa` hex color.
+fn rgba(hex: &str) -> Option<(f64, f64, f64, u8)> {
+    let hex = hex.strip_prefix('#')?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some((
+        channel(0)? as f64,
+        channel(2)? as f64,
+        channel(4)? as f64,
+        alpha,
+    ))
+}
+
+fn to_px(value: &str) -> Option<f64> {
+    if let Some(px) = value.strip_suffix("px") {
+        px.parse().ok()
+    } else if let Some(rem) = value.strip_suffix("rem") {
+        rem.parse::<f64>().ok().map(|r| r * ROOT_FONT_SIZE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+
+    fn tokens() -> DesignTokens {
+        let mut tokens = DesignTokens::default();
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        tokens.colors.insert("blue".to_string(), simple("#3B82F6"));
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("var(--color-blue)"));
+        tokens.colors.insert("white".to_string(), simple("#fff"));
+        tokens.spacing.insert("md".to_string(), simple("1rem"));
+        tokens
+    }
+
+    #[test]
+    fn test_near_color_matching() {
+        let resolved: Vec<(String, String)> = vec![
+            ("--color-blue".to_string(), "#3B82F6".to_string()),
+            ("--color-white".to_string(), "#fff".to_string()),
+        ];
+
+        let rgb = Literal::Color(color_to_hex("rgb(59, 130, 246)").unwrap());
+        assert_eq!(
+            match_token(&rgb, &resolved),
+            Some(("--color-blue".to_string(), true))
+        );
+        let near = Literal::Color("#3b82f8".to_string());
+        assert_eq!(
+            match_token(&near, &resolved),
+            Some(("--color-blue".to_string(), false))
+        );
+        assert_eq!(
+            match_token(&Literal::Color("#3b8200".to_string()), &resolved),
+            None
+        );
+        let translucent = Literal::Color(color_to_hex("rgba(59, 130, 246, 0.5)").unwrap());
+        assert_eq!(match_token(&translucent, &resolved), None);
+    }
+
+    #[test]
+    fn test_report_counts_and_ranking() {
+        let files = [
+            (
+                "app.css",
+                "/* #ffffff in a comment */\n\
+                 :root { --brand: #123456; }\n\
+                 .a:hover { color: #3b82f6; background: var(--color-white); }\n\
+                 .b { padding: 16px var(--spacing-md); border: 1px solid rgb(59, 130, 246); }\n",
+            ),
+            (
+                "card.scss",
+                "$gap: 12px;\n.card { color: #3B82F6; margin: 0; gap: 12px; fill: #FFF; }\n",
+            ),
+        ];
+        let report = adoption_report(&files, &tokens());
+
+        assert_eq!(report.files[0].tokenized, 2);
+        assert_eq!(report.files[0].hardcoded, 4);
+        assert_eq!(report.files[1].tokenized, 0);
+        assert_eq!(report.files[1].hardcoded, 3);
+        assert!((report.adoption_rate() - 2.0 / 9.0).abs() < 1e-9);
+
+        let ranked: Vec<(&str, usize, &str)> = report
+            .suggestions
+            .iter()
+            .map(|s| (s.value.as_str(), s.occurrences, s.token.as_str()))
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("#3b82f6", 3, "--color-blue"),
+                ("16px", 1, "--spacing-md"),
+                ("#ffffff", 1, "--color-white"),
+            ]
+        );
+        assert_eq!(report.suggestions[0].files, ["app.css", "card.scss"]);
+
+        let md = report.to_markdown();
+        assert!(md.contains("**22.2%** of values use tokens"));
+        assert!(md.contains("| `#3b82f6` | 3 | `var(--color-blue)` |"));
+        assert!(report.to_json().unwrap().contains("\"hardcoded\": 7"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Token value annotations for generated CSS.
+//!
+//! Maps custom property declarations and `var()` references in CSS text to
+//! their resolved values so the output panel can draw swatch chips and flag
+//! missing tokens. Annotation is done per line and cached, so re-annotating
+//! after an edit only processes the lines that changed.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use indexmap::IndexMap;
+
+use super::DesignTokens;
+use super::extract::{inline_vars, var_calls};
+
+/// What an annotation covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// `--name: value` declaration.
+    Declaration,
+    /// `var(...)` reference.
+    Reference,
+}
+
+/// Type of a resolved value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Color,
+    Dimension,
+    Other,
+    /// The variable isn't a known token.
+    Missing,
+}
+
+/// Annotation for a span of CSS text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssAnnotation {
+    /// Byte range in the annotated text.
+    pub range: Range<usize>,
+    pub kind: AnnotationKind,
+    /// Custom property name, e.g. "--color-primary".
+    pub var: String,
+    /// Fully resolved value, if the variable is a token.
+    pub value: Option<String>,
+    pub value_type: ValueType,
+    /// Hex form of color values, for swatches.
+    pub hex: Option<String>,
+}
+
+/// Annotate CSS against a token set.
+pub fn annotate_css(css: &str, tokens: &DesignTokens) -> Vec<CssAnnotation> {
+    CssAnnotator::new(tokens).annotate(css)
+}
+
+/// Incremental annotator; caches annotations per line of CSS.
+#[derive(Debug, Clone)]
+pub struct CssAnnotator {
+    vars: IndexMap<String, String>,
+    /// Line text to annotations with line-relative ranges.
+    cache: HashMap<String, Vec<CssAnnotation>>,
+    /// Lines processed (not served from cache) by the last call.
+    pub last_dirty_lines: usize,
+}
+
+impl CssAnnotator {
+    pub fn new(tokens: &DesignTokens) -> Self {
+        Self {
+            vars: tokens.css_variables(false).into_iter().collect(),
+            cache: HashMap::new(),
+            last_dirty_lines: 0,
+        }
+    }
+
+    /// Replace the token set, invalidating the cache.
+    pub fn set_tokens(&mut self, tokens: &DesignTokens) {
+        self.vars = tokens.css_variables(false).into_iter().collect();
+        self.cache.clear();
+    }
+
+    /// Annotate CSS, reusing cached results for unchanged lines.
+    pub fn annotate(&mut self, css: &str) -> Vec<CssAnnotation> {
+        let mut annotations = Vec::new();
+        let mut offset = 0;
+        self.last_dirty_lines = 0;
+        for line in css.split_inclusive('\n') {
+            if !self.cache.contains_key(line) {
+                self.last_dirty_lines += 1;
+                let annotated = self.annotate_line(line);
+                self.cache.insert(line.to_string(), annotated);
+            }
+            annotations.extend(self.cache[line].iter().map(|a| CssAnnotation {
+                range: a.range.start + offset..a.range.end + offset,
+                ..a.clone()
+            }));
+            offset += line.len();
+        }
+        annotations
+    }
+
+    fn annotate_line(&self, line: &str) -> Vec<CssAnnotation> {
+        let mut annotations = Vec::new();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("--")
+            && let Some(colon) = trimmed.find(':')
+        {
+            let start = line.len() - trimmed.len();
+            let end = start + trimmed.find(';').unwrap_or(trimmed.trim_end().len());
+            let var = trimmed[..colon].trim().to_string();
+            annotations.push(self.annotation(start..end, AnnotationKind::Declaration, var));
+        }
+
+        for (start, end, var) in var_calls(line) {
+            if var.starts_with("--") {
+                annotations.push(self.annotation(start..end, AnnotationKind::Reference, var));
+            }
+        }
+
+        annotations
+    }
+
+    fn annotation(&self, range: Range<usize>, kind: AnnotationKind, var: String) -> CssAnnotation {
+        let value = self
+            .vars
+            .get(&var)
+            .map(|v| inline_vars(v, &self.vars, &mut vec![var.clone()]));
+        let value_type = match &value {
+            None => ValueType::Missing,
+            Some(v) => value_type(v),
+        };
+        let hex = value
+            .as_deref()
+            .filter(|_| value_type == ValueType::Color)
+            .and_then(color_to_hex);
+        CssAnnotation {
+            range,
+            kind,
+            var,
+            value,
+            value_type,
+            hex,
+        }
+    }
+}
+
+fn value_type(value: &str) -> ValueType {
+    let v = value.trim();
+    if v.starts_with('#') || v.starts_with("rgb") || v.starts_with("hsl") {
+        return ValueType::Color;
+    }
+    let number_end = v
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(v.len());
+    let unit = &v[number_end..];
+    if number_end > 0
+        && v[..number_end].parse::<f64>().is_ok()
+        && matches!(unit, "" | "px" | "rem" | "em" | "%" | "vh" | "vw")
+    {
+        return ValueType::Dimension;
+    }
+    ValueType::Other
+}
+
+/// `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()`/`rgba()` or `hsl()`/`hsla()` as
+/// lowercase `#rrggbb` (with `aa` when not opaque).
+pub fn color_to_hex(value: &str) -> Option<String> {
+    let v = value.trim().to_ascii_lowercase();
+    if let Some(hex) = v.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return match hex.len() {
+            3 => Some(format!(
+                "#{}",
+                hex.chars().flat_map(|c| [c, c]).collect::<String>()
+            )),
+            6 | 8 => Some(format!("#{}", hex)),
+            _ => None,
+        };
+    }
+
+    let (name, args) = v.split_once('(')?;
+    let args: Vec<&str> = args
+        .trim_end_matches(')')
+        .split([',', ' ', '/'])
+        .filter(|a| !a.is_empty())
+        .collect();
+    if args.len() < 3 {
+        return None;
+    }
+    let alpha = match args.get(3) {
+        Some(a) => parse_fraction(a)?,
+        None => 1.0,
+    };
+    let (r, g, b) = match name {
+        "rgb" | "rgba" => {
+            let channel = |s: &str| -> Option<f64> {
+                match s.strip_suffix('%') {
+                    Some(p) => p.parse::<f64>().ok().map(|p| p * 2.55),
+                    None => s.parse().ok(),
+                }
+            };
+            (channel(args[0])?, channel(args[1])?, channel(args[2])?)
+        }
+        "hsl" | "hsla" => {
+            let h = args[0].trim_end_matches("deg").parse::<f64>().ok()?;
+            let s = parse_fraction(args[1])?;
+            let l = parse_fraction(args[2])?;
+            hsl_to_rgb(h, s, l)
+        }
+        _ => return None,
+    };
+
+    let byte = |c: f64| c.round().clamp(0.0, 255.0) as u8;
+    let mut hex = format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b));
+    if alpha < 1.0 {
+        hex.push_str(&format!("{:02x}", byte(alpha * 255.0)));
+    }
+    Some(hex)
+}
+
+/// "50%" or "0.5" as 0.5.
+fn parse_fraction(s: &str) -> Option<f64> {
+    match s.strip_suffix('%') {
+        Some(p) => p.parse::<f64>().ok().map(|p| p / 100.0),
+        None => s.parse().ok(),
+    }
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    ((r + m) * 255.0, (g + m) * 255.0, (b + m) * 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{ComponentStyle, CssDesigner, StyleProperties, TokenValue};
+
+    fn fixture() -> (String, DesignTokens) {
+        let mut designer = CssDesigner::new();
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        designer
+            .tokens
+            .colors
+            .insert("brand".to_string(), simple("#f60"));
+        designer
+            .tokens
+            .colors
+            .insert("primary".to_string(), simple("var(--color-brand)"));
+        designer
+            .tokens
+            .spacing
+            .insert("md".to_string(), simple("16px"));
+
+        let button = ComponentStyle {
+            base: StyleProperties {
+                color: Some("var(--color-primary)".to_string()),
+                padding: Some("var(--spacing-md) var(--spacing-xl)".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let css = designer.generate_css() + &button.to_css("button");
+        (css, designer.tokens)
+    }
+
+    #[test]
+    fn test_ranges_line_up() {
+        let (css, tokens) = fixture();
+        let annotations = annotate_css(&css, &tokens);
+        let text = |a: &CssAnnotation| &css[a.range.clone()];
+
+        let decl = &annotations[0];
+        assert_eq!(decl.kind, AnnotationKind::Declaration);
+        assert_eq!(text(decl), "--color-brand: #f60");
+        assert_eq!(decl.hex.as_deref(), Some("#ff6600"));
+
+        let refs: Vec<&CssAnnotation> = annotations
+            .iter()
+            .filter(|a| a.kind == AnnotationKind::Reference)
+            .collect();
+        assert!(refs.iter().all(|a| text(a) == format!("var({})", a.var)));
+
+        let primary = refs.iter().find(|a| a.var == "--color-primary").unwrap();
+        assert_eq!(primary.value.as_deref(), Some("#f60"));
+        assert_eq!(primary.value_type, ValueType::Color);
+
+        let md = refs.iter().find(|a| a.var == "--spacing-md").unwrap();
+        assert_eq!(md.value_type, ValueType::Dimension);
+        assert_eq!(md.hex, None);
+
+        let missing = refs.iter().find(|a| a.var == "--spacing-xl").unwrap();
+        assert_eq!(missing.value_type, ValueType::Missing);
+        assert_eq!(missing.value, None);
+    }
+
+    #[test]
+    fn test_incremental_reannotation() {
+        let (css, tokens) = fixture();
+        let mut annotator = CssAnnotator::new(&tokens);
+        let first = annotator.annotate(&css);
+        // Repeated lines such as closing braces are only annotated once.
+        let unique: std::collections::HashSet<&str> = css.split_inclusive('\n').collect();
+        assert_eq!(annotator.last_dirty_lines, unique.len());
+
+        let edited = css.replace("16px", "1rem");
+        let second = annotator.annotate(&edited);
+        assert_eq!(annotator.last_dirty_lines, 1);
+        assert_eq!(second.len(), first.len());
+        assert_eq!(second, annotate_css(&edited, &tokens));
+    }
+
+    #[test]
+    fn test_color_to_hex() {
+        assert_eq!(color_to_hex("#ABC").as_deref(), Some("#aabbcc"));
+        assert_eq!(color_to_hex("rgb(255, 0, 128)").as_deref(), Some("#ff0080"));
+        assert_eq!(
+            color_to_hex("rgba(0 0 0 / 50%)").as_deref(),
+            Some("#00000080")
+        );
+        assert_eq!(
+            color_to_hex("hsl(120, 100%, 50%)").as_deref(),
+            Some("#00ff00")
+        );
+        assert_eq!(color_to_hex("red"), None);
+    }
+}
@@ -0,0 +1,455 @@
+//! Batch edits across a token category.
+//!
+//! [`DesignTokens::transform_category`] applies one [`NumericOp`] to every
+//! value in a category, e.g. scaling the spacing ramp by 1.25. Numbers keep
+//! their unit; references, `calc()` and anything else that isn't a plain
+//! number are left alone and reported as skipped. Colors take the HSL ops
+//! instead and keep their notation (hex, `rgb()` or `hsl()`).
+
+use thiserror::Error;
+
+use super::color::{Hsl, Rgb};
+use super::contrast::parse_color;
+use super::{DesignTokens, TokenCategory, TokenValue};
+
+/// Decimal places kept unless the caller asks for others.
+pub const DEFAULT_PRECISION: u32 = 4;
+
+/// An edit applied to each value in a category.
+///
+/// Numeric ops work in the value's own unit: `Offset(4.0)` turns `16px`
+/// into `20px` and `1rem` into `5rem`. Color amounts are fractions, so
+/// `Lighten(0.1)` raises lightness by 10 percentage points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericOp {
+    Multiply(f64),
+    Offset(f64),
+    /// Round to the nearest multiple of the step.
+    RoundToStep(f64),
+    Clamp {
+        min: f64,
+        max: f64,
+    },
+    Lighten(f64),
+    Darken(f64),
+    /// Raise saturation; a negative amount desaturates.
+    Saturate(f64),
+}
+
+impl NumericOp {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NumericOp::Multiply(_) => "Multiply",
+            NumericOp::Offset(_) => "Offset",
+            NumericOp::RoundToStep(_) => "Round",
+            NumericOp::Clamp { .. } => "Clamp",
+            NumericOp::Lighten(_) => "Lighten",
+            NumericOp::Darken(_) => "Darken",
+            NumericOp::Saturate(_) => "Saturate",
+        }
+    }
+
+    /// Whether the op works on colors rather than numbers.
+    pub fn is_color_op(&self) -> bool {
+        matches!(
+            self,
+            NumericOp::Lighten(_) | NumericOp::Darken(_) | NumericOp::Saturate(_)
+        )
+    }
+
+    fn check(&self, category: TokenCategory) -> Result<(), TransformError> {
+        if self.is_color_op() != (category == TokenCategory::Colors) {
+            return Err(TransformError::WrongCategory {
+                op: self.label(),
+                category: category.label(),
+            });
+        }
+        let valid = match *self {
+            NumericOp::Multiply(n)
+            | NumericOp::Offset(n)
+            | NumericOp::Lighten(n)
+            | NumericOp::Darken(n)
+            | NumericOp::Saturate(n) => n.is_finite(),
+            NumericOp::RoundToStep(step) => step.is_finite() && step > 0.0,
+            NumericOp::Clamp { min, max } => min <= max,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(TransformError::InvalidOp(format!("{:?}", self)))
+        }
+    }
+
+    fn apply_number(&self, n: f64) -> f64 {
+        match *self {
+            NumericOp::Multiply(factor) => n * factor,
+            NumericOp::Offset(offset) => n + offset,
+            NumericOp::RoundToStep(step) => (n / step).round() * step,
+            NumericOp::Clamp { min, max } => n.clamp(min, max),
+            _ => n,
+        }
+    }
+
+    fn apply_color(&self, hsl: Hsl) -> Hsl {
+        match *self {
+            NumericOp::Lighten(amount) => Hsl {
+                l: (hsl.l + amount).clamp(0.0, 1.0),
+                ..hsl
+            },
+            NumericOp::Darken(amount) => Hsl {
+                l: (hsl.l - amount).clamp(0.0, 1.0),
+                ..hsl
+            },
+            NumericOp::Saturate(amount) => Hsl {
+                s: (hsl.s + amount).clamp(0.0, 1.0),
+                ..hsl
+            },
+            _ => hsl,
+        }
+    }
+}
+
+/// Batch edit error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TransformError {
+    /// A color op on a numeric category or the other way round.
+    #[error("{op} does not apply to {category}")]
+    WrongCategory {
+        op: &'static str,
+        category: &'static str,
+    },
+
+    /// A non-finite amount, a step that isn't positive or an empty range.
+    #[error("Invalid operation: {0}")]
+    InvalidOp(String),
+
+    /// The result is not a finite number.
+    #[error("'{0}' would not be a finite number")]
+    NotFinite(String),
+}
+
+/// Outcome of a batch edit. Scale steps are listed by step path, e.g.
+/// `spacing.ramp.2`; adaptive tokens by token path.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransformReport {
+    pub changed: Vec<String>,
+    /// Values that aren't plain numbers or colors, such as `var()` and
+    /// `calc()`.
+    pub skipped: Vec<String>,
+    pub errors: Vec<TransformError>,
+}
+
+/// What an op did to one value.
+enum Outcome {
+    Changed,
+    Unchanged,
+    Skipped,
+    NotFinite,
+}
+
+impl DesignTokens {
+    /// Apply `op` to every value in `category`, keeping
+    /// [`DEFAULT_PRECISION`] decimals.
+    pub fn transform_category(
+        &mut self,
+        category: TokenCategory,
+        op: NumericOp,
+    ) -> TransformReport {
+        self.transform_category_with_precision(category, op, DEFAULT_PRECISION)
+    }
+
+    /// Apply `op` to every value in `category`, rounding results to
+    /// `precision` decimals. Nothing changes if the report has errors.
+    pub fn transform_category_with_precision(
+        &mut self,
+        category: TokenCategory,
+        op: NumericOp,
+        precision: u32,
+    ) -> TransformReport {
+        let mut report = TransformReport::default();
+        if let Err(e) = op.check(category) {
+            report.errors.push(e);
+            return report;
+        }
+        let mut tokens = self.clone();
+        for (prefix, map) in tokens.categories_mut() {
+            // Font families are never numeric.
+            if prefix.split('.').next() != Some(category.key()) || prefix == "typography.fonts" {
+                continue;
+            }
+            for (name, value) in map.iter_mut() {
+                let path = format!("{}.{}", prefix, name);
+                match value {
+                    TokenValue::Simple(v) => {
+                        report.note(path, apply(&op, v, precision));
+                    }
+                    TokenValue::Adaptive { light, dark } => {
+                        let light = apply(&op, light, precision);
+                        let dark = apply(&op, dark, precision);
+                        let outcome = match (light, dark) {
+                            (Outcome::NotFinite, _) | (_, Outcome::NotFinite) => Outcome::NotFinite,
+                            (Outcome::Changed, _) | (_, Outcome::Changed) => Outcome::Changed,
+                            (Outcome::Skipped, Outcome::Skipped) => Outcome::Skipped,
+                            _ => Outcome::Unchanged,
+                        };
+                        report.note(path, outcome);
+                    }
+                    TokenValue::Scale(steps) => {
+                        for (step, v) in steps.iter_mut() {
+                            let outcome = apply(&op, v, precision);
+                            report.note(format!("{}.{}", path, step), outcome);
+                        }
+                    }
+                }
+            }
+        }
+        if report.errors.is_empty() {
+            *self = tokens;
+        }
+        report
+    }
+}
+
+impl TransformReport {
+    fn note(&mut self, path: String, outcome: Outcome) {
+        match outcome {
+            Outcome::Changed => self.changed.push(path),
+            Outcome::Unchanged => {}
+            Outcome::Skipped => self.skipped.push(path),
+            Outcome::NotFinite => self.errors.push(TransformError::NotFinite(path)),
+        }
+    }
+}
+
+/// Apply `op` to one value, updating it in place when it changes.
+fn apply(op: &NumericOp, value: &mut String, precision: u32) -> Outcome {
+    let result = if op.is_color_op() {
+        transform_color(op, value, precision)
+    } else {
+        transform_number(op, value, precision)
+    };
+    match result {
+        Some(Ok(new)) if new != *value => {
+            *value = new;
+            Outcome::Changed
+        }
+        Some(Ok(_)) => Outcome::Unchanged,
+        Some(Err(())) => Outcome::NotFinite,
+        None => Outcome::Skipped,
+    }
+}
+
+/// `None` when the value isn't a number with an optional unit.
+fn transform_number(op: &NumericOp, value: &str, precision: u32) -> Option<Result<String, ()>> {
+    let (number, unit) = split_number(value)?;
+    let n = op.apply_number(number);
+    if !n.is_finite() {
+        return Some(Err(()));
+    }
+    Some(Ok(format!("{}{}", format_number(n, precision), unit)))
+}
+
+/// Split `1.5rem` into `1.5` and `rem`. The unit may be empty or `%`.
+fn split_number(value: &str) -> Option<(f64, &str)> {
+    let value = value.trim();
+    let split = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && matches!(c, '-' | '+'))))
+        .map_or(value.len(), |(i, _)| i);
+    let (number, unit) = value.split_at(split);
+    if !(unit.is_empty() || unit == "%" || unit.chars().all(|c| c.is_ascii_alphabetic())) {
+        return None;
+    }
+    Some((number.parse().ok()?, unit))
+}
+
+fn transform_color(op: &NumericOp, value: &str, precision: u32) -> Option<Result<String, ()>> {
+    let rgb = parse_color(value)?;
+    let hsl = op.apply_color(Hsl::from(rgb));
+    let func = value.trim().to_ascii_lowercase();
+    let out = if func.starts_with("hsl") {
+        let number = |n: f64| format_number(n, precision);
+        let (h, s, l) = (number(hsl.h), number(hsl.s * 100.0), number(hsl.l * 100.0));
+        if hsl.alpha < 1.0 {
+            format!("hsla({}, {}%, {}%, {})", h, s, l, number(hsl.alpha))
+        } else {
+            format!("hsl({}, {}%, {}%)", h, s, l)
+        }
+    } else if func.starts_with("rgb") {
+        rgb_function(hsl.to_rgb(), precision)
+    } else {
+        hsl.to_rgb().to_hex()
+    };
+    Some(Ok(out))
+}
+
+fn rgb_function(rgb: Rgb, precision: u32) -> String {
+    let byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round();
+    let (r, g, b) = (byte(rgb.r), byte(rgb.g), byte(rgb.b));
+    if rgb.alpha < 1.0 {
+        let alpha = format_number(rgb.alpha, precision);
+        format!("rgba({}, {}, {}, {})", r, g, b, alpha)
+    } else {
+        format!("rgb({}, {}, {})", r, g, b)
+    }
+}
+
+/// `n` rounded to `precision` decimals, without trailing zeros.
+fn format_number(n: f64, precision: u32) -> String {
+    let mut number = format!("{:.*}", precision as usize, n);
+    if number.contains('.') {
+        while number.ends_with('0') {
+            number.pop();
+        }
+        if number.ends_with('.') {
+            number.pop();
+        }
+    }
+    if number == "-0" {
+        number = "0".to_string();
+    }
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn scale(steps: &[(&str, &str)]) -> TokenValue {
+        TokenValue::Scale(
+            steps
+                .iter()
+                .map(|(s, v)| (s.to_string(), v.to_string()))
+                .collect::<IndexMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_units_preserved() {
+        let mut tokens = DesignTokens::default();
+        tokens.spacing.insert("xs".to_string(), simple("0"));
+        tokens.spacing.insert("sm".to_string(), simple("0.5rem"));
+        tokens.spacing.insert("md".to_string(), simple("16px"));
+        tokens
+            .spacing
+            .insert("ramp".to_string(), scale(&[("1", "4px"), ("2", "10%")]));
+
+        let report = tokens.transform_category(TokenCategory::Spacing, NumericOp::Multiply(1.25));
+        assert_eq!(
+            report.changed,
+            [
+                "spacing.sm",
+                "spacing.md",
+                "spacing.ramp.1",
+                "spacing.ramp.2"
+            ]
+        );
+        assert!(report.skipped.is_empty() && report.errors.is_empty());
+        assert_eq!(tokens.spacing["xs"], simple("0"));
+        assert_eq!(tokens.spacing["sm"], simple("0.625rem"));
+        assert_eq!(tokens.spacing["md"], simple("20px"));
+        assert_eq!(
+            tokens.spacing["ramp"],
+            scale(&[("1", "5px"), ("2", "12.5%")])
+        );
+
+        let report = tokens.transform_category_with_precision(
+            TokenCategory::Spacing,
+            NumericOp::Multiply(1.0 / 3.0),
+            2,
+        );
+        assert_eq!(report.changed.len(), 4);
+        assert_eq!(tokens.spacing["md"], simple("6.67px"));
+
+        tokens.transform_category(TokenCategory::Spacing, NumericOp::RoundToStep(4.0));
+        assert_eq!(tokens.spacing["md"], simple("8px"));
+        assert_eq!(tokens.spacing["sm"], simple("0rem"));
+        tokens.transform_category(
+            TokenCategory::Spacing,
+            NumericOp::Clamp { min: 1.0, max: 6.0 },
+        );
+        assert_eq!(tokens.spacing["md"], simple("6px"));
+        assert_eq!(tokens.spacing["xs"], simple("1"));
+    }
+
+    #[test]
+    fn test_calc_and_var_skipped() {
+        let mut tokens = DesignTokens::default();
+        tokens.spacing.insert("md".to_string(), simple("16px"));
+        tokens
+            .spacing
+            .insert("lg".to_string(), simple("calc(var(--spacing-md) * 2)"));
+        tokens
+            .spacing
+            .insert("gap".to_string(), simple("var(--spacing-md)"));
+        tokens.spacing.insert("pad".to_string(), simple("4px 8px"));
+
+        let report = tokens.transform_category(TokenCategory::Spacing, NumericOp::Offset(4.0));
+        assert_eq!(report.changed, ["spacing.md"]);
+        assert_eq!(report.skipped, ["spacing.lg", "spacing.gap", "spacing.pad"]);
+        assert_eq!(tokens.spacing["md"], simple("20px"));
+        assert_eq!(tokens.spacing["lg"], simple("calc(var(--spacing-md) * 2)"));
+
+        let before = tokens.clone();
+        let report = tokens.transform_category(TokenCategory::Spacing, NumericOp::Lighten(0.1));
+        assert_eq!(
+            report.errors,
+            [TransformError::WrongCategory {
+                op: "Lighten",
+                category: "Spacing",
+            }]
+        );
+        let report =
+            tokens.transform_category(TokenCategory::Spacing, NumericOp::Multiply(f64::MAX));
+        assert_eq!(
+            report.errors,
+            [TransformError::NotFinite("spacing.md".to_string())]
+        );
+        assert_eq!(tokens.spacing, before.spacing);
+    }
+
+    #[test]
+    fn test_color_ops() {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert("gray".to_string(), simple("#808080"));
+        tokens
+            .colors
+            .insert("accent".to_string(), simple("hsl(200, 50%, 40%)"));
+        tokens
+            .colors
+            .insert("link".to_string(), simple("var(--color-accent)"));
+        tokens.colors.insert(
+            "blue".to_string(),
+            scale(&[("100", "#dbeafe"), ("500", "rgb(59, 130, 246)")]),
+        );
+
+        let report = tokens.transform_category(TokenCategory::Colors, NumericOp::Lighten(0.1));
+        assert_eq!(
+            report.changed,
+            [
+                "colors.gray",
+                "colors.accent",
+                "colors.blue.100",
+                "colors.blue.500"
+            ]
+        );
+        assert_eq!(report.skipped, ["colors.link"]);
+        assert_eq!(tokens.colors["gray"], simple("#9a9a9a"));
+        assert_eq!(tokens.colors["accent"], simple("hsl(200, 50%, 50%)"));
+        assert_eq!(
+            tokens.colors["blue"],
+            scale(&[("100", "#ffffff"), ("500", "rgb(108, 161, 248)")])
+        );
+
+        tokens.transform_category(TokenCategory::Colors, NumericOp::Darken(0.2));
+        assert_eq!(tokens.colors["accent"], simple("hsl(200, 50%, 30%)"));
+        tokens.transform_category(TokenCategory::Colors, NumericOp::Saturate(-1.0));
+        assert_eq!(tokens.colors["accent"], simple("hsl(200, 0%, 30%)"));
+        assert_eq!(tokens.colors["gray"], simple("#676767"));
+    }
+}
@@ -0,0 +1,454 @@
+//! Incremental CSS generation.
+//!
+//! Generated CSS is split into chunks: one per token category and one per
+//! component style. Each chunk is keyed by a hash of its own input and of
+//! every token it references, directly or through other tokens, so editing
+//! one token regenerates only the chunks that can see it. Chunks are joined
+//! in the order uncached generation uses, so the output is the same.
+
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+use indexmap::IndexMap;
+
+use super::usage::value_refs;
+use super::{
+    ComponentStyles, CssDesigner, DesignTokens, OutputOrdering, TokenValue, assemble_tokens_css,
+    category_css,
+};
+use crate::asset::content_hash;
+use crate::perf::trace_span;
+use crate::timestamp::Timestamps;
+
+/// Chunk lookups since the counters were last reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CssCacheStats {
+    /// Chunks reused unchanged.
+    pub hits: u64,
+    /// Chunks generated because their input changed or was new.
+    pub misses: u64,
+}
+
+impl CssCacheStats {
+    /// Share of lookups that were hits, 0 when there were none.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// A generated chunk with the key of the input it was made from.
+#[derive(Debug, Clone)]
+struct Chunk {
+    key: u64,
+    /// Light and dark declarations; components only use the first.
+    css: (String, String),
+}
+
+/// Generated CSS chunks kept between generations.
+///
+/// One cache can serve both token and component generation. Chunks of
+/// removed categories or components are dropped on the next generation.
+#[derive(Debug, Clone, Default)]
+pub struct CssCache {
+    tokens: HashMap<&'static str, Chunk>,
+    components: HashMap<String, Chunk>,
+    stats: CssCacheStats,
+}
+
+impl CssCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> CssCacheStats {
+        self.stats
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.stats.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.stats.misses
+    }
+
+    /// Zero the hit and miss counters, keeping the chunks.
+    pub fn reset_stats(&mut self) {
+        self.stats = CssCacheStats::default();
+    }
+
+    /// Number of cached chunks.
+    pub fn len(&self) -> usize {
+        self.tokens.len() + self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every chunk and zero the counters.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Reuse the chunk under `name` if its key matches, else generate it.
+fn fetch<K: Eq + Hash>(
+    chunks: &mut HashMap<K, Chunk>,
+    stats: &mut CssCacheStats,
+    name: K,
+    key: u64,
+    generate: impl FnOnce() -> (String, String),
+) {
+    match chunks.get(&name) {
+        Some(chunk) if chunk.key == key => stats.hits += 1,
+        _ => {
+            stats.misses += 1;
+            chunks.insert(
+                name,
+                Chunk {
+                    key,
+                    css: generate(),
+                },
+            );
+        }
+    }
+}
+
+/// Hash of several hashes, in order.
+fn combine(hashes: &[u64]) -> u64 {
+    let bytes: Vec<u8> = hashes.iter().flat_map(|h| h.to_le_bytes()).collect();
+    content_hash(&bytes)
+}
+
+/// The token a path names, with `colors.blue-500` naming the
+/// `colors.blue` scale.
+fn resolve_token<'a>(tokens: &'a DesignTokens, path: &str) -> Option<(String, &'a TokenValue)> {
+    let lookup = |path: &str| {
+        let (prefix, name) = path.rsplit_once('.')?;
+        let (_, map) = tokens
+            .categories()
+            .into_iter()
+            .find(|(p, _)| *p == prefix)?;
+        map.get(name)
+    };
+    if let Some(value) = lookup(path) {
+        return Some((path.to_string(), value));
+    }
+    let (base, _) = path.rsplit_once('-')?;
+    match lookup(base)? {
+        value @ TokenValue::Scale(_) => Some((base.to_string(), value)),
+        _ => None,
+    }
+}
+
+/// Every token `roots` reference, directly or through other tokens.
+fn transitive_refs(
+    tokens: &DesignTokens,
+    deps: &IndexMap<String, Vec<String>>,
+    roots: impl IntoIterator<Item = String>,
+) -> BTreeSet<String> {
+    let mut seen = BTreeSet::new();
+    let mut stack: Vec<String> = roots.into_iter().collect();
+    while let Some(path) = stack.pop() {
+        let path = resolve_token(tokens, &path).map_or(path, |(path, _)| path);
+        for target in deps.get(&path).into_iter().flatten() {
+            if seen.insert(target.clone()) {
+                stack.push(target.clone());
+            }
+        }
+    }
+    seen
+}
+
+/// Hash of the paths and current values of `refs`.
+fn refs_hash(tokens: &DesignTokens, refs: &BTreeSet<String>) -> u64 {
+    let mut bytes = Vec::new();
+    for path in refs {
+        bytes.extend(path.as_bytes());
+        bytes.push(0);
+        if let Some((_, value)) = resolve_token(tokens, path) {
+            bytes.extend(serde_json::to_vec(value).unwrap_or_default());
+        }
+        bytes.push(0);
+    }
+    content_hash(&bytes)
+}
+
+impl DesignTokens {
+    /// Content hash of one category's values and descriptions, `None` for
+    /// an unknown prefix.
+    pub fn category_hash(&self, prefix: &str) -> Option<u64> {
+        let (prefix, map) = self.categories().into_iter().find(|(p, _)| *p == prefix)?;
+        let mut bytes = serde_json::to_vec(map).unwrap_or_default();
+        for name in map.keys() {
+            let path = format!("{}.{}", prefix, name);
+            if let Some(description) = self.docs.get(&path).and_then(|d| d.description.as_ref()) {
+                bytes.extend(path.as_bytes());
+                bytes.extend(description.as_bytes());
+            }
+        }
+        Some(content_hash(&bytes))
+    }
+
+    /// Content hash of every category, in category order.
+    pub fn category_hashes(&self) -> IndexMap<&'static str, u64> {
+        self.categories()
+            .into_iter()
+            .filter_map(|(prefix, _)| Some((prefix, self.category_hash(prefix)?)))
+            .collect()
+    }
+}
+
+impl ComponentStyles {
+    /// Content hash of a component's style with `extends` applied, `None`
+    /// for an unknown component. Timestamps are left out.
+    pub fn component_hash(&self, component: &str) -> Option<u64> {
+        let mut style = self.resolve(component)?;
+        style.timestamps = Timestamps::default();
+        Some(content_hash(
+            &serde_json::to_vec(&style).unwrap_or_default(),
+        ))
+    }
+
+    /// [`generate_css_ordered`](Self::generate_css_ordered), reusing the
+    /// chunks in `cache` of components whose style and referenced `tokens`
+    /// are unchanged.
+    pub fn generate_css_cached(
+        &self,
+        tokens: &DesignTokens,
+        ordering: &OutputOrdering,
+        cache: &mut CssCache,
+    ) -> String {
+        let styles = &*self.ordered(ordering);
+        trace_span!("css", "generate_css_cached", count = styles.styles.len());
+        let deps = tokens.find_dependencies();
+        for name in styles.styles.keys() {
+            let Some(style) = styles.resolve(name) else {
+                continue;
+            };
+            let roots = std::iter::once(&style.base)
+                .chain(style.states.values())
+                .chain(style.breakpoints.values())
+                .flat_map(|props| value_refs(&props.to_css()).collect::<Vec<_>>());
+            let refs = transitive_refs(tokens, &deps, roots);
+            let Some(hash) = styles.component_hash(name) else {
+                continue;
+            };
+            let key = combine(&[hash, refs_hash(tokens, &refs)]);
+            fetch(
+                &mut cache.components,
+                &mut cache.stats,
+                name.clone(),
+                key,
+                || (style.to_css(name), String::new()),
+            );
+        }
+        cache
+            .components
+            .retain(|name, _| styles.styles.contains_key(name));
+
+        styles
+            .styles
+            .keys()
+            .filter_map(|name| cache.components.get(name))
+            .map(|chunk| chunk.css.0.as_str())
+            .collect()
+    }
+}
+
+impl CssDesigner {
+    /// [`generate_css_ordered`](Self::generate_css_ordered), reusing the
+    /// chunks in `cache` of categories whose tokens and referenced tokens
+    /// are unchanged.
+    pub fn generate_css_cached(&self, ordering: &OutputOrdering, cache: &mut CssCache) -> String {
+        let tokens = &*self.tokens.ordered(ordering);
+        trace_span!("css", "generate_css_cached", count = tokens.count());
+        let deps = tokens.find_dependencies();
+        let mut present = Vec::new();
+        for (prefix, map) in tokens.categories() {
+            if map.is_empty() {
+                continue;
+            }
+            let roots = map.keys().map(|name| format!("{}.{}", prefix, name));
+            let refs = transitive_refs(tokens, &deps, roots);
+            let Some(hash) = tokens.category_hash(prefix) else {
+                continue;
+            };
+            let key = combine(&[
+                hash,
+                refs_hash(tokens, &refs),
+                u64::from(self.token_comments),
+            ]);
+            fetch(&mut cache.tokens, &mut cache.stats, prefix, key, || {
+                category_css(prefix, map, &tokens.docs, self.token_comments)
+            });
+            present.push(prefix);
+        }
+        cache.tokens.retain(|prefix, _| present.contains(prefix));
+
+        let chunks = present.iter().filter_map(|prefix| cache.tokens.get(prefix));
+        assemble_tokens_css(chunks.map(|chunk| &chunk.css), &self.dark_mode, ":root")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{ComponentStyle, DarkModeStrategy, StateVariant, StyleProperties};
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn designer() -> CssDesigner {
+        let mut designer =
+            CssDesigner::new().with_dark_mode(DarkModeStrategy::Selector(".dark".into()));
+        let tokens = &mut designer.tokens;
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("#3b82f6"));
+        tokens.colors.insert(
+            "surface".to_string(),
+            TokenValue::Adaptive {
+                light: "#ffffff".to_string(),
+                dark: "#111827".to_string(),
+            },
+        );
+        tokens
+            .colors
+            .insert("accent".to_string(), simple("#f59e0b"));
+        tokens.shadows.insert(
+            "focus".to_string(),
+            simple("0 0 0 2px var(--color-primary)"),
+        );
+        tokens.spacing.insert("md".to_string(), simple("1rem"));
+        tokens.radius.insert("md".to_string(), simple("0.5rem"));
+        designer
+    }
+
+    fn styles() -> ComponentStyles {
+        let mut styles = ComponentStyles::new();
+        let mut button = ComponentStyle::default();
+        button.base.background = Some("var(--color-primary)".to_string());
+        button.states.insert(
+            StateVariant::Hover,
+            StyleProperties {
+                box_shadow: Some("var(--shadow-focus)".to_string()),
+                ..Default::default()
+            },
+        );
+        styles.set("button".to_string(), button);
+        let mut card = ComponentStyle::default();
+        card.base.padding = Some("var(--spacing-md)".to_string());
+        styles.set("card".to_string(), card);
+        let mut link = ComponentStyle::default();
+        link.base.color = Some("var(--color-accent)".to_string());
+        styles.set("link".to_string(), link);
+        let mut primary_card = ComponentStyle {
+            extends: Some("card".to_string()),
+            ..Default::default()
+        };
+        primary_card.base.border_radius = Some("var(--radius-md)".to_string());
+        styles.set("primary-card".to_string(), primary_card);
+        styles
+    }
+
+    #[test]
+    fn test_cached_output_matches_uncached() {
+        let mut designer = designer().with_token_comments(true);
+        let mut styles = styles();
+        let mut cache = CssCache::new();
+        let ordering = OutputOrdering::default();
+
+        for _ in 0..2 {
+            assert_eq!(
+                designer.generate_css_cached(&ordering, &mut cache),
+                designer.generate_css()
+            );
+            let css = styles.generate_css_cached(&designer.tokens, &ordering, &mut cache);
+            assert_eq!(css, styles.generate_css());
+        }
+
+        // Edits, removals and toggled comments all show up in cached output.
+        designer.tokens.spacing.shift_remove("md");
+        designer
+            .tokens
+            .z_index
+            .insert("modal".to_string(), simple("100"));
+        designer.token_comments = false;
+        styles.styles.shift_remove("link");
+        styles.get_mut("card").unwrap().base.margin = Some("0 auto".to_string());
+        assert_eq!(
+            designer.generate_css_cached(&ordering, &mut cache),
+            designer.generate_css()
+        );
+        let css = styles.generate_css_cached(&designer.tokens, &ordering, &mut cache);
+        assert_eq!(css, styles.generate_css());
+        assert!(css.contains(".primary-card {\n  padding: var(--spacing-md);"));
+    }
+
+    #[test]
+    fn test_editing_a_color_regenerates_only_dependent_chunks() {
+        let mut designer = designer();
+        let styles = styles();
+        let mut cache = CssCache::new();
+        let ordering = OutputOrdering::default();
+        designer.generate_css_cached(&ordering, &mut cache);
+        styles.generate_css_cached(&designer.tokens, &ordering, &mut cache);
+        // colors, spacing, radius, shadows; button, card, link, primary-card.
+        assert_eq!(cache.stats(), CssCacheStats { hits: 0, misses: 8 });
+        assert_eq!(cache.len(), 8);
+
+        cache.reset_stats();
+        designer.generate_css_cached(&ordering, &mut cache);
+        styles.generate_css_cached(&designer.tokens, &ordering, &mut cache);
+        assert_eq!(cache.stats(), CssCacheStats { hits: 8, misses: 0 });
+        assert_eq!(cache.stats().hit_rate(), 1.0);
+
+        // Shadows reference the color; the button references it directly
+        // and through the shadow. Nothing else sees it.
+        cache.reset_stats();
+        designer
+            .tokens
+            .colors
+            .insert("primary".to_string(), simple("#2563eb"));
+        let css = designer.generate_css_cached(&ordering, &mut cache);
+        assert!(css.contains("--color-primary: #2563eb;"));
+        assert_eq!(cache.stats(), CssCacheStats { hits: 2, misses: 2 });
+        cache.reset_stats();
+        styles.generate_css_cached(&designer.tokens, &ordering, &mut cache);
+        assert_eq!(cache.stats(), CssCacheStats { hits: 3, misses: 1 });
+    }
+
+    #[test]
+    fn test_hashes_ignore_timestamps_and_track_extends() {
+        let designer = designer();
+        let mut styles = styles();
+        let hashes = designer.tokens.category_hashes();
+        assert_eq!(hashes.len(), 9);
+        assert_eq!(
+            designer.tokens.category_hash("colors"),
+            hashes.get("colors").copied()
+        );
+        assert_eq!(designer.tokens.category_hash("unknown"), None);
+
+        let before = styles.component_hash("primary-card").unwrap();
+        styles
+            .get_mut("primary-card")
+            .unwrap()
+            .timestamps
+            .touch(1_700_000_000, Some("ana"));
+        assert_eq!(styles.component_hash("primary-card"), Some(before));
+
+        // A change to the base style changes the extending style's hash.
+        styles.get_mut("card").unwrap().base.padding = Some("2rem".to_string());
+        assert_ne!(styles.component_hash("primary-card"), Some(before));
+        assert_eq!(styles.component_hash("missing"), None);
+    }
+}
@@ -0,0 +1,286 @@
+//! Token category presentation for the designer sidebar.
+//!
+//! Every built-in [`TokenCategory`] has a default icon and accent color.
+//! Projects can override these or assign them to custom categories through
+//! [`CategoryPresentations`]; anything unassigned falls back to a neutral tag.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::{DesignTokens, TokenCategory, ValidationSeverity};
+
+/// Icon used for categories without a presentation.
+pub const FALLBACK_ICON: &str = "tag";
+
+/// Accent color used for categories without a presentation.
+pub const FALLBACK_ACCENT: &str = "#64748b";
+
+impl TokenCategory {
+    pub fn all() -> &'static [TokenCategory] {
+        &[
+            TokenCategory::Colors,
+            TokenCategory::Spacing,
+            TokenCategory::Radius,
+            TokenCategory::Shadows,
+            TokenCategory::Typography,
+            TokenCategory::Transitions,
+            TokenCategory::ZIndex,
+        ]
+    }
+
+    /// Token path prefix for the category.
+    pub fn key(&self) -> &'static str {
+        match self {
+            TokenCategory::Colors => "colors",
+            TokenCategory::Spacing => "spacing",
+            TokenCategory::Radius => "radius",
+            TokenCategory::Shadows => "shadows",
+            TokenCategory::Typography => "typography",
+            TokenCategory::Transitions => "transitions",
+            TokenCategory::ZIndex => "z-index",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|c| c.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TokenCategory::Colors => "Colors",
+            TokenCategory::Spacing => "Spacing",
+            TokenCategory::Radius => "Radius",
+            TokenCategory::Shadows => "Shadows",
+            TokenCategory::Typography => "Typography",
+            TokenCategory::Transitions => "Transitions",
+            TokenCategory::ZIndex => "Z-Index",
+        }
+    }
+}
+
+/// Icon and accent color for a category.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryPresentation {
+    /// Icon name (e.g. "palette").
+    pub icon: String,
+    /// Accent color, any CSS color value.
+    pub accent: String,
+}
+
+impl CategoryPresentation {
+    pub fn new(icon: impl Into<String>, accent: impl Into<String>) -> Self {
+        Self {
+            icon: icon.into(),
+            accent: accent.into(),
+        }
+    }
+
+    /// Default presentation for a built-in category.
+    pub fn builtin(category: TokenCategory) -> Self {
+        let (icon, accent) = match category {
+            TokenCategory::Colors => ("palette", "#ec4899"),
+            TokenCategory::Spacing => ("ruler", "#3b82f6"),
+            TokenCategory::Radius => ("corner", "#8b5cf6"),
+            TokenCategory::Shadows => ("layers", "#475569"),
+            TokenCategory::Typography => ("type", "#f59e0b"),
+            TokenCategory::Transitions => ("timer", "#10b981"),
+            TokenCategory::ZIndex => ("stack", "#06b6d4"),
+        };
+        Self::new(icon, accent)
+    }
+
+    /// Presentation for categories nobody assigned one to.
+    pub fn fallback() -> Self {
+        Self::new(FALLBACK_ICON, FALLBACK_ACCENT)
+    }
+}
+
+/// Per-project presentation overrides, keyed by category key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryPresentations {
+    #[serde(default)]
+    pub overrides: IndexMap<String, CategoryPresentation>,
+}
+
+impl CategoryPresentations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_override(
+        mut self,
+        key: impl Into<String>,
+        presentation: CategoryPresentation,
+    ) -> Self {
+        self.overrides.insert(key.into(), presentation);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Override, then built-in default, then fallback.
+    pub fn resolve(&self, key: &str) -> CategoryPresentation {
+        self.overrides
+            .get(key)
+            .cloned()
+            .or_else(|| TokenCategory::from_key(key).map(CategoryPresentation::builtin))
+            .unwrap_or_else(CategoryPresentation::fallback)
+    }
+}
+
+/// One row in the category sidebar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryListItem {
+    pub key: String,
+    pub label: String,
+    /// Built-in category, if this isn't a custom one.
+    pub category: Option<TokenCategory>,
+    pub presentation: CategoryPresentation,
+    pub token_count: usize,
+    /// Validation errors on tokens in this category.
+    pub errors: usize,
+    /// Validation warnings on tokens in this category.
+    pub warnings: usize,
+}
+
+/// Category sidebar view model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CategoryListView {
+    pub items: Vec<CategoryListItem>,
+}
+
+impl CategoryListView {
+    /// Build the sidebar rows.
+    ///
+    /// Categories follow `order`; built-ins missing from it are appended in
+    /// their default order. Keys that aren't built-in are custom categories.
+    pub fn build(
+        tokens: &DesignTokens,
+        order: &[String],
+        presentation: &CategoryPresentations,
+    ) -> Self {
+        let mut keys: Vec<&str> = Vec::new();
+        for key in order.iter().map(String::as_str) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        for key in TokenCategory::all().iter().map(TokenCategory::key) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let paths = tokens.all_paths();
+        let problems = tokens.validate();
+        let in_category =
+            |path: &str, key: &str| path.strip_prefix(key).is_some_and(|r| r.starts_with('.'));
+
+        let items = keys
+            .into_iter()
+            .map(|key| {
+                let category = TokenCategory::from_key(key);
+                let count_problems = |severity: ValidationSeverity| {
+                    problems
+                        .iter()
+                        .filter(|p| p.severity == severity && in_category(&p.path, key))
+                        .count()
+                };
+                CategoryListItem {
+                    key: key.to_string(),
+                    label: category
+                        .map(|c| c.label().to_string())
+                        .unwrap_or_else(|| custom_label(key)),
+                    category,
+                    presentation: presentation.resolve(key),
+                    token_count: paths.iter().filter(|p| in_category(p, key)).count(),
+                    errors: count_problems(ValidationSeverity::Error),
+                    warnings: count_problems(ValidationSeverity::Warning),
+                }
+            })
+            .collect();
+
+        Self { items }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CategoryListItem> {
+        self.items.iter().find(|i| i.key == key)
+    }
+}
+
+/// "brand-motion" -> "Brand Motion".
+fn custom_label(key: &str) -> String {
+    key.split(['-', '_'])
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+
+    #[test]
+    fn test_builtin_defaults() {
+        let presentations = CategoryPresentations::new();
+        for category in TokenCategory::all() {
+            let resolved = presentations.resolve(category.key());
+            assert_ne!(resolved, CategoryPresentation::fallback());
+            assert_eq!(resolved, CategoryPresentation::builtin(*category));
+            assert_eq!(TokenCategory::from_key(category.key()), Some(*category));
+        }
+    }
+
+    #[test]
+    fn test_custom_categories_fall_back() {
+        let presentations = CategoryPresentations::new()
+            .with_override("brand", CategoryPresentation::new("star", "#ff0000"));
+        let order = vec!["brand-motion".to_string(), "brand".to_string()];
+
+        let view = CategoryListView::build(&DesignTokens::default(), &order, &presentations);
+
+        assert_eq!(view.items.len(), TokenCategory::all().len() + 2);
+        let motion = &view.items[0];
+        assert_eq!(motion.label, "Brand Motion");
+        assert_eq!(motion.category, None);
+        assert_eq!(motion.presentation, CategoryPresentation::fallback());
+        assert_eq!(motion.token_count, 0);
+        assert_eq!(view.items[1].presentation.icon, "star");
+        assert_eq!(view.items[2].key, "colors");
+    }
+
+    #[test]
+    fn test_counts_and_problem_badges() {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#3b82f6".to_string()),
+        );
+        tokens
+            .colors
+            .insert("broken".to_string(), TokenValue::Simple("#12".to_string()));
+        tokens
+            .typography
+            .sizes
+            .insert("sm".to_string(), TokenValue::Simple("0.875rem".to_string()));
+
+        let view = CategoryListView::build(&tokens, &[], &CategoryPresentations::new());
+
+        let colors = view.get("colors").unwrap();
+        assert_eq!(colors.token_count, 2);
+        assert_eq!(colors.warnings, 1);
+        assert_eq!(view.get("spacing").unwrap().warnings, 0);
+        assert_eq!(view.get("typography").unwrap().token_count, 1);
+        assert_eq!(view.get("spacing").unwrap().token_count, 0);
+    }
+}
@@ -0,0 +1,344 @@
+//! Color math for token tooling: hex parsing, HSL, OKLCH, WCAG luminance
+//! and color vision deficiency simulation.
+
+/// An sRGB color with components in `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub alpha: f64,
+}
+
+impl Rgb {
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Self {
+            r,
+            g,
+            b,
+            alpha: 1.0,
+        }
+    }
+
+    /// Parse `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`.
+    pub fn from_hex(value: &str) -> Option<Self> {
+        let hex = value.trim().strip_prefix('#')?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let digits: Vec<u8> = match hex.len() {
+            3 | 4 => hex
+                .chars()
+                .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16).ok())
+                .collect::<Option<_>>()?,
+            6 | 8 => (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        let channel = |i: usize| digits[i] as f64 / 255.0;
+        Some(Self {
+            r: channel(0),
+            g: channel(1),
+            b: channel(2),
+            alpha: digits.get(3).map_or(1.0, |a| *a as f64 / 255.0),
+        })
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when not opaque.
+    pub fn to_hex(&self) -> String {
+        let byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let mut hex = format!(
+            "#{:02x}{:02x}{:02x}",
+            byte(self.r),
+            byte(self.g),
+            byte(self.b)
+        );
+        if byte(self.alpha) != 255 {
+            hex.push_str(&format!("{:02x}", byte(self.alpha)));
+        }
+        hex
+    }
+
+    /// WCAG relative luminance.
+    pub fn luminance(&self) -> f64 {
+        let [r, g, b] = [self.r, self.g, self.b].map(to_linear);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// How the color looks with a color vision deficiency.
+    pub fn simulate(&self, kind: ColorBlindness) -> Self {
+        let [r, g, b] = [self.r, self.g, self.b].map(to_linear);
+        let [mr, mg, mb] = kind.matrix();
+        let row = |m: [f64; 3]| from_linear((m[0] * r + m[1] * g + m[2] * b).clamp(0.0, 1.0));
+        Self {
+            r: row(mr),
+            g: row(mg),
+            b: row(mb),
+            alpha: self.alpha,
+        }
+    }
+
+    fn in_gamut(&self) -> bool {
+        const EPS: f64 = 1e-6;
+        [self.r, self.g, self.b]
+            .iter()
+            .all(|c| (-EPS..=1.0 + EPS).contains(c))
+    }
+}
+
+/// Color vision deficiency to simulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorBlindness {
+    /// No red cones.
+    Protanopia,
+    /// No green cones.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    pub const ALL: [Self; 3] = [Self::Protanopia, Self::Deuteranopia, Self::Tritanopia];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Protanopia => "Protanopia",
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// Transform on linear RGB, from Machado, Oliveira and Fernandes (2009)
+    /// at full severity.
+    pub fn matrix(&self) -> [[f64; 3]; 3] {
+        match self {
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+/// WCAG contrast ratio between two colors, from 1.0 to 21.0.
+pub fn contrast_ratio(a: &Rgb, b: &Rgb) -> f64 {
+    let (la, lb) = (a.luminance(), b.luminance());
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+fn to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// A color in HSL: hue in degrees, saturation and lightness in `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+    pub alpha: f64,
+}
+
+impl From<Rgb> for Hsl {
+    fn from(rgb: Rgb) -> Self {
+        let max = rgb.r.max(rgb.g).max(rgb.b);
+        let min = rgb.r.min(rgb.g).min(rgb.b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        if d == 0.0 {
+            return Self {
+                h: 0.0,
+                s: 0.0,
+                l,
+                alpha: rgb.alpha,
+            };
+        }
+        let h = if max == rgb.r {
+            (rgb.g - rgb.b) / d
+        } else if max == rgb.g {
+            (rgb.b - rgb.r) / d + 2.0
+        } else {
+            (rgb.r - rgb.g) / d + 4.0
+        };
+        Self {
+            h: (h * 60.0).rem_euclid(360.0),
+            s: d / (1.0 - (2.0 * l - 1.0).abs()),
+            l,
+            alpha: rgb.alpha,
+        }
+    }
+}
+
+impl Hsl {
+    pub fn to_rgb(self) -> Rgb {
+        let (h, s, l) = (self.h.rem_euclid(360.0), self.s, self.l);
+        let channel = |n: f64| {
+            let k = (n + h / 30.0) % 12.0;
+            l - s * l.min(1.0 - l) * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+        };
+        Rgb {
+            r: channel(0.0),
+            g: channel(8.0),
+            b: channel(4.0),
+            alpha: self.alpha,
+        }
+    }
+}
+
+/// A color in OKLCH: lightness `0.0..=1.0`, chroma, hue in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+    pub alpha: f64,
+}
+
+impl From<Rgb> for Oklch {
+    fn from(rgb: Rgb) -> Self {
+        let [r, g, b] = [rgb.r, rgb.g, rgb.b].map(to_linear);
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        let lightness = 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s;
+        let a = 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s;
+        let b = 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s;
+        Self {
+            l: lightness,
+            c: a.hypot(b),
+            h: b.atan2(a).to_degrees().rem_euclid(360.0),
+            alpha: rgb.alpha,
+        }
+    }
+}
+
+impl Oklch {
+    /// Convert without gamut mapping; components may fall outside `0..=1`.
+    fn to_rgb_unclamped(self) -> Rgb {
+        let (a, b) = (
+            self.c * self.h.to_radians().cos(),
+            self.c * self.h.to_radians().sin(),
+        );
+        let l = (self.l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m = (self.l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s = (self.l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+        Rgb {
+            r: from_linear(4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s),
+            g: from_linear(-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s),
+            b: from_linear(-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s),
+            alpha: self.alpha,
+        }
+    }
+
+    /// Convert to sRGB, reducing chroma until the color fits the gamut.
+    pub fn to_rgb(self) -> Rgb {
+        let rgb = self.to_rgb_unclamped();
+        if rgb.in_gamut() {
+            return rgb;
+        }
+        let (mut lo, mut hi) = (0.0, self.c);
+        for _ in 0..24 {
+            let mid = (lo + hi) / 2.0;
+            if (Oklch { c: mid, ..self }).to_rgb_unclamped().in_gamut() {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let rgb = Oklch { c: lo, ..self }.to_rgb_unclamped();
+        Rgb {
+            r: rgb.r.clamp(0.0, 1.0),
+            g: rgb.g.clamp(0.0, 1.0),
+            b: rgb.b.clamp(0.0, 1.0),
+            alpha: rgb.alpha,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        for hex in ["#000000", "#ffffff", "#3b82f6", "#0f172a80"] {
+            assert_eq!(Rgb::from_hex(hex).unwrap().to_hex(), hex);
+        }
+        assert_eq!(Rgb::from_hex("#fa0").unwrap().to_hex(), "#ffaa00");
+        assert!(Rgb::from_hex("#12345").is_none());
+        assert!(Rgb::from_hex("blue").is_none());
+    }
+
+    #[test]
+    fn test_oklch_known_values() {
+        let white = Oklch::from(Rgb::new(1.0, 1.0, 1.0));
+        assert!((white.l - 1.0).abs() < 1e-4 && white.c < 1e-4);
+
+        // Reference value for pure red: oklch(62.8% 0.2577 29.23).
+        let red = Oklch::from(Rgb::new(1.0, 0.0, 0.0));
+        assert!((red.l - 0.628).abs() < 1e-3);
+        assert!((red.c - 0.2577).abs() < 1e-3);
+        assert!((red.h - 29.23).abs() < 0.1);
+        assert_eq!(red.to_rgb().to_hex(), "#ff0000");
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        let hsl = Hsl::from(Rgb::from_hex("#3b82f6").unwrap());
+        assert!((hsl.h - 217.2).abs() < 0.1);
+        assert!((hsl.s - 0.912).abs() < 1e-3 && (hsl.l - 0.598).abs() < 1e-3);
+        assert_eq!(hsl.to_rgb().to_hex(), "#3b82f6");
+        assert_eq!(Hsl::from(Rgb::new(0.5, 0.5, 0.5)).s, 0.0);
+    }
+
+    #[test]
+    fn test_simulate_reference_colors() {
+        let simulate = |hex: &str, kind| Rgb::from_hex(hex).unwrap().simulate(kind).to_hex();
+        assert_eq!(simulate("#ff0000", ColorBlindness::Protanopia), "#6d5f00");
+        assert_eq!(simulate("#ff0000", ColorBlindness::Deuteranopia), "#a39000");
+        assert_eq!(simulate("#ff0000", ColorBlindness::Tritanopia), "#ff000f");
+        assert_eq!(simulate("#00ff00", ColorBlindness::Deuteranopia), "#efd63a");
+        assert_eq!(simulate("#0000ff", ColorBlindness::Tritanopia), "#006b96");
+        assert_eq!(simulate("#3b82f680", ColorBlindness::Tritanopia), "#009eb080");
+        // Neutrals are unchanged.
+        for kind in ColorBlindness::ALL {
+            for hex in ["#000000", "#808080", "#ffffff"] {
+                assert_eq!(simulate(hex, kind), hex);
+            }
+        }
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Rgb::new(0.0, 0.0, 0.0);
+        let white = Rgb::new(1.0, 1.0, 1.0);
+        assert!((contrast_ratio(&black, &white) - 21.0).abs() < 1e-9);
+        assert!((contrast_ratio(&white, &white) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,359 @@
+//! WCAG contrast checks between color tokens.
+//!
+//! Pairs listed in [`DesignTokens::contrast_pairs`] (or the defaults) are
+//! checked by [`DesignTokens::validate`], and failures show up in the
+//! Validation panel as warnings on the foreground token.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::color::{Hsl, Rgb, contrast_ratio};
+use super::{DesignTokens, TokenValidationError, TokenValue, ValidationSeverity};
+
+/// Minimum ratio for AA, normal text.
+pub const AA_NORMAL: f64 = 4.5;
+/// Minimum ratio for AA, large text.
+pub const AA_LARGE: f64 = 3.0;
+/// Minimum ratio for AAA, normal text.
+pub const AAA_NORMAL: f64 = 7.0;
+/// Minimum ratio for AAA, large text.
+pub const AAA_LARGE: f64 = 4.5;
+
+/// Contrast check error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ContrastError {
+    /// No token exists at the path.
+    #[error("Token '{0}' not found")]
+    NotFound(String),
+
+    /// The token is a scale, which has no single color.
+    #[error("Token '{0}' is a scale; check one of its steps")]
+    Scale(String),
+
+    /// The value isn't a color this module can parse.
+    #[error("Token '{path}' has an unsupported color value '{value}'")]
+    InvalidColor { path: String, value: String },
+}
+
+/// Contrast of one foreground/background combination.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContrastResult {
+    pub ratio: f64,
+    pub aa_normal: bool,
+    pub aa_large: bool,
+    pub aaa_normal: bool,
+    pub aaa_large: bool,
+}
+
+impl ContrastResult {
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio,
+            aa_normal: ratio >= AA_NORMAL,
+            aa_large: ratio >= AA_LARGE,
+            aaa_normal: ratio >= AAA_NORMAL,
+            aaa_large: ratio >= AAA_LARGE,
+        }
+    }
+}
+
+/// Contrast between two tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastReport {
+    pub fg: String,
+    pub bg: String,
+    pub light: ContrastResult,
+    /// Present when either token is Adaptive.
+    pub dark: Option<ContrastResult>,
+}
+
+impl ContrastReport {
+    /// Whether every mode passes AA for normal text.
+    pub fn passes_aa(&self) -> bool {
+        self.light.aa_normal && self.dark.is_none_or(|d| d.aa_normal)
+    }
+}
+
+/// A foreground token checked against a background token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContrastPair {
+    pub fg: String,
+    pub bg: String,
+}
+
+impl ContrastPair {
+    pub fn new(fg: impl Into<String>, bg: impl Into<String>) -> Self {
+        Self {
+            fg: fg.into(),
+            bg: bg.into(),
+        }
+    }
+}
+
+/// Pairs checked when none are configured.
+pub fn default_contrast_pairs() -> Vec<ContrastPair> {
+    vec![
+        ContrastPair::new("colors.text", "colors.background"),
+        ContrastPair::new("colors.primary-foreground", "colors.primary"),
+    ]
+}
+
+/// Parse `#hex`, `rgb()`/`rgba()` or `hsl()`/`hsla()`, in either the comma
+/// or the space-separated syntax.
+pub fn parse_color(value: &str) -> Option<Rgb> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return Rgb::from_hex(value);
+    }
+    let (func, args) = value.strip_suffix(')')?.split_once('(')?;
+    let args: Vec<&str> = args
+        .split([',', ' ', '/'])
+        .filter(|a| !a.is_empty())
+        .collect();
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let alpha = match args.get(3) {
+        Some(a) => fraction(a, 1.0)?,
+        None => 1.0,
+    };
+    let rgb = match func.trim().to_ascii_lowercase().as_str() {
+        "rgb" | "rgba" => Rgb::new(
+            fraction(args[0], 255.0)?,
+            fraction(args[1], 255.0)?,
+            fraction(args[2], 255.0)?,
+        ),
+        "hsl" | "hsla" => Hsl {
+            h: args[0].trim_end_matches("deg").parse().ok()?,
+            s: fraction(args[1].strip_suffix('%')?, 100.0)?,
+            l: fraction(args[2].strip_suffix('%')?, 100.0)?,
+            alpha: 1.0,
+        }
+        .to_rgb(),
+        _ => return None,
+    };
+    Some(Rgb { alpha, ..rgb })
+}
+
+/// A number scaled by `max`, or a percentage, as `0.0..=1.0`.
+fn fraction(value: &str, max: f64) -> Option<f64> {
+    let n = match value.strip_suffix('%') {
+        Some(pct) => pct.parse::<f64>().ok()? / 100.0,
+        None => value.parse::<f64>().ok()? / max,
+    };
+    Some(n.clamp(0.0, 1.0))
+}
+
+/// Composite a translucent foreground over an opaque background.
+fn over(fg: &Rgb, bg: &Rgb) -> Rgb {
+    let mix = |f: f64, b: f64| f * fg.alpha + b * (1.0 - fg.alpha);
+    Rgb::new(mix(fg.r, bg.r), mix(fg.g, bg.g), mix(fg.b, bg.b))
+}
+
+impl DesignTokens {
+    /// Light and dark colors of a token; both are the same for Simple.
+    fn color_modes(&self, path: &str) -> Result<(Rgb, Option<Rgb>), ContrastError> {
+        let value = path
+            .rsplit_once('.')
+            .and_then(|(prefix, name)| {
+                let (_, map) = self.categories().into_iter().find(|(p, _)| *p == prefix)?;
+                map.get(name)
+            })
+            .ok_or_else(|| ContrastError::NotFound(path.to_string()))?;
+        let parse = |v: &str| {
+            parse_color(v).ok_or_else(|| ContrastError::InvalidColor {
+                path: path.to_string(),
+                value: v.to_string(),
+            })
+        };
+        match value {
+            TokenValue::Simple(v) => Ok((parse(v)?, None)),
+            TokenValue::Adaptive { light, dark } => Ok((parse(light)?, Some(parse(dark)?))),
+            TokenValue::Scale(_) => Err(ContrastError::Scale(path.to_string())),
+        }
+    }
+
+    /// WCAG contrast of `fg_path` on `bg_path`. Adaptive tokens are
+    /// checked in both modes.
+    pub fn check_contrast(
+        &self,
+        fg_path: &str,
+        bg_path: &str,
+    ) -> Result<ContrastReport, ContrastError> {
+        let (fg_light, fg_dark) = self.color_modes(fg_path)?;
+        let (bg_light, bg_dark) = self.color_modes(bg_path)?;
+        let result = |fg: &Rgb, bg: &Rgb| ContrastResult::new(contrast_ratio(&over(fg, bg), bg));
+
+        let dark = (fg_dark.is_some() || bg_dark.is_some())
+            .then(|| result(&fg_dark.unwrap_or(fg_light), &bg_dark.unwrap_or(bg_light)));
+        Ok(ContrastReport {
+            fg: fg_path.to_string(),
+            bg: bg_path.to_string(),
+            light: result(&fg_light, &bg_light),
+            dark,
+        })
+    }
+
+    /// Check several pairs at once.
+    pub fn check_contrast_pairs(
+        &self,
+        pairs: &[ContrastPair],
+    ) -> Vec<Result<ContrastReport, ContrastError>> {
+        pairs
+            .iter()
+            .map(|p| self.check_contrast(&p.fg, &p.bg))
+            .collect()
+    }
+
+    /// Configured pairs, or the defaults when none are.
+    pub(super) fn checked_contrast_pairs(&self) -> Vec<ContrastPair> {
+        if self.contrast_pairs.is_empty() {
+            default_contrast_pairs()
+        } else {
+            self.contrast_pairs.clone()
+        }
+    }
+
+    /// Warnings for configured pairs below AA. Pairs whose tokens are
+    /// missing or not plain colors are skipped.
+    pub(super) fn contrast_warnings(&self) -> Vec<TokenValidationError> {
+        let pairs = self.checked_contrast_pairs();
+        let mut errors = Vec::new();
+        for report in self.check_contrast_pairs(&pairs).into_iter().flatten() {
+            let modes = [("", Some(report.light)), (" in dark mode", report.dark)];
+            for (mode, result) in modes {
+                if let Some(result) = result
+                    && !result.aa_normal
+                {
+                    errors.push(TokenValidationError {
+                        path: report.fg.clone(),
+                        message: format!(
+                            "Contrast {:.2}:1 on {}{} is below AA ({}:1)",
+                            result.ratio, report.bg, mode, AA_NORMAL
+                        ),
+                        severity: ValidationSeverity::Warning,
+                    });
+                }
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    #[test]
+    fn test_parse_color_formats() {
+        let hex = |v: &str| parse_color(v).map(|c| c.to_hex());
+        assert_eq!(hex("#3b82f6").as_deref(), Some("#3b82f6"));
+        assert_eq!(hex("rgb(59, 130, 246)").as_deref(), Some("#3b82f6"));
+        assert_eq!(hex("rgb(59 130 246 / 50%)").as_deref(), Some("#3b82f680"));
+        assert_eq!(hex("rgba(0, 0, 0, 0.5)").as_deref(), Some("#00000080"));
+        assert_eq!(hex("hsl(0, 100%, 50%)").as_deref(), Some("#ff0000"));
+        assert_eq!(hex("hsl(240deg 100% 50%)").as_deref(), Some("#0000ff"));
+        assert_eq!(hex("hsla(120, 100%, 25%, 1)").as_deref(), Some("#008000"));
+        assert!(parse_color("var(--color-primary)").is_none());
+        assert!(parse_color("rgb(1, 2)").is_none());
+        assert!(parse_color("hsl(0, 100, 50)").is_none());
+    }
+
+    #[test]
+    fn test_check_contrast_levels() {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert("text".to_string(), simple("#000"));
+        tokens.colors.insert("muted".to_string(), simple("#767676"));
+        tokens.colors.insert("faint".to_string(), simple("#949494"));
+        tokens
+            .colors
+            .insert("background".to_string(), simple("#fff"));
+
+        let black = tokens
+            .check_contrast("colors.text", "colors.background")
+            .unwrap();
+        assert!((black.light.ratio - 21.0).abs() < 1e-9);
+        assert!(black.light.aaa_normal && black.dark.is_none());
+
+        // #767676 is the lightest gray that passes AA on white.
+        let muted = tokens
+            .check_contrast("colors.muted", "colors.background")
+            .unwrap();
+        assert!(muted.light.aa_normal && !muted.light.aaa_normal);
+        let faint = tokens
+            .check_contrast("colors.faint", "colors.background")
+            .unwrap();
+        assert!(!faint.light.aa_normal && faint.light.aa_large);
+
+        assert_eq!(
+            tokens.check_contrast("colors.text", "colors.surface"),
+            Err(ContrastError::NotFound("colors.surface".to_string()))
+        );
+        tokens
+            .colors
+            .insert("link".to_string(), simple("currentColor"));
+        assert!(matches!(
+            tokens.check_contrast("colors.link", "colors.background"),
+            Err(ContrastError::InvalidColor { .. })
+        ));
+    }
+
+    #[test]
+    fn test_adaptive_pairs_feed_validation() {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "text".to_string(),
+            TokenValue::Adaptive {
+                light: "#111827".to_string(),
+                dark: "#374151".to_string(),
+            },
+        );
+        tokens.colors.insert(
+            "background".to_string(),
+            TokenValue::Adaptive {
+                light: "#ffffff".to_string(),
+                dark: "#111827".to_string(),
+            },
+        );
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("#3b82f6"));
+        tokens
+            .colors
+            .insert("primary-foreground".to_string(), simple("rgb(255 255 255)"));
+
+        let reports = tokens.check_contrast_pairs(&default_contrast_pairs());
+        let text = reports[0].as_ref().unwrap();
+        assert!(text.light.aaa_normal);
+        assert!(!text.dark.unwrap().aa_large);
+        assert!(!text.passes_aa());
+
+        let warnings: Vec<(String, String)> = tokens
+            .validate()
+            .into_iter()
+            .map(|e| (e.path, e.message))
+            .collect();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].0, "colors.text");
+        assert!(warnings[0].1.ends_with("in dark mode is below AA (4.5:1)"));
+        // White on blue-500 is 3.68:1.
+        assert_eq!(
+            warnings[1],
+            (
+                "colors.primary-foreground".to_string(),
+                "Contrast 3.68:1 on colors.primary is below AA (4.5:1)".to_string()
+            )
+        );
+
+        // Configured pairs replace the defaults.
+        tokens.contrast_pairs = vec![ContrastPair::new(
+            "colors.text",
+            "colors.primary-foreground",
+        )];
+        assert!(tokens.validate().is_empty());
+    }
+}
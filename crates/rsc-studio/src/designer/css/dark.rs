@@ -0,0 +1,276 @@
+//! Dark-mode derivation for color tokens.
+//!
+//! Simple hex colors become Adaptive tokens whose dark arm is derived from
+//! the light value. Adaptive tokens already have a hand-picked dark value
+//! and are left alone. Scale tokens have no dark arm in the token model,
+//! so their derived scales are reported for review but not written.
+
+use indexmap::IndexMap;
+
+use super::color::{Oklch, Rgb, contrast_ratio};
+use super::{CssDesigner, DesignTokens, TokenValue};
+
+/// Contrast (WCAG AA, normal text) below which a derived pair is flagged.
+pub const MIN_CONTRAST: f64 = 4.5;
+
+/// How dark values are derived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeriveStrategy {
+    /// Flip OKLCH lightness, keeping hue and chroma. Scale steps are
+    /// inverted one by one.
+    #[default]
+    InvertLightness,
+    /// Reverse scales so the lightest step takes the darkest value. Simple
+    /// colors are inverted as with [`DeriveStrategy::InvertLightness`].
+    MappedScale,
+}
+
+/// A derived light/dark pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DarkDerivation {
+    pub path: String,
+    pub light: TokenValue,
+    pub dark: TokenValue,
+    /// Contrast of the light value against the light background.
+    pub light_contrast: Option<f64>,
+    /// Contrast of the dark value against the dark background.
+    pub dark_contrast: Option<f64>,
+}
+
+impl DarkDerivation {
+    /// Whether the dark value falls below [`MIN_CONTRAST`] although the
+    /// light value met it.
+    pub fn loses_contrast(&self) -> bool {
+        match (self.light_contrast, self.dark_contrast) {
+            (Some(light), Some(dark)) => light >= MIN_CONTRAST && dark < MIN_CONTRAST,
+            _ => false,
+        }
+    }
+}
+
+/// Result of a dark-mode derivation, for review before keeping it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DarkModeReport {
+    /// Simple tokens converted to Adaptive.
+    pub derived: Vec<DarkDerivation>,
+    /// Derived dark scales (not written).
+    pub scales: Vec<DarkDerivation>,
+    /// Tokens that were already Adaptive.
+    pub skipped: Vec<String>,
+    /// Tokens whose values aren't hex colors.
+    pub unsupported: Vec<String>,
+}
+
+impl DarkModeReport {
+    pub fn is_empty(&self) -> bool {
+        self.derived.is_empty() && self.scales.is_empty()
+    }
+
+    /// Derived pairs that lose contrast in dark mode.
+    pub fn low_contrast(&self) -> impl Iterator<Item = &DarkDerivation> {
+        self.derived.iter().filter(|d| d.loses_contrast())
+    }
+}
+
+/// Flip the OKLCH lightness of a hex color.
+pub fn invert_lightness(value: &str) -> Option<String> {
+    let oklch = Oklch::from(Rgb::from_hex(value)?);
+    Some(
+        Oklch {
+            l: 1.0 - oklch.l,
+            ..oklch
+        }
+        .to_rgb()
+        .to_hex(),
+    )
+}
+
+fn invert_scale(steps: &IndexMap<String, String>) -> Option<IndexMap<String, String>> {
+    steps
+        .iter()
+        .map(|(step, v)| Some((step.clone(), invert_lightness(v)?)))
+        .collect()
+}
+
+fn reverse_scale(steps: &IndexMap<String, String>) -> IndexMap<String, String> {
+    steps
+        .keys()
+        .cloned()
+        .zip(steps.values().rev().cloned())
+        .collect()
+}
+
+/// Light and dark backgrounds for the contrast checks: `colors.background`
+/// when it is a hex color, white and black otherwise.
+fn backgrounds(tokens: &DesignTokens) -> (Rgb, Rgb) {
+    let white = Rgb::new(1.0, 1.0, 1.0);
+    let black = Rgb::new(0.0, 0.0, 0.0);
+    match tokens.colors.get("background") {
+        Some(TokenValue::Simple(v)) => match Rgb::from_hex(v) {
+            Some(light) => {
+                let dark = invert_lightness(v).and_then(|d| Rgb::from_hex(&d));
+                (light, dark.unwrap_or(black))
+            }
+            None => (white, black),
+        },
+        Some(TokenValue::Adaptive { light, dark }) => (
+            Rgb::from_hex(light).unwrap_or(white),
+            Rgb::from_hex(dark).unwrap_or(black),
+        ),
+        _ => (white, black),
+    }
+}
+
+impl DesignTokens {
+    /// Derive dark values for the color tokens. Simple colors become
+    /// Adaptive; everything else is only reported.
+    pub fn derive_dark_variants(&mut self, strategy: DeriveStrategy) -> DarkModeReport {
+        let mut report = DarkModeReport::default();
+        let (light_bg, dark_bg) = backgrounds(self);
+        let contrast = |value: &str, bg: &Rgb| Rgb::from_hex(value).map(|c| contrast_ratio(&c, bg));
+
+        for (name, value) in self.colors.iter_mut() {
+            let path = format!("colors.{}", name);
+            match value {
+                TokenValue::Adaptive { .. } => report.skipped.push(path),
+                TokenValue::Simple(light) => {
+                    let Some(dark) = invert_lightness(light) else {
+                        report.unsupported.push(path);
+                        continue;
+                    };
+                    let is_background = name == "background";
+                    report.derived.push(DarkDerivation {
+                        path,
+                        light_contrast: contrast(light, &light_bg).filter(|_| !is_background),
+                        dark_contrast: contrast(&dark, &dark_bg).filter(|_| !is_background),
+                        light: TokenValue::Simple(light.clone()),
+                        dark: TokenValue::Simple(dark.clone()),
+                    });
+                    *value = TokenValue::Adaptive {
+                        light: std::mem::take(light),
+                        dark,
+                    };
+                }
+                TokenValue::Scale(steps) => {
+                    let dark = match strategy {
+                        DeriveStrategy::InvertLightness => invert_scale(steps),
+                        DeriveStrategy::MappedScale => Some(reverse_scale(steps)),
+                    };
+                    match dark {
+                        Some(dark) => report.scales.push(DarkDerivation {
+                            path,
+                            light: value.clone(),
+                            dark: TokenValue::Scale(dark),
+                            light_contrast: None,
+                            dark_contrast: None,
+                        }),
+                        None => report.unsupported.push(path),
+                    }
+                }
+            }
+        }
+        report
+    }
+}
+
+impl CssDesigner {
+    /// Derive dark values for the color tokens, undoable as one step.
+    pub fn derive_dark_variants(&mut self, strategy: DeriveStrategy) -> DarkModeReport {
+        let before = self.tokens.clone();
+        let report = self.tokens.derive_dark_variants(strategy);
+        self.record("Derive dark mode", &before);
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    #[test]
+    fn test_invert_lightness_known_colors() {
+        assert_eq!(invert_lightness("#ffffff").unwrap(), "#000000");
+        assert_eq!(invert_lightness("#000000").unwrap(), "#ffffff");
+        // Mid gray sits at L = 0.5999, so it lands at L = 0.4001.
+        assert_eq!(invert_lightness("#808080").unwrap(), "#484848");
+        assert!(invert_lightness("var(--color-blue-500)").is_none());
+
+        // A saturated blue keeps its hue and, within gamut, its chroma.
+        let light = Oklch::from(Rgb::from_hex("#1e3a8a").unwrap());
+        let dark = Oklch::from(Rgb::from_hex(&invert_lightness("#1e3a8a").unwrap()).unwrap());
+        assert!((dark.l - (1.0 - light.l)).abs() < 0.01);
+        assert!((dark.h - light.h).abs() < 2.0);
+        assert!(dark.c <= light.c + 0.005);
+    }
+
+    #[test]
+    fn test_adaptive_tokens_untouched() {
+        let mut tokens = DesignTokens::default();
+        let surface = TokenValue::Adaptive {
+            light: "#ffffff".to_string(),
+            dark: "#123456".to_string(),
+        };
+        tokens.colors.insert("surface".to_string(), surface.clone());
+        tokens.colors.insert("text".to_string(), simple("#111827"));
+        tokens
+            .colors
+            .insert("link".to_string(), simple("currentColor"));
+
+        let report = tokens.derive_dark_variants(DeriveStrategy::InvertLightness);
+        assert_eq!(tokens.colors["surface"], surface);
+        assert_eq!(report.skipped, ["colors.surface"]);
+        assert_eq!(report.unsupported, ["colors.link"]);
+        assert!(matches!(
+            &tokens.colors["text"],
+            TokenValue::Adaptive { light, .. } if light == "#111827"
+        ));
+
+        // Near-black text on white stays readable as light text on black.
+        let text = &report.derived[0];
+        assert!(text.light_contrast.unwrap() > 15.0);
+        assert!(text.dark_contrast.unwrap() > 7.0);
+        assert_eq!(report.low_contrast().count(), 0);
+    }
+
+    #[test]
+    fn test_scales_and_contrast_flags() {
+        let mut tokens = DesignTokens::default();
+        let steps: IndexMap<String, String> =
+            [("100", "#dbeafe"), ("500", "#3b82f6"), ("900", "#1e3a8a")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        tokens
+            .colors
+            .insert("blue".to_string(), TokenValue::Scale(steps.clone()));
+        // Mid-lightness orange passes on white, but its inversion stays
+        // mid-lightness and fails on black.
+        tokens
+            .colors
+            .insert("warning".to_string(), simple("#b45309"));
+
+        let mut mapped = tokens.clone();
+        let report = mapped.derive_dark_variants(DeriveStrategy::MappedScale);
+        assert_eq!(mapped.colors["blue"], TokenValue::Scale(steps));
+        let TokenValue::Scale(dark) = &report.scales[0].dark else {
+            panic!("expected a scale");
+        };
+        assert_eq!(dark["100"], "#1e3a8a");
+        assert_eq!(dark["900"], "#dbeafe");
+
+        let report = tokens.derive_dark_variants(DeriveStrategy::InvertLightness);
+        let TokenValue::Scale(dark) = &report.scales[0].dark else {
+            panic!("expected a scale");
+        };
+        assert_eq!(dark["100"], invert_lightness("#dbeafe").unwrap());
+
+        let warning = &report.derived[0];
+        assert!(warning.light_contrast.unwrap() >= MIN_CONTRAST);
+        assert!(warning.loses_contrast());
+        assert_eq!(report.low_contrast().count(), 1);
+    }
+}
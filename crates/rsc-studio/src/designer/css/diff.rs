@@ -0,0 +1,363 @@
+//! Token set comparison for the import preview.
+//!
+//! [`DesignTokens::diff`] lists what applying another token set would add,
+//! remove and change. Map order is not part of a token's value, so a
+//! category or scale whose entries only moved is reported separately as
+//! informational.
+
+use indexmap::IndexMap;
+
+use super::{DesignTokens, TokenValue};
+
+/// Half of an adaptive value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptiveHalf {
+    Light,
+    Dark,
+}
+
+/// A token present on one side only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenEntry {
+    pub path: String,
+    pub value: TokenValue,
+}
+
+/// A token whose value differs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedToken {
+    pub path: String,
+    pub before: TokenValue,
+    pub after: TokenValue,
+    /// Set when both values are adaptive and only this half changed.
+    pub only: Option<AdaptiveHalf>,
+}
+
+/// Differences between two token sets, in category order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenDiff {
+    pub added: Vec<TokenEntry>,
+    pub removed: Vec<TokenEntry>,
+    pub changed: Vec<ChangedToken>,
+    /// Categories, or scale token paths, whose entries were only
+    /// reordered. Informational; applying the diff doesn't need them.
+    pub reordered: Vec<String>,
+}
+
+/// Counts for a [`TokenDiff`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenDiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub reordered: usize,
+}
+
+/// Whether the keys both maps share appear in a different order.
+fn order_differs<'a, V, W>(
+    old: impl Iterator<Item = (&'a String, V)>,
+    new: &IndexMap<String, W>,
+) -> bool {
+    let mut last = None;
+    for (key, _) in old {
+        let Some(index) = new.get_index_of(key) else {
+            continue;
+        };
+        if last.is_some_and(|last| index < last) {
+            return true;
+        }
+        last = Some(index);
+    }
+    false
+}
+
+fn changed_half(before: &TokenValue, after: &TokenValue) -> Option<AdaptiveHalf> {
+    let (
+        TokenValue::Adaptive {
+            light: old_light,
+            dark: old_dark,
+        },
+        TokenValue::Adaptive { light, dark },
+    ) = (before, after)
+    else {
+        return None;
+    };
+    match (old_light != light, old_dark != dark) {
+        (true, false) => Some(AdaptiveHalf::Light),
+        (false, true) => Some(AdaptiveHalf::Dark),
+        _ => None,
+    }
+}
+
+impl DesignTokens {
+    /// What changes when `other` replaces this token set.
+    pub fn diff(&self, other: &DesignTokens) -> TokenDiff {
+        let mut diff = TokenDiff::default();
+        for ((prefix, old), (_, new)) in self.categories().into_iter().zip(other.categories()) {
+            for (name, value) in old {
+                let path = format!("{}.{}", prefix, name);
+                match new.get(name) {
+                    None => diff.removed.push(TokenEntry {
+                        path,
+                        value: value.clone(),
+                    }),
+                    // IndexMap equality ignores order, so moved scale
+                    // steps compare equal here.
+                    Some(after) if after == value => {
+                        if let (TokenValue::Scale(old_steps), TokenValue::Scale(new_steps)) =
+                            (value, after)
+                            && order_differs(old_steps.iter(), new_steps)
+                        {
+                            diff.reordered.push(path);
+                        }
+                    }
+                    Some(after) => diff.changed.push(ChangedToken {
+                        only: changed_half(value, after),
+                        path,
+                        before: value.clone(),
+                        after: after.clone(),
+                    }),
+                }
+            }
+            for (name, value) in new {
+                if !old.contains_key(name) {
+                    diff.added.push(TokenEntry {
+                        path: format!("{}.{}", prefix, name),
+                        value: value.clone(),
+                    });
+                }
+            }
+            if order_differs(old.iter(), new) {
+                diff.reordered.push(prefix.to_string());
+            }
+        }
+        diff
+    }
+}
+
+/// One-line rendering of a value for a Markdown table cell.
+fn cell(value: &TokenValue) -> String {
+    let text = match value {
+        TokenValue::Simple(v) => format!("`{}`", v),
+        TokenValue::Adaptive { light, dark } => format!("light `{}`, dark `{}`", light, dark),
+        TokenValue::Scale(steps) => steps
+            .iter()
+            .map(|(step, v)| format!("{} `{}`", step, v))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    text.replace('|', "\\|")
+}
+
+impl TokenDiff {
+    /// Whether applying the diff would change any value.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn summary(&self) -> TokenDiffSummary {
+        TokenDiffSummary {
+            added: self.added.len(),
+            removed: self.removed.len(),
+            changed: self.changed.len(),
+            reordered: self.reordered.len(),
+        }
+    }
+
+    /// Markdown for the import modal.
+    pub fn to_markdown(&self) -> String {
+        let summary = self.summary();
+        let mut md = String::from("## Token changes\n\n");
+        if self.is_empty() {
+            md.push_str("No token values change.\n");
+        } else {
+            md.push_str(&format!(
+                "{} added, {} removed, {} changed.\n",
+                summary.added, summary.removed, summary.changed
+            ));
+        }
+
+        for (title, entries) in [("Added", &self.added), ("Removed", &self.removed)] {
+            if entries.is_empty() {
+                continue;
+            }
+            md.push_str(&format!(
+                "\n### {}\n\n| Token | Value |\n|---|---|\n",
+                title
+            ));
+            for entry in entries {
+                md.push_str(&format!("| `{}` | {} |\n", entry.path, cell(&entry.value)));
+            }
+        }
+
+        if !self.changed.is_empty() {
+            md.push_str("\n### Changed\n\n| Token | Before | After |\n|---|---|---|\n");
+            for change in &self.changed {
+                let note = match change.only {
+                    Some(AdaptiveHalf::Light) => " (light only)",
+                    Some(AdaptiveHalf::Dark) => " (dark only)",
+                    None => "",
+                };
+                md.push_str(&format!(
+                    "| `{}`{} | {} | {} |\n",
+                    change.path,
+                    note,
+                    cell(&change.before),
+                    cell(&change.after)
+                ));
+            }
+        }
+
+        if !self.reordered.is_empty() {
+            md.push_str("\n### Order\n\n");
+            for path in &self.reordered {
+                md.push_str(&format!("- Entries of `{}` were reordered.\n", path));
+            }
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn adaptive(light: &str, dark: &str) -> TokenValue {
+        TokenValue::Adaptive {
+            light: light.to_string(),
+            dark: dark.to_string(),
+        }
+    }
+
+    fn scale(steps: &[(&str, &str)]) -> TokenValue {
+        TokenValue::Scale(
+            steps
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<IndexMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_diff_categories() {
+        let mut before = DesignTokens::default();
+        before
+            .colors
+            .insert("text".to_string(), adaptive("#111", "#eee"));
+        before
+            .colors
+            .insert("muted".to_string(), adaptive("#666", "#999"));
+        before.spacing.insert("sm".to_string(), simple("4px"));
+        before
+            .typography
+            .sizes
+            .insert("lg".to_string(), simple("18px"));
+        before.z_index.insert("modal".to_string(), simple("100"));
+
+        let mut after = DesignTokens::default();
+        after
+            .colors
+            .insert("text".to_string(), adaptive("#111", "#fff"));
+        after
+            .colors
+            .insert("muted".to_string(), adaptive("#555", "#888"));
+        after.spacing.insert("sm".to_string(), simple("4px"));
+        after
+            .typography
+            .sizes
+            .insert("lg".to_string(), simple("20px"));
+        after
+            .transitions
+            .insert("fast".to_string(), simple("100ms"));
+
+        let diff = before.diff(&after);
+        let changed: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|c| (c.path.as_str(), c.only))
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                ("colors.text", Some(AdaptiveHalf::Dark)),
+                ("colors.muted", None),
+                ("typography.sizes.lg", None),
+            ]
+        );
+        assert_eq!(diff.added[0].path, "transitions.fast");
+        assert_eq!(diff.removed[0].path, "z-index.modal");
+        assert_eq!(
+            diff.summary(),
+            TokenDiffSummary {
+                added: 1,
+                removed: 1,
+                changed: 3,
+                reordered: 0,
+            }
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn test_reordering_is_informational() {
+        let mut before = DesignTokens::default();
+        before.colors.insert("a".to_string(), simple("#000"));
+        before.colors.insert("b".to_string(), simple("#fff"));
+        before.colors.insert(
+            "blue".to_string(),
+            scale(&[("100", "#dbeafe"), ("500", "#3b82f6")]),
+        );
+
+        let mut after = DesignTokens::default();
+        after.colors.insert("b".to_string(), simple("#fff"));
+        after.colors.insert("c".to_string(), simple("#f00"));
+        after.colors.insert(
+            "blue".to_string(),
+            scale(&[("500", "#3b82f6"), ("100", "#dbeafe")]),
+        );
+        after.colors.insert("a".to_string(), simple("#000"));
+
+        let diff = before.diff(&after);
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.reordered, ["colors.blue", "colors"]);
+
+        // Only an added token in between is not a reorder.
+        let mut appended = before.clone();
+        appended
+            .colors
+            .shift_insert(1, "c".to_string(), simple("#f00"));
+        assert!(before.diff(&appended).reordered.is_empty());
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let mut before = DesignTokens::default();
+        before
+            .colors
+            .insert("text".to_string(), adaptive("#111", "#eee"));
+        before
+            .shadows
+            .insert("sm".to_string(), simple("0 1px 2px a|b"));
+        let mut after = DesignTokens::default();
+        after
+            .colors
+            .insert("text".to_string(), adaptive("#000", "#eee"));
+
+        let md = before.diff(&after).to_markdown();
+        assert!(md.starts_with("## Token changes\n\n0 added, 1 removed, 1 changed.\n"));
+        assert!(md.contains("| `shadows.sm` | `0 1px 2px a\\|b` |\n"));
+        assert!(md.contains(
+            "| `colors.text` (light only) | light `#111`, dark `#eee` | light `#000`, dark `#eee` |\n"
+        ));
+        assert!(!md.contains("### Added"));
+        assert_eq!(
+            after.diff(&after).to_markdown(),
+            "## Token changes\n\nNo token values change.\n"
+        );
+    }
+}
@@ -0,0 +1,674 @@
+//! Structured editing of dimension values.
+//!
+//! A [`DimensionField`] splits a value like `1.5rem` into a number and a
+//! unit so the editor can show a stepper and a unit dropdown instead of a
+//! bare text box. What units are offered, the step per unit and the
+//! allowed range come from a [`UnitPolicy`] picked by token category or
+//! CSS property. Values the field can't model — `calc()`, `var()`,
+//! keywords, shorthands with several values — stay editable as raw text.
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use super::{CssDesigner, StyleProperties, TokenValue};
+
+/// Pixels per `rem`/`em` when switching units.
+const ROOT_FONT_SIZE: f64 = 16.0;
+
+/// Keywords every property accepts.
+const GLOBAL_KEYWORDS: &[&str] = &["inherit", "initial", "unset", "revert", "revert-layer"];
+
+/// Step used for unitless numbers unless the policy says otherwise.
+const UNITLESS_STEP: f64 = 0.1;
+
+/// A CSS length unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    Px,
+    Rem,
+    Em,
+    Percent,
+    Vh,
+    Vw,
+    Vmin,
+    Vmax,
+    Ch,
+    Ex,
+}
+
+impl LengthUnit {
+    /// Every unit, in dropdown order.
+    pub const ALL: [LengthUnit; 10] = [
+        LengthUnit::Px,
+        LengthUnit::Rem,
+        LengthUnit::Em,
+        LengthUnit::Percent,
+        LengthUnit::Vh,
+        LengthUnit::Vw,
+        LengthUnit::Vmin,
+        LengthUnit::Vmax,
+        LengthUnit::Ch,
+        LengthUnit::Ex,
+    ];
+
+    /// CSS spelling of the unit.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LengthUnit::Px => "px",
+            LengthUnit::Rem => "rem",
+            LengthUnit::Em => "em",
+            LengthUnit::Percent => "%",
+            LengthUnit::Vh => "vh",
+            LengthUnit::Vw => "vw",
+            LengthUnit::Vmin => "vmin",
+            LengthUnit::Vmax => "vmax",
+            LengthUnit::Ch => "ch",
+            LengthUnit::Ex => "ex",
+        }
+    }
+
+    /// Unit from its CSS spelling, case-insensitively.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|u| u.as_str().eq_ignore_ascii_case(s))
+    }
+
+    /// Stepper increment when the policy doesn't override it.
+    pub fn default_step(self) -> f64 {
+        match self {
+            LengthUnit::Rem | LengthUnit::Em => 0.125,
+            LengthUnit::Ch | LengthUnit::Ex => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    /// Pixels per unit, for units with a fixed relation to `px`.
+    fn px_factor(self) -> Option<f64> {
+        match self {
+            LengthUnit::Px => Some(1.0),
+            LengthUnit::Rem | LengthUnit::Em => Some(ROOT_FONT_SIZE),
+            _ => None,
+        }
+    }
+}
+
+/// Dimension parsing and validation errors.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DimensionError {
+    /// No value entered.
+    #[error("Value is empty")]
+    Empty,
+
+    /// The text doesn't start with a number.
+    #[error("'{0}' is not a number")]
+    NotANumber(String),
+
+    /// The suffix isn't a known length unit.
+    #[error("Unknown unit '{0}'")]
+    UnknownUnit(String),
+
+    /// The unit is valid CSS but not offered for this value.
+    #[error("Unit '{0}' is not allowed here")]
+    UnitNotAllowed(String),
+
+    /// A non-zero number without a unit where one is required.
+    #[error("A unit is required")]
+    UnitRequired,
+
+    /// The number is below the policy's minimum.
+    #[error("Value must be at least {0}")]
+    BelowMin(f64),
+
+    /// The number is above the policy's maximum.
+    #[error("Value must be at most {0}")]
+    AboveMax(f64),
+}
+
+/// A number with an optional length unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DimensionValue {
+    pub value: f64,
+    /// `None` for unitless numbers such as `0` or a `line-height` of `1.5`.
+    pub unit: Option<LengthUnit>,
+}
+
+impl DimensionValue {
+    pub fn new(value: f64, unit: Option<LengthUnit>) -> Self {
+        Self {
+            value: round(value),
+            unit,
+        }
+    }
+
+    /// Parse text like `16px`, `-0.5rem` or `1.5`.
+    pub fn parse(text: &str) -> Result<Self, DimensionError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(DimensionError::Empty);
+        }
+        let split = text
+            .char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && matches!(c, '-' | '+'))))
+            .map_or(text.len(), |(i, _)| i);
+        let (number, unit) = text.split_at(split);
+        let value: f64 = number
+            .parse()
+            .map_err(|_| DimensionError::NotANumber(text.to_string()))?;
+        let unit = match unit {
+            "" => None,
+            unit => Some(
+                LengthUnit::parse(unit)
+                    .ok_or_else(|| DimensionError::UnknownUnit(unit.to_string()))?,
+            ),
+        };
+        Ok(Self::new(value, unit))
+    }
+
+    /// CSS text, without trailing zeros.
+    pub fn format(&self) -> String {
+        let mut number = format!("{:.4}", self.value);
+        while number.ends_with('0') {
+            number.pop();
+        }
+        if number.ends_with('.') {
+            number.pop();
+        }
+        if number == "-0" {
+            number = "0".to_string();
+        }
+        match self.unit {
+            Some(unit) => format!("{}{}", number, unit.as_str()),
+            None => number,
+        }
+    }
+}
+
+/// Round to the precision [`DimensionValue::format`] keeps, so repeated
+/// stepping doesn't accumulate float noise.
+fn round(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// Which units, steps and range a dimension accepts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitPolicy {
+    /// Units offered in the dropdown; the first is used when stepping from
+    /// a bare `0`.
+    pub units: Vec<LengthUnit>,
+    /// Accept non-zero numbers without a unit.
+    pub unitless: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Keywords kept as raw text, besides the global ones.
+    pub keywords: Vec<&'static str>,
+    /// Per-unit step overrides.
+    pub steps: IndexMap<LengthUnit, f64>,
+}
+
+impl UnitPolicy {
+    pub fn new(units: &[LengthUnit]) -> Self {
+        Self {
+            units: units.to_vec(),
+            unitless: false,
+            min: None,
+            max: None,
+            keywords: Vec::new(),
+            steps: IndexMap::new(),
+        }
+    }
+
+    /// Disallow values below zero.
+    pub fn non_negative(mut self) -> Self {
+        self.min = Some(0.0);
+        self
+    }
+
+    pub fn with_range(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn with_unitless(mut self, unitless: bool) -> Self {
+        self.unitless = unitless;
+        self
+    }
+
+    pub fn with_keywords(mut self, keywords: &[&'static str]) -> Self {
+        self.keywords = keywords.to_vec();
+        self
+    }
+
+    pub fn with_step(mut self, unit: LengthUnit, step: f64) -> Self {
+        self.steps.insert(unit, step);
+        self
+    }
+
+    /// Policy for tokens under a category prefix such as `spacing`, or
+    /// `None` if the category doesn't hold dimensions.
+    pub fn for_token_category(prefix: &str) -> Option<Self> {
+        use LengthUnit::*;
+        match prefix {
+            "spacing" => Some(Self::new(&[Rem, Px, Em])),
+            "radius" => Some(Self::new(&[Px, Rem, Em, Percent]).non_negative()),
+            "typography.sizes" => Some(Self::new(&[Rem, Px, Em, Percent]).non_negative()),
+            _ => None,
+        }
+    }
+
+    /// Policy for a [`StyleProperties`] property, or `None` if it isn't
+    /// dimension-like.
+    pub fn for_property(name: &str) -> Option<Self> {
+        use LengthUnit::*;
+        const SIZING: &[&str] = &["auto", "fit-content", "min-content", "max-content"];
+        let box_units = [Px, Rem, Em, Percent];
+        let policy = match name {
+            "width" | "height" | "min-width" | "min-height" => {
+                Self::new(&[Px, Rem, Em, Percent, Vh, Vw, Vmin, Vmax, Ch])
+                    .non_negative()
+                    .with_keywords(SIZING)
+            }
+            "max-width" | "max-height" => {
+                Self::new(&[Px, Rem, Em, Percent, Vh, Vw, Vmin, Vmax, Ch])
+                    .non_negative()
+                    .with_keywords(&["none", "fit-content", "min-content", "max-content"])
+            }
+            "gap" | "border-radius" => Self::new(&box_units).non_negative(),
+            "padding" | "padding-top" | "padding-right" | "padding-bottom" | "padding-left" => {
+                Self::new(&box_units).non_negative()
+            }
+            "margin" | "margin-top" | "margin-right" | "margin-bottom" | "margin-left" => {
+                Self::new(&box_units).with_keywords(&["auto"])
+            }
+            "top" | "right" | "bottom" | "left" => {
+                Self::new(&[Px, Rem, Em, Percent, Vh, Vw]).with_keywords(&["auto"])
+            }
+            "border-width" => Self::new(&[Px, Rem, Em])
+                .non_negative()
+                .with_keywords(&["thin", "medium", "thick"]),
+            "font-size" => Self::new(&[Rem, Px, Em, Percent])
+                .non_negative()
+                .with_keywords(&["smaller", "larger"]),
+            "line-height" => Self::new(&[Rem, Px, Em, Percent])
+                .non_negative()
+                .with_unitless(true)
+                .with_keywords(&["normal"]),
+            _ => return None,
+        };
+        Some(policy)
+    }
+
+    /// Stepper increment for `unit`.
+    pub fn step_for(&self, unit: Option<LengthUnit>) -> f64 {
+        match unit {
+            Some(unit) => self
+                .steps
+                .get(&unit)
+                .copied()
+                .unwrap_or_else(|| unit.default_step()),
+            None => UNITLESS_STEP,
+        }
+    }
+
+    fn is_keyword(&self, text: &str) -> bool {
+        let text = text.to_ascii_lowercase();
+        GLOBAL_KEYWORDS.contains(&text.as_str()) || self.keywords.contains(&text.as_str())
+    }
+
+    /// Check a parsed value against the units and range.
+    pub fn validate(&self, value: &DimensionValue) -> Result<(), DimensionError> {
+        match value.unit {
+            Some(unit) if !self.units.contains(&unit) => {
+                return Err(DimensionError::UnitNotAllowed(unit.as_str().to_string()));
+            }
+            None if value.value != 0.0 && !self.unitless => {
+                return Err(DimensionError::UnitRequired);
+            }
+            _ => {}
+        }
+        if let Some(min) = self.min
+            && value.value < min
+        {
+            return Err(DimensionError::BelowMin(min));
+        }
+        if let Some(max) = self.max
+            && value.value > max
+        {
+            return Err(DimensionError::AboveMax(max));
+        }
+        Ok(())
+    }
+
+    fn clamp(&self, value: f64) -> f64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+}
+
+/// Why a field is in raw text mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawReason {
+    /// `calc()`, `var()` or another CSS function.
+    Function,
+    /// A keyword such as `auto`.
+    Keyword,
+    /// Several space-separated values, as in a `padding` shorthand.
+    Compound,
+}
+
+/// How the field is currently edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldState {
+    /// Number and unit, edited with the stepper and unit dropdown.
+    Structured(DimensionValue),
+    /// Edited as plain text; the editor shows a raw-mode indicator.
+    Raw(RawReason),
+    /// The text doesn't parse or breaks the policy; shown inline.
+    Invalid(DimensionError),
+}
+
+/// Editor state for one dimension value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionField {
+    /// Text as shown in the input.
+    pub text: String,
+    pub state: FieldState,
+    pub policy: UnitPolicy,
+}
+
+impl DimensionField {
+    pub fn new(text: impl Into<String>, policy: UnitPolicy) -> Self {
+        let mut field = Self {
+            text: String::new(),
+            state: FieldState::Raw(RawReason::Keyword),
+            policy,
+        };
+        field.set_text(text);
+        field
+    }
+
+    /// Replace the text, as when the user types, and re-validate.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        let trimmed = self.text.trim();
+        self.state = if trimmed.contains('(') {
+            FieldState::Raw(RawReason::Function)
+        } else if trimmed.contains(char::is_whitespace) {
+            FieldState::Raw(RawReason::Compound)
+        } else if self.policy.is_keyword(trimmed) {
+            FieldState::Raw(RawReason::Keyword)
+        } else {
+            match DimensionValue::parse(trimmed).and_then(|v| self.policy.validate(&v).map(|_| v)) {
+                Ok(value) => FieldState::Structured(value),
+                Err(error) => FieldState::Invalid(error),
+            }
+        };
+    }
+
+    /// Parsed value, when the field is structured.
+    pub fn dimension(&self) -> Option<DimensionValue> {
+        match self.state {
+            FieldState::Structured(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Whether the field is in raw text mode.
+    pub fn is_raw(&self) -> bool {
+        matches!(self.state, FieldState::Raw(_))
+    }
+
+    /// Inline error to show, if any.
+    pub fn error(&self) -> Option<&DimensionError> {
+        match &self.state {
+            FieldState::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Units for the dropdown.
+    pub fn units(&self) -> &[LengthUnit] {
+        &self.policy.units
+    }
+
+    /// Move the value by `steps` increments of the current unit's step,
+    /// clamped to the policy's range. A bare `0` steps in the first
+    /// allowed unit. Does nothing outside structured mode.
+    pub fn step(&mut self, steps: f64) -> bool {
+        let Some(current) = self.dimension() else {
+            return false;
+        };
+        let unit = match current.unit {
+            None if !self.policy.unitless => self.policy.units.first().copied(),
+            unit => unit,
+        };
+        let value = self
+            .policy
+            .clamp(current.value + steps * self.policy.step_for(unit));
+        let next = DimensionValue::new(value, unit);
+        self.text = next.format();
+        self.state = FieldState::Structured(next);
+        true
+    }
+
+    /// Arrow up; Shift steps ten times as far.
+    pub fn increment(&mut self, shift: bool) -> bool {
+        self.step(if shift { 10.0 } else { 1.0 })
+    }
+
+    /// Arrow down; Shift steps ten times as far.
+    pub fn decrement(&mut self, shift: bool) -> bool {
+        self.step(if shift { -10.0 } else { -1.0 })
+    }
+
+    /// Switch unit from the dropdown. Between `px`, `rem` and `em` the
+    /// number is converted so the length stays the same; other switches
+    /// keep the number.
+    pub fn set_unit(&mut self, unit: LengthUnit) -> Result<(), DimensionError> {
+        if !self.policy.units.contains(&unit) {
+            return Err(DimensionError::UnitNotAllowed(unit.as_str().to_string()));
+        }
+        let Some(current) = self.dimension() else {
+            return Ok(());
+        };
+        let value = match (
+            current.unit.and_then(LengthUnit::px_factor),
+            unit.px_factor(),
+        ) {
+            (Some(from), Some(to)) => current.value * from / to,
+            _ => current.value,
+        };
+        let next = DimensionValue::new(self.policy.clamp(value), Some(unit));
+        self.text = next.format();
+        self.state = FieldState::Structured(next);
+        Ok(())
+    }
+
+    /// Text to store, or the error that blocks committing it.
+    pub fn committed(&self) -> Result<String, DimensionError> {
+        match &self.state {
+            FieldState::Invalid(error) => Err(error.clone()),
+            _ => Ok(self.text.trim().to_string()),
+        }
+    }
+}
+
+impl CssDesigner {
+    /// Field for editing a simple token in a dimension category.
+    pub fn dimension_field(&self, path: &str) -> Option<DimensionField> {
+        let (prefix, _) = path.rsplit_once('.')?;
+        let policy = UnitPolicy::for_token_category(prefix)?;
+        match self.get_token(path)? {
+            TokenValue::Simple(value) => Some(DimensionField::new(value.clone(), policy)),
+            _ => None,
+        }
+    }
+
+    /// Store a field's value as a simple token.
+    pub fn set_dimension(
+        &mut self,
+        path: &str,
+        field: &DimensionField,
+    ) -> Result<(), DimensionError> {
+        let value = field.committed()?;
+        self.set_token(path, TokenValue::Simple(value));
+        Ok(())
+    }
+}
+
+impl StyleProperties {
+    /// Field for editing a dimension-like property, empty when unset.
+    pub fn dimension_field(&self, name: &str) -> Option<DimensionField> {
+        let policy = UnitPolicy::for_property(name)?;
+        let value = self.get(name).cloned().unwrap_or_default();
+        Some(DimensionField::new(value, policy))
+    }
+
+    /// Store a field's value; empty text clears the property.
+    pub fn set_dimension(
+        &mut self,
+        name: &str,
+        field: &DimensionField,
+    ) -> Result<(), DimensionError> {
+        if field.text.trim().is_empty() {
+            self.set(name, None);
+            return Ok(());
+        }
+        let value = field.committed()?;
+        self.set(name, Some(value));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_format_round_trip() {
+        for text in [
+            "16px", "1.5rem", "-0.25em", "50%", "100vh", "0", "1.5", "0.125rem",
+        ] {
+            assert_eq!(DimensionValue::parse(text).unwrap().format(), text);
+        }
+        assert_eq!(
+            DimensionValue::parse(" 2.50REM ").unwrap().format(),
+            "2.5rem"
+        );
+        assert_eq!(
+            DimensionValue::parse("").unwrap_err(),
+            DimensionError::Empty
+        );
+        assert_eq!(
+            DimensionValue::parse("1remm").unwrap_err(),
+            DimensionError::UnknownUnit("remm".into())
+        );
+        assert!(matches!(
+            DimensionValue::parse("px"),
+            Err(DimensionError::NotANumber(_))
+        ));
+    }
+
+    #[test]
+    fn test_stepper_and_clamping() {
+        let mut field =
+            DimensionField::new("1rem", UnitPolicy::for_token_category("radius").unwrap());
+        assert!(field.increment(false));
+        assert_eq!(field.text, "1.125rem");
+        assert!(field.decrement(true));
+        assert_eq!(field.text, "0rem");
+        // Non-negative policy clamps instead of going below zero.
+        field.decrement(false);
+        assert_eq!(field.text, "0rem");
+
+        let policy = UnitPolicy::new(&[LengthUnit::Px]).with_step(LengthUnit::Px, 4.0);
+        let mut field = DimensionField::new("0", policy);
+        field.increment(true);
+        assert_eq!(field.text, "40px");
+
+        field.set_text("-3px");
+        assert!(field.error().is_none());
+        let mut field = DimensionField::new("-3px", UnitPolicy::for_property("gap").unwrap());
+        assert_eq!(field.error(), Some(&DimensionError::BelowMin(0.0)));
+        assert!(!field.increment(false));
+        field.set_text("3vh");
+        assert_eq!(
+            field.error(),
+            Some(&DimensionError::UnitNotAllowed("vh".into()))
+        );
+        field.set_text("3");
+        assert_eq!(field.error(), Some(&DimensionError::UnitRequired));
+    }
+
+    #[test]
+    fn test_unit_switch() {
+        let mut field =
+            DimensionField::new("24px", UnitPolicy::for_property("padding-top").unwrap());
+        field.set_unit(LengthUnit::Rem).unwrap();
+        assert_eq!(field.text, "1.5rem");
+        field.increment(false);
+        assert_eq!(field.text, "1.625rem");
+        field.set_unit(LengthUnit::Px).unwrap();
+        assert_eq!(field.text, "26px");
+        field.increment(true);
+        assert_eq!(field.text, "36px");
+        // No fixed relation to px: the number is kept.
+        field.set_unit(LengthUnit::Percent).unwrap();
+        assert_eq!(field.text, "36%");
+        assert_eq!(
+            field.set_unit(LengthUnit::Vw),
+            Err(DimensionError::UnitNotAllowed("vw".into()))
+        );
+    }
+
+    #[test]
+    fn test_raw_mode_and_style_properties() {
+        let mut style = StyleProperties::default();
+        style.set("margin", Some("calc(1rem + 2px)".into()));
+        let mut field = style.dimension_field("margin").unwrap();
+        assert_eq!(field.state, FieldState::Raw(RawReason::Function));
+        assert!(!field.increment(false));
+
+        field.set_text("var(--spacing-md)");
+        assert!(field.is_raw());
+        field.set_text("1rem 2rem");
+        assert_eq!(field.state, FieldState::Raw(RawReason::Compound));
+        field.set_text("auto");
+        assert_eq!(field.state, FieldState::Raw(RawReason::Keyword));
+        field.set_text("-8px");
+        assert_eq!(
+            field.dimension(),
+            Some(DimensionValue::new(-8.0, Some(LengthUnit::Px)))
+        );
+        style.set_dimension("margin", &field).unwrap();
+        assert_eq!(style.get("margin").map(String::as_str), Some("-8px"));
+
+        let field = style.dimension_field("line-height").unwrap();
+        assert_eq!(field.text, "");
+        let mut field = DimensionField::new("1.5", field.policy);
+        field.increment(false);
+        assert_eq!(field.text, "1.6");
+
+        assert!(style.dimension_field("color").is_none());
+        let mut style = StyleProperties::default();
+        let field = DimensionField::new("wide", UnitPolicy::for_property("width").unwrap());
+        assert!(style.set_dimension("width", &field).is_err());
+        assert!(style.get("width").is_none());
+    }
+
+    #[test]
+    fn test_token_dimension_field() {
+        let mut designer = CssDesigner::new();
+        designer.set_token("spacing.gutter", TokenValue::Simple("16px".into()));
+        let mut field = designer.dimension_field("spacing.gutter").unwrap();
+        field.set_unit(LengthUnit::Rem).unwrap();
+        designer.set_dimension("spacing.gutter", &field).unwrap();
+        assert_eq!(
+            designer.get_token("spacing.gutter"),
+            Some(&TokenValue::Simple("1rem".into()))
+        );
+        designer.set_token("colors.ink", TokenValue::Simple("#000".into()));
+        assert!(designer.dimension_field("colors.ink").is_none());
+    }
+}
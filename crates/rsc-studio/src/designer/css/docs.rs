@@ -0,0 +1,426 @@
+//! Per-token documentation: descriptions, deprecation and tags.
+//!
+//! Docs live beside the values in [`DesignTokens::docs`], keyed by token
+//! path. In files a documented token is written as an object,
+//! `{ "value": ..., "description": ..., "deprecated": ..., "tags": [...] }`,
+//! and every other token keeps its bare value form. Both forms are read.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::contrast::ContrastPair;
+use super::usage::TokenReferences;
+use super::{
+    ComponentStyles, DesignTokens, TokenUsageTracker, TokenValidationError, TokenValue,
+    ValidationSeverity,
+};
+use crate::timestamp::Timestamps;
+
+/// Documentation of one token. Serialized on its own only for merge
+/// conflicts; token files inline it with the value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TokenDocs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Why the token is deprecated, usually naming the replacement path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    /// Free-form groups, e.g. `brand` or `legacy`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl TokenDocs {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.deprecated.is_none() && self.tags.is_empty()
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_deprecated(mut self, message: impl Into<String>) -> Self {
+        self.deprecated = Some(message.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+}
+
+/// A token uses another token that is deprecated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedUse {
+    /// The deprecated token.
+    pub path: String,
+    /// Its deprecation message.
+    pub message: String,
+}
+
+impl DeprecatedUse {
+    /// Description for the user, e.g. "Uses deprecated token
+    /// 'colors.brand-old': use colors.brand".
+    pub fn describe(&self) -> String {
+        if self.message.is_empty() {
+            format!("Uses deprecated token '{}'", self.path)
+        } else {
+            format!("Uses deprecated token '{}': {}", self.path, self.message)
+        }
+    }
+}
+
+impl DesignTokens {
+    /// Docs of the token at `path`, if it has any.
+    pub fn token_docs(&self, path: &str) -> Option<&TokenDocs> {
+        self.docs.get(path).filter(|docs| !docs.is_empty())
+    }
+
+    /// Paths of the tokens tagged `tag`, in category order.
+    pub fn paths_with_tag(&self, tag: &str) -> Vec<String> {
+        self.all_paths()
+            .into_iter()
+            .filter(|path| {
+                self.docs
+                    .get(path)
+                    .is_some_and(|docs| docs.tags.iter().any(|t| t == tag))
+            })
+            .collect()
+    }
+
+    /// Deprecated tokens that `paths` name, resolving scale steps to their
+    /// scale.
+    fn deprecated_uses<'a>(
+        &self,
+        references: &TokenReferences,
+        paths: impl IntoIterator<Item = &'a String>,
+    ) -> Vec<DeprecatedUse> {
+        let mut uses: Vec<DeprecatedUse> = Vec::new();
+        for path in paths {
+            let Some(path) = references.resolve(path) else {
+                continue;
+            };
+            let Some(message) = self.docs.get(&path).and_then(|d| d.deprecated.as_ref()) else {
+                continue;
+            };
+            if !uses.iter().any(|u| u.path == path) {
+                uses.push(DeprecatedUse {
+                    path,
+                    message: message.clone(),
+                });
+            }
+        }
+        uses
+    }
+
+    /// One warning per token that references a deprecated token.
+    pub(super) fn deprecation_warnings(&self) -> Vec<TokenValidationError> {
+        if self.docs.values().all(|docs| docs.deprecated.is_none()) {
+            return Vec::new();
+        }
+        let references = TokenReferences::new(self);
+        let mut warnings = Vec::new();
+        for (path, targets) in &references.refs {
+            for deprecated in self.deprecated_uses(&references, targets) {
+                warnings.push(TokenValidationError {
+                    path: path.clone(),
+                    message: deprecated.describe(),
+                    severity: ValidationSeverity::Warning,
+                });
+            }
+        }
+        warnings
+    }
+}
+
+impl ComponentStyles {
+    /// Deprecated tokens each style references, by style name. Styles that
+    /// use none are left out.
+    pub fn deprecated_token_uses(
+        &self,
+        tokens: &DesignTokens,
+    ) -> IndexMap<String, Vec<DeprecatedUse>> {
+        if tokens.docs.values().all(|docs| docs.deprecated.is_none()) {
+            return IndexMap::new();
+        }
+        let references = TokenReferences::new(tokens);
+        self.styles
+            .iter()
+            .filter_map(|(name, style)| {
+                let mut tracker = TokenUsageTracker::new();
+                tracker.scan_component_style(style);
+                let mut used: Vec<&String> = tracker.used.iter().collect();
+                used.sort();
+                let uses = tokens.deprecated_uses(&references, used);
+                (!uses.is_empty()).then(|| (name.clone(), uses))
+            })
+            .collect()
+    }
+}
+
+/// A documented token as written in files.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DocumentedToken {
+    value: TokenValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprecated: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+/// A token entry in files: documented, or the bare value.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum FileToken {
+    Documented(DocumentedToken),
+    Plain(TokenValue),
+}
+
+type FileTokenMap = IndexMap<String, FileToken>;
+
+#[derive(Default, Serialize, Deserialize)]
+struct FileTypography {
+    #[serde(default)]
+    fonts: FileTokenMap,
+    #[serde(default)]
+    sizes: FileTokenMap,
+    #[serde(default)]
+    weights: FileTokenMap,
+}
+
+/// [`DesignTokens`] as written in files, with docs inline.
+#[derive(Serialize, Deserialize)]
+pub(super) struct TokenFile {
+    #[serde(default)]
+    colors: FileTokenMap,
+    #[serde(default)]
+    spacing: FileTokenMap,
+    #[serde(default)]
+    radius: FileTokenMap,
+    #[serde(default)]
+    shadows: FileTokenMap,
+    #[serde(default)]
+    typography: FileTypography,
+    #[serde(default)]
+    transitions: FileTokenMap,
+    #[serde(default)]
+    z_index: FileTokenMap,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    metadata: IndexMap<String, Timestamps>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    contrast_pairs: Vec<ContrastPair>,
+}
+
+impl From<TokenFile> for DesignTokens {
+    fn from(file: TokenFile) -> Self {
+        let mut tokens = DesignTokens {
+            metadata: file.metadata,
+            contrast_pairs: file.contrast_pairs,
+            ..Default::default()
+        };
+        let maps = [
+            file.colors,
+            file.spacing,
+            file.radius,
+            file.shadows,
+            file.typography.fonts,
+            file.typography.sizes,
+            file.typography.weights,
+            file.transitions,
+            file.z_index,
+        ];
+        let mut docs = IndexMap::new();
+        for ((prefix, target), source) in tokens.categories_mut().into_iter().zip(maps) {
+            for (name, token) in source {
+                let value = match token {
+                    FileToken::Plain(value) => value,
+                    FileToken::Documented(token) => {
+                        let token_docs = TokenDocs {
+                            description: token.description,
+                            deprecated: token.deprecated,
+                            tags: token.tags,
+                        };
+                        if !token_docs.is_empty() {
+                            docs.insert(format!("{}.{}", prefix, name), token_docs);
+                        }
+                        token.value
+                    }
+                };
+                target.insert(name, value);
+            }
+        }
+        tokens.docs = docs;
+        tokens
+    }
+}
+
+impl From<DesignTokens> for TokenFile {
+    fn from(mut tokens: DesignTokens) -> Self {
+        let mut docs = std::mem::take(&mut tokens.docs);
+        let [
+            colors,
+            spacing,
+            radius,
+            shadows,
+            fonts,
+            sizes,
+            weights,
+            transitions,
+            z_index,
+        ] = tokens.categories_mut().map(|(prefix, map)| {
+            std::mem::take(map)
+                .into_iter()
+                .map(|(name, value)| {
+                    let token = match docs.shift_remove(&format!("{}.{}", prefix, name)) {
+                        Some(docs) if !docs.is_empty() => FileToken::Documented(DocumentedToken {
+                            value,
+                            description: docs.description,
+                            deprecated: docs.deprecated,
+                            tags: docs.tags,
+                        }),
+                        _ => FileToken::Plain(value),
+                    };
+                    (name, token)
+                })
+                .collect()
+        });
+        TokenFile {
+            colors,
+            spacing,
+            radius,
+            shadows,
+            typography: FileTypography {
+                fonts,
+                sizes,
+                weights,
+            },
+            transitions,
+            z_index,
+            metadata: tokens.metadata,
+            contrast_pairs: tokens.contrast_pairs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::ComponentStyle;
+
+    const MIXED: &str = r##"{
+  "colors": {
+    "brand": "#2563eb",
+    "brand-old": {
+      "value": "#1d4ed8",
+      "deprecated": "use colors.brand"
+    },
+    "surface": {
+      "value": {
+        "light": "#ffffff",
+        "dark": "#0f172a"
+      },
+      "description": "Page background",
+      "tags": [
+        "layout"
+      ]
+    },
+    "blue": {
+      "100": "#dbeafe",
+      "500": "var(--color-brand-old)"
+    }
+  },
+  "spacing": {
+    "md": {
+      "value": "16px",
+      "tags": [
+        "layout",
+        "rhythm"
+      ]
+    }
+  },
+  "radius": {},
+  "shadows": {},
+  "typography": {
+    "fonts": {},
+    "sizes": {},
+    "weights": {}
+  },
+  "transitions": {},
+  "z_index": {}
+}"##;
+
+    #[test]
+    fn test_round_trip_mixed_forms() {
+        let tokens = DesignTokens::from_json(MIXED).unwrap();
+        assert_eq!(
+            tokens.colors["surface"],
+            TokenValue::Adaptive {
+                light: "#ffffff".to_string(),
+                dark: "#0f172a".to_string(),
+            }
+        );
+        assert!(matches!(tokens.colors["blue"], TokenValue::Scale(_)));
+        assert_eq!(tokens.token_docs("colors.brand"), None);
+        assert_eq!(
+            tokens.token_docs("colors.surface"),
+            Some(
+                &TokenDocs::default()
+                    .with_description("Page background")
+                    .with_tag("layout")
+            )
+        );
+        assert_eq!(
+            tokens.paths_with_tag("layout"),
+            ["colors.surface", "spacing.md"]
+        );
+        assert_eq!(tokens.to_json().unwrap(), MIXED);
+
+        let yaml = tokens.to_yaml().unwrap();
+        assert!(yaml.contains("deprecated: use colors.brand"));
+        let back = DesignTokens::from_yaml(&yaml).unwrap();
+        assert_eq!(back.colors, tokens.colors);
+        assert_eq!(back.spacing, tokens.spacing);
+        assert_eq!(back.docs, tokens.docs);
+    }
+
+    #[test]
+    fn test_deprecated_references_warn() {
+        let tokens = DesignTokens::from_json(MIXED).unwrap();
+        let warnings: Vec<(String, String)> = tokens
+            .validate()
+            .into_iter()
+            .filter(|e| e.message.contains("deprecated"))
+            .map(|e| (e.path, e.message))
+            .collect();
+        assert_eq!(
+            warnings,
+            [(
+                "colors.blue".to_string(),
+                "Uses deprecated token 'colors.brand-old': use colors.brand".to_string()
+            )]
+        );
+
+        let mut styles = ComponentStyles::default();
+        let mut button = ComponentStyle::default();
+        button
+            .base
+            .set("color", Some("var(--color-brand-old)".to_string()));
+        button
+            .base
+            .set("background", Some("var(--color-brand)".to_string()));
+        styles.set("button".to_string(), button);
+        let uses = styles.deprecated_token_uses(&tokens);
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses["button"][0].path, "colors.brand-old");
+
+        let mut tokens = tokens;
+        tokens.docs.shift_remove("colors.brand-old");
+        assert!(tokens.validate().is_empty());
+        assert!(styles.deprecated_token_uses(&tokens).is_empty());
+    }
+}
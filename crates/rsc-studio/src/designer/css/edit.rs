@@ -0,0 +1,272 @@
+//! Undo/redo for CSS Designer token edits.
+//!
+//! Every designer action records the tokens it changed as one group of
+//! [`TokenEdit`]s, so an import or a rename undoes in a single step.
+
+use super::{CssDesigner, DesignTokens, TokenValue};
+
+/// One token change. `before` is `None` for an insertion and `after` is
+/// `None` for a deletion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenEdit {
+    pub path: String,
+    pub before: Option<TokenValue>,
+    pub after: Option<TokenValue>,
+    /// Position of the token in its category, so undoing a deletion puts
+    /// it back where it was.
+    pub index: usize,
+}
+
+/// The edits needed to turn `before` into `after`, deletions first.
+pub fn diff_tokens(before: &DesignTokens, after: &DesignTokens) -> Vec<TokenEdit> {
+    let mut deletions = Vec::new();
+    let mut changes = Vec::new();
+    for ((prefix, old), (_, new)) in before.categories().into_iter().zip(after.categories()) {
+        // Latest first, so undoing (in reverse) restores them front to back.
+        for (index, (name, value)) in old.iter().enumerate().rev() {
+            if !new.contains_key(name) {
+                deletions.push(TokenEdit {
+                    path: format!("{}.{}", prefix, name),
+                    before: Some(value.clone()),
+                    after: None,
+                    index,
+                });
+            }
+        }
+        for (index, (name, value)) in new.iter().enumerate() {
+            let previous = old.get(name);
+            if previous != Some(value) {
+                changes.push(TokenEdit {
+                    path: format!("{}.{}", prefix, name),
+                    before: previous.cloned(),
+                    after: Some(value.clone()),
+                    index,
+                });
+            }
+        }
+    }
+    // Deletions are undone last, after the insertions that followed them
+    // have been removed again.
+    deletions.extend(changes);
+    deletions
+}
+
+/// Write `value` at `path`, removing the token for `None`. New tokens are
+/// inserted at `index`.
+pub(super) fn write(
+    tokens: &mut DesignTokens,
+    path: &str,
+    value: Option<TokenValue>,
+    index: usize,
+) {
+    let Some((prefix, name)) = path.rsplit_once('.') else {
+        return;
+    };
+    let Some((_, map)) = tokens
+        .categories_mut()
+        .into_iter()
+        .find(|(p, _)| *p == prefix)
+    else {
+        return;
+    };
+    match value {
+        Some(value) => match map.get_mut(name) {
+            Some(existing) => *existing = value,
+            None => {
+                map.shift_insert(index.min(map.len()), name.to_string(), value);
+            }
+        },
+        None => {
+            map.shift_remove(name);
+        }
+    }
+}
+
+impl CssDesigner {
+    /// Set the undo depth.
+    pub fn with_history_depth(mut self, depth: usize) -> Self {
+        self.history.max_depth = depth;
+        self
+    }
+
+    /// Set a token value, recording the edit.
+    pub fn set_token(&mut self, path: &str, value: TokenValue) {
+        self.edit_token(path, Some(value));
+    }
+
+    /// Delete a token, recording the edit.
+    pub fn remove_token(&mut self, path: &str) -> Option<TokenValue> {
+        let removed = self.get_token(path).cloned();
+        self.edit_token(path, None);
+        removed
+    }
+
+    fn edit_token(&mut self, path: &str, value: Option<TokenValue>) {
+        let before = self.tokens.clone();
+        let index = path
+            .rsplit_once('.')
+            .and_then(|(prefix, _)| {
+                self.tokens
+                    .categories()
+                    .into_iter()
+                    .find(|(p, _)| *p == prefix)
+            })
+            .map_or(0, |(_, map)| map.len());
+        let label = match &value {
+            Some(_) => format!("Set {}", path),
+            None => format!("Delete {}", path),
+        };
+        write(&mut self.tokens, path, value, index);
+        self.record(label, &before);
+    }
+
+    /// Record the difference from `before` as one history entry.
+    pub(super) fn record(&mut self, label: impl Into<String>, before: &DesignTokens) {
+        let edits = diff_tokens(before, &self.tokens);
+        if !edits.is_empty() {
+            self.history.push(label, edits);
+        }
+    }
+
+    /// Revert the last edit group. Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.history.undo() else {
+            return false;
+        };
+        let edits = entry.change.clone();
+        for edit in edits.into_iter().rev() {
+            write(&mut self.tokens, &edit.path, edit.before, edit.index);
+        }
+        self.deselect_missing();
+        true
+    }
+
+    /// Re-apply the last undone edit group. Returns false if there was
+    /// nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(entry) = self.history.redo() else {
+            return false;
+        };
+        let edits = entry.change.clone();
+        for edit in edits {
+            write(&mut self.tokens, &edit.path, edit.after, edit.index);
+        }
+        self.deselect_missing();
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    fn deselect_missing(&mut self) {
+        if let Some(selected) = &self.selected_token
+            && self.get_token(selected).is_none()
+        {
+            self.selected_token = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::MergeStrategy;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn color_names(designer: &CssDesigner) -> Vec<&str> {
+        designer.tokens.colors.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_undo_redo_set_and_delete() {
+        let mut designer = CssDesigner::new();
+        assert!(!designer.can_undo());
+
+        designer.set_token("colors.primary", simple("#3b82f6"));
+        designer.set_token("colors.accent", simple("#f00"));
+        designer.set_token("colors.muted", simple("#888"));
+        designer.set_token("colors.border", simple("#ddd"));
+        designer.set_token("colors.primary", simple("#3b82f7"));
+        designer.remove_token("colors.primary");
+        assert_eq!(color_names(&designer), ["accent", "muted", "border"]);
+
+        // The deleted token comes back in its old position.
+        assert!(designer.undo());
+        assert_eq!(
+            color_names(&designer),
+            ["primary", "accent", "muted", "border"]
+        );
+        assert_eq!(
+            designer.get_token("colors.primary"),
+            Some(&simple("#3b82f7"))
+        );
+        assert!(designer.undo());
+        assert_eq!(
+            designer.get_token("colors.primary"),
+            Some(&simple("#3b82f6"))
+        );
+        assert!(designer.can_redo());
+
+        assert!(designer.redo());
+        assert_eq!(
+            designer.get_token("colors.primary"),
+            Some(&simple("#3b82f7"))
+        );
+
+        // A new edit clears the redo stack.
+        designer.set_token("spacing.md", simple("16px"));
+        assert!(!designer.can_redo());
+        assert!(!designer.redo());
+    }
+
+    #[test]
+    fn test_import_undoes_as_one_group() {
+        let mut designer = CssDesigner::new();
+        designer.set_token("colors.primary", simple("#000"));
+        designer.set_token("colors.old", simple("#111"));
+        designer.set_token("colors.older", simple("#333"));
+        designer.selected_token = Some("colors.new".to_string());
+
+        let mut incoming = DesignTokens::default();
+        incoming
+            .colors
+            .insert("primary".to_string(), simple("#fff"));
+        incoming.colors.insert("new".to_string(), simple("#222"));
+        incoming.spacing.insert("sm".to_string(), simple("4px"));
+        designer.merge_tokens(incoming, MergeStrategy::Replace);
+        assert_eq!(designer.tokens.count(), 3);
+        assert_eq!(designer.history.undo_label(), Some("Import tokens"));
+
+        assert!(designer.undo());
+        assert_eq!(color_names(&designer), ["primary", "old", "older"]);
+        assert_eq!(designer.get_token("colors.primary"), Some(&simple("#000")));
+        assert!(designer.tokens.spacing.is_empty());
+        assert_eq!(designer.selected_token, None);
+
+        assert!(designer.redo());
+        assert_eq!(color_names(&designer), ["primary", "new"]);
+        assert_eq!(designer.tokens.spacing["sm"], simple("4px"));
+    }
+
+    #[test]
+    fn test_history_depth() {
+        let mut designer = CssDesigner::new().with_history_depth(3);
+        for i in 0..5 {
+            designer.set_token("colors.primary", simple(&format!("#00{}", i)));
+        }
+        let mut undone = 0;
+        while designer.undo() {
+            undone += 1;
+        }
+        assert_eq!(undone, 3);
+        assert_eq!(designer.get_token("colors.primary"), Some(&simple("#001")));
+    }
+}
@@ -0,0 +1,339 @@
+//! Scratch token overrides for trying values without committing them.
+//!
+//! Experiments are layered over the real tokens in the preview only.
+//! Autosave, exports and validation keep reading [`StudioStore::tokens`],
+//! unless preview validation is switched on. Applying an override moves it
+//! into the real tokens as one undoable edit.
+//!
+//! The preview CSS of the overrides is its own layer, emitted after the
+//! token CSS so its declarations win. It is regenerated only when the
+//! overrides change, so switching experiments on and off is free.
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use super::color::ColorBlindness;
+use super::edit::write;
+use super::{DarkModeStrategy, DesignTokens, TokenValidationError, TokenValue, tokens_css};
+use crate::error::StudioError;
+use crate::history::HistoryScope;
+use crate::store::{StoreChange, StudioStore};
+
+/// Experiment error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ExperimentError {
+    /// Only existing tokens can be overridden.
+    #[error("Token '{0}' not found")]
+    NotFound(String),
+}
+
+/// What the cached layer CSS was generated for.
+type LayerKey = (DarkModeStrategy, Option<ColorBlindness>);
+
+/// Token overrides layered over the real tokens in the preview.
+#[derive(Debug, Clone)]
+pub struct TokenExperiments {
+    overrides: IndexMap<String, TokenValue>,
+    /// Show the overrides in the preview. Off keeps them without showing
+    /// them.
+    pub enabled: bool,
+    /// Validate the preview tokens, overrides included, instead of the
+    /// real ones.
+    pub validate: bool,
+    /// Times the layer CSS was regenerated.
+    pub layer_builds: usize,
+    layer: Option<(LayerKey, String)>,
+}
+
+impl Default for TokenExperiments {
+    fn default() -> Self {
+        Self {
+            overrides: IndexMap::new(),
+            enabled: true,
+            validate: false,
+            layer_builds: 0,
+            layer: None,
+        }
+    }
+}
+
+/// The value of the token at `path`.
+fn lookup<'a>(tokens: &'a DesignTokens, path: &str) -> Option<&'a TokenValue> {
+    let (prefix, name) = path.rsplit_once('.')?;
+    tokens
+        .categories()
+        .into_iter()
+        .find(|(p, _)| *p == prefix)
+        .and_then(|(_, map)| map.get(name))
+}
+
+impl TokenExperiments {
+    /// Overridden values by token path, in the order they were first tried.
+    pub fn overrides(&self) -> &IndexMap<String, TokenValue> {
+        &self.overrides
+    }
+
+    pub fn get(&self, path: &str) -> Option<&TokenValue> {
+        self.overrides.get(path)
+    }
+
+    /// Number shown on the experiment badge.
+    pub fn len(&self) -> usize {
+        self.overrides.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Try `value` for the token at `path`. Trying the real value drops
+    /// the override.
+    pub fn set(
+        &mut self,
+        tokens: &DesignTokens,
+        path: &str,
+        value: TokenValue,
+    ) -> Result<(), ExperimentError> {
+        let current = lookup(tokens, path).ok_or_else(|| ExperimentError::NotFound(path.into()))?;
+        if *current == value {
+            self.take(path);
+        } else if self.overrides.get(path) != Some(&value) {
+            self.overrides.insert(path.to_string(), value);
+            self.layer = None;
+        }
+        Ok(())
+    }
+
+    /// Remove the override of `path`, returning it.
+    pub fn take(&mut self, path: &str) -> Option<TokenValue> {
+        let value = self.overrides.shift_remove(path)?;
+        self.layer = None;
+        Some(value)
+    }
+
+    /// Drop every override.
+    pub fn clear(&mut self) {
+        if !self.overrides.is_empty() {
+            self.overrides.clear();
+            self.layer = None;
+        }
+    }
+
+    /// `tokens` with the overrides written over them. Overrides of tokens
+    /// that have since been deleted are skipped.
+    pub fn layered(&self, tokens: &DesignTokens) -> DesignTokens {
+        let mut layered = tokens.clone();
+        for (path, value) in &self.overrides {
+            if lookup(tokens, path).is_some() {
+                write(&mut layered, path, Some(value.clone()), 0);
+            }
+        }
+        layered
+    }
+
+    /// CSS declaring only the overridden variables, to follow the token
+    /// CSS. Empty without overrides. Cached until the overrides change.
+    pub fn layer_css(
+        &mut self,
+        dark_mode: &DarkModeStrategy,
+        simulation: Option<ColorBlindness>,
+    ) -> &str {
+        let key = (dark_mode.clone(), simulation);
+        if self.layer.as_ref().is_none_or(|(k, _)| *k != key) {
+            let css = if self.overrides.is_empty() {
+                String::new()
+            } else {
+                let mut layer = DesignTokens::default();
+                for (path, value) in &self.overrides {
+                    write(&mut layer, path, Some(value.clone()), usize::MAX);
+                }
+                if let Some(kind) = simulation {
+                    layer = layer.simulated(kind);
+                }
+                tokens_css(&layer, dark_mode, ":root", false)
+            };
+            self.layer_builds += 1;
+            self.layer = Some((key, css));
+        }
+        self.layer.as_ref().map_or("", |(_, css)| css)
+    }
+}
+
+impl StudioStore {
+    /// Try a value for a token without changing it.
+    pub fn try_token_value(&mut self, path: &str, value: TokenValue) -> Result<(), StudioError> {
+        let result = self.experiments.set(&self.tokens, path, value);
+        self.checked(result)
+    }
+
+    /// Move the override of `path` into the real tokens as one undoable
+    /// edit. Returns false if `path` has no override.
+    pub fn apply_experiment(&mut self, path: &str) -> Result<bool, StudioError> {
+        let Some(value) = self.experiments.take(path) else {
+            return Ok(false);
+        };
+        if lookup(&self.tokens, path).is_none() {
+            return self.checked(Err(ExperimentError::NotFound(path.to_string())));
+        }
+        let mut after = self.tokens.clone();
+        write(&mut after, path, Some(value.clone()), 0);
+        let change = StoreChange::Tokens {
+            before: Box::new(self.tokens.clone()),
+            after: Box::new(after),
+        };
+        if let Err(error) = self.try_commit(HistoryScope::CssDesigner, "Apply experiment", change) {
+            self.experiments.overrides.insert(path.to_string(), value);
+            self.experiments.layer = None;
+            return Err(error);
+        }
+        Ok(true)
+    }
+
+    /// Apply every override, one history entry each. Stops at the first
+    /// rejected one.
+    pub fn apply_experiments(&mut self) -> Result<usize, StudioError> {
+        let paths: Vec<String> = self.experiments.overrides.keys().cloned().collect();
+        let mut applied = 0;
+        for path in paths {
+            if self.apply_experiment(&path)? {
+                applied += 1;
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Drop the override of `path`. Returns false if there was none.
+    pub fn discard_experiment(&mut self, path: &str) -> bool {
+        self.experiments.take(path).is_some()
+    }
+
+    /// Drop every override.
+    pub fn discard_experiments(&mut self) {
+        self.experiments.clear();
+    }
+
+    /// CSS for the experiment layer of the preview, empty while
+    /// experiments are switched off.
+    pub fn experiment_css(&mut self, dark_mode: &DarkModeStrategy) -> &str {
+        if !self.experiments.enabled {
+            return "";
+        }
+        self.experiments
+            .layer_css(dark_mode, self.ui.color_simulation)
+    }
+
+    /// Validation shown next to the preview: of the real tokens, or of the
+    /// preview tokens when experiments are validated too.
+    pub fn preview_validation(&self) -> Vec<TokenValidationError> {
+        if self.experiments.enabled && self.experiments.validate {
+            self.experiments.layered(&self.tokens).validate()
+        } else {
+            self.tokens.validate()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StudioConfig;
+    use crate::project::ProjectFile;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        let mut tokens = DesignTokens::default();
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("#3b82f6"));
+        tokens.spacing.insert("md".to_string(), simple("16px"));
+        store.set_tokens(tokens);
+        store
+    }
+
+    #[test]
+    fn test_exports_ignore_experiments() {
+        let mut store = store();
+        let real = store.tokens.to_json().unwrap();
+        store
+            .try_token_value("colors.primary", simple("#ef4444"))
+            .unwrap();
+        let error = store.try_token_value("colors.missing", simple("red"));
+        assert_eq!(
+            error.unwrap_err().code,
+            crate::error::ErrorCode::TokenNotFound
+        );
+        assert_eq!(store.experiments.len(), 1);
+
+        let project = ProjectFile::from_store(&store, &StudioConfig::default());
+        assert_eq!(project.tokens.to_json().unwrap(), real);
+        assert_eq!(store.preview_tokens().colors["primary"], simple("#ef4444"));
+        store.experiments.enabled = false;
+        assert_eq!(store.preview_tokens().colors["primary"], simple("#3b82f6"));
+        assert_eq!(store.experiment_css(&DarkModeStrategy::default()), "");
+
+        // Trying the real value again is not an experiment.
+        store
+            .try_token_value("colors.primary", simple("#3b82f6"))
+            .unwrap();
+        assert!(store.experiments.is_empty());
+    }
+
+    #[test]
+    fn test_apply_records_one_entry_per_override() {
+        let mut store = store();
+        let depth = store
+            .history
+            .scope(HistoryScope::CssDesigner)
+            .unwrap()
+            .len();
+        store
+            .try_token_value("colors.primary", simple("#ef4444"))
+            .unwrap();
+        store.try_token_value("spacing.md", simple("20px")).unwrap();
+        store.try_token_value("spacing.md", simple("24px")).unwrap();
+
+        assert_eq!(store.apply_experiments(), Ok(2));
+        let history = store.history.scope(HistoryScope::CssDesigner).unwrap();
+        assert_eq!(history.len(), depth + 2);
+        assert_eq!(history.undo_label(), Some("Apply experiment"));
+        assert!(store.experiments.is_empty());
+        assert_eq!(store.tokens.spacing["md"], simple("24px"));
+
+        assert!(store.undo_scope(HistoryScope::CssDesigner));
+        assert_eq!(store.tokens.spacing["md"], simple("16px"));
+        assert_eq!(store.tokens.colors["primary"], simple("#ef4444"));
+        assert_eq!(store.apply_experiment("spacing.md"), Ok(false));
+    }
+
+    #[test]
+    fn test_layer_css_rebuilds_only_on_change() {
+        let mut store = store();
+        let dark_mode = DarkModeStrategy::default();
+        store
+            .try_token_value("colors.primary", simple("#ef4444"))
+            .unwrap();
+        assert_eq!(
+            store.experiment_css(&dark_mode),
+            ":root {\n  --color-primary: #ef4444;\n}\n"
+        );
+        store.experiments.enabled = false;
+        store.experiments.enabled = true;
+        store.experiment_css(&dark_mode);
+        assert_eq!(store.experiments.layer_builds, 1);
+
+        store.try_token_value("spacing.md", simple("20px")).unwrap();
+        assert!(
+            store
+                .experiment_css(&dark_mode)
+                .contains("--spacing-md: 20px;")
+        );
+        assert_eq!(store.experiments.layer_builds, 2);
+        assert!(store.discard_experiment("spacing.md"));
+        store.discard_experiments();
+        assert_eq!(store.experiment_css(&dark_mode), "");
+    }
+}
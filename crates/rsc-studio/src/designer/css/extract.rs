@@ -0,0 +1,305 @@
+//! Single-component CSS extraction.
+//!
+//! Produces one component's rules together with just the tokens it needs,
+//! for teams copying styles into an app without the full export.
+
+use indexmap::{IndexMap, IndexSet};
+
+use super::{ComponentStyles, DesignTokens, TokenValue, path_to_css_var};
+
+/// How token references are emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenMode {
+    /// Keep `var(...)` references and prepend a minimal `:root` block.
+    #[default]
+    Root,
+    /// Replace references with their literal values.
+    Inline,
+}
+
+/// Extraction options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractOptions {
+    pub mode: TokenMode,
+    /// Resolve adaptive tokens to their dark values.
+    pub dark: bool,
+}
+
+impl ExtractOptions {
+    pub fn inline() -> Self {
+        Self {
+            mode: TokenMode::Inline,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_dark(mut self, dark: bool) -> Self {
+        self.dark = dark;
+        self
+    }
+}
+
+/// Extracted component CSS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractResult {
+    pub css: String,
+    /// Token paths the component depends on, directly or transitively.
+    pub dependencies: Vec<String>,
+    /// Referenced variables that aren't tokens.
+    pub unresolved: Vec<String>,
+}
+
+impl ComponentStyles {
+    /// Extract one component's CSS with its token dependencies.
+    ///
+    /// Returns `None` if the component has no style.
+    pub fn extract_component_css(
+        &self,
+        name: &str,
+        tokens: &DesignTokens,
+        options: ExtractOptions,
+    ) -> Option<ExtractResult> {
+        let rules = self.get(name)?.to_css(name);
+        let vars: IndexMap<String, String> =
+            tokens.css_variables(options.dark).into_iter().collect();
+        let paths = var_paths(tokens);
+
+        // Dependency closure over variable names.
+        let mut needed: IndexSet<String> = IndexSet::new();
+        let mut unresolved: IndexSet<String> = IndexSet::new();
+        let mut queue: Vec<String> = var_refs(&rules);
+        while let Some(var) = queue.pop() {
+            if needed.contains(&var) || unresolved.contains(&var) {
+                continue;
+            }
+            match vars.get(&var) {
+                Some(value) => {
+                    queue.extend(var_refs(value));
+                    needed.insert(var);
+                }
+                None => {
+                    unresolved.insert(var);
+                }
+            }
+        }
+
+        // Keep token order rather than discovery order.
+        let ordered: Vec<(&String, &String)> = vars
+            .iter()
+            .filter(|(var, _)| needed.contains(*var))
+            .collect();
+
+        let css = match options.mode {
+            TokenMode::Root => {
+                let mut css = String::new();
+                if !ordered.is_empty() {
+                    css.push_str(":root {\n");
+                    for (var, value) in &ordered {
+                        css.push_str(&format!("  {}: {};\n", var, value));
+                    }
+                    css.push_str("}\n\n");
+                }
+                css.push_str(&rules);
+                css
+            }
+            TokenMode::Inline => inline_vars(&rules, &vars, &mut Vec::new()),
+        };
+
+        Some(ExtractResult {
+            css,
+            dependencies: ordered
+                .iter()
+                .filter_map(|(var, _)| paths.get(*var).cloned())
+                .collect(),
+            unresolved: unresolved.into_iter().collect(),
+        })
+    }
+}
+
+/// CSS variable name to token path, with scale steps as `path.step`.
+fn var_paths(tokens: &DesignTokens) -> IndexMap<String, String> {
+    let mut paths = IndexMap::new();
+    for (prefix, values) in tokens.categories() {
+        for (name, value) in values {
+            let path = format!("{}.{}", prefix, name);
+            let Some(var) = path_to_css_var(&path) else {
+                continue;
+            };
+            if let TokenValue::Scale(steps) = value {
+                for step in steps.keys() {
+                    paths.insert(format!("{}-{}", var, step), format!("{}.{}", path, step));
+                }
+            } else {
+                paths.insert(var, path);
+            }
+        }
+    }
+    paths
+}
+
+/// Find the `var(...)` calls in a value: (start, end, variable name).
+pub(crate) fn var_calls(value: &str) -> Vec<(usize, usize, String)> {
+    let mut calls = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = value[from..].find("var(") {
+        let start = from + offset;
+        let inner_start = start + 4;
+        let mut depth = 1;
+        let mut end = None;
+        for (i, c) in value[inner_start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(inner_start + i + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(end) = end else { break };
+        let inner = &value[inner_start..end - 1];
+        let name = inner.split(',').next().unwrap_or_default().trim();
+        calls.push((start, end, name.to_string()));
+        from = inner_start;
+    }
+    calls
+}
+
+fn var_refs(value: &str) -> Vec<String> {
+    var_calls(value)
+        .into_iter()
+        .map(|(_, _, name)| name)
+        .filter(|name| name.starts_with("--"))
+        .collect()
+}
+
+/// Replace token references with their values, recursively.
+///
+/// `stack` guards against reference cycles; cyclic or unknown references
+/// are left as written.
+pub(crate) fn inline_vars(
+    value: &str,
+    vars: &IndexMap<String, String>,
+    stack: &mut Vec<String>,
+) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, end, name) in var_calls(value) {
+        if start < last {
+            // Nested inside a call already handled.
+            continue;
+        }
+        let Some(resolved) = vars.get(&name).filter(|_| !stack.contains(&name)) else {
+            continue;
+        };
+        stack.push(name);
+        out.push_str(&value[last..start]);
+        out.push_str(&inline_vars(resolved, vars, stack));
+        stack.pop();
+        last = end;
+    }
+    out.push_str(&value[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{ComponentStyle, StateVariant, StyleProperties};
+
+    fn fixture() -> (ComponentStyles, DesignTokens) {
+        let mut tokens = DesignTokens::default();
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        tokens.colors.insert("blue".to_string(), simple("#3b82f6"));
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("var(--color-blue)"));
+        tokens.colors.insert(
+            "primary-hover".to_string(),
+            simple("color-mix(in srgb, var(--color-primary) 80%, black)"),
+        );
+        tokens.colors.insert("unused".to_string(), simple("#000"));
+        tokens.spacing.insert("md".to_string(), simple("16px"));
+        tokens.spacing.insert("lg".to_string(), simple("24px"));
+
+        let mut button = ComponentStyle {
+            base: StyleProperties {
+                background_color: Some("var(--color-primary)".to_string()),
+                padding: Some("var(--spacing-md)".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        button.states.insert(
+            StateVariant::Hover,
+            StyleProperties {
+                background_color: Some("var(--color-primary-hover, red)".to_string()),
+                ..Default::default()
+            },
+        );
+        let mut styles = ComponentStyles::new();
+        styles.set("button".to_string(), button);
+        (styles, tokens)
+    }
+
+    #[test]
+    fn test_dependency_closure() {
+        let (styles, tokens) = fixture();
+
+        let result = styles
+            .extract_component_css("button", &tokens, ExtractOptions::default())
+            .unwrap();
+
+        assert_eq!(
+            result.dependencies,
+            [
+                "colors.blue",
+                "colors.primary",
+                "colors.primary-hover",
+                "spacing.md"
+            ]
+        );
+        assert!(
+            result
+                .css
+                .starts_with(":root {\n  --color-blue: #3b82f6;\n")
+        );
+        assert!(!result.css.contains("--color-unused"));
+        assert!(!result.css.contains("--spacing-lg"));
+        assert!(result.css.contains(".button:hover {"));
+        assert!(result.unresolved.is_empty());
+        assert!(
+            styles
+                .extract_component_css("card", &tokens, ExtractOptions::default())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_inline_mode() {
+        let (styles, mut tokens) = fixture();
+        tokens.spacing.insert(
+            "loop".to_string(),
+            TokenValue::Simple("var(--spacing-loop)".to_string()),
+        );
+        let mut styles = styles;
+        styles.get_mut("button").unwrap().base.margin = Some("var(--spacing-loop)".to_string());
+
+        let result = styles
+            .extract_component_css("button", &tokens, ExtractOptions::inline())
+            .unwrap();
+
+        assert!(!result.css.contains(":root"));
+        assert!(result.css.contains("background-color: #3b82f6;"));
+        assert!(
+            result
+                .css
+                .contains("background-color: color-mix(in srgb, #3b82f6 80%, black);")
+        );
+        assert!(result.css.contains("padding: 16px;"));
+        assert!(result.css.contains("margin: var(--spacing-loop);"));
+    }
+}
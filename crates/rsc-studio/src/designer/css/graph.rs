@@ -0,0 +1,319 @@
+//! Token reference graph for the "Token graph" view.
+//!
+//! Every token becomes a read-only node on a flow canvas and every `var()`
+//! reference an edge from the referencing token to the referenced one.
+//! Node ids encode the token path so a click can open the token in the
+//! CSS designer.
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use rsc_flow::NodeStatus;
+use rsc_flow::prelude::*;
+use serde_json::json;
+
+use super::usage::TokenReferences;
+use super::{CategoryPresentations, CssDesigner, DesignTokens, TokenCategory, TokenValue};
+
+/// Node id prefix for token nodes.
+pub const TOKEN_NODE_PREFIX: &str = "token:";
+
+/// Class name on nodes and edges that are part of a reference cycle.
+pub const CYCLE_CLASS: &str = "token-cycle";
+
+/// Longest value preview shown under a node label, in characters.
+const PREVIEW_CHARS: usize = 24;
+
+/// Node id for a token path.
+pub fn token_node_id(path: &str) -> String {
+    format!("{}{}", TOKEN_NODE_PREFIX, path)
+}
+
+/// Token path encoded in a node id.
+pub fn token_path(node_id: &str) -> Option<&str> {
+    node_id
+        .strip_prefix(TOKEN_NODE_PREFIX)
+        .filter(|path| !path.is_empty())
+}
+
+/// Short, single-line description of a token value.
+fn value_preview(value: &TokenValue) -> String {
+    let text = match value {
+        TokenValue::Simple(v) => v.clone(),
+        TokenValue::Adaptive { light, dark } => format!("{} / {}", light, dark),
+        TokenValue::Scale(steps) => format!("{} steps", steps.len()),
+    };
+    if text.chars().count() <= PREVIEW_CHARS {
+        return text;
+    }
+    let mut short: String = text.chars().take(PREVIEW_CHARS - 1).collect();
+    short.push('…');
+    short
+}
+
+/// Strongly connected groups of tokens that reference each other, in
+/// token order. Tokens referencing themselves are not counted.
+fn reference_cycles(refs: &IndexMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    struct Tarjan<'a> {
+        refs: &'a IndexMap<String, Vec<String>>,
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        cycles: Vec<Vec<String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, path: &'a str) {
+            let index = self.index.len();
+            self.index.insert(path, index);
+            self.low.insert(path, index);
+            self.stack.push(path);
+            for target in self.refs.get(path).into_iter().flatten() {
+                let target = target.as_str();
+                if !self.index.contains_key(target) {
+                    self.visit(target);
+                    let low = self.low[path].min(self.low[target]);
+                    self.low.insert(path, low);
+                } else if self.stack.contains(&target) {
+                    let low = self.low[path].min(self.index[target]);
+                    self.low.insert(path, low);
+                }
+            }
+            if self.low[path] == index {
+                let start = self.stack.iter().rposition(|p| *p == path).unwrap_or(0);
+                let group = self.stack.split_off(start);
+                if group.len() > 1 {
+                    let mut group: Vec<String> = group.into_iter().map(String::from).collect();
+                    group.sort_by_key(|p| self.refs.get_index_of(p));
+                    self.cycles.push(group);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        refs,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        cycles: Vec::new(),
+    };
+    for path in refs.keys() {
+        if !tarjan.index.contains_key(path.as_str()) {
+            tarjan.visit(path);
+        }
+    }
+    let mut cycles = tarjan.cycles;
+    cycles.sort_by_key(|group| refs.get_index_of(&group[0]));
+    cycles
+}
+
+/// Token graph view model.
+#[derive(Debug, Clone)]
+pub struct TokenGraphView {
+    /// Laid-out, read-only canvas.
+    pub canvas: FlowCanvas<NodeData, EdgeData>,
+    /// Tokens that reference each other in a loop, one group per cycle.
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl TokenGraphView {
+    /// Build the graph, coloring nodes with each category's accent.
+    pub fn build(tokens: &DesignTokens, presentation: &CategoryPresentations) -> Self {
+        let references = TokenReferences::new(tokens);
+        let cycles = reference_cycles(&references.refs);
+        let cycle_of: HashMap<&str, usize> = cycles
+            .iter()
+            .enumerate()
+            .flat_map(|(i, group)| group.iter().map(move |p| (p.as_str(), i)))
+            .collect();
+
+        let mut canvas: FlowCanvas<NodeData, EdgeData> = FlowCanvas::new();
+        canvas.config.deletable = false;
+        canvas.config.edges_updatable = false;
+
+        for (prefix, map) in tokens.categories() {
+            let category = prefix.split('.').next().unwrap_or(prefix);
+            let style = presentation.resolve(category);
+            for (name, value) in map {
+                let path = format!("{}.{}", prefix, name);
+                let mut data = NodeData::new(&path)
+                    .with_icon(&style.icon)
+                    .with_description(value_preview(value));
+                data.metadata.insert("path".to_string(), json!(path));
+                data.metadata
+                    .insert("category".to_string(), json!(category));
+                data.metadata
+                    .insert("accent".to_string(), json!(style.accent));
+
+                let mut node = Node::new(
+                    token_node_id(&path),
+                    NodeType::Custom("token".to_string()),
+                    Position::zero(),
+                )
+                .with_data(data);
+                node.draggable = false;
+                node.connectable = false;
+                node.style
+                    .insert("border-color".to_string(), style.accent.clone());
+                if cycle_of.contains_key(path.as_str()) {
+                    node.class_names.push(CYCLE_CLASS.to_string());
+                    if let Some(data) = &mut node.data {
+                        data.status = Some(NodeStatus::Error);
+                    }
+                }
+                canvas.add_node(node);
+            }
+        }
+
+        for (path, targets) in &references.refs {
+            for target in targets {
+                let source_id = token_node_id(path);
+                let target_id = token_node_id(target);
+                let mut edge: Edge<EdgeData> = Edge::new(
+                    format!("{}->{}", source_id, target_id),
+                    source_id,
+                    target_id,
+                );
+                let group = cycle_of.get(path.as_str());
+                if group.is_some() && group == cycle_of.get(target.as_str()) {
+                    edge.class_names.push(CYCLE_CLASS.to_string());
+                    edge.animated = true;
+                }
+                canvas.add_edge(edge);
+            }
+        }
+
+        canvas.auto_layout(LayoutConfig {
+            direction: LayoutDirection::LeftToRight,
+            node_sep: 30.0,
+            rank_sep: 120.0,
+            default_node_dimensions: Dimensions::new(180.0, 56.0),
+            ..Default::default()
+        });
+
+        Self { canvas, cycles }
+    }
+
+    /// Whether a token is part of a reference cycle.
+    pub fn in_cycle(&self, path: &str) -> bool {
+        self.cycles
+            .iter()
+            .any(|group| group.iter().any(|p| p == path))
+    }
+}
+
+/// Token graph with the default category presentation.
+pub fn tokens_to_flow(tokens: &DesignTokens) -> FlowCanvas<NodeData, EdgeData> {
+    TokenGraphView::build(tokens, &CategoryPresentations::default()).canvas
+}
+
+impl CssDesigner {
+    /// Select the token behind a graph node. Returns false for ids that
+    /// don't name an existing token.
+    pub fn open_graph_node(&mut self, node_id: &str) -> bool {
+        let Some(path) = token_path(node_id) else {
+            return false;
+        };
+        if !self.tokens.all_paths().iter().any(|p| p == path) {
+            return false;
+        }
+        let key = path.split('.').next().unwrap_or(path);
+        if let Some(category) = TokenCategory::from_key(key) {
+            self.selected_category = category;
+        }
+        self.selected_token = Some(path.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn designer() -> CssDesigner {
+        let mut designer = CssDesigner::new();
+        let tokens = &mut designer.tokens;
+        tokens.colors.insert("brand".to_string(), simple("#2563eb"));
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("var(--color-brand)"));
+        tokens.colors.insert(
+            "blue".to_string(),
+            TokenValue::Scale(IndexMap::from([("500".to_string(), "#3b82f6".to_string())])),
+        );
+        tokens.colors.insert(
+            "link".to_string(),
+            TokenValue::Adaptive {
+                light: "var(--color-blue-500)".to_string(),
+                dark: "var(--color-primary)".to_string(),
+            },
+        );
+        tokens
+            .spacing
+            .insert("a".to_string(), simple("var(--spacing-b)"));
+        tokens
+            .spacing
+            .insert("b".to_string(), simple("calc(var(--spacing-a) * 2)"));
+        tokens.radius.insert("md".to_string(), simple("6px"));
+        designer
+    }
+
+    #[test]
+    fn test_tokens_to_flow() {
+        let designer = designer();
+        let view = TokenGraphView::build(&designer.tokens, &CategoryPresentations::default());
+        let canvas = &view.canvas;
+        assert_eq!(canvas.nodes.len(), 7);
+        // primary -> brand, link -> blue, link -> primary, a -> b, b -> a.
+        assert_eq!(canvas.edges.len(), 5);
+        assert!(
+            canvas
+                .edges
+                .contains_key("token:colors.link->token:colors.blue")
+        );
+        assert!(!canvas.config.deletable && !canvas.config.edges_updatable);
+
+        let link = &canvas.nodes["token:colors.link"];
+        assert!(!link.draggable && !link.connectable);
+        assert!(link.dimensions.is_some());
+        let data = link.data.as_ref().unwrap();
+        assert_eq!(data.label, "colors.link");
+        assert_eq!(
+            data.description.as_deref(),
+            Some("var(--color-blue-500) /…")
+        );
+        let colors = CategoryPresentations::default().resolve("colors");
+        assert_eq!(link.style["border-color"], colors.accent);
+        assert_eq!(data.icon.as_deref(), Some(colors.icon.as_str()));
+
+        assert_eq!(view.cycles, [vec!["spacing.a", "spacing.b"]]);
+        let a = &canvas.nodes["token:spacing.a"];
+        assert!(a.class_names.contains(&CYCLE_CLASS.to_string()));
+        // Cycle-only nodes still get laid out.
+        assert!(a.dimensions.is_some());
+        assert!(canvas.edges["token:spacing.b->token:spacing.a"].animated);
+        assert!(!view.in_cycle("colors.primary"));
+        assert_eq!(tokens_to_flow(&designer.tokens).nodes.len(), 7);
+    }
+
+    #[test]
+    fn test_node_id_round_trip() {
+        for path in ["colors.primary", "typography.sizes.xl", "z-index.modal"] {
+            assert_eq!(token_path(&token_node_id(path)), Some(path));
+        }
+        assert_eq!(token_path("workflow-1"), None);
+        assert_eq!(token_path(TOKEN_NODE_PREFIX), None);
+
+        let mut designer = designer();
+        assert!(designer.open_graph_node("token:spacing.b"));
+        assert_eq!(designer.selected_category, TokenCategory::Spacing);
+        assert_eq!(designer.selected_token.as_deref(), Some("spacing.b"));
+        assert!(!designer.open_graph_node("token:spacing.missing"));
+        assert_eq!(designer.selected_token.as_deref(), Some("spacing.b"));
+    }
+}
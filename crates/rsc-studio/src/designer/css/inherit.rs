@@ -0,0 +1,291 @@
+//! Component style inheritance through `extends`.
+//!
+//! A style's own properties win over inherited ones, property by property,
+//! in the base, every state and every breakpoint. Chains may be any depth.
+
+use thiserror::Error;
+
+use super::{ComponentStyle, ComponentStyles};
+
+/// Component style inheritance error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ComponentStyleError {
+    /// No style exists with the name.
+    #[error("Component style '{0}' not found")]
+    NotFound(String),
+
+    /// A style extends a style that doesn't exist.
+    #[error("Component style '{component}' extends unknown style '{parent}'")]
+    UnknownParent { component: String, parent: String },
+
+    /// Styles extend each other in a loop; the first name is repeated last.
+    #[error("Component styles extend each other in a cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// Apply `base` under `style`.
+fn inherit(style: &mut ComponentStyle, base: &ComponentStyle) {
+    style.base = style.base.merged_over(&base.base);
+    for (state, props) in &base.states {
+        let merged = match style.states.get(state) {
+            Some(own) => own.merged_over(props),
+            None => props.clone(),
+        };
+        style.states.insert(*state, merged);
+    }
+    for (breakpoint, props) in &base.breakpoints {
+        let merged = match style.breakpoints.get(breakpoint) {
+            Some(own) => own.merged_over(props),
+            None => props.clone(),
+        };
+        style.breakpoints.insert(*breakpoint, merged);
+    }
+}
+
+impl ComponentStyles {
+    /// `component` followed by the styles it extends, nearest first.
+    pub fn extends_chain(&self, component: &str) -> Result<Vec<String>, ComponentStyleError> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut name = component.to_string();
+        loop {
+            if let Some(start) = chain.iter().position(|n| *n == name) {
+                let mut cycle = chain.split_off(start);
+                cycle.push(name);
+                return Err(ComponentStyleError::Cycle(cycle));
+            }
+            let Some(style) = self.styles.get(&name) else {
+                return Err(match chain.pop() {
+                    Some(component) => ComponentStyleError::UnknownParent {
+                        component,
+                        parent: name,
+                    },
+                    None => ComponentStyleError::NotFound(name),
+                });
+            };
+            chain.push(name);
+            match &style.extends {
+                Some(parent) => name = parent.clone(),
+                None => return Ok(chain),
+            }
+        }
+    }
+
+    /// A style with its `extends` chain applied, or why the chain is broken.
+    pub fn try_resolve(&self, component: &str) -> Result<ComponentStyle, ComponentStyleError> {
+        let chain = self.extends_chain(component)?;
+        let mut style = self.styles[component].clone();
+        for name in &chain[1..] {
+            inherit(&mut style, &self.styles[name]);
+        }
+        Ok(style)
+    }
+
+    /// A style with its `extends` chain applied. A missing parent or a
+    /// repeated name ends the chain; see [`ComponentStyles::check_extends`]
+    /// to report those.
+    pub fn resolve(&self, component: &str) -> Option<ComponentStyle> {
+        let mut style = self.styles.get(component)?.clone();
+        let mut seen = vec![component.to_string()];
+        let mut parent = style.extends.clone();
+        while let Some(name) = parent {
+            if seen.contains(&name) {
+                break;
+            }
+            let Some(base) = self.styles.get(&name) else {
+                break;
+            };
+            inherit(&mut style, base);
+            seen.push(name);
+            parent = base.extends.clone();
+        }
+        Some(style)
+    }
+
+    /// Every broken `extends` chain, each cycle reported once.
+    pub fn check_extends(&self) -> Vec<ComponentStyleError> {
+        let mut errors: Vec<ComponentStyleError> = Vec::new();
+        for name in self.styles.keys() {
+            let Err(error) = self.extends_chain(name) else {
+                continue;
+            };
+            let duplicate = errors.iter().any(|e| match (e, &error) {
+                (ComponentStyleError::Cycle(a), ComponentStyleError::Cycle(b)) => {
+                    a.len() == b.len() && b.iter().all(|n| a.contains(n))
+                }
+                (a, b) => a == b,
+            });
+            if !duplicate {
+                errors.push(error);
+            }
+        }
+        errors
+    }
+
+    /// Whether setting `style` as `component` would close an `extends` loop
+    /// through it.
+    pub fn check_new_style(
+        &self,
+        component: &str,
+        style: &ComponentStyle,
+    ) -> Result<(), ComponentStyleError> {
+        let mut chain = vec![component.to_string()];
+        let mut parent = style.extends.clone();
+        while let Some(name) = parent {
+            if name == component {
+                chain.push(name);
+                return Err(ComponentStyleError::Cycle(chain));
+            }
+            if chain.contains(&name) {
+                // A loop further up that doesn't involve this style.
+                break;
+            }
+            parent = self.styles.get(&name).and_then(|s| s.extends.clone());
+            chain.push(name);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{Breakpoint, StateVariant, StyleProperties};
+    use crate::error::ErrorCode;
+    use crate::store::StudioStore;
+
+    fn props(pairs: &[(&str, &str)]) -> StyleProperties {
+        let mut props = StyleProperties::default();
+        for (name, value) in pairs {
+            props.set(name, Some(value.to_string()));
+        }
+        props
+    }
+
+    fn style(extends: Option<&str>, base: &[(&str, &str)]) -> ComponentStyle {
+        ComponentStyle {
+            base: props(base),
+            extends: extends.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_override_precedence() {
+        let mut button = style(None, &[("padding", "8px"), ("color", "black")]);
+        button.states.insert(
+            StateVariant::Hover,
+            props(&[("background", "gray"), ("color", "white")]),
+        );
+        button.breakpoints.insert(
+            Breakpoint::Md,
+            props(&[("padding", "12px"), ("width", "auto")]),
+        );
+        let mut secondary = style(Some("button"), &[("color", "navy")]);
+        secondary
+            .states
+            .insert(StateVariant::Hover, props(&[("background", "silver")]));
+        let mut outline = style(Some("button-secondary"), &[("border", "1px solid")]);
+        outline
+            .breakpoints
+            .insert(Breakpoint::Md, props(&[("width", "100%")]));
+
+        let mut styles = ComponentStyles::new();
+        styles.set("button".to_string(), button);
+        styles.set("button-secondary".to_string(), secondary);
+        styles.set("button-outline".to_string(), outline);
+
+        let resolved = styles.try_resolve("button-outline").unwrap();
+        assert_eq!(resolved.base.get("border").unwrap(), "1px solid");
+        assert_eq!(resolved.base.get("color").unwrap(), "navy");
+        assert_eq!(resolved.base.get("padding").unwrap(), "8px");
+        // The nearest state override wins per property; others come from further up.
+        let hover = &resolved.states[&StateVariant::Hover];
+        assert_eq!(hover.get("background").unwrap(), "silver");
+        assert_eq!(hover.get("color").unwrap(), "white");
+        let md = &resolved.breakpoints[&Breakpoint::Md];
+        assert_eq!(md.get("width").unwrap(), "100%");
+        assert_eq!(md.get("padding").unwrap(), "12px");
+
+        assert_eq!(
+            styles.extends_chain("button-outline").unwrap(),
+            ["button-outline", "button-secondary", "button"]
+        );
+        assert!(styles.check_extends().is_empty());
+    }
+
+    #[test]
+    fn test_cycles_are_reported() {
+        let mut styles = ComponentStyles::new();
+        styles.set("a".to_string(), style(Some("b"), &[("color", "red")]));
+        styles.set("b".to_string(), style(Some("c"), &[("padding", "1px")]));
+        styles.set("c".to_string(), style(Some("a"), &[]));
+        styles.set("d".to_string(), style(Some("a"), &[]));
+        styles.set("e".to_string(), style(Some("missing"), &[]));
+
+        assert_eq!(
+            styles.try_resolve("d").unwrap_err().to_string(),
+            "Component styles extend each other in a cycle: a -> b -> c -> a"
+        );
+        // The lenient resolve still terminates.
+        let a = styles.resolve("a").unwrap();
+        assert_eq!(a.base.get("padding").unwrap(), "1px");
+        assert!(styles.generate_css().contains(".a {"));
+
+        assert_eq!(
+            styles.check_extends(),
+            [
+                ComponentStyleError::Cycle(vec![
+                    "a".to_string(),
+                    "b".to_string(),
+                    "c".to_string(),
+                    "a".to_string()
+                ]),
+                ComponentStyleError::UnknownParent {
+                    component: "e".to_string(),
+                    parent: "missing".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            styles.try_resolve("z").unwrap_err(),
+            ComponentStyleError::NotFound("z".to_string())
+        );
+    }
+
+    #[test]
+    fn test_store_rejects_cycles() {
+        let mut store = StudioStore::new();
+        store
+            .set_component_style("button", style(None, &[]))
+            .unwrap();
+        store
+            .set_component_style("button-secondary", style(Some("button"), &[]))
+            .unwrap();
+
+        let error = store
+            .set_component_style("button", style(Some("button-secondary"), &[]))
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::CycleDetected);
+        assert!(store.component_styles.styles["button"].extends.is_none());
+        assert_eq!(store.problems.len(), 1);
+    }
+
+    #[test]
+    fn test_extends_round_trip() {
+        let mut styles = ComponentStyles::new();
+        styles.set("button".to_string(), style(None, &[]));
+        styles.set(
+            "button-secondary".to_string(),
+            style(Some("button"), &[("color", "navy")]),
+        );
+        let json = serde_json::to_string(&styles).unwrap();
+        assert_eq!(json.matches("\"extends\"").count(), 1);
+
+        let back: ComponentStyles = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            back.styles["button-secondary"].extends.as_deref(),
+            Some("button")
+        );
+        assert!(back.styles["button"].extends.is_none());
+    }
+}
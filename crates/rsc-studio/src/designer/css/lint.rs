@@ -0,0 +1,327 @@
+//! Style property lints for the CSS designer.
+//!
+//! Catches declarations whose effect depends on output order (a shorthand
+//! and one of its longhands set together), values that would emit an
+//! empty declaration, and keywords an enumerated property doesn't accept.
+
+use indexmap::IndexMap;
+
+use super::{
+    Breakpoint, ComponentStyle, ComponentStyles, StateVariant, StyleProperties, ValidationSeverity,
+};
+
+/// Shorthands and the longhands they reset.
+const SHORTHANDS: &[(&str, &[&str])] = &[
+    (
+        "padding",
+        &[
+            "padding-top",
+            "padding-right",
+            "padding-bottom",
+            "padding-left",
+        ],
+    ),
+    (
+        "margin",
+        &["margin-top", "margin-right", "margin-bottom", "margin-left"],
+    ),
+    ("border", &["border-width", "border-style", "border-color"]),
+    ("background", &["background-color"]),
+    ("overflow", &["overflow-x", "overflow-y"]),
+];
+
+const OVERFLOW_KEYWORDS: &[&str] = &["visible", "hidden", "clip", "scroll", "auto"];
+
+/// Enumerated properties and their keywords.
+const KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "display",
+        &[
+            "block",
+            "inline",
+            "inline-block",
+            "flex",
+            "inline-flex",
+            "grid",
+            "inline-grid",
+            "flow-root",
+            "contents",
+            "table",
+            "table-row",
+            "table-cell",
+            "list-item",
+            "none",
+        ],
+    ),
+    (
+        "position",
+        &["static", "relative", "absolute", "fixed", "sticky"],
+    ),
+    (
+        "flex-direction",
+        &["row", "row-reverse", "column", "column-reverse"],
+    ),
+    (
+        "text-align",
+        &["left", "right", "center", "justify", "start", "end"],
+    ),
+    ("overflow", OVERFLOW_KEYWORDS),
+    ("overflow-x", OVERFLOW_KEYWORDS),
+    ("overflow-y", OVERFLOW_KEYWORDS),
+];
+
+/// Keywords every property accepts.
+const GLOBAL_KEYWORDS: &[&str] = &["inherit", "initial", "unset", "revert", "revert-layer"];
+
+/// Kind of style lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleLintKind {
+    /// A shorthand and one of its longhands are both set.
+    ShorthandConflict,
+    /// A property is set to an empty value.
+    EmptyValue,
+    /// An enumerated property has a keyword it doesn't accept.
+    InvalidValue,
+}
+
+/// A problem with a set of style properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleLint {
+    pub kind: StyleLintKind,
+    /// CSS names of the properties involved, shorthand first.
+    pub properties: Vec<String>,
+    pub severity: ValidationSeverity,
+    pub message: String,
+    /// Suggested resolution, for the designer's quick-fix text.
+    pub suggestion: String,
+}
+
+/// Where in a component style a lint was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintScope {
+    Base,
+    State(StateVariant),
+    Breakpoint(Breakpoint),
+}
+
+/// A lint on one part of a component style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentLint {
+    pub scope: LintScope,
+    pub lint: StyleLint,
+}
+
+impl StyleProperties {
+    /// Shorthand conflicts, empty values and invalid keywords, in
+    /// property order.
+    pub fn lint(&self) -> Vec<StyleLint> {
+        let mut lints = Vec::new();
+
+        for (shorthand, longhands) in SHORTHANDS {
+            if self.get(shorthand).is_none() {
+                continue;
+            }
+            let set: Vec<&str> = longhands
+                .iter()
+                .copied()
+                .filter(|l| self.get(l).is_some())
+                .collect();
+            if set.is_empty() {
+                continue;
+            }
+            lints.push(StyleLint {
+                kind: StyleLintKind::ShorthandConflict,
+                properties: std::iter::once(*shorthand)
+                    .chain(set.iter().copied())
+                    .map(String::from)
+                    .collect(),
+                severity: ValidationSeverity::Warning,
+                message: format!(
+                    "{} and {} are both set; the longhand wins because it is written later",
+                    shorthand,
+                    set.join(", ")
+                ),
+                suggestion: format!(
+                    "Fold {} into {}, or remove {}",
+                    set.join(", "),
+                    shorthand,
+                    shorthand
+                ),
+            });
+        }
+
+        let values = StyleProperties::PROPERTY_NAMES
+            .iter()
+            .filter_map(|name| self.get(name).map(|v| (*name, v.as_str())))
+            .chain(self.custom.iter().map(|(n, v)| (n.as_str(), v.as_str())));
+        for (name, value) in values {
+            let value = value.trim();
+            if value.is_empty() || value == "!important" {
+                lints.push(StyleLint {
+                    kind: StyleLintKind::EmptyValue,
+                    properties: vec![name.to_string()],
+                    severity: ValidationSeverity::Warning,
+                    message: format!("{} is set but empty", name),
+                    suggestion: format!("Give {} a value or clear it", name),
+                });
+                continue;
+            }
+            let Some((_, allowed)) = KEYWORDS.iter().find(|(n, _)| *n == name) else {
+                continue;
+            };
+            let keyword = value
+                .trim_end_matches("!important")
+                .trim()
+                .to_ascii_lowercase();
+            let valid = keyword.contains("var(")
+                || allowed.contains(&keyword.as_str())
+                || GLOBAL_KEYWORDS.contains(&keyword.as_str());
+            if !valid {
+                lints.push(StyleLint {
+                    kind: StyleLintKind::InvalidValue,
+                    properties: vec![name.to_string()],
+                    severity: ValidationSeverity::Error,
+                    message: format!("'{}' is not a valid {} value", value, name),
+                    suggestion: format!("Use one of: {}", allowed.join(", ")),
+                });
+            }
+        }
+
+        lints
+    }
+}
+
+impl ComponentStyle {
+    /// Lints for the base style, then each state and breakpoint.
+    pub fn lint(&self) -> Vec<ComponentLint> {
+        let scoped = std::iter::once((LintScope::Base, &self.base))
+            .chain(
+                self.states
+                    .iter()
+                    .map(|(state, props)| (LintScope::State(*state), props)),
+            )
+            .chain(
+                self.breakpoints
+                    .iter()
+                    .map(|(bp, props)| (LintScope::Breakpoint(*bp), props)),
+            );
+        scoped
+            .flat_map(|(scope, props)| {
+                props
+                    .lint()
+                    .into_iter()
+                    .map(move |lint| ComponentLint { scope, lint })
+            })
+            .collect()
+    }
+}
+
+impl ComponentStyles {
+    /// Lints per component, omitting components without any. Inherited
+    /// properties are linted on the component that declares them.
+    pub fn lint_all(&self) -> IndexMap<String, Vec<ComponentLint>> {
+        self.styles
+            .iter()
+            .map(|(name, style)| (name.clone(), style.lint()))
+            .filter(|(_, lints)| !lints.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(pairs: &[(&str, &str)]) -> StyleProperties {
+        let mut props = StyleProperties::default();
+        for (name, value) in pairs {
+            props.set(name, Some(value.to_string()));
+        }
+        props
+    }
+
+    #[test]
+    fn test_shorthand_conflicts() {
+        let lints = props(&[
+            ("padding", "1rem"),
+            ("padding-top", "2rem"),
+            ("padding-left", "0"),
+            ("border", "1px solid red"),
+            ("border-color", "blue"),
+            ("margin-top", "4px"),
+            ("overflow-x", "auto"),
+        ])
+        .lint();
+        let conflicts: Vec<_> = lints.iter().map(|l| l.properties.clone()).collect();
+        assert_eq!(
+            conflicts,
+            [
+                vec!["padding", "padding-top", "padding-left"],
+                vec!["border", "border-color"],
+            ]
+        );
+        assert!(
+            lints
+                .iter()
+                .all(|l| l.kind == StyleLintKind::ShorthandConflict
+                    && l.severity == ValidationSeverity::Warning)
+        );
+        assert_eq!(
+            lints[1].suggestion,
+            "Fold border-color into border, or remove border"
+        );
+    }
+
+    #[test]
+    fn test_empty_and_invalid_values() {
+        let mut style = props(&[
+            ("display", "flexbox"),
+            ("position", "STICKY !important"),
+            ("overflow", "var(--overflow)"),
+            ("color", " "),
+            ("text-align", "inherit"),
+        ]);
+        style.custom.insert("--gap".to_string(), String::new());
+        let lints = style.lint();
+        let found: Vec<_> = lints
+            .iter()
+            .map(|l| (l.kind, l.properties[0].as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (StyleLintKind::InvalidValue, "display"),
+                (StyleLintKind::EmptyValue, "color"),
+                (StyleLintKind::EmptyValue, "--gap"),
+            ]
+        );
+        assert_eq!(lints[0].severity, ValidationSeverity::Error);
+        assert!(lints[0].suggestion.contains("inline-flex"));
+    }
+
+    #[test]
+    fn test_lint_all() {
+        let mut styles = ComponentStyles::new();
+        let mut button = ComponentStyle::default();
+        button.states.insert(
+            StateVariant::Hover,
+            props(&[("margin", "0"), ("margin-left", "auto")]),
+        );
+        button
+            .breakpoints
+            .insert(Breakpoint::Md, props(&[("display", "nope")]));
+        styles.set("button".to_string(), button);
+        styles.set("card".to_string(), ComponentStyle::default());
+
+        let lints = styles.lint_all();
+        assert_eq!(lints.len(), 1);
+        let scopes: Vec<_> = lints["button"].iter().map(|l| l.scope).collect();
+        assert_eq!(
+            scopes,
+            [
+                LintScope::State(StateVariant::Hover),
+                LintScope::Breakpoint(Breakpoint::Md),
+            ]
+        );
+    }
+}
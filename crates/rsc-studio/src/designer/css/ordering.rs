@@ -0,0 +1,433 @@
+//! Deterministic ordering of generated output.
+//!
+//! Token and component maps keep insertion order, which depends on edit
+//! history: removing and re-adding a token moves it to the end. An
+//! [`OutputOrdering`] other than insertion order sorts tokens, scale steps
+//! and components by name before CSS or an export is written, so two
+//! semantically equal sets produce the same bytes.
+//! [`normalize_order`](DesignTokens::normalize_order) rewrites the maps
+//! themselves, so saved project files are stable too.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::{Breakpoint, ComponentStyles, DesignTokens, StateVariant, TokenValue};
+use crate::export::tokens::{ExportError, ExportFormat};
+
+/// Order of tokens and components in generated output.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputOrdering {
+    /// The order entries were added in.
+    #[default]
+    InsertionOrder,
+    /// By name, with digit runs compared as numbers (`2` before `10`).
+    Alphabetical,
+    /// Listed token paths (`colors.primary`, `colors.blue.50`) and
+    /// component names first, in list order; the rest alphabetically.
+    Pinned(Vec<String>),
+}
+
+impl OutputOrdering {
+    pub fn is_default(&self) -> bool {
+        *self == Self::InsertionOrder
+    }
+
+    /// Compare two token paths or component names.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            OutputOrdering::InsertionOrder => Ordering::Equal,
+            OutputOrdering::Alphabetical => natural_cmp(a, b),
+            OutputOrdering::Pinned(pins) => {
+                let rank = |key: &str| pins.iter().position(|p| p == key).unwrap_or(usize::MAX);
+                rank(a).cmp(&rank(b)).then_with(|| natural_cmp(a, b))
+            }
+        }
+    }
+
+    /// Sort `map`, whose keys are names under `prefix` (paths when empty).
+    /// Returns whether the order changed.
+    fn sort<V>(&self, map: &mut IndexMap<String, V>, prefix: &str) -> bool {
+        if self.is_default() {
+            return false;
+        }
+        let path = |key: &str| match prefix {
+            "" => key.to_string(),
+            _ => format!("{}.{}", prefix, key),
+        };
+        let before: Vec<String> = map.keys().cloned().collect();
+        map.sort_by(|a, _, b, _| self.compare(&path(a), &path(b)));
+        map.keys().ne(before.iter())
+    }
+}
+
+/// Compare strings with runs of digits ordered by value, so scale steps
+/// sort as `50, 100, 900` instead of `100, 50, 900`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a_chars, mut b_chars) = (a.char_indices().peekable(), b.char_indices().peekable());
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some((i, x)), Some((j, y))) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_run = digit_run(&a[i..]);
+                let b_run = digit_run(&b[j..]);
+                let (a_num, b_num) = (a_run.trim_start_matches('0'), b_run.trim_start_matches('0'));
+                let order = a_num.len().cmp(&b_num.len()).then_with(|| a_num.cmp(b_num));
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a_chars.nth(a_run.len() - 1);
+                b_chars.nth(b_run.len() - 1);
+            }
+            (Some((_, x)), Some((_, y))) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn digit_run(s: &str) -> &str {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    &s[..end]
+}
+
+impl DesignTokens {
+    /// Rewrite every token map and scale to `ordering`, and the per-path
+    /// docs and metadata to follow the tokens. Returns whether anything
+    /// moved.
+    pub fn normalize_order(&mut self, ordering: &OutputOrdering) -> bool {
+        let mut changed = false;
+        for (prefix, map) in self.categories_mut() {
+            changed |= ordering.sort(map, prefix);
+            for (name, value) in map.iter_mut() {
+                if let TokenValue::Scale(steps) = value {
+                    changed |= ordering.sort(steps, &format!("{}.{}", prefix, name));
+                }
+            }
+        }
+        if ordering.is_default() {
+            return changed;
+        }
+        let paths: HashMap<String, usize> = self
+            .all_paths()
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| (path, i))
+            .collect();
+        let rank = |path: &String| paths.get(path).copied().unwrap_or(usize::MAX);
+        changed |= sort_by_rank(&mut self.docs, rank);
+        changed |= sort_by_rank(&mut self.metadata, rank);
+        changed
+    }
+
+    /// The tokens in `ordering`, borrowed when that is insertion order.
+    pub fn ordered(&self, ordering: &OutputOrdering) -> Cow<'_, DesignTokens> {
+        if ordering.is_default() {
+            return Cow::Borrowed(self);
+        }
+        let mut tokens = self.clone();
+        tokens.normalize_order(ordering);
+        Cow::Owned(tokens)
+    }
+
+    /// [`export`](Self::export) with tokens and scale steps in `ordering`.
+    pub fn export_ordered(
+        &self,
+        format: ExportFormat,
+        ordering: &OutputOrdering,
+    ) -> Result<String, ExportError> {
+        self.ordered(ordering).export(format)
+    }
+}
+
+impl ComponentStyles {
+    /// Rewrite the component map to `ordering`, and each component's state
+    /// and breakpoint overrides to their cascade order. Returns whether
+    /// anything moved.
+    pub fn normalize_order(&mut self, ordering: &OutputOrdering) -> bool {
+        if ordering.is_default() {
+            return false;
+        }
+        let mut changed = ordering.sort(&mut self.styles, "");
+        for style in self.styles.values_mut() {
+            changed |= sort_by_rank(&mut style.states, |s| rank_in(StateVariant::all(), s));
+            changed |= sort_by_rank(&mut style.breakpoints, |b| rank_in(Breakpoint::all(), b));
+        }
+        changed
+    }
+
+    /// The styles in `ordering`, borrowed when that is insertion order.
+    pub fn ordered(&self, ordering: &OutputOrdering) -> Cow<'_, ComponentStyles> {
+        if ordering.is_default() {
+            return Cow::Borrowed(self);
+        }
+        let mut styles = self.clone();
+        styles.normalize_order(ordering);
+        Cow::Owned(styles)
+    }
+
+    /// [`generate_css`](Self::generate_css) with components in `ordering`.
+    pub fn generate_css_ordered(&self, ordering: &OutputOrdering) -> String {
+        self.ordered(ordering).generate_css()
+    }
+}
+
+fn rank_in<K: PartialEq>(all: &[K], key: &K) -> usize {
+    all.iter().position(|k| k == key).unwrap_or(usize::MAX)
+}
+
+/// Stable sort of `map` by `rank` of each key. Returns whether the order
+/// changed.
+fn sort_by_rank<K, V>(map: &mut IndexMap<K, V>, rank: impl Fn(&K) -> usize) -> bool {
+    let sorted = map
+        .keys()
+        .zip(map.keys().skip(1))
+        .all(|(a, b)| rank(a) <= rank(b));
+    map.sort_by(|a, _, b, _| rank(a).cmp(&rank(b)));
+    !sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{
+        ComponentStyle, CssCache, CssDesigner, DarkModeStrategy, StyleProperties,
+    };
+    use crate::history::HistoryScope;
+    use crate::store::StudioStore;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn scale(steps: &[(&str, &str)]) -> TokenValue {
+        TokenValue::Scale(
+            steps
+                .iter()
+                .map(|(s, v)| (s.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    fn adaptive(light: &str, dark: &str) -> TokenValue {
+        TokenValue::Adaptive {
+            light: light.to_string(),
+            dark: dark.to_string(),
+        }
+    }
+
+    /// The same tokens, built in one order and then edited into the other.
+    fn equal_sets() -> (DesignTokens, DesignTokens) {
+        let mut a = DesignTokens::default();
+        a.colors.insert("primary".into(), simple("#3b82f6"));
+        a.colors
+            .insert("surface".into(), adaptive("#fff", "#0f172a"));
+        a.colors.insert(
+            "blue".into(),
+            scale(&[("50", "#eff6ff"), ("100", "#dbeafe")]),
+        );
+        a.spacing.insert("sm".into(), simple("0.5rem"));
+        a.spacing.insert("md".into(), simple("1rem"));
+        a.spacing.insert("space-10".into(), simple("2.5rem"));
+        a.spacing.insert("space-2".into(), simple("0.5rem"));
+        a.shadows
+            .insert("card".into(), adaptive("0 1px 2px #0002", "none"));
+
+        let mut b = DesignTokens::default();
+        b.shadows
+            .insert("card".into(), adaptive("0 1px 2px #0002", "none"));
+        b.spacing.insert("space-2".into(), simple("0.5rem"));
+        b.spacing.insert("md".into(), simple("1rem"));
+        b.colors.insert(
+            "blue".into(),
+            scale(&[("100", "#dbeafe"), ("50", "#eff6ff")]),
+        );
+        b.colors
+            .insert("surface".into(), adaptive("#fff", "#0f172a"));
+        b.colors.insert("primary".into(), simple("#000"));
+        b.spacing.insert("space-10".into(), simple("2.5rem"));
+        b.spacing.insert("sm".into(), simple("0.5rem"));
+        // Re-adding moves a token to the end under insertion order.
+        b.colors.shift_remove("primary");
+        b.colors.insert("primary".into(), simple("#3b82f6"));
+        (a, b)
+    }
+
+    #[test]
+    fn test_alphabetical_output_is_byte_identical() {
+        let (a, b) = equal_sets();
+        let css = |tokens: &DesignTokens, ordering: OutputOrdering| {
+            let mut designer = CssDesigner::new();
+            designer.load_tokens(tokens.clone());
+            designer.generate_css_ordered(&ordering)
+        };
+        assert_ne!(
+            css(&a, OutputOrdering::InsertionOrder),
+            css(&b, OutputOrdering::InsertionOrder)
+        );
+        let sorted = css(&a, OutputOrdering::Alphabetical);
+        assert_eq!(sorted, css(&b, OutputOrdering::Alphabetical));
+        assert!(sorted.starts_with(
+            ":root {\n  --color-blue-50: #eff6ff;\n  --color-blue-100: #dbeafe;\n  \
+             --color-primary: #3b82f6;\n  --color-surface: #fff;\n  --spacing-md: 1rem;\n  \
+             --spacing-sm: 0.5rem;\n  --spacing-space-2: 0.5rem;\n  --spacing-space-10: 2.5rem;\n"
+        ));
+
+        let ordering = OutputOrdering::Alphabetical;
+        for format in [
+            ExportFormat::CssVariables,
+            ExportFormat::Json,
+            ExportFormat::Scss,
+            ExportFormat::Tailwind,
+            ExportFormat::FigmaTokens,
+            ExportFormat::StyleDictionary,
+        ] {
+            assert_eq!(
+                a.export_ordered(format, &ordering).unwrap(),
+                b.export_ordered(format, &ordering).unwrap(),
+                "{:?}",
+                format
+            );
+        }
+
+        let (mut a, mut b) = (a, b);
+        assert!(b.normalize_order(&ordering));
+        a.normalize_order(&ordering);
+        assert!(!b.normalize_order(&ordering));
+        assert_eq!(a.to_json().unwrap(), b.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_pinned_order() {
+        let (tokens, _) = equal_sets();
+        let ordering = OutputOrdering::Pinned(vec![
+            "colors.surface".into(),
+            "colors.blue.100".into(),
+            "spacing.space-10".into(),
+        ]);
+        // JSON exports write the keys in the same order.
+        for format in [
+            ExportFormat::Tailwind,
+            ExportFormat::FigmaTokens,
+            ExportFormat::StyleDictionary,
+        ] {
+            let out = tokens.export_ordered(format, &ordering).unwrap();
+            let at = |key: &str| out.find(&format!("\"{}\"", key)).unwrap();
+            assert!(at("blue") < at("primary"), "{:?}", format);
+            assert!(at("space-10") < at("md"), "{:?}", format);
+        }
+
+        let tokens = tokens.ordered(&ordering);
+        assert_eq!(
+            tokens.colors.keys().collect::<Vec<_>>(),
+            ["surface", "blue", "primary"]
+        );
+        let TokenValue::Scale(steps) = &tokens.colors["blue"] else {
+            panic!("not a scale");
+        };
+        assert_eq!(steps.keys().collect::<Vec<_>>(), ["100", "50"]);
+        assert_eq!(
+            tokens.spacing.keys().collect::<Vec<_>>(),
+            ["space-10", "md", "sm", "space-2"]
+        );
+    }
+
+    #[test]
+    fn test_component_css_order() {
+        let style = |color: &str| {
+            let mut style = ComponentStyle::default();
+            style.base.set("color", Some(color.to_string()));
+            let mut hover = StyleProperties::default();
+            hover.set("opacity", Some("0.9".to_string()));
+            let mut disabled = StyleProperties::default();
+            disabled.set("opacity", Some("0.5".to_string()));
+            style.states.insert(StateVariant::Disabled, disabled);
+            style.states.insert(StateVariant::Hover, hover);
+            style
+        };
+        let mut a = ComponentStyles::new();
+        a.set("card".into(), style("black"));
+        a.set("button".into(), style("blue"));
+        let mut b = ComponentStyles::new();
+        b.set("button".into(), style("blue"));
+        b.set("card".into(), style("black"));
+
+        let ordering = OutputOrdering::Alphabetical;
+        let css = a.generate_css_ordered(&ordering);
+        assert_eq!(css, b.generate_css_ordered(&ordering));
+        assert!(css.starts_with(".button {"));
+        assert!(css.find(".button:hover").unwrap() < css.find(".button:disabled").unwrap());
+        assert_eq!(
+            a.generate_css_ordered(&OutputOrdering::InsertionOrder),
+            a.generate_css()
+        );
+    }
+
+    #[test]
+    fn test_store_normalize_order_undoes() {
+        let (_, tokens) = equal_sets();
+        let mut store = StudioStore::new();
+        store.set_tokens(tokens);
+        store.output_ordering = OutputOrdering::Alphabetical;
+        let names = |store: &StudioStore| store.tokens.colors.keys().cloned().collect::<Vec<_>>();
+
+        assert!(store.normalize_order());
+        assert_eq!(names(&store), ["blue", "primary", "surface"]);
+        assert!(!store.normalize_order());
+        assert!(store.undo_scope(HistoryScope::CssDesigner));
+        assert_eq!(names(&store), ["blue", "surface", "primary"]);
+    }
+
+    #[test]
+    fn test_store_exports_follow_the_ordering() {
+        let (a, b) = equal_sets();
+        let mut store = StudioStore::new();
+        store.set_tokens(a.clone());
+        let mut styles = ComponentStyles::new();
+        let mut card = ComponentStyle::default();
+        card.base.color = Some("var(--color-primary)".to_string());
+        styles.set("card".into(), card.clone());
+        styles.set("button".into(), card);
+        store.component_styles = styles.clone();
+        store.output_ordering = OutputOrdering::Alphabetical;
+
+        // The second pass is served from the cache.
+        let mut cache = CssCache::new();
+        for _ in 0..2 {
+            let css = store.component_css(&mut cache);
+            assert_eq!(css, styles.generate_css_ordered(&store.output_ordering));
+            assert!(css.starts_with(".button {"));
+        }
+        assert_eq!(cache.stats().hits, 2);
+        let vars = store.export_tokens(ExportFormat::CssVariables).unwrap();
+        assert_eq!(
+            vars,
+            b.export_ordered(ExportFormat::CssVariables, &store.output_ordering)
+                .unwrap()
+        );
+        assert_ne!(vars, a.export(ExportFormat::CssVariables).unwrap());
+
+        let themes = store.generate_all_css(&DarkModeStrategy::default());
+        assert!(themes.find("--color-blue-50").unwrap() < themes.find("--color-primary").unwrap());
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["space-10", "space-2", "b", "A", "space-02", "50", "100"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            ["50", "100", "A", "b", "space-02", "space-2", "space-10"]
+        );
+    }
+}
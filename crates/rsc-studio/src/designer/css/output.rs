@@ -0,0 +1,538 @@
+//! Post-processing for generated CSS.
+//!
+//! Generated CSS is parsed into a flat rule tree, optionally rewritten and
+//! printed again. Merged media queries are emitted where the last block
+//! with the same query was, so every moved rule only moves later and still
+//! follows the base rules it overrides.
+
+use std::collections::HashMap;
+
+use super::{ComponentStyles, CssDesigner};
+
+/// How generated CSS is post-processed. The default leaves it untouched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CssOutputOptions {
+    /// Strip comments and whitespace.
+    pub minify: bool,
+    /// Combine `@media` blocks with the same query into one.
+    pub merge_media_queries: bool,
+    /// Sort declarations by property name, for stable diffs.
+    pub sort_properties: bool,
+}
+
+impl CssOutputOptions {
+    /// Every pass enabled.
+    pub fn compact() -> Self {
+        Self {
+            minify: true,
+            merge_media_queries: true,
+            sort_properties: true,
+        }
+    }
+
+    pub fn with_minify(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    pub fn with_merge_media_queries(mut self, merge: bool) -> Self {
+        self.merge_media_queries = merge;
+        self
+    }
+
+    pub fn with_sort_properties(mut self, sort: bool) -> Self {
+        self.sort_properties = sort;
+        self
+    }
+}
+
+/// A node of parsed CSS.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssNode {
+    /// Comment text without the delimiters.
+    Comment(String),
+    /// At-rule without a block, e.g. `@import url(a.css)`.
+    Statement(String),
+    /// Style rule.
+    Rule {
+        selector: String,
+        declarations: Vec<(String, String)>,
+    },
+    /// At-rule with nested rules, e.g. `@media (min-width: 640px)`.
+    Block {
+        prelude: String,
+        children: Vec<CssNode>,
+    },
+}
+
+/// Collapse whitespace runs outside quotes to one space.
+fn collapse(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut quote = None;
+    for c in s.trim().chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                out.push(c);
+            }
+            None if c.is_whitespace() => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            None => {
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+struct Parser<'a> {
+    css: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.css[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Read up to (not including) the first of `stops` outside quotes,
+    /// parentheses and comments.
+    fn read_until(&mut self, stops: &[char]) -> String {
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut quote = None;
+        while let Some(c) = self.peek() {
+            if let Some(q) = quote {
+                if c == '\\' {
+                    self.pos += 1;
+                } else if c == q {
+                    quote = None;
+                }
+            } else if self.css[self.pos..].starts_with("/*") {
+                let end = self.css[self.pos + 2..]
+                    .find("*/")
+                    .map_or(self.css.len(), |i| self.pos + 2 + i + 2);
+                self.pos = end;
+                continue;
+            } else {
+                match c {
+                    '"' | '\'' => quote = Some(c),
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    _ if depth == 0 && stops.contains(&c) => break,
+                    _ => {}
+                }
+            }
+            self.pos += c.len_utf8();
+        }
+        self.css[start..self.pos].to_string()
+    }
+
+    /// Nodes until a closing brace or the end.
+    fn nodes(&mut self) -> Vec<CssNode> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.css[self.pos..].starts_with("/*") {
+                let body = &self.css[self.pos + 2..];
+                let len = body.find("*/").unwrap_or(body.len());
+                nodes.push(CssNode::Comment(body[..len].trim().to_string()));
+                self.pos = (self.pos + 2 + len + 2).min(self.css.len());
+                continue;
+            }
+            match self.peek() {
+                None => break,
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {}
+            }
+            let prelude = collapse(&strip_comments(&self.read_until(&['{', ';', '}'])));
+            match self.peek() {
+                Some('{') => {
+                    self.pos += 1;
+                    if prelude.starts_with('@') {
+                        let children = self.nodes();
+                        nodes.push(CssNode::Block { prelude, children });
+                    } else {
+                        let declarations = self.declarations();
+                        nodes.push(CssNode::Rule {
+                            selector: prelude,
+                            declarations,
+                        });
+                    }
+                }
+                Some(';') => {
+                    self.pos += 1;
+                    if !prelude.is_empty() {
+                        nodes.push(CssNode::Statement(prelude));
+                    }
+                }
+                _ => {}
+            }
+        }
+        nodes
+    }
+
+    /// Declarations up to and including the closing brace.
+    fn declarations(&mut self) -> Vec<(String, String)> {
+        let mut declarations = Vec::new();
+        loop {
+            let text = strip_comments(&self.read_until(&[';', '}']));
+            if let Some((property, value)) = text.split_once(':') {
+                let property = property.trim();
+                if !property.is_empty() {
+                    declarations.push((property.to_string(), collapse(value)));
+                }
+            }
+            match self.peek() {
+                Some(';') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => break,
+            }
+        }
+        declarations
+    }
+}
+
+fn strip_comments(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse CSS into nodes. Unbalanced input is read as far as it goes.
+pub fn parse_css(css: &str) -> Vec<CssNode> {
+    let mut parser = Parser { css, pos: 0 };
+    let mut nodes = Vec::new();
+    while parser.pos < css.len() {
+        // A stray closing brace ends `nodes()` early; keep going after it.
+        nodes.extend(parser.nodes());
+    }
+    nodes
+}
+
+/// Combine top-level `@media` blocks that share a query, dropping rules
+/// repeated verbatim inside the merged block (the last copy is kept).
+fn merge_media(nodes: Vec<CssNode>) -> Vec<CssNode> {
+    let key = |node: &CssNode| match node {
+        CssNode::Block { prelude, .. } if prelude.starts_with("@media") => {
+            Some(tighten_prelude(prelude))
+        }
+        _ => None,
+    };
+    let mut last: HashMap<String, usize> = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if let Some(key) = key(node) {
+            last.insert(key, i);
+        }
+    }
+
+    // Query text as first written, and the rules gathered so far.
+    let mut pending: HashMap<String, (String, Vec<CssNode>)> = HashMap::new();
+    let mut out = Vec::new();
+    for (i, node) in nodes.into_iter().enumerate() {
+        let Some(query) = key(&node) else {
+            out.push(node);
+            continue;
+        };
+        let CssNode::Block { prelude, children } = node else {
+            unreachable!("media key implies a block");
+        };
+        let merged = pending
+            .entry(query.clone())
+            .or_insert((prelude, Vec::new()));
+        merged.1.extend(children);
+        if last[&query] == i {
+            let (prelude, mut children) = pending.remove(&query).unwrap_or_default();
+            let mut kept: Vec<CssNode> = Vec::with_capacity(children.len());
+            while let Some(child) = children.pop() {
+                if !matches!(child, CssNode::Rule { .. }) || !kept.contains(&child) {
+                    kept.push(child);
+                }
+            }
+            kept.reverse();
+            out.push(CssNode::Block {
+                prelude,
+                children: kept,
+            });
+        }
+    }
+    out
+}
+
+fn sort_declarations(nodes: &mut [CssNode]) {
+    for node in nodes {
+        match node {
+            CssNode::Rule { declarations, .. } => {
+                declarations.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            CssNode::Block { children, .. } => sort_declarations(children),
+            CssNode::Comment(_) | CssNode::Statement(_) => {}
+        }
+    }
+}
+
+/// Minified selector: no spaces around combinators or commas.
+fn tighten_selector(selector: &str) -> String {
+    let mut out = selector.to_string();
+    for (from, to) in [(", ", ","), (" > ", ">"), (" + ", "+"), (" ~ ", "~")] {
+        out = out.replace(from, to);
+    }
+    out
+}
+
+/// Minified at-rule prelude, e.g. `@media (min-width:640px)`.
+fn tighten_prelude(prelude: &str) -> String {
+    prelude.replace(": ", ":").replace(", ", ",")
+}
+
+fn write_minified(nodes: &[CssNode], out: &mut String) {
+    for node in nodes {
+        match node {
+            CssNode::Comment(_) => {}
+            CssNode::Statement(text) => {
+                out.push_str(text);
+                out.push(';');
+            }
+            CssNode::Rule {
+                selector,
+                declarations,
+            } => {
+                out.push_str(&tighten_selector(selector));
+                out.push('{');
+                let body: Vec<String> = declarations
+                    .iter()
+                    .map(|(property, value)| format!("{}:{}", property, value))
+                    .collect();
+                out.push_str(&body.join(";"));
+                out.push('}');
+            }
+            CssNode::Block { prelude, children } => {
+                out.push_str(&tighten_prelude(prelude));
+                out.push('{');
+                write_minified(children, out);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn write_pretty(nodes: &[CssNode], indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    for (i, node) in nodes.iter().enumerate() {
+        if indent == 0 && i > 0 {
+            out.push('\n');
+        }
+        match node {
+            CssNode::Comment(text) => out.push_str(&format!("{}/* {} */\n", pad, text)),
+            CssNode::Statement(text) => out.push_str(&format!("{}{};\n", pad, text)),
+            CssNode::Rule {
+                selector,
+                declarations,
+            } => {
+                out.push_str(&format!("{}{} {{\n", pad, selector));
+                for (property, value) in declarations {
+                    out.push_str(&format!("{}  {}: {};\n", pad, property, value));
+                }
+                out.push_str(&format!("{}}}\n", pad));
+            }
+            CssNode::Block { prelude, children } => {
+                out.push_str(&format!("{}{} {{\n", pad, prelude));
+                write_pretty(children, indent + 2, out);
+                out.push_str(&format!("{}}}\n", pad));
+            }
+        }
+    }
+}
+
+/// Apply `options` to CSS text. Default options return it unchanged.
+pub fn process_css(css: &str, options: &CssOutputOptions) -> String {
+    if *options == CssOutputOptions::default() {
+        return css.to_string();
+    }
+    let mut nodes = parse_css(css);
+    if options.merge_media_queries {
+        nodes = merge_media(nodes);
+    }
+    if options.sort_properties {
+        sort_declarations(&mut nodes);
+    }
+    let mut out = String::with_capacity(css.len());
+    if options.minify {
+        write_minified(&nodes, &mut out);
+    } else {
+        write_pretty(&nodes, 0, &mut out);
+    }
+    out
+}
+
+impl CssDesigner {
+    /// Token CSS with output post-processing applied.
+    pub fn generate_css_with(&self, options: &CssOutputOptions) -> String {
+        process_css(&self.generate_css(), options)
+    }
+}
+
+impl ComponentStyles {
+    /// Component CSS with output post-processing applied.
+    pub fn generate_css_with(&self, options: &CssOutputOptions) -> String {
+        process_css(&self.generate_css(), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{Breakpoint, ComponentStyle, StyleProperties, TokenValue};
+
+    fn props(pairs: &[(&str, &str)]) -> StyleProperties {
+        let mut props = StyleProperties::default();
+        for (name, value) in pairs {
+            props.set(name, Some(value.to_string()));
+        }
+        props
+    }
+
+    fn styles() -> ComponentStyles {
+        let mut styles = ComponentStyles::new();
+        for name in ["button", "card", "badge"] {
+            let mut style = ComponentStyle {
+                base: props(&[("padding", "4px"), ("color", "var(--color-text)")]),
+                ..Default::default()
+            };
+            style
+                .breakpoints
+                .insert(Breakpoint::Md, props(&[("padding", "8px"), ("gap", "2px")]));
+            style
+                .breakpoints
+                .insert(Breakpoint::Lg, props(&[("padding", "12px")]));
+            styles.set(name.to_string(), style);
+        }
+        styles
+    }
+
+    /// (media query, selector, declarations).
+    type FlatRule = (String, String, Vec<(String, String)>);
+
+    fn rules(css: &str) -> Vec<FlatRule> {
+        fn walk(nodes: &[CssNode], media: &str, out: &mut Vec<FlatRule>) {
+            for node in nodes {
+                match node {
+                    CssNode::Rule {
+                        selector,
+                        declarations,
+                    } => {
+                        let mut declarations = declarations.clone();
+                        declarations.sort();
+                        out.push((media.to_string(), selector.clone(), declarations));
+                    }
+                    CssNode::Block { prelude, children } => {
+                        walk(children, &tighten_prelude(prelude), out)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(&parse_css(css), "", &mut out);
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_merge_media_queries_keeps_selectors() {
+        let styles = styles();
+        let raw = styles.generate_css();
+        let merged =
+            styles.generate_css_with(&CssOutputOptions::default().with_merge_media_queries(true));
+
+        assert_eq!(raw.matches("@media").count(), 6);
+        assert_eq!(merged.matches("@media").count(), 2);
+        assert_eq!(rules(&merged), rules(&raw));
+        assert!(merged.len() < raw.len());
+        assert!(merged.ends_with(
+            "@media (min-width: 1024px) {\n  .button {\n    padding: 12px;\n  }\n  \
+             .card {\n    padding: 12px;\n  }\n  .badge {\n    padding: 12px;\n  }\n}\n"
+        ));
+        // Default options leave the output alone.
+        assert_eq!(styles.generate_css_with(&CssOutputOptions::default()), raw);
+    }
+
+    #[test]
+    fn test_minify_and_sort() {
+        let styles = styles();
+        let raw = styles.generate_css();
+        let compact = styles.generate_css_with(&CssOutputOptions::compact());
+        let merged =
+            styles.generate_css_with(&CssOutputOptions::default().with_merge_media_queries(true));
+        assert!(compact.len() < merged.len() && merged.len() < raw.len());
+        assert!(!compact.contains('\n'));
+        assert!(compact.starts_with(".button{color:var(--color-text);padding:4px}"));
+        assert!(compact.contains("@media (min-width:768px){.button{gap:2px;padding:8px}"));
+        assert_eq!(rules(&compact), rules(&raw));
+
+        let mut designer = CssDesigner::new();
+        designer.tokens.colors.insert(
+            "text".to_string(),
+            TokenValue::Adaptive {
+                light: "#111".to_string(),
+                dark: "#eee".to_string(),
+            },
+        );
+        assert_eq!(
+            designer.generate_css_with(&CssOutputOptions::default().with_minify(true)),
+            ":root{--color-text:#111}\
+             @media (prefers-color-scheme:dark){:root{--color-text:#eee}}"
+        );
+    }
+
+    #[test]
+    fn test_parse_comments_and_strings() {
+        let css = "/* header */\n@import url(\"a;b.css\");\n\
+                   .icon::after { content: \"}\" ; /* note */ margin : 0  auto }\n";
+        assert_eq!(
+            parse_css(css),
+            [
+                CssNode::Comment("header".to_string()),
+                CssNode::Statement("@import url(\"a;b.css\")".to_string()),
+                CssNode::Rule {
+                    selector: ".icon::after".to_string(),
+                    declarations: vec![
+                        ("content".to_string(), "\"}\"".to_string()),
+                        ("margin".to_string(), "0 auto".to_string()),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            process_css(css, &CssOutputOptions::default().with_minify(true)),
+            "@import url(\"a;b.css\");.icon::after{content:\"}\";margin:0 auto}"
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Reading existing CSS back into style properties.
+//!
+//! Declarations map onto [`StyleProperties`] fields where a field exists
+//! and land in [`StyleProperties::custom`] otherwise, so parsing and then
+//! emitting loses nothing. Unlike [`super::output::parse_css`], malformed
+//! input is an error rather than skipped.
+
+use thiserror::Error;
+
+use super::{Breakpoint, ComponentStyle, StateVariant, StyleProperties};
+
+/// CSS parse error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CssParseError {
+    /// A declaration without a `:`.
+    #[error("Expected 'property: value', found '{0}'")]
+    MissingColon(String),
+
+    /// A declaration without a value.
+    #[error("Property '{0}' has no value")]
+    EmptyValue(String),
+
+    /// A comment, string or block that never closes.
+    #[error("Unterminated {0}")]
+    Unterminated(&'static str),
+
+    /// A brace where a declaration was expected.
+    #[error("Unexpected '{0}'")]
+    UnexpectedBrace(char),
+
+    /// A rule for the component that a style can't express.
+    #[error("Selector '{0}' can't be represented as a component style")]
+    UnsupportedSelector(String),
+
+    /// An at-rule other than a known breakpoint's `min-width` query.
+    #[error("At-rule '{0}' doesn't match a breakpoint")]
+    UnsupportedAtRule(String),
+}
+
+/// `css` with comments removed, quotes respected.
+fn strip_comments(css: &str) -> Result<String, CssParseError> {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.char_indices().peekable();
+    let mut quote = None;
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                if let Some((_, escaped)) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        if c == '/' && css[i..].starts_with("/*") {
+            let end = css[i + 2..]
+                .find("*/")
+                .ok_or(CssParseError::Unterminated("comment"))?;
+            let resume = i + 2 + end + 2;
+            while chars.peek().is_some_and(|(j, _)| *j < resume) {
+                chars.next();
+            }
+            // Keep tokens on either side of the comment apart.
+            out.push(' ');
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            quote = Some(c);
+        }
+        out.push(c);
+    }
+    if quote.is_some() {
+        return Err(CssParseError::Unterminated("string"));
+    }
+    Ok(out)
+}
+
+/// Split on `separator` outside quotes and parentheses.
+fn split_top_level(css: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in css.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if c == separator && depth == 0 => {
+                parts.push(&css[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&css[start..]);
+    parts
+}
+
+/// Whitespace runs outside quotes collapsed to one space.
+fn collapse_whitespace(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut quote = None;
+    for c in value.trim().chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                out.push(c);
+            }
+            None if c.is_whitespace() => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            None => {
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+impl StyleProperties {
+    /// Parse the declarations of one rule body, e.g.
+    /// `color: red; padding: 4px !important;`.
+    pub fn from_css_block(css: &str) -> Result<StyleProperties, CssParseError> {
+        let css = strip_comments(css)?;
+        let mut props = StyleProperties::default();
+        for declaration in split_top_level(&css, ';') {
+            let declaration = declaration.trim();
+            if declaration.is_empty() {
+                continue;
+            }
+            if let Some(brace) = ['{', '}']
+                .into_iter()
+                .find(|brace| split_top_level(declaration, *brace).len() > 1)
+            {
+                return Err(CssParseError::UnexpectedBrace(brace));
+            }
+            let Some((name, value)) = declaration.split_once(':') else {
+                return Err(CssParseError::MissingColon(declaration.to_string()));
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(CssParseError::MissingColon(declaration.to_string()));
+            }
+            // Custom properties are case-sensitive; everything else isn't.
+            let name = if name.starts_with("--") {
+                name.to_string()
+            } else {
+                name.to_ascii_lowercase()
+            };
+            let value = collapse_whitespace(value).replace("! important", "!important");
+            if value.is_empty() {
+                return Err(CssParseError::EmptyValue(name));
+            }
+            if Self::PROPERTY_NAMES.contains(&name.as_str()) {
+                props.set(&name, Some(value));
+            } else {
+                props.custom.insert(name, value);
+            }
+        }
+        Ok(props)
+    }
+
+    /// Properties of `self` with those of `later` applied on top.
+    fn apply(&mut self, later: StyleProperties) {
+        *self = later.merged_over(self);
+    }
+}
+
+/// Top-level `(prelude, body)` pairs of a stylesheet.
+fn blocks(css: &str) -> Result<Vec<(String, String)>, CssParseError> {
+    let mut blocks = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut prelude_start = 0;
+    let mut body_start = 0;
+    for (i, c) in css.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '{' => {
+                if depth == 0 {
+                    body_start = i + 1;
+                }
+                depth += 1;
+            }
+            '}' => {
+                if depth == 0 {
+                    return Err(CssParseError::UnexpectedBrace('}'));
+                }
+                depth -= 1;
+                if depth == 0 {
+                    let prelude = collapse_whitespace(&css[prelude_start..body_start - 1]);
+                    blocks.push((prelude, css[body_start..i].to_string()));
+                    prelude_start = i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return Err(CssParseError::Unterminated("block"));
+    }
+    if !css[prelude_start..].trim().is_empty() {
+        return Err(CssParseError::MissingColon(
+            css[prelude_start..].trim().to_string(),
+        ));
+    }
+    Ok(blocks)
+}
+
+/// Breakpoint for `@media (min-width: Npx)`.
+fn media_breakpoint(prelude: &str) -> Option<Breakpoint> {
+    let query = prelude.strip_prefix("@media")?.trim();
+    let width = query
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_once(':')
+        .filter(|(feature, _)| feature.trim() == "min-width")?
+        .1
+        .trim()
+        .strip_suffix("px")?;
+    let width: u32 = width.trim().parse().ok()?;
+    Breakpoint::all()
+        .iter()
+        .copied()
+        .find(|b| b.min_width() == Some(width))
+}
+
+/// State selected by `selector` on component `name`. `None` for rules on
+/// other components.
+fn selector_state(name: &str, selector: &str) -> Option<Result<StateVariant, CssParseError>> {
+    let class = format!(".{}", name);
+    let selects_component = |s: &str| {
+        s.trim().strip_prefix(&class).is_some_and(|rest| {
+            !rest.starts_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+    };
+    if !split_top_level(selector, ',')
+        .into_iter()
+        .any(selects_component)
+    {
+        return None;
+    }
+    let unsupported = || {
+        Some(Err(CssParseError::UnsupportedSelector(
+            selector.to_string(),
+        )))
+    };
+    let Some(suffix) = selector.strip_prefix(&class) else {
+        return unsupported();
+    };
+    match StateVariant::all()
+        .iter()
+        .find(|state| state.css_selector() == suffix)
+    {
+        Some(state) => Some(Ok(*state)),
+        None => unsupported(),
+    }
+}
+
+impl ComponentStyle {
+    /// Rebuild the style of component `name` from its rules: `.name` for
+    /// the base, `.name:hover` and friends for states, and `.name` inside
+    /// `@media (min-width: Npx)` for the breakpoint of that width. Rules
+    /// for other components are ignored; repeated rules cascade.
+    pub fn from_css_rules(name: &str, css: &str) -> Result<ComponentStyle, CssParseError> {
+        let css = strip_comments(css)?;
+        let mut style = ComponentStyle::default();
+        for (prelude, body) in blocks(&css)? {
+            if prelude.starts_with('@') {
+                let Some(breakpoint) = media_breakpoint(&prelude) else {
+                    return Err(CssParseError::UnsupportedAtRule(prelude));
+                };
+                for (selector, body) in blocks(&body)? {
+                    match selector_state(name, &selector) {
+                        None => continue,
+                        Some(Ok(StateVariant::Default)) => {}
+                        Some(_) => return Err(CssParseError::UnsupportedSelector(selector)),
+                    }
+                    let props = StyleProperties::from_css_block(&body)?;
+                    style
+                        .breakpoints
+                        .entry(breakpoint)
+                        .or_default()
+                        .apply(props);
+                }
+                continue;
+            }
+            let state = match selector_state(name, &prelude) {
+                None => continue,
+                Some(state) => state?,
+            };
+            let props = StyleProperties::from_css_block(&body)?;
+            match state {
+                StateVariant::Default => style.base.apply(props),
+                state => style.states.entry(state).or_default().apply(props),
+            }
+        }
+        Ok(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_css_block() {
+        let props = StyleProperties::from_css_block(
+            "\n  color : var(--color-text) ;\n  /* spacing */ padding: 4px  8px !important;\
+             \n  -webkit-line-clamp: 2;\n  --Local: 'a; b';\n  content: \"}\";\n  font-family: \"A B\", sans-serif;\n",
+        )
+        .unwrap();
+        assert_eq!(props.color.as_deref(), Some("var(--color-text)"));
+        assert_eq!(props.padding.as_deref(), Some("4px 8px !important"));
+        assert_eq!(props.font_family.as_deref(), Some("\"A B\", sans-serif"));
+        assert_eq!(props.custom["-webkit-line-clamp"], "2");
+        assert_eq!(props.custom["--Local"], "'a; b'");
+        assert_eq!(
+            props.to_css(),
+            "  padding: 4px 8px !important;\n  color: var(--color-text);\n  \
+             font-family: \"A B\", sans-serif;\n  -webkit-line-clamp: 2;\n  --Local: 'a; b';\n  \
+             content: \"}\";\n"
+        );
+        // Emitted CSS parses back to the same declarations.
+        let again = StyleProperties::from_css_block(&props.to_css()).unwrap();
+        assert_eq!(again.to_css(), props.to_css());
+    }
+
+    #[test]
+    fn test_from_css_block_errors() {
+        assert_eq!(
+            StyleProperties::from_css_block("color red;").unwrap_err(),
+            CssParseError::MissingColon("color red".to_string())
+        );
+        assert_eq!(
+            StyleProperties::from_css_block("color: ;").unwrap_err(),
+            CssParseError::EmptyValue("color".to_string())
+        );
+        assert_eq!(
+            StyleProperties::from_css_block("color: red; /* open").unwrap_err(),
+            CssParseError::Unterminated("comment")
+        );
+        assert_eq!(
+            StyleProperties::from_css_block("a { color: red }").unwrap_err(),
+            CssParseError::UnexpectedBrace('{')
+        );
+    }
+
+    #[test]
+    fn test_from_css_rules_round_trip() {
+        let css = "\
+/* legacy */
+.button { padding: 4px; color: black; cursor: pointer }
+.button-group { display: flex; }
+.button:hover { background: gray; }
+.button:focus { outline: 2px solid blue; }
+@media (min-width: 768px) {
+  .button { padding: 8px; }
+  .card { padding: 0; }
+}
+.button { color: navy; }
+";
+        let style = ComponentStyle::from_css_rules("button", css).unwrap();
+        assert_eq!(style.base.color.as_deref(), Some("navy"));
+        assert_eq!(style.base.padding.as_deref(), Some("4px"));
+        assert!(style.base.display.is_none());
+        assert_eq!(
+            style.states[&StateVariant::Focus].custom["outline"],
+            "2px solid blue"
+        );
+        assert_eq!(
+            style.breakpoints[&Breakpoint::Md].padding.as_deref(),
+            Some("8px")
+        );
+
+        let emitted = style.to_css("button");
+        let back = ComponentStyle::from_css_rules("button", &emitted).unwrap();
+        assert_eq!(back.to_css("button"), emitted);
+    }
+
+    #[test]
+    fn test_from_css_rules_unsupported() {
+        assert_eq!(
+            ComponentStyle::from_css_rules("button", ".button > span { color: red }").unwrap_err(),
+            CssParseError::UnsupportedSelector(".button > span".to_string())
+        );
+        assert_eq!(
+            ComponentStyle::from_css_rules("button", "@media print { .button { color: red } }")
+                .unwrap_err(),
+            CssParseError::UnsupportedAtRule("@media print".to_string())
+        );
+        assert_eq!(
+            ComponentStyle::from_css_rules(
+                "button",
+                "@media (min-width: 768px) { .button:hover { color: red } }"
+            )
+            .unwrap_err(),
+            CssParseError::UnsupportedSelector(".button:hover".to_string())
+        );
+        assert_eq!(
+            ComponentStyle::from_css_rules("button", ".button { color: red }").map(|_| ()),
+            Ok(())
+        );
+    }
+}
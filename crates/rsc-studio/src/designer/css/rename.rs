@@ -0,0 +1,293 @@
+//! Token renaming with reference rewriting.
+
+use thiserror::Error;
+
+use super::extract::var_calls;
+use super::{CssDesigner, DesignTokens, TokenValue, path_to_css_var};
+
+/// Token rename error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TokenRenameError {
+    /// No token exists at the old path.
+    #[error("Token '{0}' not found")]
+    NotFound(String),
+
+    /// A token with the new name already exists in the category.
+    #[error("Token '{0}' already exists")]
+    AlreadyExists(String),
+
+    /// The new name is empty or contains characters other than letters,
+    /// digits, `-` and `_`.
+    #[error("Invalid token name '{0}'")]
+    InvalidName(String),
+}
+
+/// Result of a token rename.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameReport {
+    pub old_path: String,
+    pub new_path: String,
+    /// Tokens whose values were rewritten.
+    pub dependents: Vec<String>,
+    /// Number of `var(...)` references rewritten.
+    pub references_updated: usize,
+}
+
+pub(super) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Point `var(from)` references at `to`, keeping fallbacks. Returns the
+/// rewritten value and the number of references changed.
+fn rewrite_refs(value: &str, from: &str, to: &str) -> (String, usize) {
+    let mut names: Vec<usize> = var_calls(value)
+        .into_iter()
+        .filter(|(_, _, name)| name == from)
+        .filter_map(|(start, end, _)| Some(start + 4 + value[start + 4..end].find(from)?))
+        .collect();
+    names.sort_unstable();
+
+    let mut out = value.to_string();
+    for start in names.iter().rev() {
+        out.replace_range(*start..start + from.len(), to);
+    }
+    (out, names.len())
+}
+
+impl DesignTokens {
+    /// Rename a token, keeping its position, and rewrite `var(...)`
+    /// references to it (including scale steps) in dependent tokens.
+    pub fn rename_token(
+        &mut self,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<RenameReport, TokenRenameError> {
+        if !is_valid_name(new_name) {
+            return Err(TokenRenameError::InvalidName(new_name.to_string()));
+        }
+        let not_found = || TokenRenameError::NotFound(old_path.to_string());
+
+        let (prefix, map) = self
+            .categories_mut()
+            .into_iter()
+            .find(|(prefix, _)| {
+                old_path
+                    .strip_prefix(*prefix)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .ok_or_else(not_found)?;
+        let old_name = &old_path[prefix.len() + 1..];
+        let new_path = format!("{}.{}", prefix, new_name);
+        let index = map.get_index_of(old_name).ok_or_else(not_found)?;
+        if new_name == old_name {
+            return Ok(RenameReport {
+                old_path: old_path.to_string(),
+                new_path,
+                dependents: Vec::new(),
+                references_updated: 0,
+            });
+        }
+        if map.contains_key(new_name) {
+            return Err(TokenRenameError::AlreadyExists(new_path));
+        }
+
+        let value = map.shift_remove(old_name).ok_or_else(not_found)?;
+        let steps: Vec<String> = match &value {
+            TokenValue::Scale(steps) => steps.keys().cloned().collect(),
+            _ => Vec::new(),
+        };
+        map.shift_insert(index, new_name.to_string(), value);
+        if let Some(index) = self.metadata.get_index_of(old_path)
+            && let Some((_, ts)) = self.metadata.shift_remove_index(index)
+        {
+            self.metadata.shift_insert(index, new_path.clone(), ts);
+        }
+        if let Some(docs) = self.docs.shift_remove(old_path) {
+            self.docs.insert(new_path.clone(), docs);
+        }
+
+        // Variable renames: the token itself, then each scale step.
+        let old_var = path_to_css_var(old_path).ok_or_else(not_found)?;
+        let new_var = path_to_css_var(&new_path).ok_or_else(not_found)?;
+        let mut renames = vec![(old_path.to_string(), old_var.clone(), new_var.clone())];
+        for step in &steps {
+            renames.push((
+                format!("{}-{}", old_path, step),
+                format!("{}-{}", old_var, step),
+                format!("{}-{}", new_var, step),
+            ));
+        }
+
+        let mut dependents: Vec<String> = Vec::new();
+        for (path, _, _) in &renames {
+            for dependent in self.find_dependents(path) {
+                if !dependents.contains(&dependent) {
+                    dependents.push(dependent);
+                }
+            }
+        }
+
+        let mut references_updated = 0;
+        for dependent in &dependents {
+            let Some(value) = self.token_mut(dependent) else {
+                continue;
+            };
+            let values: Vec<&mut String> = match value {
+                TokenValue::Simple(v) => vec![v],
+                TokenValue::Adaptive { light, dark } => vec![light, dark],
+                TokenValue::Scale(steps) => steps.values_mut().collect(),
+            };
+            for v in values {
+                for (_, from, to) in &renames {
+                    let (rewritten, count) = rewrite_refs(v, from, to);
+                    *v = rewritten;
+                    references_updated += count;
+                }
+            }
+        }
+
+        Ok(RenameReport {
+            old_path: old_path.to_string(),
+            new_path,
+            dependents,
+            references_updated,
+        })
+    }
+
+    fn token_mut(&mut self, path: &str) -> Option<&mut TokenValue> {
+        self.categories_mut().into_iter().find_map(|(prefix, map)| {
+            let name = path.strip_prefix(prefix)?.strip_prefix('.')?;
+            map.get_mut(name)
+        })
+    }
+}
+
+impl CssDesigner {
+    /// Rename a token and its references, keeping it selected if it was.
+    /// The rename undoes as one step.
+    pub fn rename_token(
+        &mut self,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<RenameReport, TokenRenameError> {
+        let before = self.tokens.clone();
+        let report = self.tokens.rename_token(old_path, new_name)?;
+        self.record(format!("Rename {}", old_path), &before);
+        if self.selected_token.as_deref() == Some(old_path) {
+            self.selected_token = Some(report.new_path.clone());
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn designer() -> CssDesigner {
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        let mut designer = CssDesigner::new();
+        let tokens = &mut designer.tokens;
+        tokens.colors.insert("white".to_string(), simple("#fff"));
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("#3b82f6"));
+        tokens.colors.insert(
+            "primary-hover".to_string(),
+            simple("color-mix(in srgb, var(--color-primary) 80%, var(--color-primary, black))"),
+        );
+        tokens.colors.insert(
+            "surface".to_string(),
+            TokenValue::Adaptive {
+                light: "var(--color-white)".to_string(),
+                dark: "var(--color-primary)".to_string(),
+            },
+        );
+        tokens.shadows.insert(
+            "focus".to_string(),
+            simple("0 0 0 2px var(--color-primary)"),
+        );
+        designer
+    }
+
+    #[test]
+    fn test_rename_rewrites_references() {
+        let mut designer = designer();
+        designer.selected_token = Some("colors.primary".to_string());
+
+        let report = designer.rename_token("colors.primary", "brand").unwrap();
+
+        assert_eq!(report.new_path, "colors.brand");
+        assert_eq!(report.references_updated, 4);
+        assert_eq!(
+            report.dependents,
+            ["colors.primary-hover", "colors.surface", "shadows.focus"]
+        );
+        let keys: Vec<&String> = designer.tokens.colors.keys().collect();
+        assert_eq!(keys, ["white", "brand", "primary-hover", "surface"]);
+        assert_eq!(
+            designer.get_token("colors.primary-hover"),
+            Some(&TokenValue::Simple(
+                "color-mix(in srgb, var(--color-brand) 80%, var(--color-brand, black))".to_string()
+            ))
+        );
+        assert_eq!(
+            designer.get_token("colors.surface"),
+            Some(&TokenValue::Adaptive {
+                light: "var(--color-white)".to_string(),
+                dark: "var(--color-brand)".to_string(),
+            })
+        );
+        assert_eq!(designer.selected_token.as_deref(), Some("colors.brand"));
+    }
+
+    #[test]
+    fn test_rename_scale_steps() {
+        let mut designer = designer();
+        let mut steps = IndexMap::new();
+        steps.insert("500".to_string(), "#3b82f6".to_string());
+        designer
+            .tokens
+            .colors
+            .insert("blue".to_string(), TokenValue::Scale(steps));
+        let mut accents = IndexMap::new();
+        accents.insert("light".to_string(), "var(--color-blue-500)".to_string());
+        designer
+            .tokens
+            .colors
+            .insert("accent".to_string(), TokenValue::Scale(accents));
+
+        let report = designer.rename_token("colors.blue", "ocean").unwrap();
+
+        assert_eq!(report.references_updated, 1);
+        match designer.get_token("colors.accent") {
+            Some(TokenValue::Scale(steps)) => {
+                assert_eq!(steps["light"], "var(--color-ocean-500)")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rename_errors() {
+        let mut designer = designer();
+
+        assert_eq!(
+            designer.rename_token("colors.primary", "white"),
+            Err(TokenRenameError::AlreadyExists("colors.white".to_string()))
+        );
+        assert_eq!(
+            designer.rename_token("colors.primary", "brand.main"),
+            Err(TokenRenameError::InvalidName("brand.main".to_string()))
+        );
+        assert_eq!(
+            designer.rename_token("colors.missing", "brand"),
+            Err(TokenRenameError::NotFound("colors.missing".to_string()))
+        );
+        assert!(designer.get_token("colors.primary").is_some());
+    }
+}
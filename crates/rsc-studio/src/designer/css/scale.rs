@@ -0,0 +1,203 @@
+//! Color scales generated from a single base color.
+//!
+//! Steps keep the base hue and saturation and take their lightness from a
+//! reference curve (close to Tailwind's palettes), shifted so that step 500
+//! is the base color. The shift fades out towards the ends of the scale,
+//! so every scale spans roughly the same range however light its base is.
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use super::color::Hsl;
+use super::contrast::parse_color;
+use super::{CssDesigner, TokenValue};
+
+/// Steps produced by [`CssDesigner::expand_to_scale`].
+pub const DEFAULT_SCALE_STEPS: &[u16] = &[50, 100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+/// Reference HSL lightness per step.
+const LIGHTNESS: &[(u16, f64)] = &[
+    (50, 0.97),
+    (100, 0.93),
+    (200, 0.87),
+    (300, 0.77),
+    (400, 0.68),
+    (500, 0.60),
+    (600, 0.53),
+    (700, 0.48),
+    (800, 0.40),
+    (900, 0.33),
+    (950, 0.21),
+];
+
+/// The base color couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("Can't parse color '{0}'")]
+pub struct ColorParseError(pub String);
+
+/// Scale expansion error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ScaleError {
+    /// No token exists at the path.
+    #[error("Token '{0}' not found")]
+    NotFound(String),
+
+    /// Only color tokens can become scales.
+    #[error("Token '{0}' is not a color")]
+    NotColor(String),
+
+    /// The token is already Adaptive or a Scale.
+    #[error("Token '{0}' is not a simple value")]
+    NotSimple(String),
+
+    #[error(transparent)]
+    Parse(#[from] ColorParseError),
+}
+
+/// Reference lightness at `step`, interpolated between known steps.
+fn reference_lightness(step: u16) -> f64 {
+    let (first, last) = (LIGHTNESS[0], LIGHTNESS[LIGHTNESS.len() - 1]);
+    if step <= first.0 {
+        return first.1;
+    }
+    LIGHTNESS
+        .windows(2)
+        .find(|w| step <= w[1].0)
+        .map_or(last.1, |w| {
+            let ((s0, l0), (s1, l1)) = (w[0], w[1]);
+            l0 + (l1 - l0) * (step - s0) as f64 / (s1 - s0) as f64
+        })
+}
+
+/// Generate a scale from `base`, one `#rrggbb` value per step. Step 500
+/// is `base` unchanged.
+pub fn generate_scale(
+    base: &str,
+    steps: &[u16],
+) -> Result<IndexMap<String, String>, ColorParseError> {
+    let rgb = parse_color(base).ok_or_else(|| ColorParseError(base.to_string()))?;
+    let hsl = Hsl::from(rgb);
+    let offset = hsl.l - reference_lightness(500);
+
+    Ok(steps
+        .iter()
+        .map(|&step| {
+            let value = if step == 500 {
+                base.to_string()
+            } else {
+                let fade = 1.0 - (f64::from(step.abs_diff(500)) / 450.0).min(1.0);
+                let l = (reference_lightness(step) + offset * fade).clamp(0.0, 1.0);
+                Hsl { l, ..hsl }.to_rgb().to_hex()
+            };
+            (step.to_string(), value)
+        })
+        .collect())
+}
+
+impl CssDesigner {
+    /// Replace a simple color token with a generated scale, keeping its
+    /// value as step 500 and its position in the category. Undoable.
+    pub fn expand_to_scale(&mut self, token_path: &str) -> Result<(), ScaleError> {
+        let name = token_path
+            .strip_prefix("colors.")
+            .ok_or_else(|| ScaleError::NotColor(token_path.to_string()))?;
+        let value = match self.tokens.colors.get(name) {
+            Some(TokenValue::Simple(value)) => value,
+            Some(_) => return Err(ScaleError::NotSimple(token_path.to_string())),
+            None => return Err(ScaleError::NotFound(token_path.to_string())),
+        };
+        let scale = generate_scale(value, DEFAULT_SCALE_STEPS)?;
+
+        let before = self.tokens.clone();
+        self.tokens.colors[name] = TokenValue::Scale(scale);
+        self.record(format!("Expand {} to scale", token_path), &before);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scale(base: &str) -> Vec<String> {
+        generate_scale(base, DEFAULT_SCALE_STEPS)
+            .unwrap()
+            .into_values()
+            .collect()
+    }
+
+    #[test]
+    fn test_scale_snapshots() {
+        assert_eq!(
+            scale("#3b82f6"),
+            [
+                "#f0f6fe", "#dde9fd", "#bfd6fc", "#8eb7fa", "#629bf8", "#3b82f6", "#196cf4",
+                "#0b5fea", "#094fc3", "#0742a1",
+            ]
+        );
+        assert_eq!(
+            scale("#ef4444"),
+            [
+                "#fef1f1", "#fcdede", "#fac2c2", "#f69393", "#f26969", "#ef4444", "#ec2323",
+                "#e21313", "#bc1010", "#9b0d0d",
+            ]
+        );
+        // A light base still darkens to a usable 900.
+        assert_eq!(
+            scale("#fde047"),
+            [
+                "#fffcf0", "#fff9de", "#fef5c3", "#feed95", "#fde66b", "#fde047", "#fdd920",
+                "#fcd403", "#d0af02", "#a88e02",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_scale_inputs() {
+        let steps = generate_scale("rgb(128, 128, 128)", &[50, 500, 950]).unwrap();
+        assert_eq!(steps.keys().collect::<Vec<_>>(), ["50", "500", "950"]);
+        assert_eq!(steps["500"], "rgb(128, 128, 128)");
+        assert_eq!(steps["50"], "#f7f7f7");
+        assert_eq!(
+            generate_scale("var(--color-blue)", &[500]),
+            Err(ColorParseError("var(--color-blue)".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_expand_to_scale() {
+        let mut designer = CssDesigner::new();
+        designer.set_token("colors.text", TokenValue::Simple("#111".to_string()));
+        designer.set_token("colors.primary", TokenValue::Simple("#3b82f6".to_string()));
+        designer.set_token("colors.muted", TokenValue::Simple("#888".to_string()));
+        designer.set_token("spacing.md", TokenValue::Simple("1rem".to_string()));
+
+        designer.expand_to_scale("colors.primary").unwrap();
+        let keys: Vec<&str> = designer.tokens.colors.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["text", "primary", "muted"]);
+        let TokenValue::Scale(steps) = &designer.tokens.colors["primary"] else {
+            panic!("expected a scale");
+        };
+        assert_eq!(steps.len(), 10);
+        assert_eq!(steps["500"], "#3b82f6");
+
+        assert_eq!(
+            designer.expand_to_scale("colors.primary"),
+            Err(ScaleError::NotSimple("colors.primary".to_string()))
+        );
+        assert_eq!(
+            designer.expand_to_scale("spacing.md"),
+            Err(ScaleError::NotColor("spacing.md".to_string()))
+        );
+        assert_eq!(
+            designer.expand_to_scale("colors.accent"),
+            Err(ScaleError::NotFound("colors.accent".to_string()))
+        );
+
+        assert!(designer.undo());
+        assert_eq!(
+            designer.tokens.colors["primary"],
+            TokenValue::Simple("#3b82f6".to_string())
+        );
+    }
+}
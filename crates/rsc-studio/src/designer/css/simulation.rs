@@ -0,0 +1,216 @@
+//! Color vision deficiency simulation for previews.
+//!
+//! The chosen simulation is a UI setting: it changes the token CSS injected
+//! into the preview and the colors the canvas is drawn with, never the
+//! tokens themselves or anything exported. [`DesignTokens::degraded_contrast`]
+//! lists contrast pairs that pass but fail once simulated.
+
+use rsc_flow::FlowTheme;
+
+use super::color::ColorBlindness;
+use super::contrast::{ContrastResult, parse_color};
+use super::{CssDesigner, DesignTokens, TokenValue, tokens_css};
+use crate::store::StudioStore;
+
+/// A color value as seen with `kind`. Values that aren't plain colors,
+/// such as `var()` references, are returned unchanged.
+pub fn simulate_color(value: &str, kind: ColorBlindness) -> String {
+    parse_color(value).map_or_else(|| value.to_string(), |rgb| rgb.simulate(kind).to_hex())
+}
+
+/// Canvas colors as seen with `kind`.
+pub fn simulate_flow_theme(theme: &FlowTheme, kind: ColorBlindness) -> FlowTheme {
+    theme.map_colors(|color| simulate_color(color, kind))
+}
+
+/// A contrast pair that passes a threshold its simulated colors fail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegradedContrast {
+    pub fg: String,
+    pub bg: String,
+    /// The failure is in dark mode.
+    pub dark: bool,
+    pub original: ContrastResult,
+    pub simulated: ContrastResult,
+}
+
+impl DesignTokens {
+    /// Copy with every color token passed through the simulation.
+    pub fn simulated(&self, kind: ColorBlindness) -> DesignTokens {
+        let mut tokens = self.clone();
+        let simulate = |v: &mut String| *v = simulate_color(v, kind);
+        for value in tokens.colors.values_mut() {
+            match value {
+                TokenValue::Simple(v) => simulate(v),
+                TokenValue::Adaptive { light, dark } => {
+                    simulate(light);
+                    simulate(dark);
+                }
+                TokenValue::Scale(steps) => steps.values_mut().for_each(simulate),
+            }
+        }
+        tokens
+    }
+
+    /// Checked contrast pairs that pass AA for normal or large text but
+    /// fail it with `kind`. Pairs that can't be checked are skipped.
+    pub fn degraded_contrast(&self, kind: ColorBlindness) -> Vec<DegradedContrast> {
+        let simulated = self.simulated(kind);
+        let mut degraded = Vec::new();
+        for pair in self.checked_contrast_pairs() {
+            let (Ok(original), Ok(after)) = (
+                self.check_contrast(&pair.fg, &pair.bg),
+                simulated.check_contrast(&pair.fg, &pair.bg),
+            ) else {
+                continue;
+            };
+            let modes = [
+                (false, Some(original.light), Some(after.light)),
+                (true, original.dark, after.dark),
+            ];
+            for (dark, original, simulated) in modes {
+                if let (Some(original), Some(simulated)) = (original, simulated)
+                    && ((original.aa_normal && !simulated.aa_normal)
+                        || (original.aa_large && !simulated.aa_large))
+                {
+                    degraded.push(DegradedContrast {
+                        fg: pair.fg.clone(),
+                        bg: pair.bg.clone(),
+                        dark,
+                        original,
+                        simulated,
+                    });
+                }
+            }
+        }
+        degraded
+    }
+}
+
+impl CssDesigner {
+    /// Token CSS for the preview, with colors simulated when `simulation`
+    /// is set. Exports use [`generate_css`](Self::generate_css) instead.
+    pub fn preview_css(&self, simulation: Option<ColorBlindness>) -> String {
+        match simulation {
+            Some(kind) => tokens_css(
+                &self.tokens.simulated(kind),
+                &self.dark_mode,
+                ":root",
+                self.token_comments,
+            ),
+            None => self.generate_css(),
+        }
+    }
+}
+
+impl StudioStore {
+    /// Set or clear the simulation used by the preview and the canvas.
+    pub fn set_color_simulation(&mut self, simulation: Option<ColorBlindness>) {
+        self.ui.color_simulation = simulation;
+    }
+
+    /// Tokens as the preview shows them, experiments included.
+    pub fn preview_tokens(&self) -> DesignTokens {
+        let tokens = if self.experiments.enabled {
+            self.experiments.layered(&self.tokens)
+        } else {
+            self.tokens.clone()
+        };
+        match self.ui.color_simulation {
+            Some(kind) => tokens.simulated(kind),
+            None => tokens,
+        }
+    }
+
+    /// `theme` as the canvas shows it.
+    pub fn canvas_theme(&self, theme: &FlowTheme) -> FlowTheme {
+        match self.ui.color_simulation {
+            Some(kind) => simulate_flow_theme(theme, kind),
+            None => theme.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::ContrastPair;
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    #[test]
+    fn test_degraded_contrast() {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert("error".to_string(), simple("#ff0000"));
+        tokens.colors.insert("text".to_string(), simple("#334155"));
+        tokens
+            .colors
+            .insert("surface".to_string(), simple("#000000"));
+        tokens
+            .colors
+            .insert("background".to_string(), simple("#ffffff"));
+        tokens.contrast_pairs = vec![
+            ContrastPair::new("colors.error", "colors.surface"),
+            ContrastPair::new("colors.text", "colors.background"),
+            ContrastPair::new("colors.missing", "colors.background"),
+        ];
+
+        // Red on black passes AA, but reads much darker without red cones.
+        let degraded = tokens.degraded_contrast(ColorBlindness::Protanopia);
+        assert_eq!(degraded.len(), 1);
+        let red = &degraded[0];
+        assert_eq!((red.fg.as_str(), red.dark), ("colors.error", false));
+        assert!(red.original.aa_normal);
+        assert!(!red.simulated.aa_normal && red.simulated.aa_large);
+        assert!(
+            tokens
+                .degraded_contrast(ColorBlindness::Deuteranopia)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_preview_only_simulation() {
+        let mut store = StudioStore::new();
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "accent".to_string(),
+            TokenValue::Adaptive {
+                light: "#ff0000".to_string(),
+                dark: "rgb(0 255 0)".to_string(),
+            },
+        );
+        tokens
+            .colors
+            .insert("link".to_string(), simple("var(--color-accent)"));
+        store.tokens = tokens.clone();
+
+        assert_eq!(store.preview_tokens().colors, tokens.colors);
+        store.set_color_simulation(Some(ColorBlindness::Deuteranopia));
+        let preview = store.preview_tokens();
+        assert_eq!(
+            preview.colors["accent"],
+            TokenValue::Adaptive {
+                light: "#a39000".to_string(),
+                dark: "#efd63a".to_string(),
+            }
+        );
+        assert_eq!(preview.colors["link"], simple("var(--color-accent)"));
+        assert_eq!(store.tokens.colors, tokens.colors);
+
+        let theme = store.canvas_theme(&FlowTheme::default());
+        assert_eq!(theme.node_fill, "#ffffff");
+        assert_ne!(theme.selection, FlowTheme::default().selection);
+
+        let designer = CssDesigner {
+            tokens,
+            ..Default::default()
+        };
+        assert_eq!(designer.preview_css(None), designer.generate_css());
+        let css = designer.preview_css(Some(ColorBlindness::Protanopia));
+        assert!(css.contains("#6d5f00"));
+        assert!(designer.generate_css().contains("#ff0000"));
+    }
+}
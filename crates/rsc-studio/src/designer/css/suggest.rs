@@ -0,0 +1,421 @@
+//! Value suggestions for token and style inputs.
+//!
+//! Suggestions are ranked token references first, then recently used
+//! literals, then CSS keywords. Only references from categories compatible
+//! with the edited value are offered, so a radius field never suggests
+//! color tokens.
+
+use super::{DesignTokens, TokenValue, path_to_css_var};
+use crate::store::StudioStore;
+
+/// Maximum number of recently used values kept in [`UiState`](crate::store::UiState).
+pub const RECENT_VALUES_LIMIT: usize = 20;
+
+const NAMED_COLORS: &[&str] = &[
+    "transparent",
+    "currentColor",
+    "black",
+    "white",
+    "gray",
+    "red",
+    "orange",
+    "yellow",
+    "green",
+    "blue",
+    "purple",
+    "pink",
+];
+
+const GLOBAL_KEYWORDS: &[&str] = &["inherit", "initial", "unset"];
+
+/// What is being edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuggestTarget {
+    /// A color token.
+    Color,
+    /// A spacing or radius token.
+    Dimension,
+    /// A [`StyleProperties`](super::StyleProperties) field, by CSS property name.
+    Property(String),
+}
+
+impl SuggestTarget {
+    pub fn property(name: impl Into<String>) -> Self {
+        Self::Property(name.into())
+    }
+
+    fn kind(&self) -> ValueKind {
+        match self {
+            SuggestTarget::Color => ValueKind::Color,
+            SuggestTarget::Dimension => ValueKind::Dimension,
+            SuggestTarget::Property(name) => ValueKind::of_property(name),
+        }
+    }
+}
+
+/// Value type accepted by a target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueKind {
+    Color,
+    Dimension,
+    Shadow,
+    FontFamily,
+    FontSize,
+    FontWeight,
+    Duration,
+    ZIndex,
+    /// Keyword-only property (e.g. `display`).
+    Keyword,
+}
+
+impl ValueKind {
+    fn of_property(name: &str) -> Self {
+        match name {
+            "color" | "background" | "background-color" | "border-color" => ValueKind::Color,
+            "box-shadow" => ValueKind::Shadow,
+            "font-family" => ValueKind::FontFamily,
+            "font-size" => ValueKind::FontSize,
+            "font-weight" => ValueKind::FontWeight,
+            "transition" => ValueKind::Duration,
+            "z-index" => ValueKind::ZIndex,
+            "gap" | "width" | "height" | "min-width" | "min-height" | "max-width"
+            | "max-height" | "border-width" | "border-radius" | "top" | "right" | "bottom"
+            | "left" | "line-height" => ValueKind::Dimension,
+            _ if name.starts_with("padding") || name.starts_with("margin") => ValueKind::Dimension,
+            _ => ValueKind::Keyword,
+        }
+    }
+
+    /// Token path prefixes whose values fit this kind.
+    fn token_prefixes(self) -> &'static [&'static str] {
+        match self {
+            ValueKind::Color => &["colors"],
+            ValueKind::Dimension => &["spacing", "radius"],
+            ValueKind::Shadow => &["shadows"],
+            ValueKind::FontFamily => &["typography.fonts"],
+            ValueKind::FontSize => &["typography.sizes"],
+            ValueKind::FontWeight => &["typography.weights"],
+            ValueKind::Duration => &["transitions"],
+            ValueKind::ZIndex => &["z-index"],
+            ValueKind::Keyword => &[],
+        }
+    }
+
+    /// Whether a literal value plausibly fits this kind.
+    fn accepts(self, value: &str, property: Option<&str>) -> bool {
+        let starts_numeric = value
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.' || c == '-');
+        match self {
+            ValueKind::Color => {
+                value.starts_with('#')
+                    || value.starts_with("rgb")
+                    || value.starts_with("hsl")
+                    || NAMED_COLORS.iter().any(|c| c.eq_ignore_ascii_case(value))
+            }
+            ValueKind::Dimension | ValueKind::FontSize => starts_numeric || value == "auto",
+            ValueKind::FontWeight | ValueKind::ZIndex => {
+                value.chars().all(|c| c.is_ascii_digit())
+                    || keywords(self, property).contains(&value)
+            }
+            ValueKind::Duration => starts_numeric || value.contains(' '),
+            ValueKind::Shadow | ValueKind::FontFamily => true,
+            ValueKind::Keyword => keywords(self, property).contains(&value),
+        }
+    }
+}
+
+/// Keywords valid for a kind, with property-specific ones for keyword properties.
+fn keywords(kind: ValueKind, property: Option<&str>) -> &'static [&'static str] {
+    match kind {
+        ValueKind::Color => NAMED_COLORS,
+        ValueKind::Dimension => &["auto", "0", "100%"],
+        ValueKind::Shadow => &["none"],
+        ValueKind::FontFamily => &["sans-serif", "serif", "monospace", "system-ui"],
+        ValueKind::FontSize => &["small", "medium", "large"],
+        ValueKind::FontWeight => &["normal", "bold", "lighter", "bolder"],
+        ValueKind::Duration => &["none", "all"],
+        ValueKind::ZIndex => &["auto"],
+        ValueKind::Keyword => match property.unwrap_or_default() {
+            "display" => &[
+                "block",
+                "inline",
+                "inline-block",
+                "flex",
+                "inline-flex",
+                "grid",
+                "none",
+            ],
+            "flex-direction" => &["row", "row-reverse", "column", "column-reverse"],
+            "align-items" => &["flex-start", "flex-end", "center", "stretch", "baseline"],
+            "justify-content" => &[
+                "flex-start",
+                "flex-end",
+                "center",
+                "space-between",
+                "space-around",
+                "space-evenly",
+            ],
+            "border-style" => &["none", "solid", "dashed", "dotted", "double"],
+            "text-align" => &["left", "center", "right", "justify"],
+            "text-decoration" => &["none", "underline", "line-through"],
+            "cursor" => &["default", "pointer", "text", "move", "grab", "not-allowed"],
+            "position" => &["static", "relative", "absolute", "fixed", "sticky"],
+            "overflow" | "overflow-x" | "overflow-y" => &["visible", "hidden", "scroll", "auto"],
+            _ => &[],
+        },
+    }
+}
+
+/// Suggestion source, in ranking order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SuggestionKind {
+    Reference,
+    Recent,
+    Keyword,
+}
+
+/// One suggestion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    /// Text shown in the list (e.g. "colors.primary").
+    pub label: String,
+    /// Text inserted on accept (e.g. "var(--color-primary)").
+    pub insert_text: String,
+}
+
+/// Inputs for [`suggest_values`].
+#[derive(Debug, Clone)]
+pub struct SuggestContext<'a> {
+    pub target: SuggestTarget,
+    pub tokens: &'a DesignTokens,
+    /// Recently used literal values, most recent first.
+    pub recents: &'a [String],
+}
+
+impl<'a> SuggestContext<'a> {
+    pub fn new(target: SuggestTarget, tokens: &'a DesignTokens) -> Self {
+        Self {
+            target,
+            tokens,
+            recents: &[],
+        }
+    }
+
+    pub fn with_recents(mut self, recents: &'a [String]) -> Self {
+        self.recents = recents;
+        self
+    }
+}
+
+/// Suggest values for an input whose current text is `prefix`.
+///
+/// Inside a `var(--` reference only token references are matched, by CSS
+/// variable name; otherwise references also match by token name.
+pub fn suggest_values(context: &SuggestContext<'_>, prefix: &str) -> Vec<Suggestion> {
+    let kind = context.target.kind();
+    let property = match &context.target {
+        SuggestTarget::Property(name) => Some(name.as_str()),
+        _ => None,
+    };
+    let prefix = prefix.trim();
+    let var_prefix = prefix
+        .strip_prefix("var(")
+        .map(|rest| rest.trim_start_matches('-'));
+    let matches = |text: &str| text.to_lowercase().starts_with(&prefix.to_lowercase());
+
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut push = |suggestion: Suggestion| {
+        if !suggestions
+            .iter()
+            .any(|s| s.insert_text == suggestion.insert_text)
+        {
+            suggestions.push(suggestion);
+        }
+    };
+
+    for (category, values) in context.tokens.categories() {
+        if !kind.token_prefixes().contains(&category) {
+            continue;
+        }
+        for (name, value) in values {
+            let path = format!("{}.{}", category, name);
+            let Some(base) = path_to_css_var(&path) else {
+                continue;
+            };
+            let vars: Vec<(String, String)> = match value {
+                TokenValue::Scale(steps) => steps
+                    .keys()
+                    .map(|step| (format!("{}.{}", path, step), format!("{}-{}", base, step)))
+                    .collect(),
+                _ => vec![(path, base)],
+            };
+            for (label, var) in vars {
+                let bare = var.trim_start_matches('-');
+                let hit = match var_prefix {
+                    Some(p) => bare.starts_with(p),
+                    None => matches(name) || matches(bare) || matches(&label),
+                };
+                if hit {
+                    push(Suggestion {
+                        kind: SuggestionKind::Reference,
+                        label,
+                        insert_text: format!("var({})", var),
+                    });
+                }
+            }
+        }
+    }
+
+    if var_prefix.is_none() {
+        for value in context.recents {
+            if matches(value) && kind.accepts(value, property) {
+                push(Suggestion {
+                    kind: SuggestionKind::Recent,
+                    label: value.clone(),
+                    insert_text: value.clone(),
+                });
+            }
+        }
+
+        let globals = if property.is_some() {
+            GLOBAL_KEYWORDS
+        } else {
+            &[]
+        };
+        for keyword in keywords(kind, property).iter().chain(globals) {
+            if matches(keyword) {
+                push(Suggestion {
+                    kind: SuggestionKind::Keyword,
+                    label: keyword.to_string(),
+                    insert_text: keyword.to_string(),
+                });
+            }
+        }
+    }
+
+    suggestions
+}
+
+impl StudioStore {
+    /// Remember a literal value typed into a token or style input.
+    ///
+    /// References are not recorded; they are always offered from the tokens.
+    pub fn record_recent_value(&mut self, value: &str) {
+        let value = value.trim();
+        if value.is_empty() || value.starts_with("var(") {
+            return;
+        }
+        let recents = &mut self.ui.recent_values;
+        recents.retain(|v| v != value);
+        recents.insert(0, value.to_string());
+        recents.truncate(RECENT_VALUES_LIMIT);
+    }
+
+    /// Suggestion context over the store's tokens and recent values.
+    pub fn suggest_context(&self, target: SuggestTarget) -> SuggestContext<'_> {
+        SuggestContext::new(target, &self.tokens).with_recents(&self.ui.recent_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        let tokens = &mut store.tokens;
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#3b82f6".to_string()),
+        );
+        tokens.colors.insert(
+            "primary-muted".to_string(),
+            TokenValue::Simple("#93c5fd".to_string()),
+        );
+        tokens
+            .radius
+            .insert("md".to_string(), TokenValue::Simple("8px".to_string()));
+        tokens
+            .spacing
+            .insert("md".to_string(), TokenValue::Simple("16px".to_string()));
+        store
+    }
+
+    fn inserts(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.insert_text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_category_compatibility() {
+        let mut store = store();
+        store.record_recent_value("#ff0000");
+        store.record_recent_value("12px");
+
+        let radius = suggest_values(
+            &store.suggest_context(SuggestTarget::property("border-radius")),
+            "",
+        );
+        assert!(radius.iter().all(|s| !s.insert_text.contains("color")));
+        assert!(!inserts(&radius).contains(&"#ff0000"));
+        assert_eq!(
+            inserts(&radius)[..3],
+            ["var(--spacing-md)", "var(--radius-md)", "12px"]
+        );
+
+        let color = suggest_values(&store.suggest_context(SuggestTarget::Color), "");
+        assert_eq!(
+            inserts(&color)[..3],
+            [
+                "var(--color-primary)",
+                "var(--color-primary-muted)",
+                "#ff0000"
+            ]
+        );
+        assert!(inserts(&color).contains(&"transparent"));
+    }
+
+    #[test]
+    fn test_var_prefix_matching() {
+        let store = store();
+        let context = store.suggest_context(SuggestTarget::property("background-color"));
+
+        let suggestions = suggest_values(&context, "var(--color-primary-m");
+        assert_eq!(inserts(&suggestions), ["var(--color-primary-muted)"]);
+        assert_eq!(suggestions[0].label, "colors.primary-muted");
+
+        assert_eq!(suggest_values(&context, "var(--col").len(), 2);
+        assert!(suggest_values(&context, "var(--spacing").is_empty());
+
+        let by_name = suggest_values(&context, "prim");
+        assert_eq!(by_name.len(), 2);
+        assert!(by_name.iter().all(|s| s.kind == SuggestionKind::Reference));
+    }
+
+    #[test]
+    fn test_keywords_and_recents() {
+        let mut store = store();
+        for i in 0..RECENT_VALUES_LIMIT + 5 {
+            store.record_recent_value(&format!("{}px", i));
+        }
+        store.record_recent_value("var(--spacing-md)");
+        store.record_recent_value("flex");
+        assert_eq!(store.ui.recent_values.len(), RECENT_VALUES_LIMIT);
+        assert_eq!(store.ui.recent_values[0], "flex");
+
+        let display = suggest_values(
+            &store.suggest_context(SuggestTarget::property("display")),
+            "fl",
+        );
+        assert_eq!(display[0].kind, SuggestionKind::Recent);
+        assert_eq!(inserts(&display), ["flex"]);
+
+        let position = suggest_values(
+            &store.suggest_context(SuggestTarget::property("position")),
+            "",
+        );
+        assert_eq!(position.last().unwrap().insert_text, "unset");
+    }
+}
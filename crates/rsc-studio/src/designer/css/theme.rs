@@ -0,0 +1,521 @@
+//! Named token sets within one project.
+//!
+//! A [`ThemeManager`] keeps every theme and which one is active. The
+//! designer and the store edit the active theme through their `tokens`
+//! field as before; switching parks the edited set back in the manager
+//! and loads the next one.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::rename::is_valid_name;
+use super::{CssDesigner, DarkModeStrategy, DesignTokens, OutputOrdering, ThemeSet, tokens_css};
+use crate::error::StudioError;
+use crate::history::HistoryScope;
+use crate::store::StudioStore;
+use crate::store::dirty::ChangeCategory;
+use crate::store::events::StoreEvent;
+
+/// Name of the theme a new project starts with.
+pub const DEFAULT_THEME: &str = "default";
+
+/// Theme error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ThemeError {
+    /// No theme has this name.
+    #[error("Theme '{0}' not found")]
+    NotFound(String),
+
+    /// A theme with this name already exists.
+    #[error("Theme '{0}' already exists")]
+    AlreadyExists(String),
+
+    /// The name is empty or contains characters other than letters,
+    /// digits, `-` and `_`.
+    #[error("Invalid theme name '{0}'")]
+    InvalidName(String),
+
+    /// A project always keeps at least one theme.
+    #[error("Cannot delete '{0}', the only theme")]
+    LastTheme(String),
+}
+
+/// Named token sets and the active selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeManager {
+    themes: IndexMap<String, DesignTokens>,
+    active: String,
+}
+
+impl Default for ThemeManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_THEME, DesignTokens::default())
+    }
+}
+
+impl ThemeManager {
+    /// A manager with a single active theme.
+    pub fn new(name: impl Into<String>, tokens: DesignTokens) -> Self {
+        let name = name.into();
+        Self {
+            themes: IndexMap::from([(name.clone(), tokens)]),
+            active: name,
+        }
+    }
+
+    /// Name of the active theme.
+    pub fn active(&self) -> &str {
+        &self.active
+    }
+
+    /// Tokens of the active theme as last parked.
+    pub fn active_tokens(&self) -> &DesignTokens {
+        &self.themes[&self.active]
+    }
+
+    /// Replace the active theme's tokens.
+    pub fn set_active_tokens(&mut self, tokens: DesignTokens) {
+        self.themes[&self.active] = tokens;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DesignTokens> {
+        self.themes.get(name)
+    }
+
+    /// Themes by name, in creation order.
+    pub(crate) fn themes(&self) -> &IndexMap<String, DesignTokens> {
+        &self.themes
+    }
+
+    /// A manager of `themes` with `active` selected, or the first theme if
+    /// `active` isn't one of them. `None` without themes.
+    pub(crate) fn from_themes(
+        themes: IndexMap<String, DesignTokens>,
+        active: &str,
+    ) -> Option<Self> {
+        let active = if themes.contains_key(active) {
+            active.to_string()
+        } else {
+            themes.keys().next()?.clone()
+        };
+        Some(Self { themes, active })
+    }
+
+    /// Theme names in creation order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.themes.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.themes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.themes.is_empty()
+    }
+
+    /// Whether this is just the single theme a new project starts with,
+    /// which the project file doesn't need to record.
+    pub fn is_default(&self) -> bool {
+        self.themes.len() == 1 && self.active == DEFAULT_THEME
+    }
+
+    /// Add a theme, copying `base` or starting empty.
+    pub fn create_theme(&mut self, name: &str, base: Option<&str>) -> Result<(), ThemeError> {
+        if !is_valid_name(name) {
+            return Err(ThemeError::InvalidName(name.to_string()));
+        }
+        if self.themes.contains_key(name) {
+            return Err(ThemeError::AlreadyExists(name.to_string()));
+        }
+        let tokens = match base {
+            Some(base) => self
+                .themes
+                .get(base)
+                .cloned()
+                .ok_or_else(|| ThemeError::NotFound(base.to_string()))?,
+            None => DesignTokens::default(),
+        };
+        self.themes.insert(name.to_string(), tokens);
+        Ok(())
+    }
+
+    /// Make `name` the active theme.
+    pub fn switch_theme(&mut self, name: &str) -> Result<(), ThemeError> {
+        if !self.themes.contains_key(name) {
+            return Err(ThemeError::NotFound(name.to_string()));
+        }
+        self.active = name.to_string();
+        Ok(())
+    }
+
+    /// Remove a theme. Deleting the active theme activates the one before
+    /// it, or the new first theme.
+    pub fn delete_theme(&mut self, name: &str) -> Result<DesignTokens, ThemeError> {
+        let index = self
+            .themes
+            .get_index_of(name)
+            .ok_or_else(|| ThemeError::NotFound(name.to_string()))?;
+        if self.themes.len() == 1 {
+            return Err(ThemeError::LastTheme(name.to_string()));
+        }
+        let (_, tokens) = self.themes.shift_remove_index(index).unwrap();
+        if self.active == name {
+            let (next, _) = self.themes.get_index(index.saturating_sub(1)).unwrap();
+            self.active = next.clone();
+        }
+        Ok(tokens)
+    }
+
+    /// Rename a theme in place, keeping its position.
+    pub fn rename_theme(&mut self, old: &str, new: &str) -> Result<(), ThemeError> {
+        if !is_valid_name(new) {
+            return Err(ThemeError::InvalidName(new.to_string()));
+        }
+        let index = self
+            .themes
+            .get_index_of(old)
+            .ok_or_else(|| ThemeError::NotFound(old.to_string()))?;
+        if old == new {
+            return Ok(());
+        }
+        if self.themes.contains_key(new) {
+            return Err(ThemeError::AlreadyExists(new.to_string()));
+        }
+        let (_, tokens) = self.themes.shift_remove_index(index).unwrap();
+        self.themes.shift_insert(index, new.to_string(), tokens);
+        if self.active == old {
+            self.active = new.to_string();
+        }
+        Ok(())
+    }
+
+    /// Every theme's variables scoped under `[data-theme="name"]`, with
+    /// tokens in `ordering`.
+    pub fn generate_all_css(
+        &self,
+        dark_mode: &DarkModeStrategy,
+        ordering: &OutputOrdering,
+    ) -> String {
+        self.themes
+            .iter()
+            .map(|(name, tokens)| {
+                tokens_css(
+                    &tokens.ordered(ordering),
+                    dark_mode,
+                    &format!("[data-theme=\"{}\"]", name),
+                    false,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The themes as a [`ThemeSet`] defaulting to the active one, for the
+    /// style guide and catalog exports.
+    pub fn to_theme_set(&self) -> ThemeSet {
+        ThemeSet {
+            themes: self.themes.clone(),
+            default: Some(self.active.clone()),
+        }
+    }
+}
+
+impl CssDesigner {
+    /// Park the edited tokens in the active theme.
+    fn park_tokens(&mut self) {
+        self.themes.set_active_tokens(self.tokens.clone());
+    }
+
+    /// Load the active theme's tokens, starting a fresh history.
+    fn unpark_tokens(&mut self) {
+        self.load_tokens(self.themes.active_tokens().clone());
+        self.selected_token = None;
+    }
+
+    /// Add a theme, copying `base` or starting empty.
+    pub fn create_theme(&mut self, name: &str, base: Option<&str>) -> Result<(), ThemeError> {
+        self.park_tokens();
+        self.themes.create_theme(name, base)
+    }
+
+    /// Switch to another theme. Token history does not carry over.
+    pub fn switch_theme(&mut self, name: &str) -> Result<(), ThemeError> {
+        if name == self.themes.active() {
+            return Ok(());
+        }
+        self.park_tokens();
+        self.themes.switch_theme(name)?;
+        self.unpark_tokens();
+        Ok(())
+    }
+
+    pub fn delete_theme(&mut self, name: &str) -> Result<DesignTokens, ThemeError> {
+        let was_active = name == self.themes.active();
+        self.park_tokens();
+        let tokens = self.themes.delete_theme(name)?;
+        if was_active {
+            self.unpark_tokens();
+        }
+        Ok(tokens)
+    }
+
+    pub fn rename_theme(&mut self, old: &str, new: &str) -> Result<(), ThemeError> {
+        self.themes.rename_theme(old, new)
+    }
+
+    /// Every theme's variables, scoped under `[data-theme="name"]`.
+    pub fn generate_all_css(&self, ordering: &OutputOrdering) -> String {
+        let mut themes = self.themes.clone();
+        themes.set_active_tokens(self.tokens.clone());
+        themes.generate_all_css(&self.dark_mode, ordering)
+    }
+}
+
+impl StudioStore {
+    /// Park the store's tokens in the active theme.
+    fn park_tokens(&mut self) {
+        self.themes.set_active_tokens(self.tokens.clone());
+    }
+
+    pub fn create_theme(&mut self, name: &str, base: Option<&str>) -> Result<(), StudioError> {
+        self.park_tokens();
+        let result = self.themes.create_theme(name, base);
+        self.checked(result)?;
+        self.dirty.touch(&[ChangeCategory::Tokens]);
+        Ok(())
+    }
+
+    /// Switch to another theme. Token edits can't be undone across a
+    /// switch, so the CSS designer history is cleared.
+    pub fn switch_theme(&mut self, name: &str) -> Result<(), StudioError> {
+        if name == self.themes.active() {
+            return Ok(());
+        }
+        let from = self.themes.active().to_string();
+        self.park_tokens();
+        let result = self.themes.switch_theme(name);
+        self.checked(result)?;
+        self.tokens = self.themes.active_tokens().clone();
+        self.history.scope_mut(HistoryScope::CssDesigner).clear();
+        self.dirty.touch(&[ChangeCategory::Tokens]);
+        let event = StoreEvent::ThemeSwitched {
+            from,
+            to: name.to_string(),
+            revision: self.dirty.revision(),
+        };
+        self.emit("Switch theme", vec![event], false);
+        Ok(())
+    }
+
+    pub fn delete_theme(&mut self, name: &str) -> Result<DesignTokens, StudioError> {
+        let was_active = name == self.themes.active();
+        self.park_tokens();
+        let result = self.themes.delete_theme(name);
+        let tokens = self.checked(result)?;
+        if was_active {
+            self.tokens = self.themes.active_tokens().clone();
+            self.history.scope_mut(HistoryScope::CssDesigner).clear();
+        }
+        self.dirty.touch(&[ChangeCategory::Tokens]);
+        Ok(tokens)
+    }
+
+    pub fn rename_theme(&mut self, old: &str, new: &str) -> Result<(), StudioError> {
+        let result = self.themes.rename_theme(old, new);
+        self.checked(result)?;
+        self.dirty.touch(&[ChangeCategory::Tokens]);
+        Ok(())
+    }
+
+    /// The themes with the active one's current tokens, for saving and
+    /// export.
+    pub fn themes_snapshot(&self) -> ThemeManager {
+        let mut themes = self.themes.clone();
+        themes.set_active_tokens(self.tokens.clone());
+        themes
+    }
+
+    /// Every theme's variables in the project's output ordering.
+    pub fn generate_all_css(&self, dark_mode: &DarkModeStrategy) -> String {
+        self.themes_snapshot()
+            .generate_all_css(dark_mode, &self.output_ordering)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+    use crate::error::ErrorCode;
+    use crate::project::ProjectFile;
+
+    fn designer() -> CssDesigner {
+        let mut designer = CssDesigner::new();
+        designer.tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#3b82f6".to_string()),
+        );
+        designer
+    }
+
+    #[test]
+    fn test_create_and_switch() {
+        let mut designer = designer();
+        designer.create_theme("brand-b", Some("default")).unwrap();
+        designer.create_theme("blank", None).unwrap();
+        designer.switch_theme("brand-b").unwrap();
+        assert_eq!(designer.themes.active(), "brand-b");
+        assert!(designer.get_token("colors.primary").is_some());
+
+        designer.tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#f00".to_string()),
+        );
+        designer.switch_theme("default").unwrap();
+        assert_eq!(
+            designer.get_token("colors.primary"),
+            Some(&TokenValue::Simple("#3b82f6".to_string()))
+        );
+        assert_eq!(
+            designer.themes.get("brand-b").unwrap().colors["primary"],
+            TokenValue::Simple("#f00".to_string())
+        );
+        designer.switch_theme("blank").unwrap();
+        assert!(designer.tokens.colors.is_empty());
+
+        assert_eq!(
+            designer.create_theme("blank", None),
+            Err(ThemeError::AlreadyExists("blank".to_string()))
+        );
+        assert_eq!(
+            designer.create_theme("x", Some("missing")),
+            Err(ThemeError::NotFound("missing".to_string()))
+        );
+        assert_eq!(
+            designer.switch_theme("nope"),
+            Err(ThemeError::NotFound("nope".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_delete_and_rename() {
+        let mut themes = ThemeManager::default();
+        assert_eq!(
+            themes.delete_theme("default").unwrap_err(),
+            ThemeError::LastTheme("default".to_string())
+        );
+        themes.create_theme("high-contrast", None).unwrap();
+        themes.create_theme("brand-b", None).unwrap();
+        themes.switch_theme("high-contrast").unwrap();
+
+        themes.rename_theme("high-contrast", "hc").unwrap();
+        assert_eq!(themes.active(), "hc");
+        assert_eq!(
+            themes.names().collect::<Vec<_>>(),
+            ["default", "hc", "brand-b"]
+        );
+        assert_eq!(
+            themes.rename_theme("hc", "brand-b"),
+            Err(ThemeError::AlreadyExists("brand-b".to_string()))
+        );
+        assert_eq!(
+            themes.rename_theme("hc", "my theme"),
+            Err(ThemeError::InvalidName("my theme".to_string()))
+        );
+
+        themes.delete_theme("hc").unwrap();
+        assert_eq!(themes.active(), "default");
+        themes.delete_theme("default").unwrap();
+        assert_eq!(themes.active(), "brand-b");
+        assert_eq!(themes.len(), 1);
+    }
+
+    #[test]
+    fn test_generate_all_css() {
+        let mut designer = designer();
+        designer
+            .create_theme("dark-brand", Some("default"))
+            .unwrap();
+        designer.switch_theme("dark-brand").unwrap();
+        designer.tokens.colors.insert(
+            "surface".to_string(),
+            TokenValue::Adaptive {
+                light: "#fff".to_string(),
+                dark: "#000".to_string(),
+            },
+        );
+        // Unparked edits to the active theme are included.
+        let css = designer.generate_all_css(&OutputOrdering::default());
+        assert!(css.starts_with("[data-theme=\"default\"] {\n  --color-primary: #3b82f6;\n}\n"));
+        assert!(css.contains(
+            "[data-theme=\"dark-brand\"] {\n  --color-primary: #3b82f6;\n  --color-surface: #fff;\n}\n"
+        ));
+        assert!(
+            css.contains(
+                "@media (prefers-color-scheme: dark) {\n  [data-theme=\"dark-brand\"] {\n"
+            )
+        );
+
+        let designer = designer.with_dark_mode(DarkModeStrategy::class("dark"));
+        assert!(
+            designer
+                .generate_all_css(&OutputOrdering::default())
+                .contains("[data-theme=\"dark-brand\"].dark {\n  --color-surface: #000;\n}\n")
+        );
+        assert_eq!(
+            designer.themes.to_theme_set().default_theme(),
+            Some("dark-brand")
+        );
+    }
+
+    #[test]
+    fn test_store_themes_roundtrip() {
+        let mut store = StudioStore::new();
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#000".to_string()),
+        );
+        store.set_tokens(tokens);
+        let json = ProjectFile::from_store(&store, &Default::default())
+            .to_json()
+            .unwrap();
+        assert!(!json.contains("\"themes\""));
+
+        store
+            .create_theme("high-contrast", Some("default"))
+            .unwrap();
+        store.switch_theme("high-contrast").unwrap();
+        assert!(!store.history.can_undo(HistoryScope::CssDesigner));
+        let error = store.delete_theme("brand").unwrap_err();
+        assert_eq!(error.code, ErrorCode::UnknownTheme);
+        assert_eq!(store.problems.len(), 1);
+
+        let mut tokens = store.tokens.clone();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#fff".to_string()),
+        );
+        store.set_tokens(tokens);
+        let json = ProjectFile::from_store(&store, &Default::default())
+            .to_json()
+            .unwrap();
+
+        let mut loaded = StudioStore::new();
+        ProjectFile::from_json(&json)
+            .unwrap()
+            .load_into(&mut loaded);
+        assert_eq!(loaded.themes.active(), "high-contrast");
+        assert_eq!(
+            loaded.tokens.colors["primary"],
+            TokenValue::Simple("#fff".to_string())
+        );
+        loaded.switch_theme("default").unwrap();
+        assert_eq!(
+            loaded.tokens.colors["primary"],
+            TokenValue::Simple("#000".to_string())
+        );
+    }
+}
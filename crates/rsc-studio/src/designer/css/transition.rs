@@ -0,0 +1,241 @@
+//! Structured transition builder.
+//!
+//! A [`TransitionSpec`] is a list of per-property transitions that compiles
+//! to the CSS `transition` shorthand, so authors don't write it by hand.
+
+use serde::{Deserialize, Serialize};
+
+use super::{DesignTokens, StyleProperties, TokenValidationError, ValidationSeverity};
+
+/// Properties that trigger layout when animated.
+const LAYOUT_PROPERTIES: &[&str] = &[
+    "width",
+    "height",
+    "min-width",
+    "min-height",
+    "max-width",
+    "max-height",
+    "top",
+    "right",
+    "bottom",
+    "left",
+    "margin",
+    "padding",
+];
+
+/// Transition duration or delay: a `transitions` token or milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TransitionTime {
+    Ms(u32),
+    /// Token name in the `transitions` category (e.g. "fast").
+    Token(String),
+}
+
+impl TransitionTime {
+    pub fn to_css(&self) -> String {
+        match self {
+            TransitionTime::Ms(ms) => format!("{}ms", ms),
+            TransitionTime::Token(name) => format!("var(--transition-{})", name),
+        }
+    }
+}
+
+/// One property's transition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionEntry {
+    /// CSS property name (e.g. "background-color"), or "all".
+    pub property: String,
+    pub duration: TransitionTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub easing: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<TransitionTime>,
+}
+
+impl TransitionEntry {
+    pub fn new(property: impl Into<String>, duration: TransitionTime) -> Self {
+        Self {
+            property: property.into(),
+            duration,
+            easing: None,
+            delay: None,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: impl Into<String>) -> Self {
+        self.easing = Some(easing.into());
+        self
+    }
+
+    pub fn with_delay(mut self, delay: TransitionTime) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Compile to a single `transition` list item.
+    pub fn to_css(&self) -> String {
+        let mut parts = vec![self.property.clone(), self.duration.to_css()];
+        if let Some(easing) = &self.easing {
+            parts.push(easing.clone());
+        }
+        if let Some(delay) = &self.delay {
+            parts.push(delay.to_css());
+        }
+        parts.join(" ")
+    }
+}
+
+/// Ordered list of transitions for a style.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransitionSpec {
+    #[serde(default)]
+    pub entries: Vec<TransitionEntry>,
+}
+
+impl TransitionSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_entry(mut self, entry: TransitionEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Compile to the `transition` shorthand, keeping entry order.
+    pub fn to_css(&self) -> String {
+        self.entries
+            .iter()
+            .map(TransitionEntry::to_css)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Check properties against the known list and token references
+    /// against the `transitions` category.
+    pub fn validate(&self, tokens: &DesignTokens) -> Vec<TokenValidationError> {
+        let mut errors = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let path = format!("transition[{}]", i);
+            if entry.property != "all"
+                && !StyleProperties::PROPERTY_NAMES.contains(&entry.property.as_str())
+            {
+                errors.push(TokenValidationError {
+                    path: path.clone(),
+                    message: format!("Unknown property '{}'", entry.property),
+                    severity: ValidationSeverity::Error,
+                });
+            }
+            for time in std::iter::once(&entry.duration).chain(entry.delay.as_ref()) {
+                if let TransitionTime::Token(name) = time
+                    && !tokens.transitions.contains_key(name)
+                {
+                    errors.push(TokenValidationError {
+                        path: path.clone(),
+                        message: format!("Unknown transition token '{}'", name),
+                        severity: ValidationSeverity::Error,
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    /// Flag transitions on properties that force layout on every frame.
+    pub fn lint(&self) -> Vec<TokenValidationError> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| LAYOUT_PROPERTIES.contains(&e.property.as_str()))
+            .map(|(i, e)| TokenValidationError {
+                path: format!("transition[{}]", i),
+                message: format!(
+                    "Animating '{}' triggers layout on every frame; animate transform instead",
+                    e.property
+                ),
+                severity: ValidationSeverity::Warning,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+
+    fn spec() -> TransitionSpec {
+        TransitionSpec::new()
+            .with_entry(
+                TransitionEntry::new("background-color", TransitionTime::Ms(150))
+                    .with_easing("ease-in-out"),
+            )
+            .with_entry(TransitionEntry::new(
+                "transform",
+                TransitionTime::Token("fast".to_string()),
+            ))
+            .with_entry(
+                TransitionEntry::new("opacity", TransitionTime::Ms(200))
+                    .with_delay(TransitionTime::Ms(50)),
+            )
+    }
+
+    #[test]
+    fn test_compiles_in_entry_order() {
+        assert_eq!(
+            spec().to_css(),
+            "background-color 150ms ease-in-out, transform var(--transition-fast), opacity 200ms 50ms"
+        );
+
+        let mut props = StyleProperties {
+            transition: Some("all 1s".to_string()),
+            transitions: Some(spec()),
+            ..Default::default()
+        };
+        assert!(
+            props
+                .to_css()
+                .contains("transition: background-color 150ms ease-in-out, transform")
+        );
+
+        props.transitions = None;
+        assert!(props.to_css().contains("transition: all 1s;"));
+    }
+
+    #[test]
+    fn test_validate_properties_and_tokens() {
+        let mut tokens = DesignTokens::default();
+        assert_eq!(spec().validate(&tokens).len(), 1);
+
+        tokens
+            .transitions
+            .insert("fast".to_string(), TokenValue::Simple("100ms".to_string()));
+        assert!(spec().validate(&tokens).is_empty());
+
+        let bad = TransitionSpec::new()
+            .with_entry(TransitionEntry::new("colour", TransitionTime::Ms(100)));
+        assert_eq!(
+            bad.validate(&tokens)[0].message,
+            "Unknown property 'colour'"
+        );
+    }
+
+    #[test]
+    fn test_lint_layout_properties() {
+        let spec = spec()
+            .with_entry(TransitionEntry::new("width", TransitionTime::Ms(200)))
+            .with_entry(TransitionEntry::new("left", TransitionTime::Ms(200)));
+
+        let warnings = spec.lint();
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].path, "transition[3]");
+        assert!(warnings[0].message.contains("transform"));
+        assert_eq!(warnings[1].severity, ValidationSeverity::Warning);
+    }
+}
@@ -0,0 +1,299 @@
+//! Unused token analysis against component styles.
+//!
+//! Component styles are the roots: a token is live if a style references
+//! it, or a live token does. Everything else is unused, split into tokens
+//! nothing references and tokens only other unused tokens reference.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use super::extract::var_calls;
+use super::{
+    ComponentStyle, ComponentStyles, CssDesigner, DesignTokens, TokenUsageTracker, TokenValue,
+    css_var_to_path,
+};
+
+/// Unused tokens grouped by category prefix (e.g. "colors",
+/// "typography.sizes"). Categories without unused tokens are left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnusedReport {
+    /// Tokens no component style or token references.
+    pub unreferenced: IndexMap<&'static str, Vec<String>>,
+    /// Tokens referenced only by other unused tokens.
+    pub transitively_unused: IndexMap<&'static str, Vec<String>>,
+}
+
+impl UnusedReport {
+    pub fn is_empty(&self) -> bool {
+        self.unreferenced.is_empty() && self.transitively_unused.is_empty()
+    }
+
+    /// Number of unused tokens of both kinds.
+    pub fn len(&self) -> usize {
+        self.unreferenced
+            .values()
+            .chain(self.transitively_unused.values())
+            .map(Vec::len)
+            .sum()
+    }
+}
+
+/// Variable names referenced by a value, as token paths.
+pub(super) fn value_refs(value: &str) -> impl Iterator<Item = String> {
+    var_calls(value)
+        .into_iter()
+        .filter_map(|(_, _, name)| name.strip_prefix("--").map(css_var_to_path))
+        .filter(|path| !path.is_empty())
+}
+
+/// Every token path with the token paths its value references.
+pub(super) struct TokenReferences<'a> {
+    values: IndexMap<String, &'a TokenValue>,
+    /// Referenced paths per token, scale steps resolved to their scale.
+    pub refs: IndexMap<String, Vec<String>>,
+}
+
+impl<'a> TokenReferences<'a> {
+    pub fn new(tokens: &'a DesignTokens) -> Self {
+        let values: IndexMap<String, &TokenValue> = tokens
+            .categories()
+            .into_iter()
+            .flat_map(|(prefix, map)| {
+                map.iter()
+                    .map(move |(name, value)| (format!("{}.{}", prefix, name), value))
+            })
+            .collect();
+        let mut references = Self {
+            values,
+            refs: IndexMap::new(),
+        };
+        references.refs = references
+            .values
+            .iter()
+            .map(|(path, value)| {
+                let strings: Vec<&String> = match value {
+                    TokenValue::Simple(v) => vec![v],
+                    TokenValue::Adaptive { light, dark } => vec![light, dark],
+                    TokenValue::Scale(steps) => steps.values().collect(),
+                };
+                let mut targets: Vec<String> = Vec::new();
+                for target in strings.into_iter().flat_map(|v| value_refs(v)) {
+                    if let Some(target) = references.resolve(&target)
+                        && target != *path
+                        && !targets.contains(&target)
+                    {
+                        targets.push(target);
+                    }
+                }
+                (path.clone(), targets)
+            })
+            .collect();
+        references
+    }
+
+    /// The token a path names. `colors.blue-500` refers to step 500 of the
+    /// `colors.blue` scale.
+    pub fn resolve(&self, path: &str) -> Option<String> {
+        if self.values.contains_key(path) {
+            return Some(path.to_string());
+        }
+        let (base, _) = path.rsplit_once('-')?;
+        matches!(self.values.get(base), Some(TokenValue::Scale(_))).then(|| base.to_string())
+    }
+}
+
+impl TokenUsageTracker {
+    /// Mark the references of every component style as used.
+    pub fn scan_component_styles(&mut self, styles: &ComponentStyles) {
+        for style in styles.styles.values() {
+            self.scan_component_style(style);
+        }
+    }
+
+    /// Mark the references in a style's base, state and breakpoint properties.
+    pub fn scan_component_style(&mut self, style: &ComponentStyle) {
+        self.mark_from_css(&style.base.to_css());
+        for props in style.states.values().chain(style.breakpoints.values()) {
+            self.mark_from_css(&props.to_css());
+        }
+    }
+
+    /// Tokens not reachable from the marked paths.
+    pub fn unused_report(&self, tokens: &DesignTokens) -> UnusedReport {
+        let references = TokenReferences::new(tokens);
+        let refs = &references.refs;
+
+        let mut live: HashSet<String> = HashSet::new();
+        let mut stack: Vec<String> = self
+            .used
+            .iter()
+            .filter_map(|p| references.resolve(p))
+            .collect();
+        while let Some(path) = stack.pop() {
+            if live.insert(path.clone())
+                && let Some(targets) = refs.get(path.as_str())
+            {
+                stack.extend(targets.iter().cloned());
+            }
+        }
+        let referenced: HashSet<&String> = refs.values().flatten().collect();
+
+        let mut report = UnusedReport::default();
+        for (prefix, map) in tokens.categories() {
+            for name in map.keys() {
+                let path = format!("{}.{}", prefix, name);
+                if live.contains(&path) {
+                    continue;
+                }
+                let group = if referenced.contains(&path) {
+                    &mut report.transitively_unused
+                } else {
+                    &mut report.unreferenced
+                };
+                group.entry(prefix).or_default().push(path);
+            }
+        }
+        report
+    }
+}
+
+impl CssDesigner {
+    /// Tokens that no component style uses, directly or through other tokens.
+    pub fn unused_token_report(&self, styles: &ComponentStyles) -> UnusedReport {
+        let mut tracker = TokenUsageTracker::new();
+        tracker.scan_component_styles(styles);
+        tracker.unused_report(&self.tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{Breakpoint, StateVariant, StyleProperties};
+
+    fn simple(v: &str) -> TokenValue {
+        TokenValue::Simple(v.to_string())
+    }
+
+    fn designer() -> CssDesigner {
+        let mut designer = CssDesigner::new();
+        let tokens = &mut designer.tokens;
+        tokens.colors.insert("brand".to_string(), simple("#2563eb"));
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("var(--color-brand)"));
+        tokens.colors.insert(
+            "blue".to_string(),
+            TokenValue::Scale(IndexMap::from([
+                ("500".to_string(), "#3b82f6".to_string()),
+                ("700".to_string(), "#1d4ed8".to_string()),
+            ])),
+        );
+        // ghost -> shade -> ink, and nothing uses ghost.
+        tokens
+            .colors
+            .insert("ghost".to_string(), simple("var(--color-shade)"));
+        tokens.colors.insert(
+            "shade".to_string(),
+            TokenValue::Adaptive {
+                light: "var(--color-ink)".to_string(),
+                dark: "#000".to_string(),
+            },
+        );
+        tokens.colors.insert("ink".to_string(), simple("#111"));
+        tokens.spacing.insert("md".to_string(), simple("1rem"));
+        tokens.spacing.insert("lg".to_string(), simple("1.5rem"));
+        tokens.radius.insert("lg".to_string(), simple("12px"));
+        tokens
+            .radius
+            .insert("loop".to_string(), simple("var(--radius-loop)"));
+        tokens.z_index.insert("modal".to_string(), simple("100"));
+        designer
+    }
+
+    fn styles() -> ComponentStyles {
+        let mut button = ComponentStyle::default();
+        button.base.background = Some("var(--color-primary)".to_string());
+        button.states.insert(
+            StateVariant::Hover,
+            StyleProperties {
+                background: Some("var(--color-blue-700)".to_string()),
+                padding: Some("var(--spacing-md, 8px)".to_string()),
+                ..Default::default()
+            },
+        );
+        button.breakpoints.insert(
+            Breakpoint::Md,
+            StyleProperties {
+                border_radius: Some("var(--radius-lg)".to_string()),
+                ..Default::default()
+            },
+        );
+        let mut styles = ComponentStyles::new();
+        styles.set("button".to_string(), button);
+        styles
+    }
+
+    #[test]
+    fn test_scan_component_styles() {
+        let mut tracker = TokenUsageTracker::new();
+        tracker.scan_component_styles(&styles());
+        let mut used: Vec<&str> = tracker.used.iter().map(String::as_str).collect();
+        used.sort();
+        assert_eq!(
+            used,
+            [
+                "colors.blue-700",
+                "colors.primary",
+                "radius.lg",
+                "spacing.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused_token_report() {
+        let report = designer().unused_token_report(&styles());
+        assert_eq!(
+            report.unreferenced,
+            IndexMap::from([
+                ("colors", vec!["colors.ghost".to_string()]),
+                ("spacing", vec!["spacing.lg".to_string()]),
+                ("radius", vec!["radius.loop".to_string()]),
+                ("z-index", vec!["z-index.modal".to_string()]),
+            ])
+        );
+        // brand stays live through primary; shade and ink only hang off ghost.
+        assert_eq!(
+            report.transitively_unused,
+            IndexMap::from([(
+                "colors",
+                vec!["colors.shade".to_string(), "colors.ink".to_string()]
+            )])
+        );
+        assert_eq!(report.len(), 6);
+    }
+
+    #[test]
+    fn test_unused_cycle_is_transitive() {
+        let mut designer = CssDesigner::new();
+        designer
+            .tokens
+            .spacing
+            .insert("a".to_string(), simple("var(--spacing-b)"));
+        designer
+            .tokens
+            .spacing
+            .insert("b".to_string(), simple("var(--spacing-a)"));
+        let report = designer.unused_token_report(&ComponentStyles::new());
+        assert!(report.unreferenced.is_empty());
+        assert_eq!(
+            report.transitively_unused["spacing"],
+            ["spacing.a", "spacing.b"]
+        );
+
+        let empty = CssDesigner::new().unused_token_report(&styles());
+        assert!(empty.is_empty());
+    }
+}
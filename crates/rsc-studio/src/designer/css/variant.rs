@@ -0,0 +1,460 @@
+//! Size and tone variants generated from a base component style.
+//!
+//! A variant such as `button-sm` extends its base and only stores the
+//! properties that differ: padding and font-size tokens moved along their
+//! scales, or color tokens swapped for the tone. The values written are
+//! remembered in [`GeneratedVariant`], so regenerating updates what the
+//! generator wrote and leaves later hand edits alone.
+
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+
+use super::extract::var_calls;
+use super::{
+    ComponentStyle, ComponentStyles, DesignTokens, StateVariant, StyleProperties, css_var_to_path,
+    path_to_css_var,
+};
+
+/// Properties whose spacing and font-size tokens follow size steps.
+const SIZE_PROPERTIES: &[&str] = &[
+    "padding",
+    "padding-top",
+    "padding-right",
+    "padding-bottom",
+    "padding-left",
+    "gap",
+    "font-size",
+];
+
+/// Marker on a generated variant.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedVariant {
+    /// Component the variant was generated from.
+    pub from: String,
+    /// Values as last generated, keyed like `padding` or `hover:background`.
+    #[serde(default)]
+    pub values: IndexMap<String, String>,
+}
+
+/// A size variant: `steps` moves tokens up (positive) or down the scale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SizeStep {
+    pub name: String,
+    pub steps: i32,
+}
+
+/// A tone variant: color token paths to swap, e.g. `colors.primary` to
+/// `colors.danger`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToneMapping {
+    pub name: String,
+    pub swaps: IndexMap<String, String>,
+}
+
+/// Which variants to generate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariantSpec {
+    #[serde(default)]
+    pub sizes: Vec<SizeStep>,
+    #[serde(default)]
+    pub tones: Vec<ToneMapping>,
+}
+
+impl VariantSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_size(mut self, name: impl Into<String>, steps: i32) -> Self {
+        self.sizes.push(SizeStep {
+            name: name.into(),
+            steps,
+        });
+        self
+    }
+
+    pub fn with_tone<'a>(
+        mut self,
+        name: impl Into<String>,
+        swaps: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.tones.push(ToneMapping {
+            name: name.into(),
+            swaps: swaps
+                .into_iter()
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .collect(),
+        });
+        self
+    }
+}
+
+/// Result of applying generated variants.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariantReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    /// Hand-edited values left in place, as `component/key`.
+    pub kept: Vec<String>,
+    /// Existing styles with a variant's name that weren't generated.
+    pub skipped: Vec<String>,
+}
+
+/// Move a spacing or font-size token `steps` along its scale, clamping
+/// at the ends. Other tokens are returned unchanged.
+pub fn step_token(path: &str, steps: i32, tokens: &DesignTokens) -> String {
+    let scale = ["spacing", "typography.sizes"]
+        .into_iter()
+        .find_map(|prefix| {
+            let name = path.strip_prefix(prefix)?.strip_prefix('.')?;
+            let (_, map) = tokens
+                .categories()
+                .into_iter()
+                .find(|(p, _)| *p == prefix)?;
+            Some((prefix, map, map.get_index_of(name)?))
+        });
+    let Some((prefix, map, index)) = scale else {
+        return path.to_string();
+    };
+    let target = (index as i64 + steps as i64).clamp(0, map.len() as i64 - 1) as usize;
+    let (name, _) = map.get_index(target).expect("index within scale");
+    format!("{}.{}", prefix, name)
+}
+
+/// Rewrite the token `var()` references in a value, all at once.
+fn map_refs(value: &str, map: impl Fn(&str) -> String) -> String {
+    let mut out = value.to_string();
+    let mut calls = var_calls(value);
+    calls.sort_by_key(|(start, _, _)| *start);
+    for (start, end, name) in calls.into_iter().rev() {
+        let Some(var) = name.strip_prefix("--") else {
+            continue;
+        };
+        let path = css_var_to_path(var);
+        if path.is_empty() {
+            continue;
+        }
+        let Some(to) = path_to_css_var(&map(&path)).filter(|to| *to != name) else {
+            continue;
+        };
+        if let Some(offset) = value[start + 4..end].find(&name) {
+            let at = start + 4 + offset;
+            out.replace_range(at..at + name.len(), &to);
+        }
+    }
+    out
+}
+
+/// Base and state properties, keyed like `padding` or `hover:padding`.
+fn flatten(style: &ComponentStyle) -> IndexMap<String, String> {
+    let mut values = IndexMap::new();
+    let scopes =
+        std::iter::once((None, &style.base)).chain(style.states.iter().map(|(s, p)| (Some(s), p)));
+    for (state, props) in scopes {
+        for name in StyleProperties::PROPERTY_NAMES {
+            if let Some(value) = props.get(name) {
+                values.insert(key(state, name), value.clone());
+            }
+        }
+    }
+    values
+}
+
+fn key(state: Option<&StateVariant>, property: &str) -> String {
+    match state {
+        Some(state) => format!("{}:{}", state.label().to_lowercase(), property),
+        None => property.to_string(),
+    }
+}
+
+fn set_flat(style: &mut ComponentStyle, key: &str, value: Option<String>) {
+    match key.split_once(':') {
+        Some((state, property)) => {
+            let Some(state) = StateVariant::all()
+                .iter()
+                .find(|s| s.label().eq_ignore_ascii_case(state))
+            else {
+                return;
+            };
+            match style.states.get_mut(state) {
+                Some(props) => props.set(property, value),
+                None if value.is_some() => {
+                    let mut props = StyleProperties::default();
+                    props.set(property, value);
+                    style.states.insert(*state, props);
+                }
+                None => {}
+            }
+        }
+        None => style.base.set(key, value),
+    }
+}
+
+impl ComponentStyles {
+    /// Generate size and tone variants of `component`, named
+    /// `{component}-{variant}`. Each extends the component and holds only
+    /// the properties that changed. Breakpoint overrides are inherited
+    /// as-is.
+    pub fn generate_variants(
+        &self,
+        component: &str,
+        spec: &VariantSpec,
+        tokens: &DesignTokens,
+    ) -> Vec<(String, ComponentStyle)> {
+        let Some(base) = self.resolve(component) else {
+            return Vec::new();
+        };
+        let base_values = flatten(&base);
+
+        let sizes = spec.sizes.iter().map(|size| {
+            let values = base_values
+                .iter()
+                .filter(|(key, _)| {
+                    let property = key.rsplit(':').next().unwrap_or_default();
+                    SIZE_PROPERTIES.contains(&property)
+                })
+                .map(|(key, value)| {
+                    let stepped = map_refs(value, |path| step_token(path, size.steps, tokens));
+                    (key.clone(), stepped)
+                })
+                .collect();
+            (&size.name, values)
+        });
+        let tones = spec.tones.iter().map(|tone| {
+            let values = base_values
+                .iter()
+                .map(|(key, value)| {
+                    let swapped = map_refs(value, |path| {
+                        tone.swaps
+                            .get(path)
+                            .cloned()
+                            .unwrap_or_else(|| path.to_string())
+                    });
+                    (key.clone(), swapped)
+                })
+                .collect();
+            (&tone.name, values)
+        });
+
+        sizes
+            .chain(tones)
+            .map(|(name, values): (&String, IndexMap<String, String>)| {
+                let values: IndexMap<String, String> = values
+                    .into_iter()
+                    .filter(|(key, value)| base_values.get(key) != Some(value))
+                    .collect();
+                let mut style = ComponentStyle {
+                    extends: Some(component.to_string()),
+                    include_in_catalog: base.include_in_catalog,
+                    ..Default::default()
+                };
+                for (key, value) in &values {
+                    set_flat(&mut style, key, Some(value.clone()));
+                }
+                style.generated = Some(GeneratedVariant {
+                    from: component.to_string(),
+                    values,
+                });
+                (format!("{}-{}", component, name), style)
+            })
+            .collect()
+    }
+
+    /// Add or update generated variants. A value is only overwritten if it
+    /// still matches what was generated last time; styles of the same name
+    /// that weren't generated are left alone.
+    pub fn apply_variants(&mut self, variants: Vec<(String, ComponentStyle)>) -> VariantReport {
+        let mut report = VariantReport::default();
+        for (name, variant) in variants {
+            let Some(existing) = self.styles.get_mut(&name) else {
+                self.styles.insert(name.clone(), variant);
+                report.created.push(name);
+                continue;
+            };
+            let Some(previous) = existing.generated.take() else {
+                report.skipped.push(name);
+                continue;
+            };
+            let generated = variant.generated.unwrap_or_default();
+            let current = flatten(existing);
+            let keys: IndexSet<&String> = previous
+                .values
+                .keys()
+                .chain(generated.values.keys())
+                .collect();
+            let mut changed = false;
+            for key in keys {
+                if current.get(key) != previous.values.get(key) {
+                    report.kept.push(format!("{}/{}", name, key));
+                    continue;
+                }
+                let value = generated.values.get(key);
+                if current.get(key) != value {
+                    set_flat(existing, key, value.cloned());
+                    changed = true;
+                }
+            }
+            existing.extends = variant.extends;
+            existing.generated = Some(generated);
+            if changed {
+                report.updated.push(name);
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+
+    fn tokens() -> DesignTokens {
+        let mut tokens = DesignTokens::default();
+        for (name, value) in [("xs", "4px"), ("sm", "8px"), ("md", "12px"), ("lg", "16px")] {
+            tokens
+                .spacing
+                .insert(name.to_string(), TokenValue::Simple(value.to_string()));
+        }
+        for (name, value) in [("sm", "14px"), ("md", "16px"), ("lg", "18px")] {
+            tokens
+                .typography
+                .sizes
+                .insert(name.to_string(), TokenValue::Simple(value.to_string()));
+        }
+        tokens
+    }
+
+    fn button() -> ComponentStyles {
+        let mut styles = ComponentStyles::new();
+        let mut button = ComponentStyle {
+            base: StyleProperties {
+                padding: Some("var(--spacing-sm) var(--spacing-md)".to_string()),
+                font_size: Some("var(--font-size-md)".to_string()),
+                background: Some("var(--color-primary)".to_string()),
+                color: Some("var(--color-primary-foreground)".to_string()),
+                border_radius: Some("6px".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        button.states.insert(
+            StateVariant::Hover,
+            StyleProperties {
+                background: Some("var(--color-primary-hover)".to_string()),
+                ..Default::default()
+            },
+        );
+        styles.set("button".to_string(), button);
+        styles
+    }
+
+    fn spec() -> VariantSpec {
+        VariantSpec::new()
+            .with_size("sm", -1)
+            .with_size("lg", 1)
+            .with_size("xl", 5)
+            .with_tone(
+                "danger",
+                [
+                    ("colors.primary", "colors.danger"),
+                    ("colors.primary-hover", "colors.danger-hover"),
+                    ("colors.primary-foreground", "colors.danger-foreground"),
+                ],
+            )
+    }
+
+    #[test]
+    fn test_size_steps_follow_scales() {
+        let tokens = tokens();
+        assert_eq!(step_token("spacing.sm", 1, &tokens), "spacing.md");
+        assert_eq!(step_token("spacing.sm", -3, &tokens), "spacing.xs");
+        assert_eq!(
+            step_token("typography.sizes.md", 4, &tokens),
+            "typography.sizes.lg"
+        );
+        assert_eq!(step_token("colors.primary", 1, &tokens), "colors.primary");
+        assert_eq!(step_token("spacing.missing", 1, &tokens), "spacing.missing");
+
+        let variants = button().generate_variants("button", &spec(), &tokens);
+        let names: Vec<&str> = variants.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            ["button-sm", "button-lg", "button-xl", "button-danger"]
+        );
+
+        // Both padding tokens move at once, without chaining sm -> xs.
+        let sm = &variants[0].1.base;
+        assert_eq!(
+            sm.padding.as_deref(),
+            Some("var(--spacing-xs) var(--spacing-sm)")
+        );
+        assert_eq!(sm.font_size.as_deref(), Some("var(--font-size-sm)"));
+        assert_eq!(sm.background, None);
+        let xl = &variants[2].1.base;
+        assert_eq!(
+            xl.padding.as_deref(),
+            Some("var(--spacing-lg) var(--spacing-lg)")
+        );
+        assert_eq!(xl.font_size.as_deref(), Some("var(--font-size-lg)"));
+
+        let danger = &variants[3].1;
+        assert_eq!(danger.base.padding, None);
+        assert_eq!(
+            danger.base.background.as_deref(),
+            Some("var(--color-danger)")
+        );
+        assert_eq!(
+            danger.states[&StateVariant::Hover].background.as_deref(),
+            Some("var(--color-danger-hover)")
+        );
+    }
+
+    #[test]
+    fn test_variants_extend_base() {
+        let tokens = tokens();
+        let mut styles = button();
+        let variants = styles.generate_variants("button", &spec(), &tokens);
+        let report = styles.apply_variants(variants);
+        assert_eq!(report.created.len(), 4);
+        assert_eq!(
+            styles.styles["button-sm"].extends.as_deref(),
+            Some("button")
+        );
+
+        // A later base edit shows up in the variant.
+        styles.get_mut("button").unwrap().base.border_radius = Some("9999px".to_string());
+        let sm = styles.resolve("button-sm").unwrap();
+        assert_eq!(sm.base.border_radius.as_deref(), Some("9999px"));
+        assert_eq!(sm.base.background.as_deref(), Some("var(--color-primary)"));
+        assert_eq!(
+            sm.states[&StateVariant::Hover].background.as_deref(),
+            Some("var(--color-primary-hover)")
+        );
+        let css = styles.generate_css();
+        assert!(css.contains(".button-sm {\n  padding: var(--spacing-xs) var(--spacing-sm);"));
+        assert!(css.contains(".button-danger:hover {\n  background: var(--color-danger-hover);"));
+    }
+
+    #[test]
+    fn test_regeneration_keeps_manual_tweaks() {
+        let tokens = tokens();
+        let mut styles = button();
+        styles.set("button-lg".to_string(), ComponentStyle::default());
+        let report = styles.apply_variants(styles.generate_variants("button", &spec(), &tokens));
+        assert_eq!(report.skipped, ["button-lg"]);
+
+        styles.get_mut("button-sm").unwrap().base.font_size = Some("13px".to_string());
+        let button = styles.get_mut("button").unwrap();
+        button.base.padding = Some("var(--spacing-md)".to_string());
+        button.base.font_size = Some("var(--font-size-lg)".to_string());
+
+        let report = styles.apply_variants(styles.generate_variants("button", &spec(), &tokens));
+        assert_eq!(report.updated, ["button-sm", "button-xl"]);
+        assert_eq!(report.kept, ["button-sm/font-size"]);
+        let sm = &styles.styles["button-sm"].base;
+        assert_eq!(sm.padding.as_deref(), Some("var(--spacing-sm)"));
+        assert_eq!(sm.font_size.as_deref(), Some("13px"));
+        assert!(styles.styles["button-lg"].generated.is_none());
+    }
+}
@@ -0,0 +1,385 @@
+//! Simulated preview widths.
+//!
+//! The preview iframe is as wide as the panel, so width media queries in
+//! component CSS would follow the panel rather than the breakpoint being
+//! designed. [`simulate_width`] evaluates them against a chosen width
+//! instead: matching blocks are flattened into the surrounding CSS and the
+//! rest are dropped.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Breakpoint, CssDesigner};
+use crate::preview::BASE_FRAME_WIDTH;
+
+/// Pixels per `em`/`rem` in media queries.
+const EM_PX: f64 = 16.0;
+
+/// Width the preview simulates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreviewViewport {
+    /// The minimum width of a breakpoint; [`BASE_FRAME_WIDTH`] for the base.
+    Breakpoint(Breakpoint),
+    /// A width in CSS pixels.
+    Custom(u32),
+}
+
+impl PreviewViewport {
+    /// Width in CSS pixels.
+    pub fn width(&self) -> u32 {
+        match self {
+            PreviewViewport::Breakpoint(b) => b.min_width().unwrap_or(BASE_FRAME_WIDTH),
+            PreviewViewport::Custom(width) => *width,
+        }
+    }
+
+    /// The widest breakpoint active at this width.
+    pub fn breakpoint(&self) -> Breakpoint {
+        let width = self.width();
+        Breakpoint::all()
+            .iter()
+            .copied()
+            .rfind(|b| b.min_width().unwrap_or(0) <= width)
+            .unwrap_or_default()
+    }
+
+    /// Toolbar label, e.g. `MD · 768px`.
+    pub fn label(&self) -> String {
+        match self {
+            PreviewViewport::Breakpoint(b) => format!("{} · {}px", b.label(), self.width()),
+            PreviewViewport::Custom(width) => format!("{}px", width),
+        }
+    }
+}
+
+impl CssDesigner {
+    /// Width of the preview frame; `None` when the preview fills the panel.
+    pub fn preview_width(&self) -> Option<u32> {
+        self.viewport.map(|viewport| viewport.width())
+    }
+
+    /// Preview CSS with width media queries resolved for the viewport.
+    pub fn simulate_viewport(&self, css: &str) -> String {
+        match self.preview_width() {
+            Some(width) => simulate_width(css, width),
+            None => css.to_string(),
+        }
+    }
+}
+
+/// Resolve `min-width` and `max-width` media queries, including nested
+/// ones, as if the viewport were `width` pixels wide.
+///
+/// Queries on other features, such as `prefers-color-scheme`, are kept
+/// with their width conditions removed.
+pub fn simulate_width(css: &str, width: u32) -> String {
+    let mut out = String::new();
+    simulate_block(css, f64::from(width), &mut out);
+    out
+}
+
+/// What a media query comes to at the simulated width.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Match,
+    Drop,
+    /// Depends on other features; the query left to check.
+    Keep(String),
+}
+
+fn simulate_block(css: &str, width: f64, out: &mut String) {
+    let mut rest = css;
+    while let Some(open) = next_brace(rest, 0)
+        .filter(|&(_, b)| b == b'{')
+        .map(|(i, _)| i)
+    {
+        let Some(close) = matching_brace(rest, open) else {
+            break;
+        };
+        // Declarations and comments before a nested rule stay as they are.
+        let prelude = &rest[..open];
+        let split = [
+            prelude.rfind(';').map(|i| i + 1),
+            prelude.rfind("*/").map(|i| i + 2),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(0);
+        let (head, prelude) = prelude.split_at(split);
+        out.push_str(head);
+        let body = &rest[open + 1..close];
+        let selector = prelude.trim_start();
+        let lead = &prelude[..prelude.len() - selector.len()];
+
+        match selector.strip_prefix("@media") {
+            Some(query) => match evaluate_query(query.trim(), width) {
+                Outcome::Match => {
+                    out.push_str(lead);
+                    simulate_block(&dedent(body), width, out);
+                }
+                Outcome::Drop => {}
+                Outcome::Keep(query) => {
+                    out.push_str(lead);
+                    out.push_str(&format!("@media {} {{", query));
+                    simulate_block(body, width, out);
+                    out.push('}');
+                }
+            },
+            None => {
+                out.push_str(prelude);
+                out.push('{');
+                simulate_block(body, width, out);
+                out.push('}');
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+}
+
+fn evaluate_query(query: &str, width: f64) -> Outcome {
+    let mut kept = Vec::new();
+    for branch in query.split(',') {
+        match evaluate_branch(branch.trim(), width) {
+            Outcome::Match => return Outcome::Match,
+            Outcome::Drop => {}
+            Outcome::Keep(branch) => kept.push(branch),
+        }
+    }
+    if kept.is_empty() {
+        Outcome::Drop
+    } else {
+        Outcome::Keep(kept.join(", "))
+    }
+}
+
+fn evaluate_branch(branch: &str, width: f64) -> Outcome {
+    if branch.starts_with("not ") {
+        return Outcome::Keep(branch.to_string());
+    }
+    let branch = branch.strip_prefix("only ").unwrap_or(branch);
+    let mut rest = Vec::new();
+    for part in branch.split(" and ") {
+        match part.trim() {
+            "" | "all" | "screen" => {}
+            "print" => return Outcome::Drop,
+            feature => match width_feature(feature, width) {
+                Some(true) => {}
+                Some(false) => return Outcome::Drop,
+                None => rest.push(feature),
+            },
+        }
+    }
+    if rest.is_empty() {
+        Outcome::Match
+    } else {
+        Outcome::Keep(rest.join(" and "))
+    }
+}
+
+/// Whether a width feature such as `(min-width: 768px)` or
+/// `(width < 1024px)` holds; `None` for other features.
+fn width_feature(feature: &str, width: f64) -> Option<bool> {
+    let inner = feature.strip_prefix('(')?.strip_suffix(')')?.trim();
+    if let Some((name, value)) = inner.split_once(':') {
+        let px = length_px(value)?;
+        return match name.trim() {
+            "min-width" => Some(width >= px),
+            "max-width" => Some(width <= px),
+            "width" => Some(width == px),
+            _ => None,
+        };
+    }
+    for op in [">=", "<=", ">", "<", "="] {
+        let Some((left, right)) = inner.split_once(op) else {
+            continue;
+        };
+        let (left, right) = (left.trim(), right.trim());
+        let (value, flipped) = match (left, right) {
+            ("width", value) => (value, false),
+            (value, "width") => (value, true),
+            _ => return None,
+        };
+        let px = length_px(value)?;
+        let (a, b) = if flipped { (px, width) } else { (width, px) };
+        return Some(match op {
+            ">=" => a >= b,
+            "<=" => a <= b,
+            ">" => a > b,
+            "<" => a < b,
+            _ => a == b,
+        });
+    }
+    None
+}
+
+fn length_px(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (number, scale) = if let Some(n) = value.strip_suffix("px") {
+        (n, 1.0)
+    } else if let Some(n) = value
+        .strip_suffix("rem")
+        .or_else(|| value.strip_suffix("em"))
+    {
+        (n, EM_PX)
+    } else {
+        (value, 1.0)
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (scale != 1.0 || value.ends_with("px") || number == 0.0).then_some(number * scale)
+}
+
+/// The next `{` or `}` at or after `from`, skipping comments and strings.
+fn next_brace(css: &str, from: usize) -> Option<(usize, u8)> {
+    let bytes = css.as_bytes();
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'}' => return Some((i, bytes[i])),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = css[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| i + 2 + end + 2);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn matching_brace(css: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut from = open;
+    while let Some((i, brace)) = next_brace(css, from) {
+        if brace == b'{' {
+            depth += 1;
+        } else {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+        from = i + 1;
+    }
+    None
+}
+
+/// A media block's body without its blank edges and one indent level.
+fn dedent(body: &str) -> String {
+    body.trim_matches(|c| c == '\n' || c == '\r')
+        .lines()
+        .map(|line| line.strip_prefix("  ").unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSS: &str = "\
+.card { padding: 8px; }
+
+@media (min-width: 768px) {
+  .card { padding: 16px; }
+}
+
+@media (min-width: 1024px) {
+  .card { padding: 24px; }
+}
+
+@media screen and (max-width: 1023px) {
+  .card { border: none; }
+  @media (min-width: 768px) {
+    .card { gap: 4px; }
+  }
+}
+";
+
+    #[test]
+    fn test_widths_around_md_and_lg() {
+        assert_eq!(
+            simulate_width(CSS, 767),
+            "\
+.card { padding: 8px; }
+
+.card { border: none; }
+"
+        );
+        assert_eq!(
+            simulate_width(CSS, 768),
+            "\
+.card { padding: 8px; }
+
+.card { padding: 16px; }
+
+.card { border: none; }
+.card { gap: 4px; }
+"
+        );
+        assert_eq!(simulate_width(CSS, 1023), simulate_width(CSS, 768));
+        assert_eq!(
+            simulate_width(CSS, 1024),
+            "\
+.card { padding: 8px; }
+
+.card { padding: 16px; }
+
+.card { padding: 24px; }
+"
+        );
+    }
+
+    #[test]
+    fn test_other_features_kept() {
+        let css = "\
+@media (prefers-color-scheme: dark) and (min-width: 48em) {
+  .card { color: white; }
+}
+@media print, (width >= 1024px) {
+  .card { color: black; }
+}
+/* nested rule */ .list { @media (max-width: 600px) { gap: 0; } color: red; }
+";
+        assert_eq!(
+            simulate_width(css, 800),
+            "\
+@media (prefers-color-scheme: dark) {
+  .card { color: white; }
+}
+/* nested rule */ .list { color: red; }
+"
+        );
+        assert_eq!(
+            simulate_width(css, 360),
+            "\n/* nested rule */ .list {  gap: 0;  color: red; }\n"
+        );
+    }
+
+    #[test]
+    fn test_viewport() {
+        let md = PreviewViewport::Breakpoint(Breakpoint::Md);
+        assert_eq!(md.width(), 768);
+        assert_eq!(md.label(), "MD · 768px");
+        assert_eq!(PreviewViewport::Custom(1100).breakpoint(), Breakpoint::Lg);
+        assert_eq!(
+            PreviewViewport::Breakpoint(Breakpoint::Base).breakpoint(),
+            Breakpoint::Base
+        );
+
+        let mut designer = CssDesigner::new();
+        assert_eq!(designer.preview_width(), None);
+        assert_eq!(designer.simulate_viewport(CSS), CSS);
+        designer.viewport = Some(md);
+        assert_eq!(designer.preview_width(), Some(768));
+        assert_eq!(designer.simulate_viewport(CSS), simulate_width(CSS, 768));
+    }
+}
@@ -0,0 +1,294 @@
+//! Drop previews for the navigation designer and the workflow list.
+//!
+//! Preview functions capture a snapshot of the store's workflows when they
+//! are registered, at drag start, and describe the move a drop would make.
+//! The workflow list also sets its touch activation here, so that swiping
+//! through the list scrolls rather than reorders.
+
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use rsc_dnd::{ActivationConstraint, DndContext, DragPayload, DropPreview};
+
+use crate::designer::NavigationDesigner;
+use crate::entity::{Context, EntityId, Preset, Workflow};
+use crate::store::StudioStore;
+
+/// How long a touch must hold a workflow row before it drags, in
+/// milliseconds.
+pub const WORKFLOW_LIST_LONG_PRESS_MS: u64 = 200;
+
+/// How far a held touch may drift vertically before the list scrolls
+/// instead.
+pub const WORKFLOW_LIST_TOUCH_TOLERANCE: f64 = 8.0;
+
+/// A navigation entity and its ancestors.
+enum Located<'a> {
+    Workflow(&'a Workflow),
+    Context(&'a Workflow, &'a Context),
+    Preset(&'a Context, &'a Preset),
+}
+
+fn locate<'a>(workflows: &'a IndexMap<EntityId, Workflow>, id: &str) -> Option<Located<'a>> {
+    if let Some(workflow) = workflows.get(id) {
+        return Some(Located::Workflow(workflow));
+    }
+    for workflow in workflows.values() {
+        if let Some(context) = workflow.contexts.get(id) {
+            return Some(Located::Context(workflow, context));
+        }
+        for context in workflow.contexts.values() {
+            if let Some(preset) = context.presets.get(id) {
+                return Some(Located::Preset(context, preset));
+            }
+        }
+    }
+    None
+}
+
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// Preview a drop on the navigation canvas: contexts move between
+/// workflows, presets between contexts.
+pub fn nav_drop_preview(
+    workflows: &IndexMap<EntityId, Workflow>,
+    payload: &DragPayload,
+) -> DropPreview {
+    let (Some(dragged), Some(target)) = (
+        locate(workflows, &payload.active),
+        locate(workflows, &payload.over),
+    ) else {
+        return DropPreview::blocked("Nothing to move");
+    };
+
+    match (dragged, target) {
+        (Located::Workflow(_), _) => DropPreview::blocked("Workflows can't be nested"),
+        (Located::Context(from, context), Located::Workflow(to)) => {
+            if from.id == to.id {
+                return DropPreview::blocked(format!(
+                    "'{}' is already in '{}'",
+                    context.name, to.name
+                ));
+            }
+            let mut preview =
+                DropPreview::ok(format!("Move '{}' into '{}'", context.name, to.name))
+                    .with_detail(format!("{} will be re-pointed", plural(1, "edge", "edges")));
+            if !context.presets.is_empty() {
+                preview = preview.with_detail(format!(
+                    "{} will move with it",
+                    plural(context.presets.len(), "preset", "presets")
+                ));
+            }
+            if to.contexts.values().any(|c| c.name == context.name) {
+                preview = preview.with_warning(format!(
+                    "'{}' already has a context named '{}'",
+                    to.name, context.name
+                ));
+            }
+            if from.default_context.as_deref() == Some(context.id.as_str()) {
+                preview =
+                    preview.with_warning(format!("'{}' will lose its default context", from.name));
+            }
+            preview
+        }
+        (Located::Preset(from, preset), Located::Context(_, to)) => {
+            if from.id == to.id {
+                return DropPreview::blocked(format!(
+                    "'{}' is already in '{}'",
+                    preset.name, to.name
+                ));
+            }
+            let mut preview = DropPreview::ok(format!("Move '{}' into '{}'", preset.name, to.name))
+                .with_detail(format!("{} will be re-pointed", plural(1, "edge", "edges")));
+            if from.default_preset.as_deref() == Some(preset.id.as_str()) {
+                preview =
+                    preview.with_warning(format!("'{}' will lose its default preset", from.name));
+            }
+            preview
+        }
+        (Located::Context(..), _) => {
+            DropPreview::blocked("Contexts can only be dropped on a workflow")
+        }
+        (Located::Preset(..), _) => {
+            DropPreview::blocked("Presets can only be dropped on a context")
+        }
+    }
+}
+
+/// Preview a drop in the workflow list, where dropping reorders.
+pub fn workflow_list_preview(
+    workflows: &IndexMap<EntityId, Workflow>,
+    payload: &DragPayload,
+) -> DropPreview {
+    let (Some((from, dragged)), Some((to, target))) = (
+        workflows.get_full(&payload.active).map(|(i, _, w)| (i, w)),
+        workflows.get_full(&payload.over).map(|(i, _, w)| (i, w)),
+    ) else {
+        return DropPreview::blocked("Only workflows can be reordered here");
+    };
+    if from == to {
+        return DropPreview::blocked(format!("'{}' can't be dropped onto itself", dragged.name));
+    }
+    let place = if from < to { "after" } else { "before" };
+    DropPreview::ok(format!(
+        "Move '{}' {} '{}'",
+        dragged.name, place, target.name
+    ))
+    .with_detail(format!(
+        "{} will shift",
+        plural(from.abs_diff(to), "workflow", "workflows")
+    ))
+}
+
+impl NavigationDesigner {
+    /// Register previews for the workflow and context nodes on the canvas,
+    /// against the store's current workflows.
+    pub fn register_drop_previews(&self, dnd: &mut DndContext, store: &StudioStore) {
+        let workflows = Arc::new(store.workflows.clone());
+        for node in self.canvas.nodes.values() {
+            let workflows = workflows.clone();
+            dnd.register_preview(node.id.clone(), move |payload| {
+                nav_drop_preview(&workflows, payload)
+            });
+        }
+    }
+}
+
+/// Register previews for the rows of the workflow list, keyed by workflow id.
+pub fn register_workflow_list_previews(dnd: &mut DndContext, store: &StudioStore) {
+    let workflows = Arc::new(store.workflows.clone());
+    for id in store.workflows.keys() {
+        let workflows = workflows.clone();
+        dnd.register_preview(id.clone(), move |payload| {
+            workflow_list_preview(&workflows, payload)
+        });
+    }
+}
+
+/// Set up `dnd` for the sortable workflow list: previews for its rows and
+/// a long press to start touch drags.
+pub fn setup_workflow_list_dnd(dnd: &mut DndContext, store: &StudioStore) {
+    register_workflow_list_previews(dnd, store);
+    dnd.touch_sensor.activation_constraint = ActivationConstraint::LongPress {
+        delay: WORKFLOW_LIST_LONG_PRESS_MS,
+        tolerance: WORKFLOW_LIST_TOUCH_TOLERANCE,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsc_dnd::{DragEvent, PointerKind, PreviewSeverity};
+
+    fn payload(active: &str, over: &str) -> DragPayload {
+        DragPayload {
+            active: active.to_string(),
+            over: over.to_string(),
+            data: serde_json::Value::Null,
+            target_data: serde_json::Value::Null,
+        }
+    }
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        let mut auth = Workflow::new("Auth flow").with_id("w1");
+        let mut login = Context::new("Login").with_id("c1");
+        login.add_preset(Preset::new("Compact").with_id("p1"));
+        login.add_preset(Preset::new("Wide").with_id("p2"));
+        auth.add_context(login);
+        auth.add_context(Context::new("Signup").with_id("c2"));
+        store.add_workflow(auth);
+        let mut shop = Workflow::new("Shop").with_id("w2");
+        shop.add_context(Context::new("Signup").with_id("c3"));
+        store.add_workflow(shop);
+        store.add_workflow(Workflow::new("Admin").with_id("w3"));
+        store
+    }
+
+    #[test]
+    fn test_nav_preview() {
+        let store = store();
+        let workflows = &store.workflows;
+
+        let preview = nav_drop_preview(workflows, &payload("c1", "w2"));
+        assert_eq!(preview.title, "Move 'Login' into 'Shop'");
+        assert_eq!(
+            preview.details,
+            [
+                "1 edge will be re-pointed",
+                "2 presets will move with it",
+                "'Auth flow' will lose its default context",
+            ]
+        );
+        // `add_context` made Login the default of Auth flow.
+        assert_eq!(preview.severity, PreviewSeverity::Warning);
+
+        let preview = nav_drop_preview(workflows, &payload("c2", "w2"));
+        assert_eq!(preview.severity, PreviewSeverity::Warning);
+        assert!(preview.details[1].contains("already has a context named 'Signup'"));
+
+        assert!(nav_drop_preview(workflows, &payload("c1", "w1")).is_blocked());
+        assert!(nav_drop_preview(workflows, &payload("w1", "w2")).is_blocked());
+        assert!(nav_drop_preview(workflows, &payload("c1", "c3")).is_blocked());
+        assert_eq!(
+            nav_drop_preview(workflows, &payload("p2", "c2")).severity,
+            PreviewSeverity::Ok
+        );
+    }
+
+    #[test]
+    fn test_registered_previews_gate_drops() {
+        let store = store();
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&store.workflows.values().collect::<Vec<_>>());
+        let mut dnd = DndContext::new();
+        designer.register_drop_previews(&mut dnd, &store);
+
+        dnd.start_drag("c1");
+        dnd.set_over(Some("w1".to_string()));
+        assert!(!dnd.end_drag().is_dropped());
+
+        dnd.start_drag("c1");
+        dnd.set_over(Some("w3".to_string()));
+        assert!(dnd.can_drop());
+        assert_eq!(
+            dnd.preview.as_ref().unwrap().title,
+            "Move 'Login' into 'Admin'"
+        );
+        assert!(dnd.end_drag().is_dropped());
+    }
+
+    #[test]
+    fn test_workflow_list_preview() {
+        let store = store();
+        let mut dnd = DndContext::new();
+        setup_workflow_list_dnd(&mut dnd, &store);
+
+        dnd.start_drag("w1");
+        dnd.set_over(Some("w3".to_string()));
+        assert!(dnd.can_drop());
+        let preview = dnd.preview.as_ref().unwrap();
+        assert_eq!(preview.title, "Move 'Auth flow' after 'Admin'");
+        assert_eq!(preview.details, ["2 workflows will shift"]);
+
+        dnd.set_over(Some("w1".to_string()));
+        assert!(!dnd.can_drop());
+        assert_eq!(
+            workflow_list_preview(&store.workflows, &payload("w3", "w2")).title,
+            "Move 'Admin' before 'Shop'"
+        );
+        dnd.end_drag();
+
+        // A touch swiping down the list scrolls it.
+        let start = std::time::Instant::now();
+        dnd.pointer_down("w2", PointerKind::Touch, 0.0, 0.0, start);
+        let later = start + std::time::Duration::from_millis(100);
+        assert!(matches!(
+            dnd.pointer_move(0.0, WORKFLOW_LIST_TOUCH_TOLERANCE + 1.0, later),
+            Some(DragEvent::Cancel { .. })
+        ));
+        assert!(!dnd.is_dragging());
+    }
+}
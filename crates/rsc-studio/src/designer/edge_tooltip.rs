@@ -0,0 +1,442 @@
+//! Hover tooltips for navigation edges.
+//!
+//! [`EdgeTooltip`] gathers what the canvas shows when the pointer rests on
+//! an edge: its endpoints, label, guard condition, layer and imported
+//! weight. The canvas asks for it only once the hover delay has passed,
+//! and the SVG export reuses the same text for `<title>` elements.
+
+use rsc_flow::{Edge, EdgeData, Position};
+
+use crate::designer::NavigationDesigner;
+use crate::export::print::{PageTile, render_svg_pages_overlaid};
+use crate::export::state_machine;
+use crate::store::StudioStore;
+
+/// Hover time before an edge tooltip shows, in milliseconds.
+pub const DEFAULT_TOOLTIP_DELAY_MS: u64 = 400;
+
+/// Words a condition uses that aren't variables.
+const CONDITION_KEYWORDS: &[&str] = &["and", "or", "not", "true", "false", "null"];
+
+/// Two-character operators, matched before single characters.
+const LONG_OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "&&", "||"];
+
+/// Tooltip content for one edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeTooltip {
+    pub edge_id: String,
+    /// Source node label.
+    pub source: String,
+    /// Target node label.
+    pub target: String,
+    pub label: Option<String>,
+    /// Guard condition, reformatted.
+    pub condition: Option<String>,
+    /// Variables the condition reads, in first-use order.
+    pub variables: Vec<String>,
+    pub layer: Option<String>,
+    /// Traversal weight from the analytics overlay.
+    pub weight: Option<f64>,
+    /// Where to place the tooltip: the point on the edge nearest the
+    /// cursor, or its midpoint.
+    pub anchor: Position,
+}
+
+impl EdgeTooltip {
+    /// Plain-text form, one fact per line.
+    pub fn title(&self) -> String {
+        let mut lines = vec![format!("{} → {}", self.source, self.target)];
+        if let Some(label) = &self.label {
+            lines.push(format!("Label: {}", label));
+        }
+        if let Some(condition) = &self.condition {
+            lines.push(format!("When: {}", condition));
+            if !self.variables.is_empty() {
+                lines.push(format!("Reads: {}", self.variables.join(", ")));
+            }
+        }
+        if let Some(layer) = &self.layer {
+            lines.push(format!("Layer: {}", layer));
+        }
+        if let Some(weight) = self.weight {
+            lines.push(format!("Weight: {}", weight));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Edge the pointer is resting on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeHover {
+    pub edge_id: String,
+    pub cursor: Position,
+    /// When the hover started, in milliseconds.
+    pub since_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Literal(String),
+    Operator(String),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(expr: &str) -> Vec<Token> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        i += 1;
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            ',' => tokens.push(Token::Comma),
+            '\'' | '"' => {
+                while i < chars.len() && chars[i] != c {
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                tokens.push(Token::Literal(chars[start..i].iter().collect()));
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Literal(chars[start..i].iter().collect()));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '$' | '.'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let pair: String = chars[start..(start + 2).min(chars.len())].iter().collect();
+                if LONG_OPERATORS.contains(&pair.as_str()) {
+                    i += 1;
+                    tokens.push(Token::Operator(pair));
+                } else {
+                    tokens.push(Token::Operator(c.to_string()));
+                }
+            }
+        }
+    }
+    tokens
+}
+
+/// Reformat a guard condition with even operator spacing and list the
+/// variables it reads. Conditions are free text; this only normalizes
+/// spacing and doesn't check that the expression is well formed.
+pub fn format_condition(expr: &str) -> (String, Vec<String>) {
+    let tokens = tokenize(expr);
+    let mut out = String::new();
+    let mut variables: Vec<String> = Vec::new();
+    // Whether the next token starts an operand, making an operator there
+    // unary.
+    let mut expect_operand = true;
+    let mut prev_unary = false;
+    for (i, token) in tokens.iter().enumerate() {
+        let glued = i == 0
+            || prev_unary
+            || matches!(token, Token::Close | Token::Comma)
+            || tokens[i - 1] == Token::Open
+            || (matches!(tokens[i - 1], Token::Word(_)) && *token == Token::Open);
+        if !glued {
+            out.push(' ');
+        }
+        prev_unary = false;
+        match token {
+            Token::Word(word) => {
+                out.push_str(word);
+                let keyword = word.to_ascii_lowercase();
+                let call = tokens.get(i + 1) == Some(&Token::Open);
+                if CONDITION_KEYWORDS.contains(&keyword.as_str()) {
+                    expect_operand = matches!(keyword.as_str(), "and" | "or" | "not");
+                    continue;
+                }
+                if !call && !variables.contains(word) {
+                    variables.push(word.clone());
+                }
+                expect_operand = false;
+            }
+            Token::Literal(text) => {
+                out.push_str(text);
+                expect_operand = false;
+            }
+            Token::Operator(op) => {
+                out.push_str(op);
+                prev_unary = expect_operand;
+                expect_operand = true;
+            }
+            Token::Open => {
+                out.push('(');
+                expect_operand = true;
+            }
+            Token::Close => {
+                out.push(')');
+                expect_operand = false;
+            }
+            Token::Comma => {
+                out.push(',');
+                expect_operand = true;
+            }
+        }
+    }
+    (out, variables)
+}
+
+/// Id of the workflow that owns a navigation node.
+fn workflow_of(designer: &NavigationDesigner, node_id: &str) -> Option<String> {
+    let mut id = node_id.to_string();
+    loop {
+        let data = designer.canvas.get_node(&id)?.data.as_ref()?;
+        match &data.parent_id {
+            Some(parent) => id = parent.clone(),
+            None => return Some(data.entity_id.clone()),
+        }
+    }
+}
+
+impl NavigationDesigner {
+    /// Tooltip for an edge, anchored near `cursor` when given.
+    pub fn edge_tooltip(
+        &self,
+        edge_id: &str,
+        store: &StudioStore,
+        cursor: Option<Position>,
+    ) -> Option<EdgeTooltip> {
+        let edge = self.canvas.get_edge(edge_id)?;
+        let node_label = |id: &str| {
+            self.canvas
+                .get_node(id)
+                .and_then(|n| n.data.as_ref())
+                .map_or_else(|| id.to_string(), |d| d.label.clone())
+        };
+        let data = edge.data.as_ref();
+        let (condition, variables) = match state_machine::condition(data) {
+            Some(text) => {
+                let (pretty, variables) = format_condition(&text);
+                (Some(pretty), variables)
+            }
+            None => (None, Vec::new()),
+        };
+        let weight = workflow_of(self, &edge.source)
+            .and_then(|workflow| store.edge_weights_for(&workflow))
+            .and_then(|weights| weights.get(edge_id));
+        let (start, end) = self.canvas.edge_endpoints(edge_id)?;
+        let anchor = match cursor {
+            Some(cursor) => cursor.closest_on_segment(&start, &end),
+            None => Position::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0),
+        };
+        Some(EdgeTooltip {
+            edge_id: edge_id.to_string(),
+            source: node_label(&edge.source),
+            target: node_label(&edge.target),
+            label: data
+                .and_then(|d| d.label.clone())
+                .or_else(|| edge.label.as_ref().map(|l| l.text.clone())),
+            condition,
+            variables,
+            layer: data.and_then(|d| d.layer.clone()),
+            weight,
+            anchor,
+        })
+    }
+
+    /// Tooltip for the hovered edge once the store's hover delay has
+    /// passed.
+    pub fn hovered_edge_tooltip(&self, store: &StudioStore, now_ms: u64) -> Option<EdgeTooltip> {
+        let hover = store.ui.canvas.edge_hover.as_ref()?;
+        if now_ms.saturating_sub(hover.since_ms) < store.ui.canvas.tooltip_delay_ms {
+            return None;
+        }
+        self.edge_tooltip(&hover.edge_id, store, Some(hover.cursor))
+    }
+
+    /// SVG pages of the visible canvas, with each edge's tooltip text as
+    /// its `<title>`. With edge weights shown, the selected workflow's
+    /// weighted edges are stroked by traffic and each page carries the
+    /// legend.
+    pub fn render_svg_pages_with_titles(
+        &self,
+        store: &StudioStore,
+        tiles: &[PageTile],
+    ) -> Vec<String> {
+        let canvas = self.visible_canvas(store);
+        let overlay = store
+            .ui
+            .canvas
+            .show_edge_weights
+            .then(|| store.weight_overlay())
+            .flatten();
+        render_svg_pages_overlaid(
+            &canvas,
+            tiles,
+            |edge: &Edge<EdgeData>| self.edge_tooltip(&edge.id, store, None).map(|t| t.title()),
+            overlay.as_ref(),
+        )
+    }
+}
+
+impl StudioStore {
+    /// Record the pointer over an edge. Moving along the same edge keeps
+    /// the original hover start.
+    pub fn hover_edge(&mut self, edge_id: &str, cursor: Position, now_ms: u64) {
+        let canvas = &mut self.ui.canvas;
+        match &mut canvas.edge_hover {
+            Some(hover) if hover.edge_id == edge_id => hover.cursor = cursor,
+            hover => {
+                *hover = Some(EdgeHover {
+                    edge_id: edge_id.to_string(),
+                    cursor,
+                    since_ms: now_ms,
+                })
+            }
+        }
+    }
+
+    /// The pointer left the hovered edge.
+    pub fn leave_edge(&mut self) {
+        self.ui.canvas.edge_hover = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::WeightMap;
+    use crate::entity::{Context, Workflow};
+    use crate::export::print::paginate_flow;
+    use crate::export::state_machine::CONDITION_KEY;
+    use rsc_flow::{Dimensions, EdgeLabel};
+
+    fn setup() -> (StudioStore, NavigationDesigner) {
+        let mut store = StudioStore::new();
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        let mut cart = Context::new("Cart").with_id("c1");
+        cart.layer = Some("happy".to_string());
+        workflow.add_context(cart);
+        workflow.add_context(Context::new("Audit").with_id("c2"));
+        store.add_workflow(workflow);
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&store.workflows.values().collect::<Vec<_>>());
+        designer.apply_layout();
+        (store, designer)
+    }
+
+    fn edge_to(designer: &NavigationDesigner, target: &str) -> String {
+        designer.canvas.get_incoming_edges(target)[0].id.clone()
+    }
+
+    #[test]
+    fn test_format_condition() {
+        let (pretty, vars) = format_condition("cart.total>=100&&!user.blocked");
+        assert_eq!(pretty, "cart.total >= 100 && !user.blocked");
+        assert_eq!(vars, ["cart.total", "user.blocked"]);
+
+        let (pretty, vars) = format_condition("  len( items ,2)==-1 or  status=='paid' ");
+        assert_eq!(pretty, "len(items, 2) == -1 or status == 'paid'");
+        assert_eq!(vars, ["items", "status"]);
+    }
+
+    #[test]
+    fn test_tooltip_with_all_data() {
+        let (mut store, mut designer) = setup();
+        let id = edge_to(&designer, "c1");
+        let edge = designer.canvas.edges.get_mut(&id).unwrap();
+        let data = edge.data.as_mut().unwrap();
+        data.label = Some("Continue".to_string());
+        data.metadata
+            .insert(CONDITION_KEY.to_string(), "cart.items>0".into());
+        let mut weights = WeightMap::new();
+        weights.add(id.clone(), 42.0);
+        store.set_edge_weights("w1", weights);
+
+        let (start, end) = designer.canvas.edge_endpoints(&id).unwrap();
+        let cursor = Position::new(start.x + 30.0, (start.y + end.y) / 2.0);
+        let tooltip = designer.edge_tooltip(&id, &store, Some(cursor)).unwrap();
+        assert_eq!(
+            (tooltip.source.as_str(), tooltip.target.as_str()),
+            ("Checkout", "Cart")
+        );
+        assert_eq!(tooltip.condition.as_deref(), Some("cart.items > 0"));
+        assert_eq!(tooltip.variables, ["cart.items"]);
+        assert_eq!(tooltip.weight, Some(42.0));
+        assert_eq!(tooltip.anchor, cursor.closest_on_segment(&start, &end));
+        assert_eq!(
+            tooltip.title(),
+            "Checkout → Cart\nLabel: Continue\nWhen: cart.items > 0\nReads: cart.items\nLayer: happy\nWeight: 42"
+        );
+    }
+
+    #[test]
+    fn test_tooltip_without_optional_data() {
+        let (store, mut designer) = setup();
+        let id = edge_to(&designer, "c2");
+        let tooltip = designer.edge_tooltip(&id, &store, None).unwrap();
+        assert_eq!(tooltip.label, None);
+        assert_eq!(tooltip.condition, None);
+        assert!(tooltip.variables.is_empty());
+        assert_eq!(tooltip.layer, None);
+        assert_eq!(tooltip.weight, None);
+        let (start, end) = designer.canvas.edge_endpoints(&id).unwrap();
+        assert_eq!(tooltip.anchor.y, (start.y + end.y) / 2.0);
+        assert_eq!(tooltip.title(), "Checkout → Audit");
+
+        // A canvas edge label stands in when the edge data has none.
+        designer.canvas.edges.get_mut(&id).unwrap().label = Some(EdgeLabel::new("Review"));
+        let tooltip = designer.edge_tooltip(&id, &store, None).unwrap();
+        assert_eq!(tooltip.label.as_deref(), Some("Review"));
+        assert!(designer.edge_tooltip("missing", &store, None).is_none());
+    }
+
+    #[test]
+    fn test_hover_delay_and_svg_titles() {
+        let (mut store, designer) = setup();
+        let id = edge_to(&designer, "c1");
+        store.hover_edge(&id, Position::zero(), 1_000);
+        assert!(designer.hovered_edge_tooltip(&store, 1_200).is_none());
+        // Moving along the same edge doesn't restart the delay.
+        store.hover_edge(&id, Position::new(5.0, 5.0), 1_300);
+        let tooltip = designer.hovered_edge_tooltip(&store, 1_400).unwrap();
+        assert_eq!(tooltip.edge_id, id);
+        store.leave_edge();
+        assert!(designer.hovered_edge_tooltip(&store, 9_000).is_none());
+
+        let tiles = paginate_flow(&designer.canvas, Dimensions::new(2000.0, 2000.0), 0.0);
+        let svg = designer
+            .render_svg_pages_with_titles(&store, &tiles)
+            .concat();
+        assert!(svg.contains("<title>Checkout → Cart\nLayer: happy</title></line>"));
+        assert!(svg.contains("<title>Checkout → Audit</title></line>"));
+        assert!(!svg.contains("weight-legend"));
+
+        let mut weights = WeightMap::new();
+        weights.add(id.clone(), 5.0);
+        weights.add(edge_to(&designer, "c2"), 50.0);
+        store.set_edge_weights("w1", weights);
+        let plain = designer
+            .render_svg_pages_with_titles(&store, &tiles)
+            .concat();
+        assert!(!plain.contains("#2563eb"));
+
+        store.ui.canvas.show_edge_weights = true;
+        store.select_workflow("w1");
+        let svg = designer
+            .render_svg_pages_with_titles(&store, &tiles)
+            .concat();
+        assert!(svg.contains("<g class=\"weight-legend\""));
+        assert!(svg.contains(">50</text>"));
+        // Of two weights, the heavier lands in the third of four buckets.
+        assert!(svg.contains("stroke=\"#2563eb\" stroke-width=\"4.333"));
+    }
+}
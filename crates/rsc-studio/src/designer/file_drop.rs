@@ -0,0 +1,179 @@
+//! Files dropped or pasted onto the navigation canvas.
+//!
+//! The host layer reads the file and hands over its bytes, mime type and
+//! name with the drop point. Images are stored in the asset registry and
+//! shown as annotation nodes; anything else is rejected.
+
+use rsc_dnd::{DndContext, DroppableInfo, Rect};
+use rsc_flow::prelude::*;
+use uuid::Uuid;
+
+use crate::designer::NavigationDesigner;
+use crate::designer::navigation::{EntityType, NavigationNodeData};
+use crate::error::StudioError;
+use crate::store::StudioStore;
+use crate::timestamp::Timestamps;
+
+/// Droppable id of the navigation canvas.
+pub const CANVAS_DROPPABLE: &str = "navigation-canvas";
+
+/// Payload kind for dropped files.
+pub const FILE_PAYLOAD: &str = "file";
+
+/// Longest side of a new annotation, in canvas units.
+pub const MAX_ANNOTATION_EXTENT: f64 = 480.0;
+
+/// A file dropped by the host layer.
+#[derive(Debug, Clone)]
+pub struct FileDrop {
+    pub name: String,
+    pub mime: String,
+    pub bytes: Vec<u8>,
+    /// Drop point relative to the canvas element, in screen pixels.
+    pub position: Position,
+}
+
+/// `width` x `height` scaled down to fit within `max` on both sides.
+/// Smaller images keep their size.
+pub fn fit_within(width: u32, height: u32, max: f64) -> Dimensions {
+    let (width, height) = (width as f64, height as f64);
+    let scale = (max / width.max(height)).min(1.0);
+    Dimensions::new(width * scale, height * scale)
+}
+
+impl NavigationDesigner {
+    /// Make the canvas, occupying `rect`, accept dropped files.
+    pub fn register_file_drop(&self, dnd: &mut DndContext, rect: Rect) {
+        dnd.register_droppable(
+            CANVAS_DROPPABLE,
+            DroppableInfo {
+                rect,
+                disabled: false,
+                accepts: vec![FILE_PAYLOAD.to_string()],
+                data: serde_json::Value::Null,
+                parent: None,
+                z_index: 0,
+            },
+        );
+    }
+
+    /// Store a dropped image and add an annotation node centered on the
+    /// drop point. Returns the node id; rejections are also recorded in
+    /// the store's problems.
+    pub fn drop_file(
+        &mut self,
+        store: &mut StudioStore,
+        drop: FileDrop,
+    ) -> Result<String, StudioError> {
+        let added = store
+            .assets
+            .add_image(&drop.name, &drop.mime, drop.bytes)
+            .map(|asset| (asset.id.clone(), asset.size));
+        let (asset_id, size) = store.checked(added)?;
+
+        let dimensions = fit_within(size.width, size.height, MAX_ANNOTATION_EXTENT);
+        let center = self.canvas.viewport.screen_to_canvas(drop.position);
+        let position = Position::new(
+            center.x - dimensions.width / 2.0,
+            center.y - dimensions.height / 2.0,
+        );
+
+        let id = format!("annotation-{}", Uuid::new_v4());
+        let node = Node::new(&id, NodeType::Custom("annotation".to_string()), position)
+            .with_dimensions(dimensions)
+            .with_data(NavigationNodeData {
+                entity_type: EntityType::Annotation,
+                entity_id: asset_id,
+                parent_id: None,
+                label: drop.name,
+                icon: Some("image".to_string()),
+                description: None,
+                timestamps: Timestamps::default(),
+                kind: None,
+            });
+        self.canvas.add_node(node);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::tests::png;
+    use crate::entity::Workflow;
+    use crate::error::ErrorCode;
+
+    fn drop(name: &str, mime: &str, bytes: Vec<u8>) -> FileDrop {
+        FileDrop {
+            name: name.to_string(),
+            mime: mime.to_string(),
+            bytes,
+            position: Position::new(400.0, 300.0),
+        }
+    }
+
+    #[test]
+    fn test_size_clamp() {
+        assert_eq!(fit_within(1920, 1080, 480.0), Dimensions::new(480.0, 270.0));
+        assert_eq!(fit_within(300, 1200, 480.0), Dimensions::new(120.0, 480.0));
+        assert_eq!(fit_within(200, 100, 480.0), Dimensions::new(200.0, 100.0));
+
+        let mut store = StudioStore::new();
+        let mut designer = NavigationDesigner::new();
+        designer.canvas.viewport.transform = ViewportTransform::new(100.0, 0.0, 2.0);
+        let id = designer
+            .drop_file(
+                &mut store,
+                drop("shot.png", "image/png", png(1920, 1080, 0)),
+            )
+            .unwrap();
+        let node = designer.canvas.get_node(&id).unwrap();
+        assert_eq!(node.dimensions, Some(Dimensions::new(480.0, 270.0)));
+        // (400, 300) on screen is (150, 150) on the canvas.
+        assert_eq!(node.position, Position::new(-90.0, 15.0));
+    }
+
+    #[test]
+    fn test_dropping_twice_reuses_the_asset() {
+        let mut store = StudioStore::new();
+        let mut designer = NavigationDesigner::new();
+        let first = designer
+            .drop_file(&mut store, drop("a.png", "image/png", png(64, 64, 7)))
+            .unwrap();
+        let second = designer
+            .drop_file(&mut store, drop("b.png", "image/png", png(64, 64, 7)))
+            .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(store.assets.len(), 1);
+        let asset_of = |id: &str| designer.get_entity_at(id).unwrap().entity_id.clone();
+        assert_eq!(asset_of(&first), asset_of(&second));
+
+        // Annotations survive reloading the workflows and stay where dropped.
+        let position = designer.canvas.get_node(&first).unwrap().position;
+        designer.load_workflows(&[&Workflow::new("Main").with_id("w1")]);
+        assert_eq!(designer.canvas.nodes.len(), 3);
+        assert_eq!(designer.canvas.get_node(&first).unwrap().position, position);
+    }
+
+    #[test]
+    fn test_non_images_are_rejected() {
+        let mut store = StudioStore::new();
+        let mut designer = NavigationDesigner::new();
+        let error = designer
+            .drop_file(&mut store, drop("notes.pdf", "application/pdf", vec![1, 2]))
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::UnsupportedFile);
+        assert_eq!(
+            error.message,
+            "'notes.pdf' is not an image (application/pdf)"
+        );
+        assert_eq!(store.problems, [error]);
+        assert!(designer.canvas.nodes.is_empty());
+
+        store.assets.max_bytes = 16;
+        let error = designer
+            .drop_file(&mut store, drop("big.png", "image/png", png(8, 8, 0)))
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::LimitExceeded);
+    }
+}
@@ -0,0 +1,561 @@
+//! Undo/redo of flow graph edits in the navigation designer.
+//!
+//! Each edit is a [`FlowCommand`] that can apply and revert itself on the
+//! designer canvas. Renaming or removing a node that stands for a
+//! workflow, context or preset also updates the entity. The store records
+//! both as a [`StoreChange::Flow`] in the navigation designer's history; a
+//! transaction groups several edits, such as the moves of a multi-node
+//! drag, into one entry. Like presentations, the graph lives on the
+//! designer, so these store methods take it as an argument, and undo
+//! queues canvas commands until [`StudioStore::sync_flow`].
+
+use rsc_flow::prelude::*;
+use rsc_flow::{ConnectionCandidate, ConnectionVerdict, NodeId};
+
+use super::NavigationDesigner;
+use super::navigation::{EntityType, NavigationNodeData};
+use crate::designer::css::ValidationSeverity;
+use crate::entity::{Context, Preset};
+use crate::error::{ErrorCode, StudioError};
+use crate::history::HistoryScope;
+use crate::store::{StoreChange, StudioStore};
+
+type NavigationCanvas = FlowCanvas<NavigationNodeData, EdgeData>;
+
+/// A reversible edit of the navigation canvas.
+#[derive(Debug, Clone)]
+pub enum FlowCommand {
+    AddNode(Box<Node<NavigationNodeData>>),
+    /// Removing a node also removes its edges; both go back to their
+    /// original positions on revert.
+    RemoveNode {
+        node: Box<Node<NavigationNodeData>>,
+        index: usize,
+        /// Connected edges with their indices, in edge order.
+        edges: Vec<(usize, Edge<EdgeData>)>,
+    },
+    MoveNode {
+        id: NodeId,
+        from: Position,
+        to: Position,
+    },
+    AddEdge(Box<Edge<EdgeData>>),
+    RemoveEdge {
+        edge: Box<Edge<EdgeData>>,
+        index: usize,
+    },
+    RenameNode {
+        id: NodeId,
+        from: String,
+        to: String,
+    },
+}
+
+impl FlowCommand {
+    /// History label of a single command.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AddNode(_) => "Add node",
+            Self::RemoveNode { .. } => "Remove node",
+            Self::MoveNode { .. } => "Move node",
+            Self::AddEdge(_) => "Add edge",
+            Self::RemoveEdge { .. } => "Remove edge",
+            Self::RenameNode { .. } => "Rename node",
+        }
+    }
+
+    pub fn apply(&self, canvas: &mut NavigationCanvas) {
+        match self {
+            Self::AddNode(node) => canvas.add_node(Node::clone(node)),
+            Self::RemoveNode { node, .. } => {
+                canvas.remove_node(&node.id);
+            }
+            Self::MoveNode { id, to, .. } => set_position(canvas, id, *to),
+            Self::AddEdge(edge) => canvas.add_edge(Edge::clone(edge)),
+            Self::RemoveEdge { edge, .. } => {
+                canvas.remove_edge(&edge.id);
+            }
+            Self::RenameNode { id, to, .. } => set_label(canvas, id, to),
+        }
+    }
+
+    pub fn revert(&self, canvas: &mut NavigationCanvas) {
+        match self {
+            Self::AddNode(node) => {
+                canvas.remove_node(&node.id);
+            }
+            Self::RemoveNode { node, index, edges } => {
+                let index = (*index).min(canvas.nodes.len());
+                canvas
+                    .nodes
+                    .shift_insert(index, node.id.clone(), Node::clone(node));
+                // Ascending indices put every edge back where it was.
+                for (index, edge) in edges {
+                    let index = (*index).min(canvas.edges.len());
+                    canvas
+                        .edges
+                        .shift_insert(index, edge.id.clone(), edge.clone());
+                }
+            }
+            Self::MoveNode { id, from, .. } => set_position(canvas, id, *from),
+            Self::AddEdge(edge) => {
+                canvas.remove_edge(&edge.id);
+            }
+            Self::RemoveEdge { edge, index } => {
+                let index = (*index).min(canvas.edges.len());
+                canvas
+                    .edges
+                    .shift_insert(index, edge.id.clone(), Edge::clone(edge));
+            }
+            Self::RenameNode { id, from, .. } => set_label(canvas, id, from),
+        }
+    }
+}
+
+fn set_position(canvas: &mut NavigationCanvas, id: &str, position: Position) {
+    if let Some(node) = canvas.get_node_mut(id) {
+        node.position = position;
+    }
+}
+
+fn set_label(canvas: &mut NavigationCanvas, id: &str, label: &str) {
+    if let Some(data) = canvas.get_node_mut(id).and_then(|n| n.data.as_mut()) {
+        data.label = label.to_string();
+    }
+}
+
+/// Commands recorded since [`StudioStore::begin_transaction`], with the
+/// workflow changes they made.
+#[derive(Debug, Clone)]
+pub struct FlowTransaction {
+    pub label: String,
+    pub commands: Vec<FlowCommand>,
+    pub entities: Vec<StoreChange>,
+}
+
+/// Commands an undo or redo left for the designer canvas.
+#[derive(Debug, Clone)]
+pub struct FlowReplay {
+    pub commands: Vec<FlowCommand>,
+    /// Apply the commands if true, revert them if false.
+    pub forward: bool,
+}
+
+impl StudioStore {
+    /// Apply a command and record it, in the open transaction if any.
+    pub fn execute_flow(&mut self, designer: &mut NavigationDesigner, command: FlowCommand) {
+        let mut entities: Vec<_> = self.entity_change(designer, &command).into_iter().collect();
+        if self.flow_transaction.is_none() {
+            let label = command.label();
+            let change = StoreChange::Flow {
+                commands: vec![command],
+                entities,
+            };
+            self.commit(HistoryScope::NavDesigner, label, change);
+            self.sync_flow(designer);
+            return;
+        }
+        command.apply(&mut designer.canvas);
+        let (now, by) = (self.clock.now(), self.modified_by.clone());
+        for change in &mut entities {
+            change.stamp(now, by.as_deref());
+            self.apply_change(change, true);
+        }
+        if let Some(transaction) = self.flow_transaction.as_mut() {
+            transaction.commands.push(command);
+            transaction.entities.extend(entities);
+        }
+    }
+
+    /// Bring the designer canvas up to date with undone and redone flow
+    /// edits. Returns whether any were applied.
+    pub fn sync_flow(&mut self, designer: &mut NavigationDesigner) -> bool {
+        let replays = std::mem::take(&mut self.flow_replay);
+        for replay in &replays {
+            if replay.forward {
+                for command in &replay.commands {
+                    command.apply(&mut designer.canvas);
+                }
+            } else {
+                for command in replay.commands.iter().rev() {
+                    command.revert(&mut designer.canvas);
+                }
+            }
+        }
+        !replays.is_empty()
+    }
+
+    /// The workflow change a command makes, if its node stands for a
+    /// workflow, context or preset in the store.
+    fn entity_change(
+        &self,
+        designer: &NavigationDesigner,
+        command: &FlowCommand,
+    ) -> Option<StoreChange> {
+        let (data, rename) = match command {
+            FlowCommand::AddNode(node) => (node.data.as_ref()?, None),
+            FlowCommand::RemoveNode { node, .. } => (node.data.as_ref()?, None),
+            FlowCommand::RenameNode { id, to, .. } => {
+                (designer.get_entity_at(id)?, Some(to.as_str()))
+            }
+            _ => return None,
+        };
+        let adding = matches!(command, FlowCommand::AddNode(_));
+        let workflow_id = match data.entity_type {
+            EntityType::Workflow => &data.entity_id,
+            EntityType::Context => data.parent_id.as_ref()?,
+            EntityType::Preset => {
+                let context = data.parent_id.as_ref()?;
+                self.workflows
+                    .values()
+                    .find(|w| w.contexts.contains_key(context))
+                    .map(|w| &w.id)?
+            }
+            EntityType::Annotation => return None,
+        };
+        let (index, _, before) = self.workflows.get_full(workflow_id)?;
+        let mut after = before.clone();
+        let id = &data.entity_id;
+        match (data.entity_type, rename) {
+            (EntityType::Workflow, Some(name)) => after.name = name.to_string(),
+            (EntityType::Context, Some(name)) => {
+                after.contexts.get_mut(id)?.name = name.to_string()
+            }
+            (EntityType::Preset, Some(name)) => {
+                let context = after.contexts.get_mut(data.parent_id.as_ref()?)?;
+                context.presets.get_mut(id)?.name = name.to_string();
+            }
+            (EntityType::Context, None) if adding => {
+                if after.contexts.contains_key(id) {
+                    return None;
+                }
+                after.add_context(Context::new(&data.label).with_id(id));
+            }
+            (EntityType::Context, None) => {
+                after.contexts.shift_remove(id)?;
+            }
+            (EntityType::Preset, None) => {
+                let context = after.contexts.get_mut(data.parent_id.as_ref()?)?;
+                if !adding {
+                    context.presets.shift_remove(id)?;
+                } else if context.presets.contains_key(id) {
+                    return None;
+                } else {
+                    context.add_preset(Preset::new(&data.label).with_id(id));
+                }
+            }
+            // A workflow node being added already has its workflow.
+            (EntityType::Workflow, None) if adding => return None,
+            (EntityType::Workflow, None) => {
+                return Some(StoreChange::Workflow {
+                    id: workflow_id.clone(),
+                    index,
+                    before: Some(Box::new(before.clone())),
+                    after: None,
+                });
+            }
+            (EntityType::Annotation, _) => return None,
+        }
+        Some(StoreChange::Workflow {
+            id: workflow_id.clone(),
+            index,
+            before: Some(Box::new(before.clone())),
+            after: Some(Box::new(after)),
+        })
+    }
+
+    /// Add a node. Returns false if the id is taken.
+    pub fn add_flow_node(
+        &mut self,
+        designer: &mut NavigationDesigner,
+        node: Node<NavigationNodeData>,
+    ) -> bool {
+        if designer.canvas.nodes.contains_key(&node.id) {
+            return false;
+        }
+        self.execute_flow(designer, FlowCommand::AddNode(Box::new(node)));
+        true
+    }
+
+    /// Remove a node and its edges.
+    pub fn remove_flow_node(&mut self, designer: &mut NavigationDesigner, id: &str) -> bool {
+        let canvas = &designer.canvas;
+        let Some((index, _, node)) = canvas.nodes.get_full(id) else {
+            return false;
+        };
+        let edges = canvas
+            .edges
+            .values()
+            .enumerate()
+            .filter(|(_, e)| e.source == id || e.target == id)
+            .map(|(i, e)| (i, e.clone()))
+            .collect();
+        let command = FlowCommand::RemoveNode {
+            node: Box::new(node.clone()),
+            index,
+            edges,
+        };
+        self.execute_flow(designer, command);
+        true
+    }
+
+    /// Move a node. Returns false if there is no such node or it is
+    /// already there.
+    pub fn move_flow_node(
+        &mut self,
+        designer: &mut NavigationDesigner,
+        id: &str,
+        to: Position,
+    ) -> bool {
+        let Some(node) = designer.canvas.get_node(id) else {
+            return false;
+        };
+        if node.position == to {
+            return false;
+        }
+        let command = FlowCommand::MoveNode {
+            id: id.to_string(),
+            from: node.position,
+            to,
+        };
+        self.execute_flow(designer, command);
+        true
+    }
+
+    /// Add an edge. Returns false if the id is taken or the canvas
+    /// validator denies the connection; the denial reason, like any
+    /// warning, is recorded in `problems` for a toast.
+    pub fn add_flow_edge(
+        &mut self,
+        designer: &mut NavigationDesigner,
+        edge: Edge<EdgeData>,
+    ) -> bool {
+        if designer.canvas.edges.contains_key(&edge.id) {
+            return false;
+        }
+        match designer
+            .canvas
+            .validate_connection(&ConnectionCandidate::from_edge(&edge))
+        {
+            ConnectionVerdict::Deny(reason) => {
+                let _ = self.checked::<(), _>(Err(FlowError::invalid_connection(reason)));
+                return false;
+            }
+            ConnectionVerdict::AllowWithWarning(warning) => {
+                let warning = StudioError::new(ErrorCode::InvalidConnection, warning)
+                    .with_severity(ValidationSeverity::Warning);
+                self.problems.push(warning);
+            }
+            ConnectionVerdict::Allow => {}
+        }
+        self.execute_flow(designer, FlowCommand::AddEdge(Box::new(edge)));
+        true
+    }
+
+    pub fn remove_flow_edge(&mut self, designer: &mut NavigationDesigner, id: &str) -> bool {
+        let Some((index, _, edge)) = designer.canvas.edges.get_full(id) else {
+            return false;
+        };
+        let command = FlowCommand::RemoveEdge {
+            edge: Box::new(edge.clone()),
+            index,
+        };
+        self.execute_flow(designer, command);
+        true
+    }
+
+    /// Change a node's label. Returns false if there is no such node or
+    /// the label is unchanged.
+    pub fn rename_flow_node(
+        &mut self,
+        designer: &mut NavigationDesigner,
+        id: &str,
+        name: &str,
+    ) -> bool {
+        let Some(data) = designer.canvas.get_node(id).and_then(|n| n.data.as_ref()) else {
+            return false;
+        };
+        if data.label == name {
+            return false;
+        }
+        let command = FlowCommand::RenameNode {
+            id: id.to_string(),
+            from: data.label.clone(),
+            to: name.to_string(),
+        };
+        self.execute_flow(designer, command);
+        true
+    }
+
+    /// Group the following flow edits into one history entry until
+    /// [`commit_transaction`](Self::commit_transaction). Does nothing if a
+    /// transaction is already open.
+    pub fn begin_transaction(&mut self, label: impl Into<String>) {
+        if self.flow_transaction.is_none() {
+            self.flow_transaction = Some(FlowTransaction {
+                label: label.into(),
+                commands: Vec::new(),
+                entities: Vec::new(),
+            });
+        }
+    }
+
+    /// Close the open transaction. Returns whether it recorded an entry;
+    /// an empty transaction records nothing.
+    pub fn commit_transaction(&mut self) -> bool {
+        match self.flow_transaction.take() {
+            Some(transaction) if !transaction.commands.is_empty() => {
+                let change = StoreChange::Flow {
+                    commands: transaction.commands,
+                    entities: transaction.entities,
+                };
+                self.record(HistoryScope::NavDesigner, transaction.label, change);
+                if let Some(persistence) = self.persistence.as_mut() {
+                    persistence.note_edit();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Undo the last navigation designer edit and update the canvas.
+    pub fn undo_flow(&mut self, designer: &mut NavigationDesigner) -> bool {
+        let undone = self.undo_scope(HistoryScope::NavDesigner);
+        self.sync_flow(designer);
+        undone
+    }
+
+    /// Redo the last undone navigation designer edit and update the canvas.
+    pub fn redo_flow(&mut self, designer: &mut NavigationDesigner) -> bool {
+        let redone = self.redo_scope(HistoryScope::NavDesigner);
+        self.sync_flow(designer);
+        redone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Workflow;
+    use crate::history::History;
+
+    fn setup() -> (StudioStore, NavigationDesigner) {
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        for (id, name) in [("c1", "Cart"), ("c2", "Payment"), ("c3", "Done")] {
+            workflow.add_context(Context::new(name).with_id(id));
+        }
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        designer.canvas.add_edge(Edge::new("t1", "c1", "c2"));
+        designer.canvas.add_edge(Edge::new("t2", "c2", "c3"));
+        let mut store = StudioStore::new();
+        store.workflows.insert("w1".to_string(), workflow);
+        (store, designer)
+    }
+
+    fn nav_history(store: &StudioStore) -> &History<StoreChange> {
+        store.history.scope(HistoryScope::NavDesigner).unwrap()
+    }
+
+    fn contexts(store: &StudioStore) -> Vec<&str> {
+        ids(&store.workflows["w1"].contexts)
+    }
+
+    fn ids<T>(map: &indexmap::IndexMap<String, T>) -> Vec<&str> {
+        map.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_undo_node_removal_restores_edges() {
+        let (mut store, mut designer) = setup();
+        let nodes_before: Vec<String> = designer.canvas.nodes.keys().cloned().collect();
+        let edges_before: Vec<String> = designer.canvas.edges.keys().cloned().collect();
+        let position = designer.canvas.get_node("c2").unwrap().position;
+
+        assert!(store.remove_flow_node(&mut designer, "c2"));
+        assert!(designer.canvas.get_node("c2").is_none());
+        assert_eq!(designer.canvas.edges.len(), 2);
+        assert_eq!(nav_history(&store).undo_label(), Some("Remove node"));
+        assert_eq!(contexts(&store), ["c1", "c3"]);
+        assert!(store.is_dirty());
+
+        // The store's own undo reverts the entity and queues the canvas.
+        assert!(store.undo());
+        assert_eq!(contexts(&store), ["c1", "c2", "c3"]);
+        assert!(!store.is_dirty());
+        assert!(store.sync_flow(&mut designer));
+        assert_eq!(ids(&designer.canvas.nodes), nodes_before);
+        assert_eq!(ids(&designer.canvas.edges), edges_before);
+        let restored = designer.canvas.get_node("c2").unwrap();
+        assert_eq!(restored.position, position);
+        assert_eq!(restored.data.as_ref().unwrap().label, "Payment");
+        let t1 = designer.canvas.get_edge("t1").unwrap();
+        assert_eq!((t1.source.as_str(), t1.target.as_str()), ("c1", "c2"));
+
+        assert!(store.redo_flow(&mut designer));
+        assert!(designer.canvas.get_node("c2").is_none());
+        assert!(!store.redo_flow(&mut designer));
+    }
+
+    #[test]
+    fn test_drag_transaction_is_one_entry() {
+        let (mut store, mut designer) = setup();
+        let start: Vec<Position> = ["c1", "c2"]
+            .map(|id| designer.canvas.get_node(id).unwrap().position)
+            .to_vec();
+
+        store.begin_transaction("Move nodes");
+        for step in 1..=3 {
+            for (id, from) in ["c1", "c2"].iter().zip(&start) {
+                let to = Position::new(from.x + 10.0 * step as f64, from.y);
+                assert!(store.move_flow_node(&mut designer, id, to));
+            }
+        }
+        assert!(store.commit_transaction());
+        assert!(!store.commit_transaction());
+        assert_eq!(nav_history(&store).len(), 1);
+        assert_eq!(nav_history(&store).undo_label(), Some("Move nodes"));
+        assert_eq!(
+            designer.canvas.get_node("c1").unwrap().position.x,
+            start[0].x + 30.0
+        );
+
+        assert!(store.undo_flow(&mut designer));
+        assert_eq!(designer.canvas.get_node("c1").unwrap().position, start[0]);
+        assert_eq!(designer.canvas.get_node("c2").unwrap().position, start[1]);
+        assert!(!store.undo_flow(&mut designer));
+    }
+
+    #[test]
+    fn test_edge_and_rename_commands() {
+        let (mut store, mut designer) = setup();
+        store.history.scope_mut(HistoryScope::NavDesigner).max_depth = 2;
+
+        assert!(!store.add_flow_edge(&mut designer, Edge::new("t3", "c1", "missing")));
+        assert!(!store.add_flow_edge(&mut designer, Edge::new("t3", "c1", "c2")));
+        assert!(!store.add_flow_edge(&mut designer, Edge::new("t3", "c1", "c1")));
+        let reasons: Vec<&str> = store.problems.iter().map(|p| p.message.as_str()).collect();
+        assert_eq!(
+            reasons[1..],
+            [
+                "Invalid connection: 'c1' and 'c2' are already connected by 't1'",
+                "Invalid connection: A node can't connect to itself",
+            ]
+        );
+        assert!(store.add_flow_edge(&mut designer, Edge::new("t3", "c3", "c1")));
+        assert!(store.remove_flow_edge(&mut designer, "t1"));
+        assert!(store.rename_flow_node(&mut designer, "c3", "Confirmation"));
+        assert!(!store.rename_flow_node(&mut designer, "c3", "Confirmation"));
+        assert_eq!(nav_history(&store).len(), 2);
+        assert_eq!(store.workflows["w1"].contexts["c3"].name, "Confirmation");
+
+        assert!(store.undo_flow(&mut designer));
+        assert_eq!(designer.get_entity_at("c3").unwrap().label, "Done");
+        assert_eq!(store.workflows["w1"].contexts["c3"].name, "Done");
+        assert!(store.undo_flow(&mut designer));
+        assert_eq!(ids(&designer.canvas.edges)[3..], ["t1", "t2", "t3"]);
+        // The edge addition fell off the capped history.
+        assert!(!store.undo_flow(&mut designer));
+    }
+}
@@ -0,0 +1,471 @@
+//! Kind-based styling of navigation nodes and edges.
+//!
+//! Contexts and presets carry an optional `kind`, transitions one in their
+//! edge metadata under [`KIND_KEY`]. A [`FlowStyleSheet`] maps kinds to
+//! visual properties; whatever a kind leaves unset, and every unknown
+//! kind, falls back to the sheet's defaults. The canvas renderer only
+//! applies class names: [`FlowStyleSheet::to_css`] emits the rules and
+//! [`NavigationDesigner::apply_flow_styles`] tags the nodes and edges. The
+//! sheet is saved with the project.
+
+use indexmap::IndexMap;
+use rsc_flow::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::designer::NavigationDesigner;
+use crate::designer::navigation::NavigationNodeData;
+use crate::entity::slugify;
+
+/// Edge metadata key holding a transition's kind.
+pub const KIND_KEY: &str = "kind";
+
+/// Class every navigation node gets.
+pub const NODE_CLASS: &str = "rsc-node";
+
+/// Class every navigation edge gets.
+pub const EDGE_CLASS: &str = "rsc-edge";
+
+/// End marker of an edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Arrowhead {
+    #[default]
+    Arrow,
+    ArrowClosed,
+    Circle,
+    None,
+}
+
+impl Arrowhead {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Arrowhead::Arrow => "arrow",
+            Arrowhead::ArrowClosed => "arrow-closed",
+            Arrowhead::Circle => "circle",
+            Arrowhead::None => "none",
+        }
+    }
+}
+
+/// Node properties; unset ones fall back to the sheet's default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<String>,
+    /// CSS border shorthand, e.g. `1px dashed #64748b`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<String>,
+    /// Corner radius in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_radius: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+impl NodeStyle {
+    pub fn with_fill(mut self, fill: impl Into<String>) -> Self {
+        self.fill = Some(fill.into());
+        self
+    }
+
+    pub fn with_border(mut self, border: impl Into<String>) -> Self {
+        self.border = Some(border.into());
+        self
+    }
+
+    pub fn with_border_radius(mut self, radius: f64) -> Self {
+        self.border_radius = Some(radius);
+        self
+    }
+
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+}
+
+/// Edge properties; unset ones fall back to the sheet's default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EdgeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke: Option<String>,
+    /// Dash and gap lengths; empty draws a solid line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dash: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrowhead: Option<Arrowhead>,
+}
+
+impl EdgeStyle {
+    pub fn with_stroke(mut self, stroke: impl Into<String>) -> Self {
+        self.stroke = Some(stroke.into());
+        self
+    }
+
+    pub fn with_dash(mut self, dash: impl Into<Vec<f64>>) -> Self {
+        self.dash = Some(dash.into());
+        self
+    }
+
+    pub fn with_arrowhead(mut self, arrowhead: Arrowhead) -> Self {
+        self.arrowhead = Some(arrowhead);
+        self
+    }
+}
+
+/// Style of one node with every fallback applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedNodeStyle {
+    /// The styled kind; `None` when the node has no kind or an unknown one.
+    pub kind: Option<String>,
+    pub fill: String,
+    pub border: String,
+    pub border_radius: f64,
+    pub icon: Option<String>,
+    pub class_names: Vec<String>,
+}
+
+/// Style of one edge with every fallback applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedEdgeStyle {
+    /// The styled kind; `None` when the edge has no kind or an unknown one.
+    pub kind: Option<String>,
+    pub stroke: String,
+    pub dash: Vec<f64>,
+    pub arrowhead: Arrowhead,
+    pub class_names: Vec<String>,
+}
+
+/// Node and edge styles by kind.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowStyleSheet {
+    pub node_default: NodeStyle,
+    pub edge_default: EdgeStyle,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub nodes: IndexMap<String, NodeStyle>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub edges: IndexMap<String, EdgeStyle>,
+}
+
+impl Default for FlowStyleSheet {
+    fn default() -> Self {
+        Self {
+            node_default: NodeStyle::default()
+                .with_fill("#ffffff")
+                .with_border("1px solid #cbd5e1")
+                .with_border_radius(8.0),
+            edge_default: EdgeStyle::default()
+                .with_stroke("#94a3b8")
+                .with_dash(Vec::new())
+                .with_arrowhead(Arrowhead::Arrow),
+            nodes: IndexMap::new(),
+            edges: IndexMap::new(),
+        }
+    }
+}
+
+/// Class of a node kind, e.g. `rsc-node--full-page` for "Full page".
+pub fn node_kind_class(kind: &str) -> String {
+    format!("{}--{}", NODE_CLASS, slugify(kind))
+}
+
+/// Class of an edge kind, e.g. `rsc-edge--redirect`.
+pub fn edge_kind_class(kind: &str) -> String {
+    format!("{}--{}", EDGE_CLASS, slugify(kind))
+}
+
+impl FlowStyleSheet {
+    /// Whether the sheet is the default one, with no kinds.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn with_node_kind(mut self, kind: impl Into<String>, style: NodeStyle) -> Self {
+        self.nodes.insert(kind.into(), style);
+        self
+    }
+
+    pub fn with_edge_kind(mut self, kind: impl Into<String>, style: EdgeStyle) -> Self {
+        self.edges.insert(kind.into(), style);
+        self
+    }
+
+    /// Style of a node of `kind`.
+    pub fn node_style(&self, kind: Option<&str>) -> ResolvedNodeStyle {
+        let (kind, style) = match kind.and_then(|k| self.nodes.get_key_value(k)) {
+            Some((kind, style)) => (Some(kind), style),
+            None => (None, &NodeStyle::default()),
+        };
+        let default = &self.node_default;
+        let mut class_names = vec![NODE_CLASS.to_string()];
+        class_names.extend(kind.map(|k| node_kind_class(k)));
+        ResolvedNodeStyle {
+            kind: kind.cloned(),
+            fill: style
+                .fill
+                .clone()
+                .or_else(|| default.fill.clone())
+                .unwrap_or_default(),
+            border: style
+                .border
+                .clone()
+                .or_else(|| default.border.clone())
+                .unwrap_or_default(),
+            border_radius: style.border_radius.or(default.border_radius).unwrap_or(0.0),
+            icon: style.icon.clone().or_else(|| default.icon.clone()),
+            class_names,
+        }
+    }
+
+    /// Style of an edge of `kind`.
+    pub fn edge_style(&self, kind: Option<&str>) -> ResolvedEdgeStyle {
+        let (kind, style) = match kind.and_then(|k| self.edges.get_key_value(k)) {
+            Some((kind, style)) => (Some(kind), style),
+            None => (None, &EdgeStyle::default()),
+        };
+        let default = &self.edge_default;
+        let mut class_names = vec![EDGE_CLASS.to_string()];
+        class_names.extend(kind.map(|k| edge_kind_class(k)));
+        ResolvedEdgeStyle {
+            kind: kind.cloned(),
+            stroke: style
+                .stroke
+                .clone()
+                .or_else(|| default.stroke.clone())
+                .unwrap_or_default(),
+            dash: style
+                .dash
+                .clone()
+                .or_else(|| default.dash.clone())
+                .unwrap_or_default(),
+            arrowhead: style.arrowhead.or(default.arrowhead).unwrap_or_default(),
+            class_names,
+        }
+    }
+
+    pub fn resolve_node_style(&self, node: &Node<NavigationNodeData>) -> ResolvedNodeStyle {
+        self.node_style(node.data.as_ref().and_then(|d| d.kind.as_deref()))
+    }
+
+    pub fn resolve_edge_style(&self, edge: &Edge<EdgeData>) -> ResolvedEdgeStyle {
+        let kind = edge
+            .data
+            .as_ref()
+            .and_then(|d| d.metadata.get(KIND_KEY))
+            .and_then(|v| v.as_str());
+        self.edge_style(kind)
+    }
+
+    /// Rules for the default and every kind. Kind rules only set what the
+    /// kind overrides, so they layer over the default's.
+    pub fn to_css(&self) -> String {
+        let mut css = String::new();
+        node_rule(&mut css, format!(".{}", NODE_CLASS), &self.node_default);
+        for (kind, style) in &self.nodes {
+            node_rule(
+                &mut css,
+                format!(".{}.{}", NODE_CLASS, node_kind_class(kind)),
+                style,
+            );
+        }
+        edge_rule(&mut css, format!(".{}", EDGE_CLASS), &self.edge_default);
+        for (kind, style) in &self.edges {
+            edge_rule(
+                &mut css,
+                format!(".{}.{}", EDGE_CLASS, edge_kind_class(kind)),
+                style,
+            );
+        }
+        css
+    }
+}
+
+fn rule(css: &mut String, selector: String, declarations: Vec<String>) {
+    if declarations.is_empty() {
+        return;
+    }
+    css.push_str(&selector);
+    css.push_str(" {\n");
+    for declaration in declarations {
+        css.push_str(&format!("  {};\n", declaration));
+    }
+    css.push_str("}\n");
+}
+
+fn node_rule(css: &mut String, selector: String, style: &NodeStyle) {
+    let mut declarations = Vec::new();
+    if let Some(fill) = &style.fill {
+        declarations.push(format!("background: {}", fill));
+    }
+    if let Some(border) = &style.border {
+        declarations.push(format!("border: {}", border));
+    }
+    if let Some(radius) = style.border_radius {
+        declarations.push(format!("border-radius: {}px", radius));
+    }
+    rule(css, selector, declarations);
+}
+
+fn edge_rule(css: &mut String, selector: String, style: &EdgeStyle) {
+    let mut declarations = Vec::new();
+    if let Some(stroke) = &style.stroke {
+        declarations.push(format!("stroke: {}", stroke));
+    }
+    if let Some(dash) = &style.dash {
+        let dash = if dash.is_empty() {
+            "none".to_string()
+        } else {
+            dash.iter()
+                .map(f64::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        declarations.push(format!("stroke-dasharray: {}", dash));
+    }
+    if let Some(arrowhead) = style.arrowhead {
+        let marker = match arrowhead {
+            Arrowhead::None => "none".to_string(),
+            other => format!("url(#rsc-marker-{})", other.key()),
+        };
+        declarations.push(format!("marker-end: {}", marker));
+    }
+    rule(css, selector, declarations);
+}
+
+/// Whether `class` is one the style sheet hands out.
+fn is_style_class(class: &str) -> bool {
+    [NODE_CLASS, EDGE_CLASS]
+        .iter()
+        .any(|base| class == *base || class.starts_with(&format!("{}--", base)))
+}
+
+impl NavigationDesigner {
+    /// Replace the style sheet and restyle the canvas.
+    pub fn set_style_sheet(&mut self, sheet: FlowStyleSheet) {
+        self.style_sheet = sheet;
+        self.apply_flow_styles();
+    }
+
+    /// Give every node and edge the classes of its kind, keeping classes
+    /// from elsewhere.
+    pub fn apply_flow_styles(&mut self) {
+        for node in self.canvas.nodes.values_mut() {
+            let style = self.style_sheet.resolve_node_style(node);
+            node.class_names.retain(|c| !is_style_class(c));
+            node.class_names.extend(style.class_names);
+        }
+        for edge in self.canvas.edges.values_mut() {
+            let style = self.style_sheet.resolve_edge_style(edge);
+            edge.class_names.retain(|c| !is_style_class(c));
+            edge.class_names.extend(style.class_names);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Context, Workflow};
+
+    fn sheet() -> FlowStyleSheet {
+        FlowStyleSheet::default()
+            .with_node_kind("modal", NodeStyle::default().with_fill("#f1f5f9"))
+            .with_node_kind(
+                "Full page",
+                NodeStyle::default()
+                    .with_border_radius(0.0)
+                    .with_icon("page"),
+            )
+            .with_edge_kind(
+                "redirect",
+                EdgeStyle::default()
+                    .with_dash(vec![6.0, 4.0])
+                    .with_arrowhead(Arrowhead::ArrowClosed),
+            )
+    }
+
+    #[test]
+    fn test_fallback_and_unknown_kinds() {
+        let sheet = sheet();
+        let modal = sheet.node_style(Some("modal"));
+        assert_eq!(modal.fill, "#f1f5f9");
+        assert_eq!(modal.border, "1px solid #cbd5e1");
+        assert_eq!(modal.border_radius, 8.0);
+        assert_eq!(modal.class_names, ["rsc-node", "rsc-node--modal"]);
+
+        let unknown = sheet.node_style(Some("sheet"));
+        assert_eq!(unknown.kind, None);
+        assert_eq!(unknown, sheet.node_style(None));
+        assert_eq!(unknown.fill, "#ffffff");
+        assert_eq!(unknown.class_names, ["rsc-node"]);
+
+        let redirect = sheet.edge_style(Some("redirect"));
+        assert_eq!(redirect.stroke, "#94a3b8");
+        assert_eq!(redirect.dash, [6.0, 4.0]);
+        assert_eq!(redirect.arrowhead, Arrowhead::ArrowClosed);
+        assert_eq!(sheet.edge_style(Some("push")).arrowhead, Arrowhead::Arrow);
+
+        let json = serde_json::to_string(&sheet).unwrap();
+        let back: FlowStyleSheet = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, sheet);
+        assert!(
+            serde_json::from_str::<FlowStyleSheet>("{}")
+                .unwrap()
+                .is_default()
+        );
+    }
+
+    #[test]
+    fn test_class_names_are_stable() {
+        let sheet = sheet();
+        assert_eq!(node_kind_class("Full page"), "rsc-node--full-page");
+        assert_eq!(
+            sheet.node_style(Some("Full page")).class_names,
+            sheet.clone().node_style(Some("Full page")).class_names
+        );
+        assert_eq!(
+            sheet.to_css(),
+            concat!(
+                ".rsc-node {\n  background: #ffffff;\n  border: 1px solid #cbd5e1;\n",
+                "  border-radius: 8px;\n}\n",
+                ".rsc-node.rsc-node--modal {\n  background: #f1f5f9;\n}\n",
+                ".rsc-node.rsc-node--full-page {\n  border-radius: 0px;\n}\n",
+                ".rsc-edge {\n  stroke: #94a3b8;\n  stroke-dasharray: none;\n",
+                "  marker-end: url(#rsc-marker-arrow);\n}\n",
+                ".rsc-edge.rsc-edge--redirect {\n  stroke-dasharray: 6 4;\n",
+                "  marker-end: url(#rsc-marker-arrow-closed);\n}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_designer_applies_classes() {
+        let mut workflow = Workflow::new("Shop").with_id("w1");
+        let mut login = Context::new("Login").with_id("login");
+        login.kind = Some("modal".to_string());
+        workflow.add_context(login);
+        workflow.add_context(Context::new("Home").with_id("home"));
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        let mut edge = Edge::new("t1", "home", "login").with_data(EdgeData::default());
+        edge.class_names.push("token-cycle".to_string());
+        edge.data
+            .as_mut()
+            .unwrap()
+            .metadata
+            .insert(KIND_KEY.to_string(), "redirect".into());
+        designer.canvas.add_edge(edge);
+        designer.set_style_sheet(sheet());
+        designer.apply_flow_styles();
+
+        let classes = |id: &str| designer.canvas.nodes[id].class_names.clone();
+        assert_eq!(classes("login"), ["rsc-node", "rsc-node--modal"]);
+        assert_eq!(classes("home"), ["rsc-node"]);
+        assert_eq!(
+            designer.canvas.edges["t1"].class_names,
+            ["token-cycle", "rsc-edge", "rsc-edge--redirect"]
+        );
+    }
+}
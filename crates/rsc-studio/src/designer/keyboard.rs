@@ -0,0 +1,181 @@
+//! Keyboard control of the navigation canvas.
+//!
+//! Key events arrive through a [`KeyboardSensor`] and go to the store's
+//! canvas keyboard controller. Nudges and deletions are recorded in the
+//! flow history like the same edits made with the mouse.
+
+use rsc_dnd::{KeyboardSensor, SensorEvent, SensorEventType};
+use rsc_flow::{CanvasKey, FocusDirection, KeyboardCommand};
+
+use super::NavigationDesigner;
+use crate::store::StudioStore;
+
+/// The canvas key for `event`, honoring the sensor's move keys.
+pub fn canvas_key(sensor: &KeyboardSensor, event: &SensorEvent) -> Option<CanvasKey> {
+    if event.event_type != SensorEventType::KeyDown {
+        return None;
+    }
+    // Shortcuts with these modifiers belong to the studio.
+    let modifiers = event.modifiers;
+    if modifiers.ctrl || modifiers.alt || modifiers.meta {
+        return None;
+    }
+    let key = event.key.as_ref()?;
+    let keys = &sensor.move_keys;
+    let direction = [
+        (&keys.up, FocusDirection::Up),
+        (&keys.down, FocusDirection::Down),
+        (&keys.left, FocusDirection::Left),
+        (&keys.right, FocusDirection::Right),
+    ]
+    .into_iter()
+    .find(|(keys, _)| keys.contains(key))
+    .map(|(_, direction)| direction);
+    match direction {
+        Some(direction) => Some(CanvasKey::Arrow(direction)),
+        None => CanvasKey::from_key(key),
+    }
+}
+
+impl StudioStore {
+    /// Handle a key event on the navigation canvas. Returns whether the
+    /// key was used; unused keys should go on to the page.
+    pub fn canvas_key(
+        &mut self,
+        designer: &mut NavigationDesigner,
+        sensor: &KeyboardSensor,
+        event: &SensorEvent,
+    ) -> bool {
+        let Some(key) = canvas_key(sensor, event) else {
+            return false;
+        };
+        let command =
+            self.ui
+                .canvas
+                .keyboard
+                .handle_key(&mut designer.canvas, key, event.modifiers.shift);
+        match command {
+            KeyboardCommand::None => false,
+            KeyboardCommand::Nudge { id, to } => {
+                self.move_flow_node(designer, &id, to);
+                true
+            }
+            KeyboardCommand::Delete(id) => self.remove_flow_node(designer, &id),
+            KeyboardCommand::Focus(_)
+            | KeyboardCommand::Rename(_)
+            | KeyboardCommand::CancelRename(_) => true,
+        }
+    }
+
+    /// Rename the node whose rename field is open and close the field.
+    /// Returns false if no field is open or the label is unchanged.
+    pub fn commit_canvas_rename(&mut self, designer: &mut NavigationDesigner, name: &str) -> bool {
+        match self.ui.canvas.keyboard.finish_rename() {
+            Some(id) => self.rename_flow_node(designer, &id, name),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsc_dnd::Modifiers;
+    use rsc_flow::Position;
+
+    use crate::designer::navigation::NAVIGATION_SNAP_GRID;
+    use crate::entity::{Context, Workflow};
+    use crate::history::HistoryScope;
+    use crate::settings::interaction::NUDGE_STEP_SETTING;
+
+    /// Cart and Payment side by side, Done below Cart.
+    fn designer() -> NavigationDesigner {
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        for (id, name) in [("c1", "Cart"), ("c2", "Payment"), ("c3", "Done")] {
+            workflow.add_context(Context::new(name).with_id(id));
+        }
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        for (id, x, y) in [("c1", 0.0, 0.0), ("c2", 300.0, 0.0), ("c3", 0.0, 200.0)] {
+            designer.canvas.nodes[id].position = Position::new(x, y);
+        }
+        designer
+    }
+
+    fn key(key: &str, shift: bool) -> SensorEvent {
+        SensorEvent {
+            event_type: SensorEventType::KeyDown,
+            x: 0.0,
+            y: 0.0,
+            button: None,
+            key: Some(key.to_string()),
+            modifiers: Modifiers {
+                shift,
+                ..Modifiers::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_keyboard_editing() {
+        let mut store = StudioStore::new();
+        store.set_setting(NUDGE_STEP_SETTING, 4.0).unwrap();
+        let mut designer = designer();
+        let sensor = KeyboardSensor::new();
+
+        assert!(store.canvas_key(&mut designer, &sensor, &key("Tab", false)));
+        assert!(store.canvas_key(&mut designer, &sensor, &key("ArrowRight", false)));
+        assert_eq!(designer.canvas.selected_nodes, ["c2"]);
+
+        // The navigation canvas snaps to its grid; without it, nudges use
+        // the step setting.
+        assert!(store.canvas_key(&mut designer, &sensor, &key("ArrowDown", true)));
+        let grid = NAVIGATION_SNAP_GRID;
+        assert_eq!(
+            designer.canvas.nodes["c2"].position,
+            Position::new(300.0, grid)
+        );
+        designer.canvas.config.snap.grid = None;
+        assert!(store.canvas_key(&mut designer, &sensor, &key("ArrowLeft", true)));
+        assert_eq!(
+            designer.canvas.nodes["c2"].position,
+            Position::new(296.0, grid)
+        );
+        assert_eq!(
+            store
+                .history
+                .scope(HistoryScope::NavDesigner)
+                .and_then(|h| h.undo_label()),
+            Some("Move node")
+        );
+
+        assert!(store.canvas_key(&mut designer, &sensor, &key("Enter", false)));
+        assert!(!store.canvas_key(&mut designer, &sensor, &key("Delete", false)));
+        assert!(store.commit_canvas_rename(&mut designer, "Pay"));
+        assert_eq!(
+            designer.canvas.nodes["c2"].data.as_ref().unwrap().label,
+            "Pay"
+        );
+
+        assert!(store.canvas_key(&mut designer, &sensor, &key("Delete", false)));
+        assert!(designer.canvas.get_node("c2").is_none());
+        assert!(store.undo_flow(&mut designer));
+        assert!(designer.canvas.get_node("c2").is_some());
+    }
+
+    #[test]
+    fn test_sensor_keys() {
+        let mut sensor = KeyboardSensor::new();
+        sensor.move_keys.left.push("h".to_string());
+        let left = CanvasKey::Arrow(FocusDirection::Left);
+        assert_eq!(canvas_key(&sensor, &key("h", false)), Some(left));
+        assert_eq!(canvas_key(&sensor, &key("x", false)), None);
+
+        let mut ctrl = key("ArrowLeft", false);
+        ctrl.modifiers.ctrl = true;
+        assert_eq!(canvas_key(&sensor, &ctrl), None);
+        let mut up = key("ArrowLeft", false);
+        up.event_type = SensorEventType::KeyUp;
+        assert_eq!(canvas_key(&sensor, &up), None);
+    }
+}
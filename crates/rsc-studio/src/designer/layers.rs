@@ -0,0 +1,260 @@
+//! Edge layers in the navigation designer.
+//!
+//! The edge into a context or preset is its only incoming edge, so the
+//! entity carries the edge's layer. The layer registry is saved with the
+//! project; visibility toggles live in the canvas UI state.
+
+use std::collections::HashSet;
+
+use rsc_flow::{EdgeData, EdgeLayer, FlowCanvas};
+
+use crate::designer::NavigationDesigner;
+use crate::designer::navigation::NavigationNodeData;
+use crate::history::HistoryScope;
+use crate::store::{StoreChange, StudioStore};
+
+/// A row in the layers panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerListItem {
+    pub name: String,
+    pub color: String,
+    pub visible: bool,
+    /// Edges on the layer.
+    pub edge_count: usize,
+}
+
+/// Layers panel view model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayersPanel {
+    pub items: Vec<LayerListItem>,
+    /// Edges on no layer; these are always shown.
+    pub unlayered: usize,
+    pub hide_layer_only_nodes: bool,
+}
+
+impl LayersPanel {
+    pub fn build(store: &StudioStore) -> Self {
+        let layers: Vec<Option<&str>> = store
+            .workflows
+            .values()
+            .flat_map(|w| w.contexts.values())
+            .flat_map(|c| {
+                std::iter::once(c.layer.as_deref())
+                    .chain(c.presets.values().map(|p| p.layer.as_deref()))
+            })
+            .collect();
+        let count = |name: Option<&str>| layers.iter().filter(|l| **l == name).count();
+
+        Self {
+            items: store
+                .layers
+                .iter()
+                .map(|layer| LayerListItem {
+                    name: layer.name.clone(),
+                    color: layer.color.clone(),
+                    visible: store.is_layer_visible(&layer.name),
+                    edge_count: count(Some(&layer.name)),
+                })
+                .collect(),
+            unlayered: count(None),
+            hide_layer_only_nodes: store.ui.canvas.hide_layer_only_nodes,
+        }
+    }
+}
+
+impl StudioStore {
+    /// Whether a layer is shown: the user's toggle if any, otherwise the
+    /// layer's default.
+    pub fn is_layer_visible(&self, name: &str) -> bool {
+        self.ui
+            .canvas
+            .layer_visibility
+            .get(name)
+            .copied()
+            .unwrap_or_else(|| self.layers.visible_by_default(name))
+    }
+
+    /// Names of the layers currently hidden.
+    pub fn hidden_layers(&self) -> HashSet<String> {
+        self.layers
+            .iter()
+            .filter(|l| !self.is_layer_visible(&l.name))
+            .map(|l| l.name.clone())
+            .collect()
+    }
+
+    /// Flip a layer's visibility.
+    pub fn toggle_layer(&mut self, name: &str) {
+        let visible = self.is_layer_visible(name);
+        self.ui
+            .canvas
+            .layer_visibility
+            .insert(name.to_string(), !visible);
+    }
+
+    /// Add or replace a layer.
+    pub fn add_layer(&mut self, layer: EdgeLayer) {
+        self.layers.insert(layer);
+    }
+
+    /// Delete a layer. Its edges move to no layer, as one undoable change.
+    pub fn remove_layer(&mut self, name: &str) -> Option<EdgeLayer> {
+        let removed = self.layers.remove(name)?;
+        self.ui.canvas.layer_visibility.shift_remove(name);
+
+        let changes: Vec<StoreChange> = self
+            .workflows
+            .values()
+            .enumerate()
+            .filter_map(|(index, workflow)| {
+                let mut after = workflow.clone();
+                let mut changed = false;
+                for context in after.contexts.values_mut() {
+                    let presets = context.presets.values_mut().map(|p| &mut p.layer);
+                    for layer in std::iter::once(&mut context.layer).chain(presets) {
+                        if layer.as_deref() == Some(name) {
+                            *layer = None;
+                            changed = true;
+                        }
+                    }
+                }
+                changed.then(|| StoreChange::Workflow {
+                    id: workflow.id.clone(),
+                    index,
+                    before: Some(Box::new(workflow.clone())),
+                    after: Some(Box::new(after)),
+                })
+            })
+            .collect();
+        if !changes.is_empty() {
+            self.commit(
+                HistoryScope::NavDesigner,
+                format!("Delete layer {}", name),
+                StoreChange::Batch(changes),
+            );
+        }
+        Some(removed)
+    }
+}
+
+impl NavigationDesigner {
+    /// The canvas as shown with the store's hidden layers, for rendering
+    /// and SVG export.
+    pub fn visible_canvas(&self, store: &StudioStore) -> FlowCanvas<NavigationNodeData, EdgeData> {
+        self.canvas.with_layers_hidden(
+            &store.hidden_layers(),
+            store.ui.canvas.hide_layer_only_nodes,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Context, Preset, Workflow};
+    use crate::export::print::{paginate_flow, render_svg_pages};
+    use rsc_flow::Dimensions;
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        store.add_layer(EdgeLayer::new("happy", "#22c55e"));
+        store.add_layer(EdgeLayer::new("error", "#ef4444"));
+        store.add_layer(EdgeLayer::new("admin", "#a855f7").hidden());
+
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        let mut cart = Context::new("Cart").with_id("c1");
+        cart.layer = Some("happy".to_string());
+        let mut retry = Preset::new("Retry").with_id("p1");
+        retry.layer = Some("error".to_string());
+        cart.add_preset(retry);
+        cart.add_preset(Preset::new("Compact").with_id("p2"));
+        workflow.add_context(cart);
+        let mut audit = Context::new("Audit").with_id("c2");
+        audit.layer = Some("admin".to_string());
+        workflow.add_context(audit);
+        store.add_workflow(workflow);
+        store
+    }
+
+    fn designer(store: &StudioStore) -> NavigationDesigner {
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&store.workflows.values().collect::<Vec<_>>());
+        designer
+    }
+
+    #[test]
+    fn test_panel_and_toggles() {
+        let mut store = store();
+        let panel = LayersPanel::build(&store);
+        let rows: Vec<(&str, bool, usize)> = panel
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.visible, i.edge_count))
+            .collect();
+        assert_eq!(
+            rows,
+            [("happy", true, 1), ("error", true, 1), ("admin", false, 1)]
+        );
+        assert_eq!(panel.unlayered, 1);
+
+        store.toggle_layer("admin");
+        store.toggle_layer("error");
+        assert_eq!(store.hidden_layers(), HashSet::from(["error".to_string()]));
+
+        // Hiding "happy" hides the cart edge; with node hiding on, the cart
+        // and the presets only reachable through it go too.
+        store.toggle_layer("happy");
+        let designer = designer(&store);
+        assert_eq!(designer.visible_canvas(&store).edges.len(), 2);
+        store.ui.canvas.hide_layer_only_nodes = true;
+        let visible = designer.visible_canvas(&store);
+        assert_eq!(visible.nodes.keys().collect::<Vec<_>>(), ["w1", "c2"]);
+        // The full canvas still has every node reachable.
+        assert!(designer.canvas.unreachable_nodes().is_empty());
+    }
+
+    #[test]
+    fn test_remove_layer_reassigns_edges() {
+        let mut store = store();
+        store.toggle_layer("error");
+        assert!(store.remove_layer("error").is_some());
+        assert!(store.remove_layer("error").is_none());
+
+        let retry = &store.get_workflow("w1").unwrap().contexts["c1"].presets["p1"];
+        assert_eq!(retry.layer, None);
+        assert!(!store.ui.canvas.layer_visibility.contains_key("error"));
+        assert_eq!(LayersPanel::build(&store).unlayered, 2);
+
+        // The edge is shown again now that it is unlayered.
+        let designer = designer(&store);
+        assert_eq!(designer.visible_canvas(&store).edges.len(), 3);
+
+        assert!(store.undo_scope(HistoryScope::NavDesigner));
+        let retry = &store.get_workflow("w1").unwrap().contexts["c1"].presets["p1"];
+        assert_eq!(retry.layer.as_deref(), Some("error"));
+    }
+
+    #[test]
+    fn test_svg_export_honors_visibility() {
+        let mut store = store();
+        store.ui.canvas.hide_layer_only_nodes = true;
+        let mut designer = designer(&store);
+        designer.apply_layout();
+
+        let count = |canvas: &FlowCanvas<NavigationNodeData, EdgeData>| {
+            let page = Dimensions {
+                width: 10_000.0,
+                height: 10_000.0,
+            };
+            let tiles = paginate_flow(canvas, page, 0.0);
+            let svg = render_svg_pages(canvas, &tiles).concat();
+            (
+                svg.matches("class=\"edge\"").count(),
+                svg.matches("class=\"node\"").count(),
+            )
+        };
+        assert_eq!(count(&designer.canvas), (4, 5));
+        // The admin layer is hidden by default.
+        assert_eq!(count(&designer.visible_canvas(&store)), (3, 4));
+    }
+}
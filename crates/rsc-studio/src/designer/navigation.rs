@@ -2,16 +2,28 @@
 //! Visual editor for designing workflow → context → preset hierarchies.
 
 use rsc_flow::prelude::*;
+use rsc_flow::{EdgeLabel, NoDuplicateEdges, NoSelfLoops, SnapConfig, ValidatorChain, VisibleSet};
 
+use crate::designer::flow_style::FlowStyleSheet;
 use crate::entity::{Context, Preset, Workflow};
+use crate::timestamp::Timestamps;
+
+/// Grid dropped nodes snap to, in canvas units.
+pub const NAVIGATION_SNAP_GRID: f64 = 8.0;
+
+/// Screen pixels rendered beyond each viewport edge, so panning does not
+/// show nodes popping in.
+pub const NAVIGATION_OVERSCAN: f64 = 200.0;
 
 /// Navigation designer state.
 #[derive(Debug, Clone)]
 pub struct NavigationDesigner {
     /// Flow canvas for visualization.
-    pub canvas: FlowCanvas<NavigationNodeData, ()>,
+    pub canvas: FlowCanvas<NavigationNodeData, EdgeData>,
     /// Layout configuration.
     pub layout_config: LayoutConfig,
+    /// Node and edge styles by kind.
+    pub style_sheet: FlowStyleSheet,
 }
 
 impl Default for NavigationDesigner {
@@ -22,20 +34,35 @@ impl Default for NavigationDesigner {
 
 impl NavigationDesigner {
     pub fn new() -> Self {
+        let mut canvas = FlowCanvas::new();
+        canvas.config.snap =
+            SnapConfig::default().with_grid(NAVIGATION_SNAP_GRID, NAVIGATION_SNAP_GRID);
+        canvas.set_connection_validator(Box::new(
+            ValidatorChain::new().with(NoDuplicateEdges).with(NoSelfLoops),
+        ));
         Self {
-            canvas: FlowCanvas::new(),
+            canvas,
             layout_config: LayoutConfig {
                 direction: LayoutDirection::TopToBottom,
                 node_sep: 80.0,
                 rank_sep: 120.0,
                 ..Default::default()
             },
+            style_sheet: FlowStyleSheet::default(),
         }
     }
 
-    /// Load workflows into the canvas.
+    /// Snap dropped nodes to a `grid`-unit grid; 0 turns snapping off.
+    pub fn set_snap_grid(&mut self, grid: f64) {
+        self.canvas.config.snap.grid = (grid > 0.0).then_some((grid, grid));
+    }
+
+    /// Load workflows into the canvas, keeping its configuration.
     pub fn load_workflows(&mut self, workflows: &[&Workflow]) {
+        let annotations = self.take_annotations();
+        let config = std::mem::take(&mut self.canvas.config);
         self.canvas = FlowCanvas::new();
+        self.canvas.config = config;
 
         for workflow in workflows {
             self.add_workflow_node(workflow);
@@ -43,6 +70,33 @@ impl NavigationDesigner {
 
         // Apply auto-layout
         self.canvas.auto_layout(self.layout_config.clone());
+        self.restore_annotations(annotations);
+        self.apply_flow_styles();
+    }
+
+    /// Remove annotation nodes, which sit where they were placed and stay
+    /// out of auto-layout.
+    fn take_annotations(&mut self) -> Vec<Node<NavigationNodeData>> {
+        let ids: Vec<String> = self
+            .canvas
+            .nodes
+            .values()
+            .filter(|n| {
+                n.data
+                    .as_ref()
+                    .is_some_and(|d| d.entity_type == EntityType::Annotation)
+            })
+            .map(|n| n.id.clone())
+            .collect();
+        ids.iter()
+            .filter_map(|id| self.canvas.remove_node(id))
+            .collect()
+    }
+
+    fn restore_annotations(&mut self, annotations: Vec<Node<NavigationNodeData>>) {
+        for node in annotations {
+            self.canvas.add_node(node);
+        }
     }
 
     /// Add a workflow node and its children.
@@ -59,6 +113,8 @@ impl NavigationDesigner {
             label: workflow.name.clone(),
             icon: workflow.icon.clone(),
             description: workflow.description.clone(),
+            timestamps: workflow.timestamps.clone(),
+            kind: None,
         });
 
         self.canvas.add_node(node);
@@ -69,7 +125,7 @@ impl NavigationDesigner {
     }
 
     /// Add a context node and its children.
-    fn add_context_node(&mut self, context: &Context, parent_id: &str) {
+    pub(crate) fn add_context_node(&mut self, context: &Context, parent_id: &str) {
         let node = Node::new(
             &context.id,
             NodeType::Custom("context".to_string()),
@@ -82,10 +138,16 @@ impl NavigationDesigner {
             label: context.name.clone(),
             icon: context.icon.clone(),
             description: context.description.clone(),
+            timestamps: context.timestamps.clone(),
+            kind: context.kind.clone(),
         });
 
         self.canvas.add_node(node);
-        self.canvas.add_edge(Edge::auto(parent_id, &context.id));
+        self.canvas
+            .add_edge(Edge::auto(parent_id, &context.id).with_data(EdgeData {
+                layer: context.layer.clone(),
+                ..Default::default()
+            }));
 
         for preset in context.presets.values() {
             self.add_preset_node(preset, &context.id);
@@ -106,10 +168,16 @@ impl NavigationDesigner {
             label: preset.name.clone(),
             icon: preset.icon.clone(),
             description: preset.description.clone(),
+            timestamps: preset.timestamps.clone(),
+            kind: preset.kind.clone(),
         });
 
         self.canvas.add_node(node);
-        self.canvas.add_edge(Edge::auto(context_id, &preset.id));
+        self.canvas
+            .add_edge(Edge::auto(context_id, &preset.id).with_data(EdgeData {
+                layer: preset.layer.clone(),
+                ..Default::default()
+            }));
     }
 
     /// Get the entity at a node.
@@ -117,15 +185,61 @@ impl NavigationDesigner {
         self.canvas.get_node(node_id).and_then(|n| n.data.as_ref())
     }
 
+    /// Name the transition an edge draws. The name goes into the edge
+    /// data and becomes the edge's label, keeping any placement the label
+    /// already had; an empty name clears both. Returns false if there is
+    /// no such edge.
+    pub fn set_transition_label(&mut self, edge_id: &str, name: &str) -> bool {
+        let Some(edge) = self.canvas.edges.get_mut(edge_id) else {
+            return false;
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            edge.label = None;
+            if let Some(data) = edge.data.as_mut() {
+                data.label = None;
+            }
+            return true;
+        }
+        edge.data.get_or_insert_with(EdgeData::default).label = Some(name.to_string());
+        match edge.label.as_mut() {
+            Some(label) => label.text = name.to_string(),
+            None => edge.label = Some(EdgeLabel::new(name)),
+        }
+        true
+    }
+
+    /// Label every edge whose data names a transition but which has no
+    /// label yet, as after loading a canvas saved without labels.
+    pub fn sync_transition_labels(&mut self) {
+        for edge in self.canvas.edges.values_mut() {
+            if edge.label.is_none()
+                && let Some(name) = edge.data.as_ref().and_then(|d| d.label.as_deref())
+                && !name.is_empty()
+            {
+                edge.label = Some(EdgeLabel::new(name));
+            }
+        }
+    }
+
     /// Re-layout the canvas.
     pub fn apply_layout(&mut self) {
+        let annotations = self.take_annotations();
         self.canvas.auto_layout(self.layout_config.clone());
+        self.restore_annotations(annotations);
     }
 
     /// Fit the viewport to show all nodes.
     pub fn fit_view(&mut self, canvas_size: Dimensions) {
         self.canvas.fit_view(50.0, canvas_size);
     }
+
+    /// Nodes and edges to render on a `canvas_size` screen area. The
+    /// minimap still draws the whole canvas.
+    pub fn visible_elements(&self, canvas_size: Dimensions) -> VisibleSet {
+        self.canvas
+            .visible_elements(&self.canvas.viewport, canvas_size, NAVIGATION_OVERSCAN)
+    }
 }
 
 /// Node data for navigation entities.
@@ -137,6 +251,10 @@ pub struct NavigationNodeData {
     pub label: String,
     pub icon: Option<String>,
     pub description: Option<String>,
+    /// Created/modified times of the entity, for the inspector.
+    pub timestamps: Timestamps,
+    /// Visual kind of the entity, if any.
+    pub kind: Option<String>,
 }
 
 /// Entity type for node styling.
@@ -145,6 +263,8 @@ pub enum EntityType {
     Workflow,
     Context,
     Preset,
+    /// Dropped image; `entity_id` is its asset id.
+    Annotation,
 }
 
 #[cfg(test)]
@@ -163,6 +283,42 @@ mod tests {
 
         assert_eq!(designer.canvas.nodes.len(), 3); // workflow + context + preset
         assert_eq!(designer.canvas.edges.len(), 2); // w1->c1, c1->p1
+        assert_eq!(designer.canvas.config.snap.grid, Some((8.0, 8.0)));
+        assert!(designer.canvas.config.connection_validator.is_some());
+    }
+
+    #[test]
+    fn test_transition_labels() {
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        workflow.add_context(Context::new("Cart").with_id("c1"));
+        workflow.add_context(Context::new("Payment").with_id("c2"));
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        for id in ["t1", "t2"] {
+            designer.canvas.add_edge(Edge::new(id, "c1", "c2"));
+        }
+        designer.canvas.edges["t2"].data = Some(EdgeData {
+            label: Some("Express".to_string()),
+            ..Default::default()
+        });
+
+        assert!(designer.set_transition_label("t1", " Pay "));
+        assert!(!designer.set_transition_label("missing", "Pay"));
+        designer.sync_transition_labels();
+        let edge = designer.canvas.get_edge("t1").unwrap();
+        assert_eq!(edge.label.as_ref().unwrap().text, "Pay");
+        assert_eq!(edge.data.as_ref().unwrap().label.as_deref(), Some("Pay"));
+        let edge = designer.canvas.get_edge("t2").unwrap();
+        assert_eq!(edge.label.as_ref().unwrap().text, "Express");
+
+        // Both transitions join the same contexts; their labels don't overlap.
+        let positions = designer.canvas.label_positions();
+        assert_eq!(positions.len(), 2);
+        assert_ne!(positions["t1"], positions["t2"]);
+
+        designer.set_transition_label("t1", "");
+        assert!(designer.canvas.get_edge("t1").unwrap().label.is_none());
+        assert_eq!(designer.canvas.label_positions().len(), 1);
     }
 
     #[test]
@@ -390,4 +546,21 @@ mod tests {
         assert_eq!(context_edges.len(), 1);
         assert_eq!(context_edges[0].source, "w1");
     }
+
+    #[test]
+    fn test_visible_elements_follow_the_viewport() {
+        let mut workflow = Workflow::new("Dev").with_id("w1");
+        workflow.add_context(Context::new("Code").with_id("c1"));
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        let screen = Dimensions::new(800.0, 600.0);
+
+        designer.fit_view(screen);
+        let visible = designer.visible_elements(screen);
+        assert_eq!(visible.nodes.len(), 2);
+        assert_eq!(visible.edges.len(), 1);
+
+        designer.canvas.viewport.transform = ViewportTransform::new(-10_000.0, 0.0, 1.0);
+        assert!(designer.visible_elements(screen).is_empty());
+    }
 }
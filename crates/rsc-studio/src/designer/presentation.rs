@@ -0,0 +1,393 @@
+//! Step-through presentation of a path on the navigation canvas.
+//!
+//! Presenting walks a node sequence one step at a time: each step fits the
+//! viewport to its node, highlights the edge it was reached by and dims
+//! everything else. The viewport, selection and keyboard focus from before
+//! the presentation are put back on exit. The presentation lives in the
+//! store for the session only and is never written to a project.
+
+use std::fmt;
+
+use rsc_flow::prelude::*;
+use rsc_flow::{EdgeId, NodeId};
+use thiserror::Error;
+
+use super::NavigationDesigner;
+use crate::store::StudioStore;
+
+/// Space around the focused node, in screen pixels.
+pub const PRESENTATION_PADDING: f64 = 80.0;
+
+/// Presentation error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PresentationError {
+    /// The path has no nodes.
+    #[error("Presentation path is empty")]
+    EmptyPath,
+
+    /// A node of the path is not on the canvas.
+    #[error("Node '{0}' not found")]
+    NodeNotFound(String),
+
+    /// Two consecutive nodes have no edge between them.
+    #[error("No edge between '{0}' and '{1}'")]
+    NotConnected(String, String),
+}
+
+/// One node of the presented path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresentationStep {
+    pub node_id: NodeId,
+    /// Edge from the previous step; `None` for the first.
+    pub edge_id: Option<EdgeId>,
+    pub title: String,
+    /// Markdown for the presenter panel.
+    pub description: Option<String>,
+}
+
+/// Position within the path, shown as "Step 3 of 7".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresentationProgress {
+    /// 1-based.
+    pub step: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for PresentationProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Step {} of {}", self.step, self.total)
+    }
+}
+
+/// Canvas state put back when the presentation ends.
+#[derive(Debug, Clone)]
+struct SavedView {
+    viewport: Viewport,
+    selected_nodes: Vec<NodeId>,
+    selected_edges: Vec<EdgeId>,
+    focused_node: Option<NodeId>,
+}
+
+/// A presentation in progress.
+#[derive(Debug, Clone)]
+pub struct Presentation {
+    pub steps: Vec<PresentationStep>,
+    current: usize,
+    canvas_size: Dimensions,
+    saved: SavedView,
+}
+
+impl Presentation {
+    /// Step being shown.
+    pub fn current(&self) -> &PresentationStep {
+        &self.steps[self.current]
+    }
+
+    pub fn progress(&self) -> PresentationProgress {
+        PresentationProgress {
+            step: self.current + 1,
+            total: self.steps.len(),
+        }
+    }
+
+    /// Whether a node is drawn dimmed: every node but the current one.
+    pub fn is_node_dimmed(&self, id: &str) -> bool {
+        self.current().node_id != id
+    }
+
+    /// Whether an edge is highlighted: the one the current step was
+    /// reached by. Every other edge is dimmed.
+    pub fn is_edge_highlighted(&self, id: &str) -> bool {
+        self.current().edge_id.as_deref() == Some(id)
+    }
+}
+
+impl NavigationDesigner {
+    /// Steps for `path`, with the edge joining each node to the one before.
+    /// An edge in the path's direction is preferred over one against it.
+    pub fn presentation_steps(
+        &self,
+        path: &[&str],
+    ) -> Result<Vec<PresentationStep>, PresentationError> {
+        if path.is_empty() {
+            return Err(PresentationError::EmptyPath);
+        }
+        let mut steps = Vec::with_capacity(path.len());
+        for (i, &id) in path.iter().enumerate() {
+            let node = self
+                .canvas
+                .get_node(id)
+                .ok_or_else(|| PresentationError::NodeNotFound(id.to_string()))?;
+            let edge_id = match i.checked_sub(1).map(|p| path[p]) {
+                None => None,
+                Some(prev) => {
+                    let joins = |source: &str, target: &str| {
+                        self.canvas
+                            .edges
+                            .values()
+                            .find(|e| e.source == source && e.target == target)
+                    };
+                    let edge = joins(prev, id).or_else(|| joins(id, prev)).ok_or_else(|| {
+                        PresentationError::NotConnected(prev.to_string(), id.to_string())
+                    })?;
+                    Some(edge.id.clone())
+                }
+            };
+            let data = node.data.as_ref();
+            steps.push(PresentationStep {
+                node_id: node.id.clone(),
+                edge_id,
+                title: data.map_or_else(|| node.id.clone(), |d| d.label.clone()),
+                description: data.and_then(|d| d.description.clone()),
+            });
+        }
+        Ok(steps)
+    }
+
+    /// Fit the viewport to a step's node and make it the only selection.
+    fn show_step(&mut self, step: &PresentationStep, canvas_size: Dimensions) {
+        self.canvas
+            .zoom_to_node(&step.node_id, PRESENTATION_PADDING, canvas_size);
+        self.canvas.select_node(&step.node_id, false);
+        self.canvas.focused_node = Some(step.node_id.clone());
+    }
+}
+
+impl StudioStore {
+    /// Start presenting `path`, replacing any presentation in progress,
+    /// and show its first step.
+    pub fn start_presentation(
+        &mut self,
+        designer: &mut NavigationDesigner,
+        path: &[&str],
+        canvas_size: Dimensions,
+    ) -> Result<(), PresentationError> {
+        let steps = designer.presentation_steps(path)?;
+        self.exit_presentation(designer);
+        let canvas = &designer.canvas;
+        let saved = SavedView {
+            viewport: canvas.viewport.clone(),
+            selected_nodes: canvas.selected_nodes.clone(),
+            selected_edges: canvas.selected_edges.clone(),
+            focused_node: canvas.focused_node.clone(),
+        };
+        designer.show_step(&steps[0], canvas_size);
+        self.presentation = Some(Presentation {
+            steps,
+            current: 0,
+            canvas_size,
+            saved,
+        });
+        Ok(())
+    }
+
+    /// Move `delta` steps, staying within the path. Returns false when
+    /// not presenting or already at that end.
+    fn present_step(&mut self, designer: &mut NavigationDesigner, delta: isize) -> bool {
+        let Some(presentation) = self.presentation.as_mut() else {
+            return false;
+        };
+        let Some(next) = presentation
+            .current
+            .checked_add_signed(delta)
+            .filter(|&i| i < presentation.steps.len())
+        else {
+            return false;
+        };
+        presentation.current = next;
+        designer.show_step(&presentation.steps[next], presentation.canvas_size);
+        true
+    }
+
+    pub fn present_next(&mut self, designer: &mut NavigationDesigner) -> bool {
+        self.present_step(designer, 1)
+    }
+
+    pub fn present_prev(&mut self, designer: &mut NavigationDesigner) -> bool {
+        self.present_step(designer, -1)
+    }
+
+    /// End the presentation and restore the viewport, selection and focus
+    /// from before it started.
+    pub fn exit_presentation(&mut self, designer: &mut NavigationDesigner) -> bool {
+        let Some(presentation) = self.presentation.take() else {
+            return false;
+        };
+        let saved = presentation.saved;
+        let canvas = &mut designer.canvas;
+        canvas.clear_selection();
+        for id in &saved.selected_nodes {
+            canvas.select_node(id, true);
+        }
+        for id in &saved.selected_edges {
+            if let Some(edge) = canvas.edges.get_mut(id) {
+                edge.selected = true;
+            }
+        }
+        canvas.selected_edges = saved.selected_edges;
+        canvas.viewport = saved.viewport;
+        canvas.focused_node = saved.focused_node;
+        true
+    }
+
+    /// Handle a key while presenting: arrows and page keys step, Escape
+    /// exits. Returns whether the key was used.
+    pub fn presentation_key(&mut self, designer: &mut NavigationDesigner, key: &str) -> bool {
+        if self.presentation.is_none() {
+            return false;
+        }
+        match key {
+            "ArrowRight" | "ArrowDown" | "PageDown" | " " => self.present_next(designer),
+            "ArrowLeft" | "ArrowUp" | "PageUp" => self.present_prev(designer),
+            "Escape" => self.exit_presentation(designer),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Context, Workflow};
+
+    const SCREEN: Dimensions = Dimensions {
+        width: 1200.0,
+        height: 800.0,
+    };
+
+    fn setup() -> (StudioStore, NavigationDesigner) {
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        let mut cart = Context::new("Cart").with_id("c1");
+        cart.description = Some("Review **items**".to_string());
+        workflow.add_context(cart);
+        workflow.add_context(Context::new("Payment").with_id("c2"));
+        let mut store = StudioStore::new();
+        store.add_workflow(workflow);
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&store.workflows.values().collect::<Vec<_>>());
+        designer.canvas.add_edge(Edge::new("pay", "c1", "c2"));
+        (store, designer)
+    }
+
+    #[test]
+    fn test_step_through_path() {
+        let (mut store, mut designer) = setup();
+        store
+            .start_presentation(&mut designer, &["w1", "c1", "c2"], SCREEN)
+            .unwrap();
+        let into_cart = designer.canvas.get_incoming_edges("c1")[0].id.clone();
+
+        let expect =
+            |store: &StudioStore, designer: &NavigationDesigner, node: &str, edge: Option<&str>| {
+                let p = store.presentation.as_ref().unwrap();
+                assert_eq!(p.current().node_id, node);
+                assert_eq!(designer.canvas.selected_nodes, [node]);
+                assert_eq!(designer.canvas.focused_node.as_deref(), Some(node));
+                assert!(!p.is_node_dimmed(node));
+                for other in designer.canvas.nodes.keys().filter(|id| *id != node) {
+                    assert!(p.is_node_dimmed(other));
+                }
+                let highlighted: Vec<_> = designer
+                    .canvas
+                    .edges
+                    .keys()
+                    .filter(|id| p.is_edge_highlighted(id))
+                    .map(String::as_str)
+                    .collect();
+                assert_eq!(highlighted, edge.into_iter().collect::<Vec<_>>());
+                // The focused node is centered on screen.
+                let bounds = designer.canvas.get_node(node).unwrap();
+                let dims = bounds.dimensions.unwrap_or(Dimensions::new(150.0, 50.0));
+                let center = Position::new(
+                    bounds.position.x + dims.width / 2.0,
+                    bounds.position.y + dims.height / 2.0,
+                );
+                let on_screen = designer.canvas.viewport.canvas_to_screen(center);
+                assert!((on_screen.x - 600.0).abs() < 1e-6 && (on_screen.y - 400.0).abs() < 1e-6);
+            };
+
+        expect(&store, &designer, "w1", None);
+        assert_eq!(
+            store.presentation.as_ref().unwrap().progress().to_string(),
+            "Step 1 of 3"
+        );
+        assert!(!store.present_prev(&mut designer));
+
+        assert!(store.presentation_key(&mut designer, "ArrowRight"));
+        expect(&store, &designer, "c1", Some(&into_cart));
+        let p = store.presentation.as_ref().unwrap();
+        assert_eq!(p.current().title, "Cart");
+        assert_eq!(p.current().description.as_deref(), Some("Review **items**"));
+
+        assert!(store.present_next(&mut designer));
+        expect(&store, &designer, "c2", Some("pay"));
+        assert_eq!(
+            store.presentation.as_ref().unwrap().progress().to_string(),
+            "Step 3 of 3"
+        );
+        assert!(!store.present_next(&mut designer));
+
+        assert!(store.presentation_key(&mut designer, "ArrowLeft"));
+        expect(&store, &designer, "c1", Some(&into_cart));
+    }
+
+    #[test]
+    fn test_exit_restores_view() {
+        let (mut store, mut designer) = setup();
+        designer.canvas.viewport.pan(35.0, -20.0);
+        designer.canvas.select_node("c2", false);
+        designer.canvas.edges.get_mut("pay").unwrap().selected = true;
+        designer.canvas.selected_edges.push("pay".to_string());
+        designer.canvas.focused_node = Some("c2".to_string());
+        let transform = |d: &NavigationDesigner| {
+            let t = d.canvas.viewport.transform;
+            (t.x, t.y, t.zoom)
+        };
+        let before = transform(&designer);
+
+        store
+            .start_presentation(&mut designer, &["w1", "c1", "c2"], SCREEN)
+            .unwrap();
+        store.present_next(&mut designer);
+        assert_ne!(transform(&designer), before);
+        assert!(!designer.canvas.edges["pay"].selected);
+
+        assert!(store.presentation_key(&mut designer, "Escape"));
+        assert!(store.presentation.is_none());
+        assert_eq!(transform(&designer), before);
+        assert_eq!(designer.canvas.selected_nodes, ["c2"]);
+        assert_eq!(designer.canvas.selected_edges, ["pay"]);
+        assert!(designer.canvas.nodes["c2"].selected);
+        assert!(!designer.canvas.nodes["c1"].selected);
+        assert!(designer.canvas.edges["pay"].selected);
+        assert_eq!(designer.canvas.focused_node.as_deref(), Some("c2"));
+        assert!(!store.presentation_key(&mut designer, "Escape"));
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        let (mut store, mut designer) = setup();
+        assert_eq!(
+            store.start_presentation(&mut designer, &[], SCREEN),
+            Err(PresentationError::EmptyPath)
+        );
+        assert_eq!(
+            designer.presentation_steps(&["w1", "nope"]),
+            Err(PresentationError::NodeNotFound("nope".into()))
+        );
+        assert_eq!(
+            designer
+                .presentation_steps(&["w1", "c2", "w1"])
+                .map(|s| s.len()),
+            Ok(3)
+        );
+        designer.canvas.remove_edge("pay");
+        assert_eq!(
+            designer.presentation_steps(&["c1", "c2"]),
+            Err(PresentationError::NotConnected("c1".into(), "c2".into()))
+        );
+        // Going backwards along an edge is fine.
+        assert!(designer.presentation_steps(&["c1", "w1"]).is_ok());
+        assert!(store.presentation.is_none());
+    }
+}
@@ -0,0 +1,939 @@
+//! CSS value parsing.
+//!
+//! [`CssValue::parse`] reads a property or token value into numbers with
+//! units, colors, identifiers, strings, function calls and lists, so token
+//! validation can check what a value is rather than how it ends. The
+//! `expect_*` functions back the token validators in [`css`](super::css)
+//! and say what they found, e.g. "Expected a length, found a color".
+
+use std::fmt;
+
+use thiserror::Error;
+
+use super::css::color::Rgb;
+use super::css::contrast::parse_color;
+
+/// Units of `<length>` values.
+const LENGTH_UNITS: &[&str] = &[
+    "px", "rem", "em", "ex", "ch", "cap", "ic", "lh", "rlh", "vw", "vh", "vi", "vb", "vmin",
+    "vmax", "svw", "svh", "lvw", "lvh", "dvw", "dvh", "cqw", "cqh", "cqi", "cqb", "cqmin", "cqmax",
+    "cm", "mm", "q", "in", "pt", "pc",
+];
+
+/// Other dimension units and what they measure.
+const OTHER_UNITS: &[(&str, &str)] = &[
+    ("ms", "a time"),
+    ("s", "a time"),
+    ("deg", "an angle"),
+    ("grad", "an angle"),
+    ("rad", "an angle"),
+    ("turn", "an angle"),
+    ("dpi", "a resolution"),
+    ("dpcm", "a resolution"),
+    ("dppx", "a resolution"),
+    ("x", "a resolution"),
+    ("fr", "a flex value"),
+    ("hz", "a frequency"),
+    ("khz", "a frequency"),
+];
+
+/// Functions that compute a number, length or other numeric value.
+const MATH_FUNCTIONS: &[&str] = &["calc", "min", "max", "clamp", "round", "mod", "rem", "abs"];
+
+/// Functions that produce a color.
+const COLOR_FUNCTIONS: &[&str] = &[
+    "rgb",
+    "rgba",
+    "hsl",
+    "hsla",
+    "hwb",
+    "lab",
+    "lch",
+    "oklab",
+    "oklch",
+    "color",
+    "color-mix",
+    "light-dark",
+];
+
+/// Color functions [`parse_color`] can evaluate.
+const RGB_FUNCTIONS: &[&str] = &["rgb", "rgba", "hsl", "hsla"];
+
+/// Functions that produce an image.
+const IMAGE_FUNCTIONS: &[&str] = &[
+    "url",
+    "linear-gradient",
+    "radial-gradient",
+    "conic-gradient",
+    "repeating-linear-gradient",
+    "repeating-radial-gradient",
+    "repeating-conic-gradient",
+    "image-set",
+];
+
+/// CSS value errors.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CssValueError {
+    /// No value entered.
+    #[error("Value is empty")]
+    Empty,
+
+    /// A character that can't start a value here.
+    #[error("Unexpected '{ch}' at {at}")]
+    UnexpectedChar { ch: char, at: usize },
+
+    /// A function call without its closing parenthesis.
+    #[error("Missing ')' after '{0}('")]
+    UnclosedFunction(String),
+
+    /// A quoted string without its closing quote.
+    #[error("Unterminated string")]
+    UnterminatedString,
+
+    /// Two commas in a row, or a comma at the start or end.
+    #[error("Empty item in '{0}'")]
+    EmptyItem(String),
+
+    /// A `#` color with the wrong number of digits or non-hex characters.
+    #[error("Invalid hex color '{0}'")]
+    InvalidHex(String),
+
+    /// An `rgb()` or `hsl()` call with arguments that aren't a color.
+    #[error("Invalid color function '{0}'")]
+    InvalidColor(String),
+
+    /// A number followed by a unit CSS doesn't have.
+    #[error("Unknown unit '{0}'")]
+    UnknownUnit(String),
+
+    /// A fractional number where an integer is required.
+    #[error("Expected an integer, found {0}")]
+    NotAnInteger(String),
+
+    /// A valid value of the wrong type.
+    #[error("Expected {expected}, found {found}")]
+    Expected {
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+/// A parsed CSS value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssValue {
+    Number(f64),
+    Percentage(f64),
+    /// A number with a unit other than `%`, e.g. `16px` or `150ms`.
+    Dimension {
+        value: f64,
+        unit: String,
+    },
+    /// A `#` token, without the `#`.
+    Hash(String),
+    Ident(String),
+    /// A quoted string, without the quotes.
+    Str(String),
+    /// A function call with its comma-separated arguments. A bare
+    /// parenthesized group, as inside `calc()`, has an empty name.
+    Function {
+        name: String,
+        args: Vec<CssValue>,
+    },
+    /// `+`, `-`, `*` or `/` between values.
+    Operator(char),
+    /// Space-separated values.
+    List(Vec<CssValue>),
+    /// Comma-separated values, e.g. a font stack.
+    CommaList(Vec<CssValue>),
+}
+
+/// A `<length>`; the unit is empty for a unitless zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Length {
+    pub value: f64,
+    pub unit: String,
+}
+
+impl CssValue {
+    /// Parse a complete value.
+    pub fn parse(text: &str) -> Result<Self, CssValueError> {
+        let mut parser = Parser { src: text, pos: 0 };
+        let mut items = parser.comma_list(None)?;
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            CssValue::CommaList(items)
+        })
+    }
+
+    /// A plain number.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            CssValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// A length, or a unitless zero.
+    pub fn as_length(&self) -> Option<Length> {
+        match self {
+            CssValue::Number(n) if *n == 0.0 => Some(Length {
+                value: 0.0,
+                unit: String::new(),
+            }),
+            CssValue::Dimension { value, unit } if is_length_unit(unit) => Some(Length {
+                value: *value,
+                unit: unit.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The color as sRGB, for hex colors, named colors and the `rgb()` and
+    /// `hsl()` functions. Other color syntaxes are colors (see
+    /// [`is_color`](Self::is_color)) but can't be evaluated here.
+    pub fn as_color(&self) -> Option<Rgb> {
+        match self {
+            CssValue::Hash(hex) => Rgb::from_hex(&format!("#{}", hex)),
+            CssValue::Ident(name) => named_color(name),
+            CssValue::Function { name, .. } if is_one_of(name, RGB_FUNCTIONS) => {
+                parse_color(&self.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the value is a color of any syntax, including
+    /// `currentcolor` and functions such as `oklch()`.
+    pub fn is_color(&self) -> bool {
+        match self {
+            CssValue::Hash(_) => true,
+            CssValue::Ident(name) => {
+                named_color(name).is_some() || name.eq_ignore_ascii_case("currentcolor")
+            }
+            CssValue::Function { name, .. } => is_one_of(name, COLOR_FUNCTIONS),
+            _ => false,
+        }
+    }
+
+    /// Whether the value is a `var()` reference.
+    pub fn is_reference(&self) -> bool {
+        matches!(self, CssValue::Function { name, .. } if name.eq_ignore_ascii_case("var"))
+    }
+
+    /// What the value is, for messages: "a length", "a color", ...
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CssValue::Number(_) => "a number",
+            CssValue::Percentage(_) => "a percentage",
+            CssValue::Dimension { unit, .. } => unit_kind(unit).unwrap_or("a dimension"),
+            CssValue::Str(_) => "a string",
+            CssValue::Operator(_) => "an operator",
+            CssValue::List(_) | CssValue::CommaList(_) => "a list",
+            _ if self.is_color() => "a color",
+            CssValue::Ident(_) | CssValue::Hash(_) => "a keyword",
+            CssValue::Function { name, .. } => {
+                if self.is_reference() {
+                    "a reference"
+                } else if name.is_empty() || is_one_of(name, MATH_FUNCTIONS) {
+                    "a calculation"
+                } else if is_one_of(name, IMAGE_FUNCTIONS) {
+                    "an image"
+                } else {
+                    "a function"
+                }
+            }
+        }
+    }
+
+    /// What a value computes to: the kind of a plain value, or for a
+    /// calculation the kind of its non-number operands. `None` when a
+    /// calculation only combines references and numbers.
+    fn computed_kind(&self) -> Option<&'static str> {
+        let CssValue::Function { name, args } = self else {
+            return Some(self.kind());
+        };
+        if !(name.is_empty() || is_one_of(name, MATH_FUNCTIONS)) {
+            return Some(self.kind());
+        }
+        let mut references = false;
+        for arg in args {
+            let operands: &[CssValue] = match arg {
+                CssValue::List(items) => items,
+                arg => std::slice::from_ref(arg),
+            };
+            for operand in operands {
+                if operand.is_reference() {
+                    references = true;
+                    continue;
+                }
+                match operand.computed_kind() {
+                    None => references = true,
+                    Some("a number" | "an operator") => {}
+                    Some(kind) => return Some(kind),
+                }
+            }
+        }
+        if references { None } else { Some("a number") }
+    }
+}
+
+impl fmt::Display for CssValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |items: &[CssValue], sep: &str| {
+            items
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        match self {
+            CssValue::Number(n) => write!(f, "{}", n),
+            CssValue::Percentage(n) => write!(f, "{}%", n),
+            CssValue::Dimension { value, unit } => write!(f, "{}{}", value, unit),
+            CssValue::Hash(hex) => write!(f, "#{}", hex),
+            CssValue::Ident(name) => write!(f, "{}", name),
+            CssValue::Str(s) => write!(f, "\"{}\"", s),
+            CssValue::Function { name, args } => write!(f, "{}({})", name, join(args, ", ")),
+            CssValue::Operator(op) => write!(f, "{}", op),
+            CssValue::List(items) => write!(f, "{}", join(items, " ")),
+            CssValue::CommaList(items) => write!(f, "{}", join(items, ", ")),
+        }
+    }
+}
+
+/// Parse a color value: a reference, a hex or named color, or a color
+/// function.
+pub fn expect_color(text: &str) -> Result<CssValue, CssValueError> {
+    let value = CssValue::parse(text)?;
+    match &value {
+        v if v.is_reference() => {}
+        CssValue::Hash(hex) if Rgb::from_hex(&format!("#{}", hex)).is_none() => {
+            return Err(CssValueError::InvalidHex(value.to_string()));
+        }
+        CssValue::Function { name, .. }
+            if is_one_of(name, RGB_FUNCTIONS) && value.as_color().is_none() =>
+        {
+            return Err(CssValueError::InvalidColor(value.to_string()));
+        }
+        v if v.is_color() => {}
+        _ => return Err(expected("a color", &value)),
+    }
+    Ok(value)
+}
+
+/// Parse a length or percentage value. Unitless numbers other than `0`
+/// are rejected, as are calculations on anything but lengths.
+pub fn expect_length(text: &str) -> Result<CssValue, CssValueError> {
+    let value = CssValue::parse(text)?;
+    if value.is_reference() || value.as_length().is_some() {
+        return Ok(value);
+    }
+    if let CssValue::Dimension { unit, .. } = &value
+        && unit_kind(unit).is_none()
+    {
+        return Err(CssValueError::UnknownUnit(unit.clone()));
+    }
+    match value.computed_kind() {
+        None | Some("a length" | "a percentage") => Ok(value),
+        Some(_) => Err(expected("a length", &value)),
+    }
+}
+
+/// Parse an integer value, such as a z-index.
+pub fn expect_integer(text: &str) -> Result<CssValue, CssValueError> {
+    let value = CssValue::parse(text)?;
+    if value.is_reference() {
+        return Ok(value);
+    }
+    if let Some(n) = value.as_number() {
+        return if n.fract() == 0.0 {
+            Ok(value)
+        } else {
+            Err(CssValueError::NotAnInteger(value.to_string()))
+        };
+    }
+    match value.computed_kind() {
+        None | Some("a number") => Ok(value),
+        Some(_) => Err(expected("an integer", &value)),
+    }
+}
+
+fn expected(expected: &'static str, value: &CssValue) -> CssValueError {
+    CssValueError::Expected {
+        expected,
+        found: value.computed_kind().unwrap_or("a calculation"),
+    }
+}
+
+fn is_one_of(name: &str, names: &[&str]) -> bool {
+    names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+fn is_length_unit(unit: &str) -> bool {
+    is_one_of(unit, LENGTH_UNITS)
+}
+
+fn unit_kind(unit: &str) -> Option<&'static str> {
+    if is_length_unit(unit) {
+        return Some("a length");
+    }
+    OTHER_UNITS
+        .iter()
+        .find(|(u, _)| u.eq_ignore_ascii_case(unit))
+        .map(|(_, kind)| *kind)
+}
+
+fn named_color(name: &str) -> Option<Rgb> {
+    let name = name.to_ascii_lowercase();
+    if name == "transparent" {
+        return Some(Rgb {
+            alpha: 0.0,
+            ..Rgb::new(0.0, 0.0, 0.0)
+        });
+    }
+    let i = NAMED_COLORS
+        .binary_search_by(|(n, _)| n.cmp(&name.as_str()))
+        .ok()?;
+    let rgb = NAMED_COLORS[i].1;
+    let channel = |shift: u32| ((rgb >> shift) & 0xff) as f64 / 255.0;
+    Some(Rgb::new(channel(16), channel(8), channel(0)))
+}
+
+/// CSS named colors, sorted by name.
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.src[self.pos..].chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Comma-separated items up to the end, or up to and including the
+    /// `)` closing `function`.
+    fn comma_list(&mut self, function: Option<&str>) -> Result<Vec<CssValue>, CssValueError> {
+        let mut items = Vec::new();
+        loop {
+            let item = self.space_list()?;
+            let next = self.peek();
+            let last = match next {
+                Some(',') => false,
+                Some(')') if function.is_some() => true,
+                None if function.is_none() => true,
+                None => {
+                    let name = function.unwrap_or_default().to_string();
+                    return Err(CssValueError::UnclosedFunction(name));
+                }
+                Some(ch) => return Err(CssValueError::UnexpectedChar { ch, at: self.pos }),
+            };
+            self.bump();
+            match item {
+                Some(item) => items.push(item),
+                // `f()` has no arguments; an empty value is an error.
+                None if last && items.is_empty() && next.is_some() => {}
+                None if last && items.is_empty() => return Err(CssValueError::Empty),
+                None => {
+                    let name = function
+                        .map_or_else(|| self.src.trim().to_string(), |name| format!("{}()", name));
+                    return Err(CssValueError::EmptyItem(name));
+                }
+            }
+            if last {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Space-separated values up to a comma, `)` or the end.
+    fn space_list(&mut self) -> Result<Option<CssValue>, CssValueError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some(',' | ')') => break,
+                Some(_) => items.push(self.component()?),
+            }
+        }
+        Ok(match items.len() {
+            0 => None,
+            1 => items.pop(),
+            _ => Some(CssValue::List(items)),
+        })
+    }
+
+    fn component(&mut self) -> Result<CssValue, CssValueError> {
+        let at = self.pos;
+        let Some(ch) = self.peek() else {
+            return Err(CssValueError::Empty);
+        };
+        if self.starts_number() {
+            return self.number();
+        }
+        if self.starts_ident() {
+            let name = self.ident();
+            if self.peek() != Some('(') {
+                return Ok(CssValue::Ident(name));
+            }
+            self.bump();
+            let args = self.comma_list(Some(&name))?;
+            return Ok(CssValue::Function { name, args });
+        }
+        match ch {
+            '"' | '\'' => {
+                self.bump();
+                let start = self.pos;
+                while let Some(c) = self.bump() {
+                    if c == ch {
+                        let s = self.src[start..self.pos - 1].to_string();
+                        return Ok(CssValue::Str(s));
+                    }
+                    if c == '\\' {
+                        self.bump();
+                    }
+                }
+                Err(CssValueError::UnterminatedString)
+            }
+            '#' => {
+                self.bump();
+                let name = self.ident();
+                if name.is_empty() {
+                    return Err(CssValueError::UnexpectedChar { ch, at });
+                }
+                Ok(CssValue::Hash(name))
+            }
+            '(' => {
+                self.bump();
+                let args = self.comma_list(Some(""))?;
+                Ok(CssValue::Function {
+                    name: String::new(),
+                    args,
+                })
+            }
+            '+' | '-' | '*' | '/' => {
+                self.bump();
+                Ok(CssValue::Operator(ch))
+            }
+            _ => Err(CssValueError::UnexpectedChar { ch, at }),
+        }
+    }
+
+    fn starts_number(&self) -> bool {
+        let digit = |n| self.peek_at(n).is_some_and(|c: char| c.is_ascii_digit());
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => true,
+            Some('.') => digit(1),
+            Some('+' | '-') => digit(1) || (self.peek_at(1) == Some('.') && digit(2)),
+            _ => false,
+        }
+    }
+
+    fn starts_ident(&self) -> bool {
+        let ident_start = |c: char| c.is_alphabetic() || c == '_' || !c.is_ascii();
+        match self.peek() {
+            Some('-') => self.peek_at(1).is_some_and(|c| ident_start(c) || c == '-'),
+            Some(c) => ident_start(c),
+            None => false,
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii())
+        {
+            self.bump();
+        }
+        self.src[start..self.pos].to_string()
+    }
+
+    fn number(&mut self) -> Result<CssValue, CssValueError> {
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            while p.peek().is_some_and(|c| c.is_ascii_digit()) {
+                p.bump();
+            }
+        };
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.bump();
+        }
+        digits(self);
+        if self.peek() == Some('.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+            digits(self);
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let digit = |n| self.peek_at(n).is_some_and(|c: char| c.is_ascii_digit());
+            let sign = matches!(self.peek_at(1), Some('+' | '-'));
+            if digit(1) || (sign && digit(2)) {
+                self.bump();
+                if sign {
+                    self.bump();
+                }
+                digits(self);
+            }
+        }
+        let value: f64 =
+            self.src[start..self.pos]
+                .parse()
+                .map_err(|_| CssValueError::UnexpectedChar {
+                    ch: self.src[start..].chars().next().unwrap_or(' '),
+                    at: start,
+                })?;
+        if self.peek() == Some('%') {
+            self.bump();
+            return Ok(CssValue::Percentage(value));
+        }
+        if self.starts_ident() {
+            let unit = self.ident();
+            return Ok(CssValue::Dimension { value, unit });
+        }
+        Ok(CssValue::Number(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            CssValue::parse("1.5e1px").unwrap(),
+            CssValue::Dimension {
+                value: 15.0,
+                unit: "px".to_string()
+            }
+        );
+        assert_eq!(
+            CssValue::parse("clamp(1rem, 2vw, 2rem)")
+                .unwrap()
+                .to_string(),
+            "clamp(1rem, 2vw, 2rem)"
+        );
+        assert_eq!(
+            CssValue::parse("10 px").unwrap(),
+            CssValue::List(vec![
+                CssValue::Number(10.0),
+                CssValue::Ident("px".to_string())
+            ])
+        );
+        assert_eq!(
+            CssValue::parse("Inter, \"Helvetica Neue\", sans-serif")
+                .unwrap()
+                .to_string(),
+            "Inter, \"Helvetica Neue\", sans-serif"
+        );
+        let calc = CssValue::parse("calc(var(--spacing-md) * -2 + (1px - 2%))").unwrap();
+        assert_eq!(calc.kind(), "a calculation");
+        assert_eq!(
+            calc.to_string(),
+            "calc(var(--spacing-md) * -2 + (1px - 2%))"
+        );
+        assert_eq!(CssValue::parse("  ").unwrap_err(), CssValueError::Empty);
+        assert_eq!(
+            CssValue::parse("rgb(1, 2").unwrap_err(),
+            CssValueError::UnclosedFunction("rgb".to_string())
+        );
+        assert_eq!(
+            CssValue::parse("min(1px,, 2px)").unwrap_err().to_string(),
+            "Empty item in 'min()'"
+        );
+        assert_eq!(
+            CssValue::parse("1px)").unwrap_err(),
+            CssValueError::UnexpectedChar { ch: ')', at: 3 }
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        let parse = |text: &str| CssValue::parse(text).unwrap();
+        assert_eq!(parse("42").as_number(), Some(42.0));
+        assert_eq!(parse("42px").as_number(), None);
+        assert_eq!(
+            parse("-0.5rem").as_length(),
+            Some(Length {
+                value: -0.5,
+                unit: "rem".to_string()
+            })
+        );
+        assert_eq!(parse("0").as_length().unwrap().unit, "");
+        assert_eq!(parse("150ms").as_length(), None);
+        assert_eq!(parse("150ms").kind(), "a time");
+
+        let hex = |value: &str| parse(value).as_color().map(|c| c.to_hex());
+        assert_eq!(hex("#3b82f6").as_deref(), Some("#3b82f6"));
+        assert_eq!(hex("RebeccaPurple").as_deref(), Some("#663399"));
+        assert_eq!(hex("rgb(59 130 246 / 50%)").as_deref(), Some("#3b82f680"));
+        assert_eq!(hex("hsl(0, 100%, 50%)").as_deref(), Some("#ff0000"));
+        assert_eq!(hex("transparent").as_deref(), Some("#00000000"));
+        assert!(parse("oklch(70% 0.1 200)").is_color());
+        assert_eq!(parse("oklch(70% 0.1 200)").as_color(), None);
+        assert!(!parse("bold").is_color());
+    }
+
+    #[test]
+    fn test_color_corpus() {
+        let valid = [
+            "#fff",
+            "#fff8",
+            "#3b82f6",
+            "#3b82f680",
+            "navy",
+            "currentColor",
+            "transparent",
+            "rgb(59, 130, 246)",
+            "rgba(0,0,0,0.5)",
+            "rgb(59 130 246 / 50%)",
+            "hsl(217 91% 60%)",
+            "oklch(62.8% 0.258 29.23)",
+            "color-mix(in srgb, red 50%, blue)",
+            "var(--color-primary)",
+            "var(--color-primary, #000)",
+        ];
+        for value in valid {
+            assert!(expect_color(value).is_ok(), "{} should be a color", value);
+        }
+        let invalid = [
+            ("", "Value is empty"),
+            ("#xyz", "Invalid hex color '#xyz'"),
+            ("#12", "Invalid hex color '#12'"),
+            ("#1234567", "Invalid hex color '#1234567'"),
+            ("rgb(1, 2)", "Invalid color function 'rgb(1, 2)'"),
+            ("rgb(", "Missing ')' after 'rgb('"),
+            ("blu", "Expected a color, found a keyword"),
+            ("16px", "Expected a color, found a length"),
+            ("#fff #000", "Expected a color, found a list"),
+            (
+                "linear-gradient(red, blue)",
+                "Expected a color, found an image",
+            ),
+        ];
+        for (value, message) in invalid {
+            let error = expect_color(value).expect_err(value);
+            assert_eq!(error.to_string(), message, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_length_and_integer_corpus() {
+        let valid = [
+            "0",
+            "16px",
+            "-0.5rem",
+            "1.5e1px",
+            ".5em",
+            "50%",
+            "100dvh",
+            "calc(100% - 2rem)",
+            "clamp(1rem, 2vw, 2rem)",
+            "min(10px, 5vw)",
+            "calc(var(--spacing-md) * 2)",
+            "var(--spacing-md)",
+        ];
+        for value in valid {
+            assert!(expect_length(value).is_ok(), "{} should be a length", value);
+        }
+        let invalid = [
+            ("10 px", "Expected a length, found a list"),
+            ("px10", "Expected a length, found a keyword"),
+            ("10pxx", "Unknown unit 'pxx'"),
+            ("1.25", "Expected a length, found a number"),
+            ("#fff", "Expected a length, found a color"),
+            ("150ms", "Expected a length, found a time"),
+            ("calc(2 * 3)", "Expected a length, found a number"),
+            ("calc(1s + 2s)", "Expected a length, found a time"),
+            ("16px;", "Unexpected ';' at 4"),
+        ];
+        for (value, message) in invalid {
+            let error = expect_length(value).expect_err(value);
+            assert_eq!(error.to_string(), message, "{}", value);
+        }
+
+        for value in ["10", "-1", "+5", "1e3", "calc(10 * 2)", "var(--z-modal)"] {
+            assert!(
+                expect_integer(value).is_ok(),
+                "{} should be an integer",
+                value
+            );
+        }
+        for (value, message) in [
+            ("1.5", "Expected an integer, found 1.5"),
+            ("auto", "Expected an integer, found a keyword"),
+            ("10px", "Expected an integer, found a length"),
+            ("", "Value is empty"),
+        ] {
+            let error = expect_integer(value).expect_err(value);
+            assert_eq!(error.to_string(), message, "{}", value);
+        }
+    }
+}
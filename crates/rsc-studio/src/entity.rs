@@ -1,13 +1,39 @@
 //! Studio entities (Workflow, Context, Preset).
 //! Ported from Flowize's entity model.
 
+pub mod clipboard;
+pub mod validate;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::timestamp::Timestamps;
+
+pub use clipboard::{PasteResult, SUBGRAPH_CLIPBOARD_FORMAT, SubgraphClipboard};
+pub use validate::{END_STATE_KEY, WorkflowDiagnostic, WorkflowDiagnosticKind};
+
 /// Entity identifier.
 pub type EntityId = String;
 
+/// Convert a display name into a lowercase, dash-separated slug.
+///
+/// `"Sign In / Register"` becomes `"sign-in-register"`.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
 /// Workflow - represents a high-level feature area or user journey.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Workflow {
@@ -30,6 +56,9 @@ pub struct Workflow {
     /// Metadata.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub metadata: IndexMap<String, serde_json::Value>,
+    /// Created/modified times.
+    #[serde(flatten)]
+    pub timestamps: Timestamps,
 }
 
 impl Workflow {
@@ -42,6 +71,7 @@ impl Workflow {
             contexts: IndexMap::new(),
             default_context: None,
             metadata: IndexMap::new(),
+            timestamps: Timestamps::default(),
         }
     }
 
@@ -80,6 +110,16 @@ pub struct Context {
     /// Metadata.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub metadata: IndexMap<String, serde_json::Value>,
+    /// Layer of the edge from the workflow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    /// Visual kind, e.g. `modal` or `page`, styled by the designer's
+    /// [`FlowStyleSheet`](crate::designer::flow_style::FlowStyleSheet).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Created/modified times.
+    #[serde(flatten)]
+    pub timestamps: Timestamps,
 }
 
 impl Context {
@@ -92,6 +132,9 @@ impl Context {
             presets: IndexMap::new(),
             default_preset: None,
             metadata: IndexMap::new(),
+            layer: None,
+            kind: None,
+            timestamps: Timestamps::default(),
         }
     }
 
@@ -133,6 +176,16 @@ pub struct Preset {
     /// Metadata.
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub metadata: IndexMap<String, serde_json::Value>,
+    /// Layer of the edge from the context.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    /// Visual kind, e.g. `modal` or `page`, styled by the designer's
+    /// [`FlowStyleSheet`](crate::designer::flow_style::FlowStyleSheet).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Created/modified times.
+    #[serde(flatten)]
+    pub timestamps: Timestamps,
 }
 
 impl Preset {
@@ -146,6 +199,9 @@ impl Preset {
             tools: IndexMap::new(),
             extends: None,
             metadata: IndexMap::new(),
+            layer: None,
+            kind: None,
+            timestamps: Timestamps::default(),
         }
     }
 
@@ -303,6 +359,13 @@ pub enum Position {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Sign In / Register"), "sign-in-register");
+        assert_eq!(slugify("  Dashboard  "), "dashboard");
+        assert_eq!(slugify("step_2 (final)"), "step-2-final");
+    }
+
     #[test]
     fn test_workflow_creation() {
         let mut workflow = Workflow::new("Development");
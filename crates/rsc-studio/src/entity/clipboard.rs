@@ -0,0 +1,373 @@
+//! Copying contexts between workflows.
+//!
+//! [`Workflow::copy_selection`] captures the selected contexts with their
+//! presets, the transitions drawn between them and where they sit relative
+//! to the selection's top-left corner. The clipboard is plain JSON, so it
+//! survives the system clipboard and pastes into any workflow of any
+//! project. Transitions to contexts outside the selection can't come
+//! along; they are dropped at copy time and reported again on paste.
+
+use indexmap::IndexMap;
+use rsc_flow::NodeId;
+use rsc_flow::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Context, EntityId, Workflow};
+use crate::designer::NavigationDesigner;
+use crate::designer::navigation::NavigationNodeData;
+use crate::export::workflow::TransitionExport;
+use crate::import::ImportError;
+
+/// Tag of clipboard text holding a [`SubgraphClipboard`].
+pub const SUBGRAPH_CLIPBOARD_FORMAT: &str = "rsc-studio/subgraph";
+
+/// Copied contexts and the transitions between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubgraphClipboard {
+    /// Always [`SUBGRAPH_CLIPBOARD_FORMAT`].
+    pub format: String,
+    pub contexts: Vec<Context>,
+    /// Transitions with both ends copied, hierarchy edges included.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<TransitionExport>,
+    /// Offsets from the selection's top-left corner, by entity id.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub positions: IndexMap<EntityId, Position>,
+    /// Transitions left behind because one end wasn't selected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<TransitionExport>,
+}
+
+/// What a paste added to a workflow.
+#[derive(Debug, Clone)]
+pub struct PasteResult {
+    /// Ids of the new contexts, in clipboard order.
+    pub contexts: Vec<EntityId>,
+    pub transitions: Vec<TransitionExport>,
+    /// Canvas positions by new entity id.
+    pub positions: IndexMap<EntityId, Position>,
+    /// Ids on the clipboard to the ids they were given.
+    pub id_map: IndexMap<String, String>,
+    /// Transitions that were left behind at copy time.
+    pub dropped: Vec<TransitionExport>,
+}
+
+impl SubgraphClipboard {
+    pub fn is_empty(&self) -> bool {
+        self.contexts.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Read clipboard text, rejecting text that isn't a copied subgraph.
+    pub fn from_json(json: &str) -> Result<Self, ImportError> {
+        let clip: Self =
+            serde_json::from_str(json).map_err(|e| ImportError::Parse(e.to_string()))?;
+        if clip.format != SUBGRAPH_CLIPBOARD_FORMAT {
+            return Err(ImportError::Parse(format!(
+                "not a copied subgraph: {}",
+                clip.format
+            )));
+        }
+        Ok(clip)
+    }
+}
+
+fn transition(edge: &Edge<EdgeData>) -> TransitionExport {
+    TransitionExport {
+        id: edge.id.clone(),
+        source: edge.source.clone(),
+        target: edge.target.clone(),
+        label: edge.data.as_ref().and_then(|d| d.label.clone()),
+        layer: edge.data.as_ref().and_then(|d| d.layer.clone()),
+    }
+}
+
+impl Workflow {
+    /// Copy the contexts among `node_ids`, with their presets, as drawn on
+    /// `canvas`. Other ids, including lone presets, are ignored.
+    pub fn copy_selection(
+        &self,
+        canvas: &FlowCanvas<NavigationNodeData, EdgeData>,
+        node_ids: &[NodeId],
+    ) -> SubgraphClipboard {
+        let contexts: Vec<Context> = self
+            .contexts
+            .values()
+            .filter(|c| node_ids.contains(&c.id))
+            .cloned()
+            .collect();
+        let ids: Vec<&EntityId> = contexts
+            .iter()
+            .flat_map(|c| std::iter::once(&c.id).chain(c.presets.keys()))
+            .collect();
+
+        let mut transitions = Vec::new();
+        let mut dropped = Vec::new();
+        for edge in canvas.edges.values() {
+            match (ids.contains(&&edge.source), ids.contains(&&edge.target)) {
+                (true, true) => transitions.push(transition(edge)),
+                // The edge from the workflow node is hierarchy, not a
+                // transition.
+                (false, true) if edge.source == self.id => {}
+                (false, false) => {}
+                _ => dropped.push(transition(edge)),
+            }
+        }
+
+        let placed: Vec<(&EntityId, Position)> = ids
+            .iter()
+            .filter_map(|id| Some((*id, canvas.get_node(id)?.position)))
+            .collect();
+        let left = placed
+            .iter()
+            .map(|(_, p)| p.x)
+            .fold(f64::INFINITY, f64::min);
+        let top = placed
+            .iter()
+            .map(|(_, p)| p.y)
+            .fold(f64::INFINITY, f64::min);
+        let positions = placed
+            .into_iter()
+            .map(|(id, p)| (id.clone(), Position::new(p.x - left, p.y - top)))
+            .collect();
+
+        SubgraphClipboard {
+            format: SUBGRAPH_CLIPBOARD_FORMAT.to_string(),
+            contexts,
+            transitions,
+            positions,
+            dropped,
+        }
+    }
+
+    /// Add the copied contexts with new ids, their top-left corner at
+    /// `at`. Place them on the canvas with
+    /// [`NavigationDesigner::apply_paste`].
+    pub fn paste(&mut self, clip: &SubgraphClipboard, at: Position) -> PasteResult {
+        let mut id_map = IndexMap::new();
+        let mut renew = |id: &str| -> String {
+            id_map
+                .entry(id.to_string())
+                .or_insert_with(|| Uuid::new_v4().to_string())
+                .clone()
+        };
+        let mut contexts: Vec<Context> = Vec::new();
+        for copied in &clip.contexts {
+            let mut context = copied.clone();
+            context.id = renew(&copied.id);
+            context.presets = copied
+                .presets
+                .values()
+                .map(|preset| {
+                    let mut preset = preset.clone();
+                    preset.id = renew(&preset.id);
+                    (preset.id.clone(), preset)
+                })
+                .collect();
+            contexts.push(context);
+        }
+
+        // References resolve once every entity has its new id.
+        for context in &mut contexts {
+            if let Some(default) = &mut context.default_preset
+                && let Some(id) = id_map.get(default)
+            {
+                *default = id.clone();
+            }
+            for preset in context.presets.values_mut() {
+                // A base preset that wasn't copied keeps its id.
+                if let Some(extends) = &mut preset.extends
+                    && let Some(id) = id_map.get(extends)
+                {
+                    *extends = id.clone();
+                }
+            }
+        }
+
+        let positions = clip
+            .positions
+            .iter()
+            .filter_map(|(id, offset)| {
+                let position = Position::new(at.x + offset.x, at.y + offset.y);
+                Some((id_map.get(id)?.clone(), position))
+            })
+            .collect();
+        let mut transitions = Vec::new();
+        for copied in &clip.transitions {
+            let (Some(source), Some(target)) =
+                (id_map.get(&copied.source), id_map.get(&copied.target))
+            else {
+                continue;
+            };
+            let transition = TransitionExport {
+                id: Uuid::new_v4().to_string(),
+                source: source.clone(),
+                target: target.clone(),
+                ..copied.clone()
+            };
+            id_map.insert(copied.id.clone(), transition.id.clone());
+            transitions.push(transition);
+        }
+
+        let ids = contexts.iter().map(|c| c.id.clone()).collect();
+        for context in contexts {
+            self.add_context(context);
+        }
+        PasteResult {
+            contexts: ids,
+            transitions,
+            positions,
+            id_map,
+            dropped: clip.dropped.clone(),
+        }
+    }
+}
+
+impl NavigationDesigner {
+    /// Draw pasted contexts of `workflow` where the paste put them. Nodes
+    /// a reload already drew are moved instead.
+    pub fn apply_paste(&mut self, workflow: &Workflow, paste: &PasteResult) {
+        for id in &paste.contexts {
+            if self.canvas.get_node(id).is_none()
+                && let Some(context) = workflow.contexts.get(id)
+            {
+                self.add_context_node(context, &workflow.id);
+            }
+        }
+        self.place_nodes(&paste.positions);
+        self.draw_transitions(&paste.transitions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Preset, WorkflowDiagnosticKind};
+
+    /// Cart -> Shipping -> Payment, plus Help linked from Cart.
+    fn checkout() -> (Workflow, NavigationDesigner) {
+        let mut workflow = Workflow::new("Shop").with_id("w1");
+        for (id, name) in [
+            ("cart", "Cart"),
+            ("shipping", "Shipping"),
+            ("payment", "Payment"),
+            ("help", "Help"),
+        ] {
+            workflow.add_context(Context::new(name).with_id(id));
+        }
+        let mut compact = Preset::new("Compact").with_id("compact");
+        compact.extends = Some("elsewhere".to_string());
+        workflow.contexts["payment"].add_preset(compact);
+        workflow.contexts["payment"].default_preset = Some("compact".to_string());
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        for (id, source, target, label) in [
+            ("t1", "cart", "shipping", "Checkout"),
+            ("t2", "shipping", "payment", "Continue"),
+            ("t3", "cart", "help", "Help"),
+        ] {
+            designer.canvas.add_edge(Edge::new(id, source, target));
+            designer.set_transition_label(id, label);
+        }
+        for (id, x, y) in [
+            ("cart", 100.0, 50.0),
+            ("shipping", 300.0, 50.0),
+            ("payment", 500.0, 80.0),
+            ("compact", 500.0, 200.0),
+        ] {
+            designer.canvas.nodes[id].position = Position::new(x, y);
+        }
+        (workflow, designer)
+    }
+
+    fn selection() -> Vec<NodeId> {
+        ["cart", "shipping", "payment", "w1"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn test_paste_remaps_ids() {
+        let (mut workflow, designer) = checkout();
+        let clip = workflow.copy_selection(&designer.canvas, &selection());
+        assert_eq!(clip.contexts.len(), 3);
+        assert_eq!(clip.dropped.len(), 1);
+        assert_eq!(clip.dropped[0].label.as_deref(), Some("Help"));
+
+        let paste = workflow.paste(&clip, Position::zero());
+        assert_eq!(workflow.contexts.len(), 7);
+        assert_eq!(paste.dropped.len(), 1);
+        let ids = &paste.id_map;
+        let payment = &workflow.contexts[&ids["payment"]];
+        assert_ne!(ids["payment"], "payment");
+        assert_eq!(payment.default_preset.as_ref(), Some(&ids["compact"]));
+        assert_eq!(
+            payment.presets[&ids["compact"]].extends.as_deref(),
+            Some("elsewhere")
+        );
+        let labelled: Vec<_> = paste
+            .transitions
+            .iter()
+            .filter_map(|t| Some((&t.source, &t.target, t.label.as_deref()?)))
+            .collect();
+        assert_eq!(
+            labelled,
+            [
+                (&ids["cart"], &ids["shipping"], "Checkout"),
+                (&ids["shipping"], &ids["payment"], "Continue"),
+            ]
+        );
+        let checkout = paste.transitions.iter().find(|t| t.id == ids["t1"]);
+        assert_eq!(checkout.and_then(|t| t.label.as_deref()), Some("Checkout"));
+    }
+
+    #[test]
+    fn test_paste_keeps_relative_positions() {
+        let (mut workflow, mut designer) = checkout();
+        let clip = workflow.copy_selection(&designer.canvas, &selection());
+        assert_eq!(clip.positions["cart"], Position::zero());
+        assert_eq!(clip.positions["compact"], Position::new(400.0, 150.0));
+
+        let paste = workflow.paste(&clip, Position::new(40.0, 600.0));
+        designer.apply_paste(&workflow, &paste);
+        let at = |id: &str| designer.canvas.nodes[&paste.id_map[id]].position;
+        assert_eq!(at("cart"), Position::new(40.0, 600.0));
+        assert_eq!(at("shipping"), Position::new(240.0, 600.0));
+        assert_eq!(at("payment"), Position::new(440.0, 630.0));
+        assert_eq!(at("compact"), Position::new(440.0, 750.0));
+        // Two transitions plus each context's and the preset's hierarchy
+        // edge, besides the original eight.
+        assert_eq!(designer.canvas.edges.len(), 8 + 6);
+    }
+
+    #[test]
+    fn test_cross_workflow_paste_through_json() {
+        let (workflow, designer) = checkout();
+        let json = workflow
+            .copy_selection(&designer.canvas, &selection())
+            .to_json()
+            .unwrap();
+        let clip = SubgraphClipboard::from_json(&json).unwrap();
+
+        let mut other = Workflow::new("Store").with_id("w2");
+        let paste = other.paste(&clip, Position::zero());
+        let names: Vec<&str> = other.contexts.values().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Cart", "Shipping", "Payment"]);
+        assert_eq!(other.default_context.as_ref(), Some(&paste.contexts[0]));
+
+        let mut target = NavigationDesigner::new();
+        target.load_workflows(&[&other]);
+        target.apply_paste(&other, &paste);
+        // Payment was a dead end in the source workflow too.
+        let diagnostics = other.validate(&target.canvas);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, WorkflowDiagnosticKind::DeadEnd);
+
+        assert!(SubgraphClipboard::from_json(r#"{"format": "x", "contexts": []}"#).is_err());
+    }
+}
@@ -0,0 +1,338 @@
+//! Navigation graph checks for a workflow.
+//!
+//! A workflow's transitions are the edges drawn from its contexts on the
+//! navigation canvas; edges from a context to its own presets are
+//! hierarchy and are ignored. [`Workflow::validate`] walks that graph from
+//! the start context and reports what a user would trip over: screens they
+//! can't reach, screens they can't leave, ambiguous transition names and
+//! transitions to contexts that were deleted.
+
+use std::collections::{HashSet, VecDeque};
+
+use indexmap::IndexMap;
+use rsc_flow::prelude::*;
+
+use super::{EntityId, Workflow};
+use crate::designer::css::ValidationSeverity;
+use crate::designer::navigation::{EntityType, NavigationNodeData};
+
+/// Context metadata flag marking it an end state, which may have no
+/// outgoing transitions.
+pub const END_STATE_KEY: &str = "end_state";
+
+/// What a [`WorkflowDiagnostic`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkflowDiagnosticKind {
+    /// No start context, or it no longer exists.
+    MissingStart,
+    /// No path leads to the context from the start.
+    Unreachable,
+    /// The context has no way out and isn't marked an end state.
+    DeadEnd,
+    /// Several transitions leaving one context share a name.
+    DuplicateTransition,
+    /// The transition's target no longer exists.
+    DanglingTransition,
+}
+
+/// A problem in a workflow's navigation graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowDiagnostic {
+    pub kind: WorkflowDiagnosticKind,
+    pub severity: ValidationSeverity,
+    pub workflow_id: EntityId,
+    /// Offending contexts, in workflow order.
+    pub contexts: Vec<EntityId>,
+    /// Offending transitions (edge ids), in canvas order.
+    pub transitions: Vec<String>,
+    pub message: String,
+}
+
+/// One transition out of a context.
+struct Transition<'a> {
+    id: &'a str,
+    source: &'a str,
+    target: &'a str,
+    name: Option<String>,
+}
+
+impl Workflow {
+    /// Check the navigation graph drawn on `canvas`. An empty list means
+    /// the workflow is sound.
+    pub fn validate(
+        &self,
+        canvas: &FlowCanvas<NavigationNodeData, EdgeData>,
+    ) -> Vec<WorkflowDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let diagnostic =
+            |kind, severity, contexts: Vec<&str>, transitions, message| WorkflowDiagnostic {
+                kind,
+                severity,
+                workflow_id: self.id.clone(),
+                contexts: contexts.into_iter().map(str::to_string).collect(),
+                transitions,
+                message,
+            };
+        let name = |id: &str| self.contexts[id].name.as_str();
+
+        let transitions: Vec<Transition> = canvas
+            .edges
+            .values()
+            .filter(|edge| {
+                self.contexts
+                    .get(edge.source.as_str())
+                    .is_some_and(|c| !c.presets.contains_key(edge.target.as_str()))
+            })
+            .filter(|edge| {
+                canvas
+                    .get_node(&edge.target)
+                    .and_then(|n| n.data.as_ref())
+                    .is_none_or(|d| d.entity_type == EntityType::Context)
+            })
+            .map(|edge| Transition {
+                id: &edge.id,
+                source: &edge.source,
+                target: &edge.target,
+                name: edge
+                    .label
+                    .as_ref()
+                    .map(|l| l.text.trim().to_string())
+                    .or_else(|| edge.data.as_ref().and_then(|d| d.label.clone()))
+                    .filter(|n| !n.is_empty()),
+            })
+            .collect();
+        let exists = |id: &str| self.contexts.contains_key(id) || canvas.get_node(id).is_some();
+
+        let start = self
+            .default_context
+            .as_deref()
+            .filter(|id| self.contexts.contains_key(*id));
+        match start {
+            Some(start) => {
+                let mut reached = HashSet::from([start]);
+                let mut queue = VecDeque::from([start]);
+                while let Some(id) = queue.pop_front() {
+                    for t in transitions.iter().filter(|t| t.source == id) {
+                        if self.contexts.contains_key(t.target) && reached.insert(t.target) {
+                            queue.push_back(t.target);
+                        }
+                    }
+                }
+                for id in self.contexts.keys().map(String::as_str) {
+                    if !reached.contains(id) {
+                        diagnostics.push(diagnostic(
+                            WorkflowDiagnosticKind::Unreachable,
+                            ValidationSeverity::Warning,
+                            vec![id],
+                            Vec::new(),
+                            format!("\"{}\" can't be reached from \"{}\"", name(id), name(start)),
+                        ));
+                    }
+                }
+            }
+            None => diagnostics.push(diagnostic(
+                WorkflowDiagnosticKind::MissingStart,
+                ValidationSeverity::Error,
+                Vec::new(),
+                Vec::new(),
+                format!("Workflow \"{}\" has no start context", self.name),
+            )),
+        }
+
+        for (id, context) in &self.contexts {
+            let is_end = context
+                .metadata
+                .get(END_STATE_KEY)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            if !is_end && !transitions.iter().any(|t| t.source == id.as_str()) {
+                diagnostics.push(diagnostic(
+                    WorkflowDiagnosticKind::DeadEnd,
+                    ValidationSeverity::Warning,
+                    vec![id],
+                    Vec::new(),
+                    format!(
+                        "\"{}\" has no way out and isn't marked as an end state",
+                        context.name
+                    ),
+                ));
+            }
+        }
+
+        let mut named: IndexMap<(&str, &str), Vec<String>> = IndexMap::new();
+        for t in &transitions {
+            if let Some(name) = &t.name {
+                named
+                    .entry((t.source, name.as_str()))
+                    .or_default()
+                    .push(t.id.to_string());
+            }
+        }
+        for ((source, transition), ids) in named {
+            if ids.len() > 1 {
+                let message = format!(
+                    "{} transitions named \"{}\" leave \"{}\"",
+                    ids.len(),
+                    transition,
+                    name(source)
+                );
+                diagnostics.push(diagnostic(
+                    WorkflowDiagnosticKind::DuplicateTransition,
+                    ValidationSeverity::Error,
+                    vec![source],
+                    ids,
+                    message,
+                ));
+            }
+        }
+
+        for t in transitions.iter().filter(|t| !exists(t.target)) {
+            diagnostics.push(diagnostic(
+                WorkflowDiagnosticKind::DanglingTransition,
+                ValidationSeverity::Error,
+                vec![t.source],
+                vec![t.id.to_string()],
+                format!(
+                    "A transition from \"{}\" leads to {}, which no longer exists",
+                    name(t.source),
+                    t.target
+                ),
+            ));
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::NavigationDesigner;
+    use crate::entity::{Context, Preset};
+
+    /// Home -> List -> Detail -> Done, with Done an end state.
+    fn shop() -> (Workflow, FlowCanvas<NavigationNodeData, EdgeData>) {
+        let mut workflow = Workflow::new("Shop").with_id("shop");
+        for (id, name) in [
+            ("home", "Home"),
+            ("list", "List"),
+            ("detail", "Detail"),
+            ("done", "Done"),
+        ] {
+            workflow.add_context(Context::new(name).with_id(id));
+        }
+        workflow.contexts["home"].add_preset(Preset::new("Compact").with_id("compact"));
+        workflow.contexts["done"]
+            .metadata
+            .insert(END_STATE_KEY.to_string(), true.into());
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        let mut canvas = designer.canvas;
+        for (id, source, target, label) in [
+            ("e1", "home", "list", "Browse"),
+            ("e2", "list", "detail", "Open"),
+            ("e3", "detail", "done", "Buy"),
+            ("e4", "detail", "list", "Back"),
+        ] {
+            canvas.add_edge(Edge::new(id, source, target).with_label(label));
+        }
+        (workflow, canvas)
+    }
+
+    fn kinds(diagnostics: &[WorkflowDiagnostic]) -> Vec<WorkflowDiagnosticKind> {
+        diagnostics.iter().map(|d| d.kind).collect()
+    }
+
+    #[test]
+    fn test_clean_graph() {
+        let (workflow, canvas) = shop();
+        assert_eq!(workflow.validate(&canvas), []);
+    }
+
+    #[test]
+    fn test_reachability_and_start() {
+        let (mut workflow, mut canvas) = shop();
+        // Detail is only reachable through List.
+        canvas.remove_edge("e2");
+        let diagnostics = workflow.validate(&canvas);
+        assert_eq!(
+            kinds(&diagnostics),
+            [
+                WorkflowDiagnosticKind::Unreachable,
+                WorkflowDiagnosticKind::Unreachable,
+                WorkflowDiagnosticKind::DeadEnd,
+            ]
+        );
+        assert_eq!(diagnostics[0].contexts, ["detail"]);
+        assert_eq!(diagnostics[1].contexts, ["done"]);
+        assert_eq!(diagnostics[2].contexts, ["list"]);
+        assert_eq!(diagnostics[2].severity, ValidationSeverity::Warning);
+
+        // Without a start, nothing is unreachable; the start is the problem.
+        workflow.default_context = Some("deleted".to_string());
+        let diagnostics = workflow.validate(&canvas);
+        assert_eq!(diagnostics[0].kind, WorkflowDiagnosticKind::MissingStart);
+        assert_eq!(diagnostics[0].severity, ValidationSeverity::Error);
+        assert_eq!(
+            diagnostics[0].message,
+            "Workflow \"Shop\" has no start context"
+        );
+        assert!(!kinds(&diagnostics).contains(&WorkflowDiagnosticKind::Unreachable));
+    }
+
+    #[test]
+    fn test_transition_problems() {
+        let (workflow, mut canvas) = shop();
+        canvas.add_edge(Edge::new("e5", "detail", "list").with_label("Back"));
+        canvas.add_edge(Edge::new("e6", "list", "gone").with_label("Compare"));
+        let diagnostics = workflow.validate(&canvas);
+        assert_eq!(
+            kinds(&diagnostics),
+            [
+                WorkflowDiagnosticKind::DuplicateTransition,
+                WorkflowDiagnosticKind::DanglingTransition,
+            ]
+        );
+        assert_eq!(diagnostics[0].contexts, ["detail"]);
+        assert_eq!(diagnostics[0].transitions, ["e4", "e5"]);
+        assert_eq!(
+            diagnostics[0].message,
+            "2 transitions named \"Back\" leave \"Detail\""
+        );
+        assert_eq!(diagnostics[1].contexts, ["list"]);
+        assert_eq!(diagnostics[1].transitions, ["e6"]);
+    }
+
+    #[test]
+    fn test_store_badges_and_findings() {
+        let (workflow, _) = shop();
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        designer
+            .canvas
+            .add_edge(Edge::new("e1", "home", "gone").with_label("Browse"));
+        let mut store = crate::store::StudioStore::new();
+        store.add_workflow(workflow);
+        store.validate_workflows(&designer);
+
+        // Home leads nowhere real, so the rest is unreachable and List and
+        // Detail are dead ends.
+        let badges = store.workflow_badges();
+        assert_eq!(badges["home"], ValidationSeverity::Error);
+        assert_eq!(badges["list"], ValidationSeverity::Warning);
+        assert!(!badges.contains_key("shop"));
+        let findings = store.findings();
+        assert_eq!(findings.len(), 6);
+        assert_eq!(
+            findings[5].code,
+            crate::error::ErrorCode::DanglingTransition
+        );
+        assert_eq!(
+            findings[5].entity,
+            Some(crate::error::EntityRef::Edge("e1".to_string()))
+        );
+
+        store.workflows["shop"].default_context = None;
+        store.validate_workflows(&designer);
+        assert_eq!(store.workflow_badges()["shop"], ValidationSeverity::Error);
+    }
+}
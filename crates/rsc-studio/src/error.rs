@@ -0,0 +1,654 @@
+//! Crate-wide error type.
+//!
+//! Module errors stay close to their code; [`StudioError`] is what leaves
+//! the store. Each carries a stable [`ErrorCode`], a severity, the entity
+//! it concerns and a hint for recovering, so the problems panel and toasts
+//! can render every failure the same way.
+
+use rsc_dnd::DndError;
+use rsc_flow::FlowError;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::asset::AssetError;
+use crate::config::ConfigError;
+use crate::designer::css::{
+    ComponentStyleError, ExperimentError, ThemeError, TokenImportError, TokenRenameError,
+    TokenValidationError, ValidationSeverity,
+};
+use crate::export::ExportError;
+use crate::export::pack::PackError;
+use crate::export::router::CodegenError;
+use crate::export::style_guide::StyleGuideError;
+use crate::entity::{WorkflowDiagnostic, WorkflowDiagnosticKind};
+use crate::import::ImportError;
+use crate::panel::PanelError;
+use crate::project::ProjectError;
+use crate::settings::SettingsError;
+use crate::store::events::EventError;
+use crate::store::persistence::PersistenceError;
+use crate::template::component::ScaffoldError;
+
+/// Stable error codes. The string form is part of the public contract;
+/// new variants may be added but existing codes never change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    TokenParse,
+    TokenInvalid,
+    TokenNotFound,
+    TokenExists,
+    TokenNameInvalid,
+    ComponentNotFound,
+    NodeNotFound,
+    EdgeNotFound,
+    InvalidConnection,
+    CycleDetected,
+    LayoutFailed,
+    DragTargetNotFound,
+    InvalidDropTarget,
+    DragNotAllowed,
+    ProjectParse,
+    ProjectIo,
+    PackParse,
+    PackIncompatible,
+    PackInvalid,
+    NoThemes,
+    UnknownTheme,
+    ThemeExists,
+    ThemeNameInvalid,
+    LastTheme,
+    UnknownSetting,
+    InvalidSetting,
+    ImportMissingColumn,
+    ImportEmpty,
+    LimitExceeded,
+    UnsupportedFile,
+    StyleConflict,
+    StyleInvalid,
+    DeprecatedToken,
+    Serialization,
+    UnknownPanel,
+    PanelExists,
+    PanelDisabled,
+    UnknownPanelAction,
+    InvalidPanelAction,
+    ScaffoldNameInvalid,
+    ScaffoldExists,
+    ImportParse,
+    ImportUnsupportedVersion,
+    CodegenInvalid,
+    CodegenEmpty,
+    WorkflowNoStart,
+    UnreachableContext,
+    DeadEndContext,
+    DuplicateTransition,
+    DanglingTransition,
+    PersistenceFailed,
+    ConfigInvalid,
+    EventDepthExceeded,
+}
+
+impl ErrorCode {
+    /// Stable string form, e.g. `token_not_found`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::TokenParse => "token_parse",
+            ErrorCode::TokenInvalid => "token_invalid",
+            ErrorCode::TokenNotFound => "token_not_found",
+            ErrorCode::TokenExists => "token_exists",
+            ErrorCode::TokenNameInvalid => "token_name_invalid",
+            ErrorCode::ComponentNotFound => "component_not_found",
+            ErrorCode::NodeNotFound => "node_not_found",
+            ErrorCode::EdgeNotFound => "edge_not_found",
+            ErrorCode::InvalidConnection => "invalid_connection",
+            ErrorCode::CycleDetected => "cycle_detected",
+            ErrorCode::LayoutFailed => "layout_failed",
+            ErrorCode::DragTargetNotFound => "drag_target_not_found",
+            ErrorCode::InvalidDropTarget => "invalid_drop_target",
+            ErrorCode::DragNotAllowed => "drag_not_allowed",
+            ErrorCode::ProjectParse => "project_parse",
+            ErrorCode::ProjectIo => "project_io",
+            ErrorCode::PackParse => "pack_parse",
+            ErrorCode::PackIncompatible => "pack_incompatible",
+            ErrorCode::PackInvalid => "pack_invalid",
+            ErrorCode::NoThemes => "no_themes",
+            ErrorCode::UnknownTheme => "unknown_theme",
+            ErrorCode::ThemeExists => "theme_exists",
+            ErrorCode::ThemeNameInvalid => "theme_name_invalid",
+            ErrorCode::LastTheme => "last_theme",
+            ErrorCode::UnknownSetting => "unknown_setting",
+            ErrorCode::InvalidSetting => "invalid_setting",
+            ErrorCode::ImportMissingColumn => "import_missing_column",
+            ErrorCode::ImportEmpty => "import_empty",
+            ErrorCode::LimitExceeded => "limit_exceeded",
+            ErrorCode::UnsupportedFile => "unsupported_file",
+            ErrorCode::StyleConflict => "style_conflict",
+            ErrorCode::StyleInvalid => "style_invalid",
+            ErrorCode::DeprecatedToken => "deprecated_token",
+            ErrorCode::Serialization => "serialization",
+            ErrorCode::UnknownPanel => "unknown_panel",
+            ErrorCode::PanelExists => "panel_exists",
+            ErrorCode::PanelDisabled => "panel_disabled",
+            ErrorCode::UnknownPanelAction => "unknown_panel_action",
+            ErrorCode::InvalidPanelAction => "invalid_panel_action",
+            ErrorCode::ScaffoldNameInvalid => "scaffold_name_invalid",
+            ErrorCode::ScaffoldExists => "scaffold_exists",
+            ErrorCode::ImportParse => "import_parse",
+            ErrorCode::ImportUnsupportedVersion => "import_unsupported_version",
+            ErrorCode::CodegenInvalid => "codegen_invalid",
+            ErrorCode::CodegenEmpty => "codegen_empty",
+            ErrorCode::WorkflowNoStart => "workflow_no_start",
+            ErrorCode::UnreachableContext => "unreachable_context",
+            ErrorCode::DeadEndContext => "dead_end_context",
+            ErrorCode::DuplicateTransition => "duplicate_transition",
+            ErrorCode::DanglingTransition => "dangling_transition",
+            ErrorCode::PersistenceFailed => "persistence_failed",
+            ErrorCode::ConfigInvalid => "config_invalid",
+            ErrorCode::EventDepthExceeded => "event_depth_exceeded",
+        }
+    }
+}
+
+/// The entity an error concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum EntityRef {
+    /// Token path, e.g. `colors.primary`.
+    Token(String),
+    Node(String),
+    Edge(String),
+    /// Component style name.
+    Component(String),
+    Setting(String),
+    Theme(String),
+}
+
+/// An error surfaced to the user.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{message}")]
+pub struct StudioError {
+    pub code: ErrorCode,
+    pub severity: ValidationSeverity,
+    pub message: String,
+    pub entity: Option<EntityRef>,
+    pub recovery_hint: Option<String>,
+}
+
+impl StudioError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: ValidationSeverity::Error,
+            message: message.into(),
+            entity: None,
+            recovery_hint: None,
+        }
+    }
+
+    pub fn with_severity(mut self, severity: ValidationSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_entity(mut self, entity: EntityRef) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.recovery_hint = Some(hint.into());
+        self
+    }
+}
+
+/// Build a [`StudioError`] from a module error's message.
+fn from_display(code: ErrorCode, error: &impl std::fmt::Display) -> StudioError {
+    StudioError::new(code, error.to_string())
+}
+
+impl From<FlowError> for StudioError {
+    fn from(error: FlowError) -> Self {
+        match &error {
+            FlowError::NodeNotFound(id) => from_display(ErrorCode::NodeNotFound, &error)
+                .with_entity(EntityRef::Node(id.clone()))
+                .with_hint("The node may have been deleted; refresh the canvas"),
+            FlowError::EdgeNotFound(id) => from_display(ErrorCode::EdgeNotFound, &error)
+                .with_entity(EntityRef::Edge(id.clone())),
+            FlowError::InvalidConnection { .. } => {
+                from_display(ErrorCode::InvalidConnection, &error)
+                    .with_hint("Connect an output handle to a compatible input")
+            }
+            FlowError::CycleDetected(id) => from_display(ErrorCode::CycleDetected, &error)
+                .with_entity(EntityRef::Node(id.clone()))
+                .with_hint("Remove one of the edges that closes the loop"),
+            FlowError::LayoutError(_) => from_display(ErrorCode::LayoutFailed, &error),
+            FlowError::SerializationError(_) => from_display(ErrorCode::Serialization, &error),
+        }
+    }
+}
+
+impl From<DndError> for StudioError {
+    fn from(error: DndError) -> Self {
+        match &error {
+            DndError::ElementNotFound(id) => from_display(ErrorCode::DragTargetNotFound, &error)
+                .with_entity(EntityRef::Node(id.clone())),
+            DndError::InvalidDropTarget(id) => from_display(ErrorCode::InvalidDropTarget, &error)
+                .with_entity(EntityRef::Node(id.clone()))
+                .with_severity(ValidationSeverity::Warning)
+                .with_hint("Drop onto a highlighted target"),
+            DndError::NotAllowed(_) => from_display(ErrorCode::DragNotAllowed, &error)
+                .with_severity(ValidationSeverity::Warning),
+        }
+    }
+}
+
+impl From<WorkflowDiagnostic> for StudioError {
+    fn from(diagnostic: WorkflowDiagnostic) -> Self {
+        let (code, hint) = match diagnostic.kind {
+            WorkflowDiagnosticKind::MissingStart => {
+                (ErrorCode::WorkflowNoStart, "Set a default context")
+            }
+            WorkflowDiagnosticKind::Unreachable => (
+                ErrorCode::UnreachableContext,
+                "Add a transition to it or remove it",
+            ),
+            WorkflowDiagnosticKind::DeadEnd => (
+                ErrorCode::DeadEndContext,
+                "Add a transition out or mark it as an end state",
+            ),
+            WorkflowDiagnosticKind::DuplicateTransition => (
+                ErrorCode::DuplicateTransition,
+                "Give each transition a distinct name",
+            ),
+            WorkflowDiagnosticKind::DanglingTransition => (
+                ErrorCode::DanglingTransition,
+                "Reconnect or delete the transition",
+            ),
+        };
+        let entity = match (diagnostic.transitions.first(), diagnostic.contexts.first()) {
+            (Some(edge), _) => EntityRef::Edge(edge.clone()),
+            (None, Some(context)) => EntityRef::Node(context.clone()),
+            (None, None) => EntityRef::Node(diagnostic.workflow_id.clone()),
+        };
+        StudioError::new(code, diagnostic.message)
+            .with_severity(diagnostic.severity)
+            .with_entity(entity)
+            .with_hint(hint)
+    }
+}
+
+impl From<TokenValidationError> for StudioError {
+    fn from(error: TokenValidationError) -> Self {
+        StudioError::new(
+            ErrorCode::TokenInvalid,
+            format!("{}: {}", error.path, error.message),
+        )
+        .with_severity(error.severity)
+        .with_entity(EntityRef::Token(error.path))
+    }
+}
+
+impl From<TokenImportError> for StudioError {
+    fn from(error: TokenImportError) -> Self {
+        match error {
+            TokenImportError::ParseError(message) => StudioError::new(
+                ErrorCode::TokenParse,
+                format!("Could not parse tokens: {}", message),
+            )
+            .with_hint("Check the file is valid JSON or YAML"),
+            TokenImportError::ValidationError(errors) => {
+                let entity = errors.first().map(|e| EntityRef::Token(e.path.clone()));
+                let message = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.path, e.message))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                StudioError {
+                    entity,
+                    ..StudioError::new(ErrorCode::TokenInvalid, message)
+                }
+            }
+        }
+    }
+}
+
+impl From<TokenRenameError> for StudioError {
+    fn from(error: TokenRenameError) -> Self {
+        match &error {
+            TokenRenameError::NotFound(path) => from_display(ErrorCode::TokenNotFound, &error)
+                .with_entity(EntityRef::Token(path.clone())),
+            TokenRenameError::AlreadyExists(path) => from_display(ErrorCode::TokenExists, &error)
+                .with_entity(EntityRef::Token(path.clone()))
+                .with_hint("Choose a different name"),
+            TokenRenameError::InvalidName(_) => from_display(ErrorCode::TokenNameInvalid, &error)
+                .with_hint("Use letters, digits, '-' and '_' only"),
+        }
+    }
+}
+
+impl From<ExperimentError> for StudioError {
+    fn from(error: ExperimentError) -> Self {
+        match &error {
+            ExperimentError::NotFound(path) => from_display(ErrorCode::TokenNotFound, &error)
+                .with_entity(EntityRef::Token(path.clone()))
+                .with_hint("Only existing tokens can be tried out"),
+        }
+    }
+}
+
+impl From<ComponentStyleError> for StudioError {
+    fn from(error: ComponentStyleError) -> Self {
+        match &error {
+            ComponentStyleError::NotFound(name) => {
+                from_display(ErrorCode::ComponentNotFound, &error)
+                    .with_entity(EntityRef::Component(name.clone()))
+            }
+            ComponentStyleError::UnknownParent { component, .. } => {
+                from_display(ErrorCode::ComponentNotFound, &error)
+                    .with_entity(EntityRef::Component(component.clone()))
+                    .with_hint("Extend an existing style or clear the base style")
+            }
+            ComponentStyleError::Cycle(names) => from_display(ErrorCode::CycleDetected, &error)
+                .with_entity(EntityRef::Component(names[0].clone()))
+                .with_hint("Remove the base style from one of the styles in the cycle"),
+        }
+    }
+}
+
+impl From<AssetError> for StudioError {
+    fn from(error: AssetError) -> Self {
+        match error {
+            AssetError::NotAnImage { .. } | AssetError::Unreadable(_) => {
+                from_display(ErrorCode::UnsupportedFile, &error)
+                    .with_hint("Drop a PNG, GIF or JPEG image")
+            }
+            AssetError::TooLarge { .. } => from_display(ErrorCode::LimitExceeded, &error)
+                .with_hint("Resize or compress the image"),
+        }
+    }
+}
+
+impl From<ProjectError> for StudioError {
+    fn from(error: ProjectError) -> Self {
+        match error {
+            ProjectError::Parse(_) => from_display(ErrorCode::ProjectParse, &error)
+                .with_hint("The file may be from a newer studio or corrupted"),
+            ProjectError::Compression(_) | ProjectError::Read(_) => {
+                from_display(ErrorCode::ProjectIo, &error)
+            }
+            ProjectError::UnsupportedVersion { .. } => {
+                from_display(ErrorCode::ProjectParse, &error)
+                    .with_hint("Update the studio to open this project")
+            }
+        }
+    }
+}
+
+impl From<PackError> for StudioError {
+    fn from(error: PackError) -> Self {
+        let code = match error {
+            PackError::Parse(_) => ErrorCode::PackParse,
+            PackError::IncompatibleSchema { .. } => ErrorCode::PackIncompatible,
+            PackError::Validation(_) => ErrorCode::PackInvalid,
+        };
+        let studio = from_display(code, &error);
+        match code {
+            ErrorCode::PackIncompatible => {
+                studio.with_hint("Install a pack version built for this studio")
+            }
+            _ => studio,
+        }
+    }
+}
+
+impl From<ExportError> for StudioError {
+    fn from(error: ExportError) -> Self {
+        match error {
+            ExportError::Serialize(_) => from_display(ErrorCode::Serialization, &error),
+        }
+    }
+}
+
+impl From<CodegenError> for StudioError {
+    fn from(error: CodegenError) -> Self {
+        match &error {
+            CodegenError::InvalidModuleName(_) => from_display(ErrorCode::CodegenInvalid, &error)
+                .with_hint("Use a lowercase snake_case name"),
+            CodegenError::NoContexts(_) => from_display(ErrorCode::CodegenEmpty, &error)
+                .with_hint("Add a context to the workflow"),
+            CodegenError::InvalidParams(id)
+            | CodegenError::InvalidParamType { context: id, .. } => {
+                from_display(ErrorCode::CodegenInvalid, &error)
+                    .with_entity(EntityRef::Node(id.clone()))
+                    .with_hint("Declare params as a list of names or a map of names to types")
+            }
+        }
+    }
+}
+
+impl From<StyleGuideError> for StudioError {
+    fn from(error: StyleGuideError) -> Self {
+        match &error {
+            StyleGuideError::NoThemes => {
+                from_display(ErrorCode::NoThemes, &error).with_hint("Include at least one theme")
+            }
+            StyleGuideError::UnknownDefault(name) | StyleGuideError::UnknownSectionTheme(name) => {
+                from_display(ErrorCode::UnknownTheme, &error)
+                    .with_entity(EntityRef::Theme(name.clone()))
+            }
+        }
+    }
+}
+
+impl From<ThemeError> for StudioError {
+    fn from(error: ThemeError) -> Self {
+        match &error {
+            ThemeError::NotFound(name) => from_display(ErrorCode::UnknownTheme, &error)
+                .with_entity(EntityRef::Theme(name.clone())),
+            ThemeError::AlreadyExists(name) => from_display(ErrorCode::ThemeExists, &error)
+                .with_entity(EntityRef::Theme(name.clone()))
+                .with_hint("Choose a different name"),
+            ThemeError::InvalidName(_) => from_display(ErrorCode::ThemeNameInvalid, &error)
+                .with_hint("Use letters, digits, '-' and '_' only"),
+            ThemeError::LastTheme(name) => from_display(ErrorCode::LastTheme, &error)
+                .with_entity(EntityRef::Theme(name.clone()))
+                .with_hint("Create another theme first"),
+        }
+    }
+}
+
+impl From<SettingsError> for StudioError {
+    fn from(error: SettingsError) -> Self {
+        match &error {
+            SettingsError::UnknownSetting(id) => from_display(ErrorCode::UnknownSetting, &error)
+                .with_entity(EntityRef::Setting(id.clone())),
+            SettingsError::TypeMismatch { id, .. }
+            | SettingsError::OutOfRange { id, .. }
+            | SettingsError::InvalidOption { id, .. } => {
+                from_display(ErrorCode::InvalidSetting, &error)
+                    .with_entity(EntityRef::Setting(id.clone()))
+                    .with_hint("Reset the setting to its default")
+            }
+        }
+    }
+}
+
+impl From<ImportError> for StudioError {
+    fn from(error: ImportError) -> Self {
+        match error {
+            ImportError::MissingColumn(_) => from_display(ErrorCode::ImportMissingColumn, &error)
+                .with_hint("Add the column to the header row"),
+            ImportError::Empty => from_display(ErrorCode::ImportEmpty, &error),
+            ImportError::Parse(_) => from_display(ErrorCode::ImportParse, &error),
+            ImportError::UnsupportedVersion { .. } => {
+                from_display(ErrorCode::ImportUnsupportedVersion, &error)
+                    .with_hint("Update the studio to import this file")
+            }
+        }
+    }
+}
+
+impl From<PersistenceError> for StudioError {
+    fn from(error: PersistenceError) -> Self {
+        match &error {
+            PersistenceError::Storage(_) => from_display(ErrorCode::PersistenceFailed, &error)
+                .with_hint("Save the project to keep your changes"),
+            PersistenceError::Serialize(_) => from_display(ErrorCode::Serialization, &error),
+            PersistenceError::Unavailable => from_display(ErrorCode::PersistenceFailed, &error)
+                .with_hint("Choose a storage backend for auto-save"),
+        }
+    }
+}
+
+impl From<ConfigError> for StudioError {
+    fn from(error: ConfigError) -> Self {
+        from_display(ErrorCode::ConfigInvalid, &error)
+            .with_hint("Fix the value in the layer named at the start of the message")
+    }
+}
+
+impl From<EventError> for StudioError {
+    fn from(error: EventError) -> Self {
+        from_display(ErrorCode::EventDepthExceeded, &error)
+            .with_hint("Check for event subscribers that react to each other's changes")
+    }
+}
+
+impl From<PanelError> for StudioError {
+    fn from(error: PanelError) -> Self {
+        match &error {
+            PanelError::NotFound(_) => from_display(ErrorCode::UnknownPanel, &error),
+            PanelError::Duplicate(_) => from_display(ErrorCode::PanelExists, &error),
+            PanelError::Disabled(_) => from_display(ErrorCode::PanelDisabled, &error)
+                .with_severity(ValidationSeverity::Warning)
+                .with_hint("Switch the panel on under features.panels"),
+            PanelError::UnknownAction { .. } => {
+                from_display(ErrorCode::UnknownPanelAction, &error)
+            }
+            PanelError::InvalidPayload { .. } => {
+                from_display(ErrorCode::InvalidPanelAction, &error)
+            }
+        }
+    }
+}
+
+impl From<ScaffoldError> for StudioError {
+    fn from(error: ScaffoldError) -> Self {
+        match &error {
+            ScaffoldError::InvalidName(_) => from_display(ErrorCode::ScaffoldNameInvalid, &error)
+                .with_hint("Start with a capital letter and use letters and digits only"),
+            ScaffoldError::FileExists(_) => from_display(ErrorCode::ScaffoldExists, &error)
+                .with_hint("Choose a different name or overwrite"),
+            ScaffoldError::StyleExists(name) => from_display(ErrorCode::ScaffoldExists, &error)
+                .with_entity(EntityRef::Component(name.clone()))
+                .with_hint("Choose a different name or overwrite"),
+            ScaffoldError::MissingTemplate(_) | ScaffoldError::Template(_) => {
+                from_display(ErrorCode::ConfigInvalid, &error)
+                    .with_hint("Check the component templates in the templates directory")
+            }
+            ScaffoldError::Write(_) => from_display(ErrorCode::ProjectIo, &error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_errors_map_to_codes() {
+        let cases: Vec<(StudioError, ErrorCode, Option<EntityRef>)> = vec![
+            (
+                FlowError::NodeNotFound("n1".to_string()).into(),
+                ErrorCode::NodeNotFound,
+                Some(EntityRef::Node("n1".to_string())),
+            ),
+            (
+                FlowError::invalid_connection("type mismatch").into(),
+                ErrorCode::InvalidConnection,
+                None,
+            ),
+            (
+                DndError::InvalidDropTarget("zone".to_string()).into(),
+                ErrorCode::InvalidDropTarget,
+                Some(EntityRef::Node("zone".to_string())),
+            ),
+            (
+                TokenRenameError::AlreadyExists("colors.brand".to_string()).into(),
+                ErrorCode::TokenExists,
+                Some(EntityRef::Token("colors.brand".to_string())),
+            ),
+            (
+                TokenImportError::ParseError("eof".to_string()).into(),
+                ErrorCode::TokenParse,
+                None,
+            ),
+            (
+                SettingsError::UnknownSetting("editor.font".to_string()).into(),
+                ErrorCode::UnknownSetting,
+                Some(EntityRef::Setting("editor.font".to_string())),
+            ),
+            (
+                StyleGuideError::UnknownDefault("brand".to_string()).into(),
+                ErrorCode::UnknownTheme,
+                Some(EntityRef::Theme("brand".to_string())),
+            ),
+            (
+                PackError::IncompatibleSchema {
+                    min: 2,
+                    max: 3,
+                    current: 1,
+                }
+                .into(),
+                ErrorCode::PackIncompatible,
+                None,
+            ),
+            (
+                ImportError::MissingColumn("count".to_string()).into(),
+                ErrorCode::ImportMissingColumn,
+                None,
+            ),
+        ];
+
+        for (error, code, entity) in cases {
+            assert_eq!(error.code, code, "{}", error);
+            assert_eq!(error.entity, entity, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_validation_errors_keep_path_and_severity() {
+        let warning = TokenValidationError {
+            path: "spacing.md".to_string(),
+            message: "Unitless value".to_string(),
+            severity: ValidationSeverity::Warning,
+        };
+        let error: StudioError = warning.clone().into();
+        assert_eq!(error.severity, ValidationSeverity::Warning);
+        assert_eq!(
+            error.entity,
+            Some(EntityRef::Token("spacing.md".to_string()))
+        );
+
+        let import: StudioError = TokenImportError::ValidationError(vec![warning]).into();
+        assert_eq!(import.code, ErrorCode::TokenInvalid);
+        assert_eq!(import.message, "spacing.md: Unitless value");
+        assert_eq!(
+            import.entity,
+            Some(EntityRef::Token("spacing.md".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_codes_are_stable_strings() {
+        assert_eq!(ErrorCode::TokenNotFound.as_str(), "token_not_found");
+        assert_eq!(
+            serde_json::to_value(ErrorCode::DragTargetNotFound).unwrap(),
+            "drag_target_not_found"
+        );
+        let error = StudioError::from(FlowError::NodeNotFound("n1".to_string()));
+        assert_eq!(error.to_string(), "Node not found: n1");
+        assert!(error.recovery_hint.is_some());
+    }
+}
@@ -1,18 +1,39 @@
 //! Export functionality for studio configurations.
 
+pub mod batch;
+pub mod catalog;
+pub mod pack;
+pub mod print;
+pub mod project;
+pub mod router;
+pub mod state_machine;
+pub mod style_guide;
+pub mod tokens;
+pub mod workflow;
+
 use serde_yaml;
 
+pub use batch::{BatchRenderOptions, RenderedWorkflow, render_all_workflows};
+pub use project::{PROJECT_SCHEMA_VERSION, StudioProject};
+pub use router::{CodegenError, RouterGenOptions, UnknownTransition, generate_router};
+pub use state_machine::{StateMachineOptions, flow_to_state_machine};
+pub use tokens::{ExportError, ExportFormat};
+pub use workflow::{WorkflowExport, WorkflowImport};
+
 use crate::entity::Workflow;
+use crate::perf::trace_span;
 use crate::store::StudioStore;
 
 /// Export configuration to YAML.
 pub fn export_to_yaml(store: &StudioStore) -> Result<String, serde_yaml::Error> {
+    trace_span!("export", "export_yaml", count = store.workflows.len());
     let workflows: Vec<&Workflow> = store.workflows.values().collect();
     serde_yaml::to_string(&workflows)
 }
 
 /// Import configuration from YAML.
 pub fn import_from_yaml(yaml: &str) -> Result<Vec<Workflow>, serde_yaml::Error> {
+    trace_span!("import", "import_yaml", bytes = yaml.len());
     serde_yaml::from_str(yaml)
 }
 
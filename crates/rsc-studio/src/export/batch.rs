@@ -0,0 +1,284 @@
+//! Headless rendering of every workflow in a project.
+//!
+//! Each workflow is laid out on a canvas of its own and written as one SVG
+//! fitted to its content, named by the workflow's slug. Rendering reads the
+//! project only; the store it came from is never touched.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use rsc_flow::Node;
+
+use crate::designer::NavigationDesigner;
+use crate::designer::navigation::NavigationNodeData;
+use crate::entity::{Workflow, slugify};
+use crate::export::print::render_svg_image;
+use crate::perf::trace_span;
+use crate::project::ProjectFile;
+use crate::report::{ReportError, ReportOutcome};
+
+/// Workflow metadata flag marking it archived.
+pub const ARCHIVED_KEY: &str = "archived";
+
+/// Options for [`render_all_workflows`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRenderOptions {
+    /// Space around the content, in world units.
+    pub padding: f64,
+    /// Also render workflows marked archived.
+    pub include_archived: bool,
+}
+
+impl Default for BatchRenderOptions {
+    fn default() -> Self {
+        Self {
+            padding: 40.0,
+            include_archived: false,
+        }
+    }
+}
+
+/// What happened to one workflow.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderStatus {
+    Written,
+    /// Archived and `include_archived` is off.
+    SkippedArchived,
+    /// Writing the file failed.
+    Failed(String),
+}
+
+/// Result for one workflow, in project order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedWorkflow {
+    pub workflow_id: String,
+    pub name: String,
+    /// File written, or that would have been.
+    pub path: PathBuf,
+    pub status: RenderStatus,
+}
+
+fn is_archived(workflow: &Workflow) -> bool {
+    workflow
+        .metadata
+        .get(ARCHIVED_KEY)
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// SVG of one workflow laid out on its own canvas.
+pub fn render_workflow_svg(workflow: &Workflow, padding: f64) -> String {
+    trace_span!("export", "render_workflow", entity_id = %workflow.id);
+    let mut designer = NavigationDesigner::new();
+    designer.load_workflows(&[workflow]);
+    designer.apply_layout();
+    let label = |node: &Node<NavigationNodeData>| {
+        node.data
+            .as_ref()
+            .map_or_else(|| node.id.clone(), |d| d.label.clone())
+    };
+    // A workflow always has its own node, so the canvas is never empty.
+    render_svg_image(&designer.canvas, padding, label, |_| None).unwrap_or_default()
+}
+
+/// Write one SVG per workflow into `out_dir`, creating it if needed.
+/// Files are named by workflow slug; clashing slugs get a numeric suffix.
+pub fn render_all_workflows(
+    project: &ProjectFile,
+    options: &BatchRenderOptions,
+    out_dir: &Path,
+) -> Vec<RenderedWorkflow> {
+    let created = std::fs::create_dir_all(out_dir).map_err(|e| e.to_string());
+    let mut used = HashSet::new();
+    project
+        .workflows
+        .iter()
+        .map(|workflow| {
+            let mut slug = slugify(&workflow.name);
+            if slug.is_empty() {
+                slug = workflow.id.clone();
+            }
+            let mut stem = slug.clone();
+            let mut n = 2;
+            while !used.insert(stem.clone()) {
+                stem = format!("{}-{}", slug, n);
+                n += 1;
+            }
+            let path = out_dir.join(format!("{}.svg", stem));
+
+            let status = if is_archived(workflow) && !options.include_archived {
+                RenderStatus::SkippedArchived
+            } else {
+                let svg = render_workflow_svg(workflow, options.padding);
+                match created
+                    .clone()
+                    .and_then(|_| std::fs::write(&path, svg).map_err(|e| e.to_string()))
+                {
+                    Ok(()) => RenderStatus::Written,
+                    Err(error) => RenderStatus::Failed(error),
+                }
+            };
+            RenderedWorkflow {
+                workflow_id: workflow.id.clone(),
+                name: workflow.name.clone(),
+                path,
+                status,
+            }
+        })
+        .collect()
+}
+
+/// Options of the headless render entry point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderArgs {
+    pub out_dir: PathBuf,
+    pub options: BatchRenderOptions,
+    /// Arguments that aren't render flags, in order.
+    pub rest: Vec<String>,
+}
+
+impl RenderArgs {
+    /// Read `--out-dir` (default `renders`) and `--include-archived`.
+    /// Other arguments are collected in `rest`.
+    pub fn parse<I, S>(args: I) -> Result<Self, ReportError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut parsed = Self {
+            out_dir: PathBuf::from("renders"),
+            options: BatchRenderOptions::default(),
+            rest: Vec::new(),
+        };
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            match arg.split_once('=') {
+                Some(("--out-dir", value)) => parsed.out_dir = PathBuf::from(value),
+                _ if arg == "--out-dir" => {
+                    let value = args.next().ok_or(ReportError::MissingValue("--out-dir"))?;
+                    parsed.out_dir = PathBuf::from(value);
+                }
+                _ if arg == "--include-archived" => parsed.options.include_archived = true,
+                _ => parsed.rest.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Render every workflow of `project` as `args` asks. Prints one line per
+/// workflow and exits with 1 if any file failed to write.
+pub fn run_render(project: &ProjectFile, args: &RenderArgs) -> ReportOutcome {
+    let rendered = render_all_workflows(project, &args.options, &args.out_dir);
+    let mut output = String::new();
+    for r in &rendered {
+        let line = match &r.status {
+            RenderStatus::Written => format!("wrote {}", r.path.display()),
+            RenderStatus::SkippedArchived => format!("skipped {} (archived)", r.name),
+            RenderStatus::Failed(error) => format!("failed {}: {}", r.name, error),
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+    let failed = rendered
+        .iter()
+        .any(|r| matches!(r.status, RenderStatus::Failed(_)));
+    ReportOutcome {
+        output,
+        exit_code: i32::from(failed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StudioConfig;
+    use crate::entity::Context;
+    use crate::store::StudioStore;
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        let mut checkout = Workflow::new("Checkout & Pay").with_id("w1");
+        checkout.add_context(Context::new("Cart").with_id("c1"));
+        store.add_workflow(checkout);
+        let mut onboarding = Workflow::new("Onboarding").with_id("w2");
+        onboarding.add_context(Context::new("Welcome").with_id("c2"));
+        store.add_workflow(onboarding);
+        let mut legacy = Workflow::new("Checkout & Pay").with_id("w3");
+        legacy
+            .metadata
+            .insert(ARCHIVED_KEY.to_string(), true.into());
+        store.add_workflow(legacy);
+        store
+    }
+
+    fn out_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rsc-render-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_render_all_workflows() {
+        let store = store();
+        let before = ProjectFile::from_store(&store, &StudioConfig::default())
+            .to_json()
+            .unwrap();
+        let project = ProjectFile::from_store(&store, &StudioConfig::default());
+        let dir = out_dir("all");
+
+        let rendered = render_all_workflows(&project, &BatchRenderOptions::default(), &dir);
+        let summary: Vec<_> = rendered
+            .iter()
+            .map(|r| (r.path.file_name().unwrap().to_str().unwrap(), &r.status))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("checkout-pay.svg", &RenderStatus::Written),
+                ("onboarding.svg", &RenderStatus::Written),
+                ("checkout-pay-2.svg", &RenderStatus::SkippedArchived),
+            ]
+        );
+        let svg = std::fs::read_to_string(dir.join("checkout-pay.svg")).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(">Checkout &amp; Pay</text>"));
+        assert!(svg.contains(">Cart</text>"));
+        assert!(!svg.contains("crop-marks"));
+        assert!(dir.join("onboarding.svg").exists());
+        assert!(!dir.join("checkout-pay-2.svg").exists());
+
+        // The store and its project snapshot are untouched.
+        let after = ProjectFile::from_store(&store, &StudioConfig::default())
+            .to_json()
+            .unwrap();
+        assert_eq!(before, after);
+        assert_eq!(project.to_json().unwrap(), before);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_render_args() {
+        let dir = out_dir("cli");
+        let args = RenderArgs::parse([
+            "project.json".to_string(),
+            format!("--out-dir={}", dir.display()),
+            "--include-archived".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(args.rest, ["project.json"]);
+        assert!(args.options.include_archived);
+
+        let project = ProjectFile::from_store(&store(), &StudioConfig::default());
+        let outcome = run_render(&project, &args);
+        assert_eq!(outcome.exit_code, 0);
+        assert_eq!(outcome.output.lines().count(), 3);
+        assert!(dir.join("checkout-pay-2.svg").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            RenderArgs::parse(["--out-dir"]),
+            Err(ReportError::MissingValue("--out-dir"))
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Component catalog export.
+//!
+//! A single HTML page listing styled components grouped by type, each with
+//! a state matrix, breakpoint frames and its resolved CSS. Themes use the
+//! same variable blocks and switcher as the style guide.
+
+use indexmap::IndexMap;
+
+use super::style_guide::{BASE_CSS, escape, switcher_script, theme_block};
+use crate::designer::css::{
+    ComponentStyle, ComponentStyles, ComponentType, StateVariant, ThemeSet,
+};
+use crate::entity::slugify;
+
+/// Catalog page title.
+const CATALOG_TITLE: &str = "Component Catalog";
+
+/// Height of breakpoint preview frames.
+const FRAME_HEIGHT: u32 = 120;
+
+const CATALOG_CSS: &str = "\
+body { display: grid; grid-template-columns: 14rem 1fr; gap: 2rem; }
+header { grid-column: 1 / -1; }
+.catalog-sidebar ul { list-style: none; padding: 0; }
+.state-matrix, .breakpoint-frames { display: flex; flex-wrap: wrap; gap: 1rem; overflow-x: auto; }
+.breakpoint-frames iframe { border: 1px solid rgba(0,0,0,.1); background: #fff; }
+figure { margin: 0; }
+";
+
+/// Keeps breakpoint frames on the selected theme.
+const FRAME_SYNC_SCRIPT: &str = r#"<script>
+(function () {
+  var select = document.getElementById("theme-switcher");
+  function sync() {
+    var frames = document.querySelectorAll("iframe[data-catalog-frame]");
+    for (var i = 0; i < frames.length; i++) {
+      var doc = frames[i].contentDocument;
+      if (doc) { doc.documentElement.setAttribute("data-theme", select.value); }
+    }
+  }
+  select.addEventListener("change", sync);
+  window.addEventListener("load", sync);
+})();
+</script>
+"#;
+
+/// Render the component catalog.
+///
+/// `components` maps component class names to their type. Components whose
+/// style has `include_in_catalog` off are left out. `samples` overrides the
+/// preview markup per component; `{class}` in a sample is replaced with the
+/// element's class list.
+pub fn component_catalog(
+    components: &IndexMap<String, ComponentType>,
+    styles: &ComponentStyles,
+    themes: &ThemeSet,
+    samples: &IndexMap<String, String>,
+) -> String {
+    let default_style = ComponentStyle::default();
+    let entries: Vec<(&String, ComponentType, &ComponentStyle)> = components
+        .iter()
+        .map(|(name, ty)| (name, *ty, styles.get(name).unwrap_or(&default_style)))
+        .filter(|(_, _, style)| style.include_in_catalog)
+        .collect();
+
+    let theme_names: Vec<&str> = themes.themes.keys().map(String::as_str).collect();
+    let default_theme = themes.default_theme().unwrap_or_default();
+    let theme_css: String = themes
+        .themes
+        .iter()
+        .map(|(name, tokens)| theme_block(name, tokens))
+        .collect();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str(&format!(
+        "<html lang=\"en\" data-theme=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n",
+        escape(default_theme)
+    ));
+    html.push_str(&format!("<title>{}</title>\n", CATALOG_TITLE));
+    html.push_str("<style>\n");
+    html.push_str(&theme_css);
+    html.push_str(BASE_CSS);
+    html.push_str(CATALOG_CSS);
+    for (name, _, style) in &entries {
+        html.push_str(&style.to_css(name));
+        html.push_str(&forced_state_css(name, style));
+    }
+    html.push_str("</style>\n</head>\n<body>\n");
+
+    html.push_str(&format!("<header>\n<h1>{}</h1>\n", CATALOG_TITLE));
+    if !theme_names.is_empty() {
+        html.push_str("<label>Theme <select id=\"theme-switcher\">\n");
+        for name in &theme_names {
+            html.push_str(&format!(
+                "<option value=\"{0}\">{0}</option>\n",
+                escape(name)
+            ));
+        }
+        html.push_str("</select></label>\n");
+    }
+    html.push_str("</header>\n");
+
+    // Sidebar, grouped in ComponentType order.
+    html.push_str("<nav class=\"catalog-sidebar\">\n");
+    for ty in ComponentType::all() {
+        let group: Vec<&String> = entries
+            .iter()
+            .filter(|(_, t, _)| t == ty)
+            .map(|(name, _, _)| *name)
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+        html.push_str(&format!("<h3>{}</h3>\n<ul>\n", ty.label()));
+        for name in group {
+            html.push_str(&format!(
+                "<li><a href=\"#{}\">{}</a></li>\n",
+                anchor(name),
+                escape(name)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str("</nav>\n<main>\n");
+
+    for (name, ty, style) in &entries {
+        let sample = samples
+            .get(*name)
+            .cloned()
+            .unwrap_or_else(|| default_sample(*ty));
+        html.push_str(&entry_section(
+            name,
+            *ty,
+            style,
+            &sample,
+            &theme_css,
+            default_theme,
+        ));
+    }
+    html.push_str("</main>\n");
+
+    if !theme_names.is_empty() {
+        html.push_str(&switcher_script(&theme_names, default_theme));
+        html.push_str(FRAME_SYNC_SCRIPT);
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn anchor(name: &str) -> String {
+    format!("component-{}", slugify(name))
+}
+
+fn state_class(state: StateVariant) -> String {
+    format!("state-{}", state.label().to_lowercase())
+}
+
+/// Rules that apply each state's overrides through a class, so the state
+/// matrix can show hover or focus styles without interaction.
+fn forced_state_css(name: &str, style: &ComponentStyle) -> String {
+    let mut css = String::new();
+    for (state, props) in &style.states {
+        let body = props.to_css();
+        if *state != StateVariant::Default && !body.is_empty() {
+            css.push_str(&format!(
+                ".{}.{} {{\n{}}}\n\n",
+                name,
+                state_class(*state),
+                body
+            ));
+        }
+    }
+    css
+}
+
+fn default_sample(ty: ComponentType) -> String {
+    match ty {
+        ComponentType::Button => "<button class=\"{class}\">Button</button>".to_string(),
+        ComponentType::Input => "<input class=\"{class}\" placeholder=\"Input\">".to_string(),
+        _ => format!("<div class=\"{{class}}\">{}</div>", ty.label()),
+    }
+}
+
+fn render_sample(sample: &str, class: &str) -> String {
+    sample.replace("{class}", &escape(class))
+}
+
+fn entry_section(
+    name: &str,
+    ty: ComponentType,
+    style: &ComponentStyle,
+    sample: &str,
+    theme_css: &str,
+    default_theme: &str,
+) -> String {
+    let id = anchor(name);
+    let css = style.to_css(name);
+    let mut html = format!(
+        "<section id=\"{0}\" class=\"catalog-entry\">\n<h2><a href=\"#{0}\">{1}</a> <small>{2}</small></h2>\n",
+        id,
+        escape(name),
+        ty.label()
+    );
+
+    html.push_str("<div class=\"state-matrix\">\n");
+    let states = std::iter::once(StateVariant::Default).chain(
+        style
+            .states
+            .keys()
+            .copied()
+            .filter(|s| *s != StateVariant::Default),
+    );
+    for state in states {
+        let class = match state {
+            StateVariant::Default => name.to_string(),
+            _ => format!("{} {}", name, state_class(state)),
+        };
+        html.push_str(&format!(
+            "<figure>{}<figcaption>{}</figcaption></figure>\n",
+            render_sample(sample, &class),
+            state.label()
+        ));
+    }
+    html.push_str("</div>\n");
+
+    let frames: Vec<(&str, u32)> = style
+        .breakpoints
+        .keys()
+        .filter_map(|bp| Some((bp.label(), bp.min_width()?)))
+        .collect();
+    if !frames.is_empty() {
+        html.push_str("<div class=\"breakpoint-frames\">\n");
+        let doc = format!(
+            "<!DOCTYPE html><html data-theme=\"{}\"><head><style>{}{}</style></head><body>{}</body></html>",
+            escape(default_theme),
+            theme_css,
+            css,
+            render_sample(sample, name)
+        );
+        for (label, width) in frames {
+            html.push_str(&format!(
+                "<figure><iframe data-catalog-frame title=\"{} at {}\" width=\"{}\" height=\"{}\" srcdoc=\"{}\"></iframe><figcaption>{} · {}px</figcaption></figure>\n",
+                escape(name),
+                label,
+                width,
+                FRAME_HEIGHT,
+                escape(&doc).replace('\n', "&#10;"),
+                label,
+                width
+            ));
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str(&format!(
+        "<details>\n<summary>CSS</summary>\n<pre><code>{}</code></pre>\n</details>\n</section>\n",
+        escape(&css)
+    ));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{Breakpoint, DesignTokens, StyleProperties, TokenValue};
+
+    /// Structural lines of the catalog, for snapshot comparison.
+    fn outline(html: &str) -> Vec<String> {
+        html.lines()
+            .filter(|l| {
+                ["<h3>", "<li>", "<section id", "<figure>", "<summary>"]
+                    .iter()
+                    .any(|p| l.starts_with(p))
+            })
+            .map(|l| {
+                // Frames carry a whole document; keep only the caption.
+                match l.find("<iframe") {
+                    Some(i) => format!(
+                        "{}<iframe/>{}",
+                        &l[..i],
+                        &l[l.find("</iframe>").unwrap() + 9..]
+                    ),
+                    None => l.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    fn fixture() -> (IndexMap<String, ComponentType>, ComponentStyles, ThemeSet) {
+        let props = |color: &str| StyleProperties {
+            background_color: Some(color.to_string()),
+            ..Default::default()
+        };
+        let mut button = ComponentStyle {
+            base: props("var(--color-primary)"),
+            ..Default::default()
+        };
+        button.states.insert(StateVariant::Hover, props("#1d4ed8"));
+        button.breakpoints.insert(Breakpoint::Md, props("#2563eb"));
+
+        let card = ComponentStyle {
+            base: props("#fff"),
+            ..Default::default()
+        };
+        let internal = ComponentStyle {
+            include_in_catalog: false,
+            ..Default::default()
+        };
+
+        let mut styles = ComponentStyles::new();
+        styles.set("primary-button".to_string(), button);
+        styles.set("card".to_string(), card);
+        styles.set("debug-overlay".to_string(), internal);
+
+        let mut components = IndexMap::new();
+        components.insert("card".to_string(), ComponentType::Card);
+        components.insert("primary-button".to_string(), ComponentType::Button);
+        components.insert("debug-overlay".to_string(), ComponentType::Panel);
+
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Adaptive {
+                light: "#3b82f6".to_string(),
+                dark: "#60a5fa".to_string(),
+            },
+        );
+        (components, styles, ThemeSet::from_adaptive(&tokens))
+    }
+
+    #[test]
+    fn test_catalog_structure_snapshot() {
+        let (components, styles, themes) = fixture();
+        let html = component_catalog(&components, &styles, &themes, &IndexMap::new());
+
+        let expected = [
+            "<h3>Button</h3>",
+            "<li><a href=\"#component-primary-button\">primary-button</a></li>",
+            "<h3>Card</h3>",
+            "<li><a href=\"#component-card\">card</a></li>",
+            "<section id=\"component-card\" class=\"catalog-entry\">",
+            "<figure><div class=\"card\">Card</div><figcaption>Default</figcaption></figure>",
+            "<summary>CSS</summary>",
+            "<section id=\"component-primary-button\" class=\"catalog-entry\">",
+            "<figure><button class=\"primary-button\">Button</button><figcaption>Default</figcaption></figure>",
+            "<figure><button class=\"primary-button state-hover\">Button</button><figcaption>Hover</figcaption></figure>",
+            "<figure><iframe/><figcaption>MD · 768px</figcaption></figure>",
+            "<summary>CSS</summary>",
+        ];
+        assert_eq!(outline(&html), expected);
+        assert!(!html.contains("debug-overlay"));
+    }
+
+    #[test]
+    fn test_catalog_styles_and_themes() {
+        let (components, styles, themes) = fixture();
+        let mut samples = IndexMap::new();
+        samples.insert(
+            "card".to_string(),
+            "<article class=\"{class}\"><h4>Title</h4></article>".to_string(),
+        );
+        let html = component_catalog(&components, &styles, &themes, &samples);
+
+        assert!(html.contains("<html lang=\"en\" data-theme=\"light\">"));
+        assert!(html.contains("[data-theme=\"dark\"] {\n  --color-primary: #60a5fa;"));
+        assert!(html.contains(".primary-button.state-hover {\n  background-color: #1d4ed8;\n}"));
+        assert!(html.contains("<article class=\"card\"><h4>Title</h4></article>"));
+        assert!(html.contains("id=\"theme-switcher\""));
+        assert!(html.contains("srcdoc=\"&lt;!DOCTYPE html&gt;"));
+        assert!(html.contains("<pre><code>.card {\n  background-color: #fff;\n}"));
+    }
+}
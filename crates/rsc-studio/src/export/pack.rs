@@ -0,0 +1,400 @@
+//! Shareable token packs.
+//!
+//! A pack bundles design tokens, optional component style presets and
+//! preview samples behind a manifest describing who published it and which
+//! token schema versions it is compatible with.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::designer::css::{
+    ComponentStyle, DesignTokens, MergeReport, MergeStrategy, TokenUsageTracker,
+};
+use crate::history::HistoryScope;
+use crate::store::{StoreChange, StudioStore};
+
+/// Token schema version understood by this studio.
+pub const TOKEN_SCHEMA_VERSION: u32 = 1;
+
+/// A shareable bundle of tokens and component presets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPack {
+    /// Pack manifest.
+    pub manifest: PackManifest,
+    /// Token payload.
+    #[serde(default)]
+    pub tokens: DesignTokens,
+    /// Component style presets, keyed by component name.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub presets: IndexMap<String, ComponentStyle>,
+    /// Preview samples shown on the review screen.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<PackSample>,
+    /// Markdown readme.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+}
+
+/// Pack manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackManifest {
+    /// Pack name.
+    pub name: String,
+    /// Pack version.
+    pub version: String,
+    /// Author.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Compatible token schema versions.
+    #[serde(default)]
+    pub schema: SchemaRange,
+}
+
+/// Inclusive range of token schema versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Default for SchemaRange {
+    fn default() -> Self {
+        Self {
+            min: TOKEN_SCHEMA_VERSION,
+            max: TOKEN_SCHEMA_VERSION,
+        }
+    }
+}
+
+impl SchemaRange {
+    pub fn contains(&self, version: u32) -> bool {
+        self.min <= version && version <= self.max
+    }
+}
+
+/// Preview sample bundled with a pack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSample {
+    /// Sample title.
+    pub name: String,
+    /// Component the sample demonstrates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    /// Sample markup.
+    pub markup: String,
+}
+
+/// Token pack error types.
+#[derive(Debug, Error)]
+pub enum PackError {
+    /// The pack could not be parsed.
+    #[error("Invalid token pack: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// The pack targets a token schema this studio does not support.
+    #[error("Pack requires token schema {min}..={max}, studio supports {current}")]
+    IncompatibleSchema { min: u32, max: u32, current: u32 },
+
+    /// The merged result failed validation.
+    #[error("Pack failed validation: {}", .0.join("; "))]
+    Validation(Vec<String>),
+}
+
+/// Pre-install overview of a pack.
+#[derive(Debug, Clone)]
+pub struct PackSummary {
+    pub name: String,
+    pub version: String,
+    pub author: Option<String>,
+    pub schema: SchemaRange,
+    /// Whether the pack can be installed in this studio.
+    pub compatible: bool,
+    /// Total number of tokens.
+    pub token_count: usize,
+    /// Token count per category path prefix (non-empty categories only).
+    pub categories: Vec<(String, usize)>,
+    /// Names of bundled component presets.
+    pub presets: Vec<String>,
+    /// Number of preview samples.
+    pub sample_count: usize,
+    pub has_readme: bool,
+}
+
+/// Outcome of installing a pack.
+#[derive(Debug, Clone, Default)]
+pub struct PackInstallReport {
+    /// Token merge outcome.
+    pub tokens: MergeReport,
+    /// Presets that did not exist before.
+    pub presets_added: Vec<String>,
+    /// Existing presets that were replaced.
+    pub presets_overwritten: Vec<String>,
+    /// Existing presets left untouched.
+    pub presets_skipped: Vec<String>,
+}
+
+impl TokenPack {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            manifest: PackManifest {
+                name: name.into(),
+                version: version.into(),
+                author: None,
+                schema: SchemaRange::default(),
+            },
+            tokens: DesignTokens::default(),
+            presets: IndexMap::new(),
+            samples: Vec::new(),
+            readme: None,
+        }
+    }
+
+    /// Parse a pack from its JSON encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PackError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Encode the pack as JSON.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PackError> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Check the pack's schema range against this studio.
+    pub fn check_compatible(&self) -> Result<(), PackError> {
+        let schema = self.manifest.schema;
+        if schema.contains(TOKEN_SCHEMA_VERSION) {
+            Ok(())
+        } else {
+            Err(PackError::IncompatibleSchema {
+                min: schema.min,
+                max: schema.max,
+                current: TOKEN_SCHEMA_VERSION,
+            })
+        }
+    }
+
+    /// Summarize the pack for review.
+    pub fn summary(&self) -> PackSummary {
+        PackSummary {
+            name: self.manifest.name.clone(),
+            version: self.manifest.version.clone(),
+            author: self.manifest.author.clone(),
+            schema: self.manifest.schema,
+            compatible: self.check_compatible().is_ok(),
+            token_count: self.tokens.count(),
+            categories: self
+                .tokens
+                .categories()
+                .into_iter()
+                .filter(|(_, map)| !map.is_empty())
+                .map(|(prefix, map)| (prefix.to_string(), map.len()))
+                .collect(),
+            presets: self.presets.keys().cloned().collect(),
+            sample_count: self.samples.len(),
+            has_readme: self.readme.is_some(),
+        }
+    }
+}
+
+/// Parse a pack and summarize it without installing.
+pub fn inspect_pack(bytes: &[u8]) -> Result<PackSummary, PackError> {
+    Ok(TokenPack::from_bytes(bytes)?.summary())
+}
+
+/// Install a pack into the store as one undoable CSS designer action.
+///
+/// The merge is computed and validated before the store is touched, so a
+/// failing install leaves tokens, presets and history unchanged.
+pub fn install_pack(
+    store: &mut StudioStore,
+    pack: &TokenPack,
+    strategy: MergeStrategy,
+) -> Result<PackInstallReport, PackError> {
+    pack.check_compatible()?;
+
+    let mut tokens = store.tokens.clone();
+    let mut report = PackInstallReport {
+        tokens: tokens.merge(pack.tokens.clone(), strategy),
+        ..Default::default()
+    };
+
+    let mut changes = Vec::new();
+    for (name, preset) in &pack.presets {
+        let existing = store.component_styles.get(name);
+        if existing.is_some() && strategy == MergeStrategy::PreferExisting {
+            report.presets_skipped.push(name.clone());
+            continue;
+        }
+        if existing.is_some() {
+            report.presets_overwritten.push(name.clone());
+        } else {
+            report.presets_added.push(name.clone());
+        }
+        changes.push(StoreChange::ComponentStyle {
+            name: name.clone(),
+            before: existing.cloned().map(Box::new),
+            after: Some(Box::new(preset.clone())),
+        });
+    }
+
+    let problems = unresolved_references(&tokens, pack);
+    if !problems.is_empty() {
+        return Err(PackError::Validation(problems));
+    }
+
+    changes.insert(
+        0,
+        StoreChange::Tokens {
+            before: Box::new(store.tokens.clone()),
+            after: Box::new(tokens),
+        },
+    );
+    store.commit(
+        HistoryScope::CssDesigner,
+        format!("Install token pack \"{}\"", pack.manifest.name),
+        StoreChange::Batch(changes),
+    );
+
+    Ok(report)
+}
+
+/// References from the pack's tokens and presets that the merged set can't resolve.
+fn unresolved_references(merged: &DesignTokens, pack: &TokenPack) -> Vec<String> {
+    let mut tracker = TokenUsageTracker::new();
+    for refs in pack.tokens.find_dependencies().values() {
+        for path in refs {
+            tracker.mark_used(path);
+        }
+    }
+    for preset in pack.presets.values() {
+        tracker.scan_component_style(preset);
+    }
+
+    let known = merged.all_paths();
+    let mut missing: Vec<String> = tracker
+        .used
+        .into_iter()
+        .filter(|path| !known.contains(path))
+        .map(|path| format!("unresolved token reference: {}", path))
+        .collect();
+    missing.sort();
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+
+    fn sample_pack() -> TokenPack {
+        let mut pack = TokenPack::new("Acme", "1.2.0");
+        pack.manifest.author = Some("Design Ops".to_string());
+        pack.tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#ff5500".to_string()),
+        );
+        pack.tokens.colors.insert(
+            "accent".to_string(),
+            TokenValue::Simple("#00aaff".to_string()),
+        );
+        pack.tokens
+            .spacing
+            .insert("md".to_string(), TokenValue::Simple("1rem".to_string()));
+
+        let mut button = ComponentStyle::default();
+        button.base.background_color = Some("var(--color-primary)".to_string());
+        pack.presets.insert("button".to_string(), button);
+        pack.readme = Some("# Acme tokens".to_string());
+        pack
+    }
+
+    #[test]
+    fn test_inspect_pack() {
+        let bytes = sample_pack().to_bytes().unwrap();
+        let summary = inspect_pack(&bytes).unwrap();
+
+        assert_eq!(summary.name, "Acme");
+        assert_eq!(summary.author.as_deref(), Some("Design Ops"));
+        assert!(summary.compatible);
+        assert_eq!(summary.token_count, 3);
+        assert_eq!(
+            summary.categories,
+            vec![("colors".to_string(), 2), ("spacing".to_string(), 1)]
+        );
+        assert_eq!(summary.presets, vec!["button".to_string()]);
+        assert!(summary.has_readme);
+    }
+
+    #[test]
+    fn test_install_keeps_existing_conflicts() {
+        let mut store = StudioStore::new();
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#3b82f6".to_string()),
+        );
+        store.set_tokens(tokens);
+
+        let report =
+            install_pack(&mut store, &sample_pack(), MergeStrategy::PreferExisting).unwrap();
+
+        assert_eq!(report.tokens.skipped, vec!["colors.primary".to_string()]);
+        assert_eq!(
+            report.tokens.added,
+            vec!["colors.accent".to_string(), "spacing.md".to_string()]
+        );
+        assert_eq!(report.presets_added, vec!["button".to_string()]);
+        assert_eq!(
+            store.tokens.colors.get("primary"),
+            Some(&TokenValue::Simple("#3b82f6".to_string()))
+        );
+        assert!(store.component_styles.get("button").is_some());
+
+        // The whole install is one undoable step
+        assert!(store.undo_scope(HistoryScope::CssDesigner));
+        assert!(!store.tokens.colors.contains_key("accent"));
+        assert!(store.component_styles.get("button").is_none());
+        assert!(store.tokens.colors.contains_key("primary"));
+    }
+
+    #[test]
+    fn test_incompatible_schema_fails_before_mutation() {
+        let mut pack = sample_pack();
+        pack.manifest.schema = SchemaRange { min: 2, max: 3 };
+
+        let mut store = StudioStore::new();
+        let result = install_pack(&mut store, &pack, MergeStrategy::PreferIncoming);
+
+        assert!(matches!(
+            result,
+            Err(PackError::IncompatibleSchema { min: 2, max: 3, .. })
+        ));
+        assert_eq!(store.tokens.count(), 0);
+        assert!(!store.history.can_undo(HistoryScope::CssDesigner));
+    }
+
+    #[test]
+    fn test_validation_failure_rolls_back() {
+        let mut pack = sample_pack();
+        let mut card = ComponentStyle::default();
+        card.base.padding = Some("var(--spacing-xl)".to_string());
+        pack.presets.insert("card".to_string(), card);
+
+        let mut store = StudioStore::new();
+        let result = install_pack(&mut store, &pack, MergeStrategy::PreferIncoming);
+
+        match result {
+            Err(PackError::Validation(problems)) => {
+                assert_eq!(
+                    problems,
+                    vec!["unresolved token reference: spacing.xl".to_string()]
+                );
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+        assert_eq!(store.tokens.count(), 0);
+        assert!(store.component_styles.styles.is_empty());
+        assert!(!store.history.can_undo(HistoryScope::CssDesigner));
+    }
+}
@@ -0,0 +1,552 @@
+//! Print layout for workflow graphs.
+//!
+//! Large graphs are split into overlapping page-sized tiles. Each page
+//! carries crop marks and a small index map showing where the tile sits in
+//! the grid, and the set can be emitted as separate SVG pages or as one
+//! HTML document with CSS page breaks for the browser's print-to-PDF.
+
+use rsc_flow::{Dimensions, Edge, FlowCanvas, Node, Position, Rect};
+
+use crate::analytics::WeightOverlay;
+
+/// Size assumed for nodes without dimensions.
+const DEFAULT_NODE_SIZE: Dimensions = Dimensions {
+    width: 150.0,
+    height: 50.0,
+};
+
+/// Length of crop mark strokes.
+const CROP_MARK: f64 = 12.0;
+
+/// Width of the page index map.
+const INDEX_MAP_WIDTH: f64 = 60.0;
+
+/// A4 portrait at 96 dpi.
+pub const A4: Dimensions = Dimensions {
+    width: 794.0,
+    height: 1123.0,
+};
+
+/// One printed page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageTile {
+    /// 0-based page number, row-major.
+    pub index: usize,
+    pub row: usize,
+    pub col: usize,
+    pub rows: usize,
+    pub cols: usize,
+    /// Area of the canvas on this page, in world units.
+    pub rect: Rect,
+}
+
+impl PageTile {
+    /// Total pages in the set.
+    pub fn page_count(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// Page label such as "B3" (row letter, 1-based column).
+    pub fn label(&self) -> String {
+        let mut row = String::new();
+        let mut n = self.row;
+        loop {
+            row.insert(0, (b'A' + (n % 26) as u8) as char);
+            if n < 26 {
+                break;
+            }
+            n = n / 26 - 1;
+        }
+        format!("{}{}", row, self.col + 1)
+    }
+}
+
+fn node_rects<N, E>(canvas: &FlowCanvas<N, E>) -> Vec<Rect> {
+    canvas
+        .nodes
+        .values()
+        .map(|n| {
+            n.bounds().unwrap_or(Rect {
+                position: n.position,
+                dimensions: DEFAULT_NODE_SIZE,
+            })
+        })
+        .collect()
+}
+
+/// Split a canvas into overlapping page tiles.
+///
+/// Neighbouring tiles share `overlap` world units, clamped to half a page.
+/// Within the slack left over by whole pages, the grid is nudged so that
+/// as few nodes as possible end up split across pages. Returns no tiles
+/// for an empty canvas or a degenerate page size.
+pub fn paginate_flow<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    page_size: Dimensions,
+    overlap: f64,
+) -> Vec<PageTile> {
+    let Some(content) = canvas.get_bounds() else {
+        return Vec::new();
+    };
+    if page_size.width <= 0.0 || page_size.height <= 0.0 {
+        return Vec::new();
+    }
+    let rects = node_rects(canvas);
+
+    let x_spans: Vec<(f64, f64)> = rects
+        .iter()
+        .map(|r| (r.position.x, r.position.x + r.dimensions.width))
+        .collect();
+    let y_spans: Vec<(f64, f64)> = rects
+        .iter()
+        .map(|r| (r.position.y, r.position.y + r.dimensions.height))
+        .collect();
+
+    let overlap_x = overlap.clamp(0.0, page_size.width / 2.0);
+    let overlap_y = overlap.clamp(0.0, page_size.height / 2.0);
+    let (x0, cols) = tile_axis(
+        content.position.x,
+        content.dimensions.width,
+        page_size.width,
+        overlap_x,
+        &x_spans,
+    );
+    let (y0, rows) = tile_axis(
+        content.position.y,
+        content.dimensions.height,
+        page_size.height,
+        overlap_y,
+        &y_spans,
+    );
+
+    let mut tiles = Vec::with_capacity(rows * cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            tiles.push(PageTile {
+                index: tiles.len(),
+                row,
+                col,
+                rows,
+                cols,
+                rect: Rect::new(
+                    x0 + col as f64 * (page_size.width - overlap_x),
+                    y0 + row as f64 * (page_size.height - overlap_y),
+                    page_size.width,
+                    page_size.height,
+                ),
+            });
+        }
+    }
+    tiles
+}
+
+/// Tile one axis: returns the grid origin and tile count.
+fn tile_axis(
+    start: f64,
+    extent: f64,
+    page: f64,
+    overlap: f64,
+    spans: &[(f64, f64)],
+) -> (f64, usize) {
+    let step = page - overlap;
+    let count = (((extent - overlap) / step).ceil() as usize).max(1);
+    let slack = (count as f64 * step + overlap - extent).max(0.0);
+
+    // Candidate origins put a tile edge exactly on a node edge.
+    let mut candidates = vec![start];
+    for &(a, b) in spans {
+        for i in 0..count {
+            let offset = i as f64 * step;
+            candidates.push(a - offset);
+            candidates.push(b - offset - page);
+        }
+    }
+
+    let split = |origin: f64| {
+        spans
+            .iter()
+            .filter(|&&(a, b)| {
+                !(0..count).any(|i| {
+                    let lo = origin + i as f64 * step;
+                    a >= lo - 1e-9 && b <= lo + page + 1e-9
+                })
+            })
+            .count()
+    };
+
+    let origin = candidates
+        .into_iter()
+        .filter(|o| *o <= start + 1e-9 && *o >= start - slack - 1e-9)
+        .map(|o| (split(o), (start - o).abs(), o))
+        .min_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+        .map_or(start, |(_, _, o)| o);
+    (origin, count)
+}
+
+/// Render each tile as a standalone SVG page.
+pub fn render_svg_pages<N, E>(canvas: &FlowCanvas<N, E>, tiles: &[PageTile]) -> Vec<String> {
+    render_svg_pages_titled(canvas, tiles, |_| None)
+}
+
+/// Render each tile, giving edges a `<title>` where `edge_title` returns
+/// one so screen readers can describe them.
+pub fn render_svg_pages_titled<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    tiles: &[PageTile],
+    edge_title: impl Fn(&Edge<E>) -> Option<String>,
+) -> Vec<String> {
+    render_svg_pages_overlaid(canvas, tiles, edge_title, None)
+}
+
+/// [`render_svg_pages_titled`], with weighted edges stroked by `overlay`
+/// and its legend on every page.
+pub fn render_svg_pages_overlaid<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    tiles: &[PageTile],
+    edge_title: impl Fn(&Edge<E>) -> Option<String>,
+    overlay: Option<&WeightOverlay>,
+) -> Vec<String> {
+    tiles
+        .iter()
+        .map(|tile| render_tile(canvas, tile, &edge_title, overlay))
+        .collect()
+}
+
+/// Render all tiles into one HTML document, one page per tile.
+pub fn render_print_html<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    tiles: &[PageTile],
+    title: &str,
+) -> String {
+    let (width, height) = tiles.first().map_or((0.0, 0.0), |t| {
+        (t.rect.dimensions.width, t.rect.dimensions.height)
+    });
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(title)));
+    html.push_str("<style>\n");
+    html.push_str(&format!(
+        "@page {{ size: {}px {}px; margin: 0; }}\n",
+        width, height
+    ));
+    html.push_str("body { margin: 0; }\n");
+    html.push_str(".page { break-after: page; page-break-after: always; }\n");
+    html.push_str(".page:last-child { break-after: auto; page-break-after: auto; }\n");
+    html.push_str("</style>\n</head>\n<body>\n");
+    for svg in render_svg_pages(canvas, tiles) {
+        html.push_str("<div class=\"page\">\n");
+        html.push_str(&svg);
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render the whole canvas as one image sized to its content plus
+/// `padding`, without print marks. Nodes are labelled by `node_text`.
+/// Returns `None` for an empty canvas.
+pub fn render_svg_image<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    padding: f64,
+    node_text: impl Fn(&Node<N>) -> String,
+    edge_title: impl Fn(&Edge<E>) -> Option<String>,
+) -> Option<String> {
+    let content = canvas.get_bounds()?;
+    let padding = padding.max(0.0);
+    let r = Rect::new(
+        content.position.x - padding,
+        content.position.y - padding,
+        content.dimensions.width + 2.0 * padding,
+        content.dimensions.height + 2.0 * padding,
+    );
+    let mut svg = svg_open(r.dimensions);
+    svg.push_str(&render_content(canvas, r, &node_text, &edge_title, None));
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+fn svg_open(size: Dimensions) -> String {
+    let (w, h) = (size.width, size.height);
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n"
+    )
+}
+
+fn render_tile<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    tile: &PageTile,
+    edge_title: &impl Fn(&Edge<E>) -> Option<String>,
+    overlay: Option<&WeightOverlay>,
+) -> String {
+    let r = tile.rect;
+    let (w, h) = (r.dimensions.width, r.dimensions.height);
+    let mut svg = svg_open(r.dimensions);
+    svg.push_str(&render_content(
+        canvas,
+        r,
+        &|n| n.id.clone(),
+        edge_title,
+        overlay,
+    ));
+    if let Some(overlay) = overlay {
+        svg.push_str(&weight_legend(overlay));
+    }
+    svg.push_str(&crop_marks(w, h));
+    svg.push_str(&index_map(tile, w, h));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Edges and the nodes inside `r`, translated so `r` starts at the origin.
+fn render_content<N, E>(
+    canvas: &FlowCanvas<N, E>,
+    r: Rect,
+    node_text: &impl Fn(&Node<N>) -> String,
+    edge_title: &impl Fn(&Edge<E>) -> Option<String>,
+    overlay: Option<&WeightOverlay>,
+) -> String {
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<g transform=\"translate({} {})\">\n",
+        -r.position.x, -r.position.y
+    ));
+
+    let center = |id: &str| -> Option<Position> {
+        let node = canvas.nodes.get(id)?;
+        let dims = node.dimensions.unwrap_or(DEFAULT_NODE_SIZE);
+        Some(Position::new(
+            node.position.x + dims.width / 2.0,
+            node.position.y + dims.height / 2.0,
+        ))
+    };
+    for edge in canvas.edges.values() {
+        if let (Some(a), Some(b)) = (center(&edge.source), center(&edge.target)) {
+            let stroke = match overlay.and_then(|o| o.style_for(&edge.id)) {
+                Some(style) => format!(
+                    "stroke=\"{}\" stroke-width=\"{}\"",
+                    style.color, style.stroke_width
+                ),
+                None => "stroke=\"#94a3b8\"".to_string(),
+            };
+            let line = format!(
+                "<line class=\"edge\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}",
+                a.x, a.y, b.x, b.y, stroke
+            );
+            match edge_title(edge) {
+                Some(title) => svg.push_str(&format!(
+                    "{}><title>{}</title></line>\n",
+                    line,
+                    escape(&title)
+                )),
+                None => svg.push_str(&format!("{}/>\n", line)),
+            }
+        }
+    }
+    for (node, rect) in canvas.nodes.values().zip(node_rects(canvas)) {
+        if !rect.intersects(&r) {
+            continue;
+        }
+        svg.push_str(&format!(
+            "<rect class=\"node\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"#fff\" stroke=\"#334155\"/>\n",
+            rect.position.x, rect.position.y, rect.dimensions.width, rect.dimensions.height
+        ));
+        let c = rect.center();
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\" font-size=\"12\">{}</text>\n",
+            c.x,
+            c.y,
+            escape(&node_text(node))
+        ));
+    }
+    svg.push_str("</g>\n");
+    svg
+}
+
+/// Key to the weight buckets, inside the top-left crop marks.
+fn weight_legend(overlay: &WeightOverlay) -> String {
+    let mut legend = String::from("<g class=\"weight-legend\" font-size=\"10\">\n");
+    for (i, entry) in overlay.legend().iter().enumerate() {
+        let y = CROP_MARK * 2.0 + i as f64 * 14.0;
+        legend.push_str(&format!(
+            "<line x1=\"{x}\" y1=\"{y}\" x2=\"{}\" y2=\"{y}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+            CROP_MARK * 2.0 + 20.0,
+            entry.color,
+            entry.stroke_width,
+            x = CROP_MARK * 2.0,
+        ));
+        legend.push_str(&format!(
+            "<text x=\"{}\" y=\"{y}\" dominant-baseline=\"middle\">{}</text>\n",
+            CROP_MARK * 2.0 + 26.0,
+            escape(&entry.label)
+        ));
+    }
+    legend.push_str("</g>\n");
+    legend
+}
+
+/// Corner crop marks.
+fn crop_marks(w: f64, h: f64) -> String {
+    let mut marks = String::from("<g class=\"crop-marks\" stroke=\"#000\" stroke-width=\"0.5\">\n");
+    for (x, y, dx, dy) in [
+        (0.0, 0.0, 1.0, 1.0),
+        (w, 0.0, -1.0, 1.0),
+        (0.0, h, 1.0, -1.0),
+        (w, h, -1.0, -1.0),
+    ] {
+        marks.push_str(&format!(
+            "<path d=\"M{} {} h{} M{} {} v{}\"/>\n",
+            x,
+            y,
+            dx * CROP_MARK,
+            x,
+            y,
+            dy * CROP_MARK
+        ));
+    }
+    marks.push_str("</g>\n");
+    marks
+}
+
+/// Mini overview of the page grid with the current tile highlighted.
+fn index_map(tile: &PageTile, w: f64, h: f64) -> String {
+    let cell = INDEX_MAP_WIDTH / tile.cols.max(tile.rows) as f64;
+    let x0 = w - CROP_MARK - cell * tile.cols as f64;
+    let y0 = h - CROP_MARK - cell * tile.rows as f64 - 14.0;
+    let mut map = String::from("<g class=\"page-index\">\n");
+    for row in 0..tile.rows {
+        for col in 0..tile.cols {
+            let current = row == tile.row && col == tile.col;
+            map.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#64748b\" stroke-width=\"0.5\"/>\n",
+                x0 + col as f64 * cell,
+                y0 + row as f64 * cell,
+                cell,
+                cell,
+                if current { "#334155" } else { "none" }
+            ));
+        }
+    }
+    map.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"10\">{} ({}/{})</text>\n",
+        w - CROP_MARK,
+        h - CROP_MARK,
+        tile.label(),
+        tile.index + 1,
+        tile.page_count()
+    ));
+    map.push_str("</g>\n");
+    map
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsc_flow::{Edge, Node, NodeType};
+
+    fn node(id: &str, x: f64, y: f64) -> Node<()> {
+        Node::new(id, NodeType::Default, Position::new(x, y))
+            .with_dimensions(Dimensions::new(100.0, 40.0))
+    }
+
+    fn covers(tiles: &[PageTile], content: Rect) -> bool {
+        // Sample the content bounds on a fine grid.
+        let steps = 40;
+        (0..=steps).all(|i| {
+            (0..=steps).all(|j| {
+                let p = Position::new(
+                    content.position.x + content.dimensions.width * i as f64 / steps as f64,
+                    content.position.y + content.dimensions.height * j as f64 / steps as f64,
+                );
+                tiles.iter().any(|t| t.rect.contains(&p))
+            })
+        })
+    }
+
+    #[test]
+    fn test_tiles_cover_content() {
+        let canvas: FlowCanvas = FlowCanvas::from_elements(
+            vec![
+                node("a", -50.0, 10.0),
+                node("b", 900.0, 620.0),
+                node("c", 400.0, 1300.0),
+            ],
+            vec![Edge::new("e1", "a", "b")],
+        );
+        let tiles = paginate_flow(&canvas, Dimensions::new(400.0, 500.0), 40.0);
+        let content = canvas.get_bounds().unwrap();
+
+        assert!(covers(&tiles, content));
+        assert_eq!((tiles[0].rows, tiles[0].cols), (3, 3));
+        assert_eq!(tiles.len(), 9);
+        assert_eq!(tiles[4].label(), "B2");
+        // Neighbours overlap by the requested amount.
+        let step = tiles[1].rect.position.x - tiles[0].rect.position.x;
+        assert_eq!(step, 360.0);
+
+        assert!(paginate_flow(&FlowCanvas::<(), ()>::new(), A4, 20.0).is_empty());
+    }
+
+    #[test]
+    fn test_nudge_avoids_splitting_nodes() {
+        // Content spans x 0..700; pages are 400 wide with no overlap, so two
+        // columns leave 100 units of slack. Without a nudge the cut at 400
+        // goes through "mid" (360..460).
+        let canvas: FlowCanvas = FlowCanvas::from_elements(
+            vec![
+                node("left", 0.0, 0.0),
+                node("mid", 360.0, 0.0),
+                node("right", 600.0, 0.0),
+            ],
+            vec![],
+        );
+        let tiles = paginate_flow(&canvas, Dimensions::new(400.0, 400.0), 0.0);
+
+        assert_eq!(tiles.len(), 2);
+        let mid = canvas.nodes["mid"].bounds().unwrap();
+        let contains_rect = |t: &PageTile, r: &Rect| {
+            t.rect.contains(&r.position)
+                && t.rect.contains(&Position::new(
+                    r.position.x + r.dimensions.width,
+                    r.position.y + r.dimensions.height,
+                ))
+        };
+        assert!(tiles.iter().any(|t| contains_rect(t, &mid)));
+        for n in canvas.nodes.values() {
+            let r = n.bounds().unwrap();
+            assert!(
+                tiles.iter().any(|t| contains_rect(t, &r)),
+                "{} is split",
+                n.id
+            );
+        }
+        assert!(covers(&tiles, canvas.get_bounds().unwrap()));
+    }
+
+    #[test]
+    fn test_render_outputs() {
+        let canvas: FlowCanvas = FlowCanvas::from_elements(
+            vec![node("a", 0.0, 0.0), node("b<1>", 500.0, 0.0)],
+            vec![Edge::new("e1", "a", "b<1>")],
+        );
+        let tiles = paginate_flow(&canvas, Dimensions::new(400.0, 300.0), 20.0);
+        let pages = render_svg_pages(&canvas, &tiles);
+
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].contains(">a</text>"));
+        assert!(!pages[0].contains("b&lt;1&gt;"));
+        assert!(pages[1].contains("b&lt;1&gt;"));
+        assert!(pages[0].contains("class=\"crop-marks\""));
+        assert!(pages[1].contains("A2 (2/2)"));
+
+        let html = render_print_html(&canvas, &tiles, "Flow");
+        assert!(html.contains("@page { size: 400px 300px; margin: 0; }"));
+        assert_eq!(html.matches("<div class=\"page\">").count(), 2);
+    }
+}
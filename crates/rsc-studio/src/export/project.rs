@@ -0,0 +1,245 @@
+//! Single-document project bundle.
+//!
+//! A [`StudioProject`] is a [`ProjectFile`] with the project settings and a
+//! schema version alongside, so it carries every theme, the component
+//! styles, the workflows, layers and flow styles in one document. It is
+//! encoded like a project file: compressed bundles are gzipped, plain ones
+//! are the JSON itself.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::StudioConfig;
+use crate::designer::css::DesignTokens;
+use crate::error::StudioError;
+use crate::perf::trace_span;
+use crate::project::storage::{decode, encode};
+use crate::project::{ProjectError, ProjectFile, SaveOptions};
+use crate::settings::SettingStorage;
+use crate::store::StudioStore;
+use crate::store::dirty::ChangeCategory;
+
+/// Current bundle schema version.
+pub const PROJECT_SCHEMA_VERSION: u32 = 1;
+
+/// Setting locations saved with a bundle; UI state stays with the session.
+const BUNDLED_SETTINGS: [SettingStorage; 2] = [SettingStorage::Config, SettingStorage::Project];
+
+/// Everything needed to reopen a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudioProject {
+    pub schema_version: u32,
+    /// Configuration and project settings by id.
+    #[serde(default)]
+    pub settings: IndexMap<String, Value>,
+    /// Workflows, tokens, themes, styles and layers.
+    #[serde(flatten)]
+    pub project: ProjectFile,
+}
+
+impl StudioProject {
+    /// Bundle the persisted parts of a store.
+    pub fn from_store(store: &StudioStore, config: &StudioConfig) -> Self {
+        let mut settings = IndexMap::new();
+        for storage in BUNDLED_SETTINGS {
+            settings.extend(store.settings.persisted(storage));
+        }
+        Self {
+            schema_version: PROJECT_SCHEMA_VERSION,
+            settings,
+            project: ProjectFile::from_store(store, config),
+        }
+    }
+
+    /// Encode as pretty JSON.
+    pub fn save(&self) -> Result<Vec<u8>, ProjectError> {
+        self.to_bytes(&SaveOptions::plain())
+    }
+
+    /// Encode with all [`SaveOptions::compact`] optimizations.
+    pub fn save_compressed(&self) -> Result<Vec<u8>, ProjectError> {
+        self.to_bytes(&SaveOptions::compact())
+    }
+
+    fn to_bytes(&self, options: &SaveOptions) -> Result<Vec<u8>, ProjectError> {
+        trace_span!(
+            "export",
+            "save_project",
+            count = self.project.workflows.len()
+        );
+        encode(serde_json::to_value(self)?, options)
+    }
+
+    /// Decode a bundle written by [`save`](Self::save) or
+    /// [`save_compressed`](Self::save_compressed).
+    pub fn load(bytes: &[u8]) -> Result<StudioProject, ProjectError> {
+        trace_span!("import", "load_project", bytes = bytes.len());
+        let value = decode(bytes)?;
+        check_version(&value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Read only the active theme's tokens, from a bundle or from a plain
+    /// token file.
+    pub fn load_tokens_only(bytes: &[u8]) -> Result<DesignTokens, ProjectError> {
+        let mut value = decode(bytes)?;
+        if value.get("schema_version").is_none() {
+            return Ok(serde_json::from_value(value)?);
+        }
+        check_version(&value)?;
+        match value.get_mut("tokens") {
+            Some(tokens) => Ok(serde_json::from_value(tokens.take())?),
+            None => Ok(DesignTokens::default()),
+        }
+    }
+}
+
+fn check_version(value: &Value) -> Result<(), ProjectError> {
+    let found = value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if found == 0 || found > u64::from(PROJECT_SCHEMA_VERSION) {
+        return Err(ProjectError::UnsupportedVersion {
+            found,
+            supported: PROJECT_SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+impl StudioStore {
+    /// Save the project as one bundle. Everything in it counts as saved.
+    pub fn export_project(
+        &mut self,
+        config: &StudioConfig,
+        compress: bool,
+    ) -> Result<Vec<u8>, StudioError> {
+        let project = StudioProject::from_store(self, config);
+        let result = if compress {
+            project.save_compressed()
+        } else {
+            project.save()
+        };
+        let bytes = self.checked(result)?;
+        self.mark_saved(ChangeCategory::all())?;
+        Ok(bytes)
+    }
+
+    /// Open a bundle, replacing the project and the bundled settings, and
+    /// return its configuration. The load can be undone; the loaded state
+    /// counts as saved.
+    pub fn import_project(&mut self, bytes: &[u8]) -> Result<StudioConfig, StudioError> {
+        let result = StudioProject::load(bytes);
+        let bundle = self.checked(result)?;
+        let config = bundle.project.load_into(self);
+        self.load_settings(SettingStorage::Project, bundle.settings);
+        self.mark_saved(ChangeCategory::all())?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsc_flow::EdgeLayer;
+
+    use super::*;
+    use crate::designer::css::{ComponentStyle, TokenValue};
+    use crate::entity::Workflow;
+    use crate::history::HistoryScope;
+    use crate::project::storage::GZIP_MAGIC;
+
+    fn sample() -> StudioStore {
+        let mut store = StudioStore::new();
+        store.tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#ff5500".to_string()),
+        );
+        store.create_theme("dark", None).unwrap();
+        store.add_workflow(Workflow::new("Checkout").with_id("w1"));
+        store
+            .set_component_style("button", ComponentStyle::default())
+            .unwrap();
+        store.set_setting("export.format", "json").unwrap();
+        store.add_layer(EdgeLayer::new("happy", "#22c55e"));
+        store
+    }
+
+    #[test]
+    fn test_roundtrip_plain_and_compressed() {
+        let mut store = sample();
+        let config = StudioConfig::default();
+        let plain = store.export_project(&config, false).unwrap();
+        let compressed = store.export_project(&config, true).unwrap();
+        assert!(plain.starts_with(b"{"));
+        assert!(compressed.starts_with(&GZIP_MAGIC));
+        assert!(compressed.len() < plain.len());
+        assert!(!store.is_dirty());
+
+        for bytes in [plain, compressed] {
+            let mut loaded = StudioStore::new();
+            loaded.add_layer(EdgeLayer::new("stale", "#000000"));
+            loaded.import_project(&bytes).unwrap();
+            assert!(!loaded.is_dirty());
+            assert_eq!(loaded.workflows["w1"].name, "Checkout");
+            assert!(loaded.component_styles.get("button").is_some());
+            assert_eq!(
+                loaded.themes.names().collect::<Vec<_>>(),
+                ["default", "dark"]
+            );
+            assert!(loaded.tokens.colors.contains_key("primary"));
+            assert!(loaded.layers.get("happy").is_some());
+            assert!(loaded.layers.get("stale").is_none());
+            assert_eq!(
+                loaded.settings.get("export.format").unwrap().as_str(),
+                Some("json")
+            );
+
+            // Undoing the import is an unsaved change.
+            loaded.undo_scope(HistoryScope::Global);
+            assert!(loaded.workflows.is_empty());
+            assert!(loaded.is_dirty());
+        }
+    }
+
+    #[test]
+    fn test_tokens_only() {
+        let bytes = sample()
+            .export_project(&StudioConfig::default(), true)
+            .unwrap();
+        let tokens = StudioProject::load_tokens_only(&bytes).unwrap();
+        assert!(tokens.colors.contains_key("primary"));
+
+        let token_file = br##"{"colors": {"accent": "#00aaff"}}"##;
+        let tokens = StudioProject::load_tokens_only(token_file).unwrap();
+        assert!(tokens.colors.contains_key("accent"));
+        assert!(matches!(
+            StudioProject::load(token_file),
+            Err(ProjectError::UnsupportedVersion { found: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_input() {
+        let mut store = sample();
+        let mut bytes = store
+            .export_project(&StudioConfig::default(), true)
+            .unwrap();
+        bytes.truncate(bytes.len() / 2);
+        assert!(matches!(
+            StudioProject::load(&bytes),
+            Err(ProjectError::Compression(_))
+        ));
+        assert!(matches!(
+            StudioProject::load(b"{\"schema_version\": 1, \"themes\""),
+            Err(ProjectError::Parse(_))
+        ));
+
+        let newer = br#"{"schema_version": 7, "themes": {}}"#;
+        let error = store.import_project(newer).unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::ProjectParse);
+        assert_eq!(store.problems.len(), 1);
+        assert_eq!(store.workflows.len(), 1);
+    }
+}
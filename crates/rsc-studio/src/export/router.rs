@@ -0,0 +1,548 @@
+//! Navigation flow to a Rust router.
+//!
+//! Where [`flow_to_state_machine`](super::flow_to_state_machine) models
+//! events, the router models destinations: each context becomes a `Route`
+//! variant, carrying the parameters declared under [`PARAMS_KEY`] in its
+//! metadata, and `transition` matches on the (from, to) pairs drawn as
+//! edges on the navigation canvas. Guarded edges get a stub that allows
+//! the transition, with the condition as a TODO. Edges into other
+//! workflows are left out; each workflow routes within itself.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use rsc_flow::prelude::*;
+use thiserror::Error;
+
+use super::state_machine::{Idents, condition, one_line};
+use crate::designer::navigation::NavigationNodeData;
+use crate::entity::{EntityId, Workflow, slugify};
+
+/// Context metadata key declaring a route's parameters: an array of names,
+/// typed `String`, or an object mapping names to Rust types.
+pub const PARAMS_KEY: &str = "params";
+
+/// Types a route parameter may have, alone or in an `Option`.
+const PARAM_TYPES: [&str; 17] = [
+    "String", "bool", "char", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32",
+    "u64", "u128", "usize", "f32", "f64",
+];
+
+/// Strict and reserved Rust keywords.
+const KEYWORDS: [&str; 52] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// What the generated `transition` does with a transition the workflow
+/// doesn't have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownTransition {
+    /// Return `Err(TransitionError)`.
+    #[default]
+    Error,
+    /// Panic.
+    Panic,
+}
+
+/// Router generation options.
+#[derive(Debug, Clone)]
+pub struct RouterGenOptions {
+    /// Name of the generated module.
+    pub module_name: String,
+    /// Derive `Serialize` and `Deserialize` on `Route`.
+    pub derive_serde: bool,
+    pub unknown_transition: UnknownTransition,
+}
+
+impl Default for RouterGenOptions {
+    fn default() -> Self {
+        Self {
+            module_name: "router".to_string(),
+            derive_serde: false,
+            unknown_transition: UnknownTransition::Error,
+        }
+    }
+}
+
+impl RouterGenOptions {
+    pub fn with_module_name(mut self, name: impl Into<String>) -> Self {
+        self.module_name = name.into();
+        self
+    }
+
+    pub fn with_serde(mut self, derive_serde: bool) -> Self {
+        self.derive_serde = derive_serde;
+        self
+    }
+
+    pub fn with_unknown_transition(mut self, unknown: UnknownTransition) -> Self {
+        self.unknown_transition = unknown;
+        self
+    }
+}
+
+/// Code generation error types.
+#[derive(Debug, Error)]
+pub enum CodegenError {
+    #[error("Invalid module name: {0:?}")]
+    InvalidModuleName(String),
+    #[error("Workflow {0:?} has no contexts")]
+    NoContexts(String),
+    /// The context's params entry is neither an array nor an object.
+    #[error("Context {0} has malformed params")]
+    InvalidParams(EntityId),
+    #[error("Param {param:?} of context {context} has unsupported type {ty:?}")]
+    InvalidParamType {
+        context: EntityId,
+        param: String,
+        ty: String,
+    },
+}
+
+fn is_keyword(ident: &str) -> bool {
+    KEYWORDS.contains(&ident)
+}
+
+/// `name` as a snake_case identifier, or `fallback` if nothing is left.
+fn snake_case(name: &str, fallback: &str) -> String {
+    let mut ident: String = slugify(name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert_str(0, &format!("{}_", fallback));
+    }
+    if is_keyword(&ident) {
+        ident.push('_');
+    }
+    ident
+}
+
+fn is_module_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && name != "_"
+        && !is_keyword(name)
+}
+
+fn is_param_type(ty: &str) -> bool {
+    match ty.strip_prefix("Option<").and_then(|t| t.strip_suffix('>')) {
+        Some(inner) => is_param_type(inner.trim()),
+        None => PARAM_TYPES.contains(&ty),
+    }
+}
+
+/// One `Route` variant.
+struct Route {
+    ident: String,
+    snake: String,
+    name: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Route {
+    fn pattern(&self) -> String {
+        if self.fields.is_empty() {
+            format!("Route::{}", self.ident)
+        } else {
+            format!("Route::{} {{ .. }}", self.ident)
+        }
+    }
+}
+
+fn params(context: &crate::entity::Context) -> Result<Vec<(String, String)>, CodegenError> {
+    let Some(value) = context.metadata.get(PARAMS_KEY) else {
+        return Ok(Vec::new());
+    };
+    let invalid = || CodegenError::InvalidParams(context.id.clone());
+    let declared: Vec<(String, String)> = match value {
+        serde_json::Value::Array(names) => names
+            .iter()
+            .map(|n| Some((n.as_str()?.to_string(), "String".to_string())))
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?,
+        serde_json::Value::Object(types) => types
+            .iter()
+            .map(|(n, ty)| Some((n.clone(), ty.as_str()?.trim().to_string())))
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+    let mut used = HashSet::new();
+    let mut fields = Vec::new();
+    for (name, ty) in declared {
+        if !is_param_type(&ty) {
+            return Err(CodegenError::InvalidParamType {
+                context: context.id.clone(),
+                param: name,
+                ty,
+            });
+        }
+        let base = snake_case(&name, "param");
+        let mut field = base.clone();
+        let mut n = 2;
+        while !used.insert(field.clone()) {
+            field = format!("{}_{}", base, n);
+            n += 1;
+        }
+        fields.push((field, ty));
+    }
+    Ok(fields)
+}
+
+/// Edges between one pair of routes.
+#[derive(Default)]
+struct Transition {
+    labels: Vec<String>,
+    conditions: Vec<String>,
+    unguarded: bool,
+}
+
+/// Generate a Rust module routing between the contexts of `workflow`,
+/// with transitions taken from the edges on `canvas`.
+pub fn generate_router(
+    workflow: &Workflow,
+    canvas: &FlowCanvas<NavigationNodeData, EdgeData>,
+    opts: &RouterGenOptions,
+) -> Result<String, CodegenError> {
+    if !is_module_name(&opts.module_name) {
+        return Err(CodegenError::InvalidModuleName(opts.module_name.clone()));
+    }
+    if workflow.contexts.is_empty() {
+        return Err(CodegenError::NoContexts(workflow.name.clone()));
+    }
+
+    let mut idents = Idents::default();
+    let mut routes: IndexMap<&str, Route> = IndexMap::new();
+    for context in workflow.contexts.values() {
+        let route = Route {
+            ident: idents.alloc(&slugify(&context.name), "Route"),
+            snake: snake_case(&context.name, "route"),
+            name: one_line(&context.name),
+            fields: params(context)?,
+        };
+        routes.insert(context.id.as_str(), route);
+    }
+
+    let mut transitions: IndexMap<(&str, &str), Transition> = IndexMap::new();
+    for edge in canvas.edges.values() {
+        let (Some((source, _)), Some((target, _))) = (
+            routes.get_key_value(edge.source.as_str()),
+            routes.get_key_value(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        let transition = transitions.entry((*source, *target)).or_default();
+        let label = edge
+            .label
+            .as_ref()
+            .map(|l| l.text.clone())
+            .or_else(|| edge.data.as_ref().and_then(|d| d.label.clone()))
+            .map(|l| one_line(&l))
+            .filter(|l| !l.is_empty() && !transition.labels.contains(l));
+        transition.labels.extend(label);
+        match condition(edge.data.as_ref()) {
+            Some(condition) => transition.conditions.push(condition),
+            None => transition.unguarded = true,
+        }
+    }
+
+    let default_route = workflow
+        .default_context
+        .as_deref()
+        .and_then(|id| routes.get(id))
+        .filter(|r| r.fields.is_empty())
+        .map(|r| r.ident.as_str());
+    let floats = routes
+        .values()
+        .flat_map(|r| &r.fields)
+        .any(|(_, ty)| ty.contains("f32") || ty.contains("f64"));
+    let mut derives = vec!["Debug", "Clone", "PartialEq"];
+    if !floats {
+        derives.push("Eq");
+    }
+    if default_route.is_some() {
+        derives.push("Default");
+    }
+    if opts.derive_serde {
+        derives.extend(["Serialize", "Deserialize"]);
+    }
+
+    let mut body = String::new();
+    if opts.derive_serde {
+        body.push_str("use serde::{Deserialize, Serialize};\n\n");
+    }
+    body.push_str("/// Routes, one per context.\n");
+    body.push_str(&format!("#[derive({})]\n", derives.join(", ")));
+    body.push_str("pub enum Route {\n");
+    for route in routes.values() {
+        body.push_str(&format!("    /// {}\n", route.name));
+        if Some(route.ident.as_str()) == default_route {
+            body.push_str("    #[default]\n");
+        }
+        if route.fields.is_empty() {
+            body.push_str(&format!("    {},\n", route.ident));
+        } else {
+            body.push_str(&format!("    {} {{\n", route.ident));
+            for (field, ty) in &route.fields {
+                body.push_str(&format!("        {}: {},\n", field, ty));
+            }
+            body.push_str("    },\n");
+        }
+    }
+    body.push_str("}\n\n");
+
+    let (returns, ok, unknown) = match opts.unknown_transition {
+        UnknownTransition::Error => {
+            body.push_str("/// A transition the workflow doesn't have.\n");
+            body.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+            body.push_str("pub struct TransitionError {\n");
+            body.push_str("    pub from: Route,\n    pub to: Route,\n}\n\n");
+            (
+                "Result<Route, TransitionError>",
+                "Ok(to)",
+                "Err(TransitionError { from: from.clone(), to })",
+            )
+        }
+        UnknownTransition::Panic => (
+            "Route",
+            "to",
+            "panic!(\"no transition from {:?} to {:?}\", from, to)",
+        ),
+    };
+
+    let mut guard_names = HashSet::new();
+    let mut guards = String::new();
+    body.push_str("/// Navigate from `from` to `to` along the workflow's transitions.\n");
+    body.push_str(&format!(
+        "pub fn transition(from: &Route, to: Route) -> {} {{\n",
+        returns
+    ));
+    if transitions.is_empty() {
+        body.push_str(&format!("    {}\n}}\n", unknown));
+    } else {
+        body.push_str("    match (from, &to) {\n");
+        for ((source, target), transition) in &transitions {
+            let (from, to) = (&routes[*source], &routes[*target]);
+            let pattern = format!("({}, {})", from.pattern(), to.pattern());
+            if !transition.labels.is_empty() {
+                body.push_str(&format!(
+                    "        // \"{}\"\n",
+                    transition.labels.join("\", \"")
+                ));
+            }
+            if transition.unguarded {
+                body.push_str(&format!("        {} => {},\n", pattern, ok));
+                continue;
+            }
+            let mut calls = Vec::new();
+            for condition in &transition.conditions {
+                let base = format!("can_go_{}_to_{}", from.snake, to.snake);
+                let mut name = base.clone();
+                let mut n = 2;
+                while !guard_names.insert(name.clone()) {
+                    name = format!("{}_{}", base, n);
+                    n += 1;
+                }
+                guards.push_str(&format!(
+                    "\n/// Guard on the transition from {} to {}.\n\
+                     fn {}(_from: &Route, _to: &Route) -> bool {{\n    \
+                     // TODO: only when `{}`\n    true\n}}\n",
+                    from.name, to.name, name, condition
+                ));
+                calls.push(format!("{}(from, &to)", name));
+            }
+            let arm = format!("        {} if {} => {},", pattern, calls.join(" || "), ok);
+            // Long arms get a block, as rustfmt would write them.
+            if arm.len() <= 100 {
+                body.push_str(&arm);
+                body.push('\n');
+            } else {
+                body.push_str(&format!(
+                    "        {}\n            if {} =>\n        {{\n            {}\n        }}\n",
+                    pattern,
+                    calls.join(" || "),
+                    ok
+                ));
+            }
+        }
+        let covered = transitions.len() == routes.len() * routes.len()
+            && transitions.values().all(|t| t.unguarded);
+        if !covered {
+            body.push_str(&format!("        _ => {},\n", unknown));
+        }
+        body.push_str("    }\n}\n");
+    }
+    body.push_str(&guards);
+
+    let mut out = format!(
+        "//! Router for the \"{}\" workflow.\n\
+         //!\n\
+         //! Generated from the navigation flow. Guards allow every transition\n\
+         //! until filled in; their conditions are noted as TODOs.\n\n",
+        one_line(&workflow.name)
+    );
+    out.push_str(&format!("pub mod {} {{\n", opts.module_name));
+    for line in body.lines() {
+        if !line.is_empty() {
+            out.push_str("    ");
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::navigation::NavigationDesigner;
+    use crate::entity::Context;
+    use crate::export::state_machine::CONDITION_KEY;
+
+    fn checkout() -> (Workflow, FlowCanvas<NavigationNodeData, EdgeData>) {
+        let mut workflow = Workflow::new("Checkout").with_id("checkout");
+        workflow.add_context(Context::new("Cart").with_id("cart"));
+        let mut shipping = Context::new("Shipping address").with_id("shipping");
+        shipping
+            .metadata
+            .insert(PARAMS_KEY.to_string(), serde_json::json!(["order id"]));
+        workflow.add_context(shipping);
+        let mut done = Context::new("Order confirmed!").with_id("done");
+        done.metadata.insert(
+            PARAMS_KEY.to_string(),
+            serde_json::json!({"order": "u64", "type": "Option<String>"}),
+        );
+        workflow.add_context(done);
+        workflow.default_context = Some("cart".to_string());
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        let mut canvas = designer.canvas;
+        let guarded = |condition: &str| EdgeData {
+            metadata: IndexMap::from([(CONDITION_KEY.to_string(), condition.into())]),
+            ..Default::default()
+        };
+        for (id, source, target, label, data) in [
+            ("e1", "cart", "shipping", "Checkout", None),
+            ("e2", "shipping", "cart", "back", None),
+            (
+                "e3",
+                "shipping",
+                "done",
+                "Pay",
+                Some(guarded("cart.total > 0")),
+            ),
+            (
+                "e4",
+                "shipping",
+                "done",
+                "Pay",
+                Some(guarded("promo.covers_total")),
+            ),
+            ("e5", "done", "outside", "Elsewhere", None),
+        ] {
+            let mut edge = Edge::new(id, source, target).with_label(label);
+            edge.data = data;
+            canvas.add_edge(edge);
+        }
+        (workflow, canvas)
+    }
+
+    #[test]
+    fn test_generated_router_parses() {
+        let (workflow, canvas) = checkout();
+        for opts in [
+            RouterGenOptions::default(),
+            RouterGenOptions::default()
+                .with_module_name("checkout_routes")
+                .with_serde(true)
+                .with_unknown_transition(UnknownTransition::Panic),
+        ] {
+            let code = generate_router(&workflow, &canvas, &opts).unwrap();
+            if let Err(error) = syn::parse_file(&code) {
+                panic!("{}\n{}", error, code);
+            }
+        }
+
+        let code = generate_router(&workflow, &canvas, &RouterGenOptions::default()).unwrap();
+        assert!(code.contains("pub mod router {\n"));
+        assert!(code.contains("    #[derive(Debug, Clone, PartialEq, Eq, Default)]\n"));
+        assert!(code.contains("        #[default]\n        Cart,\n"));
+        assert!(code.contains("        ShippingAddress {\n            order_id: String,\n"));
+        assert!(code.contains("            order: u64,\n            type_: Option<String>,\n"));
+        assert!(
+            code.contains("            (Route::Cart, Route::ShippingAddress { .. }) => Ok(to),\n")
+        );
+        assert!(code.contains(
+            "if can_go_shipping_address_to_order_confirmed(from, &to) \
+             || can_go_shipping_address_to_order_confirmed_2(from, &to)"
+        ));
+        assert!(code.contains("        // TODO: only when `promo.covers_total`\n"));
+        assert!(code.contains("_ => Err(TransitionError { from: from.clone(), to }),"));
+        assert!(!code.contains("Elsewhere"));
+    }
+
+    #[test]
+    fn test_options_control_output() {
+        let (workflow, canvas) = checkout();
+        let opts = RouterGenOptions::default()
+            .with_serde(true)
+            .with_unknown_transition(UnknownTransition::Panic);
+        let code = generate_router(&workflow, &canvas, &opts).unwrap();
+        assert!(code.contains("    use serde::{Deserialize, Serialize};\n"));
+        assert!(code.contains("Default, Serialize, Deserialize)]"));
+        assert!(code.contains("pub fn transition(from: &Route, to: Route) -> Route {"));
+        assert!(code.contains("_ => panic!("));
+        assert!(!code.contains("TransitionError"));
+
+        // Without edges, every transition is unknown.
+        let code =
+            generate_router(&workflow, &FlowCanvas::new(), &RouterGenOptions::default()).unwrap();
+        assert!(code.contains("    Err(TransitionError { from: from.clone(), to })\n"));
+        syn::parse_file(&code).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let (mut workflow, canvas) = checkout();
+        let opts = RouterGenOptions::default().with_module_name("Router");
+        assert!(matches!(
+            generate_router(&workflow, &canvas, &opts),
+            Err(CodegenError::InvalidModuleName(_))
+        ));
+        let opts = RouterGenOptions::default().with_module_name("mod");
+        assert!(generate_router(&workflow, &canvas, &opts).is_err());
+
+        workflow.contexts["cart"].metadata.insert(
+            PARAMS_KEY.to_string(),
+            serde_json::json!({"items": "Vec<u8>"}),
+        );
+        let opts = RouterGenOptions::default();
+        assert!(matches!(
+            generate_router(&workflow, &canvas, &opts),
+            Err(CodegenError::InvalidParamType { ty, .. }) if ty == "Vec<u8>"
+        ));
+        workflow.contexts["cart"]
+            .metadata
+            .insert(PARAMS_KEY.to_string(), serde_json::json!("id"));
+        assert!(matches!(
+            generate_router(&workflow, &canvas, &opts),
+            Err(CodegenError::InvalidParams(id)) if id == "cart"
+        ));
+
+        let empty = Workflow::new("Empty");
+        assert!(matches!(
+            generate_router(&empty, &canvas, &opts),
+            Err(CodegenError::NoContexts(_))
+        ));
+    }
+}
@@ -0,0 +1,436 @@
+//! Navigation flow to a Rust state-machine skeleton.
+//!
+//! The contexts of a workflow become `State` variants and the edges drawn
+//! between them on the navigation canvas become `Event` variants, one per
+//! label. `transition` is a match over those edges. Edge conditions (the
+//! [`CONDITION_KEY`] metadata entry) are free text, so guarded transitions
+//! are emitted with a TODO and the predicate as a comment. Edges into
+//! another workflow's contexts lead to `State::External`.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use rsc_flow::prelude::*;
+
+use crate::designer::navigation::{EntityType, NavigationNodeData};
+use crate::entity::{Workflow, slugify};
+
+/// Edge metadata key holding a transition's guard condition.
+pub const CONDITION_KEY: &str = "condition";
+
+/// State-machine export options.
+#[derive(Debug, Clone)]
+pub struct StateMachineOptions {
+    /// Emit a test module asserting a few unguarded transitions.
+    pub include_tests: bool,
+    /// Event name for unlabeled edges, followed by the target state.
+    pub unlabeled_event_prefix: String,
+}
+
+impl Default for StateMachineOptions {
+    fn default() -> Self {
+        Self {
+            include_tests: true,
+            unlabeled_event_prefix: "GoTo".to_string(),
+        }
+    }
+}
+
+impl StateMachineOptions {
+    pub fn with_tests(mut self, include_tests: bool) -> Self {
+        self.include_tests = include_tests;
+        self
+    }
+
+    pub fn with_unlabeled_event_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.unlabeled_event_prefix = prefix.into();
+        self
+    }
+}
+
+/// `name` as a PascalCase identifier. Words are split on anything but
+/// ASCII letters and digits; a leading digit or an empty result gets
+/// `fallback` in front.
+pub fn pascal_case(name: &str, fallback: &str) -> String {
+    let mut ident = String::new();
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        let Some(first) = chars.next() else {
+            continue;
+        };
+        ident.push(first.to_ascii_uppercase());
+        let rest = chars.as_str();
+        // All-caps words like "OK" would trip the acronym lint.
+        if word.chars().all(|c| !c.is_ascii_lowercase()) {
+            ident.push_str(&rest.to_ascii_lowercase());
+        } else {
+            ident.push_str(rest);
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) || ident == "Self" {
+        ident.insert_str(0, fallback);
+    }
+    ident
+}
+
+/// Hands out unique identifiers, numbering repeats.
+#[derive(Default)]
+pub(crate) struct Idents {
+    used: HashSet<String>,
+}
+
+impl Idents {
+    pub(crate) fn reserve(&mut self, ident: &str) {
+        self.used.insert(ident.to_string());
+    }
+
+    pub(crate) fn alloc(&mut self, name: &str, fallback: &str) -> String {
+        let base = pascal_case(name, fallback);
+        let mut ident = base.clone();
+        let mut n = 2;
+        while !self.used.insert(ident.clone()) {
+            ident = format!("{}{}", base, n);
+            n += 1;
+        }
+        ident
+    }
+}
+
+/// One edge between states.
+struct Transition {
+    target: String,
+    condition: Option<String>,
+}
+
+/// Comment-safe single line.
+pub(crate) fn one_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub(crate) fn condition(data: Option<&EdgeData>) -> Option<String> {
+    let value = data?.metadata.get(CONDITION_KEY)?;
+    let text = match value.as_str() {
+        Some(s) => one_line(s),
+        None => value.to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Generate a Rust module implementing `workflow` as a state machine, with
+/// transitions taken from the edges on `canvas`.
+pub fn flow_to_state_machine(
+    workflow: &Workflow,
+    canvas: &FlowCanvas<NavigationNodeData, EdgeData>,
+    options: &StateMachineOptions,
+) -> String {
+    let mut state_idents = Idents::default();
+    state_idents.reserve("External");
+    let states: IndexMap<&str, String> = workflow
+        .contexts
+        .values()
+        .map(|c| {
+            (
+                c.id.as_str(),
+                state_idents.alloc(&slugify(&c.name), "State"),
+            )
+        })
+        .collect();
+
+    let mut event_idents = Idents::default();
+    let mut events: IndexMap<String, (String, String)> = IndexMap::new();
+    let mut external_idents = Idents::default();
+    let mut externals: IndexMap<String, (String, String)> = IndexMap::new();
+    let mut transitions: IndexMap<(String, String), Vec<Transition>> = IndexMap::new();
+
+    for edge in canvas.edges.values() {
+        let Some(source) = states.get(edge.source.as_str()) else {
+            continue;
+        };
+        let target = if let Some(state) = states.get(edge.target.as_str()) {
+            format!("State::{}", state)
+        } else {
+            // Edges to presets or workflows are hierarchy, not navigation.
+            let Some(node) = canvas.get_node(&edge.target).and_then(|n| n.data.as_ref()) else {
+                continue;
+            };
+            if node.entity_type != EntityType::Context {
+                continue;
+            }
+            let workflow_name = node
+                .parent_id
+                .as_deref()
+                .and_then(|id| canvas.get_node(id))
+                .and_then(|n| n.data.as_ref())
+                .map_or("", |w| w.label.as_str());
+            let (ident, _) = externals.entry(edge.target.clone()).or_insert_with(|| {
+                let name = format!("{} {}", workflow_name, node.label);
+                let doc = format!(
+                    "Context \"{}\" in workflow \"{}\".",
+                    one_line(&node.label),
+                    one_line(workflow_name)
+                );
+                (external_idents.alloc(&name, "Target"), doc)
+            });
+            format!("State::External(ExternalTarget::{})", ident)
+        };
+
+        let label = edge
+            .label
+            .as_ref()
+            .map(|l| l.text.clone())
+            .or_else(|| edge.data.as_ref().and_then(|d| d.label.clone()))
+            .filter(|l| !l.trim().is_empty());
+        let key = label.clone().unwrap_or_else(|| {
+            let target_name = target.rsplit("::").next().unwrap_or_default();
+            format!(
+                "{}{}",
+                options.unlabeled_event_prefix,
+                target_name.trim_end_matches(')')
+            )
+        });
+        let (event, _) = events.entry(key.clone()).or_insert_with(|| {
+            let doc = match &label {
+                Some(label) => format!("\"{}\"", one_line(label)),
+                None => "Unlabeled edge.".to_string(),
+            };
+            (event_idents.alloc(&key, "Event"), doc)
+        });
+
+        transitions
+            .entry((source.clone(), event.clone()))
+            .or_default()
+            .push(Transition {
+                target,
+                condition: condition(edge.data.as_ref()),
+            });
+    }
+
+    let default_state = workflow
+        .default_context
+        .as_deref()
+        .and_then(|id| states.get(id));
+
+    let mut out = format!(
+        "//! State machine for the \"{}\" workflow.\n\
+         //!\n\
+         //! Generated from the navigation flow. Guarded transitions are marked\n\
+         //! TODO with their condition as a comment.\n\n",
+        one_line(&workflow.name)
+    );
+
+    out.push_str("/// Navigation states, one per context.\n");
+    if default_state.is_some() {
+        out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]\n");
+    } else {
+        out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    }
+    out.push_str("pub enum State {\n");
+    for (id, ident) in &states {
+        out.push_str(&format!(
+            "    /// {}\n",
+            one_line(&workflow.contexts[*id].name)
+        ));
+        if Some(ident) == default_state {
+            out.push_str("    #[default]\n");
+        }
+        out.push_str(&format!("    {},\n", ident));
+    }
+    if !externals.is_empty() {
+        out.push_str("    /// A context in another workflow.\n");
+        out.push_str("    External(ExternalTarget),\n");
+    }
+    out.push_str("}\n\n");
+
+    if !externals.is_empty() {
+        out.push_str("/// Contexts in other workflows this flow leads to.\n");
+        out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+        out.push_str("pub enum ExternalTarget {\n");
+        for (ident, doc) in externals.values() {
+            out.push_str(&format!("    /// {}\n    {},\n", doc, ident));
+        }
+        out.push_str("}\n\n");
+    }
+
+    out.push_str("/// Navigation events, one per edge label.\n");
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    out.push_str("pub enum Event {\n");
+    for (ident, doc) in events.values() {
+        out.push_str(&format!("    /// {}\n    {},\n", doc, ident));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("/// The state `event` leads to from `state`, if the flow has such an edge.\n");
+    if transitions.is_empty() {
+        out.push_str("pub fn transition(_state: State, event: Event) -> Option<State> {\n");
+        out.push_str("    match event {}\n}\n");
+    } else {
+        out.push_str("pub fn transition(state: State, event: Event) -> Option<State> {\n");
+        out.push_str("    match (state, event) {\n");
+        for ((source, event), edges) in &transitions {
+            let pattern = format!("(State::{}, Event::{})", source, event);
+            let (first, rest) = edges.split_first().expect("non-empty");
+            let arm = format!("        {} => Some({}),", pattern, first.target);
+            // Long arms get a block, as rustfmt would write them.
+            if first.condition.is_none() && rest.is_empty() && arm.len() <= 100 {
+                out.push_str(&arm);
+                out.push('\n');
+                continue;
+            }
+            out.push_str(&format!("        {} => {{\n", pattern));
+            if let Some(condition) = &first.condition {
+                out.push_str(&format!("            // TODO: only when `{}`\n", condition));
+            }
+            for other in rest {
+                match &other.condition {
+                    Some(condition) => out.push_str(&format!(
+                        "            // TODO: or {} when `{}`\n",
+                        other.target, condition
+                    )),
+                    None => out.push_str(&format!("            // TODO: or {}\n", other.target)),
+                }
+            }
+            out.push_str(&format!("            Some({})\n        }}\n", first.target));
+        }
+        let covered = externals.is_empty() && transitions.len() == states.len() * events.len();
+        if !covered {
+            out.push_str("        _ => None,\n");
+        }
+        out.push_str("    }\n}\n");
+    }
+
+    let known: Vec<String> = transitions
+        .iter()
+        .filter(|(_, edges)| edges.len() == 1 && edges[0].condition.is_none())
+        .take(3)
+        .map(|((source, event), edges)| {
+            format!(
+                "        assert_eq!(\n            transition(State::{}, Event::{}),\n            Some({})\n        );\n",
+                source, event, edges[0].target
+            )
+        })
+        .collect();
+    if options.include_tests && !known.is_empty() {
+        out.push_str("\n#[cfg(test)]\nmod tests {\n    use super::*;\n\n");
+        out.push_str("    #[test]\n    fn test_known_transitions() {\n");
+        for assertion in known {
+            out.push_str(&assertion);
+        }
+        out.push_str("    }\n}\n");
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod fixture;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::navigation::NavigationDesigner;
+    use crate::entity::Context;
+
+    fn checkout() -> (Workflow, FlowCanvas<NavigationNodeData, EdgeData>) {
+        let mut workflow = Workflow::new("Checkout").with_id("checkout");
+        for (id, name) in [
+            ("cart", "Cart"),
+            ("shipping", "Shipping address"),
+            ("payment", "Payment"),
+            ("done", "Order confirmed!"),
+            ("saved", "cart"),
+        ] {
+            workflow.add_context(Context::new(name).with_id(id));
+        }
+        workflow.default_context = Some("cart".to_string());
+        let mut account = Workflow::new("Account").with_id("account");
+        account.add_context(Context::new("Billing settings").with_id("billing"));
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow, &account]);
+        let mut canvas = designer.canvas;
+        let guarded = |condition: &str| EdgeData {
+            metadata: IndexMap::from([(CONDITION_KEY.to_string(), condition.into())]),
+            ..Default::default()
+        };
+        for (id, source, target, label, data) in [
+            ("e1", "cart", "shipping", "Checkout", None),
+            ("e2", "shipping", "payment", "Continue", None),
+            ("e3", "shipping", "cart", "back", None),
+            ("e4", "payment", "shipping", "back", None),
+            (
+                "e5",
+                "payment",
+                "done",
+                "Pay",
+                Some(guarded("cart.total > 0")),
+            ),
+            (
+                "e6",
+                "payment",
+                "done",
+                "Pay",
+                Some(guarded("promo.covers_total")),
+            ),
+            ("e7", "payment", "billing", "Manage billing", None),
+            ("e8", "cart", "saved", "", None),
+            ("e9", "done", "cart", "OK", None),
+        ] {
+            let mut edge = Edge::new(id, source, target);
+            if !label.is_empty() {
+                edge = edge.with_label(label);
+            }
+            edge.data = data;
+            canvas.add_edge(edge);
+        }
+        (workflow, canvas)
+    }
+
+    #[test]
+    fn test_matches_compiled_fixture() {
+        let (workflow, canvas) = checkout();
+        let code = flow_to_state_machine(&workflow, &canvas, &StateMachineOptions::default());
+        assert_eq!(code, include_str!("state_machine/fixture.rs"));
+
+        // The fixture is compiled into the test build; check it behaves.
+        use fixture::{Event, ExternalTarget, State, transition};
+        assert_eq!(State::default(), State::Cart);
+        assert_eq!(
+            transition(State::Payment, Event::Pay),
+            Some(State::OrderConfirmed)
+        );
+        assert_eq!(
+            transition(State::Payment, Event::ManageBilling),
+            Some(State::External(ExternalTarget::AccountBillingSettings))
+        );
+        assert_eq!(transition(State::Cart, Event::Pay), None);
+    }
+
+    #[test]
+    fn test_identifiers_are_sanitized() {
+        assert_eq!(pascal_case("shipping-address", "State"), "ShippingAddress");
+        assert_eq!(pascal_case("OK", "Event"), "Ok");
+        assert_eq!(pascal_case("404 page", "State"), "State404Page");
+        assert_eq!(pascal_case("Self", "State"), "StateSelf");
+        assert_eq!(pascal_case("¡¿", "Event"), "Event");
+
+        let mut idents = Idents::default();
+        idents.reserve("External");
+        assert_eq!(idents.alloc("cart", "State"), "Cart");
+        assert_eq!(idents.alloc("Cart!", "State"), "Cart2");
+        assert_eq!(idents.alloc("external", "State"), "External2");
+    }
+
+    #[test]
+    fn test_workflow_without_edges() {
+        let mut workflow = Workflow::new("Empty");
+        workflow.add_context(Context::new("Home"));
+        let code = flow_to_state_machine(
+            &workflow,
+            &FlowCanvas::new(),
+            &StateMachineOptions::default(),
+        );
+        assert!(code.contains("pub enum Event {\n}"));
+        assert!(code.contains("    match event {}\n"));
+        assert!(!code.contains("mod tests"));
+    }
+}
@@ -0,0 +1,90 @@
+//! State machine for the "Checkout" workflow.
+//!
+//! Generated from the navigation flow. Guarded transitions are marked
+//! TODO with their condition as a comment.
+
+/// Navigation states, one per context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum State {
+    /// Cart
+    #[default]
+    Cart,
+    /// Shipping address
+    ShippingAddress,
+    /// Payment
+    Payment,
+    /// Order confirmed!
+    OrderConfirmed,
+    /// cart
+    Cart2,
+    /// A context in another workflow.
+    External(ExternalTarget),
+}
+
+/// Contexts in other workflows this flow leads to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExternalTarget {
+    /// Context "Billing settings" in workflow "Account".
+    AccountBillingSettings,
+}
+
+/// Navigation events, one per edge label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// "Checkout"
+    Checkout,
+    /// "Continue"
+    Continue,
+    /// "back"
+    Back,
+    /// "Pay"
+    Pay,
+    /// "Manage billing"
+    ManageBilling,
+    /// Unlabeled edge.
+    GoToCart2,
+    /// "OK"
+    Ok,
+}
+
+/// The state `event` leads to from `state`, if the flow has such an edge.
+pub fn transition(state: State, event: Event) -> Option<State> {
+    match (state, event) {
+        (State::Cart, Event::Checkout) => Some(State::ShippingAddress),
+        (State::ShippingAddress, Event::Continue) => Some(State::Payment),
+        (State::ShippingAddress, Event::Back) => Some(State::Cart),
+        (State::Payment, Event::Back) => Some(State::ShippingAddress),
+        (State::Payment, Event::Pay) => {
+            // TODO: only when `cart.total > 0`
+            // TODO: or State::OrderConfirmed when `promo.covers_total`
+            Some(State::OrderConfirmed)
+        }
+        (State::Payment, Event::ManageBilling) => {
+            Some(State::External(ExternalTarget::AccountBillingSettings))
+        }
+        (State::Cart, Event::GoToCart2) => Some(State::Cart2),
+        (State::OrderConfirmed, Event::Ok) => Some(State::Cart),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_transitions() {
+        assert_eq!(
+            transition(State::Cart, Event::Checkout),
+            Some(State::ShippingAddress)
+        );
+        assert_eq!(
+            transition(State::ShippingAddress, Event::Continue),
+            Some(State::Payment)
+        );
+        assert_eq!(
+            transition(State::ShippingAddress, Event::Back),
+            Some(State::Cart)
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Static HTML style guide export.
+//!
+//! The generated page is self-contained: every exported theme's variables
+//! are scoped under `[data-theme="..."]`, swatches and specimens only use
+//! `var(...)`, and a small inline script switches the `data-theme`
+//! attribute and remembers the choice in `localStorage`.
+
+use thiserror::Error;
+
+use crate::designer::css::{
+    CategoryPresentations, DesignTokens, ThemeSet, TokenCategory, TokenValue, path_to_css_var,
+};
+
+/// `localStorage` key the theme switcher persists its choice under.
+pub const THEME_STORAGE_KEY: &str = "rsc-style-guide-theme";
+
+/// Style guide export error types.
+#[derive(Debug, Error, PartialEq)]
+pub enum StyleGuideError {
+    /// Filtering left no themes to export.
+    #[error("No themes selected for export")]
+    NoThemes,
+
+    /// The requested default theme is not exported.
+    #[error("Default theme '{0}' is not included in the export")]
+    UnknownDefault(String),
+
+    /// The theme the sections are built from is not exported.
+    #[error("Section theme '{0}' is not included in the export")]
+    UnknownSectionTheme(String),
+}
+
+/// Style guide export options.
+#[derive(Debug, Clone)]
+pub struct StyleGuideOptions {
+    /// Page title.
+    pub title: String,
+    /// Themes to export; `None` exports all.
+    pub include: Option<Vec<String>>,
+    /// Themes to leave out.
+    pub exclude: Vec<String>,
+    /// Theme selected when no choice is persisted.
+    pub default_theme: Option<String>,
+    /// Theme whose tokens the swatch and specimen sections list; `None`
+    /// uses the default theme.
+    pub section_theme: Option<String>,
+    /// Category icons and colors for section headers.
+    pub category_presentation: CategoryPresentations,
+}
+
+impl Default for StyleGuideOptions {
+    fn default() -> Self {
+        Self {
+            title: "Style Guide".to_string(),
+            include: None,
+            exclude: Vec::new(),
+            default_theme: None,
+            section_theme: None,
+            category_presentation: CategoryPresentations::default(),
+        }
+    }
+}
+
+impl StyleGuideOptions {
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Only export the given themes.
+    pub fn include<I, S>(mut self, themes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include = Some(themes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Leave a theme out of the export.
+    pub fn exclude(mut self, theme: impl Into<String>) -> Self {
+        self.exclude.push(theme.into());
+        self
+    }
+
+    pub fn with_default_theme(mut self, theme: impl Into<String>) -> Self {
+        self.default_theme = Some(theme.into());
+        self
+    }
+
+    /// Build the sections from another exported theme's tokens.
+    pub fn with_section_theme(mut self, theme: impl Into<String>) -> Self {
+        self.section_theme = Some(theme.into());
+        self
+    }
+
+    /// Use the project's category icons and colors in section headers.
+    pub fn with_category_presentation(mut self, presentation: CategoryPresentations) -> Self {
+        self.category_presentation = presentation;
+        self
+    }
+}
+
+/// Exports a [`ThemeSet`] as a single HTML page.
+#[derive(Debug, Clone, Default)]
+pub struct StyleGuideExporter {
+    pub options: StyleGuideOptions,
+}
+
+impl StyleGuideExporter {
+    pub fn new(options: StyleGuideOptions) -> Self {
+        Self { options }
+    }
+
+    /// Names of the themes that will be exported, in theme set order.
+    pub fn selected_themes<'a>(&self, themes: &'a ThemeSet) -> Vec<&'a str> {
+        themes
+            .themes
+            .keys()
+            .map(String::as_str)
+            .filter(|name| {
+                self.options
+                    .include
+                    .as_ref()
+                    .is_none_or(|include| include.iter().any(|i| i == name))
+            })
+            .filter(|name| !self.options.exclude.iter().any(|e| e == name))
+            .collect()
+    }
+
+    /// Render the style guide.
+    pub fn export(&self, themes: &ThemeSet) -> Result<String, StyleGuideError> {
+        let selected = self.selected_themes(themes);
+        if selected.is_empty() {
+            return Err(StyleGuideError::NoThemes);
+        }
+        let default = match &self.options.default_theme {
+            Some(name) if selected.contains(&name.as_str()) => name.as_str(),
+            Some(name) => return Err(StyleGuideError::UnknownDefault(name.clone())),
+            None => themes
+                .default_theme()
+                .filter(|name| selected.contains(name))
+                .unwrap_or(selected[0]),
+        };
+        let section_theme = match &self.options.section_theme {
+            Some(name) if selected.contains(&name.as_str()) => name.as_str(),
+            Some(name) => return Err(StyleGuideError::UnknownSectionTheme(name.clone())),
+            None => default,
+        };
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n");
+        html.push_str(&format!(
+            "<html lang=\"en\" data-theme=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n",
+            escape(default)
+        ));
+        html.push_str(&format!("<title>{}</title>\n", escape(&self.options.title)));
+        html.push_str("<style>\n");
+        for name in &selected {
+            html.push_str(&theme_block(name, &themes.themes[*name]));
+        }
+        html.push_str(BASE_CSS);
+        html.push_str("</style>\n</head>\n<body>\n");
+
+        html.push_str(&format!(
+            "<header>\n<h1>{}</h1>\n",
+            escape(&self.options.title)
+        ));
+        html.push_str("<label>Theme <select id=\"theme-switcher\">\n");
+        for name in &selected {
+            html.push_str(&format!(
+                "<option value=\"{0}\">{0}</option>\n",
+                escape(name)
+            ));
+        }
+        html.push_str("</select></label>\n</header>\n");
+
+        // Sections list the section theme's tokens; switching theme only
+        // changes the variable values.
+        html.push_str(&sections(
+            &themes.themes[section_theme],
+            &self.options.category_presentation,
+        ));
+
+        html.push_str(&switcher_script(&selected, default));
+        html.push_str("</body>\n</html>\n");
+        Ok(html)
+    }
+}
+
+pub(super) const BASE_CSS: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem; \
+background: var(--color-background, #fff); color: var(--color-text, #111); }
+header { display: flex; justify-content: space-between; align-items: center; }
+.swatches, .specimens { display: flex; flex-wrap: wrap; gap: 1rem; }
+.swatch { width: 6rem; height: 4rem; border-radius: 4px; border: 1px solid rgba(0,0,0,.1); }
+.specimen { display: flex; flex-direction: column; gap: .25rem; }
+";
+
+/// Custom properties for one theme, scoped under its data attribute.
+pub(super) fn theme_block(name: &str, tokens: &DesignTokens) -> String {
+    let mut css = format!("[data-theme=\"{}\"] {{\n", escape(name));
+    for (var, value) in tokens.css_variables(false) {
+        css.push_str(&format!("  {}: {};\n", var, value));
+    }
+    css.push_str("}\n");
+    css
+}
+
+/// Swatch and specimen sections referencing tokens only through variables.
+fn sections(tokens: &DesignTokens, presentation: &CategoryPresentations) -> String {
+    let mut html = String::new();
+
+    let vars = |prefix: &str, names: Vec<&String>| -> Vec<String> {
+        names
+            .into_iter()
+            .filter_map(|n| path_to_css_var(&format!("{}.{}", prefix, n)))
+            .collect()
+    };
+
+    let colors: Vec<String> = tokens
+        .colors
+        .iter()
+        .flat_map(|(name, value)| {
+            let base = path_to_css_var(&format!("colors.{}", name)).unwrap_or_default();
+            match value {
+                TokenValue::Scale(steps) => steps
+                    .keys()
+                    .map(|step| format!("{}-{}", base, step))
+                    .collect(),
+                _ => vec![base],
+            }
+        })
+        .collect();
+    if !colors.is_empty() {
+        html.push_str(&section_header(TokenCategory::Colors, presentation));
+        html.push_str("<div class=\"swatches\">\n");
+        for var in colors {
+            html.push_str(&format!(
+                "<div class=\"specimen\"><div class=\"swatch\" style=\"background: var({0})\"></div><code>{0}</code></div>\n",
+                var
+            ));
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    let sizes = vars("typography.sizes", tokens.typography.sizes.keys().collect());
+    if !sizes.is_empty() {
+        html.push_str(&section_header(TokenCategory::Typography, presentation));
+        html.push_str("<div class=\"specimens\">\n");
+        for var in sizes {
+            html.push_str(&format!(
+                "<div class=\"specimen\"><span style=\"font-size: var({0})\">Aa</span><code>{0}</code></div>\n",
+                var
+            ));
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    let spacing = vars("spacing", tokens.spacing.keys().collect());
+    if !spacing.is_empty() {
+        html.push_str(&section_header(TokenCategory::Spacing, presentation));
+        html.push_str("<div class=\"specimens\">\n");
+        for var in spacing {
+            html.push_str(&format!(
+                "<div class=\"specimen\"><div style=\"width: var({0}); height: 1rem; background: currentColor\"></div><code>{0}</code></div>\n",
+                var
+            ));
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    html
+}
+
+/// Section opening with the category's sidebar icon.
+fn section_header(category: TokenCategory, presentation: &CategoryPresentations) -> String {
+    let presentation = presentation.resolve(category.key());
+    format!(
+        "<section>\n<h2><span class=\"icon\" data-icon=\"{}\" style=\"color: {}\"></span>{}</h2>\n",
+        escape(&presentation.icon),
+        escape(&presentation.accent),
+        category.label()
+    )
+}
+
+/// Inline theme switcher. Only exported themes are accepted from storage.
+pub(super) fn switcher_script(themes: &[&str], default: &str) -> String {
+    let list = themes
+        .iter()
+        .map(|t| format!("\"{}\"", escape_js(t)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        r#"<script>
+(function () {{
+  var themes = [{list}];
+  var key = "{key}";
+  var root = document.documentElement;
+  var select = document.getElementById("theme-switcher");
+  var saved = null;
+  try {{ saved = localStorage.getItem(key); }} catch (e) {{}}
+  var theme = themes.indexOf(saved) >= 0 ? saved : "{default}";
+  root.setAttribute("data-theme", theme);
+  select.value = theme;
+  select.addEventListener("change", function () {{
+    root.setAttribute("data-theme", select.value);
+    try {{ localStorage.setItem(key, select.value); }} catch (e) {{}}
+  }});
+}})();
+</script>
+"#,
+        list = list,
+        key = THEME_STORAGE_KEY,
+        default = escape_js(default),
+    )
+}
+
+pub(super) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_js(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::CategoryPresentation;
+
+    fn theme_set() -> ThemeSet {
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Adaptive {
+                light: "#3b82f6".to_string(),
+                dark: "#60a5fa".to_string(),
+            },
+        );
+        tokens
+            .spacing
+            .insert("md".to_string(), TokenValue::Simple("16px".to_string()));
+
+        let mut brand = DesignTokens::default();
+        brand.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#e11d48".to_string()),
+        );
+        brand.colors.insert(
+            "accent".to_string(),
+            TokenValue::Simple("#f59e0b".to_string()),
+        );
+
+        let mut set = ThemeSet::from_adaptive(&tokens);
+        set.themes.insert("brand".to_string(), brand);
+        set
+    }
+
+    #[test]
+    fn test_scoped_block_per_theme() {
+        let html = StyleGuideExporter::default().export(&theme_set()).unwrap();
+
+        assert!(html.contains("[data-theme=\"light\"] {\n  --color-primary: #3b82f6;"));
+        assert!(html.contains("[data-theme=\"dark\"] {\n  --color-primary: #60a5fa;"));
+        assert!(html.contains("[data-theme=\"brand\"] {\n  --color-primary: #e11d48;"));
+        assert!(html.contains("<html lang=\"en\" data-theme=\"light\">"));
+        assert!(html.contains("style=\"background: var(--color-primary)\""));
+        assert!(html.contains(THEME_STORAGE_KEY));
+        assert!(html.contains("<h2><span class=\"icon\" data-icon=\"palette\""));
+    }
+
+    #[test]
+    fn test_section_headers_use_category_overrides() {
+        let options = StyleGuideOptions::default().with_category_presentation(
+            CategoryPresentations::new()
+                .with_override("spacing", CategoryPresentation::new("arrows", "#000")),
+        );
+        let html = StyleGuideExporter::new(options)
+            .export(&theme_set())
+            .unwrap();
+
+        assert!(html.contains("data-icon=\"arrows\" style=\"color: #000\"></span>Spacing</h2>"));
+        assert!(html.contains("data-icon=\"palette\""));
+    }
+
+    #[test]
+    fn test_switcher_only_lists_included_themes() {
+        let options = StyleGuideOptions::default()
+            .exclude("brand")
+            .with_default_theme("dark");
+        let html = StyleGuideExporter::new(options)
+            .export(&theme_set())
+            .unwrap();
+
+        assert!(html.contains("var themes = [\"light\", \"dark\"];"));
+        assert!(html.contains("<option value=\"dark\">"));
+        assert!(!html.contains("brand"));
+        assert!(html.contains("data-theme=\"dark\">"));
+    }
+
+    #[test]
+    fn test_sections_from_non_default_theme() {
+        let html = StyleGuideExporter::default().export(&theme_set()).unwrap();
+        assert!(!html.contains("var(--color-accent)"));
+        assert!(html.contains("var(--spacing-md)"));
+
+        let options = StyleGuideOptions::default().with_section_theme("brand");
+        let html = StyleGuideExporter::new(options)
+            .export(&theme_set())
+            .unwrap();
+
+        assert!(html.contains("style=\"background: var(--color-accent)\""));
+        assert!(!html.contains("var(--spacing-md)"));
+        assert!(html.contains("<html lang=\"en\" data-theme=\"light\">"));
+    }
+
+    #[test]
+    fn test_default_must_be_exported() {
+        let options = StyleGuideOptions::default()
+            .include(["light"])
+            .with_default_theme("dark");
+        assert_eq!(
+            StyleGuideExporter::new(options).export(&theme_set()),
+            Err(StyleGuideError::UnknownDefault("dark".to_string()))
+        );
+
+        let options = StyleGuideOptions::default()
+            .exclude("brand")
+            .with_section_theme("brand");
+        assert_eq!(
+            StyleGuideExporter::new(options).export(&theme_set()),
+            Err(StyleGuideError::UnknownSectionTheme("brand".to_string()))
+        );
+
+        let options = StyleGuideOptions::default().include(["sepia"]);
+        assert_eq!(
+            StyleGuideExporter::new(options).export(&theme_set()),
+            Err(StyleGuideError::NoThemes)
+        );
+    }
+}
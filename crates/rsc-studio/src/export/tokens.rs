@@ -0,0 +1,356 @@
+//! Design token export formats.
+//!
+//! Besides CSS variables and raw JSON, tokens can be written as SCSS
+//! variables and as a Tailwind `theme.extend` snippet. Scale tokens become
+//! nested maps/objects. Adaptive tokens keep both variants: SCSS gets a
+//! `light`/`dark` map, Tailwind points at the CSS variable so the theme
+//! switch stays in CSS. Tokens Studio JSON is handled in [`figma`] and
+//! Style Dictionary properties in [`style_dictionary`].
+
+pub mod figma;
+pub mod style_dictionary;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::designer::css::{DesignTokens, TokenValue, path_to_css_var};
+
+/// Token export format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// `:root` custom properties, with dark values under `[data-theme="dark"]`.
+    #[default]
+    CssVariables,
+    /// The token file as JSON.
+    Json,
+    /// SCSS variables and maps.
+    Scss,
+    /// Tailwind config with the tokens under `theme.extend`.
+    Tailwind,
+    /// Tokens Studio (Figma Tokens plugin) JSON.
+    FigmaTokens,
+    /// Style Dictionary properties with the default options.
+    StyleDictionary,
+}
+
+impl ExportFormat {
+    /// Conventional file extension.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::CssVariables => "css",
+            ExportFormat::Json => "json",
+            ExportFormat::Scss => "scss",
+            ExportFormat::Tailwind => "js",
+            ExportFormat::FigmaTokens => "json",
+            ExportFormat::StyleDictionary => "json",
+        }
+    }
+}
+
+/// Token export error types.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// The tokens could not be serialized.
+    #[error("Serialize error: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// Tailwind `theme.extend` keys for the exported token categories.
+const TAILWIND_KEYS: [(&str, &str); 6] = [
+    ("colors", "colors"),
+    ("spacing", "spacing"),
+    ("radius", "borderRadius"),
+    ("shadows", "boxShadow"),
+    ("typography.sizes", "fontSize"),
+    ("typography.weights", "fontWeight"),
+];
+
+impl DesignTokens {
+    /// Export the tokens in `format`.
+    pub fn export(&self, format: ExportFormat) -> Result<String, ExportError> {
+        match format {
+            ExportFormat::CssVariables => Ok(css_variables(self)),
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ExportFormat::Scss => Ok(scss(self)),
+            ExportFormat::Tailwind => tailwind(self),
+            ExportFormat::FigmaTokens => figma::figma_tokens(self),
+            ExportFormat::StyleDictionary => self.export_style_dictionary(&Default::default()),
+        }
+    }
+}
+
+fn css_variables(tokens: &DesignTokens) -> String {
+    let mut css = String::from(":root {\n");
+    for (var, value) in tokens.css_variables(false) {
+        css.push_str(&format!("  {}: {};\n", var, value));
+    }
+    css.push_str("}\n");
+
+    let dark: Vec<(String, String)> = tokens
+        .categories()
+        .into_iter()
+        .flat_map(|(prefix, map)| {
+            map.iter().filter_map(move |(name, value)| match value {
+                TokenValue::Adaptive { dark, .. } => Some((
+                    path_to_css_var(&format!("{}.{}", prefix, name))?,
+                    dark.clone(),
+                )),
+                _ => None,
+            })
+        })
+        .collect();
+    if !dark.is_empty() {
+        css.push_str("\n[data-theme=\"dark\"] {\n");
+        for (var, value) in dark {
+            css.push_str(&format!("  {}: {};\n", var, value));
+        }
+        css.push_str("}\n");
+    }
+    css
+}
+
+/// Map values containing commas (shadow lists, font stacks) are wrapped
+/// in parentheses so SCSS doesn't split them into map entries.
+fn scss_map_value(value: &str) -> String {
+    if value.contains(',') {
+        format!("({})", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn scss(tokens: &DesignTokens) -> String {
+    let mut out = String::new();
+    for (prefix, map) in tokens.categories() {
+        for (name, value) in map {
+            let Some(var) = path_to_css_var(&format!("{}.{}", prefix, name)) else {
+                continue;
+            };
+            let var = var.trim_start_matches('-');
+            let entries: Vec<(&str, &str)> = match value {
+                TokenValue::Simple(v) => {
+                    out.push_str(&format!("${}: {};\n", var, v));
+                    continue;
+                }
+                TokenValue::Adaptive { light, dark } => vec![("light", light), ("dark", dark)],
+                TokenValue::Scale(steps) => steps
+                    .iter()
+                    .map(|(step, v)| (step.as_str(), v.as_str()))
+                    .collect(),
+            };
+            out.push_str(&format!("${}: (\n", var));
+            for (key, v) in entries {
+                out.push_str(&format!("  \"{}\": {},\n", key, scss_map_value(v)));
+            }
+            out.push_str(");\n");
+        }
+    }
+    out
+}
+
+fn tailwind(tokens: &DesignTokens) -> Result<String, ExportError> {
+    let categories = tokens.categories();
+    let mut extend = Map::new();
+    for (prefix, key) in TAILWIND_KEYS {
+        let Some((_, map)) = categories.iter().find(|(p, _)| *p == prefix) else {
+            continue;
+        };
+        if map.is_empty() {
+            continue;
+        }
+        let mut entries = Map::new();
+        for (name, value) in map.iter() {
+            let entry = match value {
+                TokenValue::Simple(v) => Value::from(v.as_str()),
+                TokenValue::Adaptive { .. } => {
+                    match path_to_css_var(&format!("{}.{}", prefix, name)) {
+                        Some(var) => Value::from(format!("var({})", var)),
+                        None => continue,
+                    }
+                }
+                TokenValue::Scale(steps) => Value::Object(
+                    steps
+                        .iter()
+                        .map(|(step, v)| (step.clone(), Value::from(v.as_str())))
+                        .collect(),
+                ),
+            };
+            entries.insert(name.clone(), entry);
+        }
+        extend.insert(key.to_string(), Value::Object(entries));
+    }
+
+    let mut theme = Map::new();
+    theme.insert("extend".to_string(), Value::Object(extend));
+    let mut config = Map::new();
+    config.insert("theme".to_string(), Value::Object(theme));
+    Ok(format!(
+        "/** @type {{import('tailwindcss').Config}} */\nmodule.exports = {};\n",
+        serde_json::to_string_pretty(&Value::Object(config))?
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn tokens() -> DesignTokens {
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        let mut tokens = DesignTokens::default();
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("#3b82f6"));
+        tokens.colors.insert(
+            "surface".to_string(),
+            TokenValue::Adaptive {
+                light: "#ffffff".to_string(),
+                dark: "#0f172a".to_string(),
+            },
+        );
+        let mut blue = IndexMap::new();
+        blue.insert("100".to_string(), "#dbeafe".to_string());
+        blue.insert("500".to_string(), "var(--color-primary)".to_string());
+        tokens
+            .colors
+            .insert("blue".to_string(), TokenValue::Scale(blue));
+        tokens.spacing.insert("md".to_string(), simple("16px"));
+        tokens.radius.insert("lg".to_string(), simple("8px"));
+        tokens.shadows.insert(
+            "card".to_string(),
+            simple("0 1px 2px rgba(0, 0, 0, 0.1), 0 4px 8px rgba(0, 0, 0, 0.1)"),
+        );
+        let mut elevation = IndexMap::new();
+        elevation.insert(
+            "1".to_string(),
+            "0 1px 2px #0001, 0 1px 1px #0001".to_string(),
+        );
+        tokens
+            .shadows
+            .insert("elevation".to_string(), TokenValue::Scale(elevation));
+        tokens
+            .typography
+            .sizes
+            .insert("base".to_string(), simple("1rem"));
+        tokens
+            .typography
+            .weights
+            .insert("bold".to_string(), simple("700"));
+        tokens
+    }
+
+    /// Read back SCSS written by [`scss`].
+    fn parse_scss(scss: &str, paths: &IndexMap<String, String>) -> DesignTokens {
+        let mut parsed = DesignTokens::default();
+        let mut lines = scss.lines();
+        while let Some(line) = lines.next() {
+            let (var, rest) = line.trim_start_matches('$').split_once(": ").unwrap();
+            let value = if rest == "(" {
+                let mut entries = IndexMap::new();
+                for entry in lines.by_ref().take_while(|l| *l != ");") {
+                    let (key, v) = entry.trim().split_once(": ").unwrap();
+                    let v = v.trim_end_matches(',');
+                    let v = v
+                        .strip_prefix('(')
+                        .and_then(|v| v.strip_suffix(')'))
+                        .unwrap_or(v);
+                    entries.insert(key.trim_matches('"').to_string(), v.to_string());
+                }
+                match (entries.get("light"), entries.get("dark")) {
+                    (Some(light), Some(dark)) if entries.len() == 2 => TokenValue::Adaptive {
+                        light: light.clone(),
+                        dark: dark.clone(),
+                    },
+                    _ => TokenValue::Scale(entries),
+                }
+            } else {
+                TokenValue::Simple(rest.trim_end_matches(';').to_string())
+            };
+            let path = &paths[var];
+            let (prefix, name) = path.rsplit_once('.').unwrap();
+            for (p, map) in parsed.categories_mut() {
+                if p == prefix {
+                    map.insert(name.to_string(), value.clone());
+                }
+            }
+        }
+        parsed
+    }
+
+    #[test]
+    fn test_scss_round_trip() {
+        let tokens = tokens();
+        let scss = tokens.export(ExportFormat::Scss).unwrap();
+
+        assert!(scss.contains("$color-primary: #3b82f6;\n"));
+        assert!(
+            scss.contains("$color-surface: (\n  \"light\": #ffffff,\n  \"dark\": #0f172a,\n);")
+        );
+        assert!(scss.contains("  \"1\": (0 1px 2px #0001, 0 1px 1px #0001),\n"));
+
+        let paths: IndexMap<String, String> = tokens
+            .all_paths()
+            .into_iter()
+            .map(|p| (path_to_css_var(&p).unwrap()[2..].to_string(), p))
+            .collect();
+        let parsed = parse_scss(&scss, &paths);
+        for ((_, original), (_, read)) in tokens.categories().into_iter().zip(parsed.categories()) {
+            assert_eq!(original, read);
+        }
+    }
+
+    #[test]
+    fn test_tailwind_round_trip() {
+        let tokens = tokens();
+        let js = tokens.export(ExportFormat::Tailwind).unwrap();
+
+        let json = js
+            .split_once("module.exports = ")
+            .and_then(|(_, rest)| rest.trim_end().strip_suffix(';'))
+            .unwrap();
+        let config: Value = serde_json::from_str(json).unwrap();
+        let extend = &config["theme"]["extend"];
+
+        assert_eq!(extend["colors"]["primary"], "#3b82f6");
+        assert_eq!(extend["colors"]["surface"], "var(--color-surface)");
+        assert_eq!(extend["colors"]["blue"]["500"], "var(--color-primary)");
+        assert_eq!(extend["borderRadius"]["lg"], "8px");
+        assert_eq!(
+            extend["boxShadow"]["elevation"]["1"],
+            "0 1px 2px #0001, 0 1px 1px #0001"
+        );
+        assert_eq!(extend["fontSize"]["base"], "1rem");
+        assert_eq!(extend["fontWeight"]["bold"], "700");
+        assert_eq!(extend["spacing"]["md"], "16px");
+
+        // Everything except the adaptive token reads back unchanged.
+        for (prefix, key) in TAILWIND_KEYS {
+            let (_, map) = tokens
+                .categories()
+                .into_iter()
+                .find(|(p, _)| *p == prefix)
+                .unwrap();
+            for (name, value) in map {
+                let read: TokenValue = serde_json::from_value(extend[key][name].clone()).unwrap();
+                if !matches!(value, TokenValue::Adaptive { .. }) {
+                    assert_eq!(&read, value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_css_and_json() {
+        let tokens = tokens();
+
+        let css = tokens.export(ExportFormat::CssVariables).unwrap();
+        assert!(css.starts_with(":root {\n  --color-primary: #3b82f6;\n"));
+        assert!(css.contains("  --color-blue-100: #dbeafe;\n"));
+        assert!(css.ends_with("[data-theme=\"dark\"] {\n  --color-surface: #0f172a;\n}\n"));
+
+        let json = tokens.export(ExportFormat::Json).unwrap();
+        let read = DesignTokens::from_json(&json).unwrap();
+        assert_eq!(read.colors, tokens.colors);
+        assert_eq!(read.shadows, tokens.shadows);
+    }
+}
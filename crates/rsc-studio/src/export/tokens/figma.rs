@@ -0,0 +1,710 @@
+//! Tokens Studio (Figma Tokens plugin) JSON.
+//!
+//! Tokens go into a `global` token set, grouped the way the plugin names
+//! them (`borderRadius`, `boxShadow`, `fontSizes`, ...). Adaptive tokens are
+//! split over a `light` and a `dark` set, with a theme for each. References
+//! are written as `{colors.primary}` and read back as `var(--color-primary)`.
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value, json};
+
+use super::ExportError;
+use crate::designer::css::extract::var_calls;
+use crate::designer::css::{DesignTokens, TokenImportError, TokenValue, path_to_css_var};
+
+/// Token set for values shared by both color schemes.
+pub const GLOBAL_SET: &str = "global";
+/// Token set for the light values of adaptive tokens.
+pub const LIGHT_SET: &str = "light";
+/// Token set for the dark values of adaptive tokens.
+pub const DARK_SET: &str = "dark";
+
+/// Category prefix, plugin group and plugin token type, in category order.
+const GROUPS: [(&str, &str, &str); 9] = [
+    ("colors", "colors", "color"),
+    ("spacing", "spacing", "spacing"),
+    ("radius", "borderRadius", "borderRadius"),
+    ("shadows", "boxShadow", "boxShadow"),
+    ("typography.fonts", "fontFamilies", "fontFamilies"),
+    ("typography.sizes", "fontSizes", "fontSizes"),
+    ("typography.weights", "fontWeights", "fontWeights"),
+    ("transitions", "transitions", "other"),
+    ("z-index", "zIndex", "other"),
+];
+
+/// Further plugin token types and the category they import into.
+const TYPE_ALIASES: [(&str, &str); 3] = [
+    ("dimension", "spacing"),
+    ("sizing", "spacing"),
+    ("fontSize", "typography.sizes"),
+];
+
+/// Parts of a composite typography token and their categories.
+const TYPOGRAPHY_PARTS: [(&str, &str); 3] = [
+    ("fontFamily", "typography.fonts"),
+    ("fontSize", "typography.sizes"),
+    ("fontWeight", "typography.weights"),
+];
+
+/// Categories whose unitless numbers are pixels.
+const PIXEL_CATEGORIES: [&str; 3] = ["spacing", "radius", "typography.sizes"];
+
+pub(super) fn figma_tokens(tokens: &DesignTokens) -> Result<String, ExportError> {
+    let refs = references(tokens);
+    let mut sets: IndexMap<&str, Map<String, Value>> = [GLOBAL_SET, LIGHT_SET, DARK_SET]
+        .into_iter()
+        .map(|set| (set, Map::new()))
+        .collect();
+    for ((_, map), (_, group, ty)) in tokens.categories().into_iter().zip(GROUPS) {
+        for (name, value) in map {
+            let token = |v: &str| figma_token(v, ty, &refs);
+            match value {
+                TokenValue::Simple(v) => insert(&mut sets[GLOBAL_SET], group, name, token(v)),
+                TokenValue::Adaptive { light, dark } => {
+                    insert(&mut sets[LIGHT_SET], group, name, token(light));
+                    insert(&mut sets[DARK_SET], group, name, token(dark));
+                }
+                TokenValue::Scale(steps) => {
+                    let steps = steps.iter().map(|(s, v)| (s.clone(), token(v))).collect();
+                    insert(&mut sets[GLOBAL_SET], group, name, Value::Object(steps));
+                }
+            }
+        }
+    }
+    let composites = typography_composites(tokens);
+    if !composites.is_empty() {
+        sets[GLOBAL_SET].insert("typography".to_string(), Value::Object(composites));
+    }
+
+    let adaptive = !sets[LIGHT_SET].is_empty();
+    sets.retain(|set, _| *set == GLOBAL_SET || adaptive);
+    let order: Vec<&str> = sets.keys().copied().collect();
+    let mut doc = Map::new();
+    doc.insert("$metadata".to_string(), json!({ "tokenSetOrder": order }));
+    if adaptive {
+        let theme = |set: &str, name: &str| {
+            json!({
+                "id": set,
+                "name": name,
+                "selectedTokenSets": { GLOBAL_SET: "source", set: "enabled" },
+            })
+        };
+        doc.insert(
+            "$themes".to_string(),
+            json!([theme(LIGHT_SET, "Light"), theme(DARK_SET, "Dark")]),
+        );
+    }
+    for (set, tokens) in sets {
+        doc.insert(set.to_string(), Value::Object(tokens));
+    }
+    Ok(serde_json::to_string_pretty(&Value::Object(doc))?)
+}
+
+fn insert(set: &mut Map<String, Value>, group: &str, name: &str, token: Value) {
+    let group = set
+        .entry(group)
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(group) = group {
+        group.insert(name.to_string(), token);
+    }
+}
+
+fn figma_token(value: &str, ty: &str, refs: &IndexMap<String, String>) -> Value {
+    let value = to_figma_refs(value, refs);
+    let value = match ty {
+        "boxShadow" => parse_shadow(&value).unwrap_or(Value::from(value)),
+        _ => Value::from(value),
+    };
+    json!({ "value": value, "type": ty })
+}
+
+/// CSS variable of every token (and scale step) to its plugin reference.
+fn references(tokens: &DesignTokens) -> IndexMap<String, String> {
+    let mut refs = IndexMap::new();
+    for ((prefix, map), (_, group, _)) in tokens.categories().into_iter().zip(GROUPS) {
+        for (name, value) in map {
+            let Some(var) = path_to_css_var(&format!("{}.{}", prefix, name)) else {
+                continue;
+            };
+            match value {
+                TokenValue::Scale(steps) => {
+                    for step in steps.keys() {
+                        refs.insert(
+                            format!("{}-{}", var, step),
+                            format!("{{{}.{}.{}}}", group, name, step),
+                        );
+                    }
+                }
+                _ => {
+                    refs.insert(var, format!("{{{}.{}}}", group, name));
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// Replace plain `var(--token)` references with plugin references.
+/// References with a fallback or to unknown variables stay as CSS.
+fn to_figma_refs(value: &str, refs: &IndexMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, end, name) in var_calls(value) {
+        let Some(reference) = refs.get(&name) else {
+            continue;
+        };
+        if start < last || value[start..end] != format!("var({})", name) {
+            continue;
+        }
+        out.push_str(&value[last..start]);
+        out.push_str(reference);
+        last = end;
+    }
+    out.push_str(&value[last..]);
+    out
+}
+
+/// Composite typography tokens, one per font size, using the first font
+/// family and the regular (or first) weight.
+fn typography_composites(tokens: &DesignTokens) -> Map<String, Value> {
+    let typography = &tokens.typography;
+    let family = typography.fonts.keys().next();
+    let weight = ["regular", "normal", "400"]
+        .into_iter()
+        .find(|w| typography.weights.contains_key(*w))
+        .map(str::to_string)
+        .or_else(|| typography.weights.keys().next().cloned());
+    let mut composites = Map::new();
+    for (size, value) in &typography.sizes {
+        if matches!(value, TokenValue::Scale(_)) {
+            continue;
+        }
+        let mut parts = Map::new();
+        if let Some(family) = family {
+            parts.insert(
+                "fontFamily".into(),
+                format!("{{fontFamilies.{}}}", family).into(),
+            );
+        }
+        if let Some(weight) = &weight {
+            parts.insert(
+                "fontWeight".into(),
+                format!("{{fontWeights.{}}}", weight).into(),
+            );
+        }
+        parts.insert("fontSize".into(), format!("{{fontSizes.{}}}", size).into());
+        composites.insert(
+            size.clone(),
+            json!({ "value": parts, "type": "typography" }),
+        );
+    }
+    composites
+}
+
+/// Split on `delimiter` outside parentheses and braces.
+fn split_top_level(value: &str, delimiter: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth -= 1,
+            c if depth == 0 && delimiter(c) => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts.into_iter().filter(|p| !p.trim().is_empty()).collect()
+}
+
+fn is_length(part: &str) -> bool {
+    part.trim_start_matches(['-', '+'])
+        .starts_with(|c: char| c.is_ascii_digit() || c == '.')
+}
+
+/// A `box-shadow` value as plugin shadow objects, or `None` if it isn't
+/// plain offsets and a color.
+fn parse_shadow(value: &str) -> Option<Value> {
+    let mut layers = Vec::new();
+    for layer in split_top_level(value, |c| c == ',') {
+        let mut parts = split_top_level(layer.trim(), char::is_whitespace);
+        let inset = parts.first() == Some(&"inset");
+        if inset {
+            parts.remove(0);
+        }
+        let lengths: Vec<&str> = parts.iter().copied().take_while(|p| is_length(p)).collect();
+        let [color] = parts[lengths.len()..] else {
+            return None;
+        };
+        // The plugin can't resolve CSS variables in a shadow color.
+        if !(2..=4).contains(&lengths.len()) || color.contains("var(") {
+            return None;
+        }
+        layers.push(json!({
+            "x": lengths[0],
+            "y": lengths[1],
+            "blur": lengths.get(2).unwrap_or(&"0"),
+            "spread": lengths.get(3).unwrap_or(&"0"),
+            "color": color,
+            "type": if inset { "innerShadow" } else { "dropShadow" },
+        }));
+    }
+    match layers.len() {
+        0 => None,
+        1 => layers.pop(),
+        _ => Some(Value::Array(layers)),
+    }
+}
+
+/// A plugin shadow object as `box-shadow` CSS.
+fn shadow_css(layer: &Value) -> Option<String> {
+    let part = |key: &str| match &layer[key] {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    let mut css = Vec::new();
+    if layer["type"] == "innerShadow" {
+        css.push("inset".to_string());
+    }
+    for key in ["x", "y", "blur"] {
+        css.push(pixels(&part(key).unwrap_or_else(|| "0".to_string())));
+    }
+    if let Some(spread) = part("spread").filter(|s| s != "0") {
+        css.push(pixels(&spread));
+    }
+    css.push(from_figma_refs(&part("color")?));
+    Some(css.join(" "))
+}
+
+/// Unitless non-zero numbers are pixels in the plugin.
+fn pixels(value: &str) -> String {
+    match value.parse::<f64>() {
+        Ok(n) if n != 0.0 => format!("{}px", value),
+        _ => value.to_string(),
+    }
+}
+
+/// Category for a plugin group name, or for a token type.
+fn group_prefix(group: &str) -> Option<&'static str> {
+    GROUPS
+        .iter()
+        .find(|(prefix, name, _)| group == *name || group == *prefix)
+        .map(|(prefix, _, _)| *prefix)
+}
+
+fn type_prefix(ty: &str) -> Option<&'static str> {
+    GROUPS
+        .iter()
+        .find(|(_, _, t)| *t == ty && ty != "other")
+        .map(|(prefix, _, _)| *prefix)
+        .or_else(|| {
+            TYPE_ALIASES
+                .iter()
+                .find(|(t, _)| *t == ty)
+                .map(|(_, prefix)| *prefix)
+        })
+}
+
+/// Replace `{group.token}` references with CSS variables. Unknown
+/// references are kept as written.
+fn from_figma_refs(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|i| open + i) else {
+            break;
+        };
+        let reference = &rest[open + 1..close];
+        let var = reference.split_once('.').and_then(|(group, name)| {
+            let prefix = group_prefix(group)?;
+            path_to_css_var(&format!("{}.{}", prefix, name.replace('.', "-")))
+        });
+        out.push_str(&rest[..open]);
+        match var {
+            Some(var) => out.push_str(&format!("var({})", var)),
+            None => out.push_str(&rest[open..=close]),
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A token leaf: name below its group, type and value.
+struct Leaf<'a> {
+    path: Vec<&'a str>,
+    ty: Option<&'a str>,
+    value: &'a Value,
+}
+
+fn leaves<'a>(node: &'a Map<String, Value>, path: Vec<&'a str>, out: &mut Vec<Leaf<'a>>) {
+    let value = node.get("value").or_else(|| node.get("$value"));
+    if let Some(value) = value {
+        let ty = node.get("type").or_else(|| node.get("$type"));
+        out.push(Leaf {
+            path,
+            ty: ty.and_then(Value::as_str),
+            value,
+        });
+        return;
+    }
+    for (key, child) in node {
+        if let Value::Object(child) = child
+            && !key.starts_with('$')
+        {
+            let mut path = path.clone();
+            path.push(key);
+            leaves(child, path, out);
+        }
+    }
+}
+
+fn leaf_value(leaf: &Leaf, prefix: &str) -> Option<String> {
+    let value = match leaf.value {
+        Value::String(s) => from_figma_refs(s),
+        Value::Number(n) if PIXEL_CATEGORIES.contains(&prefix) => pixels(&n.to_string()),
+        Value::Number(n) => n.to_string(),
+        Value::Object(_) if prefix == "shadows" => shadow_css(leaf.value)?,
+        Value::Array(layers) if prefix == "shadows" => layers
+            .iter()
+            .map(shadow_css)
+            .collect::<Option<Vec<_>>>()?
+            .join(", "),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn category<'a>(
+    tokens: &'a mut DesignTokens,
+    prefix: &str,
+) -> &'a mut IndexMap<String, TokenValue> {
+    tokens
+        .categories_mut()
+        .into_iter()
+        .find(|(p, _)| *p == prefix)
+        .map(|(_, map)| map)
+        .expect("known category prefix")
+}
+
+/// Which color scheme a token set holds, from names like `dark` or
+/// `theme/dark`.
+fn scheme(set: &str) -> Option<bool> {
+    let name = set.rsplit('/').next().unwrap_or(set).to_lowercase();
+    match name.as_str() {
+        LIGHT_SET => Some(false),
+        DARK_SET => Some(true),
+        _ => None,
+    }
+}
+
+impl DesignTokens {
+    /// Read a Tokens Studio export.
+    ///
+    /// Sets are applied in `$metadata.tokenSetOrder`, later sets winning.
+    /// Tokens in a `light` and a `dark` set become adaptive. Unknown
+    /// groups are placed by token type and skipped if that fails too;
+    /// composite typography tokens only fill in literal values.
+    pub fn from_figma_tokens(json: &str) -> Result<Self, TokenImportError> {
+        let doc: Value =
+            serde_json::from_str(json).map_err(|e| TokenImportError::ParseError(e.to_string()))?;
+        let Value::Object(doc) = doc else {
+            return Err(TokenImportError::ParseError(
+                "Expected a JSON object of token sets".to_string(),
+            ));
+        };
+
+        // A single-set file has the groups at the top level.
+        let mut sets: Vec<(&str, &Map<String, Value>)> = Vec::new();
+        if doc.keys().any(|key| group_prefix(key).is_some()) {
+            sets.push((GLOBAL_SET, &doc));
+        } else {
+            let order = doc
+                .get("$metadata")
+                .and_then(|m| m.get("tokenSetOrder"))
+                .and_then(Value::as_array);
+            let ordered = order
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .chain(doc.keys().map(String::as_str));
+            for name in ordered {
+                if let Some(Value::Object(set)) = doc.get(name)
+                    && !name.starts_with('$')
+                    && !sets.iter().any(|(n, _)| *n == name)
+                {
+                    sets.push((name, set));
+                }
+            }
+        }
+
+        let mut tokens = DesignTokens::default();
+        let mut schemes: IndexMap<(&str, String), [Option<String>; 2]> = IndexMap::new();
+        let mut composites = Vec::new();
+        for (set, groups) in sets {
+            for (group, node) in groups {
+                let Value::Object(node) = node else {
+                    continue;
+                };
+                let mut found = Vec::new();
+                leaves(node, Vec::new(), &mut found);
+                for leaf in found {
+                    if leaf.ty == Some("typography") {
+                        composites.push(leaf);
+                        continue;
+                    }
+                    let Some(prefix) =
+                        group_prefix(group).or_else(|| leaf.ty.and_then(type_prefix))
+                    else {
+                        continue;
+                    };
+                    let Some(value) = leaf_value(&leaf, prefix) else {
+                        continue;
+                    };
+                    let map = category(&mut tokens, prefix);
+                    match (scheme(set), leaf.path.as_slice()) {
+                        (_, []) => {}
+                        (Some(dark), path) => {
+                            let slot = schemes.entry((prefix, path.join("-"))).or_default();
+                            slot[usize::from(dark)] = Some(value);
+                        }
+                        (None, [name]) => {
+                            map.insert(name.to_string(), TokenValue::Simple(value));
+                        }
+                        (None, [name, step @ ..]) => {
+                            let entry = map
+                                .entry(name.to_string())
+                                .or_insert_with(|| TokenValue::Scale(IndexMap::new()));
+                            if let TokenValue::Scale(steps) = entry {
+                                steps.insert(step.join("-"), value);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for ((prefix, name), [light, dark]) in schemes {
+            let value = match (light, dark) {
+                (Some(light), Some(dark)) => TokenValue::Adaptive { light, dark },
+                (Some(value), None) | (None, Some(value)) => TokenValue::Simple(value),
+                (None, None) => continue,
+            };
+            category(&mut tokens, prefix).insert(name, value);
+        }
+
+        for leaf in composites {
+            let name = leaf.path.join("-");
+            for (part, prefix) in TYPOGRAPHY_PARTS {
+                let Some(value) = leaf.value[part].as_str().filter(|v| !v.contains('{')) else {
+                    continue;
+                };
+                category(&mut tokens, prefix)
+                    .entry(name.clone())
+                    .or_insert_with(|| TokenValue::Simple(value.to_string()));
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportFormat;
+
+    fn fixture() -> DesignTokens {
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert("primary".into(), simple("#3b82f6"));
+        tokens.colors.insert(
+            "blue".into(),
+            TokenValue::Scale(IndexMap::from([
+                ("100".to_string(), "#dbeafe".to_string()),
+                ("500".to_string(), "var(--color-primary)".to_string()),
+            ])),
+        );
+        tokens.colors.insert(
+            "surface".into(),
+            TokenValue::Adaptive {
+                light: "#ffffff".into(),
+                dark: "#0f172a".into(),
+            },
+        );
+        tokens.colors.insert(
+            "text".into(),
+            TokenValue::Adaptive {
+                light: "var(--color-blue-500)".into(),
+                dark: "#e2e8f0".into(),
+            },
+        );
+        tokens.spacing.insert("sm".into(), simple("8px"));
+        tokens
+            .spacing
+            .insert("md".into(), simple("calc(var(--spacing-sm) * 2)"));
+        tokens.radius.insert("lg".into(), simple("12px"));
+        tokens.shadows.insert(
+            "card".into(),
+            simple("0 1px 2px rgba(0, 0, 0, 0.1), 0 4px 8px -2px var(--color-primary)"),
+        );
+        tokens
+            .shadows
+            .insert("well".into(), simple("inset 0 2px 4px #0000001a"));
+        tokens
+            .shadows
+            .insert("glow".into(), simple("0 0 8px var(--glow, #fff)"));
+        tokens
+            .typography
+            .fonts
+            .insert("body".into(), simple("Inter, system-ui, sans-serif"));
+        tokens
+            .typography
+            .sizes
+            .insert("base".into(), simple("1rem"));
+        tokens
+            .typography
+            .sizes
+            .insert("lg".into(), simple("1.25rem"));
+        tokens
+            .typography
+            .weights
+            .insert("bold".into(), simple("700"));
+        tokens
+            .typography
+            .weights
+            .insert("regular".into(), simple("400"));
+        tokens
+            .transitions
+            .insert("fast".into(), simple("150ms ease"));
+        tokens.z_index.insert("modal".into(), simple("50"));
+        tokens
+    }
+
+    #[test]
+    fn test_export_structure() {
+        let json = fixture().export(ExportFormat::FigmaTokens).unwrap();
+        let doc: Value = serde_json::from_str(&json).unwrap();
+        let global = &doc["global"];
+        assert_eq!(
+            global["colors"]["primary"],
+            json!({ "value": "#3b82f6", "type": "color" })
+        );
+        assert_eq!(global["colors"]["blue"]["500"]["value"], "{colors.primary}");
+        assert_eq!(global["spacing"]["md"]["value"], "calc({spacing.sm} * 2)");
+        assert_eq!(global["borderRadius"]["lg"]["type"], "borderRadius");
+        assert_eq!(
+            global["boxShadow"]["card"]["value"][1],
+            json!({
+                "x": "0", "y": "4px", "blur": "8px", "spread": "-2px",
+                "color": "{colors.primary}", "type": "dropShadow",
+            })
+        );
+        assert_eq!(global["boxShadow"]["well"]["value"]["type"], "innerShadow");
+        // A variable with a fallback can't become a reference.
+        assert_eq!(
+            global["boxShadow"]["glow"]["value"],
+            "0 0 8px var(--glow, #fff)"
+        );
+        assert_eq!(
+            global["typography"]["lg"]["value"],
+            json!({
+                "fontFamily": "{fontFamilies.body}",
+                "fontWeight": "{fontWeights.regular}",
+                "fontSize": "{fontSizes.lg}",
+            })
+        );
+        assert_eq!(doc["light"]["colors"]["text"]["value"], "{colors.blue.500}");
+        assert_eq!(doc["dark"]["colors"]["surface"]["value"], "#0f172a");
+        assert!(global["colors"].get("surface").is_none());
+        assert_eq!(
+            doc["$metadata"]["tokenSetOrder"],
+            json!(["global", "light", "dark"])
+        );
+        assert_eq!(doc["$themes"][1]["selectedTokenSets"]["dark"], "enabled");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tokens = fixture();
+        let json = tokens.export(ExportFormat::FigmaTokens).unwrap();
+        let read = DesignTokens::from_figma_tokens(&json).unwrap();
+        for ((prefix, original), (_, read)) in
+            tokens.categories().into_iter().zip(read.categories())
+        {
+            let mut original = original.clone();
+            original.sort_keys();
+            let mut read = read.clone();
+            read.sort_keys();
+            assert_eq!(original, read, "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn test_tolerant_import() {
+        let json = r##"{
+            "core": {
+                "space": { "xs": { "value": 4, "type": "dimension" } },
+                "colors": {
+                    "accent": { "$value": "{colors.brand}", "$type": "color" },
+                    "link": { "value": "{brand.unknown}", "type": "color" },
+                    "brand": { "value": "#7c3aed", "type": "color" }
+                },
+                "typography": {
+                    "heading": {
+                        "value": { "fontFamily": "Georgia", "fontSize": 32, "fontWeight": "{fontWeights.bold}" },
+                        "type": "typography"
+                    }
+                },
+                "misc": { "gradient": { "value": "linear-gradient(red, blue)", "type": "other" } }
+            },
+            "theme/dark": { "colors": { "bg": { "value": "#000000", "type": "color" } } },
+            "$metadata": { "tokenSetOrder": ["core", "theme/dark"] }
+        }"##;
+        let tokens = DesignTokens::from_figma_tokens(json).unwrap();
+        assert_eq!(tokens.spacing["xs"], TokenValue::Simple("4px".into()));
+        assert_eq!(
+            tokens.colors["accent"],
+            TokenValue::Simple("var(--color-brand)".into())
+        );
+        assert_eq!(
+            tokens.colors["link"],
+            TokenValue::Simple("{brand.unknown}".into())
+        );
+        // Only in the dark set, so not adaptive.
+        assert_eq!(tokens.colors["bg"], TokenValue::Simple("#000000".into()));
+        assert_eq!(
+            tokens.typography.fonts["heading"],
+            TokenValue::Simple("Georgia".into())
+        );
+        assert!(tokens.typography.sizes.is_empty());
+        assert!(tokens.typography.weights.is_empty());
+        assert_eq!(tokens.all_paths().len(), 6);
+
+        assert!(DesignTokens::from_figma_tokens("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_import_without_metadata() {
+        let json = r##"{ "global": { "colors": { "primary": { "value": "#3b82f6", "type": "color" } } } }"##;
+        let tokens = DesignTokens::from_figma_tokens(json).unwrap();
+        assert_eq!(
+            tokens.colors["primary"],
+            TokenValue::Simple("#3b82f6".into())
+        );
+
+        // Without a set order, sets apply in document order.
+        let json = r##"{
+            "base": { "colors": { "bg": { "value": "#ffffff", "type": "color" } } },
+            "brand": { "colors": { "bg": { "value": "#fafaf9", "type": "color" } } }
+        }"##;
+        let tokens = DesignTokens::from_figma_tokens(json).unwrap();
+        assert_eq!(tokens.colors["bg"], TokenValue::Simple("#fafaf9".into()));
+
+        let tokens = DesignTokens::from_figma_tokens("{}").unwrap();
+        assert_eq!(tokens.count(), 0);
+    }
+}
@@ -0,0 +1,355 @@
+//! Style Dictionary `properties` JSON.
+//!
+//! Each token becomes a `{ "value", "comment" }` leaf, with the studio path
+//! as the comment. Categories are placed under configurable groups (radius
+//! under `size.radius` by default), scale steps become nested groups and
+//! adaptive tokens get a `light` and a `dark` leaf. Plain references to
+//! other tokens are written as `{color.primary.value}`.
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value, json};
+
+use super::ExportError;
+use crate::designer::css::extract::var_calls;
+use crate::designer::css::{DesignTokens, TokenValue, path_to_css_var};
+
+/// Default group for each category prefix, in category order.
+const DEFAULT_CATEGORIES: [(&str, &str); 9] = [
+    ("colors", "color"),
+    ("spacing", "size.spacing"),
+    ("radius", "size.radius"),
+    ("shadows", "shadow"),
+    ("typography.fonts", "font.family"),
+    ("typography.sizes", "size.font"),
+    ("typography.weights", "font.weight"),
+    ("transitions", "time.transition"),
+    ("z-index", "z-index"),
+];
+
+/// How group and token names are written.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum NameTransform {
+    /// `primary-hover`
+    #[default]
+    Kebab,
+    /// `primaryHover`
+    Camel,
+    /// `primary_hover`
+    Snake,
+    /// Kebab-case names under a top-level group, e.g. `acme.color.primary`.
+    Prefix(String),
+}
+
+impl NameTransform {
+    /// Apply the transform to one name segment.
+    pub fn apply(&self, name: &str) -> String {
+        let words = words(name);
+        match self {
+            NameTransform::Kebab | NameTransform::Prefix(_) => words.join("-"),
+            NameTransform::Snake => words.join("_"),
+            NameTransform::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| match word.chars().next() {
+                    Some(first) if i > 0 => {
+                        format!("{}{}", first.to_uppercase(), &word[first.len_utf8()..])
+                    }
+                    _ => word.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Lowercase words of a name split on separators and case changes.
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | ' ' | '.') {
+            words.push(String::new());
+            prev_lower = false;
+            continue;
+        }
+        if (c.is_uppercase() && prev_lower) || words.is_empty() {
+            words.push(String::new());
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        if let Some(word) = words.last_mut() {
+            word.extend(c.to_lowercase());
+        }
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+/// Style Dictionary export settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleDictionaryOptions {
+    pub name_transform: NameTransform,
+    /// Category prefix to its dotted group path, e.g. `radius` to
+    /// `border.radius`.
+    pub categories: IndexMap<String, String>,
+}
+
+impl Default for StyleDictionaryOptions {
+    fn default() -> Self {
+        Self {
+            name_transform: NameTransform::default(),
+            categories: DEFAULT_CATEGORIES
+                .into_iter()
+                .map(|(prefix, group)| (prefix.to_string(), group.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl StyleDictionaryOptions {
+    pub fn with_name_transform(mut self, transform: NameTransform) -> Self {
+        self.name_transform = transform;
+        self
+    }
+
+    /// Place the `prefix` category under `group`, e.g. `border.radius`.
+    pub fn with_category(mut self, prefix: &str, group: &str) -> Self {
+        self.categories
+            .insert(prefix.to_string(), group.to_string());
+        self
+    }
+
+    /// Transformed path segments of a category's group.
+    fn group(&self, prefix: &str) -> Vec<String> {
+        let group = self.categories.get(prefix).map_or(prefix, String::as_str);
+        let mut path: Vec<String> = match &self.name_transform {
+            NameTransform::Prefix(root) => vec![root.clone()],
+            _ => Vec::new(),
+        };
+        path.extend(group.split('.').map(|s| self.name_transform.apply(s)));
+        path
+    }
+}
+
+impl DesignTokens {
+    /// Export the tokens as a Style Dictionary properties tree.
+    pub fn export_style_dictionary(
+        &self,
+        options: &StyleDictionaryOptions,
+    ) -> Result<String, ExportError> {
+        Ok(serde_json::to_string_pretty(&properties(self, options))?)
+    }
+}
+
+fn properties(tokens: &DesignTokens, options: &StyleDictionaryOptions) -> Value {
+    let transform = &options.name_transform;
+    let refs = references(tokens, options);
+    let mut root = Map::new();
+    for (prefix, map) in tokens.categories() {
+        let group = options.group(prefix);
+        for (name, value) in map {
+            let mut path = group.clone();
+            path.push(transform.apply(name));
+            let studio = format!("{}.{}", prefix, name);
+            let leaf = |v: &str, comment: &str| property(&to_references(v, &refs), comment);
+            let node = match value {
+                TokenValue::Simple(v) => leaf(v, &studio),
+                TokenValue::Adaptive { light, dark } => json!({
+                    "light": leaf(light, &format!("{} (light)", studio)),
+                    "dark": leaf(dark, &format!("{} (dark)", studio)),
+                }),
+                TokenValue::Scale(steps) => Value::Object(
+                    steps
+                        .iter()
+                        .map(|(step, v)| {
+                            let comment = format!("{}.{}", studio, step);
+                            (transform.apply(step), leaf(v, &comment))
+                        })
+                        .collect(),
+                ),
+            };
+            insert(&mut root, &path, node);
+        }
+    }
+    Value::Object(root)
+}
+
+fn property(value: &str, comment: &str) -> Value {
+    json!({ "value": value, "comment": comment })
+}
+
+fn insert(root: &mut Map<String, Value>, path: &[String], node: Value) {
+    let Some((last, groups)) = path.split_last() else {
+        return;
+    };
+    let mut map = root;
+    for segment in groups {
+        let entry = map
+            .entry(segment.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        let Value::Object(next) = entry else {
+            return;
+        };
+        map = next;
+    }
+    map.insert(last.clone(), node);
+}
+
+/// CSS variable of every simple token and scale step to its reference.
+/// Adaptive tokens have no single value to point at and stay as CSS.
+fn references(tokens: &DesignTokens, options: &StyleDictionaryOptions) -> IndexMap<String, String> {
+    let transform = &options.name_transform;
+    let mut refs = IndexMap::new();
+    for (prefix, map) in tokens.categories() {
+        let group = options.group(prefix).join(".");
+        for (name, value) in map {
+            let Some(var) = path_to_css_var(&format!("{}.{}", prefix, name)) else {
+                continue;
+            };
+            let path = format!("{}.{}", group, transform.apply(name));
+            match value {
+                TokenValue::Simple(_) => {
+                    refs.insert(var, format!("{{{}.value}}", path));
+                }
+                TokenValue::Adaptive { .. } => {}
+                TokenValue::Scale(steps) => {
+                    for step in steps.keys() {
+                        refs.insert(
+                            format!("{}-{}", var, step),
+                            format!("{{{}.{}.value}}", path, transform.apply(step)),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// Replace plain `var(--token)` references with Style Dictionary references.
+fn to_references(value: &str, refs: &IndexMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, end, name) in var_calls(value) {
+        let Some(reference) = refs.get(&name) else {
+            continue;
+        };
+        if start < last || value[start..end] != format!("var({})", name) {
+            continue;
+        }
+        out.push_str(&value[last..start]);
+        out.push_str(reference);
+        last = end;
+    }
+    out.push_str(&value[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportFormat;
+
+    fn tokens() -> DesignTokens {
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        let mut tokens = DesignTokens::default();
+        tokens
+            .colors
+            .insert("primary-hover".to_string(), simple("#1d4ed8"));
+        tokens.colors.insert(
+            "surface".to_string(),
+            TokenValue::Adaptive {
+                light: "#ffffff".to_string(),
+                dark: "#0f172a".to_string(),
+            },
+        );
+        let mut blue = IndexMap::new();
+        blue.insert("100".to_string(), "#dbeafe".to_string());
+        blue.insert("500".to_string(), "var(--color-primary-hover)".to_string());
+        tokens
+            .colors
+            .insert("blue".to_string(), TokenValue::Scale(blue));
+        tokens.radius.insert("lg".to_string(), simple("8px"));
+        tokens.z_index.insert("modal".to_string(), simple("50"));
+        tokens
+    }
+
+    fn export(options: &StyleDictionaryOptions) -> Value {
+        let json = tokens().export_style_dictionary(options).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_nested_structure() {
+        let tree = export(&StyleDictionaryOptions::default());
+        let expected = json!({
+            "color": {
+                "primary-hover": { "value": "#1d4ed8", "comment": "colors.primary-hover" },
+                "surface": {
+                    "light": { "value": "#ffffff", "comment": "colors.surface (light)" },
+                    "dark": { "value": "#0f172a", "comment": "colors.surface (dark)" },
+                },
+                "blue": {
+                    "100": { "value": "#dbeafe", "comment": "colors.blue.100" },
+                    "500": {
+                        "value": "{color.primary-hover.value}",
+                        "comment": "colors.blue.500",
+                    },
+                },
+            },
+            "size": { "radius": { "lg": { "value": "8px", "comment": "radius.lg" } } },
+            "z-index": { "modal": { "value": "50", "comment": "z-index.modal" } },
+        });
+        assert_eq!(tree, expected);
+
+        let tree =
+            export(&StyleDictionaryOptions::default().with_category("radius", "border.radius"));
+        assert_eq!(tree["border"]["radius"]["lg"]["value"], "8px");
+        assert!(tree.get("size").is_none());
+        assert_eq!(
+            DesignTokens::default()
+                .export(ExportFormat::StyleDictionary)
+                .unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn test_name_transforms() {
+        let with =
+            |transform| export(&StyleDictionaryOptions::default().with_name_transform(transform));
+
+        let camel = with(NameTransform::Camel);
+        assert_eq!(camel["color"]["primaryHover"]["value"], "#1d4ed8");
+        assert_eq!(
+            camel["color"]["blue"]["500"]["value"],
+            "{color.primaryHover.value}"
+        );
+        assert_eq!(camel["zIndex"]["modal"]["comment"], "z-index.modal");
+
+        let snake = with(NameTransform::Snake);
+        assert_eq!(snake["color"]["primary_hover"]["value"], "#1d4ed8");
+        assert_eq!(snake["z_index"]["modal"]["value"], "50");
+
+        let kebab = with(NameTransform::Kebab);
+        assert_eq!(kebab["color"]["primary-hover"]["value"], "#1d4ed8");
+
+        let prefixed = with(NameTransform::Prefix("acme".to_string()));
+        let acme = prefixed.as_object().unwrap();
+        assert_eq!(acme.keys().collect::<Vec<_>>(), ["acme"]);
+        assert_eq!(acme["acme"]["size"]["radius"]["lg"]["value"], "8px");
+        assert_eq!(
+            acme["acme"]["color"]["blue"]["500"]["value"],
+            "{acme.color.primary-hover.value}"
+        );
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(NameTransform::Snake.apply("primaryHover"), "primary_hover");
+        assert_eq!(NameTransform::Camel.apply("font_size-2xl"), "fontSize2xl");
+        assert_eq!(NameTransform::Kebab.apply("Z Index"), "z-index");
+        assert_eq!(NameTransform::Camel.apply("100"), "100");
+    }
+}
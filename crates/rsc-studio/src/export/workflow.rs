@@ -0,0 +1,449 @@
+//! Workflow export files.
+//!
+//! A [`WorkflowExport`] holds one workflow with its contexts and presets,
+//! the transitions drawn between them on the navigation canvas and their
+//! positions, tagged with a schema version. Files from older versions are
+//! upgraded one migration at a time before they are read. Importing gives
+//! every entity and transition a new id, so the same file can be imported
+//! twice, and drops transitions whose ends aren't in the file.
+
+use indexmap::IndexMap;
+use rsc_flow::{Edge, EdgeData, EdgeLabel, FlowCanvas, Position};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::ExportError;
+use crate::designer::NavigationDesigner;
+use crate::designer::navigation::NavigationNodeData;
+use crate::entity::{EntityId, Workflow};
+use crate::import::ImportError;
+
+/// Schema version of the files this studio writes.
+pub const WORKFLOW_SCHEMA_VERSION: u32 = 2;
+
+/// Migrations in order; the one at index `i` upgrades version `i + 1`.
+const MIGRATIONS: [fn(&mut Value); 1] = [migrate_v1];
+
+/// Version 1 called transitions `edges`, with `from` and `to` ends, and
+/// stored positions as `[x, y]` pairs.
+fn migrate_v1(file: &mut Value) {
+    let Some(file) = file.as_object_mut() else {
+        return;
+    };
+    if let Some(mut edges) = file.remove("edges") {
+        for edge in edges.as_array_mut().into_iter().flatten() {
+            let Some(edge) = edge.as_object_mut() else {
+                continue;
+            };
+            for (old, new) in [("from", "source"), ("to", "target")] {
+                if let Some(end) = edge.remove(old) {
+                    edge.insert(new.to_string(), end);
+                }
+            }
+        }
+        file.insert("transitions".to_string(), edges);
+    }
+    if let Some(positions) = file.get_mut("positions").and_then(Value::as_object_mut) {
+        for position in positions.values_mut() {
+            if let Some([x, y]) = position.as_array().map(Vec::as_slice) {
+                *position = serde_json::json!({ "x": x, "y": y });
+            }
+        }
+    }
+}
+
+/// A transition between two entities of the workflow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionExport {
+    pub id: String,
+    pub source: EntityId,
+    pub target: EntityId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+}
+
+/// Contents of a workflow export file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowExport {
+    pub schema_version: u32,
+    pub workflow: Workflow,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<TransitionExport>,
+    /// Canvas positions by entity id.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub positions: IndexMap<EntityId, Position>,
+}
+
+/// A workflow read from an export file, with new ids.
+#[derive(Debug, Clone)]
+pub struct WorkflowImport {
+    pub workflow: Workflow,
+    pub transitions: Vec<TransitionExport>,
+    pub positions: IndexMap<EntityId, Position>,
+    /// Ids in the file to the ids they were given.
+    pub id_map: IndexMap<String, String>,
+    /// Transitions left out because an end isn't in the file, as in the
+    /// file.
+    pub dropped: Vec<TransitionExport>,
+}
+
+impl Workflow {
+    /// Export of the workflow alone; add the canvas state with
+    /// [`WorkflowExport::with_canvas`].
+    pub fn to_export(&self) -> WorkflowExport {
+        WorkflowExport {
+            schema_version: WORKFLOW_SCHEMA_VERSION,
+            workflow: self.clone(),
+            transitions: Vec::new(),
+            positions: IndexMap::new(),
+        }
+    }
+
+    /// Ids of the workflow, its contexts and their presets.
+    fn entity_ids(&self) -> impl Iterator<Item = &EntityId> {
+        std::iter::once(&self.id).chain(
+            self.contexts
+                .values()
+                .flat_map(|c| std::iter::once(&c.id).chain(c.presets.keys())),
+        )
+    }
+}
+
+impl WorkflowExport {
+    /// Add the positions of the workflow's nodes on `canvas` and the edges
+    /// between them, including the parent-child ones so their labels
+    /// survive.
+    pub fn with_canvas(mut self, canvas: &FlowCanvas<NavigationNodeData, EdgeData>) -> Self {
+        let ids: Vec<&EntityId> = self.workflow.entity_ids().collect();
+        self.positions = ids
+            .iter()
+            .filter_map(|id| Some(((*id).clone(), canvas.get_node(id)?.position)))
+            .collect();
+        self.transitions = canvas
+            .edges
+            .values()
+            .filter(|e| ids.contains(&&e.source) && ids.contains(&&e.target))
+            .map(|edge| TransitionExport {
+                id: edge.id.clone(),
+                source: edge.source.clone(),
+                target: edge.target.clone(),
+                label: edge.data.as_ref().and_then(|d| d.label.clone()),
+                layer: edge.data.as_ref().and_then(|d| d.layer.clone()),
+            })
+            .collect();
+        self
+    }
+
+    /// Export of `workflow` with `transitions` drawn between its contexts
+    /// and every node placed by the navigation designer's auto-layout.
+    pub fn laid_out(workflow: Workflow, transitions: &[TransitionExport]) -> Self {
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        designer.draw_transitions(transitions);
+        designer.apply_layout();
+        workflow.to_export().with_canvas(&designer.canvas)
+    }
+
+    pub fn to_json(&self) -> Result<String, ExportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Read a file of this or an older schema version.
+    pub fn from_json(json: &str) -> Result<Self, ImportError> {
+        let mut file: Value =
+            serde_json::from_str(json).map_err(|e| ImportError::Parse(e.to_string()))?;
+        if !file.is_object() {
+            return Err(ImportError::Parse("Expected a JSON object".into()));
+        }
+        // Version 1 files had no version field.
+        let version = match file.get("schema_version") {
+            None => 1,
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| ImportError::Parse("schema_version is not a number".into()))?,
+        };
+        if version == 0 || version > WORKFLOW_SCHEMA_VERSION {
+            return Err(ImportError::UnsupportedVersion {
+                found: version,
+                supported: WORKFLOW_SCHEMA_VERSION,
+            });
+        }
+        for migrate in &MIGRATIONS[version as usize - 1..] {
+            migrate(&mut file);
+        }
+        file["schema_version"] = WORKFLOW_SCHEMA_VERSION.into();
+        serde_json::from_value(file).map_err(|e| ImportError::Parse(e.to_string()))
+    }
+
+    /// Read a file and give everything in it new ids.
+    pub fn import(json: &str) -> Result<WorkflowImport, ImportError> {
+        Ok(Self::from_json(json)?.with_new_ids())
+    }
+
+    /// The contents with new ids, keeping every reference between them.
+    pub fn with_new_ids(self) -> WorkflowImport {
+        let mut id_map = IndexMap::new();
+        let mut renew = |id: &str| -> String {
+            id_map
+                .entry(id.to_string())
+                .or_insert_with(|| Uuid::new_v4().to_string())
+                .clone()
+        };
+
+        let mut workflow = self.workflow;
+        workflow.id = renew(&workflow.id);
+        let contexts = std::mem::take(&mut workflow.contexts);
+        for (_, mut context) in contexts {
+            context.id = renew(&context.id);
+            let presets = std::mem::take(&mut context.presets);
+            for (_, mut preset) in presets {
+                preset.id = renew(&preset.id);
+                context.presets.insert(preset.id.clone(), preset);
+            }
+            workflow.contexts.insert(context.id.clone(), context);
+        }
+
+        // References resolve once every entity has its new id.
+        let lookup = |id: &str| id_map.get(id).cloned();
+        if let Some(default) = &mut workflow.default_context {
+            *default = lookup(default).unwrap_or_default();
+        }
+        for context in workflow.contexts.values_mut() {
+            if let Some(default) = &mut context.default_preset {
+                *default = lookup(default).unwrap_or_default();
+            }
+            for preset in context.presets.values_mut() {
+                // A base preset outside the file keeps its id.
+                if let Some(extends) = &mut preset.extends
+                    && let Some(id) = lookup(extends)
+                {
+                    *extends = id;
+                }
+            }
+        }
+        let positions = self
+            .positions
+            .into_iter()
+            .filter_map(|(id, position)| Some((lookup(&id)?, position)))
+            .collect();
+
+        let mut transitions = Vec::new();
+        let mut dropped = Vec::new();
+        for transition in self.transitions {
+            match (lookup(&transition.source), lookup(&transition.target)) {
+                (Some(source), Some(target)) => transitions.push(TransitionExport {
+                    source,
+                    target,
+                    ..transition.clone()
+                }),
+                _ => dropped.push(transition),
+            }
+        }
+        for transition in &mut transitions {
+            let id = Uuid::new_v4().to_string();
+            id_map.insert(std::mem::replace(&mut transition.id, id.clone()), id);
+        }
+
+        WorkflowImport {
+            workflow,
+            transitions,
+            positions,
+            id_map,
+            dropped,
+        }
+    }
+}
+
+impl NavigationDesigner {
+    /// Place the nodes of an imported workflow and draw its transitions.
+    /// Call after the workflow is loaded; edges the load already drew get
+    /// the transition's label instead of a duplicate.
+    pub fn apply_import(&mut self, import: &WorkflowImport) {
+        self.place_nodes(&import.positions);
+        self.draw_transitions(&import.transitions);
+    }
+
+    /// Move the nodes with these ids.
+    pub(crate) fn place_nodes(&mut self, positions: &IndexMap<EntityId, Position>) {
+        for (id, position) in positions {
+            self.canvas.move_node(id, *position);
+        }
+    }
+
+    /// Draw transitions, labelling edges already drawn between their ends
+    /// instead of duplicating them.
+    pub(crate) fn draw_transitions(&mut self, transitions: &[TransitionExport]) {
+        for transition in transitions {
+            let existing = self
+                .canvas
+                .edges
+                .values()
+                .find(|e| e.source == transition.source && e.target == transition.target)
+                .map(|e| e.id.clone());
+            let id = match existing {
+                Some(id) => id,
+                None => {
+                    let edge = Edge::new(&transition.id, &transition.source, &transition.target)
+                        .with_data(EdgeData {
+                            layer: transition.layer.clone(),
+                            ..Default::default()
+                        });
+                    self.canvas.add_edge(edge);
+                    transition.id.clone()
+                }
+            };
+            if let Some(label) = &transition.label
+                && let Some(edge) = self.canvas.edges.get_mut(&id)
+            {
+                edge.data.get_or_insert_with(EdgeData::default).label = Some(label.clone());
+                edge.label = Some(EdgeLabel::new(label));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Context, Preset};
+
+    /// Checkout: Cart and Payment, Payment with a Compact preset.
+    fn designer() -> (Workflow, NavigationDesigner) {
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        workflow
+            .metadata
+            .insert("owner".to_string(), "payments".into());
+        workflow.add_context(Context::new("Cart").with_id("c1"));
+        let mut payment = Context::new("Payment").with_id("c2");
+        payment.add_preset(Preset::new("Compact").with_id("p1"));
+        workflow.add_context(payment);
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        designer
+            .canvas
+            .add_edge(Edge::new("t1", "c1", "c2").with_data(EdgeData::default()));
+        designer.set_transition_label("t1", "Pay");
+        designer.canvas.nodes["c2"].position = Position::new(320.0, 160.0);
+        (workflow, designer)
+    }
+
+    #[test]
+    fn test_round_trip_with_new_ids() {
+        let (workflow, designer) = designer();
+        let json = workflow
+            .to_export()
+            .with_canvas(&designer.canvas)
+            .to_json()
+            .unwrap();
+        let first = WorkflowExport::import(&json).unwrap();
+        let second = WorkflowExport::import(&json).unwrap();
+        assert_ne!(first.workflow.id, second.workflow.id);
+        assert_ne!(first.id_map["c2"], second.id_map["c2"]);
+        assert!(first.dropped.is_empty());
+
+        let imported = &first.workflow;
+        assert_eq!(imported.name, "Checkout");
+        assert_eq!(imported.metadata["owner"], "payments");
+        let names: Vec<_> = imported
+            .contexts
+            .values()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, ["Cart", "Payment"]);
+        let payment = &imported.contexts[&first.id_map["c2"]];
+        assert_eq!(payment.default_preset.as_ref(), Some(&first.id_map["p1"]));
+        assert_eq!(imported.default_context.as_ref(), Some(&first.id_map["c1"]));
+
+        // Transitions keep their ends and labels across the new ids.
+        let pay = first
+            .transitions
+            .iter()
+            .find(|t| t.label.as_deref() == Some("Pay"))
+            .unwrap();
+        assert_eq!(
+            (&pay.source, &pay.target),
+            (&first.id_map["c1"], &first.id_map["c2"])
+        );
+        assert_eq!(first.transitions.len(), 4);
+
+        let mut target = NavigationDesigner::new();
+        target.load_workflows(&[imported]);
+        target.apply_import(&first);
+        assert_eq!(target.canvas.edges.len(), 4);
+        assert_eq!(
+            target.canvas.nodes[&first.id_map["c2"]].position,
+            Position::new(320.0, 160.0)
+        );
+        assert_eq!(
+            target.canvas.edges[&first.id_map["t1"]]
+                .label
+                .as_ref()
+                .map(|l| l.text.as_str()),
+            Some("Pay")
+        );
+    }
+
+    #[test]
+    fn test_dangling_transitions_are_dropped() {
+        let (workflow, designer) = designer();
+        let mut export = workflow.to_export().with_canvas(&designer.canvas);
+        export.transitions.push(TransitionExport {
+            id: "t2".to_string(),
+            source: "c1".to_string(),
+            target: "elsewhere".to_string(),
+            label: None,
+            layer: None,
+        });
+        let import = export.with_new_ids();
+        assert_eq!(import.dropped.len(), 1);
+        assert_eq!(import.dropped[0].target, "elsewhere");
+        assert!(!import.id_map.contains_key("t2"));
+    }
+
+    #[test]
+    fn test_v1_file_migrates() {
+        let import = WorkflowExport::import(include_str!("workflow/v1.json")).unwrap();
+        let ids = &import.id_map;
+        assert_eq!(import.workflow.name, "Onboarding");
+        assert_eq!(import.workflow.contexts.len(), 2);
+        assert_eq!(import.transitions.len(), 1);
+        assert_eq!(import.transitions[0].source, ids["welcome"]);
+        assert_eq!(import.transitions[0].target, ids["profile"]);
+        assert_eq!(import.transitions[0].label.as_deref(), Some("Continue"));
+        assert_eq!(import.dropped.len(), 1);
+        assert_eq!(
+            import.positions[&ids["profile"]],
+            Position::new(240.0, 80.0)
+        );
+
+        let newer = r#"{"schema_version": 99, "workflow": {"id": "w", "name": "W"}}"#;
+        assert!(matches!(
+            WorkflowExport::import(newer),
+            Err(ImportError::UnsupportedVersion { found: 99, .. })
+        ));
+        assert!(matches!(
+            WorkflowExport::import("not json"),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_non_object_root_is_a_parse_error() {
+        for json in ["[]", "1", "\"x\"", "null"] {
+            assert!(
+                matches!(WorkflowExport::from_json(json), Err(ImportError::Parse(_))),
+                "{}",
+                json
+            );
+            assert!(matches!(
+                WorkflowExport::import(json),
+                Err(ImportError::Parse(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Undo/redo history.
+//!
+//! Changes are recorded per [`HistoryScope`] so that undoing in one designer
+//! never reverts work done in another.
+
+use std::collections::HashMap;
+
+use crate::store::Designer;
+
+/// Default maximum number of entries kept per undo stack.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// History scope an action belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HistoryScope {
+    /// Actions affecting the whole project (project load, settings).
+    Global,
+    /// Token and component style edits.
+    CssDesigner,
+    /// Workflow and flow graph edits.
+    NavDesigner,
+}
+
+impl HistoryScope {
+    pub fn all() -> &'static [HistoryScope] {
+        &[
+            HistoryScope::Global,
+            HistoryScope::CssDesigner,
+            HistoryScope::NavDesigner,
+        ]
+    }
+}
+
+impl From<Designer> for HistoryScope {
+    fn from(designer: Designer) -> Self {
+        match designer {
+            Designer::Navigation => HistoryScope::NavDesigner,
+            Designer::Css | Designer::Component => HistoryScope::CssDesigner,
+        }
+    }
+}
+
+/// A labelled history entry.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry<C> {
+    /// Human-readable description (e.g. "Add workflow").
+    pub label: String,
+    /// The recorded change.
+    pub change: C,
+    /// Store revision that recorded the entry, or 0.
+    pub revision: u64,
+}
+
+/// A single undo/redo stack with bounded depth.
+#[derive(Debug, Clone)]
+pub struct History<C> {
+    undo: Vec<HistoryEntry<C>>,
+    redo: Vec<HistoryEntry<C>>,
+    /// Maximum number of undo entries kept.
+    pub max_depth: usize,
+}
+
+impl<C> Default for History<C> {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl<C> History<C> {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            max_depth,
+        }
+    }
+
+    /// Record a new entry, clearing the redo stack.
+    pub fn push(&mut self, label: impl Into<String>, change: C) {
+        self.push_revision(label, change, 0);
+    }
+
+    /// Record a new entry tagged with the revision that made it.
+    pub fn push_revision(&mut self, label: impl Into<String>, change: C, revision: u64) {
+        self.undo.push(HistoryEntry {
+            label: label.into(),
+            change,
+            revision,
+        });
+        if self.undo.len() > self.max_depth {
+            let overflow = self.undo.len() - self.max_depth;
+            self.undo.drain(..overflow);
+        }
+        self.redo.clear();
+    }
+
+    /// Pop the most recent entry and move it to the redo stack.
+    ///
+    /// Returns the entry so the caller can revert its change.
+    pub fn undo(&mut self) -> Option<&HistoryEntry<C>> {
+        let entry = self.undo.pop()?;
+        self.redo.push(entry);
+        self.redo.last()
+    }
+
+    /// Pop the most recently undone entry and move it back to the undo stack.
+    ///
+    /// Returns the entry so the caller can re-apply its change.
+    pub fn redo(&mut self) -> Option<&HistoryEntry<C>> {
+        let entry = self.redo.pop()?;
+        self.undo.push(entry);
+        self.undo.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Label of the entry the next undo would revert.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|e| e.label.as_str())
+    }
+
+    /// Label of the entry the next redo would re-apply.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|e| e.label.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty()
+    }
+
+    /// All undo and redo entries.
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry<C>> {
+        self.undo.iter().chain(&self.redo)
+    }
+
+    /// Drop all undo and redo entries.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// Independent histories keyed by scope.
+#[derive(Debug, Clone)]
+pub struct ScopedHistory<C> {
+    stacks: HashMap<HistoryScope, History<C>>,
+    max_depth: usize,
+}
+
+impl<C> Default for ScopedHistory<C> {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl<C> ScopedHistory<C> {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            stacks: HashMap::new(),
+            max_depth,
+        }
+    }
+
+    /// Get the stack for a scope.
+    pub fn scope(&self, scope: HistoryScope) -> Option<&History<C>> {
+        self.stacks.get(&scope)
+    }
+
+    /// Get the mutable stack for a scope, creating it if needed.
+    pub fn scope_mut(&mut self, scope: HistoryScope) -> &mut History<C> {
+        let max_depth = self.max_depth;
+        self.stacks
+            .entry(scope)
+            .or_insert_with(|| History::new(max_depth))
+    }
+
+    /// Record an entry in a scope.
+    ///
+    /// Global entries invalidate every designer scope: their entries were
+    /// recorded against state the global action has replaced.
+    pub fn push(&mut self, scope: HistoryScope, label: impl Into<String>, change: C) {
+        self.push_revision(scope, label, change, 0);
+    }
+
+    /// [`push`](Self::push), tagging the entry with a store revision.
+    pub fn push_revision(
+        &mut self,
+        scope: HistoryScope,
+        label: impl Into<String>,
+        change: C,
+        revision: u64,
+    ) {
+        if scope == HistoryScope::Global {
+            self.invalidate_designer_scopes();
+        }
+        self.scope_mut(scope).push_revision(label, change, revision);
+    }
+
+    pub fn can_undo(&self, scope: HistoryScope) -> bool {
+        self.scope(scope).is_some_and(History::can_undo)
+    }
+
+    pub fn can_redo(&self, scope: HistoryScope) -> bool {
+        self.scope(scope).is_some_and(History::can_redo)
+    }
+
+    /// Clear the histories of every non-global scope.
+    pub fn invalidate_designer_scopes(&mut self) {
+        for (scope, stack) in self.stacks.iter_mut() {
+            if *scope != HistoryScope::Global {
+                stack.clear();
+            }
+        }
+    }
+
+    /// Entries of every scope, undo and redo.
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry<C>> {
+        self.stacks.values().flat_map(History::entries)
+    }
+
+    /// Drop all entries in every scope.
+    pub fn clear(&mut self) {
+        self.stacks.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_depth_cap() {
+        let mut history: History<u32> = History::new(2);
+        history.push("a", 1);
+        history.push("b", 2);
+        history.push("c", 3);
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.undo().map(|e| e.change), Some(3));
+        assert_eq!(history.undo().map(|e| e.change), Some(2));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn test_global_entry_invalidates_designer_scopes() {
+        let mut history: ScopedHistory<u32> = ScopedHistory::default();
+        history.push(HistoryScope::CssDesigner, "css", 1);
+        history.push(HistoryScope::NavDesigner, "nav", 2);
+        history.push(HistoryScope::Global, "load", 3);
+
+        assert!(!history.can_undo(HistoryScope::CssDesigner));
+        assert!(!history.can_undo(HistoryScope::NavDesigner));
+        assert!(history.can_undo(HistoryScope::Global));
+    }
+}
@@ -0,0 +1,244 @@
+//! Import of external data into studio entities.
+
+pub mod figma;
+pub mod router;
+
+pub use figma::{ImportWarning, contexts_from_figma_json, merge_figma_import};
+
+use std::collections::HashMap;
+
+use rsc_flow::FlowCanvas;
+use thiserror::Error;
+
+use crate::analytics::{UnmatchedRow, WeightMap};
+use crate::designer::navigation::NavigationNodeData;
+use crate::entity::slugify;
+use crate::perf::trace_span;
+
+/// Import error types.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    /// A required column is missing from the header row.
+    #[error("Missing column: {0}")]
+    MissingColumn(String),
+
+    /// The input contained no header row.
+    #[error("Input is empty")]
+    Empty,
+
+    /// The input is not valid JSON of the expected shape.
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    /// The file is from a newer studio.
+    #[error("Schema version {found} is not supported; this studio reads up to {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+/// Resolves `(source, target)` slug pairs to edge ids.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeMatcher {
+    edges: HashMap<(String, String), String>,
+}
+
+impl EdgeMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a matcher from a navigation canvas, using slugified node labels.
+    pub fn from_canvas<E>(canvas: &FlowCanvas<NavigationNodeData, E>) -> Self {
+        let mut matcher = Self::new();
+        let slug_of = |id: &str| {
+            canvas
+                .get_node(id)
+                .and_then(|n| n.data.as_ref())
+                .map(|d| slugify(&d.label))
+        };
+        for edge in canvas.edges.values() {
+            if let (Some(source), Some(target)) = (slug_of(&edge.source), slug_of(&edge.target)) {
+                matcher.add(&edge.id, &source, &target);
+            }
+        }
+        matcher
+    }
+
+    /// Register an edge. The first edge registered for a slug pair wins.
+    pub fn add(&mut self, edge_id: &str, source: &str, target: &str) {
+        self.edges
+            .entry((slugify(source), slugify(target)))
+            .or_insert_with(|| edge_id.to_string());
+    }
+
+    /// Find the edge connecting two slugs (compared after slugifying).
+    pub fn resolve(&self, source: &str, target: &str) -> Option<&str> {
+        self.edges
+            .get(&(slugify(source), slugify(target)))
+            .map(String::as_str)
+    }
+}
+
+/// Import per-edge traversal counts from CSV.
+///
+/// The header row must contain `source` and `target` columns and a `count`
+/// (or `weight`) column. Rows for the same edge are summed; rows that don't
+/// resolve to an edge or have an invalid count are reported as unmatched.
+/// A count must be a finite, non-negative number.
+pub fn edge_weights_from_csv(csv: &str, matcher: &EdgeMatcher) -> Result<WeightMap, ImportError> {
+    trace_span!("import", "import_edge_weights", bytes = csv.len());
+    let mut lines = csv
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, header) = lines.next().ok_or(ImportError::Empty)?;
+    let header: Vec<String> = split_csv_line(header)
+        .into_iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.contains(&h.as_str()))
+            .ok_or_else(|| ImportError::MissingColumn(names[0].to_string()))
+    };
+    let source_col = column(&["source"])?;
+    let target_col = column(&["target"])?;
+    let count_col = column(&["count", "weight"])?;
+
+    let mut weights = WeightMap::new();
+    for (index, line) in lines {
+        let fields = split_csv_line(line);
+        let field = |col: usize| fields.get(col).map(String::as_str).unwrap_or("");
+        let source = field(source_col);
+        let target = field(target_col);
+        let unmatched = |reason: &str| UnmatchedRow {
+            line: index + 1,
+            source: source.to_string(),
+            target: target.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let Some(count) = field(count_col)
+            .parse::<f64>()
+            .ok()
+            .filter(|c| c.is_finite() && *c >= 0.0)
+        else {
+            weights.unmatched.push(unmatched("invalid count"));
+            continue;
+        };
+        match matcher.resolve(source, target) {
+            Some(edge_id) => weights.add(edge_id, count),
+            None => weights.unmatched.push(unmatched("no matching edge")),
+        }
+    }
+
+    Ok(weights)
+}
+
+/// Split a CSV line into trimmed fields, honoring double-quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::NavigationDesigner;
+    use crate::entity::{Context, Workflow};
+
+    fn matcher() -> EdgeMatcher {
+        let mut matcher = EdgeMatcher::new();
+        matcher.add("e1", "Login", "Dashboard");
+        matcher.add("e2", "Dashboard", "Settings");
+        matcher
+    }
+
+    #[test]
+    fn test_matcher_slugifies_input() {
+        let matcher = matcher();
+        assert_eq!(matcher.resolve("login", "DASHBOARD"), Some("e1"));
+        assert_eq!(matcher.resolve("Dashboard ", "settings"), Some("e2"));
+        assert_eq!(matcher.resolve("Settings", "Login"), None);
+    }
+
+    #[test]
+    fn test_matcher_from_canvas() {
+        let mut workflow = Workflow::new("Auth Flow").with_id("w1");
+        workflow.add_context(Context::new("Sign In").with_id("c1"));
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[&workflow]);
+        let matcher = EdgeMatcher::from_canvas(&designer.canvas);
+
+        let edge_id = designer.canvas.get_outgoing_edges("w1")[0].id.clone();
+        assert_eq!(
+            matcher.resolve("auth-flow", "sign-in"),
+            Some(edge_id.as_str())
+        );
+    }
+
+    #[test]
+    fn test_weights_from_csv() {
+        let csv = "Source,Target,Count\n\
+                   login,dashboard,120\n\
+                   \"Dashboard\",settings,30\n\
+                   login,dashboard,5\n";
+
+        let weights = edge_weights_from_csv(csv, &matcher()).unwrap();
+
+        assert_eq!(weights.get("e1"), Some(125.0));
+        assert_eq!(weights.get("e2"), Some(30.0));
+        assert!(weights.unmatched.is_empty());
+    }
+
+    #[test]
+    fn test_unmatched_rows_are_reported() {
+        let csv = "source,target,weight\n\
+                   login,dashboard,10\n\
+                   login,billing,3\n\
+                   dashboard,settings,lots\n\
+                   login,dashboard,NaN\n\
+                   login,dashboard,inf\n\
+                   login,dashboard,-4\n";
+
+        let weights = edge_weights_from_csv(csv, &matcher()).unwrap();
+
+        assert_eq!(weights.weights.len(), 1);
+        assert_eq!(weights.get("e1"), Some(10.0));
+        assert_eq!(weights.unmatched.len(), 5);
+        assert!(
+            weights.unmatched[1..]
+                .iter()
+                .all(|r| r.reason == "invalid count")
+        );
+        assert_eq!(weights.unmatched[0].line, 3);
+        assert_eq!(weights.unmatched[0].target, "billing");
+        assert_eq!(weights.unmatched[0].reason, "no matching edge");
+        assert_eq!(weights.unmatched[1].line, 4);
+        assert_eq!(weights.unmatched[1].reason, "invalid count");
+    }
+
+    #[test]
+    fn test_missing_column() {
+        let result = edge_weights_from_csv("source,target\na,b\n", &matcher());
+        assert!(matches!(result, Err(ImportError::MissingColumn(c)) if c == "count"));
+    }
+}
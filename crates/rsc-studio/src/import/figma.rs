@@ -0,0 +1,388 @@
+//! Figma page import.
+//!
+//! Reads the REST API JSON of a Figma page (or a whole file, using its first
+//! page) and turns each top-level frame into a context node. Prototype
+//! interactions that navigate between frames become edges labelled with
+//! their trigger. Only the fields the import needs are parsed.
+
+use std::collections::HashSet;
+
+use rsc_flow::{Dimensions, Edge, EdgeData, EdgeLabel, FlowCanvas, Node, NodeType, Position};
+use serde::Deserialize;
+
+use crate::designer::navigation::{EntityType, NavigationNodeData};
+use crate::perf::trace_span;
+use crate::timestamp::Timestamps;
+
+/// Scale applied to Figma coordinates when placing nodes on the canvas.
+pub const FIGMA_CANVAS_SCALE: f64 = 0.25;
+
+/// Prefix for node and edge ids created from Figma, so re-imports match.
+const ID_PREFIX: &str = "figma:";
+
+/// Non-fatal problem found while importing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportWarning {
+    /// Figma node the warning refers to, if any.
+    pub node_id: Option<String>,
+    pub message: String,
+}
+
+impl ImportWarning {
+    fn new(node_id: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            node_id: node_id.map(str::to_string),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FigmaFile {
+    document: Option<FigmaNode>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct FigmaNode {
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    node_type: String,
+    children: Vec<FigmaNode>,
+    absolute_bounding_box: Option<FigmaBox>,
+    interactions: Vec<FigmaInteraction>,
+    /// Legacy single prototype link.
+    #[serde(rename = "transitionNodeID")]
+    transition_node_id: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default)]
+struct FigmaBox {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FigmaInteraction {
+    trigger: Option<FigmaTrigger>,
+    actions: Vec<FigmaAction>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FigmaTrigger {
+    #[serde(rename = "type")]
+    trigger_type: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct FigmaAction {
+    destination_id: Option<String>,
+}
+
+impl FigmaNode {
+    /// Collect `(trigger, destination)` links from this node and its descendants.
+    fn links(&self, out: &mut Vec<(String, String)>) {
+        for interaction in &self.interactions {
+            let trigger = interaction
+                .trigger
+                .as_ref()
+                .map(|t| trigger_label(&t.trigger_type))
+                .unwrap_or_else(|| "click".to_string());
+            for action in &interaction.actions {
+                if let Some(destination) = &action.destination_id {
+                    out.push((trigger.clone(), destination.clone()));
+                }
+            }
+        }
+        if self.interactions.is_empty()
+            && let Some(destination) = &self.transition_node_id
+        {
+            out.push(("click".to_string(), destination.clone()));
+        }
+        for child in &self.children {
+            child.links(out);
+        }
+    }
+}
+
+/// Human-readable label for a Figma trigger type (`ON_CLICK` → "click").
+fn trigger_label(trigger_type: &str) -> String {
+    let label = trigger_type.strip_prefix("ON_").unwrap_or(trigger_type);
+    label.to_lowercase().replace('_', " ")
+}
+
+/// Node id for a Figma frame.
+pub fn figma_node_id(frame_id: &str) -> String {
+    format!("{}{}", ID_PREFIX, frame_id)
+}
+
+/// Build context nodes and navigation edges from a Figma page export.
+///
+/// Positions are relative to the top-left frame and scaled by
+/// [`FIGMA_CANVAS_SCALE`]. Links to frames outside the page produce
+/// warnings instead of edges.
+pub fn contexts_from_figma_json(
+    json: &str,
+) -> (
+    Vec<Node<NavigationNodeData>>,
+    Vec<Edge<EdgeData>>,
+    Vec<ImportWarning>,
+) {
+    trace_span!("import", "import_figma", bytes = json.len());
+    let mut warnings = Vec::new();
+
+    let page = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(value) => page_from_value(value),
+        Err(e) => {
+            warnings.push(ImportWarning::new(None, format!("Invalid JSON: {}", e)));
+            return (Vec::new(), Vec::new(), warnings);
+        }
+    };
+    let Some(page) = page else {
+        warnings.push(ImportWarning::new(None, "No page found in Figma export"));
+        return (Vec::new(), Vec::new(), warnings);
+    };
+
+    let frames: Vec<&FigmaNode> = page
+        .children
+        .iter()
+        .filter(|n| n.node_type == "FRAME")
+        .collect();
+    let frame_ids: HashSet<&str> = frames.iter().map(|f| f.id.as_str()).collect();
+
+    let origin = frames
+        .iter()
+        .filter_map(|f| f.absolute_bounding_box)
+        .fold(None, |acc: Option<(f64, f64)>, b| {
+            Some(acc.map_or((b.x, b.y), |(x, y)| (x.min(b.x), y.min(b.y))))
+        })
+        .unwrap_or((0.0, 0.0));
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut seen_edges = HashSet::new();
+
+    for frame in &frames {
+        let bounds = frame.absolute_bounding_box.unwrap_or_else(|| {
+            warnings.push(ImportWarning::new(
+                Some(&frame.id),
+                format!("Frame '{}' has no bounding box", frame.name),
+            ));
+            FigmaBox::default()
+        });
+        let id = figma_node_id(&frame.id);
+        nodes.push(
+            Node::new(
+                &id,
+                NodeType::Custom("context".to_string()),
+                Position::new(
+                    (bounds.x - origin.0) * FIGMA_CANVAS_SCALE,
+                    (bounds.y - origin.1) * FIGMA_CANVAS_SCALE,
+                ),
+            )
+            .with_dimensions(Dimensions::new(
+                bounds.width * FIGMA_CANVAS_SCALE,
+                bounds.height * FIGMA_CANVAS_SCALE,
+            ))
+            .with_data(NavigationNodeData {
+                entity_type: EntityType::Context,
+                entity_id: id.clone(),
+                parent_id: None,
+                label: frame.name.clone(),
+                icon: None,
+                description: None,
+                timestamps: Timestamps::default(),
+                kind: None,
+            }),
+        );
+
+        let mut links = Vec::new();
+        frame.links(&mut links);
+        for (trigger, destination) in links {
+            if !frame_ids.contains(destination.as_str()) {
+                warnings.push(ImportWarning::new(
+                    Some(&frame.id),
+                    format!(
+                        "Frame '{}' links to '{}', which is not a frame on this page",
+                        frame.name, destination
+                    ),
+                ));
+                continue;
+            }
+            let edge_id = format!("{}{}->{}", ID_PREFIX, frame.id, destination);
+            if seen_edges.insert(edge_id.clone()) {
+                let mut edge = Edge::new(edge_id, &id, figma_node_id(&destination));
+                edge.label = Some(EdgeLabel::new(trigger));
+                edges.push(edge);
+            }
+        }
+    }
+
+    (nodes, edges, warnings)
+}
+
+/// Find the page in a Figma export: either a canvas node or a file.
+fn page_from_value(value: serde_json::Value) -> Option<FigmaNode> {
+    let is_file = value.get("document").is_some();
+    if is_file {
+        let file: FigmaFile = serde_json::from_value(value).ok()?;
+        return file
+            .document?
+            .children
+            .into_iter()
+            .find(|n| n.node_type == "CANVAS");
+    }
+    serde_json::from_value(value).ok()
+}
+
+/// Merge imported Figma nodes and edges into a canvas.
+///
+/// Frames already on the canvas (matched by frame id) get their name,
+/// position and size updated; everything else about them is kept. Existing
+/// edges are left untouched so manual edits survive a re-import.
+pub fn merge_figma_import(
+    canvas: &mut FlowCanvas<NavigationNodeData, EdgeData>,
+    nodes: Vec<Node<NavigationNodeData>>,
+    edges: Vec<Edge<EdgeData>>,
+) {
+    for node in nodes {
+        match canvas.get_node_mut(&node.id) {
+            Some(existing) => {
+                existing.position = node.position;
+                existing.dimensions = node.dimensions;
+                if let (Some(data), Some(new)) = (existing.data.as_mut(), node.data) {
+                    data.label = new.label;
+                }
+            }
+            None => canvas.add_node(node),
+        }
+    }
+    for edge in edges {
+        if canvas.get_edge(&edge.id).is_none() {
+            canvas.add_edge(edge);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"{
+        "name": "Checkout",
+        "document": {
+            "id": "0:0",
+            "type": "DOCUMENT",
+            "children": [{
+                "id": "0:1",
+                "name": "Flows",
+                "type": "CANVAS",
+                "children": [
+                    {
+                        "id": "1:1",
+                        "name": "Cart",
+                        "type": "FRAME",
+                        "absoluteBoundingBox": { "x": 100, "y": 200, "width": 400, "height": 800 },
+                        "children": [{
+                            "id": "1:5",
+                            "name": "Checkout button",
+                            "type": "INSTANCE",
+                            "interactions": [{
+                                "trigger": { "type": "ON_CLICK" },
+                                "actions": [{ "type": "NODE", "destinationId": "2:1", "navigation": "NAVIGATE" }]
+                            }]
+                        }]
+                    },
+                    {
+                        "id": "2:1",
+                        "name": "Payment",
+                        "type": "FRAME",
+                        "absoluteBoundingBox": { "x": 600, "y": 200, "width": 400, "height": 800 },
+                        "interactions": [
+                            {
+                                "trigger": { "type": "AFTER_TIMEOUT", "timeout": 3 },
+                                "actions": [{ "type": "NODE", "destinationId": "1:1" }]
+                            },
+                            {
+                                "trigger": { "type": "ON_HOVER" },
+                                "actions": [{ "type": "NODE", "destinationId": "9:9" }]
+                            }
+                        ]
+                    },
+                    { "id": "3:1", "name": "Sticky note", "type": "STICKY" }
+                ]
+            }]
+        }
+    }"#;
+
+    #[test]
+    fn test_frames_become_contexts() {
+        let (nodes, edges, warnings) = contexts_from_figma_json(FIXTURE);
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].id, "figma:1:1");
+        assert_eq!(nodes[0].data.as_ref().unwrap().label, "Cart");
+        assert_eq!(nodes[0].position, Position::zero());
+        assert_eq!(nodes[1].position, Position::new(125.0, 0.0));
+        assert_eq!(nodes[1].dimensions, Some(Dimensions::new(100.0, 200.0)));
+
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0].source, "figma:1:1");
+        assert_eq!(edges[0].target, "figma:2:1");
+        assert_eq!(edges[0].label.as_ref().unwrap().text, "click");
+        assert_eq!(edges[1].label.as_ref().unwrap().text, "after timeout");
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].node_id.as_deref(), Some("2:1"));
+        assert!(warnings[0].message.contains("9:9"));
+    }
+
+    #[test]
+    fn test_reimport_updates_frames_and_keeps_edits() {
+        let (nodes, edges, _) = contexts_from_figma_json(FIXTURE);
+        let mut canvas = FlowCanvas::from_elements(nodes, edges);
+
+        let edge_id = "figma:1:1->2:1";
+        canvas.edges[edge_id].label = Some(EdgeLabel::new("cart.total > 0"));
+        canvas
+            .get_node_mut("figma:1:1")
+            .unwrap()
+            .data
+            .as_mut()
+            .unwrap()
+            .icon = Some("cart".to_string());
+
+        let renamed = FIXTURE
+            .replace("\"Cart\"", "\"Basket\"")
+            .replace("\"x\": 100", "\"x\": 0");
+        let (nodes, edges, _) = contexts_from_figma_json(&renamed);
+        merge_figma_import(&mut canvas, nodes, edges);
+
+        assert_eq!(canvas.nodes.len(), 2);
+        assert_eq!(canvas.edges.len(), 2);
+        let cart = canvas.get_node("figma:1:1").unwrap();
+        let data = cart.data.as_ref().unwrap();
+        assert_eq!(data.label, "Basket");
+        assert_eq!(data.icon.as_deref(), Some("cart"));
+        assert_eq!(canvas.get_node("figma:2:1").unwrap().position.x, 150.0);
+        assert_eq!(
+            canvas.edges[edge_id].label.as_ref().unwrap().text,
+            "cart.total > 0"
+        );
+    }
+
+    #[test]
+    fn test_invalid_json_warns() {
+        let (nodes, edges, warnings) = contexts_from_figma_json("{ not json");
+        assert!(nodes.is_empty() && edges.is_empty());
+        assert_eq!(warnings.len(), 1);
+    }
+}
@@ -0,0 +1,489 @@
+//! Navigation structure from a hand-written router.
+//!
+//! Reads Rust source shaped like the output of
+//! [`generate_router`](crate::export::generate_router): a route enum, named
+//! `Route` or the file's only enum, and a `transition` function matching on
+//! `(from, to)` pairs. Each variant becomes a context, with its fields as
+//! the context's [`PARAMS_KEY`] parameters, and each arm that lets a pair
+//! through becomes a transition. A `_` in a pair stands for every route.
+//! Arms that return `Err` or panic are refusals and are skipped; arms the
+//! import can't follow are reported as warnings instead of failing it.
+
+use indexmap::IndexSet;
+use serde_json::Value;
+use syn::{Attribute, Block, Expr, Fields, GenericArgument, Item, Pat, PathArguments, Stmt, Type};
+use thiserror::Error;
+
+use crate::entity::{Context, Workflow};
+use crate::error::{ErrorCode, StudioError};
+use crate::export::router::PARAMS_KEY;
+use crate::export::workflow::TransitionExport;
+use crate::export::{WorkflowExport, WorkflowImport};
+use crate::store::StudioStore;
+
+/// Why router source couldn't be imported at all.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RouterParseError {
+    #[error("Parse error: {0}")]
+    Syntax(String),
+
+    #[error("No route enum found")]
+    NoRouteEnum,
+}
+
+/// A match arm the import skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouterWarning {
+    /// Position of the arm in the match, from 1.
+    pub arm: usize,
+    pub message: String,
+}
+
+/// A workflow read from router source, with new ids and laid out.
+#[derive(Debug, Clone)]
+pub struct RouterImport {
+    pub import: WorkflowImport,
+    pub warnings: Vec<RouterWarning>,
+}
+
+/// Read router source into a workflow. The transitions, positions and
+/// warnings come with [`import_router_source`].
+pub fn parse_router_source(src: &str) -> Result<Workflow, RouterParseError> {
+    Ok(import_router_source(src)?.import.workflow)
+}
+
+/// Read router source into a workflow, its transitions and their
+/// auto-layout. Place them with
+/// [`NavigationDesigner::apply_import`](crate::designer::NavigationDesigner::apply_import).
+pub fn import_router_source(src: &str) -> Result<RouterImport, RouterParseError> {
+    let file = syn::parse_file(src).map_err(|e| RouterParseError::Syntax(e.to_string()))?;
+    let mut items = Vec::new();
+    collect_items(&file.items, &mut items);
+    let enums: Vec<&syn::ItemEnum> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Enum(item) => Some(item),
+            _ => None,
+        })
+        .collect();
+    let route = match enums.iter().find(|e| e.ident == "Route") {
+        Some(route) => *route,
+        None if enums.len() == 1 => enums[0],
+        None => return Err(RouterParseError::NoRouteEnum),
+    };
+
+    let name = workflow_name(&file.attrs).unwrap_or_else(|| "Imported router".to_string());
+    let mut workflow = Workflow::new(name);
+    let mut default = None;
+    for variant in &route.variants {
+        let id = variant.ident.to_string();
+        let mut context =
+            Context::new(doc(&variant.attrs).unwrap_or_else(|| id.clone())).with_id(&id);
+        let params: serde_json::Map<String, Value> = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let name = match &field.ident {
+                    Some(ident) => ident.to_string(),
+                    None => i.to_string(),
+                };
+                let ty = type_name(&field.ty).unwrap_or_else(|| "String".to_string());
+                (name, Value::from(ty))
+            })
+            .collect();
+        if !matches!(variant.fields, Fields::Unit) {
+            context
+                .metadata
+                .insert(PARAMS_KEY.to_string(), Value::Object(params));
+        }
+        if variant.attrs.iter().any(|a| a.path().is_ident("default")) {
+            default = Some(id);
+        }
+        workflow.add_context(context);
+    }
+    if default.is_some() {
+        workflow.default_context = default;
+    }
+
+    let routes: Vec<String> = workflow.contexts.keys().cloned().collect();
+    let mut warnings = Vec::new();
+    let mut pairs: IndexSet<(String, String)> = IndexSet::new();
+    let transition_fn = items.iter().find_map(|item| match item {
+        Item::Fn(f) if f.sig.ident == "transition" => Some(f),
+        _ => None,
+    });
+    if let Some(arms) = transition_fn.and_then(|f| find_match(&f.block)) {
+        for (i, arm) in arms.iter().enumerate() {
+            let warn = |message: String| RouterWarning {
+                arm: i + 1,
+                message,
+            };
+            if refuses(&arm.body) {
+                continue;
+            }
+            match arm_pairs(&arm.pat, &routes) {
+                Ok(found) => pairs.extend(found),
+                Err(message) => warnings.push(warn(message)),
+            }
+        }
+    } else {
+        warnings.push(RouterWarning {
+            arm: 0,
+            message: "No `transition` match found; only routes were imported".to_string(),
+        });
+    }
+
+    let transitions: Vec<TransitionExport> = pairs
+        .into_iter()
+        .enumerate()
+        .map(|(i, (source, target))| TransitionExport {
+            id: format!("t{}", i + 1),
+            source,
+            target,
+            label: None,
+            layer: None,
+        })
+        .collect();
+    Ok(RouterImport {
+        import: WorkflowExport::laid_out(workflow, &transitions).with_new_ids(),
+        warnings,
+    })
+}
+
+/// Items of the file and of its inline modules.
+fn collect_items<'a>(items: &'a [Item], out: &mut Vec<&'a Item>) {
+    for item in items {
+        out.push(item);
+        if let Item::Mod(module) = item
+            && let Some((_, inner)) = &module.content
+        {
+            collect_items(inner, out);
+        }
+    }
+}
+
+/// Text of the doc comments among `attrs`, trimmed.
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn doc(attrs: &[Attribute]) -> Option<String> {
+    doc_lines(attrs).into_iter().find(|line| !line.is_empty())
+}
+
+/// The name in a generated router's `Router for the "…" workflow.` header.
+fn workflow_name(attrs: &[Attribute]) -> Option<String> {
+    doc_lines(attrs).iter().find_map(|line| {
+        let name = line
+            .strip_prefix("Router for the \"")?
+            .strip_suffix("\" workflow.")?;
+        Some(name.to_string())
+    })
+}
+
+/// A type written back out, for the plain types route fields use.
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let mut segments = Vec::new();
+            for segment in &path.path.segments {
+                let mut text = segment.ident.to_string();
+                match &segment.arguments {
+                    PathArguments::None => {}
+                    PathArguments::AngleBracketed(args) => {
+                        let args = args
+                            .args
+                            .iter()
+                            .map(|arg| match arg {
+                                GenericArgument::Type(ty) => type_name(ty),
+                                _ => None,
+                            })
+                            .collect::<Option<Vec<_>>>()?;
+                        text.push_str(&format!("<{}>", args.join(", ")));
+                    }
+                    PathArguments::Parenthesized(_) => return None,
+                }
+                segments.push(text);
+            }
+            Some(segments.join("::"))
+        }
+        Type::Tuple(tuple) => {
+            let elems = tuple
+                .elems
+                .iter()
+                .map(type_name)
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("({})", elems.join(", ")))
+        }
+        Type::Paren(inner) => type_name(&inner.elem),
+        _ => None,
+    }
+}
+
+/// Arms of the first `match` in the function body.
+fn find_match(block: &Block) -> Option<&[syn::Arm]> {
+    block.stmts.iter().find_map(|stmt| match stmt {
+        Stmt::Expr(expr, _) => match_in(expr),
+        Stmt::Local(local) => local.init.as_ref().and_then(|init| match_in(&init.expr)),
+        _ => None,
+    })
+}
+
+fn match_in(expr: &Expr) -> Option<&[syn::Arm]> {
+    match expr {
+        Expr::Match(m) => Some(&m.arms),
+        Expr::Block(b) => find_match(&b.block),
+        Expr::Paren(p) => match_in(&p.expr),
+        Expr::Return(r) => r.expr.as_deref().and_then(match_in),
+        Expr::Call(call) => call.args.iter().find_map(match_in),
+        _ => None,
+    }
+}
+
+/// Macros that turn a transition down when an arm ends in one.
+const REFUSING_MACROS: [&str; 4] = ["panic", "unreachable", "todo", "unimplemented"];
+
+fn is_refusing_macro(mac: &syn::Macro) -> bool {
+    REFUSING_MACROS.iter().any(|name| mac.path.is_ident(name))
+}
+
+/// Whether an arm body turns the transition down.
+fn refuses(body: &Expr) -> bool {
+    match body {
+        Expr::Call(call) => matches!(&*call.func, Expr::Path(p) if p.path.is_ident("Err")),
+        Expr::Macro(m) => is_refusing_macro(&m.mac),
+        Expr::Block(b) => match b.block.stmts.last() {
+            Some(Stmt::Expr(expr, None)) => refuses(expr),
+            Some(Stmt::Macro(m)) => is_refusing_macro(&m.mac),
+            _ => false,
+        },
+        Expr::Paren(p) => refuses(&p.expr),
+        Expr::Return(r) => r.expr.as_deref().is_some_and(refuses),
+        _ => false,
+    }
+}
+
+/// The (from, to) pairs an arm matches.
+fn arm_pairs(pat: &Pat, routes: &[String]) -> Result<Vec<(String, String)>, String> {
+    let elems = match pat {
+        Pat::Tuple(tuple) if tuple.elems.len() == 2 => tuple,
+        Pat::Wild(_) => {
+            return Err("A catch-all arm allows every transition; it isn't drawn".to_string());
+        }
+        _ => return Err("Expected a `(from, to)` pattern".to_string()),
+    };
+    let (from, from_any) = pat_routes(&elems.elems[0], routes)?;
+    let (to, to_any) = pat_routes(&elems.elems[1], routes)?;
+    let mut pairs = Vec::new();
+    for source in &from {
+        for target in &to {
+            // A wildcard doesn't mean staying put.
+            if source == target && (from_any || to_any) {
+                continue;
+            }
+            pairs.push((source.clone(), target.clone()));
+        }
+    }
+    Ok(pairs)
+}
+
+/// Routes a pattern matches, and whether it matches any route.
+fn pat_routes(pat: &Pat, routes: &[String]) -> Result<(Vec<String>, bool), String> {
+    let variant = |path: &syn::Path| -> Result<(Vec<String>, bool), String> {
+        let ident = path
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default();
+        if routes.contains(&ident) {
+            Ok((vec![ident], false))
+        } else {
+            Err(format!("Unknown route `{}`", ident))
+        }
+    };
+    match pat {
+        Pat::Wild(_) => Ok((routes.to_vec(), true)),
+        Pat::Ident(binding) => match &binding.subpat {
+            Some((_, sub)) => pat_routes(sub, routes),
+            None => Ok((routes.to_vec(), true)),
+        },
+        Pat::Reference(r) => pat_routes(&r.pat, routes),
+        Pat::Paren(p) => pat_routes(&p.pat, routes),
+        Pat::Path(p) => variant(&p.path),
+        Pat::Struct(s) => variant(&s.path),
+        Pat::TupleStruct(t) => variant(&t.path),
+        Pat::Or(or) => {
+            let mut found = Vec::new();
+            let mut any = false;
+            for case in &or.cases {
+                let (routes, case_any) = pat_routes(case, routes)?;
+                any |= case_any;
+                for route in routes {
+                    if !found.contains(&route) {
+                        found.push(route);
+                    }
+                }
+            }
+            Ok((found, any))
+        }
+        _ => Err("Unsupported route pattern".to_string()),
+    }
+}
+
+impl From<RouterParseError> for StudioError {
+    fn from(error: RouterParseError) -> Self {
+        let hint = match error {
+            RouterParseError::Syntax(_) => "Check that the file compiles",
+            RouterParseError::NoRouteEnum => "Name the route enum `Route`",
+        };
+        StudioError::new(ErrorCode::ImportParse, error.to_string()).with_hint(hint)
+    }
+}
+
+impl StudioStore {
+    /// Import from code: add the workflow read from router source, as one
+    /// undoable edit. Draw the returned transitions on the canvas with
+    /// [`NavigationDesigner::apply_import`](crate::designer::NavigationDesigner::apply_import).
+    pub fn import_router(&mut self, src: &str) -> Result<RouterImport, StudioError> {
+        let import = self.checked(import_router_source(src))?;
+        self.add_workflow(import.import.workflow.clone());
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::NavigationDesigner;
+
+    const FIXTURE: &str = include_str!("router/fixture.rs");
+
+    #[test]
+    fn test_variants_become_contexts() {
+        let workflow = parse_router_source(FIXTURE).unwrap();
+        assert_eq!(workflow.name, "Shop");
+        let names: Vec<&str> = workflow
+            .contexts
+            .values()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["Home", "Product list", "Product", "Cart", "Order placed"]
+        );
+        let params = |name: &str| {
+            let context = workflow.contexts.values().find(|c| c.name == name).unwrap();
+            context.metadata.get(PARAMS_KEY).cloned()
+        };
+        assert_eq!(params("Home"), None);
+        assert_eq!(
+            params("Product list"),
+            Some(serde_json::json!({ "0": "String" }))
+        );
+        assert_eq!(
+            params("Product"),
+            Some(serde_json::json!({ "0": "u64", "1": "Option<String>" }))
+        );
+        assert_eq!(
+            params("Order placed"),
+            Some(serde_json::json!({ "order_id": "u64" }))
+        );
+    }
+
+    #[test]
+    fn test_arms_become_transitions() {
+        let import = import_router_source(FIXTURE).unwrap();
+        let workflow = &import.import.workflow;
+        let name = |id: &str| workflow.contexts[id].name.as_str();
+        let pairs: Vec<(&str, &str)> = import
+            .import
+            .transitions
+            .iter()
+            .filter(|t| t.source != workflow.id)
+            .map(|t| (name(&t.source), name(&t.target)))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("Home", "Product list"),
+                ("Product list", "Product"),
+                ("Product", "Cart"),
+                ("Product", "Product list"),
+                ("Cart", "Order placed"),
+                ("Product list", "Home"),
+                ("Product", "Home"),
+                ("Cart", "Home"),
+                ("Order placed", "Home"),
+            ]
+        );
+        // The unknown route is reported; the refusals are not.
+        assert_eq!(
+            import.warnings,
+            [RouterWarning {
+                arm: 6,
+                message: "Unknown route `Wishlist`".to_string(),
+            }]
+        );
+
+        let mut designer = NavigationDesigner::new();
+        designer.load_workflows(&[workflow]);
+        designer.apply_import(&import.import);
+        assert_eq!(workflow.validate(&designer.canvas), []);
+        let home = &designer.canvas.nodes[workflow.default_context.as_ref().unwrap()];
+        assert_eq!(home.data.as_ref().unwrap().label, "Home");
+        assert_ne!(
+            import.import.positions[&workflow.contexts[1].id],
+            import.import.positions[&workflow.contexts[4].id]
+        );
+    }
+
+    #[test]
+    fn test_refusing_bodies() {
+        let refuses = |body: &str| refuses(&syn::parse_str::<Expr>(body).unwrap());
+        for body in [
+            "Err(TransitionError)",
+            "todo!()",
+            "{ unreachable!() }",
+            "{ unimplemented!(); }",
+            "{ panic!(\"no\") }",
+            "return Err(e)",
+        ] {
+            assert!(refuses(body), "{}", body);
+        }
+        for body in ["Ok(())", "{ println!() }", "{ log!(); Ok(()) }"] {
+            assert!(!refuses(body), "{}", body);
+        }
+    }
+
+    #[test]
+    fn test_store_import_and_errors() {
+        let mut store = StudioStore::new();
+        let import = store.import_router(FIXTURE).unwrap();
+        assert!(store.workflows.contains_key(&import.import.workflow.id));
+
+        let error = store.import_router("pub enum {").unwrap_err();
+        assert_eq!(error.code, ErrorCode::ImportParse);
+        assert_eq!(
+            import_router_source("enum A {} enum B {}").unwrap_err(),
+            RouterParseError::NoRouteEnum
+        );
+        let wildcard = "enum Route { A, B } fn transition() { match x { _ => Ok(to) } }";
+        let import = import_router_source(wildcard).unwrap();
+        assert_eq!(import.warnings.len(), 1);
+        assert_eq!(import.warnings[0].arm, 1);
+    }
+}
@@ -0,0 +1,42 @@
+//! Router for the "Shop" workflow.
+
+pub mod router {
+    /// Routes, one per screen.
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub enum Route {
+        /// Home
+        #[default]
+        Home,
+        /// Product list
+        Catalog(String),
+        /// Product
+        Product(u64, Option<String>),
+        Cart,
+        /// Order placed
+        Confirmed { order_id: u64 },
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TransitionError {
+        pub from: Route,
+        pub to: Route,
+    }
+
+    pub fn transition(from: &Route, to: Route) -> Result<Route, TransitionError> {
+        match (from, &to) {
+            (Route::Home, Route::Catalog(_)) => Ok(to),
+            (Route::Catalog(..), Route::Product(_, _)) => Ok(to),
+            (Route::Product(..), Route::Cart | Route::Catalog(_)) => Ok(to),
+            (Route::Cart, Route::Confirmed { .. }) if can_checkout(from) => Ok(to),
+            // Home is reachable from everywhere.
+            (_, Route::Home) => Ok(to),
+            (Route::Cart, Route::Wishlist) => Ok(to),
+            (Route::Confirmed { .. }, Route::Cart) => panic!("orders are final"),
+            _ => Err(TransitionError { from: from.clone(), to }),
+        }
+    }
+
+    fn can_checkout(_from: &Route) -> bool {
+        true
+    }
+}
@@ -30,15 +30,33 @@
 //! └─────────────────────────────────────────────────────┘
 //! ```
 
+pub mod analytics;
+pub mod asset;
 pub mod config;
 pub mod designer;
 pub mod entity;
+pub mod error;
 pub mod export;
+pub mod history;
+pub mod import;
+pub mod panel;
+pub mod perf;
+pub mod presence;
+pub mod preview;
+pub mod project;
+pub mod report;
+pub mod search;
+pub mod settings;
+pub mod status;
 pub mod store;
 pub mod template;
+pub mod timestamp;
+pub mod watch;
 
 pub use config::*;
 pub use entity::*;
+pub use error::{EntityRef, ErrorCode, StudioError};
+pub use history::*;
 pub use store::*;
 
 /// Studio version.
@@ -0,0 +1,437 @@
+//! Extension point for studio panels.
+//!
+//! A panel is a [`StudioPanel`] registered in a [`PanelRegistry`]. The host
+//! UI lists the registry's panels in the activity bar and renders each
+//! panel's JSON view model, either generically or with the renderer the
+//! panel names. User input comes back as a [`PanelAction`], dispatched to
+//! the panel through the store middleware like any other action.
+//!
+//! Like the designers, the registry lives next to the store rather than
+//! in it; its methods take the store as an argument.
+
+pub mod builtin;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::Features;
+use crate::error::StudioError;
+use crate::store::StudioStore;
+use crate::store::middleware::StoreAction;
+
+pub use builtin::{DependenciesPanel, ValidationPanel};
+
+/// Order of panels registered without one.
+pub const DEFAULT_PANEL_ORDER: i32 = 100;
+
+/// Panel error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PanelError {
+    /// No panel is registered with this id.
+    #[error("Panel '{0}' not found")]
+    NotFound(String),
+
+    /// A panel with this id is already registered.
+    #[error("Panel '{0}' is already registered")]
+    Duplicate(String),
+
+    /// The panel is switched off by a feature flag.
+    #[error("Panel '{0}' is switched off")]
+    Disabled(String),
+
+    /// The panel has no handler for the action.
+    #[error("Panel '{panel}' has no action '{action}'")]
+    UnknownAction { panel: String, action: String },
+
+    /// The action payload is missing a field or has the wrong shape.
+    #[error("Invalid '{action}' action for panel '{panel}': {message}")]
+    InvalidPayload {
+        panel: String,
+        action: String,
+        message: String,
+    },
+}
+
+/// Where a panel's icon sits in the activity bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityPlacement {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// An action sent by a panel's UI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelAction {
+    pub panel: String,
+    pub action: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+impl PanelAction {
+    pub fn new(panel: impl Into<String>, action: impl Into<String>) -> Self {
+        Self {
+            panel: panel.into(),
+            action: action.into(),
+            payload: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: serde_json::Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// String field `key` of the payload.
+    pub fn field(&self, key: &str) -> Result<&str, PanelError> {
+        self.payload
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| PanelError::InvalidPayload {
+                panel: self.panel.clone(),
+                action: self.action.clone(),
+                message: format!("expected a string '{}'", key),
+            })
+    }
+
+    /// The error for an action the panel doesn't handle.
+    pub fn unknown(&self) -> PanelError {
+        PanelError::UnknownAction {
+            panel: self.panel.clone(),
+            action: self.action.clone(),
+        }
+    }
+}
+
+/// A panel contributed to the studio.
+pub trait StudioPanel: std::fmt::Debug {
+    /// Stable id, also the key of its feature flag.
+    fn id(&self) -> &str;
+
+    fn title(&self) -> &str;
+
+    fn icon(&self) -> &str;
+
+    fn placement(&self) -> ActivityPlacement {
+        ActivityPlacement::Top
+    }
+
+    /// Position in the activity bar; lower comes first.
+    fn order(&self) -> i32 {
+        DEFAULT_PANEL_ORDER
+    }
+
+    /// Renderer registered with the host UI, or `None` for the generic one.
+    fn renderer(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the panel applies with these features, on top of its own
+    /// flag in [`Features::panels`].
+    fn enabled(&self, _features: &Features) -> bool {
+        true
+    }
+
+    /// Data the UI renders.
+    fn view_model(&self, store: &StudioStore) -> serde_json::Value;
+
+    /// Handle an action from the panel's UI, changing the store through
+    /// its regular methods.
+    fn handle_action(
+        &mut self,
+        action: &PanelAction,
+        _store: &mut StudioStore,
+    ) -> Result<(), StudioError> {
+        Err(action.unknown().into())
+    }
+
+    fn on_project_loaded(&mut self, _store: &StudioStore) {}
+
+    fn on_project_closed(&mut self) {}
+}
+
+/// A panel as listed in the activity bar.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PanelInfo {
+    pub id: String,
+    pub title: String,
+    pub icon: String,
+    pub placement: ActivityPlacement,
+    pub renderer: Option<String>,
+}
+
+/// Registered panels, in activity bar order.
+#[derive(Debug, Default)]
+pub struct PanelRegistry {
+    panels: Vec<Box<dyn StudioPanel>>,
+    /// Feature flags gating the panels.
+    pub features: Features,
+}
+
+impl PanelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in panels.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for panel in [
+            Box::new(ValidationPanel) as Box<dyn StudioPanel>,
+            Box::new(DependenciesPanel),
+        ] {
+            let _ = registry.register(panel);
+        }
+        registry
+    }
+
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Add a panel, after any panels of the same order.
+    pub fn register(&mut self, panel: Box<dyn StudioPanel>) -> Result<(), PanelError> {
+        if self.panels.iter().any(|p| p.id() == panel.id()) {
+            return Err(PanelError::Duplicate(panel.id().to_string()));
+        }
+        let index = self.panels.partition_point(|p| p.order() <= panel.order());
+        self.panels.insert(index, panel);
+        Ok(())
+    }
+
+    fn is_enabled(&self, panel: &dyn StudioPanel) -> bool {
+        self.features.panel_enabled(panel.id()) && panel.enabled(&self.features)
+    }
+
+    /// Enabled panels, in order.
+    pub fn panels(&self) -> Vec<PanelInfo> {
+        self.panels
+            .iter()
+            .filter(|p| self.is_enabled(p.as_ref()))
+            .map(|p| PanelInfo {
+                id: p.id().to_string(),
+                title: p.title().to_string(),
+                icon: p.icon().to_string(),
+                placement: p.placement(),
+                renderer: p.renderer().map(str::to_string),
+            })
+            .collect()
+    }
+
+    fn enabled_panel(&mut self, id: &str) -> Result<&mut Box<dyn StudioPanel>, PanelError> {
+        let index = self
+            .panels
+            .iter()
+            .position(|p| p.id() == id)
+            .ok_or_else(|| PanelError::NotFound(id.to_string()))?;
+        if !self.is_enabled(self.panels[index].as_ref()) {
+            return Err(PanelError::Disabled(id.to_string()));
+        }
+        Ok(&mut self.panels[index])
+    }
+
+    /// View model of an enabled panel.
+    pub fn view_model(&self, id: &str, store: &StudioStore) -> Option<serde_json::Value> {
+        self.panels
+            .iter()
+            .find(|p| p.id() == id && self.is_enabled(p.as_ref()))
+            .map(|p| p.view_model(store))
+    }
+
+    /// Send an action to its panel, through the store middleware. Errors
+    /// are recorded in the store's `problems`.
+    pub fn dispatch(
+        &mut self,
+        store: &mut StudioStore,
+        action: &PanelAction,
+    ) -> Result<(), StudioError> {
+        let panel = match self.enabled_panel(&action.panel) {
+            Ok(panel) => panel,
+            Err(error) => return store.checked(Err(error)),
+        };
+        let store_action = StoreAction::Panel {
+            panel: &action.panel,
+            action: &action.action,
+        };
+        store.before_action(&store_action)?;
+        let result = panel.handle_action(action, store);
+        store.checked(result)?;
+        store.after_action(&store_action);
+        Ok(())
+    }
+
+    /// Tell enabled panels a project was loaded into `store`.
+    pub fn project_loaded(&mut self, store: &StudioStore) {
+        for panel in &mut self.panels {
+            if self.features.panel_enabled(panel.id()) && panel.enabled(&self.features) {
+                panel.on_project_loaded(store);
+            }
+        }
+    }
+
+    /// Tell enabled panels the project was closed.
+    pub fn project_closed(&mut self) {
+        for panel in &mut self.panels {
+            if self.features.panel_enabled(panel.id()) && panel.enabled(&self.features) {
+                panel.on_project_closed();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::designer::css::DesignTokens;
+    use crate::store::middleware::StoreMiddleware;
+
+    #[derive(Debug, Default)]
+    struct CounterPanel {
+        loaded: usize,
+        closed: usize,
+        clicks: usize,
+    }
+
+    impl StudioPanel for CounterPanel {
+        fn id(&self) -> &str {
+            "counter"
+        }
+
+        fn title(&self) -> &str {
+            "Counter"
+        }
+
+        fn icon(&self) -> &str {
+            "hash"
+        }
+
+        fn view_model(&self, _: &StudioStore) -> serde_json::Value {
+            json!({ "loaded": self.loaded, "closed": self.closed, "clicks": self.clicks })
+        }
+
+        fn handle_action(
+            &mut self,
+            action: &PanelAction,
+            store: &mut StudioStore,
+        ) -> Result<(), StudioError> {
+            match action.action.as_str() {
+                "click" => self.clicks += 1,
+                "reset_tokens" => store.set_tokens(DesignTokens::default()),
+                _ => return Err(action.unknown().into()),
+            }
+            Ok(())
+        }
+
+        fn on_project_loaded(&mut self, _: &StudioStore) {
+            self.loaded += 1;
+        }
+
+        fn on_project_closed(&mut self) {
+            self.closed += 1;
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl StoreMiddleware for Recorder {
+        fn before(&self, action: &StoreAction, _: &StudioStore) -> Result<(), StudioError> {
+            if let StoreAction::Panel { action: "veto", .. } = action {
+                return Err(StudioError::new(
+                    crate::error::ErrorCode::UnknownPanelAction,
+                    "vetoed",
+                ));
+            }
+            self.0.lock().unwrap().push(format!("before {:?}", action));
+            Ok(())
+        }
+
+        fn after(&self, action: &StoreAction, _: &StudioStore) {
+            self.0.lock().unwrap().push(format!("after {:?}", action));
+        }
+    }
+
+    #[test]
+    fn test_registry_order_and_gating() {
+        let mut registry = PanelRegistry::builtin();
+        registry
+            .register(Box::new(CounterPanel::default()))
+            .unwrap();
+        assert_eq!(
+            registry.register(Box::new(CounterPanel::default())),
+            Err(PanelError::Duplicate("counter".to_string()))
+        );
+        let ids = |registry: &PanelRegistry| -> Vec<String> {
+            registry.panels().into_iter().map(|p| p.id).collect()
+        };
+        assert_eq!(ids(&registry), ["validation", "dependencies", "counter"]);
+
+        registry
+            .features
+            .panels
+            .insert("counter".to_string(), false);
+        registry.features.css_designer = false;
+        assert_eq!(ids(&registry), ["validation"]);
+        let mut store = StudioStore::new();
+        let error = registry
+            .dispatch(&mut store, &PanelAction::new("counter", "click"))
+            .unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::PanelDisabled);
+        assert!(registry.view_model("counter", &store).is_none());
+    }
+
+    #[test]
+    fn test_lifecycle_and_actions_through_middleware() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut store = StudioStore::new();
+        store.add_middleware(Recorder(log.clone()));
+        let mut registry = PanelRegistry::new();
+        registry
+            .register(Box::new(CounterPanel::default()))
+            .unwrap();
+
+        registry.project_loaded(&store);
+        registry
+            .dispatch(&mut store, &PanelAction::new("counter", "click"))
+            .unwrap();
+        registry
+            .dispatch(&mut store, &PanelAction::new("counter", "reset_tokens"))
+            .unwrap();
+        assert!(
+            registry
+                .dispatch(&mut store, &PanelAction::new("counter", "veto"))
+                .is_err()
+        );
+        let unknown = registry.dispatch(&mut store, &PanelAction::new("counter", "nope"));
+        assert_eq!(
+            unknown.unwrap_err().code,
+            crate::error::ErrorCode::UnknownPanelAction
+        );
+        registry.project_closed();
+
+        assert_eq!(
+            registry.view_model("counter", &store),
+            Some(json!({ "loaded": 1, "closed": 1, "clicks": 1 }))
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                r#"before Panel { panel: "counter", action: "click" }"#,
+                r#"after Panel { panel: "counter", action: "click" }"#,
+                r#"before Panel { panel: "counter", action: "reset_tokens" }"#,
+                r#"before Commit { scope: CssDesigner, label: "Edit tokens" }"#,
+                r#"after Commit { scope: CssDesigner, label: "Edit tokens" }"#,
+                r#"after Panel { panel: "counter", action: "reset_tokens" }"#,
+                r#"before Panel { panel: "counter", action: "nope" }"#,
+            ]
+        );
+        assert_eq!(store.problems.len(), 2);
+    }
+}
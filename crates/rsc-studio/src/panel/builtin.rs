@@ -0,0 +1,193 @@
+//! Panels shipped with the studio, built on the same trait as extensions.
+
+use indexmap::IndexMap;
+use serde_json::{Value, json};
+
+use super::{PanelAction, StudioPanel};
+use crate::config::Features;
+use crate::designer::css::ValidationSeverity;
+use crate::error::StudioError;
+use crate::report::severity_name;
+use crate::store::StudioStore;
+
+/// Problems: rejected actions, token validation and style lints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidationPanel;
+
+impl StudioPanel for ValidationPanel {
+    fn id(&self) -> &str {
+        "validation"
+    }
+
+    fn title(&self) -> &str {
+        "Problems"
+    }
+
+    fn icon(&self) -> &str {
+        "alert-triangle"
+    }
+
+    fn order(&self) -> i32 {
+        10
+    }
+
+    /// Counts per severity and the findings, as the report lists them.
+    fn view_model(&self, store: &StudioStore) -> Value {
+        let findings = store.findings();
+        let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+        let items: Vec<Value> = findings
+            .iter()
+            .map(|f| {
+                json!({
+                    "code": f.code,
+                    "severity": severity_name(f.severity),
+                    "message": f.message,
+                    "entity": f.entity,
+                    "hint": f.recovery_hint,
+                })
+            })
+            .collect();
+        json!({
+            "errors": count(ValidationSeverity::Error),
+            "warnings": count(ValidationSeverity::Warning),
+            "info": count(ValidationSeverity::Info),
+            "items": items,
+        })
+    }
+
+    /// `clear` drops the recorded rejected actions.
+    fn handle_action(
+        &mut self,
+        action: &PanelAction,
+        store: &mut StudioStore,
+    ) -> Result<(), StudioError> {
+        match action.action.as_str() {
+            "clear" => store.clear_problems(),
+            _ => return Err(action.unknown().into()),
+        }
+        Ok(())
+    }
+}
+
+/// Token references: what each token uses and what uses it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DependenciesPanel;
+
+impl StudioPanel for DependenciesPanel {
+    fn id(&self) -> &str {
+        "dependencies"
+    }
+
+    fn title(&self) -> &str {
+        "Dependencies"
+    }
+
+    fn icon(&self) -> &str {
+        "git-branch"
+    }
+
+    fn order(&self) -> i32 {
+        20
+    }
+
+    fn renderer(&self) -> Option<&str> {
+        Some("token-graph")
+    }
+
+    fn enabled(&self, features: &Features) -> bool {
+        features.css_designer
+    }
+
+    /// Tokens that reference or are referenced by others, in token order.
+    fn view_model(&self, store: &StudioStore) -> Value {
+        let uses = store.tokens.find_dependencies();
+        let mut used_by: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for (path, refs) in &uses {
+            for target in refs {
+                used_by.entry(target).or_default().push(path);
+            }
+        }
+        let tokens: Vec<Value> = store
+            .tokens
+            .all_paths()
+            .iter()
+            .filter(|path| uses.contains_key(*path) || used_by.contains_key(path.as_str()))
+            .map(|path| {
+                json!({
+                    "path": path,
+                    "uses": uses.get(path).cloned().unwrap_or_default(),
+                    "used_by": used_by.get(path.as_str()).cloned().unwrap_or_default(),
+                })
+            })
+            .collect();
+        json!({ "tokens": tokens })
+    }
+
+    /// `rename` renames the token at `path` to `name`, rewriting its
+    /// references.
+    fn handle_action(
+        &mut self,
+        action: &PanelAction,
+        store: &mut StudioStore,
+    ) -> Result<(), StudioError> {
+        match action.action.as_str() {
+            "rename" => {
+                let path = action.field("path")?;
+                let name = action.field("name")?;
+                store.rename_token(path, name)?;
+            }
+            _ => return Err(action.unknown().into()),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{DesignTokens, TokenValue};
+    use crate::panel::PanelRegistry;
+
+    #[test]
+    fn test_dependencies_panel() {
+        let mut store = StudioStore::new();
+        let mut tokens = DesignTokens::default();
+        for (name, value) in [
+            ("primary", "#3b82f6"),
+            ("link", "var(--color-primary)"),
+            ("text", "#0f172a"),
+        ] {
+            tokens
+                .colors
+                .insert(name.to_string(), TokenValue::Simple(value.to_string()));
+        }
+        store.set_tokens(tokens);
+        let mut registry = PanelRegistry::builtin();
+
+        assert_eq!(
+            registry.view_model("dependencies", &store).unwrap()["tokens"],
+            json!([
+                { "path": "colors.primary", "uses": [], "used_by": ["colors.link"] },
+                { "path": "colors.link", "uses": ["colors.primary"], "used_by": [] },
+            ])
+        );
+
+        let rename = PanelAction::new("dependencies", "rename")
+            .with_payload(json!({ "path": "colors.primary", "name": "brand" }));
+        registry.dispatch(&mut store, &rename).unwrap();
+        assert_eq!(
+            store.tokens.colors["link"],
+            TokenValue::Simple("var(--color-brand)".to_string())
+        );
+        let missing = PanelAction::new("dependencies", "rename").with_payload(json!({}));
+        assert!(registry.dispatch(&mut store, &missing).is_err());
+
+        let problems = registry.view_model("validation", &store).unwrap();
+        assert_eq!(problems["errors"], 1);
+        assert_eq!(problems["items"][0]["code"], "invalid_panel_action");
+        registry
+            .dispatch(&mut store, &PanelAction::new("validation", "clear"))
+            .unwrap();
+        assert!(store.problems.is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+//! Timing of slow studio operations.
+//!
+//! With the `tracing` feature, layout runs, CSS generation, import and
+//! export, store commits and drags open spans with a `category` field and
+//! shared field names (`entity_id`, `count`, `nodes`, `edges`). A
+//! [`PerfCollector`] installed as the subscriber sums span lifetimes per
+//! category into a [`PerfSummary`] for the devtools inspector. Without the
+//! feature the spans compile to nothing and the summary stays empty.
+
+use std::time::Duration;
+
+use indexmap::IndexMap;
+
+/// Enter a span until the end of the enclosing block; see the module
+/// docs. Expands to nothing without the `tracing` feature.
+macro_rules! trace_span {
+    ($category:literal, $name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name, category = $category $(, $($fields)*)?).entered();
+    };
+}
+
+pub(crate) use trace_span;
+
+/// Timings of one category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CategoryTiming {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl CategoryTiming {
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.total / count,
+            Err(_) => Duration::from_secs_f64(self.total.as_secs_f64() / self.count as f64),
+        }
+    }
+}
+
+/// Span timings per category, in first-seen order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerfSummary {
+    pub categories: IndexMap<String, CategoryTiming>,
+}
+
+impl PerfSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one finished span.
+    pub fn record(&mut self, category: &str, duration: Duration) {
+        let timing = self.categories.entry(category.to_string()).or_default();
+        timing.count += 1;
+        timing.total += duration;
+        timing.max = timing.max.max(duration);
+    }
+
+    pub fn get(&self, category: &str) -> Option<&CategoryTiming> {
+        self.categories.get(category)
+    }
+
+    /// Categories by total time, slowest first.
+    pub fn slowest(&self) -> Vec<(&str, &CategoryTiming)> {
+        let mut rows: Vec<_> = self
+            .categories
+            .iter()
+            .map(|(name, timing)| (name.as_str(), timing))
+            .collect();
+        rows.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.total));
+        rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.categories.clear();
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub use collector::PerfCollector;
+
+#[cfg(feature = "tracing")]
+mod collector {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex, PoisonError};
+    use std::time::Instant;
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::PerfSummary;
+
+    #[derive(Debug)]
+    struct OpenSpan {
+        category: String,
+        started: Instant,
+        refs: usize,
+    }
+
+    #[derive(Debug, Default)]
+    struct State {
+        next_id: u64,
+        open: HashMap<u64, OpenSpan>,
+        summary: PerfSummary,
+    }
+
+    /// Subscriber that times spans from creation to close and adds them
+    /// to a [`PerfSummary`] under their `category` field, or their name
+    /// when they have none. Clones share one summary.
+    #[derive(Debug, Clone, Default)]
+    pub struct PerfCollector {
+        state: Arc<Mutex<State>>,
+    }
+
+    struct CategoryVisitor(Option<String>);
+
+    impl Visit for CategoryVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "category" {
+                self.0 = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "category" {
+                self.0 = Some(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl PerfCollector {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        fn state(&self) -> std::sync::MutexGuard<'_, State> {
+            self.state.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        /// Timings of every span closed so far.
+        pub fn summary(&self) -> PerfSummary {
+            self.state().summary.clone()
+        }
+
+        /// Forget the timings collected so far.
+        pub fn reset(&self) {
+            self.state().summary.clear();
+        }
+    }
+
+    impl Subscriber for PerfCollector {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut visitor = CategoryVisitor(None);
+            attrs.record(&mut visitor);
+            let mut state = self.state();
+            state.next_id += 1;
+            let id = state.next_id;
+            state.open.insert(
+                id,
+                OpenSpan {
+                    category: visitor
+                        .0
+                        .unwrap_or_else(|| attrs.metadata().name().to_string()),
+                    started: Instant::now(),
+                    refs: 1,
+                },
+            );
+            Id::from_u64(id)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+
+        fn clone_span(&self, id: &Id) -> Id {
+            if let Some(span) = self.state().open.get_mut(&id.into_u64()) {
+                span.refs += 1;
+            }
+            id.clone()
+        }
+
+        fn try_close(&self, id: Id) -> bool {
+            let mut state = self.state();
+            let key = id.into_u64();
+            let Some(span) = state.open.get_mut(&key) else {
+                return false;
+            };
+            span.refs -= 1;
+            if span.refs > 0 {
+                return false;
+            }
+            let span = state.open.remove(&key).expect("span is open");
+            state.summary.record(&span.category, span.started.elapsed());
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_aggregates_per_category() {
+        let mut summary = PerfSummary::new();
+        summary.record("layout", Duration::from_millis(4));
+        summary.record("css", Duration::from_millis(30));
+        summary.record("layout", Duration::from_millis(10));
+
+        let layout = summary.get("layout").unwrap();
+        assert_eq!(layout.count, 2);
+        assert_eq!(layout.total, Duration::from_millis(14));
+        assert_eq!(layout.max, Duration::from_millis(10));
+        assert_eq!(layout.mean(), Duration::from_millis(7));
+        let order: Vec<_> = summary.slowest().into_iter().map(|(c, _)| c).collect();
+        assert_eq!(order, ["css", "layout"]);
+        assert_eq!(CategoryTiming::default().mean(), Duration::ZERO);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_collector_times_studio_spans() {
+        use crate::designer::NavigationDesigner;
+        use crate::entity::{Context, Workflow};
+        use crate::store::StudioStore;
+
+        let collector = PerfCollector::new();
+        tracing::subscriber::with_default(collector.clone(), || {
+            let mut workflow = Workflow::new("Checkout").with_id("w1");
+            workflow.add_context(Context::new("Cart").with_id("c1"));
+            let mut store = StudioStore::new();
+            store.add_workflow(workflow);
+            let mut designer = NavigationDesigner::new();
+            designer.load_workflows(&store.workflows.values().collect::<Vec<_>>());
+            designer.apply_layout();
+            let _ = crate::designer::CssDesigner::new().generate_css();
+        });
+
+        let summary = collector.summary();
+        assert_eq!(summary.get("layout").map(|t| t.count), Some(2));
+        assert_eq!(summary.get("store").map(|t| t.count), Some(1));
+        assert_eq!(summary.get("css").map(|t| t.count), Some(1));
+        let layout = summary.get("layout").unwrap();
+        assert!(layout.total >= layout.max);
+
+        collector.reset();
+        assert!(collector.summary().is_empty());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_drag_span_closes_on_drop() {
+        use rsc_dnd::DndContext;
+
+        let collector = PerfCollector::new();
+        tracing::subscriber::with_default(collector.clone(), || {
+            let mut dnd = DndContext::new();
+            dnd.start_drag("card");
+            assert!(collector.summary().get("dnd").is_none());
+            dnd.end_drag();
+            dnd.start_drag("card");
+            dnd.cancel_drag();
+        });
+        assert_eq!(collector.summary().get("dnd").map(|t| t.count), Some(2));
+    }
+}
@@ -0,0 +1,269 @@
+//! Presence of remote participants on the canvas.
+//!
+//! Presence is session state only: it is never serialized, so it can't leak
+//! into exported or saved projects. Updates arrive through
+//! [`StudioStore::apply_presence_update`]; the transport is up to the caller.
+
+use indexmap::IndexMap;
+use rsc_flow::Position;
+
+use crate::store::StudioStore;
+
+/// Default time without updates before a participant is dropped, in milliseconds.
+pub const DEFAULT_PRESENCE_TIMEOUT_MS: u64 = 30_000;
+
+/// A remote collaborator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub id: String,
+    pub name: String,
+    /// Color used for the cursor and selection outline.
+    pub color: String,
+}
+
+impl Participant {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, color: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            color: color.into(),
+        }
+    }
+}
+
+/// Partial presence update; `None` fields are left unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct PresenceUpdate {
+    /// Cursor position in world coordinates; `Some(None)` hides the cursor.
+    pub cursor: Option<Option<Position>>,
+    /// Selected node ids.
+    pub selection: Option<Vec<String>>,
+    /// Whether the participant is dragging.
+    pub dragging: Option<bool>,
+}
+
+impl PresenceUpdate {
+    pub fn cursor(position: Position) -> Self {
+        Self {
+            cursor: Some(Some(position)),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_selection(mut self, selection: Vec<String>) -> Self {
+        self.selection = Some(selection);
+        self
+    }
+
+    pub fn with_dragging(mut self, dragging: bool) -> Self {
+        self.dragging = Some(dragging);
+        self
+    }
+}
+
+/// Last known state of a participant.
+#[derive(Debug, Clone)]
+pub struct ParticipantState {
+    pub participant: Participant,
+    pub cursor: Option<Position>,
+    pub selection: Vec<String>,
+    pub dragging: bool,
+    /// Clock value of the last update.
+    pub last_seen: u64,
+}
+
+/// Remote cursor to draw on the canvas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteCursor<'a> {
+    pub participant: &'a Participant,
+    pub position: Position,
+    pub dragging: bool,
+}
+
+/// Remote participants and their cursors and selections.
+#[derive(Debug, Clone)]
+pub struct Presence {
+    participants: IndexMap<String, ParticipantState>,
+    /// Current clock in milliseconds, advanced by [`Presence::tick`].
+    now: u64,
+    /// Time without updates before a participant expires.
+    pub timeout_ms: u64,
+}
+
+impl Default for Presence {
+    fn default() -> Self {
+        Self {
+            participants: IndexMap::new(),
+            now: 0,
+            timeout_ms: DEFAULT_PRESENCE_TIMEOUT_MS,
+        }
+    }
+}
+
+impl Presence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply an update, adding the participant if needed.
+    pub fn apply_update(&mut self, participant: Participant, update: PresenceUpdate) {
+        let now = self.now;
+        let state = self
+            .participants
+            .entry(participant.id.clone())
+            .or_insert_with(|| ParticipantState {
+                participant: participant.clone(),
+                cursor: None,
+                selection: Vec::new(),
+                dragging: false,
+                last_seen: now,
+            });
+        state.participant = participant;
+        state.last_seen = now;
+        if let Some(cursor) = update.cursor {
+            state.cursor = cursor;
+        }
+        if let Some(selection) = update.selection {
+            state.selection = selection;
+        }
+        if let Some(dragging) = update.dragging {
+            state.dragging = dragging;
+        }
+    }
+
+    /// Advance the clock and drop participants not seen within the timeout.
+    ///
+    /// Returns the ids of expired participants.
+    pub fn tick(&mut self, now: u64) -> Vec<String> {
+        self.now = now;
+        let timeout = self.timeout_ms;
+        let mut expired = Vec::new();
+        self.participants.retain(|id, state| {
+            let alive = now.saturating_sub(state.last_seen) <= timeout;
+            if !alive {
+                expired.push(id.clone());
+            }
+            alive
+        });
+        expired
+    }
+
+    /// Remove a participant immediately (e.g. on disconnect).
+    pub fn remove(&mut self, id: &str) -> Option<ParticipantState> {
+        self.participants.shift_remove(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ParticipantState> {
+        self.participants.get(id)
+    }
+
+    pub fn participants(&self) -> impl Iterator<Item = &ParticipantState> {
+        self.participants.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.participants.is_empty()
+    }
+
+    /// Cursors of participants whose cursor is on the canvas.
+    pub fn cursors(&self) -> Vec<RemoteCursor<'_>> {
+        self.participants
+            .values()
+            .filter_map(|s| {
+                Some(RemoteCursor {
+                    participant: &s.participant,
+                    position: s.cursor?,
+                    dragging: s.dragging,
+                })
+            })
+            .collect()
+    }
+
+    /// Participants that have a node selected.
+    pub fn selected_by(&self, node_id: &str) -> Vec<&Participant> {
+        self.participants
+            .values()
+            .filter(|s| s.selection.iter().any(|id| id == node_id))
+            .map(|s| &s.participant)
+            .collect()
+    }
+}
+
+impl StudioStore {
+    /// Apply a presence update from a remote participant.
+    pub fn apply_presence_update(&mut self, participant: Participant, update: PresenceUpdate) {
+        self.presence.apply_update(participant, update);
+    }
+
+    /// Advance the presence clock, expiring stale participants.
+    pub fn tick_presence(&mut self, now: u64) -> Vec<String> {
+        self.presence.tick(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Workflow;
+    use crate::config::StudioConfig;
+    use crate::export::export_to_yaml;
+    use crate::project::ProjectFile;
+
+    fn alice() -> Participant {
+        Participant::new("u1", "Alice", "#f97316")
+    }
+
+    #[test]
+    fn test_stale_participants_expire() {
+        let mut store = StudioStore::new();
+        store.presence.timeout_ms = 1_000;
+
+        store.apply_presence_update(alice(), PresenceUpdate::cursor(Position::new(10.0, 20.0)));
+        store.tick_presence(500);
+        store.apply_presence_update(
+            Participant::new("u2", "Bob", "#22c55e"),
+            PresenceUpdate::default().with_selection(vec!["n1".to_string()]),
+        );
+
+        assert!(store.tick_presence(1_000).is_empty());
+        assert_eq!(store.tick_presence(1_200), vec!["u1".to_string()]);
+        assert!(store.presence.get("u1").is_none());
+        assert_eq!(store.presence.selected_by("n1")[0].name, "Bob");
+        assert!(store.presence.cursors().is_empty());
+    }
+
+    #[test]
+    fn test_partial_updates() {
+        let mut presence = Presence::new();
+        presence.apply_update(
+            alice(),
+            PresenceUpdate::cursor(Position::new(1.0, 2.0)).with_dragging(true),
+        );
+        presence.apply_update(
+            alice(),
+            PresenceUpdate::default().with_selection(vec!["n2".to_string()]),
+        );
+
+        let cursors = presence.cursors();
+        assert_eq!(cursors.len(), 1);
+        assert_eq!(cursors[0].position, Position::new(1.0, 2.0));
+        assert!(cursors[0].dragging);
+        assert_eq!(presence.selected_by("n2").len(), 1);
+    }
+
+    #[test]
+    fn test_presence_not_exported() {
+        let mut store = StudioStore::new();
+        store.add_workflow(Workflow::new("Main"));
+        store.apply_presence_update(alice(), PresenceUpdate::cursor(Position::zero()));
+
+        let yaml = export_to_yaml(&store).unwrap();
+        assert!(!yaml.contains("Alice"));
+        assert!(!yaml.contains("#f97316"));
+
+        let project = ProjectFile::from_store(&store, &StudioConfig::default());
+        let json = project.to_json().unwrap();
+        assert!(!json.contains("Alice"));
+        assert!(!json.contains("#f97316"));
+    }
+}
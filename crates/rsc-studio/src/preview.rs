@@ -0,0 +1,406 @@
+//! Live preview frames.
+//!
+//! The preview panel shows the same markup in several frames side by side:
+//! plain rectangles at each breakpoint width, or device frames with chrome
+//! and simulated safe areas. Browsers only report real `env(safe-area-inset-*)`
+//! values on devices, so the preview rewrites them to custom properties that
+//! each device frame sets from its preset.
+
+pub mod component;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::designer::css::Breakpoint;
+
+pub use component::{PreviewDocument, render_component_preview};
+
+/// Frame width used for [`Breakpoint::Base`], which has no minimum width.
+pub const BASE_FRAME_WIDTH: u32 = 360;
+
+/// Safe-area insets in CSS pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafeAreaInsets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl SafeAreaInsets {
+    pub fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Side name and inset, in CSS order.
+    pub fn sides(&self) -> [(&'static str, u32); 4] {
+        [
+            ("top", self.top),
+            ("right", self.right),
+            ("bottom", self.bottom),
+            ("left", self.left),
+        ]
+    }
+}
+
+/// A device the preview can frame content in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevicePreset {
+    /// Display name.
+    pub label: String,
+    /// Viewport width in CSS pixels.
+    pub width: u32,
+    /// Viewport height in CSS pixels.
+    pub height: u32,
+    /// Pixel density note shown in the caption (e.g. "@3x").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub density: Option<String>,
+    /// Safe-area insets in portrait orientation.
+    #[serde(default)]
+    pub safe_area: SafeAreaInsets,
+    /// Draw a notch in the top inset.
+    #[serde(default)]
+    pub notch: bool,
+    /// Draw a home indicator in the bottom inset.
+    #[serde(default)]
+    pub home_indicator: bool,
+}
+
+impl DevicePreset {
+    pub fn new(label: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            label: label.into(),
+            width,
+            height,
+            density: None,
+            safe_area: SafeAreaInsets::default(),
+            notch: false,
+            home_indicator: false,
+        }
+    }
+
+    pub fn with_density(mut self, density: impl Into<String>) -> Self {
+        self.density = Some(density.into());
+        self
+    }
+
+    pub fn with_safe_area(mut self, safe_area: SafeAreaInsets) -> Self {
+        self.safe_area = safe_area;
+        self
+    }
+
+    pub fn with_notch(mut self, notch: bool) -> Self {
+        self.notch = notch;
+        self
+    }
+
+    pub fn with_home_indicator(mut self, home_indicator: bool) -> Self {
+        self.home_indicator = home_indicator;
+        self
+    }
+}
+
+/// Built-in device presets, keyed by id.
+pub fn builtin_devices() -> IndexMap<String, DevicePreset> {
+    IndexMap::from([
+        (
+            "phone".to_string(),
+            DevicePreset::new("Phone", 390, 844)
+                .with_density("@3x")
+                .with_safe_area(SafeAreaInsets::new(47, 0, 34, 0))
+                .with_notch(true)
+                .with_home_indicator(true),
+        ),
+        (
+            "android".to_string(),
+            DevicePreset::new("Android", 412, 915)
+                .with_density("@2.625x")
+                .with_safe_area(SafeAreaInsets::new(24, 0, 16, 0))
+                .with_home_indicator(true),
+        ),
+        (
+            "tablet".to_string(),
+            DevicePreset::new("Tablet", 820, 1180)
+                .with_density("@2x")
+                .with_safe_area(SafeAreaInsets::new(24, 0, 20, 0))
+                .with_home_indicator(true),
+        ),
+    ])
+}
+
+/// What a preview frame shows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameKind {
+    /// A plain rectangle at a breakpoint width.
+    Breakpoint(Breakpoint),
+    /// A device viewport with safe areas.
+    Device { id: String, preset: DevicePreset },
+}
+
+/// One frame in the preview panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewFrame {
+    pub kind: FrameKind,
+    /// Draw the notch and home indicator of device frames.
+    pub show_chrome: bool,
+}
+
+impl PreviewFrame {
+    pub fn breakpoint(breakpoint: Breakpoint) -> Self {
+        Self {
+            kind: FrameKind::Breakpoint(breakpoint),
+            show_chrome: true,
+        }
+    }
+
+    pub fn device(id: impl Into<String>, preset: DevicePreset) -> Self {
+        Self {
+            kind: FrameKind::Device {
+                id: id.into(),
+                preset,
+            },
+            show_chrome: true,
+        }
+    }
+
+    pub fn with_chrome(mut self, show_chrome: bool) -> Self {
+        self.show_chrome = show_chrome;
+        self
+    }
+
+    /// One frame per breakpoint.
+    pub fn all_breakpoints() -> Vec<Self> {
+        Breakpoint::all()
+            .iter()
+            .map(|&b| Self::breakpoint(b))
+            .collect()
+    }
+
+    /// Viewport width in CSS pixels.
+    pub fn width(&self) -> u32 {
+        match &self.kind {
+            FrameKind::Breakpoint(b) => b.min_width().unwrap_or(BASE_FRAME_WIDTH),
+            FrameKind::Device { preset, .. } => preset.width,
+        }
+    }
+
+    /// Viewport height; breakpoint frames grow with their content.
+    pub fn height(&self) -> Option<u32> {
+        match &self.kind {
+            FrameKind::Breakpoint(_) => None,
+            FrameKind::Device { preset, .. } => Some(preset.height),
+        }
+    }
+
+    /// Caption under the frame.
+    pub fn caption(&self) -> String {
+        match &self.kind {
+            FrameKind::Breakpoint(b) => format!("{} · {}px", b.label(), self.width()),
+            FrameKind::Device { preset, .. } => {
+                let mut caption = format!("{} · {}×{}", preset.label, preset.width, preset.height);
+                if let Some(density) = &preset.density {
+                    caption.push_str(&format!(" · {}", density));
+                }
+                caption
+            }
+        }
+    }
+
+    /// Inline custom properties for the frame.
+    fn style(&self) -> String {
+        let mut style = format!("--frame-width: {}px;", self.width());
+        if let Some(height) = self.height() {
+            style.push_str(&format!(" --frame-height: {}px;", height));
+        }
+        if let FrameKind::Device { preset, .. } = &self.kind {
+            for (side, inset) in preset.safe_area.sides() {
+                style.push_str(&format!(" --safe-area-inset-{}: {}px;", side, inset));
+            }
+        }
+        style
+    }
+
+    /// Wrapper markup around `body`.
+    pub fn render(&self, body: &str) -> String {
+        let (class, data) = match &self.kind {
+            FrameKind::Breakpoint(b) => (
+                "preview-frame",
+                format!("data-breakpoint=\"{}\"", b.label().to_lowercase()),
+            ),
+            FrameKind::Device { id, .. } => (
+                "preview-frame preview-device",
+                format!("data-device=\"{}\"", escape(id)),
+            ),
+        };
+        let mut html = format!(
+            "<figure class=\"{}\" {} style=\"{}\">\n<div class=\"preview-screen\">\n",
+            class,
+            data,
+            self.style()
+        );
+        let device = match &self.kind {
+            FrameKind::Device { preset, .. } => Some(preset),
+            FrameKind::Breakpoint(_) => None,
+        };
+        if let Some(preset) = device
+            && preset.notch
+            && self.show_chrome
+        {
+            html.push_str("<div class=\"preview-notch\"></div>\n");
+        }
+        html.push_str("<div class=\"preview-viewport\">\n");
+        html.push_str(body);
+        if !body.ends_with('\n') {
+            html.push('\n');
+        }
+        html.push_str("</div>\n");
+        if let Some(preset) = device {
+            html.push_str("<div class=\"preview-safe-area\" aria-hidden=\"true\"></div>\n");
+            if preset.home_indicator && self.show_chrome {
+                html.push_str("<div class=\"preview-home-indicator\"></div>\n");
+            }
+        }
+        html.push_str(&format!(
+            "</div>\n<figcaption>{}</figcaption>\n</figure>\n",
+            escape(&self.caption())
+        ));
+        html
+    }
+}
+
+/// Rewrite `env(safe-area-inset-*)` to the frame's custom properties.
+pub fn simulate_safe_area(css: &str) -> String {
+    css.replace("env(safe-area-inset-", "var(--safe-area-inset-")
+}
+
+const FRAME_CSS: &str = "\
+.preview-frames { display: flex; flex-wrap: wrap; gap: 2rem; align-items: flex-start; }
+.preview-frame { margin: 0; }
+.preview-screen { position: relative; width: var(--frame-width); height: var(--frame-height, auto); }
+.preview-viewport { box-sizing: border-box; width: 100%; height: 100%; overflow: auto; \
+border: 1px solid #cbd5e1; }
+.preview-device .preview-screen { outline: 12px solid #0f172a; border-radius: 36px; overflow: hidden; }
+.preview-device .preview-viewport { border: none; }
+.preview-safe-area { position: absolute; inset: 0; pointer-events: none; box-sizing: border-box; \
+border: solid rgba(239, 68, 68, .2); border-width: var(--safe-area-inset-top) \
+var(--safe-area-inset-right) var(--safe-area-inset-bottom) var(--safe-area-inset-left); }
+.preview-notch { position: absolute; top: 0; left: 50%; transform: translateX(-50%); \
+width: 40%; height: calc(var(--safe-area-inset-top) - 10px); background: #0f172a; \
+border-radius: 0 0 16px 16px; z-index: 1; }
+.preview-home-indicator { position: absolute; bottom: 8px; left: 50%; transform: translateX(-50%); \
+width: 35%; height: 5px; border-radius: 3px; background: #0f172a; z-index: 1; }
+figcaption { font: 12px system-ui, sans-serif; color: #64748b; margin-top: .5rem; }
+";
+
+/// A preview page showing `body` in every frame, styled by `css`.
+pub fn preview_html(css: &str, body: &str, frames: &[PreviewFrame]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n<style>\n");
+    html.push_str(FRAME_CSS);
+    html.push_str(&simulate_safe_area(css));
+    if !css.is_empty() && !css.ends_with('\n') {
+        html.push('\n');
+    }
+    html.push_str("</style>\n</head>\n<body>\n<div class=\"preview-frames\">\n");
+    for frame in frames {
+        html.push_str(&frame.render(body));
+    }
+    html.push_str("</div>\n</body>\n</html>\n");
+    html
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PreviewConfig;
+
+    #[test]
+    fn test_device_frame_snapshot() {
+        let phone = builtin_devices()["phone"].clone();
+        let frame = PreviewFrame::device("phone", phone);
+        assert_eq!(
+            frame.render("<button>Buy</button>"),
+            "<figure class=\"preview-frame preview-device\" data-device=\"phone\" \
+style=\"--frame-width: 390px; --frame-height: 844px; --safe-area-inset-top: 47px; \
+--safe-area-inset-right: 0px; --safe-area-inset-bottom: 34px; --safe-area-inset-left: 0px;\">
+<div class=\"preview-screen\">
+<div class=\"preview-notch\"></div>
+<div class=\"preview-viewport\">
+<button>Buy</button>
+</div>
+<div class=\"preview-safe-area\" aria-hidden=\"true\"></div>
+<div class=\"preview-home-indicator\"></div>
+</div>
+<figcaption>Phone · 390×844 · @3x</figcaption>
+</figure>
+"
+        );
+
+        // Without chrome the safe-area overlay stays.
+        let bare = frame.with_chrome(false).render("x");
+        assert!(!bare.contains("preview-notch"));
+        assert!(!bare.contains("preview-home-indicator"));
+        assert!(bare.contains("preview-safe-area"));
+    }
+
+    #[test]
+    fn test_safe_area_variables() {
+        let css = ".bar { padding-bottom: env(safe-area-inset-bottom, 8px); }";
+        assert_eq!(
+            simulate_safe_area(css),
+            ".bar { padding-bottom: var(--safe-area-inset-bottom, 8px); }"
+        );
+
+        let frames = [
+            PreviewFrame::breakpoint(Breakpoint::Md),
+            PreviewFrame::device("tablet", builtin_devices()["tablet"].clone()),
+        ];
+        let html = preview_html(css, "<nav class=\"bar\"></nav>", &frames);
+        assert!(html.contains("var(--safe-area-inset-bottom, 8px)"));
+        assert!(!html.contains("env("));
+        // Breakpoint frames are plain rectangles without insets.
+        assert!(html.contains(
+            "<figure class=\"preview-frame\" data-breakpoint=\"md\" style=\"--frame-width: 768px;\">"
+        ));
+        assert!(html.contains("--safe-area-inset-top: 24px; --safe-area-inset-right: 0px; --safe-area-inset-bottom: 20px;"));
+        assert_eq!(html.matches("<nav class=\"bar\"></nav>").count(), 2);
+    }
+
+    #[test]
+    fn test_devices_from_config() {
+        let config: PreviewConfig = serde_json::from_str(
+            r#"{"devices": {
+                "phone": {"label": "Small phone", "width": 375, "height": 667},
+                "foldable": {"label": "Foldable", "width": 673, "height": 841,
+                    "density": "@2.5x", "safe_area": {"top": 32}}
+            }}"#,
+        )
+        .unwrap();
+        let devices = config.devices();
+        let ids: Vec<&str> = devices.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["phone", "android", "tablet", "foldable"]);
+        assert_eq!(devices["phone"].width, 375);
+        assert!(!devices["phone"].notch);
+        assert_eq!(
+            devices["foldable"].safe_area,
+            SafeAreaInsets::new(32, 0, 0, 0)
+        );
+
+        let frame = config.frame("foldable").unwrap();
+        assert_eq!(frame.caption(), "Foldable · 673×841 · @2.5x");
+        assert!(config.frame("watch").is_none());
+    }
+}
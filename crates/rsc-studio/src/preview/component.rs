@@ -0,0 +1,266 @@
+//! Live component previews.
+//!
+//! Renders one built-in component with its current style in a chosen state
+//! and breakpoint. Token references are replaced with their values so the
+//! document works in an iframe without the theme variables, and states are
+//! forced through a class (as in the component catalog) rather than real
+//! hover or focus.
+
+use indexmap::IndexMap;
+
+use super::{PreviewFrame, escape, preview_html};
+use crate::designer::css::extract::inline_vars;
+use crate::designer::css::{
+    Breakpoint, ComponentStyle, ComponentType, DesignTokens, StateVariant, StyleProperties,
+};
+
+/// A rendered component preview.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewDocument {
+    pub component: ComponentType,
+    pub state: StateVariant,
+    pub breakpoint: Breakpoint,
+    /// Rules for the component, with token references resolved.
+    pub css: String,
+    /// Sample markup.
+    pub html: String,
+}
+
+impl PreviewDocument {
+    /// A full page showing the sample in a frame at the breakpoint width.
+    pub fn to_html(&self) -> String {
+        preview_html(
+            &self.css,
+            &self.html,
+            &[PreviewFrame::breakpoint(self.breakpoint)],
+        )
+    }
+}
+
+/// Class of the preview element, e.g. `button`.
+pub fn component_class(component: ComponentType) -> String {
+    component.label().to_lowercase()
+}
+
+/// Class that forces a state's styles, e.g. `state-hover`.
+pub fn state_class(state: StateVariant) -> String {
+    format!("state-{}", state.label().to_lowercase())
+}
+
+/// Render `component` as it looks in `state` at `breakpoint`.
+///
+/// Breakpoint overrides up to `breakpoint` apply without media queries, so
+/// the preview matches the breakpoint whatever the iframe width. Pass a
+/// style with its `extends` chain already resolved.
+pub fn render_component_preview(
+    component: ComponentType,
+    style: &ComponentStyle,
+    tokens: &DesignTokens,
+    state: StateVariant,
+    breakpoint: Breakpoint,
+) -> PreviewDocument {
+    let class = component_class(component);
+    let mut css = String::new();
+    push_rule(&mut css, &format!(".{}", class), &style.base);
+    let width = breakpoint.min_width().unwrap_or(0);
+    for bp in Breakpoint::all() {
+        if let (Some(min_width), Some(props)) = (bp.min_width(), style.breakpoints.get(bp))
+            && min_width <= width
+        {
+            push_rule(&mut css, &format!(".{}", class), props);
+        }
+    }
+    if let Some(props) = style.states.get(&StateVariant::Default) {
+        push_rule(&mut css, &format!(".{}", class), props);
+    }
+    let mut classes = class.clone();
+    if state != StateVariant::Default {
+        classes = format!("{} {}", class, state_class(state));
+        if let Some(props) = style.states.get(&state) {
+            push_rule(
+                &mut css,
+                &format!(".{}.{}", class, state_class(state)),
+                props,
+            );
+        }
+    }
+
+    let vars: IndexMap<String, String> = tokens.css_variables(false).into_iter().collect();
+    PreviewDocument {
+        component,
+        state,
+        breakpoint,
+        css: inline_vars(&css, &vars, &mut Vec::new()),
+        html: sample(
+            component,
+            &escape(&classes),
+            state == StateVariant::Disabled,
+        ),
+    }
+}
+
+fn push_rule(css: &mut String, selector: &str, props: &StyleProperties) {
+    let body = props.to_css();
+    if !body.is_empty() {
+        css.push_str(&format!("{} {{\n{}}}\n\n", selector, body));
+    }
+}
+
+/// Sample markup for a component with the given class list.
+fn sample(component: ComponentType, class: &str, disabled: bool) -> String {
+    let disabled = if disabled { " disabled" } else { "" };
+    match component {
+        ComponentType::Button => {
+            format!(
+                "<button class=\"{}\" type=\"button\"{}>Button</button>",
+                class, disabled
+            )
+        }
+        ComponentType::Input => format!(
+            "<input class=\"{}\" type=\"text\" placeholder=\"Placeholder\"{}>",
+            class, disabled
+        ),
+        ComponentType::Card => format!(
+            "<article class=\"{}\">\n<h3>Card title</h3>\n<p>Card body text.</p>\n</article>",
+            class
+        ),
+        ComponentType::Modal => format!(
+            "<div class=\"{}\" role=\"dialog\" aria-modal=\"true\">\n<h2>Modal title</h2>\n\
+<p>Modal content.</p>\n<button type=\"button\">Close</button>\n</div>",
+            class
+        ),
+        ComponentType::Badge => format!("<span class=\"{}\">Badge</span>", class),
+        ComponentType::Alert => format!(
+            "<div class=\"{}\" role=\"alert\">Something needs your attention.</div>",
+            class
+        ),
+        ComponentType::Tooltip => {
+            format!(
+                "<div class=\"{}\" role=\"tooltip\">Tooltip text</div>",
+                class
+            )
+        }
+        ComponentType::Dropdown => format!(
+            "<select class=\"{}\"{}>\n<option>First option</option>\n\
+<option>Second option</option>\n</select>",
+            class, disabled
+        ),
+        ComponentType::Tabs => format!(
+            "<div class=\"{}\" role=\"tablist\">\n\
+<button type=\"button\" role=\"tab\" aria-selected=\"true\">First tab</button>\n\
+<button type=\"button\" role=\"tab\" aria-selected=\"false\">Second tab</button>\n</div>",
+            class
+        ),
+        ComponentType::Panel => format!(
+            "<section class=\"{}\">\n<h3>Panel</h3>\n<p>Panel content.</p>\n</section>",
+            class
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+
+    fn fixture() -> (ComponentStyle, DesignTokens) {
+        let mut tokens = DesignTokens::default();
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        tokens.colors.insert("blue".to_string(), simple("#3b82f6"));
+        tokens
+            .colors
+            .insert("primary".to_string(), simple("var(--color-blue)"));
+        tokens
+            .colors
+            .insert("primary-hover".to_string(), simple("#1d4ed8"));
+        tokens.spacing.insert("md".to_string(), simple("16px"));
+
+        let mut style = ComponentStyle {
+            base: StyleProperties {
+                background_color: Some("var(--color-primary)".to_string()),
+                padding: Some("var(--spacing-md)".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        style.states.insert(
+            StateVariant::Hover,
+            StyleProperties {
+                background_color: Some("var(--color-primary-hover)".to_string()),
+                ..Default::default()
+            },
+        );
+        style.breakpoints.insert(
+            Breakpoint::Md,
+            StyleProperties {
+                padding: Some("24px".to_string()),
+                ..Default::default()
+            },
+        );
+        (style, tokens)
+    }
+
+    #[test]
+    fn test_forced_hover() {
+        let (style, tokens) = fixture();
+        let button = ComponentType::Button;
+        let default = render_component_preview(
+            button,
+            &style,
+            &tokens,
+            StateVariant::Default,
+            Breakpoint::Base,
+        );
+        let hover = render_component_preview(
+            button,
+            &style,
+            &tokens,
+            StateVariant::Hover,
+            Breakpoint::Base,
+        );
+
+        assert!(!default.css.contains("#1d4ed8"));
+        assert!(!default.css.contains(":hover"));
+        assert_eq!(
+            hover.css,
+            ".button {\n  padding: 16px;\n  background-color: #3b82f6;\n}\n\n\
+.button.state-hover {\n  background-color: #1d4ed8;\n}\n\n"
+        );
+        assert_eq!(
+            hover.html,
+            "<button class=\"button state-hover\" type=\"button\">Button</button>"
+        );
+        assert!(!hover.css.contains("var("));
+        assert!(hover.to_html().contains(&hover.css));
+    }
+
+    #[test]
+    fn test_breakpoint_overrides() {
+        let (style, tokens) = fixture();
+        let at = |breakpoint| {
+            render_component_preview(
+                ComponentType::Card,
+                &style,
+                &tokens,
+                StateVariant::Default,
+                breakpoint,
+            )
+        };
+        assert!(!at(Breakpoint::Sm).css.contains("24px"));
+        let lg = at(Breakpoint::Lg);
+        assert!(lg.css.ends_with(".card {\n  padding: 24px;\n}\n\n"));
+        assert!(!lg.css.contains("@media"));
+        assert!(lg.html.starts_with("<article class=\"card\">"));
+        assert!(lg.to_html().contains("data-breakpoint=\"lg\""));
+
+        let disabled = render_component_preview(
+            ComponentType::Input,
+            &style,
+            &tokens,
+            StateVariant::Disabled,
+            Breakpoint::Base,
+        );
+        assert!(disabled.html.contains("class=\"input state-disabled\""));
+        assert!(disabled.html.ends_with(" disabled>"));
+    }
+}
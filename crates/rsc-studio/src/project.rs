@@ -0,0 +1,242 @@
+//! Project file format.
+//!
+//! A project file is the saved form of a studio session: configuration,
+//! workflows, design tokens and component styles. Session-only state
+//! (selection, history, presence) is not part of it.
+
+pub mod merge;
+pub mod storage;
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use rsc_flow::LayerRegistry;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::StudioConfig;
+use crate::designer::css::{CategoryPresentations, ComponentStyles, DesignTokens, ThemeManager};
+use crate::designer::flow_style::FlowStyleSheet;
+use crate::entity::Workflow;
+use crate::perf::trace_span;
+use crate::store::StudioStore;
+use crate::store::dirty::ChangeCategory;
+use crate::timestamp::unix_time;
+
+pub use merge::{ConflictKind, MergeConflict, MergeResult, merge_project_json};
+pub use storage::{SaveOptions, SizeBreakdown, size_report};
+
+/// Current project file format version.
+pub const PROJECT_FORMAT_VERSION: u32 = 1;
+
+/// Project file error types.
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    /// The file is not valid project JSON.
+    #[error("Parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// Reading or writing compressed data failed.
+    #[error("Compression error: {0}")]
+    Compression(#[from] std::io::Error),
+
+    /// The project file could not be read.
+    #[error("Read error: {0}")]
+    Read(std::io::Error),
+
+    /// The bundle's schema version is missing or newer than this studio.
+    #[error("Unsupported project schema version {found} (supported: {supported})")]
+    UnsupportedVersion { found: u64, supported: u32 },
+}
+
+/// Saved project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFile {
+    /// Format version the file was written with.
+    #[serde(default = "default_format_version")]
+    pub format_version: u32,
+    /// Studio configuration.
+    #[serde(default)]
+    pub config: StudioConfig,
+    /// Workflows in display order.
+    #[serde(default)]
+    pub workflows: Vec<Workflow>,
+    /// Design tokens of the active theme.
+    #[serde(default)]
+    pub tokens: DesignTokens,
+    /// All themes and the active selection. Omitted while the project has
+    /// only the default theme; `tokens` wins for the active theme.
+    #[serde(default, skip_serializing_if = "ThemeManager::is_default")]
+    pub themes: ThemeManager,
+    /// Component styles.
+    #[serde(default)]
+    pub component_styles: ComponentStyles,
+    /// Token category icon and color overrides.
+    #[serde(default, skip_serializing_if = "CategoryPresentations::is_empty")]
+    pub category_presentation: CategoryPresentations,
+    /// Edge layers.
+    #[serde(default, skip_serializing_if = "LayerRegistry::is_empty")]
+    pub layers: LayerRegistry,
+    /// Node and edge styles by kind.
+    #[serde(default, skip_serializing_if = "FlowStyleSheet::is_default")]
+    pub flow_styles: FlowStyleSheet,
+    /// Fields from newer format versions, preserved on save.
+    #[serde(flatten)]
+    pub extra: IndexMap<String, serde_json::Value>,
+}
+
+fn default_format_version() -> u32 {
+    PROJECT_FORMAT_VERSION
+}
+
+impl Default for ProjectFile {
+    fn default() -> Self {
+        Self {
+            format_version: PROJECT_FORMAT_VERSION,
+            config: StudioConfig::default(),
+            workflows: Vec::new(),
+            tokens: DesignTokens::default(),
+            themes: ThemeManager::default(),
+            component_styles: ComponentStyles::default(),
+            category_presentation: CategoryPresentations::default(),
+            layers: LayerRegistry::default(),
+            flow_styles: FlowStyleSheet::default(),
+            extra: IndexMap::new(),
+        }
+    }
+}
+
+impl ProjectFile {
+    /// Snapshot the persisted parts of a store.
+    pub fn from_store(store: &StudioStore, config: &StudioConfig) -> Self {
+        Self {
+            format_version: PROJECT_FORMAT_VERSION,
+            config: config.clone(),
+            workflows: store.workflows.values().cloned().collect(),
+            tokens: store.tokens.clone(),
+            themes: store.themes_snapshot(),
+            component_styles: store.component_styles.clone(),
+            category_presentation: store.category_presentation.clone(),
+            layers: store.layers.clone(),
+            flow_styles: store.flow_styles.clone(),
+            extra: IndexMap::new(),
+        }
+    }
+
+    /// Load the project into a store, returning its configuration. The
+    /// loaded state counts as saved.
+    pub fn load_into(self, store: &mut StudioStore) -> StudioConfig {
+        store.load_project(self.workflows, self.tokens, self.component_styles);
+        store.themes = self.themes;
+        store.themes.set_active_tokens(store.tokens.clone());
+        store.category_presentation = self.category_presentation;
+        store.layers = self.layers;
+        store.flow_styles = self.flow_styles;
+        store.modified_by = self.config.author.clone();
+        store.limits = self.config.limits.clone();
+        store.output_ordering = self.config.output_ordering.clone();
+        store.dirty.mark_saved(&[
+            ChangeCategory::Tokens,
+            ChangeCategory::Workflows,
+            ChangeCategory::ComponentStyles,
+        ]);
+        self.config
+    }
+
+    /// Read a project from disk.
+    ///
+    /// Entities saved before timestamps existed get the file's mtime.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ProjectError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(ProjectError::Read)?;
+        let mut project = Self::from_bytes(&bytes)?;
+        if let Ok(mtime) = std::fs::metadata(path).and_then(|m| m.modified()) {
+            project.backfill_timestamps(unix_time(mtime));
+        }
+        Ok(project)
+    }
+
+    /// Fill missing created/modified times with `at` (Unix seconds).
+    pub fn backfill_timestamps(&mut self, at: u64) {
+        for workflow in &mut self.workflows {
+            workflow.timestamps.backfill(at);
+            for context in workflow.contexts.values_mut() {
+                context.timestamps.backfill(at);
+                for preset in context.presets.values_mut() {
+                    preset.timestamps.backfill(at);
+                }
+            }
+        }
+        for style in self.component_styles.styles.values_mut() {
+            style.timestamps.backfill(at);
+        }
+        let tokens = &mut self.tokens;
+        for path in tokens.all_paths() {
+            tokens.metadata.entry(path).or_default().backfill(at);
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ProjectError> {
+        trace_span!("import", "read_project", bytes = json.len());
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, ProjectError> {
+        trace_span!("export", "write_project", count = self.workflows.len());
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_roundtrip() {
+        let mut store = StudioStore::new();
+        store.add_workflow(Workflow::new("Main").with_id("w1"));
+        let config = StudioConfig {
+            name: "Demo".to_string(),
+            ..Default::default()
+        };
+
+        let json = ProjectFile::from_store(&store, &config).to_json().unwrap();
+        let mut loaded = StudioStore::new();
+        let loaded_config = ProjectFile::from_json(&json)
+            .unwrap()
+            .load_into(&mut loaded);
+
+        assert_eq!(loaded_config.name, "Demo");
+        assert_eq!(loaded.get_workflow("w1").unwrap().name, "Main");
+    }
+
+    #[test]
+    fn test_read_backfills_legacy_timestamps() {
+        let json = r##"{
+            "workflows": [{"id": "w1", "name": "Main", "contexts": {
+                "c1": {"id": "c1", "name": "Edit", "modified_at": 500}
+            }}],
+            "tokens": {"colors": {"primary": "#000"}}
+        }"##;
+        let path = std::env::temp_dir().join(format!("rsc-legacy-{}.json", std::process::id()));
+        std::fs::write(&path, json).unwrap();
+        let mtime = unix_time(std::fs::metadata(&path).unwrap().modified().unwrap());
+
+        let project = ProjectFile::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let workflow = &project.workflows[0];
+        assert_eq!(workflow.timestamps.created_at, Some(mtime));
+        assert_eq!(workflow.timestamps.modified_at, Some(mtime));
+        assert_eq!(workflow.contexts["c1"].timestamps.modified_at, Some(500));
+        assert_eq!(
+            project.tokens.metadata["colors.primary"].created_at,
+            Some(mtime)
+        );
+        assert!(
+            ProjectFile::from_json(json).unwrap().workflows[0]
+                .timestamps
+                .is_empty()
+        );
+    }
+}
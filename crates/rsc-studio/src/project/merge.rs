@@ -0,0 +1,723 @@
+//! Three-way structural merge of project files.
+//!
+//! Entities are merged by identity rather than by text: tokens by path,
+//! workflows and contexts by id, themes and component styles by name. Changes made on
+//! only one side are taken; changes made differently on both sides are
+//! reported as conflicts and resolved to "ours" in the merged file.
+//!
+//! Ordering rule: entities keep the order they have in "ours", and entities
+//! added only in "theirs" are appended in the order they appear there.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{ProjectError, ProjectFile};
+use crate::designer::css::{ContrastPair, ThemeManager};
+use crate::entity::Workflow;
+use crate::timestamp::Timestamps;
+
+/// Kind of merge conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictKind {
+    /// Both sides changed the entity differently.
+    BothModified,
+    /// Both sides added an entity with the same id but different content.
+    BothAdded,
+    /// Ours deleted the entity, theirs modified it.
+    DeletedByUs,
+    /// Theirs deleted the entity, ours modified it.
+    DeletedByThem,
+}
+
+/// A conflict on one entity.
+///
+/// Values are kept as JSON so review UIs can show and diff them without
+/// knowing the entity type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergeConflict {
+    /// Entity path, e.g. `tokens.colors.primary` or `workflows.w1.contexts.c2`.
+    pub path: String,
+    pub kind: ConflictKind,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// Result of a three-way merge.
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// Merged project; conflicting entities hold "ours".
+    pub merged: ProjectFile,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    /// Whether the merge completed without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl ProjectFile {
+    /// Merge two revisions of a project that share a common base.
+    pub fn merge(base: &ProjectFile, ours: &ProjectFile, theirs: &ProjectFile) -> MergeResult {
+        let mut conflicts = Vec::new();
+        let mut merged = ours.clone();
+
+        merged.config = merge_value(
+            "config",
+            &base.config,
+            &ours.config,
+            &theirs.config,
+            &mut conflicts,
+        );
+
+        let by_id = |workflows: &[Workflow]| -> IndexMap<String, Workflow> {
+            workflows
+                .iter()
+                .map(|w| (w.id.clone(), w.clone()))
+                .collect()
+        };
+        merged.workflows = merge_keyed(
+            "workflows",
+            &by_id(&base.workflows),
+            &by_id(&ours.workflows),
+            &by_id(&theirs.workflows),
+            &mut conflicts,
+            merge_workflow,
+        )
+        .into_values()
+        .collect();
+
+        for (((prefix, target), (_, b)), ((_, o), (_, t))) in merged
+            .tokens
+            .categories_mut()
+            .into_iter()
+            .zip(base.tokens.categories())
+            .zip(
+                ours.tokens
+                    .categories()
+                    .into_iter()
+                    .zip(theirs.tokens.categories()),
+            )
+        {
+            *target = merge_keyed(
+                &format!("tokens.{}", prefix),
+                b,
+                o,
+                t,
+                &mut conflicts,
+                conflict_leaf,
+            );
+        }
+
+        merged.tokens.metadata = merged
+            .tokens
+            .all_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let ts = match (
+                    ours.tokens.metadata.get(&path),
+                    theirs.tokens.metadata.get(&path),
+                ) {
+                    (Some(o), Some(t)) => newer(o, t),
+                    (o, t) => o.or(t)?,
+                };
+                Some((path, ts.clone()))
+            })
+            .collect();
+
+        let paths = merged.tokens.all_paths();
+        merged.tokens.docs = merge_keyed(
+            "tokens.docs",
+            &base.tokens.docs,
+            &ours.tokens.docs,
+            &theirs.tokens.docs,
+            &mut conflicts,
+            conflict_leaf,
+        );
+        merged.tokens.docs.retain(|path, _| paths.contains(path));
+
+        let by_pair = |pairs: &[ContrastPair]| -> IndexMap<String, ContrastPair> {
+            pairs
+                .iter()
+                .map(|p| (format!("{}/{}", p.fg, p.bg), p.clone()))
+                .collect()
+        };
+        merged.tokens.contrast_pairs = merge_keyed(
+            "tokens.contrast_pairs",
+            &by_pair(&base.tokens.contrast_pairs),
+            &by_pair(&ours.tokens.contrast_pairs),
+            &by_pair(&theirs.tokens.contrast_pairs),
+            &mut conflicts,
+            conflict_leaf,
+        )
+        .into_values()
+        .collect();
+
+        merged.themes = merge_themes(base, ours, theirs, &merged, &mut conflicts);
+
+        merged.component_styles.styles = merge_keyed(
+            "component_styles",
+            &base.component_styles.styles,
+            &ours.component_styles.styles,
+            &theirs.component_styles.styles,
+            &mut conflicts,
+            conflict_leaf,
+        );
+
+        merged.category_presentation.overrides = merge_keyed(
+            "category_presentation",
+            &base.category_presentation.overrides,
+            &ours.category_presentation.overrides,
+            &theirs.category_presentation.overrides,
+            &mut conflicts,
+            conflict_leaf,
+        );
+
+        merged.layers.layers = merge_keyed(
+            "layers",
+            &base.layers.layers,
+            &ours.layers.layers,
+            &theirs.layers.layers,
+            &mut conflicts,
+            conflict_leaf,
+        );
+
+        let (base_styles, our_styles, their_styles) =
+            (&base.flow_styles, &ours.flow_styles, &theirs.flow_styles);
+        merged.flow_styles.node_default = merge_value(
+            "flow_styles.node_default",
+            &base_styles.node_default,
+            &our_styles.node_default,
+            &their_styles.node_default,
+            &mut conflicts,
+        );
+        merged.flow_styles.edge_default = merge_value(
+            "flow_styles.edge_default",
+            &base_styles.edge_default,
+            &our_styles.edge_default,
+            &their_styles.edge_default,
+            &mut conflicts,
+        );
+        merged.flow_styles.nodes = merge_keyed(
+            "flow_styles.nodes",
+            &base_styles.nodes,
+            &our_styles.nodes,
+            &their_styles.nodes,
+            &mut conflicts,
+            conflict_leaf,
+        );
+        merged.flow_styles.edges = merge_keyed(
+            "flow_styles.edges",
+            &base_styles.edges,
+            &our_styles.edges,
+            &their_styles.edges,
+            &mut conflicts,
+            conflict_leaf,
+        );
+
+        // Fields of newer format versions are merged by key as well, so
+        // neither side's unknown data is lost.
+        merged.extra = merge_keyed(
+            "extra",
+            &base.extra,
+            &ours.extra,
+            &theirs.extra,
+            &mut conflicts,
+            conflict_leaf,
+        );
+
+        MergeResult { merged, conflicts }
+    }
+}
+
+/// Merge three JSON project files, e.g. as a git merge driver.
+///
+/// Returns the merged file as pretty JSON together with any conflicts.
+pub fn merge_project_json(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+) -> Result<(String, Vec<MergeConflict>), ProjectError> {
+    let result = ProjectFile::merge(
+        &ProjectFile::from_json(base)?,
+        &ProjectFile::from_json(ours)?,
+        &ProjectFile::from_json(theirs)?,
+    );
+    Ok((result.merged.to_json()?, result.conflicts))
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    to_value(a) == to_value(b)
+}
+
+/// Resolve an entity changed on both sides. Returns the merged entity.
+type BothChanged<T> = fn(&str, &T, &T, &T, &mut Vec<MergeConflict>) -> T;
+
+/// Report a both-modified conflict and keep ours.
+fn conflict_leaf<T: Serialize + Clone>(
+    path: &str,
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    conflicts: &mut Vec<MergeConflict>,
+) -> T {
+    conflicts.push(MergeConflict {
+        path: path.to_string(),
+        kind: ConflictKind::BothModified,
+        base: Some(to_value(base)),
+        ours: Some(to_value(ours)),
+        theirs: Some(to_value(theirs)),
+    });
+    ours.clone()
+}
+
+/// Three-way merge of a single value.
+fn merge_value<T: Serialize + Clone>(
+    path: &str,
+    base: &T,
+    ours: &T,
+    theirs: &T,
+    conflicts: &mut Vec<MergeConflict>,
+) -> T {
+    if same(ours, theirs) || same(base, theirs) {
+        ours.clone()
+    } else if same(base, ours) {
+        theirs.clone()
+    } else {
+        conflict_leaf(path, base, ours, theirs, conflicts)
+    }
+}
+
+/// Three-way merge of entities keyed by id.
+fn merge_keyed<T: Serialize + Clone>(
+    prefix: &str,
+    base: &IndexMap<String, T>,
+    ours: &IndexMap<String, T>,
+    theirs: &IndexMap<String, T>,
+    conflicts: &mut Vec<MergeConflict>,
+    both_changed: BothChanged<T>,
+) -> IndexMap<String, T> {
+    let mut merged = IndexMap::new();
+    let keys = ours
+        .keys()
+        .chain(theirs.keys().filter(|k| !ours.contains_key(*k)));
+
+    for key in keys {
+        let path = format!("{}.{}", prefix, key);
+        let conflict = |kind, conflicts: &mut Vec<MergeConflict>| {
+            conflicts.push(MergeConflict {
+                path: path.clone(),
+                kind,
+                base: base.get(key).map(to_value),
+                ours: ours.get(key).map(to_value),
+                theirs: theirs.get(key).map(to_value),
+            });
+        };
+
+        let value = match (base.get(key), ours.get(key), theirs.get(key)) {
+            (None, Some(o), Some(t)) => {
+                if !same(o, t) {
+                    conflict(ConflictKind::BothAdded, conflicts);
+                }
+                Some(o.clone())
+            }
+            (None, Some(o), None) => Some(o.clone()),
+            (None, None, Some(t)) => Some(t.clone()),
+            (Some(b), Some(o), Some(t)) => {
+                if same(o, t) || same(b, t) {
+                    Some(o.clone())
+                } else if same(b, o) {
+                    Some(t.clone())
+                } else {
+                    Some(both_changed(&path, b, o, t, conflicts))
+                }
+            }
+            // Deleted by them: fine unless we changed it.
+            (Some(b), Some(o), None) => {
+                if same(b, o) {
+                    None
+                } else {
+                    conflict(ConflictKind::DeletedByThem, conflicts);
+                    Some(o.clone())
+                }
+            }
+            // Deleted by us: fine unless they changed it.
+            (Some(b), None, Some(t)) => {
+                if !same(b, t) {
+                    conflict(ConflictKind::DeletedByUs, conflicts);
+                }
+                None
+            }
+            (_, None, None) => None,
+        };
+
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+
+    merged
+}
+
+/// Merge themes by name. The active theme's tokens are the merged
+/// `tokens`, whose conflicts are reported per token, so the theme itself
+/// isn't reported again when every side has it active.
+fn merge_themes(
+    base: &ProjectFile,
+    ours: &ProjectFile,
+    theirs: &ProjectFile,
+    merged: &ProjectFile,
+    conflicts: &mut Vec<MergeConflict>,
+) -> ThemeManager {
+    let active = |p: &ProjectFile| p.themes.active().to_string();
+    let mut found = Vec::new();
+    let mut themes = merge_keyed(
+        "themes",
+        base.themes.themes(),
+        ours.themes.themes(),
+        theirs.themes.themes(),
+        &mut found,
+        conflict_leaf,
+    );
+    let active = merge_value(
+        "themes.active",
+        &active(base),
+        &active(ours),
+        &active(theirs),
+        conflicts,
+    );
+    let shared = [base, ours, theirs]
+        .iter()
+        .all(|p| p.themes.active() == active);
+    let active_path = format!("themes.{}", active);
+    found.retain(|c| !(shared && c.path == active_path && c.kind == ConflictKind::BothModified));
+    conflicts.extend(found);
+    if let Some(tokens) = themes.get_mut(&active) {
+        *tokens = merged.tokens.clone();
+    }
+    ThemeManager::from_themes(themes, &active).unwrap_or_else(|| ours.themes.clone())
+}
+
+/// Merge a workflow changed on both sides: contexts by id, other fields as one unit.
+fn merge_workflow(
+    path: &str,
+    base: &Workflow,
+    ours: &Workflow,
+    theirs: &Workflow,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Workflow {
+    let header = |w: &Workflow| Workflow {
+        contexts: IndexMap::new(),
+        timestamps: Timestamps::default(),
+        ..w.clone()
+    };
+    let mut merged = merge_value(
+        path,
+        &header(base),
+        &header(ours),
+        &header(theirs),
+        conflicts,
+    );
+    merged.contexts = merge_keyed(
+        &format!("{}.contexts", path),
+        &base.contexts,
+        &ours.contexts,
+        &theirs.contexts,
+        conflicts,
+        conflict_leaf,
+    );
+    merged.timestamps = newer(&ours.timestamps, &theirs.timestamps).clone();
+    merged
+}
+
+/// The more recently modified of two timestamps.
+fn newer<'a>(ours: &'a Timestamps, theirs: &'a Timestamps) -> &'a Timestamps {
+    if theirs.modified_at > ours.modified_at {
+        theirs
+    } else {
+        ours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+    use crate::entity::Context;
+
+    fn color(value: &str) -> TokenValue {
+        TokenValue::Simple(value.to_string())
+    }
+
+    fn base() -> ProjectFile {
+        let mut workflow = Workflow::new("Main").with_id("w1");
+        workflow.add_context(Context::new("Edit").with_id("c1"));
+        workflow.add_context(Context::new("Review").with_id("c2"));
+
+        let mut project = ProjectFile::default();
+        project.workflows.push(workflow);
+        project.workflows.push(Workflow::new("Docs").with_id("w2"));
+        project
+            .tokens
+            .colors
+            .insert("primary".to_string(), color("#000"));
+        project
+    }
+
+    #[test]
+    fn test_clean_merge() {
+        let base = base();
+
+        let mut ours = base.clone();
+        ours.tokens
+            .colors
+            .insert("primary".to_string(), color("#111"));
+        ours.workflows[0].contexts["c1"].name = "Write".to_string();
+
+        let mut theirs = base.clone();
+        theirs
+            .tokens
+            .colors
+            .insert("accent".to_string(), color("#f00"));
+        theirs.workflows[0].contexts["c2"].name = "Approve".to_string();
+        theirs.workflows.push(Workflow::new("Ops").with_id("w3"));
+
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        let merged = &result.merged;
+        assert_eq!(merged.tokens.colors["primary"], color("#111"));
+        assert_eq!(merged.tokens.colors["accent"], color("#f00"));
+        assert_eq!(merged.workflows[0].contexts["c1"].name, "Write");
+        assert_eq!(merged.workflows[0].contexts["c2"].name, "Approve");
+        let ids: Vec<_> = merged.workflows.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["w1", "w2", "w3"]);
+    }
+
+    #[test]
+    fn test_add_add_same_id() {
+        let base = base();
+
+        let mut ours = base.clone();
+        ours.workflows.push(Workflow::new("Billing").with_id("w9"));
+        ours.tokens.colors.insert("info".to_string(), color("#00f"));
+
+        let mut theirs = base.clone();
+        theirs
+            .workflows
+            .push(Workflow::new("Payments").with_id("w9"));
+        theirs
+            .tokens
+            .colors
+            .insert("info".to_string(), color("#00f"));
+
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+
+        // Identical additions merge; differing ones conflict and keep ours.
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.path, "workflows.w9");
+        assert_eq!(conflict.kind, ConflictKind::BothAdded);
+        assert!(conflict.base.is_none());
+        assert_eq!(result.merged.workflows[2].name, "Billing");
+    }
+
+    #[test]
+    fn test_delete_vs_modify() {
+        let base = base();
+
+        let mut ours = base.clone();
+        ours.workflows.retain(|w| w.id != "w2");
+        ours.tokens
+            .colors
+            .insert("primary".to_string(), color("#222"));
+
+        let mut theirs = base.clone();
+        theirs.workflows[1].name = "Documentation".to_string();
+        theirs.tokens.colors.shift_remove("primary");
+
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+
+        let kinds: Vec<_> = result
+            .conflicts
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("workflows.w2", ConflictKind::DeletedByUs),
+                ("tokens.colors.primary", ConflictKind::DeletedByThem),
+            ]
+        );
+        assert_eq!(result.merged.workflows.len(), 1);
+        assert_eq!(result.merged.tokens.colors["primary"], color("#222"));
+    }
+
+    #[test]
+    fn test_token_docs_merge_by_path() {
+        use crate::designer::css::TokenDocs;
+
+        let mut base = base();
+        base.tokens
+            .colors
+            .insert("accent".to_string(), color("#f00"));
+        let mut ours = base.clone();
+        ours.tokens.docs.insert(
+            "colors.primary".to_string(),
+            TokenDocs::default().with_description("Brand color"),
+        );
+        let mut theirs = base.clone();
+        theirs.tokens.docs.insert(
+            "colors.accent".to_string(),
+            TokenDocs::default().with_deprecated("Use colors.primary"),
+        );
+        theirs.tokens.docs.insert(
+            "colors.gone".to_string(),
+            TokenDocs::default().with_description("No token"),
+        );
+
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        let docs = &result.merged.tokens.docs;
+        assert_eq!(
+            docs["colors.primary"].description.as_deref(),
+            Some("Brand color")
+        );
+        assert_eq!(
+            docs["colors.accent"].deprecated.as_deref(),
+            Some("Use colors.primary")
+        );
+        // Docs of tokens the merge doesn't have are dropped.
+        assert!(!docs.contains_key("colors.gone"));
+
+        let mut other = ours.clone();
+        other.tokens.docs["colors.primary"].description = Some("Main color".to_string());
+        let result = ProjectFile::merge(&base, &ours, &other);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].path, "tokens.docs.colors.primary");
+    }
+
+    #[test]
+    fn test_contrast_pairs_union() {
+        let mut base = base();
+        let pair = |fg: &str| ContrastPair::new(format!("colors.{}", fg), "colors.bg");
+        base.tokens.contrast_pairs = vec![pair("text"), pair("muted")];
+        let mut ours = base.clone();
+        ours.tokens.contrast_pairs.push(pair("link"));
+        let mut theirs = base.clone();
+        theirs.tokens.contrast_pairs.push(pair("accent"));
+        theirs.tokens.contrast_pairs.push(pair("link"));
+        // A pair removed on one side stays removed.
+        theirs.tokens.contrast_pairs.remove(1);
+
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        assert_eq!(
+            result.merged.tokens.contrast_pairs,
+            [pair("text"), pair("link"), pair("accent")]
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_merge_by_key() {
+        let mut base = base();
+        base.extra
+            .insert("plugins".to_string(), serde_json::json!(["lint"]));
+        let mut ours = base.clone();
+        ours.extra
+            .insert("plugins".to_string(), serde_json::json!(["lint", "a11y"]));
+        let mut theirs = base.clone();
+        theirs
+            .extra
+            .insert("review".to_string(), serde_json::json!({"required": 2}));
+
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        assert_eq!(
+            result.merged.extra["plugins"],
+            serde_json::json!(["lint", "a11y"])
+        );
+        assert_eq!(
+            result.merged.extra["review"],
+            serde_json::json!({"required": 2})
+        );
+
+        theirs
+            .extra
+            .insert("plugins".to_string(), serde_json::json!([]));
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].path, "extra.plugins");
+        assert_eq!(result.conflicts[0].kind, ConflictKind::BothModified);
+    }
+
+    #[test]
+    fn test_themes_merge_by_name() {
+        let base = base();
+        let mut ours = base.clone();
+        ours.tokens
+            .colors
+            .insert("primary".to_string(), color("#111"));
+
+        let mut theirs = base.clone();
+        theirs.themes.create_theme("dark", None).unwrap();
+        theirs.themes.create_theme("brand", None).unwrap();
+        let result = ProjectFile::merge(&base, &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        let themes = &result.merged.themes;
+        let names: Vec<_> = themes.names().collect();
+        assert_eq!(names, ["default", "dark", "brand"]);
+        assert_eq!(themes.active_tokens().colors["primary"], color("#111"));
+
+        // A theme edited on both sides conflicts and keeps ours.
+        let base = theirs;
+        let dark = |value: &str| {
+            let mut project = base.clone();
+            let mut themes = IndexMap::new();
+            for name in project.themes.names() {
+                let mut tokens = project.themes.get(name).unwrap().clone();
+                if name == "dark" {
+                    tokens.colors.insert("bg".to_string(), color(value));
+                }
+                themes.insert(name.to_string(), tokens);
+            }
+            project.themes = ThemeManager::from_themes(themes, "default").unwrap();
+            project
+        };
+        let result = ProjectFile::merge(&base, &dark("#000"), &dark("#111"));
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].path, "themes.dark");
+        assert_eq!(result.conflicts[0].kind, ConflictKind::BothModified);
+        let merged_dark = result.merged.themes.get("dark").unwrap();
+        assert_eq!(merged_dark.colors["bg"], color("#000"));
+    }
+
+    #[test]
+    fn test_merge_project_json() {
+        let base = base();
+        let mut theirs = base.clone();
+        theirs.workflows[0].name = "Primary".to_string();
+
+        let (json, conflicts) = merge_project_json(
+            &base.to_json().unwrap(),
+            &base.to_json().unwrap(),
+            &theirs.to_json().unwrap(),
+        )
+        .unwrap();
+
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            ProjectFile::from_json(&json).unwrap().workflows[0].name,
+            "Primary"
+        );
+    }
+}
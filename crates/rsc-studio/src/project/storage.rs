@@ -0,0 +1,403 @@
+//! On-disk encoding of project files and size diagnostics.
+//!
+//! [`ProjectFile::to_bytes`] can round floating point values, dictionary
+//! encode repeated metadata strings and gzip the result.
+//! [`ProjectFile::from_bytes`] reads any combination of these.
+
+use std::io::{Read, Write};
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{ProjectError, ProjectFile};
+
+/// Top-level key holding the metadata string table.
+pub const STRING_TABLE_KEY: &str = "$strings";
+
+/// Key of an encoded string reference: `{"$str": <index>}`.
+const STRING_REF_KEY: &str = "$str";
+
+/// Shortest metadata string worth a table entry.
+const MIN_DICTIONARY_LEN: usize = 8;
+
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Project save options.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SaveOptions {
+    /// Round non-integer numbers (node positions and sizes) to this many decimals.
+    pub precision: Option<u32>,
+    /// Replace repeated metadata strings with references into a table.
+    pub dictionary: bool,
+    /// Gzip the output.
+    pub gzip: bool,
+    /// Pretty-print the JSON.
+    pub pretty: bool,
+}
+
+impl SaveOptions {
+    /// Plain pretty JSON, as written by [`ProjectFile::to_json`].
+    pub fn plain() -> Self {
+        Self {
+            pretty: true,
+            ..Default::default()
+        }
+    }
+
+    /// All size optimizations.
+    pub fn compact() -> Self {
+        Self {
+            precision: Some(2),
+            dictionary: true,
+            gzip: true,
+            pretty: false,
+        }
+    }
+
+    pub fn with_precision(mut self, decimals: u32) -> Self {
+        self.precision = Some(decimals);
+        self
+    }
+
+    pub fn with_dictionary(mut self, dictionary: bool) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    pub fn with_gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+}
+
+impl ProjectFile {
+    /// Encode the project for disk.
+    pub fn to_bytes(&self, options: &SaveOptions) -> Result<Vec<u8>, ProjectError> {
+        encode(serde_json::to_value(self)?, options)
+    }
+
+    /// Decode a project written by [`ProjectFile::to_bytes`], compressed or not.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProjectError> {
+        Ok(serde_json::from_value(decode(bytes)?)?)
+    }
+
+    /// Bytes per section of the project.
+    pub fn size_report(&self) -> Result<SizeBreakdown, ProjectError> {
+        size_report(self)
+    }
+}
+
+/// Encode a project document with the given options.
+pub(crate) fn encode(mut value: Value, options: &SaveOptions) -> Result<Vec<u8>, ProjectError> {
+    if let Some(decimals) = options.precision {
+        round_numbers(&mut value, decimals);
+    }
+    if options.dictionary {
+        encode_strings(&mut value);
+    }
+    let json = if options.pretty {
+        serde_json::to_vec_pretty(&value)?
+    } else {
+        serde_json::to_vec(&value)?
+    };
+    if !options.gzip {
+        return Ok(json);
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decode a document written by [`encode`], compressed or not.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Value, ProjectError> {
+    let mut value: Value = if bytes.starts_with(&GZIP_MAGIC) {
+        let mut json = Vec::new();
+        GzDecoder::new(bytes).read_to_end(&mut json)?;
+        serde_json::from_slice(&json)?
+    } else {
+        serde_json::from_slice(bytes)?
+    };
+    decode_strings(&mut value);
+    Ok(value)
+}
+
+/// Serialized size of each project section, in bytes of compact JSON.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SizeBreakdown {
+    /// Whole file as compact JSON.
+    pub total: usize,
+    /// Whole file after all [`SaveOptions::compact`] optimizations.
+    pub compacted: usize,
+    /// Section name to size. Workflows are listed as `workflows.<id>`;
+    /// fields this version doesn't know (e.g. assets or history written by
+    /// a newer version) under their own name.
+    pub sections: IndexMap<String, usize>,
+}
+
+impl SizeBreakdown {
+    /// The `n` largest sections, largest first.
+    pub fn largest(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut sections: Vec<(&str, usize)> = self
+            .sections
+            .iter()
+            .map(|(name, size)| (name.as_str(), *size))
+            .collect();
+        sections.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        sections.truncate(n);
+        sections
+    }
+}
+
+/// Compute a project's [`SizeBreakdown`].
+pub fn size_report(project: &ProjectFile) -> Result<SizeBreakdown, ProjectError> {
+    let mut sections = IndexMap::new();
+    sections.insert("config".to_string(), json_len(&project.config)?);
+    for workflow in &project.workflows {
+        sections.insert(format!("workflows.{}", workflow.id), json_len(workflow)?);
+    }
+    sections.insert("tokens".to_string(), json_len(&project.tokens)?);
+    sections.insert(
+        "component_styles".to_string(),
+        json_len(&project.component_styles)?,
+    );
+    sections.insert(
+        "category_presentation".to_string(),
+        json_len(&project.category_presentation)?,
+    );
+    for (key, value) in &project.extra {
+        sections.insert(key.clone(), json_len(value)?);
+    }
+
+    Ok(SizeBreakdown {
+        total: project.to_bytes(&SaveOptions::default())?.len(),
+        compacted: project.to_bytes(&SaveOptions::compact())?.len(),
+        sections,
+    })
+}
+
+fn json_len<T: Serialize>(value: &T) -> Result<usize, ProjectError> {
+    Ok(serde_json::to_vec(value)?.len())
+}
+
+/// Round every non-integer number to `decimals` places.
+fn round_numbers(value: &mut Value, decimals: u32) {
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            let factor = 10f64.powi(decimals as i32);
+            if let Some(rounded) = n
+                .as_f64()
+                .map(|f| (f * factor).round() / factor)
+                .and_then(serde_json::Number::from_f64)
+            {
+                *n = rounded;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| round_numbers(v, decimals)),
+        Value::Object(map) => map.values_mut().for_each(|v| round_numbers(v, decimals)),
+        _ => {}
+    }
+}
+
+/// Apply `f` to every string value inside `metadata` objects.
+fn visit_metadata(value: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|v| visit_metadata(v, f)),
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if key == "metadata"
+                    && let Value::Object(metadata) = child
+                {
+                    metadata.values_mut().for_each(&mut *f);
+                } else {
+                    visit_metadata(child, f);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace metadata strings that occur more than once with table references.
+fn encode_strings(value: &mut Value) {
+    let mut counts: IndexMap<String, usize> = IndexMap::new();
+    visit_metadata(value, &mut |v| {
+        if let Value::String(s) = v
+            && s.len() >= MIN_DICTIONARY_LEN
+        {
+            *counts.entry(s.clone()).or_default() += 1;
+        }
+    });
+    let table: IndexMap<String, usize> = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .enumerate()
+        .map(|(i, (s, _))| (s, i))
+        .collect();
+    if table.is_empty() {
+        return;
+    }
+
+    visit_metadata(value, &mut |v| {
+        if let Value::String(s) = v
+            && let Some(index) = table.get(s.as_str())
+        {
+            let mut reference = Map::new();
+            reference.insert(STRING_REF_KEY.to_string(), Value::from(*index));
+            *v = Value::Object(reference);
+        }
+    });
+    if let Value::Object(root) = value {
+        root.insert(
+            STRING_TABLE_KEY.to_string(),
+            Value::Array(table.into_keys().map(Value::String).collect()),
+        );
+    }
+}
+
+/// Resolve table references written by [`encode_strings`].
+fn decode_strings(value: &mut Value) {
+    let Some(Value::Array(table)) = value
+        .as_object_mut()
+        .and_then(|root| root.remove(STRING_TABLE_KEY))
+    else {
+        return;
+    };
+    visit_metadata(value, &mut |v| {
+        let entry = v
+            .as_object()
+            .filter(|map| map.len() == 1)
+            .and_then(|map| map.get(STRING_REF_KEY))
+            .and_then(Value::as_u64)
+            .and_then(|i| table.get(i as usize));
+        if let Some(entry) = entry {
+            *v = entry.clone();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Context, Workflow};
+
+    fn project() -> ProjectFile {
+        let source = Value::from("figma:file/abc123");
+        let mut context = Context::new("Home").with_id("c1");
+        context
+            .metadata
+            .insert("source".to_string(), source.clone());
+        context
+            .metadata
+            .insert("x".to_string(), Value::from(120.123456));
+        let mut workflow = Workflow::new("Main").with_id("w1");
+        workflow.metadata.insert("source".to_string(), source);
+        workflow
+            .metadata
+            .insert("zoom".to_string(), Value::from(0.3333333));
+        workflow.contexts.insert("c1".to_string(), context);
+
+        ProjectFile {
+            workflows: vec![workflow],
+            ..Default::default()
+        }
+    }
+
+    fn json(project: &ProjectFile) -> Value {
+        serde_json::to_value(project).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip_after_rounding() {
+        let project = project();
+        let bytes = project.to_bytes(&SaveOptions::compact()).unwrap();
+        assert!(bytes.starts_with(&GZIP_MAGIC));
+
+        let loaded = ProjectFile::from_bytes(&bytes).unwrap();
+        let mut expected = json(&project);
+        round_numbers(&mut expected, 2);
+        assert_eq!(json(&loaded), expected);
+
+        let w = &loaded.workflows[0];
+        assert_eq!(w.metadata["zoom"], Value::from(0.33));
+        assert_eq!(w.contexts["c1"].metadata["x"], Value::from(120.12));
+        assert_eq!(w.contexts["c1"].metadata["source"], "figma:file/abc123");
+
+        // Plain files still load, and unrounded output is lossless.
+        let plain = project.to_bytes(&SaveOptions::plain()).unwrap();
+        assert_eq!(
+            json(&ProjectFile::from_bytes(&plain).unwrap()),
+            json(&project)
+        );
+    }
+
+    #[test]
+    fn test_dictionary_encodes_repeated_metadata() {
+        let bytes = project()
+            .to_bytes(&SaveOptions::default().with_dictionary(true))
+            .unwrap();
+        let raw: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(
+            raw[STRING_TABLE_KEY],
+            serde_json::json!(["figma:file/abc123"])
+        );
+        assert_eq!(
+            raw["workflows"][0]["metadata"]["source"],
+            serde_json::json!({"$str": 0})
+        );
+        assert_eq!(
+            json(&ProjectFile::from_bytes(&bytes).unwrap()),
+            json(&project())
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields_survive_gzip() {
+        let newer = serde_json::json!({
+            "format_version": 2,
+            "workflows": [],
+            "assets": { "logo.svg": { "bytes": 2048 } },
+            "history": [{ "label": "Add workflow" }],
+        });
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&serde_json::to_vec(&newer).unwrap())
+            .unwrap();
+        let bytes = encoder.finish().unwrap();
+
+        let loaded = ProjectFile::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.format_version, 2);
+        assert_eq!(loaded.extra["assets"]["logo.svg"]["bytes"], 2048);
+
+        let saved = loaded.to_bytes(&SaveOptions::compact()).unwrap();
+        let reloaded = ProjectFile::from_bytes(&saved).unwrap();
+        assert_eq!(reloaded.extra["history"][0]["label"], "Add workflow");
+
+        let report = size_report(&reloaded).unwrap();
+        assert!(report.sections.contains_key("assets"));
+        assert!(report.sections.contains_key("history"));
+    }
+
+    #[test]
+    fn test_size_report() {
+        let mut project = project();
+        let mut big = Workflow::new("Big").with_id("w2");
+        for i in 0..50 {
+            let id = format!("c{}", i);
+            big.contexts
+                .insert(id.clone(), Context::new("Screen").with_id(&id));
+        }
+        project.workflows.push(big);
+
+        let report = size_report(&project).unwrap();
+
+        assert_eq!(report.largest(2)[0].0, "workflows.w2");
+        assert!(report.sections["workflows.w2"] > 10 * report.sections["workflows.w1"]);
+        assert!(report.compacted < report.total);
+        assert_eq!(report.total, serde_json::to_vec(&project).unwrap().len());
+    }
+}
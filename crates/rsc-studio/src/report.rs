@@ -0,0 +1,509 @@
+//! Machine-readable problem reports for CI.
+//!
+//! [`StudioStore::findings`] gathers everything the problems panel shows:
+//! rejected actions, token validation and component style lints. The
+//! findings export as stable JSON, as SARIF for code-scanning upload, or
+//! as GitHub workflow commands that annotate the project file in a PR.
+//! Entities map to JSON pointers into the project file, e.g.
+//! `/tokens/colors/primary`.
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::designer::css::{ComponentLint, LintScope, StyleLintKind, ValidationSeverity};
+use crate::error::{EntityRef, ErrorCode, StudioError};
+use crate::store::StudioStore;
+
+/// Version of the JSON report schema. Bumped on any breaking change.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Tool name recorded in SARIF output.
+pub const TOOL_NAME: &str = "rustscript-studio";
+
+/// Report error types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ReportError {
+    /// `--report-format` has an unknown value.
+    #[error("Unknown report format '{0}'; expected json, sarif or github")]
+    UnknownFormat(String),
+
+    /// `--fail-on` has an unknown value.
+    #[error("Unknown severity '{0}'; expected error, warning, info or none")]
+    UnknownSeverity(String),
+
+    /// A flag was given without its value.
+    #[error("{0} needs a value")]
+    MissingValue(&'static str),
+}
+
+/// Output format for a problems report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Sarif,
+    /// GitHub Actions workflow commands.
+    GithubAnnotations,
+}
+
+impl ReportFormat {
+    /// Parse a `--report-format` value.
+    pub fn from_arg(value: &str) -> Result<Self, ReportError> {
+        match value {
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            "github" => Ok(ReportFormat::GithubAnnotations),
+            _ => Err(ReportError::UnknownFormat(value.to_string())),
+        }
+    }
+}
+
+/// Options of the headless report entry point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportArgs {
+    pub format: ReportFormat,
+    /// Lowest severity that fails the run; `None` never fails.
+    pub fail_on: Option<ValidationSeverity>,
+    /// Arguments that aren't report flags, in order.
+    pub rest: Vec<String>,
+}
+
+impl Default for ReportArgs {
+    fn default() -> Self {
+        Self {
+            format: ReportFormat::Json,
+            fail_on: Some(ValidationSeverity::Error),
+            rest: Vec::new(),
+        }
+    }
+}
+
+impl ReportArgs {
+    /// Read `--report-format` and `--fail-on`, as `--flag value` or
+    /// `--flag=value`. Other arguments are collected in `rest`.
+    pub fn parse<I, S>(args: I) -> Result<Self, ReportError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let flag: &'static str = match flag.as_str() {
+                "--report-format" => "--report-format",
+                "--fail-on" => "--fail-on",
+                _ => {
+                    parsed.rest.push(arg);
+                    continue;
+                }
+            };
+            let value = inline
+                .or_else(|| args.next())
+                .ok_or(ReportError::MissingValue(flag))?;
+            if flag == "--report-format" {
+                parsed.format = ReportFormat::from_arg(&value)?;
+            } else {
+                parsed.fail_on = match value.as_str() {
+                    "error" => Some(ValidationSeverity::Error),
+                    "warning" => Some(ValidationSeverity::Warning),
+                    "info" => Some(ValidationSeverity::Info),
+                    "none" => None,
+                    _ => return Err(ReportError::UnknownSeverity(value)),
+                };
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Rendered report and the process exit code for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportOutcome {
+    pub output: String,
+    /// 1 if a finding is at or above the `--fail-on` severity, else 0.
+    pub exit_code: i32,
+}
+
+/// Render `findings` for the project at `project_path` as `args` asks.
+pub fn run_report(
+    findings: &[StudioError],
+    project_path: &str,
+    args: &ReportArgs,
+) -> ReportOutcome {
+    let output = match args.format {
+        ReportFormat::Json => problems_to_json(findings),
+        ReportFormat::Sarif => problems_to_sarif(findings, project_path),
+        ReportFormat::GithubAnnotations => problems_to_github_annotations(findings, project_path),
+    };
+    let failed = args.fail_on.is_some_and(|threshold| {
+        findings
+            .iter()
+            .any(|f| severity_rank(f.severity) >= severity_rank(threshold))
+    });
+    ReportOutcome {
+        output,
+        exit_code: i32::from(failed),
+    }
+}
+
+fn severity_rank(severity: ValidationSeverity) -> u8 {
+    match severity {
+        ValidationSeverity::Info => 0,
+        ValidationSeverity::Warning => 1,
+        ValidationSeverity::Error => 2,
+    }
+}
+
+pub(crate) fn severity_name(severity: ValidationSeverity) -> &'static str {
+    match severity {
+        ValidationSeverity::Error => "error",
+        ValidationSeverity::Warning => "warning",
+        ValidationSeverity::Info => "info",
+    }
+}
+
+/// Token path prefixes and their location under `/tokens`.
+const TOKEN_POINTERS: &[(&str, &str)] = &[
+    ("colors", "colors"),
+    ("spacing", "spacing"),
+    ("radius", "radius"),
+    ("shadows", "shadows"),
+    ("typography.fonts", "typography/fonts"),
+    ("typography.sizes", "typography/sizes"),
+    ("typography.weights", "typography/weights"),
+    ("transitions", "transitions"),
+    ("z-index", "z_index"),
+];
+
+/// Escape one JSON pointer reference token.
+fn pointer_token(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// JSON pointer to an entity in the project file, where it has one.
+pub fn entity_pointer(entity: &EntityRef) -> Option<String> {
+    match entity {
+        EntityRef::Token(path) => TOKEN_POINTERS.iter().find_map(|(prefix, pointer)| {
+            let rest = path.strip_prefix(prefix)?.strip_prefix('.')?;
+            let rest: Vec<String> = rest.split('.').map(pointer_token).collect();
+            Some(format!("/tokens/{}/{}", pointer, rest.join("/")))
+        }),
+        EntityRef::Component(name) => {
+            Some(format!("/component_styles/styles/{}", pointer_token(name)))
+        }
+        _ => None,
+    }
+}
+
+/// A component style lint as a problem on that component.
+fn style_lint_error(component: &str, lint: ComponentLint) -> StudioError {
+    let code = match lint.lint.kind {
+        StyleLintKind::ShorthandConflict => ErrorCode::StyleConflict,
+        StyleLintKind::EmptyValue | StyleLintKind::InvalidValue => ErrorCode::StyleInvalid,
+    };
+    let scope = match lint.scope {
+        LintScope::Base => String::new(),
+        LintScope::State(state) => format!(" ({})", state.label()),
+        LintScope::Breakpoint(breakpoint) => format!(" ({})", breakpoint.label()),
+    };
+    StudioError::new(
+        code,
+        format!("{}{}: {}", component, scope, lint.lint.message),
+    )
+    .with_severity(lint.lint.severity)
+    .with_entity(EntityRef::Component(component.to_string()))
+    .with_hint(lint.lint.suggestion)
+}
+
+impl StudioStore {
+    /// Everything the problems panel lists: rejected actions, then
+    /// workflow diagnostics, token validation, component style lints and
+    /// component styles using deprecated tokens.
+    pub fn findings(&self) -> Vec<StudioError> {
+        let mut findings = self.problems.clone();
+        findings.extend(
+            self.all_workflow_diagnostics()
+                .cloned()
+                .map(StudioError::from),
+        );
+        findings.extend(self.tokens.validate().into_iter().map(StudioError::from));
+        for (component, lints) in self.component_styles.lint_all() {
+            findings.extend(
+                lints
+                    .into_iter()
+                    .map(|lint| style_lint_error(&component, lint)),
+            );
+        }
+        for (component, uses) in self.component_styles.deprecated_token_uses(&self.tokens) {
+            findings.extend(uses.into_iter().map(|deprecated| {
+                StudioError::new(
+                    ErrorCode::DeprecatedToken,
+                    format!("{}: {}", component, deprecated.describe()),
+                )
+                .with_severity(ValidationSeverity::Warning)
+                .with_entity(EntityRef::Component(component.clone()))
+            }));
+        }
+        findings
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema: u32,
+    summary: JsonSummary,
+    findings: Vec<JsonFinding<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    errors: usize,
+    warnings: usize,
+    info: usize,
+}
+
+#[derive(Serialize)]
+struct JsonFinding<'a> {
+    code: ErrorCode,
+    severity: &'static str,
+    message: &'a str,
+    entity: Option<&'a EntityRef>,
+    location: Option<String>,
+    hint: Option<&'a str>,
+    /// Whether the finding carries a suggested resolution.
+    fix_available: bool,
+}
+
+/// Findings as JSON with a versioned, stable schema.
+pub fn problems_to_json(findings: &[StudioError]) -> String {
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+    let report = JsonReport {
+        schema: REPORT_SCHEMA_VERSION,
+        summary: JsonSummary {
+            errors: count(ValidationSeverity::Error),
+            warnings: count(ValidationSeverity::Warning),
+            info: count(ValidationSeverity::Info),
+        },
+        findings: findings
+            .iter()
+            .map(|f| JsonFinding {
+                code: f.code,
+                severity: severity_name(f.severity),
+                message: &f.message,
+                entity: f.entity.as_ref(),
+                location: f.entity.as_ref().and_then(entity_pointer),
+                hint: f.recovery_hint.as_deref(),
+                fix_available: f.recovery_hint.is_some(),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// Findings as a SARIF 2.1.0 log, located in the project file.
+pub fn problems_to_sarif(findings: &[StudioError], project_path: &str) -> String {
+    let mut rules: Vec<&str> = Vec::new();
+    for finding in findings {
+        if !rules.contains(&finding.code.as_str()) {
+            rules.push(finding.code.as_str());
+        }
+    }
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|f| {
+            let level = match f.severity {
+                ValidationSeverity::Error => "error",
+                ValidationSeverity::Warning => "warning",
+                ValidationSeverity::Info => "note",
+            };
+            let mut location = serde_json::json!({
+                "physicalLocation": { "artifactLocation": { "uri": project_path } }
+            });
+            if let Some(pointer) = f.entity.as_ref().and_then(entity_pointer) {
+                location["logicalLocations"] =
+                    serde_json::json!([{ "fullyQualifiedName": pointer, "kind": "member" }]);
+            }
+            let mut result = serde_json::json!({
+                "ruleId": f.code.as_str(),
+                "level": level,
+                "message": { "text": f.message },
+                "locations": [location],
+            });
+            if let Some(hint) = &f.recovery_hint {
+                result["properties"] = serde_json::json!({ "hint": hint });
+            }
+            result
+        })
+        .collect();
+    let log = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "rules": rules
+                        .iter()
+                        .map(|id| serde_json::json!({ "id": id }))
+                        .collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_default()
+}
+
+/// Escape a workflow command message.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Findings as GitHub Actions workflow commands, one line each.
+pub fn problems_to_github_annotations(findings: &[StudioError], project_path: &str) -> String {
+    findings
+        .iter()
+        .map(|f| {
+            let command = match f.severity {
+                ValidationSeverity::Error => "error",
+                ValidationSeverity::Warning => "warning",
+                ValidationSeverity::Info => "notice",
+            };
+            let title = match f.entity.as_ref().and_then(entity_pointer) {
+                Some(pointer) => format!("{} at {}", f.code.as_str(), pointer),
+                None => f.code.as_str().to_string(),
+            };
+            let mut message = f.message.clone();
+            if let Some(hint) = &f.recovery_hint {
+                message = format!("{}\n{}", message, hint);
+            }
+            format!(
+                "::{} file={},title={}::{}\n",
+                command,
+                escape_property(project_path),
+                escape_property(&title),
+                escape_data(&message)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{ComponentStyle, StateVariant, TokenValue};
+
+    const PROJECT: &str = "design/studio.json";
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        store
+            .tokens
+            .colors
+            .insert("brand".to_string(), TokenValue::Simple("#12".to_string()));
+        store
+            .tokens
+            .typography
+            .sizes
+            .insert("lg".to_string(), TokenValue::Simple("".to_string()));
+        let mut button = ComponentStyle::default();
+        button.base.set("display", Some("flexbox".to_string()));
+        let hover = button.states.entry(StateVariant::Hover).or_default();
+        hover.set("padding", Some("4px".to_string()));
+        hover.set("padding-top", Some("8px".to_string()));
+        store.component_styles.set("button".to_string(), button);
+        store.problems.push(
+            StudioError::new(ErrorCode::UnknownSetting, "Unknown setting: editor.font")
+                .with_severity(ValidationSeverity::Info)
+                .with_entity(EntityRef::Setting("editor.font".to_string())),
+        );
+        store
+    }
+
+    #[test]
+    fn test_report_fixtures() {
+        let findings = store().findings();
+        assert_eq!(
+            problems_to_json(&findings),
+            include_str!("report/fixture.json").trim_end()
+        );
+        assert_eq!(
+            problems_to_sarif(&findings, PROJECT),
+            include_str!("report/fixture.sarif").trim_end()
+        );
+        assert_eq!(
+            problems_to_github_annotations(&findings, PROJECT),
+            include_str!("report/fixture.txt")
+        );
+    }
+
+    #[test]
+    fn test_entity_pointers() {
+        let token = |path: &str| entity_pointer(&EntityRef::Token(path.to_string()));
+        assert_eq!(
+            token("colors.primary").as_deref(),
+            Some("/tokens/colors/primary")
+        );
+        assert_eq!(
+            token("typography.sizes.xl").as_deref(),
+            Some("/tokens/typography/sizes/xl")
+        );
+        assert_eq!(
+            token("z-index.modal").as_deref(),
+            Some("/tokens/z_index/modal")
+        );
+        assert_eq!(token("colors").as_deref(), None);
+        assert_eq!(
+            entity_pointer(&EntityRef::Component("a/b~c".to_string())).as_deref(),
+            Some("/component_styles/styles/a~1b~0c")
+        );
+        assert_eq!(entity_pointer(&EntityRef::Node("n1".to_string())), None);
+    }
+
+    #[test]
+    fn test_report_args_and_fail_on() {
+        let args =
+            ReportArgs::parse(["check", "--report-format=sarif", "--fail-on", "warning"]).unwrap();
+        assert_eq!(args.format, ReportFormat::Sarif);
+        assert_eq!(args.fail_on, Some(ValidationSeverity::Warning));
+        assert_eq!(args.rest, ["check"]);
+        assert_eq!(
+            ReportArgs::parse(["--report-format", "xml"]),
+            Err(ReportError::UnknownFormat("xml".to_string()))
+        );
+        assert_eq!(
+            ReportArgs::parse(["--fail-on"]),
+            Err(ReportError::MissingValue("--fail-on"))
+        );
+
+        let warning = [StudioError::new(ErrorCode::TokenInvalid, "bad")
+            .with_severity(ValidationSeverity::Warning)];
+        let exit = |args: &[&str]| {
+            run_report(
+                &warning,
+                PROJECT,
+                &ReportArgs::parse(args.iter().copied()).unwrap(),
+            )
+            .exit_code
+        };
+        assert_eq!(exit(&[]), 0);
+        assert_eq!(exit(&["--fail-on", "warning"]), 1);
+        assert_eq!(exit(&["--fail-on", "info"]), 1);
+        assert_eq!(exit(&["--fail-on", "none"]), 0);
+        assert_eq!(
+            run_report(&[], PROJECT, &ReportArgs::default()).output,
+            problems_to_json(&[])
+        );
+    }
+}
@@ -0,0 +1,510 @@
+//! Fuzzy search over the project, for the command palette.
+//!
+//! [`StudioSearchIndex`] holds one entry per token, component style,
+//! workflow, context and preset. A query matches entry labels as a
+//! case-insensitive subsequence; matches at word starts, runs of
+//! consecutive characters and label prefixes score higher. Secondary text
+//! (token values, style properties, descriptions) is searched too, at a
+//! discount. After an edit, update just the touched entries instead of
+//! rebuilding.
+
+use std::ops::Range;
+
+use indexmap::IndexMap;
+
+use crate::designer::css::{ComponentStyle, StyleProperties, TokenValue};
+use crate::entity::{Context, EntityId, Workflow};
+use crate::store::StudioStore;
+
+/// Score per matched character.
+const MATCH_SCORE: i64 = 16;
+/// Bonus for a character right after the previous match.
+const CONSECUTIVE_BONUS: i64 = 15;
+/// Bonus for a match at the start of a word.
+const BOUNDARY_BONUS: i64 = 20;
+/// Bonus for a match at the start of the text.
+const START_BONUS: i64 = 30;
+/// Bonus when the whole query is a prefix of the text.
+const PREFIX_BONUS: i64 = 25;
+/// Most characters skipped before the first match that are penalized.
+const MAX_LEADING_PENALTY: i64 = 15;
+
+/// Result category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchKind {
+    Token,
+    Component,
+    Workflow,
+    Context,
+    Preset,
+}
+
+impl SearchKind {
+    /// Heading for the category in the palette.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchKind::Token => "Tokens",
+            SearchKind::Component => "Components",
+            SearchKind::Workflow => "Workflows",
+            SearchKind::Context => "Contexts",
+            SearchKind::Preset => "Presets",
+        }
+    }
+}
+
+/// Where a hit navigates to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SearchTarget {
+    /// Token path, e.g. `colors.primary`.
+    Token(String),
+    /// Component style name.
+    Component(String),
+    Workflow(EntityId),
+    Context {
+        workflow: EntityId,
+        context: EntityId,
+    },
+    Preset {
+        workflow: EntityId,
+        context: EntityId,
+        preset: EntityId,
+    },
+}
+
+impl SearchTarget {
+    pub fn kind(&self) -> SearchKind {
+        match self {
+            SearchTarget::Token(_) => SearchKind::Token,
+            SearchTarget::Component(_) => SearchKind::Component,
+            SearchTarget::Workflow(_) => SearchKind::Workflow,
+            SearchTarget::Context { .. } => SearchKind::Context,
+            SearchTarget::Preset { .. } => SearchKind::Preset,
+        }
+    }
+
+    fn workflow(&self) -> Option<&str> {
+        match self {
+            SearchTarget::Workflow(workflow)
+            | SearchTarget::Context { workflow, .. }
+            | SearchTarget::Preset { workflow, .. } => Some(workflow),
+            _ => None,
+        }
+    }
+}
+
+/// One query result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    pub target: SearchTarget,
+    /// Text to show, e.g. the token path or context name.
+    pub label: String,
+    /// Secondary text, e.g. the token value or owning workflow.
+    pub detail: Option<String>,
+    /// Higher is better.
+    pub score: i64,
+    /// Byte ranges of `label` to highlight. Empty when only the secondary
+    /// text matched.
+    pub ranges: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    label: String,
+    detail: Option<String>,
+    /// Secondary searchable text.
+    keywords: Vec<String>,
+}
+
+/// Search index over the store's tokens, component styles and workflows.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StudioSearchIndex {
+    entries: IndexMap<SearchTarget, Entry>,
+}
+
+impl StudioSearchIndex {
+    /// Index the current store state.
+    pub fn build(store: &StudioStore) -> Self {
+        let mut index = Self::default();
+        for path in store.tokens.all_paths() {
+            index.update_token(store, &path);
+        }
+        for name in store.component_styles.styles.keys() {
+            index.update_component(store, name);
+        }
+        for id in store.workflows.keys() {
+            index.update_workflow(store, id);
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Re-index one token after it was added, edited or removed.
+    pub fn update_token(&mut self, store: &StudioStore, path: &str) {
+        let target = SearchTarget::Token(path.to_string());
+        let value = path.rsplit_once('.').and_then(|(prefix, name)| {
+            let categories = store.tokens.categories();
+            let (_, tokens) = categories.into_iter().find(|(p, _)| *p == prefix)?;
+            tokens.get(name).map(token_text)
+        });
+        let Some(value) = value else {
+            self.remove(&target);
+            return;
+        };
+        let mut keywords = vec![value.clone()];
+        if let Some(docs) = store.tokens.token_docs(path) {
+            keywords.extend(docs.description.iter().cloned());
+            keywords.extend(docs.tags.iter().cloned());
+        }
+        self.insert(target, path, Some(value), keywords);
+    }
+
+    /// Re-index one component style.
+    pub fn update_component(&mut self, store: &StudioStore, name: &str) {
+        let target = SearchTarget::Component(name.to_string());
+        match store.component_styles.get(name) {
+            Some(style) => self.insert(target, name, None, style_properties(style)),
+            None => self.remove(&target),
+        }
+    }
+
+    /// Re-index a workflow with its contexts and presets.
+    pub fn update_workflow(&mut self, store: &StudioStore, id: &str) {
+        self.entries
+            .retain(|target, _| target.workflow() != Some(id));
+        let Some(workflow) = store.workflows.get(id) else {
+            return;
+        };
+        self.insert(
+            SearchTarget::Workflow(workflow.id.clone()),
+            &workflow.name,
+            None,
+            workflow.description.iter().cloned().collect(),
+        );
+        for context in workflow.contexts.values() {
+            self.insert_context(workflow, context);
+        }
+    }
+
+    /// Re-index one context (a navigation node) with its presets.
+    pub fn update_context(&mut self, store: &StudioStore, workflow_id: &str, context_id: &str) {
+        self.entries.retain(|target, _| match target {
+            SearchTarget::Context { workflow, context }
+            | SearchTarget::Preset {
+                workflow, context, ..
+            } => workflow != workflow_id || context != context_id,
+            _ => true,
+        });
+        let Some(workflow) = store.workflows.get(workflow_id) else {
+            return;
+        };
+        if let Some(context) = workflow.contexts.get(context_id) {
+            self.insert_context(workflow, context);
+        }
+    }
+
+    /// The best `limit` matches for `text`, best first.
+    pub fn query(&self, text: &str, limit: usize) -> Vec<SearchHit> {
+        let pattern: Vec<char> = text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let mut hits: Vec<SearchHit> = self
+            .entries
+            .iter()
+            .filter_map(|(target, entry)| {
+                let label = fuzzy_match(&pattern, &entry.label);
+                let keyword = entry
+                    .keywords
+                    .iter()
+                    .filter_map(|k| fuzzy_match(&pattern, k))
+                    .map(|(score, _)| score / 2)
+                    .max();
+                let (score, ranges) = match (label, keyword) {
+                    (Some((score, ranges)), Some(k)) if score >= k => (score, ranges),
+                    (Some((score, ranges)), None) => (score, ranges),
+                    (_, Some(k)) => (k, Vec::new()),
+                    (None, None) => return None,
+                };
+                Some(SearchHit {
+                    kind: target.kind(),
+                    target: target.clone(),
+                    label: entry.label.clone(),
+                    detail: entry.detail.clone(),
+                    score,
+                    ranges,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(a.label.len().cmp(&b.label.len()))
+                .then_with(|| a.label.cmp(&b.label))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    fn insert_context(&mut self, workflow: &Workflow, context: &Context) {
+        self.insert(
+            SearchTarget::Context {
+                workflow: workflow.id.clone(),
+                context: context.id.clone(),
+            },
+            &context.name,
+            Some(workflow.name.clone()),
+            context.description.iter().cloned().collect(),
+        );
+        for preset in context.presets.values() {
+            self.insert(
+                SearchTarget::Preset {
+                    workflow: workflow.id.clone(),
+                    context: context.id.clone(),
+                    preset: preset.id.clone(),
+                },
+                &preset.name,
+                Some(format!("{} › {}", workflow.name, context.name)),
+                preset.description.iter().cloned().collect(),
+            );
+        }
+    }
+
+    fn insert(
+        &mut self,
+        target: SearchTarget,
+        label: &str,
+        detail: Option<String>,
+        keywords: Vec<String>,
+    ) {
+        let entry = Entry {
+            label: label.to_string(),
+            detail,
+            keywords,
+        };
+        self.entries.insert(target, entry);
+    }
+
+    fn remove(&mut self, target: &SearchTarget) {
+        self.entries.shift_remove(target);
+    }
+}
+
+/// A token value as one line of text.
+fn token_text(value: &TokenValue) -> String {
+    match value {
+        TokenValue::Simple(value) => value.clone(),
+        TokenValue::Adaptive { light, dark } => format!("{} / {}", light, dark),
+        TokenValue::Scale(steps) => steps
+            .iter()
+            .map(|(step, value)| format!("{}: {}", step, value))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// Names of the properties a style sets anywhere.
+fn style_properties(style: &ComponentStyle) -> Vec<String> {
+    let all: Vec<&StyleProperties> = std::iter::once(&style.base)
+        .chain(style.states.values())
+        .chain(style.breakpoints.values())
+        .collect();
+    StyleProperties::PROPERTY_NAMES
+        .iter()
+        .filter(|name| all.iter().any(|props| props.get(name).is_some()))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Score `text` against a lowercased `pattern`, with the matched byte
+/// ranges. `None` unless every pattern character appears in order.
+fn fuzzy_match(pattern: &[char], text: &str) -> Option<(i64, Vec<Range<usize>>)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|(_, c)| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let boundary = |i: usize| {
+        i == 0 || {
+            let (prev, c) = (chars[i - 1].1, chars[i].1);
+            !prev.is_alphanumeric() || (prev.is_lowercase() && c.is_uppercase())
+        }
+    };
+
+    // Try every start for the first character; later ones match greedily.
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in (0..lower.len()).filter(|&i| lower[i] == pattern[0]) {
+        let mut positions = vec![start];
+        for &wanted in &pattern[1..] {
+            let from = positions[positions.len() - 1] + 1;
+            match (from..lower.len()).find(|&i| lower[i] == wanted) {
+                Some(i) => positions.push(i),
+                None => break,
+            }
+        }
+        if positions.len() < pattern.len() {
+            break;
+        }
+        let mut score = -(start as i64).min(MAX_LEADING_PENALTY);
+        for (n, &i) in positions.iter().enumerate() {
+            score += MATCH_SCORE;
+            if i == 0 {
+                score += START_BONUS;
+            } else if boundary(i) {
+                score += BOUNDARY_BONUS;
+            }
+            if n > 0 {
+                let gap = i - positions[n - 1] - 1;
+                score += if gap == 0 {
+                    CONSECUTIVE_BONUS
+                } else {
+                    -(gap as i64)
+                };
+            }
+        }
+        if best.as_ref().is_none_or(|(b, _)| score > *b) {
+            best = Some((score, positions));
+        }
+    }
+    let (mut score, positions) = best?;
+    if lower.starts_with(pattern) {
+        score += PREFIX_BONUS;
+    }
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for i in positions {
+        let (at, c) = chars[i];
+        match ranges.last_mut() {
+            Some(range) if range.end == at => range.end = at + c.len_utf8(),
+            _ => ranges.push(at..at + c.len_utf8()),
+        }
+    }
+    Some((score, ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenDocs;
+    use crate::entity::Preset;
+
+    fn store() -> StudioStore {
+        let mut store = StudioStore::new();
+        let simple = |v: &str| TokenValue::Simple(v.to_string());
+        store
+            .tokens
+            .colors
+            .insert("primary".to_string(), simple("#2563eb"));
+        store
+            .tokens
+            .colors
+            .insert("primary-hover".to_string(), simple("#1d4ed8"));
+        store.tokens.spacing.insert("sm".to_string(), simple("4px"));
+        let mut card = ComponentStyle::default();
+        card.base.set("padding", Some("8px".to_string()));
+        store
+            .component_styles
+            .styles
+            .insert("PrimaryCard".to_string(), card);
+
+        let mut workflow = Workflow::new("Pricing").with_id("w1");
+        let mut context = Context::new("Profile").with_id("c1");
+        context.add_preset(Preset::new("Compact").with_id("p1"));
+        workflow.add_context(context);
+        store.workflows.insert("w1".to_string(), workflow);
+        store
+    }
+
+    fn labels(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_scoring_order() {
+        let index = StudioSearchIndex::build(&store());
+        assert_eq!(index.len(), 7);
+
+        let hits = index.query("primary", 10);
+        assert_eq!(
+            labels(&hits),
+            ["PrimaryCard", "colors.primary", "colors.primary-hover"]
+        );
+        assert_eq!(hits[0].kind, SearchKind::Component);
+        assert_eq!(hits[1].ranges, [Range { start: 7, end: 14 }]);
+        assert_eq!(hits[1].detail.as_deref(), Some("#2563eb"));
+
+        // Word starts beat scattered letters.
+        let hits = index.query("pc", 10);
+        assert_eq!(labels(&hits)[0], "PrimaryCard");
+        assert_eq!(hits[0].ranges, [0..1, 7..8]);
+        let hits = index.query("pr", 3);
+        assert_eq!(labels(&hits), ["Pricing", "Profile", "PrimaryCard"]);
+        assert_eq!(
+            hits[1].target,
+            SearchTarget::Context {
+                workflow: "w1".to_string(),
+                context: "c1".to_string(),
+            }
+        );
+
+        // Secondary text matches rank below label matches.
+        let hits = index.query("padding", 10);
+        assert_eq!(labels(&hits), ["PrimaryCard"]);
+        assert!(hits[0].ranges.is_empty());
+        assert_eq!(labels(&index.query("2563", 10)), ["colors.primary"]);
+        assert!(index.query("zzz", 10).is_empty());
+        assert!(index.query("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut store = store();
+        let mut index = StudioSearchIndex::build(&store);
+
+        store.tokens.colors.shift_remove("primary-hover");
+        store.tokens.colors.insert(
+            "danger".to_string(),
+            TokenValue::Simple("#dc2626".to_string()),
+        );
+        index.update_token(&store, "colors.primary-hover");
+        index.update_token(&store, "colors.danger");
+        assert_eq!(labels(&index.query("dang", 10)), ["colors.danger"]);
+        store.tokens.docs.insert(
+            "colors.danger".to_string(),
+            TokenDocs::default().with_tag("feedback"),
+        );
+        index.update_token(&store, "colors.danger");
+        assert_eq!(labels(&index.query("feedback", 10)), ["colors.danger"]);
+
+        store.workflows["w1"].contexts["c1"].name = "Account".to_string();
+        store.workflows["w1"].contexts["c1"].presets.clear();
+        index.update_context(&store, "w1", "c1");
+        assert_eq!(labels(&index.query("acc", 10)), ["Account"]);
+        assert!(index.query("compact", 10).is_empty());
+
+        store.workflows["w1"].name = "Plans".to_string();
+        index.update_workflow(&store, "w1");
+        store.component_styles.styles.shift_remove("PrimaryCard");
+        index.update_component(&store, "PrimaryCard");
+        assert_eq!(index, StudioSearchIndex::build(&store));
+        assert_eq!(
+            index.query("account", 1)[0].detail.as_deref(),
+            Some("Plans")
+        );
+
+        store.workflows.shift_remove("w1");
+        index.update_workflow(&store, "w1");
+        assert_eq!(index.len(), 3);
+    }
+}
@@ -0,0 +1,659 @@
+//! Typed settings registry.
+//!
+//! Every setting the Settings page shows is declared here with its type,
+//! default and storage location. Values are changed through
+//! [`StudioStore::set_setting`], which validates them and runs the
+//! setting's effect so dependent state stays in sync.
+
+pub mod interaction;
+pub mod studio;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::designer::edge_tooltip::DEFAULT_TOOLTIP_DELAY_MS;
+use crate::designer::navigation::NAVIGATION_SNAP_GRID;
+use crate::store::dirty::ChangeCategory;
+use crate::store::events::StoreEvent;
+use crate::store::{StudioStore, ThemeMode};
+
+/// Settings error types.
+#[derive(Debug, Error, PartialEq)]
+pub enum SettingsError {
+    /// No setting is registered with this id.
+    #[error("Unknown setting: {0}")]
+    UnknownSetting(String),
+
+    /// The value has the wrong type.
+    #[error("Setting '{id}' expects a {expected} value")]
+    TypeMismatch { id: String, expected: &'static str },
+
+    /// A number is outside the allowed range.
+    #[error("Setting '{id}' must be between {min} and {max}, got {value}")]
+    OutOfRange {
+        id: String,
+        min: f64,
+        max: f64,
+        value: f64,
+    },
+
+    /// A string is not one of the allowed options.
+    #[error("Setting '{id}' does not allow '{value}'")]
+    InvalidOption { id: String, value: String },
+}
+
+/// Setting value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SettingValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl SettingValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SettingValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            SettingValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SettingValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for SettingValue {
+    fn from(value: bool) -> Self {
+        SettingValue::Bool(value)
+    }
+}
+
+impl From<f64> for SettingValue {
+    fn from(value: f64) -> Self {
+        SettingValue::Number(value)
+    }
+}
+
+impl From<&str> for SettingValue {
+    fn from(value: &str) -> Self {
+        SettingValue::String(value.to_string())
+    }
+}
+
+/// A setting that changed value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingChange {
+    pub id: String,
+    pub old: SettingValue,
+    pub new: SettingValue,
+}
+
+/// Setting value type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingKind {
+    Bool,
+    /// One of a fixed set of string options.
+    Enum(&'static [&'static str]),
+    /// A number within an inclusive range.
+    Number {
+        min: f64,
+        max: f64,
+    },
+    String,
+}
+
+impl SettingKind {
+    fn name(&self) -> &'static str {
+        match self {
+            SettingKind::Bool => "boolean",
+            SettingKind::Enum(_) => "option",
+            SettingKind::Number { .. } => "number",
+            SettingKind::String => "string",
+        }
+    }
+}
+
+/// Where a setting is persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingStorage {
+    /// Studio configuration (`StudioConfig`).
+    Config,
+    /// The open project.
+    Project,
+    /// Session UI state; not written to disk by the project.
+    Ui,
+}
+
+/// Callback run after a setting changes.
+pub type SettingEffect = fn(&mut StudioStore, &SettingValue);
+
+/// Setting declaration.
+#[derive(Debug, Clone)]
+pub struct SettingDef {
+    /// Dotted id; the first segment is the Settings page section.
+    pub id: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+    pub kind: SettingKind,
+    pub default: SettingValue,
+    pub storage: SettingStorage,
+    pub effect: Option<SettingEffect>,
+}
+
+impl SettingDef {
+    /// Check a value against this setting's type and range.
+    pub fn validate(&self, value: &SettingValue) -> Result<(), SettingsError> {
+        let mismatch = || SettingsError::TypeMismatch {
+            id: self.id.to_string(),
+            expected: self.kind.name(),
+        };
+        match (&self.kind, value) {
+            (SettingKind::Bool, SettingValue::Bool(_)) => Ok(()),
+            (SettingKind::String, SettingValue::String(_)) => Ok(()),
+            (SettingKind::Enum(options), SettingValue::String(s)) => {
+                if options.contains(&s.as_str()) {
+                    Ok(())
+                } else {
+                    Err(SettingsError::InvalidOption {
+                        id: self.id.to_string(),
+                        value: s.clone(),
+                    })
+                }
+            }
+            (SettingKind::Number { min, max }, SettingValue::Number(n)) => {
+                if (*min..=*max).contains(n) {
+                    Ok(())
+                } else {
+                    Err(SettingsError::OutOfRange {
+                        id: self.id.to_string(),
+                        min: *min,
+                        max: *max,
+                        value: *n,
+                    })
+                }
+            }
+            _ => Err(mismatch()),
+        }
+    }
+
+    /// Section name, taken from the id prefix.
+    pub fn section(&self) -> &'static str {
+        self.id.split('.').next().unwrap_or(self.id)
+    }
+}
+
+/// Registry of setting declarations.
+#[derive(Debug, Clone)]
+pub struct SettingsRegistry {
+    defs: IndexMap<&'static str, SettingDef>,
+}
+
+impl Default for SettingsRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl SettingsRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self {
+            defs: IndexMap::new(),
+        }
+    }
+
+    /// Register a setting, replacing any with the same id.
+    pub fn register(&mut self, def: SettingDef) {
+        self.defs.insert(def.id, def);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SettingDef> {
+        self.defs.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SettingDef> {
+        self.defs.values()
+    }
+
+    /// The settings shipped with the studio.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(SettingDef {
+            id: "appearance.theme",
+            label: "Theme",
+            description: "Studio color scheme",
+            kind: SettingKind::Enum(&["system", "light", "dark"]),
+            default: "system".into(),
+            storage: SettingStorage::Config,
+            effect: Some(|store, value| {
+                store.ui.theme = value
+                    .as_str()
+                    .and_then(ThemeMode::from_key)
+                    .unwrap_or_default();
+            }),
+        });
+        registry.register(SettingDef {
+            id: "editor.autosave",
+            label: "Autosave",
+            description: "Save changes automatically",
+            kind: SettingKind::Bool,
+            default: true.into(),
+            storage: SettingStorage::Config,
+            effect: Some(|store, value| {
+                store.ui.autosave.running = value.as_bool().unwrap_or(true);
+            }),
+        });
+        registry.register(SettingDef {
+            id: "editor.autosave_interval",
+            label: "Autosave interval",
+            description: "Seconds between automatic saves",
+            kind: SettingKind::Number {
+                min: 5.0,
+                max: 600.0,
+            },
+            default: 30.0.into(),
+            storage: SettingStorage::Config,
+            effect: Some(|store, value| {
+                if let Some(secs) = value.as_number() {
+                    store.ui.autosave.interval_secs = secs as u64;
+                }
+            }),
+        });
+        registry.register(SettingDef {
+            id: "editor.keyboard_scheme",
+            label: "Keyboard scheme",
+            description: "Key bindings used by the editor",
+            kind: SettingKind::Enum(&["default", "vim", "emacs"]),
+            default: "default".into(),
+            storage: SettingStorage::Config,
+            effect: None,
+        });
+        registry.register(SettingDef {
+            id: "editor.recent_projects",
+            label: "Recent projects",
+            description: "Projects kept in the recent list",
+            kind: SettingKind::Number {
+                min: 0.0,
+                max: 50.0,
+            },
+            default: 10.0.into(),
+            storage: SettingStorage::Config,
+            effect: None,
+        });
+        registry.register(SettingDef {
+            id: "canvas.auto_layout",
+            label: "Auto layout",
+            description: "Re-run layout when the flow changes",
+            kind: SettingKind::Bool,
+            default: true.into(),
+            storage: SettingStorage::Ui,
+            effect: Some(|store, value| {
+                store.ui.canvas.auto_layout_enabled = value.as_bool().unwrap_or(true);
+            }),
+        });
+        registry.register(SettingDef {
+            id: "canvas.show_minimap",
+            label: "Show minimap",
+            description: "Display the canvas minimap",
+            kind: SettingKind::Bool,
+            default: false.into(),
+            storage: SettingStorage::Ui,
+            effect: Some(|store, value| {
+                store.ui.canvas.show_minimap = value.as_bool().unwrap_or(false);
+            }),
+        });
+        registry.register(SettingDef {
+            id: "canvas.tooltip_delay",
+            label: "Tooltip delay",
+            description: "Milliseconds to hover an edge before its tooltip shows",
+            kind: SettingKind::Number {
+                min: 0.0,
+                max: 5000.0,
+            },
+            default: (DEFAULT_TOOLTIP_DELAY_MS as f64).into(),
+            storage: SettingStorage::Config,
+            effect: Some(|store, value| {
+                store.ui.canvas.tooltip_delay_ms = value
+                    .as_number()
+                    .map_or(DEFAULT_TOOLTIP_DELAY_MS, |ms| ms as u64);
+            }),
+        });
+        registry.register(SettingDef {
+            id: "canvas.snap_grid",
+            label: "Snap grid",
+            description: "Grid dropped nodes snap to, in canvas units; 0 turns snapping off",
+            kind: SettingKind::Number {
+                min: 0.0,
+                max: 64.0,
+            },
+            default: NAVIGATION_SNAP_GRID.into(),
+            storage: SettingStorage::Config,
+            effect: Some(|store, value| {
+                store.ui.canvas.snap_grid = value.as_number().unwrap_or(NAVIGATION_SNAP_GRID);
+            }),
+        });
+        interaction::register(&mut registry);
+        registry.register(SettingDef {
+            id: "export.format",
+            label: "Export format",
+            description: "Default format for workflow export",
+            kind: SettingKind::Enum(&["yaml", "json"]),
+            default: "yaml".into(),
+            storage: SettingStorage::Project,
+            effect: None,
+        });
+        registry
+    }
+}
+
+/// A persisted setting that could not be applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedSetting {
+    pub id: String,
+    pub storage: SettingStorage,
+    pub reason: String,
+}
+
+/// Setting values plus their registry.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub registry: SettingsRegistry,
+    /// Values that differ from the registry default.
+    values: IndexMap<String, SettingValue>,
+    /// Persisted entries kept verbatim because they could not be applied.
+    unknown: IndexMap<String, (SettingStorage, serde_json::Value)>,
+}
+
+impl Settings {
+    /// Current value of a setting, falling back to its default.
+    pub fn get(&self, id: &str) -> Option<&SettingValue> {
+        self.values
+            .get(id)
+            .or_else(|| self.registry.get(id).map(|d| &d.default))
+    }
+
+    /// Validate and store a value without running effects.
+    fn set(&mut self, id: &str, value: SettingValue) -> Result<&SettingDef, SettingsError> {
+        let def = self
+            .registry
+            .get(id)
+            .ok_or_else(|| SettingsError::UnknownSetting(id.to_string()))?;
+        def.validate(&value)?;
+        if value == def.default {
+            self.values.shift_remove(id);
+        } else {
+            self.values.insert(id.to_string(), value);
+        }
+        self.unknown.shift_remove(id);
+        Ok(def)
+    }
+
+    /// Persisted entries that were preserved but not applied.
+    pub fn flagged(&self) -> Vec<FlaggedSetting> {
+        self.unknown
+            .iter()
+            .map(|(id, (storage, _))| FlaggedSetting {
+                id: id.clone(),
+                storage: *storage,
+                reason: match self.registry.get(id) {
+                    Some(_) => "invalid value".to_string(),
+                    None => "unknown setting".to_string(),
+                },
+            })
+            .collect()
+    }
+
+    /// Values to persist for a storage location, including preserved unknown entries.
+    pub fn persisted(&self, storage: SettingStorage) -> IndexMap<String, serde_json::Value> {
+        let mut out = IndexMap::new();
+        for def in self.registry.iter().filter(|d| d.storage == storage) {
+            if let Some(value) = self.values.get(def.id) {
+                out.insert(
+                    def.id.to_string(),
+                    serde_json::to_value(value).unwrap_or_default(),
+                );
+            }
+        }
+        for (id, (s, value)) in &self.unknown {
+            if *s == storage {
+                out.insert(id.clone(), value.clone());
+            }
+        }
+        out
+    }
+
+    /// Build the Settings page view model.
+    pub fn view_model(&self) -> SettingsViewModel {
+        let mut sections: IndexMap<&'static str, Vec<SettingItem>> = IndexMap::new();
+        for def in self.registry.iter() {
+            let value = self.values.get(def.id).unwrap_or(&def.default).clone();
+            sections
+                .entry(def.section())
+                .or_default()
+                .push(SettingItem {
+                    id: def.id,
+                    label: def.label,
+                    description: def.description,
+                    kind: def.kind.clone(),
+                    is_default: value == def.default,
+                    value,
+                });
+        }
+        SettingsViewModel {
+            sections: sections
+                .into_iter()
+                .map(|(name, items)| SettingsSection { name, items })
+                .collect(),
+            flagged: self.flagged(),
+        }
+    }
+}
+
+/// View model for the Settings page.
+#[derive(Debug, Clone)]
+pub struct SettingsViewModel {
+    pub sections: Vec<SettingsSection>,
+    /// Persisted settings that were preserved but not applied.
+    pub flagged: Vec<FlaggedSetting>,
+}
+
+/// A group of settings sharing an id prefix.
+#[derive(Debug, Clone)]
+pub struct SettingsSection {
+    pub name: &'static str,
+    pub items: Vec<SettingItem>,
+}
+
+/// A single row on the Settings page.
+#[derive(Debug, Clone)]
+pub struct SettingItem {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+    pub kind: SettingKind,
+    pub value: SettingValue,
+    pub is_default: bool,
+}
+
+impl StudioStore {
+    /// Validate and apply a setting, running its effect.
+    pub fn set_setting(
+        &mut self,
+        id: &str,
+        value: impl Into<SettingValue>,
+    ) -> Result<(), SettingsError> {
+        let change = self.apply_setting(id, value.into())?;
+        self.settings_changed(change.into_iter().collect());
+        Ok(())
+    }
+
+    /// Validate and store a setting and run its effect. Returns the change,
+    /// or `None` if the setting already had the value.
+    pub(crate) fn apply_setting(
+        &mut self,
+        id: &str,
+        value: SettingValue,
+    ) -> Result<Option<SettingChange>, SettingsError> {
+        let old = self.settings.get(id).cloned();
+        let effect = self.settings.set(id, value.clone())?.effect;
+        if let Some(effect) = effect {
+            effect(self, &value);
+        }
+        self.dirty.touch(&[ChangeCategory::Settings]);
+        if let Some(persistence) = self.persistence.as_mut() {
+            persistence.note_edit();
+        }
+        Ok(old.filter(|old| *old != value).map(|old| SettingChange {
+            id: id.to_string(),
+            old,
+            new: value,
+        }))
+    }
+
+    /// Write the changed configuration and tell subscribers.
+    pub(crate) fn settings_changed(&mut self, changes: Vec<SettingChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        if self.persistence.is_some() {
+            let _ = self.save_settings();
+        }
+        let event = StoreEvent::SettingsChanged {
+            changes,
+            revision: self.dirty.revision(),
+        };
+        self.emit("Change settings", vec![event], false);
+    }
+
+    /// Load persisted settings for a storage location.
+    ///
+    /// Valid entries are applied with their effects. Unknown ids and invalid
+    /// values are kept verbatim so they survive a save, and are returned.
+    pub fn load_settings(
+        &mut self,
+        storage: SettingStorage,
+        values: IndexMap<String, serde_json::Value>,
+    ) -> Vec<FlaggedSetting> {
+        for (id, raw) in values {
+            let applied = serde_json::from_value::<SettingValue>(raw.clone())
+                .ok()
+                .is_some_and(|value| self.set_setting(&id, value).is_ok());
+            if !applied {
+                self.settings.unknown.insert(id, (storage, raw));
+            }
+        }
+        self.settings
+            .flagged()
+            .into_iter()
+            .filter(|f| f.storage == storage)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_setting_runs_effect() {
+        let mut store = StudioStore::new();
+        assert!(store.ui.autosave.running);
+
+        store.set_setting("editor.autosave", false).unwrap();
+        store.set_setting("appearance.theme", "dark").unwrap();
+        store
+            .set_setting("editor.autosave_interval", 120.0)
+            .unwrap();
+
+        assert!(!store.ui.autosave.running);
+        assert_eq!(store.ui.autosave.interval_secs, 120);
+        assert_eq!(store.ui.theme, ThemeMode::Dark);
+        assert_eq!(
+            store.settings.get("editor.autosave"),
+            Some(&SettingValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn test_set_setting_validation() {
+        let mut store = StudioStore::new();
+
+        assert_eq!(
+            store.set_setting("editor.autosave", "yes"),
+            Err(SettingsError::TypeMismatch {
+                id: "editor.autosave".to_string(),
+                expected: "boolean",
+            })
+        );
+        assert!(matches!(
+            store.set_setting("editor.autosave_interval", 1.0),
+            Err(SettingsError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            store.set_setting("appearance.theme", "sepia"),
+            Err(SettingsError::InvalidOption { .. })
+        ));
+        assert!(matches!(
+            store.set_setting("nope", true),
+            Err(SettingsError::UnknownSetting(_))
+        ));
+
+        // Failed sets leave state untouched.
+        assert!(store.ui.autosave.running);
+        assert_eq!(store.ui.theme, ThemeMode::System);
+    }
+
+    #[test]
+    fn test_unknown_persisted_settings_are_preserved() {
+        let mut store = StudioStore::new();
+        let mut values = IndexMap::new();
+        values.insert("appearance.theme".to_string(), serde_json::json!("light"));
+        values.insert("editor.spellcheck".to_string(), serde_json::json!(true));
+        values.insert("editor.autosave".to_string(), serde_json::json!("often"));
+
+        let flagged = store.load_settings(SettingStorage::Config, values);
+
+        assert_eq!(store.ui.theme, ThemeMode::Light);
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[0].reason, "unknown setting");
+        assert_eq!(flagged[1].reason, "invalid value");
+
+        let persisted = store.settings.persisted(SettingStorage::Config);
+        assert_eq!(persisted["editor.spellcheck"], serde_json::json!(true));
+        assert_eq!(persisted["editor.autosave"], serde_json::json!("often"));
+        assert!(store.settings.persisted(SettingStorage::Ui).is_empty());
+    }
+
+    #[test]
+    fn test_view_model_sections() {
+        let mut store = StudioStore::new();
+        store.set_setting("canvas.show_minimap", true).unwrap();
+
+        let vm = store.settings.view_model();
+        let names: Vec<_> = vm.sections.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["appearance", "editor", "canvas", "export"]);
+
+        let canvas = &vm.sections[2];
+        let minimap = canvas
+            .items
+            .iter()
+            .find(|i| i.id == "canvas.show_minimap")
+            .unwrap();
+        assert!(!minimap.is_default);
+        assert_eq!(minimap.value, SettingValue::Bool(true));
+    }
+}
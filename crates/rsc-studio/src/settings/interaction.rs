@@ -0,0 +1,237 @@
+//! Canvas interaction settings.
+//!
+//! `canvas.interaction_preset` picks a bundled gesture table. With the
+//! custom preset, each gesture and the drag and zoom tuning come from
+//! their own `canvas.custom.*` settings. Any change rebinds the store's
+//! gesture controller right away. The keyboard nudge step applies with
+//! every preset.
+
+use rsc_dnd::{ActivationConstraint, PointerSensor};
+use rsc_flow::{CanvasAction, DEFAULT_NUDGE_STEP, Gesture, InteractionConfig, InteractionPreset};
+
+use super::{SettingDef, SettingKind, SettingStorage, SettingValue, Settings, SettingsRegistry};
+use crate::store::StudioStore;
+
+/// Preset setting id.
+pub const PRESET_SETTING: &str = "canvas.interaction_preset";
+
+/// Custom drag threshold setting id.
+pub const DRAG_THRESHOLD_SETTING: &str = "canvas.custom.drag_threshold";
+
+/// Custom zoom step setting id.
+pub const ZOOM_STEP_SETTING: &str = "canvas.custom.zoom_step";
+
+/// Keyboard nudge step setting id.
+pub const NUDGE_STEP_SETTING: &str = "canvas.nudge_step";
+
+/// Custom binding setting id per gesture.
+pub const GESTURE_SETTINGS: &[(Gesture, &str)] = &[
+    (Gesture::Scroll, "canvas.custom.scroll"),
+    (Gesture::CtrlScroll, "canvas.custom.ctrl_scroll"),
+    (Gesture::ShiftScroll, "canvas.custom.shift_scroll"),
+    (Gesture::BackgroundDrag, "canvas.custom.background_drag"),
+    (Gesture::SpaceDrag, "canvas.custom.space_drag"),
+    (Gesture::MiddleDrag, "canvas.custom.middle_drag"),
+];
+
+const PRESET_KEYS: &[&str] = &["default", "figma-like", "trackpad", "custom"];
+
+const ACTION_KEYS: &[&str] = &["pan", "pan-horizontal", "zoom", "select", "none"];
+
+fn apply(store: &mut StudioStore, _: &SettingValue) {
+    store.apply_interaction();
+}
+
+/// Register the interaction settings.
+pub(super) fn register(registry: &mut SettingsRegistry) {
+    let defaults = InteractionConfig::default();
+    registry.register(SettingDef {
+        id: PRESET_SETTING,
+        label: "Interaction preset",
+        description: "How scrolling and dragging move the canvas",
+        kind: SettingKind::Enum(PRESET_KEYS),
+        default: InteractionPreset::Default.key().into(),
+        storage: SettingStorage::Config,
+        effect: Some(apply),
+    });
+    for (gesture, id) in GESTURE_SETTINGS {
+        registry.register(SettingDef {
+            id,
+            label: gesture.label(),
+            description: "Action for this gesture with the custom preset",
+            kind: SettingKind::Enum(ACTION_KEYS),
+            default: defaults.action(*gesture).key().into(),
+            storage: SettingStorage::Config,
+            effect: Some(apply),
+        });
+    }
+    registry.register(SettingDef {
+        id: DRAG_THRESHOLD_SETTING,
+        label: "Drag threshold",
+        description: "Pixels the pointer moves before a drag starts, with the custom preset",
+        kind: SettingKind::Number {
+            min: 0.0,
+            max: 50.0,
+        },
+        default: defaults.drag_threshold.into(),
+        storage: SettingStorage::Config,
+        effect: Some(apply),
+    });
+    registry.register(SettingDef {
+        id: ZOOM_STEP_SETTING,
+        label: "Zoom step",
+        description: "Zoom factor per wheel notch, with the custom preset",
+        kind: SettingKind::Number {
+            min: 1.01,
+            max: 2.0,
+        },
+        default: defaults.zoom_step.into(),
+        storage: SettingStorage::Config,
+        effect: Some(apply),
+    });
+    registry.register(SettingDef {
+        id: NUDGE_STEP_SETTING,
+        label: "Nudge step",
+        description: "Pixels Shift+arrow moves a node when the grid is off",
+        kind: SettingKind::Number {
+            min: 1.0,
+            max: 100.0,
+        },
+        default: DEFAULT_NUDGE_STEP.into(),
+        storage: SettingStorage::Config,
+        effect: Some(apply),
+    });
+}
+
+/// Interaction config described by the current settings.
+pub fn interaction_config(settings: &Settings) -> InteractionConfig {
+    let preset = settings
+        .get(PRESET_SETTING)
+        .and_then(|v| v.as_str())
+        .and_then(InteractionPreset::from_key)
+        .unwrap_or_default();
+    let mut config = InteractionConfig::preset(preset);
+    if preset != InteractionPreset::Custom {
+        return config;
+    }
+    for (gesture, id) in GESTURE_SETTINGS {
+        if let Some(action) = settings
+            .get(id)
+            .and_then(|v| v.as_str())
+            .and_then(CanvasAction::from_key)
+        {
+            config.bindings.insert(*gesture, action);
+        }
+    }
+    if let Some(threshold) = settings
+        .get(DRAG_THRESHOLD_SETTING)
+        .and_then(|v| v.as_number())
+    {
+        config.drag_threshold = threshold;
+    }
+    if let Some(step) = settings.get(ZOOM_STEP_SETTING).and_then(|v| v.as_number()) {
+        config.zoom_step = step;
+    }
+    config
+}
+
+/// Pointer sensor honoring the drag threshold and delay of `config`.
+pub fn pointer_sensor(config: &InteractionConfig) -> PointerSensor {
+    let activation_constraint = match config.drag_delay_ms {
+        0 => ActivationConstraint::Distance(config.drag_threshold),
+        delay => ActivationConstraint::DistanceAndDelay {
+            distance: config.drag_threshold,
+            delay,
+        },
+    };
+    PointerSensor {
+        activation_constraint,
+    }
+}
+
+impl StudioStore {
+    /// Rebind the canvas gesture controller and keyboard nudge step from
+    /// the current settings.
+    pub fn apply_interaction(&mut self) {
+        let config = interaction_config(&self.settings);
+        self.ui.canvas.gestures.rebind(config);
+        if let Some(step) = self
+            .settings
+            .get(NUDGE_STEP_SETTING)
+            .and_then(|v| v.as_number())
+        {
+            self.ui.canvas.keyboard.config.nudge_step = step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_keys_match_flow() {
+        let presets: Vec<_> = InteractionPreset::all().iter().map(|p| p.key()).collect();
+        assert_eq!(presets, PRESET_KEYS);
+        let actions: Vec<_> = CanvasAction::all().iter().map(|a| a.key()).collect();
+        assert_eq!(actions, ACTION_KEYS);
+        assert_eq!(GESTURE_SETTINGS.len(), Gesture::all().len());
+    }
+
+    #[test]
+    fn test_switching_presets_mid_session() {
+        let mut store = StudioStore::new();
+        let gestures = &store.ui.canvas.gestures;
+        assert_eq!(gestures.action(Gesture::Scroll), CanvasAction::Zoom);
+        let generation = gestures.generation();
+
+        store.set_setting(PRESET_SETTING, "figma-like").unwrap();
+        let gestures = &store.ui.canvas.gestures;
+        assert_eq!(gestures.action(Gesture::Scroll), CanvasAction::Pan);
+        assert_eq!(
+            gestures.action(Gesture::BackgroundDrag),
+            CanvasAction::Select
+        );
+        assert!(gestures.generation() > generation);
+
+        store.set_setting(PRESET_SETTING, "trackpad").unwrap();
+        let config = store.ui.canvas.gestures.config();
+        assert_eq!(config.zoom_step, 1.05);
+        assert!(matches!(
+            pointer_sensor(config).activation_constraint,
+            ActivationConstraint::DistanceAndDelay { distance: 8.0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_custom_bindings() {
+        let mut store = StudioStore::new();
+        // Custom values are kept but unused until the custom preset is picked.
+        store.set_setting("canvas.custom.scroll", "pan").unwrap();
+        store.set_setting(ZOOM_STEP_SETTING, 1.5).unwrap();
+        assert_eq!(
+            store.ui.canvas.gestures.action(Gesture::Scroll),
+            CanvasAction::Zoom
+        );
+
+        store.set_setting(PRESET_SETTING, "custom").unwrap();
+        let config = store.ui.canvas.gestures.config();
+        assert_eq!(config.preset, InteractionPreset::Custom);
+        assert_eq!(config.action(Gesture::Scroll), CanvasAction::Pan);
+        assert_eq!(config.action(Gesture::CtrlScroll), CanvasAction::Zoom);
+        assert_eq!(config.zoom_step, 1.5);
+
+        // Rebinding one gesture applies live.
+        store
+            .set_setting("canvas.custom.middle_drag", "none")
+            .unwrap();
+        assert_eq!(
+            store.ui.canvas.gestures.action(Gesture::MiddleDrag),
+            CanvasAction::None
+        );
+        assert!(matches!(
+            store.set_setting("canvas.custom.scroll", "spin"),
+            Err(crate::settings::SettingsError::InvalidOption { .. })
+        ));
+    }
+}
@@ -0,0 +1,434 @@
+//! Typed view of the studio settings.
+//!
+//! [`StudioSettings`] reads the settings the Settings page edits into
+//! plain fields. The registry stays the source of truth: defaults and
+//! bounds come from the setting declarations, and a [`SettingsPatch`] is
+//! written back through them with [`StudioStore::update_settings`].
+
+use serde::{Deserialize, Serialize};
+
+use super::interaction::ZOOM_STEP_SETTING;
+use super::{SettingChange, SettingValue, Settings, SettingsError, SettingsRegistry};
+use crate::store::{StudioStore, ThemeMode};
+
+/// Theme setting id.
+pub const THEME_SETTING: &str = "appearance.theme";
+
+/// Autosave setting id.
+pub const AUTOSAVE_SETTING: &str = "editor.autosave";
+
+/// Autosave interval setting id.
+pub const AUTOSAVE_INTERVAL_SETTING: &str = "editor.autosave_interval";
+
+/// Keyboard scheme setting id.
+pub const KEYBOARD_SCHEME_SETTING: &str = "editor.keyboard_scheme";
+
+/// Recent project list length setting id.
+pub const RECENT_PROJECTS_SETTING: &str = "editor.recent_projects";
+
+/// Canvas snap grid setting id.
+pub const SNAP_GRID_SETTING: &str = "canvas.snap_grid";
+
+/// Key bindings used by the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardScheme {
+    #[default]
+    Default,
+    Vim,
+    Emacs,
+}
+
+impl KeyboardScheme {
+    pub fn all() -> &'static [KeyboardScheme] {
+        &[
+            KeyboardScheme::Default,
+            KeyboardScheme::Vim,
+            KeyboardScheme::Emacs,
+        ]
+    }
+
+    /// Setting value, e.g. `vim`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            KeyboardScheme::Default => "default",
+            KeyboardScheme::Vim => "vim",
+            KeyboardScheme::Emacs => "emacs",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|scheme| scheme.key() == key)
+    }
+}
+
+/// The studio settings as typed fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StudioSettings {
+    pub autosave: bool,
+    /// Seconds between automatic saves.
+    pub autosave_interval_secs: u64,
+    pub theme: ThemeMode,
+    /// Grid dropped nodes snap to, in canvas units; 0 turns snapping off.
+    pub snap_grid: f64,
+    /// Zoom factor per wheel notch.
+    pub zoom_step: f64,
+    pub keyboard_scheme: KeyboardScheme,
+    /// Projects kept in the recent list.
+    pub recent_projects: usize,
+}
+
+impl Default for StudioSettings {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default())
+    }
+}
+
+impl StudioSettings {
+    /// Current values of `settings`, defaults included.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let number = |id| {
+            settings
+                .get(id)
+                .and_then(SettingValue::as_number)
+                .unwrap_or_default()
+        };
+        let key = |id| settings.get(id).and_then(SettingValue::as_str);
+        Self {
+            autosave: settings
+                .get(AUTOSAVE_SETTING)
+                .and_then(SettingValue::as_bool)
+                .unwrap_or(true),
+            autosave_interval_secs: number(AUTOSAVE_INTERVAL_SETTING) as u64,
+            theme: key(THEME_SETTING)
+                .and_then(ThemeMode::from_key)
+                .unwrap_or_default(),
+            snap_grid: number(SNAP_GRID_SETTING),
+            zoom_step: number(ZOOM_STEP_SETTING),
+            keyboard_scheme: key(KEYBOARD_SCHEME_SETTING)
+                .and_then(KeyboardScheme::from_key)
+                .unwrap_or_default(),
+            recent_projects: number(RECENT_PROJECTS_SETTING) as usize,
+        }
+    }
+
+    /// Each field as a setting id and value.
+    pub fn values(&self) -> [(&'static str, SettingValue); 7] {
+        [
+            (THEME_SETTING, self.theme.key().into()),
+            (AUTOSAVE_SETTING, self.autosave.into()),
+            (
+                AUTOSAVE_INTERVAL_SETTING,
+                (self.autosave_interval_secs as f64).into(),
+            ),
+            (KEYBOARD_SCHEME_SETTING, self.keyboard_scheme.key().into()),
+            (
+                RECENT_PROJECTS_SETTING,
+                (self.recent_projects as f64).into(),
+            ),
+            (SNAP_GRID_SETTING, self.snap_grid.into()),
+            (ZOOM_STEP_SETTING, self.zoom_step.into()),
+        ]
+    }
+
+    /// Fields outside the bounds their declarations in `registry` allow.
+    pub fn validate(&self, registry: &SettingsRegistry) -> Vec<SettingsError> {
+        self.values()
+            .into_iter()
+            .filter_map(|(id, value)| registry.get(id)?.validate(&value).err())
+            .collect()
+    }
+
+    /// Fields that differ in `other`, in field order.
+    pub fn diff(&self, other: &StudioSettings) -> Vec<SettingChange> {
+        self.values()
+            .into_iter()
+            .zip(other.values())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((id, old), (_, new))| SettingChange {
+                id: id.to_string(),
+                old,
+                new,
+            })
+            .collect()
+    }
+}
+
+/// A partial update of [`StudioSettings`], e.g. from one Settings page
+/// control. Unset fields keep their value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SettingsPatch {
+    pub autosave: Option<bool>,
+    pub autosave_interval_secs: Option<u64>,
+    pub theme: Option<ThemeMode>,
+    pub snap_grid: Option<f64>,
+    pub zoom_step: Option<f64>,
+    pub keyboard_scheme: Option<KeyboardScheme>,
+    pub recent_projects: Option<usize>,
+}
+
+impl SettingsPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_autosave(mut self, autosave: bool) -> Self {
+        self.autosave = Some(autosave);
+        self
+    }
+
+    pub fn with_autosave_interval_secs(mut self, secs: u64) -> Self {
+        self.autosave_interval_secs = Some(secs);
+        self
+    }
+
+    pub fn with_theme(mut self, theme: ThemeMode) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    pub fn with_snap_grid(mut self, grid: f64) -> Self {
+        self.snap_grid = Some(grid);
+        self
+    }
+
+    pub fn with_zoom_step(mut self, step: f64) -> Self {
+        self.zoom_step = Some(step);
+        self
+    }
+
+    pub fn with_keyboard_scheme(mut self, scheme: KeyboardScheme) -> Self {
+        self.keyboard_scheme = Some(scheme);
+        self
+    }
+
+    pub fn with_recent_projects(mut self, count: usize) -> Self {
+        self.recent_projects = Some(count);
+        self
+    }
+
+    /// `settings` with the set fields replaced.
+    pub fn apply(&self, settings: &StudioSettings) -> StudioSettings {
+        StudioSettings {
+            autosave: self.autosave.unwrap_or(settings.autosave),
+            autosave_interval_secs: self
+                .autosave_interval_secs
+                .unwrap_or(settings.autosave_interval_secs),
+            theme: self.theme.unwrap_or(settings.theme),
+            snap_grid: self.snap_grid.unwrap_or(settings.snap_grid),
+            zoom_step: self.zoom_step.unwrap_or(settings.zoom_step),
+            keyboard_scheme: self.keyboard_scheme.unwrap_or(settings.keyboard_scheme),
+            recent_projects: self.recent_projects.unwrap_or(settings.recent_projects),
+        }
+    }
+}
+
+impl StudioStore {
+    /// The current settings as typed fields.
+    pub fn studio_settings(&self) -> StudioSettings {
+        StudioSettings::from_settings(&self.settings)
+    }
+
+    /// Apply a patch as one change, running each changed setting's effect
+    /// and emitting a single settings event. Nothing changes if any field
+    /// is invalid; the first error is returned.
+    pub fn update_settings(
+        &mut self,
+        patch: &SettingsPatch,
+    ) -> Result<Vec<SettingChange>, SettingsError> {
+        let current = self.studio_settings();
+        let updated = patch.apply(&current);
+        if let Some(error) = updated.validate(&self.settings.registry).into_iter().next() {
+            return Err(error);
+        }
+        let changes = current.diff(&updated);
+        for change in &changes {
+            self.apply_setting(&change.id, change.new.clone())?;
+        }
+        self.settings_changed(changes.clone());
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::store::events::{EventFilter, EventKind, StoreEvent};
+    use crate::store::persistence::{
+        MemoryStorage, Persistence, PersistenceConfig, PersistenceTarget,
+    };
+
+    #[test]
+    fn test_defaults_and_serde_round_trip() {
+        let settings = StudioSettings::default();
+        assert!(settings.autosave);
+        assert_eq!(settings.autosave_interval_secs, 30);
+        assert_eq!(settings.theme, ThemeMode::System);
+        assert_eq!(settings.snap_grid, 8.0);
+        assert_eq!(settings.recent_projects, 10);
+        assert!(settings.validate(&SettingsRegistry::builtin()).is_empty());
+
+        let custom = SettingsPatch::new()
+            .with_theme(ThemeMode::Dark)
+            .with_keyboard_scheme(KeyboardScheme::Vim)
+            .apply(&settings);
+        let json = serde_json::to_string(&custom).unwrap();
+        assert!(json.contains(r#""theme":"dark""#));
+        assert!(json.contains(r#""keyboard_scheme":"vim""#));
+        assert_eq!(
+            serde_json::from_str::<StudioSettings>(&json).unwrap(),
+            custom
+        );
+        let partial: StudioSettings = serde_json::from_str(r#"{"snap_grid":16}"#).unwrap();
+        assert_eq!(partial.snap_grid, 16.0);
+        assert_eq!(partial.zoom_step, settings.zoom_step);
+        assert_eq!(
+            settings.diff(&custom),
+            [
+                SettingChange {
+                    id: THEME_SETTING.to_string(),
+                    old: "system".into(),
+                    new: "dark".into(),
+                },
+                SettingChange {
+                    id: KEYBOARD_SCHEME_SETTING.to_string(),
+                    old: "default".into(),
+                    new: "vim".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation_failures() {
+        let mut store = StudioStore::new();
+        let registry = &store.settings.registry;
+        let invalid = SettingsPatch::new()
+            .with_autosave_interval_secs(2)
+            .with_zoom_step(3.0)
+            .apply(&StudioSettings::default());
+        let errors = invalid.validate(registry);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "Setting 'editor.autosave_interval' must be between 5 and 600, got 2"
+        );
+        assert!(matches!(
+            &errors[1],
+            SettingsError::OutOfRange { id, .. } if id == ZOOM_STEP_SETTING
+        ));
+
+        // A patch with one bad field applies none of its fields.
+        let patch = SettingsPatch::new()
+            .with_theme(ThemeMode::Dark)
+            .with_autosave_interval_secs(1_000);
+        assert!(matches!(
+            store.update_settings(&patch),
+            Err(SettingsError::OutOfRange { .. })
+        ));
+        assert_eq!(store.studio_settings(), StudioSettings::default());
+        assert_eq!(store.ui.theme, ThemeMode::System);
+    }
+
+    #[test]
+    fn test_patch_runs_effects_and_emits_one_event() {
+        let mut store = StudioStore::new();
+        let log: Arc<Mutex<Vec<StoreEvent>>> = Arc::default();
+        let sink = log.clone();
+        store.subscribe(
+            EventFilter::kinds([EventKind::Settings]),
+            move |event, _| sink.lock().unwrap().push(event.clone()),
+        );
+
+        let patch = SettingsPatch::new()
+            .with_autosave(false)
+            .with_autosave_interval_secs(120)
+            .with_theme(ThemeMode::Light)
+            .with_snap_grid(0.0);
+        let changes = store.update_settings(&patch).unwrap();
+        assert_eq!(changes.len(), 4);
+        assert!(!store.ui.autosave.running);
+        assert_eq!(store.ui.autosave.interval_secs, 120);
+        assert_eq!(store.ui.theme, ThemeMode::Light);
+        assert_eq!(store.ui.canvas.snap_grid, 0.0);
+        assert_eq!(
+            store.studio_settings(),
+            patch.apply(&StudioSettings::default())
+        );
+
+        let events = std::mem::take(&mut *log.lock().unwrap());
+        assert!(matches!(
+            &events[..],
+            [StoreEvent::SettingsChanged { changes: sent, .. }] if *sent == changes
+        ));
+
+        // Re-applying is a no-op without an event; single sets emit too.
+        assert!(store.update_settings(&patch).unwrap().is_empty());
+        store.set_setting(KEYBOARD_SCHEME_SETTING, "emacs").unwrap();
+        let events = std::mem::take(&mut *log.lock().unwrap());
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            store.studio_settings().keyboard_scheme,
+            KeyboardScheme::Emacs
+        );
+    }
+
+    #[test]
+    fn test_snap_grid_reaches_the_canvas() {
+        let mut store = StudioStore::new();
+        assert_eq!(
+            store.navigation_designer().canvas.config.snap.grid,
+            Some((8.0, 8.0))
+        );
+        store
+            .update_settings(&SettingsPatch::new().with_snap_grid(16.0))
+            .unwrap();
+        assert_eq!(
+            store.navigation_designer().canvas.config.snap.grid,
+            Some((16.0, 16.0))
+        );
+
+        store
+            .update_settings(&SettingsPatch::new().with_snap_grid(0.0))
+            .unwrap();
+        assert_eq!(store.navigation_designer().canvas.config.snap.grid, None);
+    }
+
+    #[test]
+    fn test_persistence_round_trip() {
+        let storage = Arc::new(MemoryStorage::new());
+        let persisted = || {
+            let config = PersistenceConfig::default()
+                .with_target(PersistenceTarget::Backend(storage.clone()));
+            let mut store = StudioStore::new();
+            store.enable_persistence(Persistence::new(config));
+            store
+        };
+
+        let mut store = persisted();
+        let patch = SettingsPatch::new()
+            .with_theme(ThemeMode::Dark)
+            .with_zoom_step(1.25)
+            .with_recent_projects(5);
+        store.update_settings(&patch).unwrap();
+        let expected = store.studio_settings();
+        drop(store);
+
+        let mut restarted = persisted();
+        assert_eq!(restarted.studio_settings(), StudioSettings::default());
+        assert!(restarted.restore_settings().unwrap().is_empty());
+        assert_eq!(restarted.studio_settings(), expected);
+        assert_eq!(restarted.ui.theme, ThemeMode::Dark);
+
+        // Without persistence there is nothing to restore.
+        let mut store = StudioStore::new();
+        assert!(store.restore_settings().unwrap().is_empty());
+        assert_eq!(store.studio_settings(), StudioSettings::default());
+    }
+}
@@ -0,0 +1,325 @@
+//! External status badges for contexts.
+//!
+//! Statuses such as "copy approved" or "design final" come from JSON feeds
+//! produced by CI or review tools. Like analytics weights they are kept in
+//! their own store slice, keyed by context id, and never written into the
+//! design itself.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::config::StatusConfig;
+use crate::entity::{EntityId, Workflow, slugify};
+
+/// A status feed document.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusFeed {
+    /// When the feed was generated (Unix seconds).
+    pub timestamp: u64,
+    /// Feed name, e.g. "ci" or "design-review".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub entries: Vec<StatusEntry>,
+}
+
+impl StatusFeed {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// One feed entry. Matched by `id` first, then by `slug`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusEntry {
+    /// Explicit context id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<EntityId>,
+    /// Slug of the context name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Per-entry update time; defaults to the feed timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+}
+
+/// Status applied to a context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Unix seconds.
+    pub updated_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// A feed entry that matched no context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnmatchedStatus {
+    /// 0-based index in the feed.
+    pub index: usize,
+    pub entry: StatusEntry,
+    /// Why the entry was not applied.
+    pub reason: String,
+}
+
+/// Statuses keyed by context id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusMap {
+    #[serde(default)]
+    pub statuses: IndexMap<EntityId, NodeStatus>,
+    /// Timestamp of the most recent feed applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed_timestamp: Option<u64>,
+    /// Entries from the last feed that could not be matched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmatched: Vec<UnmatchedStatus>,
+}
+
+/// Badge shown on a context node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusBadge {
+    pub status: String,
+    pub icon: String,
+    pub color: String,
+    /// Older than the configured staleness limit; rendered dimmed.
+    pub stale: bool,
+    pub updated_at: u64,
+    pub note: Option<String>,
+}
+
+impl StatusMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, context_id: &str) -> Option<&NodeStatus> {
+        self.statuses.get(context_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
+
+    /// Apply a feed, matching entries against the contexts of `workflows`.
+    ///
+    /// Entries replace earlier statuses for the same context. Returns the
+    /// number of entries applied; the rest are recorded in `unmatched`.
+    pub fn apply_feed<'a>(
+        &mut self,
+        feed: &StatusFeed,
+        workflows: impl IntoIterator<Item = &'a Workflow>,
+    ) -> usize {
+        let mut ids: Vec<&EntityId> = Vec::new();
+        let mut slugs: IndexMap<String, Vec<&EntityId>> = IndexMap::new();
+        for workflow in workflows {
+            for context in workflow.contexts.values() {
+                ids.push(&context.id);
+                slugs
+                    .entry(slugify(&context.name))
+                    .or_default()
+                    .push(&context.id);
+            }
+        }
+
+        self.unmatched.clear();
+        let mut applied = 0;
+        for (index, entry) in feed.entries.iter().enumerate() {
+            let by_id = entry
+                .id
+                .as_ref()
+                .and_then(|id| ids.iter().find(|c| **c == id).copied());
+            let by_slug = entry.slug.as_ref().map(|s| slugs.get(&slugify(s)));
+
+            let target = match (by_id, by_slug) {
+                (Some(id), _) => Ok(id),
+                (None, Some(Some(matches))) if matches.len() == 1 => Ok(matches[0]),
+                (None, Some(Some(_))) => Err("ambiguous slug"),
+                (None, _) if entry.id.is_some() || entry.slug.is_some() => {
+                    Err("no matching context")
+                }
+                (None, _) => Err("entry has no id or slug"),
+            };
+
+            match target {
+                Ok(id) => {
+                    self.statuses.insert(
+                        id.clone(),
+                        NodeStatus {
+                            status: entry.status.clone(),
+                            note: entry.note.clone(),
+                            updated_at: entry.updated_at.unwrap_or(feed.timestamp),
+                            source: feed.source.clone(),
+                        },
+                    );
+                    applied += 1;
+                }
+                Err(reason) => self.unmatched.push(UnmatchedStatus {
+                    index,
+                    entry: entry.clone(),
+                    reason: reason.to_string(),
+                }),
+            }
+        }
+
+        self.feed_timestamp = Some(
+            self.feed_timestamp
+                .map_or(feed.timestamp, |t| t.max(feed.timestamp)),
+        );
+        applied
+    }
+
+    /// Badge for a context, styled by `config`. `now` is Unix seconds.
+    pub fn badge(&self, context_id: &str, config: &StatusConfig, now: u64) -> Option<StatusBadge> {
+        let status = self.statuses.get(context_id)?;
+        let style = config.style_for(&status.status);
+        Some(StatusBadge {
+            status: status.status.clone(),
+            icon: style.icon,
+            color: style.color,
+            stale: config.is_stale(status.updated_at, now),
+            updated_at: status.updated_at,
+            note: status.note.clone(),
+        })
+    }
+
+    /// Count of contexts per status within a workflow.
+    pub fn counts(&self, workflow: &Workflow) -> IndexMap<String, usize> {
+        let mut counts = IndexMap::new();
+        for id in workflow.contexts.keys() {
+            if let Some(status) = self.statuses.get(id) {
+                *counts.entry(status.status.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StatusStyle;
+    use crate::entity::Context;
+
+    fn workflows() -> Vec<Workflow> {
+        let mut checkout = Workflow::new("Checkout").with_id("wf-1");
+        checkout.add_context(Context::new("Cart").with_id("ctx-cart"));
+        checkout.add_context(Context::new("Payment Details").with_id("ctx-pay"));
+        checkout.add_context(Context::new("Review").with_id("ctx-review"));
+        let mut account = Workflow::new("Account").with_id("wf-2");
+        account.add_context(Context::new("Review").with_id("ctx-acct-review"));
+        vec![checkout, account]
+    }
+
+    fn entry(id: Option<&str>, slug: Option<&str>, status: &str) -> StatusEntry {
+        StatusEntry {
+            id: id.map(str::to_string),
+            slug: slug.map(str::to_string),
+            status: status.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matching_precedence() {
+        let workflows = workflows();
+        let feed = StatusFeed {
+            timestamp: 1_000,
+            source: Some("review".to_string()),
+            entries: vec![
+                // Id wins over a slug that points elsewhere.
+                entry(Some("ctx-cart"), Some("payment-details"), "design final"),
+                entry(None, Some("Payment Details"), "copy approved"),
+                // Unknown id falls back to the slug.
+                entry(Some("gone"), Some("cart"), "implemented"),
+                entry(None, Some("review"), "implemented"),
+                entry(Some("gone"), None, "implemented"),
+                entry(None, None, "implemented"),
+            ],
+        };
+
+        let mut map = StatusMap::new();
+        let applied = map.apply_feed(&feed, &workflows);
+
+        assert_eq!(applied, 3);
+        assert_eq!(map.get("ctx-cart").unwrap().status, "implemented");
+        assert_eq!(map.get("ctx-pay").unwrap().status, "copy approved");
+        assert_eq!(
+            map.get("ctx-pay").unwrap().source.as_deref(),
+            Some("review")
+        );
+        let reasons: Vec<&str> = map.unmatched.iter().map(|u| u.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "ambiguous slug",
+                "no matching context",
+                "entry has no id or slug"
+            ]
+        );
+        assert_eq!(map.unmatched[0].index, 3);
+    }
+
+    #[test]
+    fn test_staleness_and_badges() {
+        let workflows = workflows();
+        let feed = StatusFeed::from_json(
+            r#"{"timestamp": 10000, "entries": [
+                {"id": "ctx-cart", "status": "implemented"},
+                {"id": "ctx-pay", "status": "copy approved", "updated_at": 2000}
+            ]}"#,
+        )
+        .unwrap();
+        let mut map = StatusMap::new();
+        map.apply_feed(&feed, &workflows);
+
+        let mut config = StatusConfig {
+            stale_after_secs: 5_000,
+            ..Default::default()
+        };
+        config.styles.insert(
+            "implemented".to_string(),
+            StatusStyle::new("check", "#16a34a"),
+        );
+
+        let fresh = map.badge("ctx-cart", &config, 12_000).unwrap();
+        assert!(!fresh.stale);
+        assert_eq!(
+            (fresh.icon.as_str(), fresh.color.as_str()),
+            ("check", "#16a34a")
+        );
+
+        let stale = map.badge("ctx-pay", &config, 12_000).unwrap();
+        assert!(stale.stale);
+        assert_eq!(stale.icon, StatusStyle::fallback().icon);
+        assert_eq!(map.feed_timestamp, Some(10_000));
+        assert!(map.badge("ctx-review", &config, 12_000).is_none());
+    }
+
+    #[test]
+    fn test_status_counts() {
+        let workflows = workflows();
+        let feed = StatusFeed {
+            timestamp: 1,
+            source: None,
+            entries: vec![
+                entry(Some("ctx-cart"), None, "implemented"),
+                entry(Some("ctx-pay"), None, "implemented"),
+                entry(Some("ctx-review"), None, "design final"),
+                entry(Some("ctx-acct-review"), None, "implemented"),
+            ],
+        };
+        let mut map = StatusMap::new();
+        map.apply_feed(&feed, &workflows);
+
+        let counts = map.counts(&workflows[0]);
+        assert_eq!(counts["implemented"], 2);
+        assert_eq!(counts["design final"], 1);
+        assert_eq!(map.counts(&workflows[1])["implemented"], 1);
+    }
+}
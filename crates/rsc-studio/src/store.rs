@@ -1,8 +1,45 @@
 //! Studio state management (Zustand-like store).
 
-use indexmap::IndexMap;
+pub mod dirty;
+pub mod events;
+pub mod limits;
+pub mod middleware;
+pub mod persistence;
+
+use std::sync::Arc;
 
-use crate::entity::{Context, EntityId, Preset, Workflow};
+use indexmap::IndexMap;
+use rsc_flow::{CanvasKeyboardController, GestureController, LayerRegistry};
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::{DEFAULT_WEIGHT_BUCKETS, WeightMap, WeightOverlay};
+use crate::asset::AssetRegistry;
+use crate::designer::NavigationDesigner;
+use crate::designer::css::{
+    CategoryPresentations, ColorBlindness, ComponentStyle, ComponentStyles, CssCache, DesignTokens,
+    NumericOp, OutputOrdering, RenameReport, ThemeManager, TokenCategory, TokenExperiments,
+    TransformReport, ValidationSeverity, VariantReport, VariantSpec,
+};
+use crate::designer::edge_tooltip::{DEFAULT_TOOLTIP_DELAY_MS, EdgeHover};
+use crate::designer::flow_history::{FlowCommand, FlowReplay, FlowTransaction};
+use crate::designer::flow_style::FlowStyleSheet;
+use crate::designer::navigation::NAVIGATION_SNAP_GRID;
+use crate::designer::presentation::Presentation;
+use crate::entity::{Context, EntityId, Preset, Workflow, WorkflowDiagnostic};
+use crate::error::StudioError;
+use crate::export::tokens::{ExportError, ExportFormat};
+use crate::history::{HistoryScope, ScopedHistory};
+use crate::perf::trace_span;
+use crate::presence::Presence;
+use crate::settings::Settings;
+use crate::status::{StatusFeed, StatusMap};
+use crate::timestamp::Clock;
+
+use self::dirty::DirtyTracker;
+use self::events::EventBus;
+use self::limits::StoreLimits;
+use self::middleware::{StoreAction, StoreMiddleware};
+use self::persistence::Persistence;
 
 /// Studio store state.
 #[derive(Debug, Clone, Default)]
@@ -21,6 +58,62 @@ pub struct StudioStore {
     pub ui: UiState,
     /// Validation errors.
     pub errors: Vec<ValidationError>,
+    /// Rejected actions, oldest first, for the problems panel and toasts.
+    pub problems: Vec<StudioError>,
+    /// Navigation graph problems per workflow, from the last
+    /// [`validate_workflows`](StudioStore::validate_workflows).
+    pub workflow_diagnostics: IndexMap<EntityId, Vec<WorkflowDiagnostic>>,
+    /// Design tokens of the active theme.
+    pub tokens: DesignTokens,
+    /// All themes; the active one is synced from `tokens` on switch.
+    pub themes: ThemeManager,
+    /// Component styles.
+    pub component_styles: ComponentStyles,
+    /// Undo/redo history, scoped per designer.
+    pub history: ScopedHistory<StoreChange>,
+    /// Imported edge traversal weights, per workflow.
+    pub edge_weights: IndexMap<EntityId, WeightMap>,
+    /// Studio settings.
+    pub settings: Settings,
+    /// Token category icon and color overrides.
+    pub category_presentation: CategoryPresentations,
+    /// Edge layers, in panel order.
+    pub layers: LayerRegistry,
+    /// Node and edge styles by kind.
+    pub flow_styles: FlowStyleSheet,
+    /// Remote participants (session only, never exported).
+    pub presence: Presence,
+    /// External context statuses from status feeds.
+    pub statuses: StatusMap,
+    /// Time source for entity timestamps.
+    pub clock: Clock,
+    /// Recorded as `modified_by` on touched entities.
+    pub modified_by: Option<String>,
+    /// Entity count and memory limits checked on every commit.
+    pub limits: StoreLimits,
+    /// Order [`normalize_order`](Self::normalize_order) rewrites tokens and
+    /// component styles to.
+    pub output_ordering: OutputOrdering,
+    /// Images dropped onto the canvas, deduplicated by content.
+    pub assets: AssetRegistry,
+    /// Step-through presentation in progress (session only, never exported).
+    pub presentation: Option<Presentation>,
+    /// Canvas edits undone or redone since the designer last ran
+    /// [`sync_flow`](Self::sync_flow).
+    pub flow_replay: Vec<FlowReplay>,
+    /// Flow edits being grouped into one history entry.
+    pub flow_transaction: Option<FlowTransaction>,
+    /// Scratch token overrides shown in the preview (session only, never
+    /// exported).
+    pub experiments: TokenExperiments,
+    /// Hooks run around every commit and panel action, in order.
+    pub middleware: Vec<Arc<dyn StoreMiddleware>>,
+    /// Auto-save snapshots, when enabled.
+    pub persistence: Option<Persistence>,
+    /// Revisions behind the unsaved-changes indicator.
+    pub dirty: DirtyTracker,
+    /// Change event subscribers.
+    pub events: EventBus,
 }
 
 impl StudioStore {
@@ -31,7 +124,22 @@ impl StudioStore {
     // === Workflow operations ===
 
     pub fn add_workflow(&mut self, workflow: Workflow) {
-        self.workflows.insert(workflow.id.clone(), workflow);
+        let id = workflow.id.clone();
+        let index = self
+            .workflows
+            .get_index_of(&id)
+            .unwrap_or(self.workflows.len());
+        let before = self.workflows.get(&id).cloned().map(Box::new);
+        self.commit(
+            HistoryScope::NavDesigner,
+            "Add workflow",
+            StoreChange::Workflow {
+                id,
+                index,
+                before,
+                after: Some(Box::new(workflow)),
+            },
+        );
     }
 
     pub fn remove_workflow(&mut self, id: &str) -> Option<Workflow> {
@@ -40,7 +148,58 @@ impl StudioStore {
             self.selected_context = None;
             self.selected_preset = None;
         }
-        self.workflows.shift_remove(id)
+        let (index, _, removed) = self.workflows.shift_remove_full(id)?;
+        self.record(
+            HistoryScope::NavDesigner,
+            "Remove workflow",
+            StoreChange::Workflow {
+                id: id.to_string(),
+                index,
+                before: Some(Box::new(removed.clone())),
+                after: None,
+            },
+        );
+        Some(removed)
+    }
+
+    /// Replace the imported edge weights for a workflow.
+    pub fn set_edge_weights(&mut self, workflow_id: impl Into<EntityId>, weights: WeightMap) {
+        self.edge_weights.insert(workflow_id.into(), weights);
+    }
+
+    /// Imported edge weights for a workflow.
+    pub fn edge_weights_for(&self, workflow_id: &str) -> Option<&WeightMap> {
+        self.edge_weights.get(workflow_id)
+    }
+
+    /// Overlay of the selected workflow's imported weights, bucketed on
+    /// that workflow's own scale. `None` without a selection or weights.
+    pub fn weight_overlay(&self) -> Option<WeightOverlay> {
+        let weights = self.edge_weights_for(self.selected_workflow.as_deref()?)?;
+        (!weights.is_empty()).then(|| WeightOverlay::new(weights, DEFAULT_WEIGHT_BUCKETS))
+    }
+
+    /// Apply an external status feed to the contexts of all workflows.
+    ///
+    /// Returns the number of entries applied; unmatched entries are kept in
+    /// `statuses.unmatched`.
+    pub fn apply_status_feed(&mut self, feed: StatusFeed) -> usize {
+        self.statuses.apply_feed(&feed, self.workflows.values())
+    }
+
+    /// Context count per external status in a workflow.
+    pub fn status_counts(&self, workflow_id: &str) -> IndexMap<String, usize> {
+        self.workflows
+            .get(workflow_id)
+            .map(|w| self.statuses.counts(w))
+            .unwrap_or_default()
+    }
+
+    /// Workflows ordered by last modification, most recent first.
+    pub fn workflows_by_modified(&self) -> Vec<&Workflow> {
+        let mut workflows: Vec<&Workflow> = self.workflows.values().collect();
+        workflows.sort_by_key(|w| std::cmp::Reverse(w.timestamps.modified_at));
+        workflows
     }
 
     pub fn get_workflow(&self, id: &str) -> Option<&Workflow> {
@@ -51,6 +210,433 @@ impl StudioStore {
         self.workflows.get_mut(id)
     }
 
+    // === Token and component style operations ===
+
+    /// Replace the design token set.
+    pub fn set_tokens(&mut self, tokens: DesignTokens) {
+        let _ = self.commit_tokens("Edit tokens", tokens);
+    }
+
+    fn commit_tokens(&mut self, label: &str, tokens: DesignTokens) -> Result<(), StudioError> {
+        let before = Box::new(self.tokens.clone());
+        self.try_commit(
+            HistoryScope::CssDesigner,
+            label,
+            StoreChange::Tokens {
+                before,
+                after: Box::new(tokens),
+            },
+        )
+    }
+
+    /// Import tokens from JSON, replacing the current set.
+    pub fn import_tokens(&mut self, json: &str) -> Result<(), StudioError> {
+        let tokens = self.checked(DesignTokens::from_json(json))?;
+        self.commit_tokens("Edit tokens", tokens)
+    }
+
+    /// Rename a token and rewrite references to it, as one undoable edit.
+    pub fn rename_token(
+        &mut self,
+        old_path: &str,
+        new_name: &str,
+    ) -> Result<RenameReport, StudioError> {
+        let mut tokens = self.tokens.clone();
+        let report = self.checked(tokens.rename_token(old_path, new_name))?;
+        self.commit_tokens("Edit tokens", tokens)?;
+        Ok(report)
+    }
+
+    /// Apply a batch edit to a token category, as one undoable edit.
+    /// Nothing is recorded when no value changed or the edit failed.
+    pub fn transform_tokens(&mut self, category: TokenCategory, op: NumericOp) -> TransformReport {
+        let mut tokens = self.tokens.clone();
+        let report = tokens.transform_category(category, op);
+        if report.errors.is_empty() && !report.changed.is_empty() {
+            let label = format!("{} {}", op.label(), category.label().to_lowercase());
+            let _ = self.commit_tokens(&label, tokens);
+        }
+        report
+    }
+
+    /// Set or replace the style of a component. Rejected if its `extends`
+    /// would make styles inherit from each other in a loop.
+    pub fn set_component_style(
+        &mut self,
+        name: impl Into<String>,
+        style: ComponentStyle,
+    ) -> Result<(), StudioError> {
+        let name = name.into();
+        let check = self.component_styles.check_new_style(&name, &style);
+        self.checked(check)?;
+        let before = self.component_styles.get(&name).cloned().map(Box::new);
+        self.try_commit(
+            HistoryScope::CssDesigner,
+            "Edit component style",
+            StoreChange::ComponentStyle {
+                name,
+                before,
+                after: Some(Box::new(style)),
+            },
+        )
+    }
+
+    /// Generate size and tone variants of a component, as one undoable
+    /// edit. See [`ComponentStyles::apply_variants`] for how earlier
+    /// variants are updated.
+    pub fn generate_component_variants(
+        &mut self,
+        component: &str,
+        spec: &VariantSpec,
+    ) -> VariantReport {
+        let variants = self
+            .component_styles
+            .generate_variants(component, spec, &self.tokens);
+        let mut styles = self.component_styles.clone();
+        let report = styles.apply_variants(variants);
+        let changes: Vec<StoreChange> = report
+            .created
+            .iter()
+            .chain(&report.updated)
+            .map(|name| StoreChange::ComponentStyle {
+                name: name.clone(),
+                before: self.component_styles.get(name).cloned().map(Box::new),
+                after: styles.get(name).cloned().map(Box::new),
+            })
+            .collect();
+        if !changes.is_empty() {
+            self.commit(
+                HistoryScope::CssDesigner,
+                "Generate variants",
+                StoreChange::Batch(changes),
+            );
+        }
+        report
+    }
+
+    /// Rewrite tokens and component styles to the project's output
+    /// ordering, as one undoable edit, so saved files stop churning.
+    /// Returns whether anything moved.
+    pub fn normalize_order(&mut self) -> bool {
+        let mut tokens = self.tokens.clone();
+        let mut styles = self.component_styles.clone();
+        let mut changes = Vec::new();
+        if tokens.normalize_order(&self.output_ordering) {
+            changes.push(StoreChange::Tokens {
+                before: Box::new(self.tokens.clone()),
+                after: Box::new(tokens),
+            });
+        }
+        if styles.normalize_order(&self.output_ordering) {
+            changes.push(StoreChange::ComponentStyleOrder {
+                before: Box::new(self.component_styles.clone()),
+                after: Box::new(styles),
+            });
+        }
+        if changes.is_empty() {
+            return false;
+        }
+        self.commit(
+            HistoryScope::CssDesigner,
+            "Normalize order",
+            StoreChange::Batch(changes),
+        );
+        true
+    }
+
+    /// The tokens exported in the project's output ordering.
+    pub fn export_tokens(&self, format: ExportFormat) -> Result<String, ExportError> {
+        self.tokens.export_ordered(format, &self.output_ordering)
+    }
+
+    /// Component CSS in the project's output ordering, reusing `cache`.
+    pub fn component_css(&self, cache: &mut CssCache) -> String {
+        self.component_styles
+            .generate_css_cached(&self.tokens, &self.output_ordering, cache)
+    }
+
+    // === Project operations ===
+
+    /// Load a project, replacing all workflows, tokens and component styles.
+    ///
+    /// This is a global action: it is recorded in the global scope and
+    /// invalidates the designer histories.
+    pub fn load_project(
+        &mut self,
+        workflows: Vec<Workflow>,
+        tokens: DesignTokens,
+        component_styles: ComponentStyles,
+    ) {
+        let mut changes = Vec::new();
+
+        for (index, (id, workflow)) in self.workflows.iter().enumerate().rev() {
+            changes.push(StoreChange::Workflow {
+                id: id.clone(),
+                index,
+                before: Some(Box::new(workflow.clone())),
+                after: None,
+            });
+        }
+        for (index, workflow) in workflows.into_iter().enumerate() {
+            changes.push(StoreChange::Workflow {
+                id: workflow.id.clone(),
+                index,
+                before: None,
+                after: Some(Box::new(workflow)),
+            });
+        }
+        for (name, style) in &self.component_styles.styles {
+            changes.push(StoreChange::ComponentStyle {
+                name: name.clone(),
+                before: Some(Box::new(style.clone())),
+                after: None,
+            });
+        }
+        for (name, style) in component_styles.styles {
+            changes.push(StoreChange::ComponentStyle {
+                name,
+                before: None,
+                after: Some(Box::new(style)),
+            });
+        }
+        changes.push(StoreChange::Tokens {
+            before: Box::new(self.tokens.clone()),
+            after: Box::new(tokens),
+        });
+
+        let change = StoreChange::Batch(changes);
+        self.apply_change(&change, true);
+        self.selected_workflow = None;
+        self.selected_context = None;
+        self.selected_preset = None;
+        self.record(HistoryScope::Global, "Load project", change);
+    }
+
+    // === Errors ===
+
+    /// Convert a module error into a [`StudioError`], recording it in
+    /// `problems`. Every rejected store action goes through here.
+    pub fn checked<T, E: Into<StudioError>>(
+        &mut self,
+        result: Result<T, E>,
+    ) -> Result<T, StudioError> {
+        result.map_err(|error| {
+            let error = error.into();
+            self.problems.push(error.clone());
+            error
+        })
+    }
+
+    /// Drop recorded problems, e.g. when the panel is cleared.
+    pub fn clear_problems(&mut self) {
+        self.problems.clear();
+    }
+
+    // === History ===
+
+    /// Apply a change and record it as a single history entry.
+    ///
+    /// Touched entities are stamped with one time for the whole change.
+    /// Rejections are recorded in `problems`; see [`StudioStore::try_commit`].
+    pub fn commit(&mut self, scope: HistoryScope, label: impl Into<String>, change: StoreChange) {
+        let _ = self.try_commit(scope, label, change);
+    }
+
+    /// Apply a change within the store [`limits`](StoreLimits).
+    ///
+    /// A change that grows a count past its hard limit is reverted and
+    /// rejected. Crossing a soft limit adds a warning to `problems`. The
+    /// store [`middleware`](self::middleware) runs around the commit.
+    pub fn try_commit(
+        &mut self,
+        scope: HistoryScope,
+        label: impl Into<String>,
+        mut change: StoreChange,
+    ) -> Result<(), StudioError> {
+        trace_span!(
+            "store",
+            "commit",
+            count = match &change {
+                StoreChange::Batch(changes) => changes.len(),
+                _ => 1,
+            }
+        );
+        let label = label.into();
+        let action = StoreAction::Commit {
+            scope,
+            label: &label,
+        };
+        self.before_action(&action)?;
+        change.stamp(self.clock.now(), self.modified_by.as_deref());
+        let history_bytes = self.history_bytes();
+        let before = self.counts(history_bytes);
+        self.apply_change(&change, true);
+        let after = self.counts(history_bytes + change.estimated_bytes());
+
+        if let Err(error) = self.limits.check_hard(&before, &after) {
+            self.apply_change(&change, false);
+            return self.checked(Err(error));
+        }
+        let warnings = self.limits.soft_warnings(&before, &after);
+        self.problems.extend(warnings);
+        self.record(scope, label.clone(), change);
+        if let Some(persistence) = self.persistence.as_mut() {
+            persistence.note_edit();
+        }
+        self.after_action(&StoreAction::Commit {
+            scope,
+            label: &label,
+        });
+        Ok(())
+    }
+
+    /// Push an applied change onto the history under a new revision and
+    /// notify subscribers.
+    pub(crate) fn record(
+        &mut self,
+        scope: HistoryScope,
+        label: impl Into<String>,
+        change: StoreChange,
+    ) {
+        let label = label.into();
+        let revision = self.dirty.record(&change.categories());
+        let events = self.change_events(&change, true);
+        let bulk = matches!(change, StoreChange::Batch(_));
+        self.history
+            .push_revision(scope, label.clone(), change, revision);
+        self.dirty
+            .retain(self.history.entries().map(|entry| entry.revision));
+        self.emit(&label, events, bulk);
+    }
+
+    /// The history scope of the active designer.
+    pub fn active_scope(&self) -> HistoryScope {
+        self.ui.active_designer.into()
+    }
+
+    /// Undo the last action of the active designer.
+    pub fn undo(&mut self) -> bool {
+        self.undo_scope(self.active_scope())
+    }
+
+    /// Redo the last undone action of the active designer.
+    pub fn redo(&mut self) -> bool {
+        self.redo_scope(self.active_scope())
+    }
+
+    /// Undo the last action recorded in a scope. An open flow transaction
+    /// is committed first.
+    pub fn undo_scope(&mut self, scope: HistoryScope) -> bool {
+        self.commit_transaction();
+        let Some(entry) = self.history.scope_mut(scope).undo() else {
+            return false;
+        };
+        let (label, change, revision) = (entry.label.clone(), entry.change.clone(), entry.revision);
+        self.apply_change(&change, false);
+        self.dirty.undo(revision, &change.categories());
+        if scope == HistoryScope::Global {
+            self.history.invalidate_designer_scopes();
+        }
+        let events = self.change_events(&change, false);
+        self.emit(&label, events, matches!(change, StoreChange::Batch(_)));
+        true
+    }
+
+    /// Redo the last undone action in a scope.
+    pub fn redo_scope(&mut self, scope: HistoryScope) -> bool {
+        self.commit_transaction();
+        let Some(entry) = self.history.scope_mut(scope).redo() else {
+            return false;
+        };
+        let (label, change, revision) = (entry.label.clone(), entry.change.clone(), entry.revision);
+        self.apply_change(&change, true);
+        self.dirty.redo(revision, &change.categories());
+        if scope == HistoryScope::Global {
+            self.history.invalidate_designer_scopes();
+        }
+        let events = self.change_events(&change, true);
+        self.emit(&label, events, matches!(change, StoreChange::Batch(_)));
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo(self.active_scope())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo(self.active_scope())
+    }
+
+    /// Apply a change forwards (redo) or backwards (undo).
+    pub(crate) fn apply_change(&mut self, change: &StoreChange, forward: bool) {
+        match change {
+            StoreChange::Workflow {
+                id,
+                index,
+                before,
+                after,
+            } => {
+                let target = if forward { after } else { before };
+                match target {
+                    Some(workflow) => {
+                        if let Some(existing) = self.workflows.get_mut(id) {
+                            *existing = Workflow::clone(workflow);
+                        } else {
+                            let index = (*index).min(self.workflows.len());
+                            self.workflows
+                                .shift_insert(index, id.clone(), Workflow::clone(workflow));
+                        }
+                    }
+                    None => {
+                        self.workflows.shift_remove(id);
+                        if self.selected_workflow.as_deref() == Some(id) {
+                            self.selected_workflow = None;
+                            self.selected_context = None;
+                            self.selected_preset = None;
+                        }
+                    }
+                }
+            }
+            StoreChange::Tokens { before, after } => {
+                self.tokens = DesignTokens::clone(if forward { after } else { before });
+            }
+            StoreChange::ComponentStyle {
+                name,
+                before,
+                after,
+            } => match if forward { after } else { before } {
+                Some(style) => self
+                    .component_styles
+                    .set(name.clone(), ComponentStyle::clone(style)),
+                None => {
+                    self.component_styles.styles.shift_remove(name);
+                }
+            },
+            StoreChange::ComponentStyleOrder { before, after } => {
+                self.component_styles =
+                    ComponentStyles::clone(if forward { after } else { before });
+            }
+            StoreChange::Flow { commands, entities } => {
+                self.apply_change(&StoreChange::Batch(entities.clone()), forward);
+                self.flow_replay.push(FlowReplay {
+                    commands: commands.clone(),
+                    forward,
+                });
+            }
+            StoreChange::Batch(changes) => {
+                if forward {
+                    for change in changes {
+                        self.apply_change(change, true);
+                    }
+                } else {
+                    for change in changes.iter().rev() {
+                        self.apply_change(change, false);
+                    }
+                }
+            }
+        }
+    }
+
     // === Selection ===
 
     pub fn select_workflow(&mut self, id: &str) {
@@ -58,28 +644,30 @@ impl StudioStore {
             self.selected_workflow = Some(id.to_string());
             self.selected_context = None;
             self.selected_preset = None;
+            self.emit("Select workflow", Vec::new(), false);
         }
     }
 
     pub fn select_context(&mut self, workflow_id: &str, context_id: &str) {
-        if let Some(workflow) = self.workflows.get(workflow_id) {
-            if workflow.contexts.contains_key(context_id) {
-                self.selected_workflow = Some(workflow_id.to_string());
-                self.selected_context = Some(context_id.to_string());
-                self.selected_preset = None;
-            }
+        if let Some(workflow) = self.workflows.get(workflow_id)
+            && workflow.contexts.contains_key(context_id)
+        {
+            self.selected_workflow = Some(workflow_id.to_string());
+            self.selected_context = Some(context_id.to_string());
+            self.selected_preset = None;
+            self.emit("Select context", Vec::new(), false);
         }
     }
 
     pub fn select_preset(&mut self, workflow_id: &str, context_id: &str, preset_id: &str) {
-        if let Some(workflow) = self.workflows.get(workflow_id) {
-            if let Some(context) = workflow.contexts.get(context_id) {
-                if context.presets.contains_key(preset_id) {
-                    self.selected_workflow = Some(workflow_id.to_string());
-                    self.selected_context = Some(context_id.to_string());
-                    self.selected_preset = Some(preset_id.to_string());
-                }
-            }
+        if let Some(workflow) = self.workflows.get(workflow_id)
+            && let Some(context) = workflow.contexts.get(context_id)
+            && context.presets.contains_key(preset_id)
+        {
+            self.selected_workflow = Some(workflow_id.to_string());
+            self.selected_context = Some(context_id.to_string());
+            self.selected_preset = Some(preset_id.to_string());
+            self.emit("Select preset", Vec::new(), false);
         }
     }
 
@@ -134,6 +722,92 @@ impl StudioStore {
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
     }
+
+    /// Check every workflow's navigation graph on the designer's canvas,
+    /// replacing the previous diagnostics.
+    pub fn validate_workflows(&mut self, designer: &NavigationDesigner) {
+        self.workflow_diagnostics = self
+            .workflows
+            .values()
+            .map(|w| (w.id.clone(), w.validate(&designer.canvas)))
+            .filter(|(_, diagnostics)| !diagnostics.is_empty())
+            .collect();
+    }
+
+    /// A navigation designer set up with the canvas settings.
+    pub fn navigation_designer(&self) -> NavigationDesigner {
+        let mut designer = NavigationDesigner::new();
+        designer.set_snap_grid(self.ui.canvas.snap_grid);
+        designer
+    }
+
+    /// Workflow diagnostics, in workflow order.
+    pub fn all_workflow_diagnostics(&self) -> impl Iterator<Item = &WorkflowDiagnostic> {
+        self.workflow_diagnostics.values().flatten()
+    }
+
+    /// Canvas node badges: the worst severity of the diagnostics on each
+    /// context, or on a workflow without a start.
+    pub fn workflow_badges(&self) -> IndexMap<EntityId, ValidationSeverity> {
+        let rank = |severity| match severity {
+            ValidationSeverity::Error => 2,
+            ValidationSeverity::Warning => 1,
+            ValidationSeverity::Info => 0,
+        };
+        let mut badges: IndexMap<EntityId, ValidationSeverity> = IndexMap::new();
+        for diagnostic in self.all_workflow_diagnostics() {
+            let nodes = if diagnostic.contexts.is_empty() {
+                std::slice::from_ref(&diagnostic.workflow_id)
+            } else {
+                diagnostic.contexts.as_slice()
+            };
+            for node in nodes {
+                let badge = badges.entry(node.clone()).or_insert(diagnostic.severity);
+                if rank(diagnostic.severity) > rank(*badge) {
+                    *badge = diagnostic.severity;
+                }
+            }
+        }
+        badges
+    }
+}
+
+/// A reversible change to store data, recorded in the history.
+#[derive(Debug, Clone)]
+pub enum StoreChange {
+    /// A workflow was added, replaced or removed.
+    Workflow {
+        id: EntityId,
+        /// Position in the workflow list.
+        index: usize,
+        before: Option<Box<Workflow>>,
+        after: Option<Box<Workflow>>,
+    },
+    /// The design token set was replaced.
+    Tokens {
+        before: Box<DesignTokens>,
+        after: Box<DesignTokens>,
+    },
+    /// A component style was added, replaced or removed.
+    ComponentStyle {
+        name: String,
+        before: Option<Box<ComponentStyle>>,
+        after: Option<Box<ComponentStyle>>,
+    },
+    /// Component styles were reordered; no style changed.
+    ComponentStyleOrder {
+        before: Box<ComponentStyles>,
+        after: Box<ComponentStyles>,
+    },
+    /// Navigation canvas edits with the workflow changes they made. The
+    /// canvas lives on the designer, so undo and redo queue the commands
+    /// in `flow_replay`.
+    Flow {
+        commands: Vec<FlowCommand>,
+        entities: Vec<StoreChange>,
+    },
+    /// Several changes applied as one action.
+    Batch(Vec<StoreChange>),
 }
 
 /// Draft entities for editing.
@@ -156,6 +830,14 @@ pub struct UiState {
     pub bottom_panel_visible: bool,
     /// Canvas state.
     pub canvas: CanvasState,
+    /// Color scheme.
+    pub theme: ThemeMode,
+    /// Autosave timer state.
+    pub autosave: AutosaveState,
+    /// Recently used literal values for input suggestions, most recent first.
+    pub recent_values: Vec<String>,
+    /// Color vision deficiency simulated in the preview and on the canvas.
+    pub color_simulation: Option<ColorBlindness>,
 }
 
 /// Active designer.
@@ -167,12 +849,74 @@ pub enum Designer {
     Component,
 }
 
+/// Studio color scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    /// Setting value, e.g. `dark`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            ThemeMode::System => "system",
+            ThemeMode::Light => "light",
+            ThemeMode::Dark => "dark",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [ThemeMode::System, ThemeMode::Light, ThemeMode::Dark]
+            .into_iter()
+            .find(|mode| mode.key() == key)
+    }
+}
+
+/// Autosave timer state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutosaveState {
+    /// Whether the autosave timer is running.
+    pub running: bool,
+    /// Seconds between saves.
+    pub interval_secs: u64,
+}
+
+impl Default for AutosaveState {
+    fn default() -> Self {
+        Self {
+            running: true,
+            interval_secs: 30,
+        }
+    }
+}
+
 /// Canvas UI state.
 #[derive(Debug, Clone, Default)]
 pub struct CanvasState {
     pub auto_layout_enabled: bool,
     pub show_minimap: bool,
     pub zoom: f64,
+    /// Style edges by imported traversal weight.
+    pub show_edge_weights: bool,
+    /// Edge layer visibility toggled by the user, overriding the layer
+    /// defaults.
+    pub layer_visibility: IndexMap<String, bool>,
+    /// Also hide nodes only reachable through hidden layers.
+    pub hide_layer_only_nodes: bool,
+    /// Gesture bindings, rebound when the interaction settings change.
+    pub gestures: GestureController,
+    /// Arrow-key navigation, nudging and rename state.
+    pub keyboard: CanvasKeyboardController,
+    /// Hover time before an edge tooltip shows, in milliseconds.
+    pub tooltip_delay_ms: u64,
+    /// Grid dropped nodes snap to; 0 turns snapping off.
+    pub snap_grid: f64,
+    /// Edge under the pointer, if any.
+    pub edge_hover: Option<EdgeHover>,
 }
 
 impl Default for UiState {
@@ -185,7 +929,19 @@ impl Default for UiState {
                 auto_layout_enabled: true,
                 show_minimap: false,
                 zoom: 1.0,
+                show_edge_weights: false,
+                layer_visibility: IndexMap::new(),
+                hide_layer_only_nodes: false,
+                gestures: GestureController::default(),
+                keyboard: CanvasKeyboardController::default(),
+                tooltip_delay_ms: DEFAULT_TOOLTIP_DELAY_MS,
+                snap_grid: NAVIGATION_SNAP_GRID,
+                edge_hover: None,
             },
+            theme: ThemeMode::default(),
+            autosave: AutosaveState::default(),
+            recent_values: Vec::new(),
+            color_simulation: None,
         }
     }
 }
@@ -201,6 +957,7 @@ pub struct ValidationError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::designer::css::TokenValue;
 
     #[test]
     fn test_store_workflow_operations() {
@@ -392,6 +1149,118 @@ mod tests {
         assert_eq!(keys, vec!["f1", "f2", "f3"]);
     }
 
+    #[test]
+    fn test_weight_overlay_is_per_workflow() {
+        let mut store = StudioStore::new();
+        store.add_workflow(Workflow::new("Checkout").with_id("w1"));
+        store.add_workflow(Workflow::new("Search").with_id("w2"));
+        let weights = |prefix: &str, scale: f64| {
+            let mut map = WeightMap::new();
+            for (i, w) in [1.0, 2.0, 3.0, 4.0].iter().enumerate() {
+                map.add(format!("{}{}", prefix, i), w * scale);
+            }
+            map
+        };
+        store.set_edge_weights("w1", weights("a", 1.0));
+        store.set_edge_weights("w2", weights("b", 1000.0));
+        assert!(store.weight_overlay().is_none());
+
+        // The small workflow's busiest edge tops its own scale.
+        store.select_workflow("w1");
+        let overlay = store.weight_overlay().unwrap();
+        assert_eq!(overlay.style_for("a3").unwrap().bucket, 3);
+        assert_eq!(overlay.style_for("a0").unwrap().bucket, 0);
+        assert!(overlay.style_for("b0").is_none());
+
+        store.select_workflow("w2");
+        let overlay = store.weight_overlay().unwrap();
+        assert_eq!(overlay.style_for("b0").unwrap().bucket, 0);
+        assert_eq!(overlay.legend()[3].label, "4000");
+    }
+
+    #[test]
+    fn test_undo_is_scoped_to_active_designer() {
+        let mut store = StudioStore::new();
+
+        store.add_workflow(Workflow::new("Main").with_id("w1"));
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert("primary".to_string(), TokenValue::Simple("#3b82f6".to_string()));
+        store.set_tokens(tokens);
+        store.add_workflow(Workflow::new("Second").with_id("w2"));
+
+        // Ctrl+Z in the CSS designer only reverts the token edit
+        store.ui.active_designer = Designer::Css;
+        assert!(store.undo());
+        assert!(store.tokens.colors.is_empty());
+        assert_eq!(store.workflows.len(), 2);
+        assert!(!store.undo());
+
+        // Ctrl+Z in the navigation designer reverts workflow additions in order
+        store.ui.active_designer = Designer::Navigation;
+        assert!(store.undo());
+        assert!(store.get_workflow("w2").is_none());
+        assert!(store.get_workflow("w1").is_some());
+        assert!(store.tokens.colors.is_empty());
+
+        // Redo stays in its scope too
+        store.ui.active_designer = Designer::Css;
+        assert!(store.redo());
+        assert!(store.tokens.colors.contains_key("primary"));
+        assert!(store.get_workflow("w2").is_none());
+    }
+
+    #[test]
+    fn test_undo_scope_explicit() {
+        let mut store = StudioStore::new();
+
+        let mut style = ComponentStyle::default();
+        style.base.padding = Some("1rem".to_string());
+        store.set_component_style("button", style).unwrap();
+        store.add_workflow(Workflow::new("Main").with_id("w1"));
+
+        // Active designer is Navigation, but the CSS scope can be targeted directly
+        assert!(store.undo_scope(HistoryScope::CssDesigner));
+        assert!(store.component_styles.get("button").is_none());
+        assert!(store.get_workflow("w1").is_some());
+    }
+
+    #[test]
+    fn test_undo_remove_workflow_restores_position() {
+        let mut store = StudioStore::new();
+        store.add_workflow(Workflow::new("Flow 1").with_id("f1"));
+        store.add_workflow(Workflow::new("Flow 2").with_id("f2"));
+        store.add_workflow(Workflow::new("Flow 3").with_id("f3"));
+
+        store.remove_workflow("f2");
+        assert!(store.undo());
+
+        let keys: Vec<_> = store.workflows.keys().collect();
+        assert_eq!(keys, vec!["f1", "f2", "f3"]);
+    }
+
+    #[test]
+    fn test_load_project_checkpoints_all_scopes() {
+        let mut store = StudioStore::new();
+        store.add_workflow(Workflow::new("Old").with_id("old"));
+        store.set_tokens(DesignTokens::default());
+
+        store.load_project(
+            vec![Workflow::new("New").with_id("new")],
+            DesignTokens::default(),
+            ComponentStyles::new(),
+        );
+
+        // Designer histories were recorded against the old project
+        assert!(!store.history.can_undo(HistoryScope::NavDesigner));
+        assert!(!store.history.can_undo(HistoryScope::CssDesigner));
+        assert!(!store.undo());
+
+        // Undoing the load itself restores the previous project
+        assert!(store.undo_scope(HistoryScope::Global));
+        assert!(store.get_workflow("old").is_some());
+        assert!(store.get_workflow("new").is_none());
+    }
+
     #[test]
     fn test_workflow_removal_clears_selection() {
         let mut store = StudioStore::new();
@@ -413,4 +1282,72 @@ mod tests {
         assert!(store.selected_context.is_none());
         assert!(store.selected_preset.is_none());
     }
+
+    #[test]
+    fn test_rejected_actions_yield_studio_errors() {
+        use crate::designer::css::TokenValue;
+        use crate::error::{EntityRef, ErrorCode};
+
+        let mut store = StudioStore::new();
+        store
+            .import_tokens(r##"{"colors": {"primary": "#000"}}"##)
+            .unwrap();
+
+        let error = store.rename_token("colors.missing", "brand").unwrap_err();
+        assert_eq!(error.code, ErrorCode::TokenNotFound);
+        assert_eq!(
+            error.entity,
+            Some(EntityRef::Token("colors.missing".to_string()))
+        );
+
+        let error = store.import_tokens("{").unwrap_err();
+        assert_eq!(error.code, ErrorCode::TokenParse);
+
+        let codes: Vec<ErrorCode> = store.problems.iter().map(|p| p.code).collect();
+        assert_eq!(codes, [ErrorCode::TokenNotFound, ErrorCode::TokenParse]);
+        assert_eq!(
+            store.tokens.colors["primary"],
+            TokenValue::Simple("#000".to_string())
+        );
+
+        store.rename_token("colors.primary", "brand").unwrap();
+        assert!(store.tokens.colors.contains_key("brand"));
+        assert!(store.undo_scope(HistoryScope::CssDesigner));
+        assert!(store.tokens.colors.contains_key("primary"));
+    }
+
+    #[test]
+    fn test_transform_tokens_is_one_edit() {
+        use crate::designer::css::TokenValue;
+
+        let mut store = StudioStore::new();
+        store
+            .import_tokens(r#"{"spacing": {"sm": "8px", "md": "16px", "lg": "var(--spacing-md)"}}"#)
+            .unwrap();
+        let report = store.transform_tokens(TokenCategory::Spacing, NumericOp::Multiply(1.25));
+        assert_eq!(report.changed, ["spacing.sm", "spacing.md"]);
+        assert_eq!(report.skipped, ["spacing.lg"]);
+        assert_eq!(
+            store
+                .history
+                .scope(HistoryScope::CssDesigner)
+                .unwrap()
+                .undo_label(),
+            Some("Multiply spacing")
+        );
+
+        assert!(store.undo_scope(HistoryScope::CssDesigner));
+        assert_eq!(
+            store.tokens.spacing["sm"],
+            TokenValue::Simple("8px".to_string())
+        );
+        assert_eq!(
+            store.tokens.spacing["md"],
+            TokenValue::Simple("16px".to_string())
+        );
+
+        let report = store.transform_tokens(TokenCategory::Spacing, NumericOp::Darken(0.1));
+        assert_eq!(report.errors.len(), 1);
+        assert!(store.history.can_redo(HistoryScope::CssDesigner));
+    }
 }
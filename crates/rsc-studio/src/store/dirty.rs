@@ -0,0 +1,285 @@
+//! Unsaved-changes tracking.
+//!
+//! Every mutating action bumps a store revision. Each [`ChangeCategory`]
+//! remembers the revision that produced its current state, so undoing back
+//! to what was saved makes the category clean again without comparing
+//! content.
+
+use std::collections::HashMap;
+
+use super::{StoreChange, StudioStore};
+use crate::error::StudioError;
+
+/// Part of the project a mutation touches, for partial saves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeCategory {
+    /// Design tokens and themes.
+    Tokens,
+    Workflows,
+    ComponentStyles,
+    Settings,
+}
+
+impl ChangeCategory {
+    pub fn all() -> &'static [ChangeCategory] {
+        &[
+            ChangeCategory::Tokens,
+            ChangeCategory::Workflows,
+            ChangeCategory::ComponentStyles,
+            ChangeCategory::Settings,
+        ]
+    }
+}
+
+impl StoreChange {
+    /// Categories the change touches, without duplicates.
+    pub fn categories(&self) -> Vec<ChangeCategory> {
+        let mut categories = Vec::new();
+        self.collect_categories(&mut categories);
+        categories
+    }
+
+    fn collect_categories(&self, categories: &mut Vec<ChangeCategory>) {
+        let category = match self {
+            StoreChange::Workflow { .. } | StoreChange::Flow { .. } => ChangeCategory::Workflows,
+            StoreChange::Tokens { .. } => ChangeCategory::Tokens,
+            StoreChange::ComponentStyle { .. } | StoreChange::ComponentStyleOrder { .. } => {
+                ChangeCategory::ComponentStyles
+            }
+            StoreChange::Batch(changes) => {
+                for change in changes {
+                    change.collect_categories(categories);
+                }
+                return;
+            }
+        };
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+}
+
+/// Revision bookkeeping behind [`StudioStore::is_dirty`].
+#[derive(Debug, Clone, Default)]
+pub struct DirtyTracker {
+    revision: u64,
+    saved_revision: u64,
+    /// Revision that produced each category's current state; absent means
+    /// the state the store started with.
+    current: HashMap<ChangeCategory, u64>,
+    saved: HashMap<ChangeCategory, u64>,
+    /// Category states replaced by each recorded revision, for undo.
+    replaced: HashMap<u64, Vec<(ChangeCategory, u64)>>,
+}
+
+impl DirtyTracker {
+    /// Revision of the last mutation, including undo and redo.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Store revision at the last save that covered every category.
+    pub fn saved_revision(&self) -> u64 {
+        self.saved_revision
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        ChangeCategory::all()
+            .iter()
+            .any(|&category| self.is_category_dirty(category))
+    }
+
+    pub fn is_category_dirty(&self, category: ChangeCategory) -> bool {
+        self.state(category) != self.saved.get(&category).copied().unwrap_or(0)
+    }
+
+    /// Categories with unsaved changes, in [`ChangeCategory::all`] order.
+    pub fn dirty_categories(&self) -> Vec<ChangeCategory> {
+        ChangeCategory::all()
+            .iter()
+            .copied()
+            .filter(|&category| self.is_category_dirty(category))
+            .collect()
+    }
+
+    /// Record an undoable mutation, returning the revision to store with
+    /// its history entry.
+    pub(crate) fn record(&mut self, categories: &[ChangeCategory]) -> u64 {
+        self.revision += 1;
+        let replaced = categories
+            .iter()
+            .map(|&category| (category, self.state(category)))
+            .collect();
+        self.replaced.insert(self.revision, replaced);
+        self.touch_at(categories, self.revision);
+        self.revision
+    }
+
+    /// Record a mutation that has no history entry.
+    pub(crate) fn touch(&mut self, categories: &[ChangeCategory]) {
+        self.revision += 1;
+        self.touch_at(categories, self.revision);
+    }
+
+    /// Revert the category states set by `revision`.
+    pub(crate) fn undo(&mut self, revision: u64, categories: &[ChangeCategory]) {
+        let Some(replaced) = self.replaced.get(&revision) else {
+            // Not recorded here; the old state is unknown, so stay dirty.
+            return self.touch(categories);
+        };
+        self.revision += 1;
+        for &(category, state) in replaced {
+            self.current.insert(category, state);
+        }
+    }
+
+    /// Restore the category states set by `revision`.
+    pub(crate) fn redo(&mut self, revision: u64, categories: &[ChangeCategory]) {
+        if !self.replaced.contains_key(&revision) {
+            return self.touch(categories);
+        }
+        self.revision += 1;
+        self.touch_at(categories, revision);
+    }
+
+    /// Make the current state of `categories` the saved one.
+    pub fn mark_saved(&mut self, categories: &[ChangeCategory]) {
+        for &category in categories {
+            self.saved.insert(category, self.state(category));
+        }
+        if !self.is_dirty() {
+            self.saved_revision = self.revision;
+        }
+    }
+
+    /// Forget undo bookkeeping for revisions no longer in any history.
+    pub(crate) fn retain(&mut self, live: impl IntoIterator<Item = u64>) {
+        let live: Vec<u64> = live.into_iter().collect();
+        self.replaced.retain(|revision, _| live.contains(revision));
+    }
+
+    fn state(&self, category: ChangeCategory) -> u64 {
+        self.current.get(&category).copied().unwrap_or(0)
+    }
+
+    fn touch_at(&mut self, categories: &[ChangeCategory], revision: u64) {
+        for &category in categories {
+            self.current.insert(category, revision);
+        }
+    }
+}
+
+impl StudioStore {
+    /// Whether any category has changes since it was last saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_dirty()
+    }
+
+    /// Record a save or export of `categories`. Once nothing is left
+    /// unsaved, the auto-save snapshots are marked saved too.
+    pub fn mark_saved(&mut self, categories: &[ChangeCategory]) -> Result<(), StudioError> {
+        self.dirty.mark_saved(categories);
+        if self.is_dirty() {
+            return Ok(());
+        }
+        self.mark_snapshots_saved()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::ComponentStyle;
+    use crate::entity::Workflow;
+    use crate::history::HistoryScope;
+
+    #[test]
+    fn test_edit_undo_redo_save() {
+        let mut store = StudioStore::new();
+        assert!(!store.is_dirty());
+
+        store.add_workflow(Workflow::new("Checkout").with_id("w1"));
+        store.add_workflow(Workflow::new("Profile").with_id("w2"));
+        assert_eq!(store.dirty.dirty_categories(), [ChangeCategory::Workflows]);
+        store.undo_scope(HistoryScope::NavDesigner);
+        assert!(store.is_dirty());
+        store.undo_scope(HistoryScope::NavDesigner);
+        assert!(!store.is_dirty());
+        store.redo_scope(HistoryScope::NavDesigner);
+        assert!(store.is_dirty());
+        assert_eq!(store.dirty.revision(), 5);
+
+        store.mark_saved(ChangeCategory::all()).unwrap();
+        assert!(!store.is_dirty());
+        assert_eq!(store.dirty.saved_revision(), 5);
+        // Undoing past the save is an unsaved change; redoing returns to it.
+        store.undo_scope(HistoryScope::NavDesigner);
+        assert!(store.is_dirty());
+        store.redo_scope(HistoryScope::NavDesigner);
+        assert!(!store.is_dirty());
+        store.redo_scope(HistoryScope::NavDesigner);
+        assert!(store.is_dirty());
+        assert!(store.workflows.contains_key("w2"));
+
+        // A new edit after undoing to the save point drops the redo branch.
+        store.undo_scope(HistoryScope::NavDesigner);
+        store.add_workflow(Workflow::new("Search").with_id("w3"));
+        store.undo_scope(HistoryScope::NavDesigner);
+        assert!(!store.is_dirty());
+        store.redo_scope(HistoryScope::NavDesigner);
+        assert_eq!(store.workflows.keys().collect::<Vec<_>>(), ["w1", "w3"]);
+        assert!(store.is_dirty());
+    }
+
+    #[test]
+    fn test_partial_save() {
+        let mut store = StudioStore::new();
+        store.add_workflow(Workflow::new("Checkout").with_id("w1"));
+        store
+            .set_component_style("button", ComponentStyle::default())
+            .unwrap();
+        store.set_setting("canvas.show_minimap", true).unwrap();
+        assert_eq!(
+            store.dirty.dirty_categories(),
+            [
+                ChangeCategory::Workflows,
+                ChangeCategory::ComponentStyles,
+                ChangeCategory::Settings,
+            ]
+        );
+
+        store.mark_saved(&[ChangeCategory::Workflows]).unwrap();
+        assert_eq!(
+            store.dirty.dirty_categories(),
+            [ChangeCategory::ComponentStyles, ChangeCategory::Settings]
+        );
+        assert_eq!(store.dirty.saved_revision(), 0);
+
+        store.undo_scope(HistoryScope::CssDesigner);
+        store.mark_saved(&[ChangeCategory::Settings]).unwrap();
+        assert!(!store.is_dirty());
+        assert_eq!(store.dirty.saved_revision(), store.dirty.revision());
+    }
+
+    #[test]
+    fn test_project_load_is_one_batch() {
+        let mut store = StudioStore::new();
+        let tokens = store.tokens.clone();
+        store.load_project(
+            vec![Workflow::new("Checkout").with_id("w1")],
+            tokens,
+            Default::default(),
+        );
+        let global = store.history.scope(HistoryScope::Global).unwrap();
+        assert_eq!(
+            global.entries().next().unwrap().change.categories(),
+            [ChangeCategory::Workflows, ChangeCategory::Tokens]
+        );
+        assert_eq!(
+            store.dirty.dirty_categories(),
+            [ChangeCategory::Tokens, ChangeCategory::Workflows]
+        );
+        store.undo_scope(HistoryScope::Global);
+        assert!(!store.is_dirty());
+    }
+}
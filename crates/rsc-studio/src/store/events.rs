@@ -0,0 +1,773 @@
+//! Typed change events for extensions.
+//!
+//! Subscribers are called after a mutation has been applied and recorded,
+//! once per event their [`EventFilter`] matches. Every event carries the
+//! store revision it produced.
+//!
+//! Callbacks get the store read-only through an [`EventContext`]. Changes
+//! they want to make are queued with [`EventContext::commit`] and
+//! committed in order once every subscriber has seen the event; those
+//! commits emit events of their own. Reactions nested deeper than
+//! [`MAX_EVENT_DEPTH`] are dropped and reported in `problems`, so a pair of
+//! subscribers reacting to each other can't loop forever.
+
+use std::fmt;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use super::{StoreChange, StudioStore};
+use crate::designer::css::TokenValue;
+use crate::designer::flow_history::FlowCommand;
+use crate::entity::EntityId;
+use crate::history::HistoryScope;
+use crate::settings::SettingChange;
+
+/// How many levels of subscriber reactions are committed.
+pub const MAX_EVENT_DEPTH: usize = 4;
+
+/// Handle for [`StudioStore::unsubscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// What happened to an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    Added,
+    Updated,
+    Removed,
+}
+
+impl Mutation {
+    fn between(existed: bool, exists: bool) -> Self {
+        match (existed, exists) {
+            (false, true) => Mutation::Added,
+            (true, false) => Mutation::Removed,
+            _ => Mutation::Updated,
+        }
+    }
+}
+
+/// Event category, for filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Token,
+    Workflow,
+    Context,
+    ComponentStyle,
+    /// Navigation canvas node.
+    Node,
+    /// Navigation canvas edge.
+    Edge,
+    Selection,
+    Theme,
+    Settings,
+}
+
+/// A change to the store.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreEvent {
+    /// A token was added (`old` is `None`), edited or removed (`new` is
+    /// `None`).
+    TokenChanged {
+        path: String,
+        old: Option<TokenValue>,
+        new: Option<TokenValue>,
+        revision: u64,
+    },
+    WorkflowChanged {
+        id: EntityId,
+        mutation: Mutation,
+        revision: u64,
+    },
+    ContextChanged {
+        workflow: EntityId,
+        context: EntityId,
+        mutation: Mutation,
+        revision: u64,
+    },
+    ComponentStyleChanged {
+        name: String,
+        mutation: Mutation,
+        revision: u64,
+    },
+    NodeChanged {
+        id: String,
+        mutation: Mutation,
+        revision: u64,
+    },
+    EdgeChanged {
+        id: String,
+        mutation: Mutation,
+        revision: u64,
+    },
+    SelectionChanged {
+        workflow: Option<EntityId>,
+        context: Option<EntityId>,
+        preset: Option<EntityId>,
+        revision: u64,
+    },
+    ThemeSwitched {
+        from: String,
+        to: String,
+        revision: u64,
+    },
+    /// Settings changed value, in the order they were applied.
+    SettingsChanged {
+        changes: Vec<SettingChange>,
+        revision: u64,
+    },
+    /// Events of one bulk action, e.g. a project load, in order.
+    BatchApplied {
+        label: String,
+        events: Vec<StoreEvent>,
+        revision: u64,
+    },
+}
+
+impl StoreEvent {
+    /// The event's category; `None` for batches.
+    pub fn kind(&self) -> Option<EventKind> {
+        Some(match self {
+            StoreEvent::TokenChanged { .. } => EventKind::Token,
+            StoreEvent::WorkflowChanged { .. } => EventKind::Workflow,
+            StoreEvent::ContextChanged { .. } => EventKind::Context,
+            StoreEvent::ComponentStyleChanged { .. } => EventKind::ComponentStyle,
+            StoreEvent::NodeChanged { .. } => EventKind::Node,
+            StoreEvent::EdgeChanged { .. } => EventKind::Edge,
+            StoreEvent::SelectionChanged { .. } => EventKind::Selection,
+            StoreEvent::ThemeSwitched { .. } => EventKind::Theme,
+            StoreEvent::SettingsChanged { .. } => EventKind::Settings,
+            StoreEvent::BatchApplied { .. } => return None,
+        })
+    }
+
+    /// Store revision after the change.
+    pub fn revision(&self) -> u64 {
+        match self {
+            StoreEvent::TokenChanged { revision, .. }
+            | StoreEvent::WorkflowChanged { revision, .. }
+            | StoreEvent::ContextChanged { revision, .. }
+            | StoreEvent::ComponentStyleChanged { revision, .. }
+            | StoreEvent::NodeChanged { revision, .. }
+            | StoreEvent::EdgeChanged { revision, .. }
+            | StoreEvent::SelectionChanged { revision, .. }
+            | StoreEvent::ThemeSwitched { revision, .. }
+            | StoreEvent::SettingsChanged { revision, .. }
+            | StoreEvent::BatchApplied { revision, .. } => *revision,
+        }
+    }
+}
+
+/// Which events a subscriber receives. A batch is delivered with just
+/// its matching events, and not at all if none match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    /// Kinds to receive; empty receives every kind.
+    pub kinds: Vec<EventKind>,
+    /// Only token events under this path prefix, e.g. `colors.`.
+    pub token_prefix: Option<String>,
+}
+
+impl EventFilter {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn kinds(kinds: impl IntoIterator<Item = EventKind>) -> Self {
+        Self {
+            kinds: kinds.into_iter().collect(),
+            token_prefix: None,
+        }
+    }
+
+    pub fn with_token_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.token_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn matches(&self, event: &StoreEvent) -> bool {
+        match event {
+            StoreEvent::BatchApplied { events, .. } => events.iter().any(|e| self.matches(e)),
+            StoreEvent::TokenChanged { path, .. }
+                if self
+                    .token_prefix
+                    .as_ref()
+                    .is_some_and(|prefix| !path.starts_with(prefix.as_str())) =>
+            {
+                false
+            }
+            _ => event
+                .kind()
+                .is_some_and(|kind| self.kinds.is_empty() || self.kinds.contains(&kind)),
+        }
+    }
+
+    /// The part of `event` this filter lets through.
+    fn select(&self, event: &StoreEvent) -> Option<StoreEvent> {
+        match event {
+            StoreEvent::BatchApplied {
+                label,
+                events,
+                revision,
+            } => {
+                let events: Vec<StoreEvent> =
+                    events.iter().filter_map(|e| self.select(e)).collect();
+                (!events.is_empty()).then(|| StoreEvent::BatchApplied {
+                    label: label.clone(),
+                    events,
+                    revision: *revision,
+                })
+            }
+            _ => self.matches(event).then(|| event.clone()),
+        }
+    }
+}
+
+/// Error raised by event dispatch.
+#[derive(Debug, Error)]
+pub enum EventError {
+    /// Subscriber reactions nested too deep; their changes were dropped.
+    #[error(
+        "Dropped {dropped} change(s) requested by event subscribers after {MAX_EVENT_DEPTH} nested reactions"
+    )]
+    DepthExceeded { dropped: usize },
+}
+
+/// What a subscriber callback can do.
+pub struct EventContext<'a> {
+    store: &'a StudioStore,
+    queued: Vec<(HistoryScope, String, StoreChange)>,
+}
+
+impl EventContext<'_> {
+    /// The store, after the change.
+    pub fn store(&self) -> &StudioStore {
+        self.store
+    }
+
+    /// Queue a change, committed after every subscriber has seen the
+    /// current event.
+    pub fn commit(&mut self, scope: HistoryScope, label: impl Into<String>, change: StoreChange) {
+        self.queued.push((scope, label.into(), change));
+    }
+}
+
+/// A subscriber callback.
+pub type EventCallback = Arc<dyn Fn(&StoreEvent, &mut EventContext) + Send + Sync>;
+
+type Selection = (Option<EntityId>, Option<EntityId>, Option<EntityId>);
+
+/// Subscribers and dispatch state.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Vec<(SubscriptionId, EventFilter, EventCallback)>,
+    next_id: u64,
+    /// Events held back by [`StudioStore::batch_events`].
+    batch: Option<Vec<StoreEvent>>,
+    depth: usize,
+    /// Selection last reported.
+    selection: Selection,
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field(
+                "subscribers",
+                &self
+                    .subscribers
+                    .iter()
+                    .map(|(id, _, _)| id)
+                    .collect::<Vec<_>>(),
+            )
+            .field("batching", &self.batch.is_some())
+            .finish()
+    }
+}
+
+impl EventBus {
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+impl StudioStore {
+    /// Call `callback` for every following event `filter` matches.
+    pub fn subscribe(
+        &mut self,
+        filter: EventFilter,
+        callback: impl Fn(&StoreEvent, &mut EventContext) + Send + Sync + 'static,
+    ) -> SubscriptionId {
+        self.events.next_id += 1;
+        let id = SubscriptionId(self.events.next_id);
+        self.events
+            .subscribers
+            .push((id, filter, Arc::new(callback)));
+        id
+    }
+
+    /// Returns false if there was no such subscription.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.events.subscribers.len();
+        self.events.subscribers.retain(|(s, _, _)| *s != id);
+        self.events.subscribers.len() != before
+    }
+
+    /// Run `f`, delivering every event it causes as one
+    /// [`BatchApplied`](StoreEvent::BatchApplied) when it returns. Nested
+    /// calls join the outer batch.
+    pub fn batch_events<R>(&mut self, label: &str, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.events.batch.is_some() {
+            return f(self);
+        }
+        self.events.batch = Some(Vec::new());
+        let result = f(self);
+        let events = self.events.batch.take().unwrap_or_default();
+        if !events.is_empty() {
+            let revision = self.dirty.revision();
+            self.dispatch(StoreEvent::BatchApplied {
+                label: label.to_string(),
+                events,
+                revision,
+            });
+        }
+        result
+    }
+
+    /// Events for applying `change` forwards or backwards. Empty without
+    /// subscribers.
+    pub(crate) fn change_events(&self, change: &StoreChange, forward: bool) -> Vec<StoreEvent> {
+        let mut events = Vec::new();
+        if !self.events.is_empty() {
+            collect_change_events(change, forward, self.dirty.revision(), &mut events);
+        }
+        events
+    }
+
+    /// Deliver `events`, with a selection change if the selection moved.
+    /// Several events of one bulk action go out as a batch.
+    pub(crate) fn emit(&mut self, label: &str, mut events: Vec<StoreEvent>, bulk: bool) {
+        let selection = (
+            self.selected_workflow.clone(),
+            self.selected_context.clone(),
+            self.selected_preset.clone(),
+        );
+        if selection != self.events.selection {
+            self.events.selection = selection.clone();
+            if !self.events.is_empty() {
+                let (workflow, context, preset) = selection;
+                events.push(StoreEvent::SelectionChanged {
+                    workflow,
+                    context,
+                    preset,
+                    revision: self.dirty.revision(),
+                });
+            }
+        }
+        if events.is_empty() {
+            return;
+        }
+        if let Some(batch) = self.events.batch.as_mut() {
+            batch.extend(events);
+            return;
+        }
+        if bulk && events.len() > 1 {
+            let revision = self.dirty.revision();
+            events = vec![StoreEvent::BatchApplied {
+                label: label.to_string(),
+                events,
+                revision,
+            }];
+        }
+        for event in events {
+            self.dispatch(event);
+        }
+    }
+
+    /// Run the subscribers for one event, then commit what they queued.
+    fn dispatch(&mut self, event: StoreEvent) {
+        let subscribers: Vec<(EventFilter, EventCallback)> = self
+            .events
+            .subscribers
+            .iter()
+            .map(|(_, filter, callback)| (filter.clone(), callback.clone()))
+            .collect();
+        let mut queued = Vec::new();
+        for (filter, callback) in subscribers {
+            let Some(event) = filter.select(&event) else {
+                continue;
+            };
+            let mut context = EventContext {
+                store: self,
+                queued: Vec::new(),
+            };
+            callback(&event, &mut context);
+            queued.append(&mut context.queued);
+        }
+        if queued.is_empty() {
+            return;
+        }
+        if self.events.depth >= MAX_EVENT_DEPTH {
+            let error = EventError::DepthExceeded {
+                dropped: queued.len(),
+            };
+            let _ = self.checked::<(), _>(Err(error));
+            return;
+        }
+        self.events.depth += 1;
+        for (scope, label, change) in queued {
+            let _ = self.try_commit(scope, label, change);
+        }
+        self.events.depth -= 1;
+    }
+}
+
+fn collect_change_events(
+    change: &StoreChange,
+    forward: bool,
+    revision: u64,
+    events: &mut Vec<StoreEvent>,
+) {
+    fn ordered<T>(before: T, after: T, forward: bool) -> (T, T) {
+        if forward {
+            (before, after)
+        } else {
+            (after, before)
+        }
+    }
+    match change {
+        StoreChange::Workflow {
+            id, before, after, ..
+        } => {
+            let (old, new) = ordered(before.as_deref(), after.as_deref(), forward);
+            events.push(StoreEvent::WorkflowChanged {
+                id: id.clone(),
+                mutation: Mutation::between(old.is_some(), new.is_some()),
+                revision,
+            });
+            let none = IndexMap::new();
+            let old = old.map_or(&none, |w| &w.contexts);
+            let new = new.map_or(&none, |w| &w.contexts);
+            let ids = old
+                .keys()
+                .chain(new.keys().filter(|k| !old.contains_key(*k)));
+            for context in ids {
+                let (was, is) = (old.get(context), new.get(context));
+                if let (Some(was), Some(is)) = (was, is)
+                    && serde_json::to_value(was).ok() == serde_json::to_value(is).ok()
+                {
+                    continue;
+                }
+                events.push(StoreEvent::ContextChanged {
+                    workflow: id.clone(),
+                    context: context.clone(),
+                    mutation: Mutation::between(was.is_some(), is.is_some()),
+                    revision,
+                });
+            }
+        }
+        StoreChange::Tokens { before, after } => {
+            let (old, new) = ordered(before, after, forward);
+            for ((prefix, old), (_, new)) in old.categories().into_iter().zip(new.categories()) {
+                let names = old
+                    .keys()
+                    .chain(new.keys().filter(|k| !old.contains_key(*k)));
+                for name in names {
+                    let (was, is) = (old.get(name), new.get(name));
+                    if was != is {
+                        events.push(StoreEvent::TokenChanged {
+                            path: format!("{}.{}", prefix, name),
+                            old: was.cloned(),
+                            new: is.cloned(),
+                            revision,
+                        });
+                    }
+                }
+            }
+        }
+        StoreChange::ComponentStyle {
+            name,
+            before,
+            after,
+        } => {
+            let (old, new) = ordered(before, after, forward);
+            events.push(StoreEvent::ComponentStyleChanged {
+                name: name.clone(),
+                mutation: Mutation::between(old.is_some(), new.is_some()),
+                revision,
+            });
+        }
+        // Reordering changes no style.
+        StoreChange::ComponentStyleOrder { .. } => {}
+        StoreChange::Flow { commands, entities } => {
+            collect_flow_events(commands, forward, revision, events);
+            for change in entities {
+                collect_change_events(change, forward, revision, events);
+            }
+        }
+        StoreChange::Batch(changes) => {
+            let mut batch = Vec::new();
+            for change in changes {
+                collect_change_events(change, forward, revision, &mut batch);
+            }
+            events.extend(batch);
+        }
+    }
+}
+
+/// Events for applying flow commands forwards or backwards.
+fn collect_flow_events(
+    commands: &[FlowCommand],
+    forward: bool,
+    revision: u64,
+    events: &mut Vec<StoreEvent>,
+) {
+    let (added, removed) = match forward {
+        true => (Mutation::Added, Mutation::Removed),
+        false => (Mutation::Removed, Mutation::Added),
+    };
+    let node = |id: &str, mutation| StoreEvent::NodeChanged {
+        id: id.to_string(),
+        mutation,
+        revision,
+    };
+    let edge = |id: &str, mutation| StoreEvent::EdgeChanged {
+        id: id.to_string(),
+        mutation,
+        revision,
+    };
+    let mut flow = Vec::new();
+    for command in commands {
+        match command {
+            FlowCommand::AddNode(n) => flow.push(node(&n.id, added)),
+            FlowCommand::RemoveNode { node: n, edges, .. } => {
+                flow.extend(edges.iter().map(|(_, e)| edge(&e.id, removed)));
+                flow.push(node(&n.id, removed));
+            }
+            FlowCommand::MoveNode { id, .. } | FlowCommand::RenameNode { id, .. } => {
+                flow.push(node(id, Mutation::Updated))
+            }
+            FlowCommand::AddEdge(e) => flow.push(edge(&e.id, added)),
+            FlowCommand::RemoveEdge { edge: e, .. } => flow.push(edge(&e.id, removed)),
+        }
+    }
+    if !forward {
+        flow.reverse();
+    }
+    events.extend(flow);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::entity::{Context, Workflow};
+
+    type Log = Arc<Mutex<Vec<StoreEvent>>>;
+
+    fn record(store: &mut StudioStore, filter: EventFilter) -> Log {
+        let log = Log::default();
+        let sink = log.clone();
+        store.subscribe(filter, move |event, _| {
+            sink.lock().unwrap().push(event.clone())
+        });
+        log
+    }
+
+    fn taken(log: &Log) -> Vec<StoreEvent> {
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    fn checkout() -> Workflow {
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        workflow.add_context(Context::new("Cart").with_id("c1"));
+        workflow
+    }
+
+    #[test]
+    fn test_subscribers_and_filters() {
+        let mut store = StudioStore::new();
+        let all = record(&mut store, EventFilter::all());
+        let colors = record(
+            &mut store,
+            EventFilter::kinds([EventKind::Token]).with_token_prefix("colors."),
+        );
+        let selection = record(&mut store, EventFilter::kinds([EventKind::Selection]));
+
+        store.add_workflow(checkout());
+        assert_eq!(
+            taken(&all),
+            [
+                StoreEvent::WorkflowChanged {
+                    id: "w1".to_string(),
+                    mutation: Mutation::Added,
+                    revision: 1,
+                },
+                StoreEvent::ContextChanged {
+                    workflow: "w1".to_string(),
+                    context: "c1".to_string(),
+                    mutation: Mutation::Added,
+                    revision: 1,
+                },
+            ]
+        );
+
+        let mut tokens = store.tokens.clone();
+        let primary = TokenValue::Simple("#2563eb".to_string());
+        tokens.colors.insert("primary".to_string(), primary.clone());
+        tokens
+            .spacing
+            .insert("sm".to_string(), TokenValue::Simple("4px".to_string()));
+        store.set_tokens(tokens);
+        assert_eq!(
+            taken(&colors),
+            [StoreEvent::TokenChanged {
+                path: "colors.primary".to_string(),
+                old: None,
+                new: Some(primary.clone()),
+                revision: 2,
+            }]
+        );
+        assert_eq!(taken(&all).len(), 2);
+
+        store.select_context("w1", "c1");
+        store.select_context("w1", "c1");
+        assert_eq!(taken(&selection).len(), 1);
+        assert_eq!(taken(&all).len(), 1);
+
+        // Undo reverses the change and can move the selection.
+        store.undo_scope(HistoryScope::NavDesigner);
+        let events = taken(&all);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            StoreEvent::WorkflowChanged {
+                mutation: Mutation::Removed,
+                revision: 3,
+                ..
+            }
+        ));
+        assert_eq!(
+            taken(&selection),
+            [StoreEvent::SelectionChanged {
+                workflow: None,
+                context: None,
+                preset: None,
+                revision: 3,
+            }]
+        );
+        store.undo_scope(HistoryScope::CssDesigner);
+        assert_eq!(
+            taken(&colors),
+            [StoreEvent::TokenChanged {
+                path: "colors.primary".to_string(),
+                old: Some(primary),
+                new: None,
+                revision: 4,
+            }]
+        );
+        assert!(taken(&selection).is_empty());
+    }
+
+    #[test]
+    fn test_batches() {
+        let mut store = StudioStore::new();
+        let workflows = record(&mut store, EventFilter::kinds([EventKind::Workflow]));
+        let themes = record(&mut store, EventFilter::kinds([EventKind::Theme]));
+
+        store.load_project(
+            vec![checkout(), Workflow::new("Profile").with_id("w2")],
+            store.tokens.clone(),
+            Default::default(),
+        );
+        let events = taken(&workflows);
+        let [StoreEvent::BatchApplied { label, events, .. }] = events.as_slice() else {
+            panic!("expected one batch, got {:?}", events);
+        };
+        assert_eq!(label, "Load project");
+        // Only the workflow events pass the filter.
+        assert_eq!(events.len(), 2);
+        assert!(taken(&themes).is_empty());
+
+        store.create_theme("dark", None).unwrap();
+        let all = record(&mut store, EventFilter::all());
+        store.batch_events("Import", |store| {
+            store.remove_workflow("w1");
+            store.remove_workflow("w2");
+            store.switch_theme("dark").unwrap();
+        });
+        let events = taken(&all);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].revision(), 5);
+        let StoreEvent::BatchApplied { events, .. } = &events[0] else {
+            panic!("expected a batch");
+        };
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            &events[3],
+            StoreEvent::ThemeSwitched { from, to, .. } if from == "default" && to == "dark"
+        ));
+        assert_eq!(taken(&workflows).len(), 1);
+        assert_eq!(taken(&themes).len(), 1);
+    }
+
+    #[test]
+    fn test_reactions_are_queued() {
+        let mut store = StudioStore::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let seen = order.clone();
+        // Keep a mirror of every workflow, which itself triggers this
+        // subscriber again.
+        store.subscribe(
+            EventFilter::kinds([EventKind::Workflow]),
+            move |event, ctx| {
+                let StoreEvent::WorkflowChanged {
+                    id,
+                    mutation: Mutation::Added,
+                    ..
+                } = event
+                else {
+                    return;
+                };
+                seen.lock().unwrap().push(format!("mirror {}", id));
+                assert!(ctx.store().workflows.contains_key(id));
+                let copy = Workflow::new("Copy").with_id(format!("{}+", id));
+                ctx.commit(
+                    HistoryScope::NavDesigner,
+                    "Mirror",
+                    StoreChange::Workflow {
+                        id: copy.id.clone(),
+                        index: ctx.store().workflows.len(),
+                        before: None,
+                        after: Some(Box::new(copy)),
+                    },
+                );
+            },
+        );
+        let seen = order.clone();
+        store.subscribe(EventFilter::all(), move |event, _| {
+            if let StoreEvent::WorkflowChanged { id, .. } = event {
+                seen.lock().unwrap().push(format!("log {}", id));
+            }
+        });
+
+        store.add_workflow(Workflow::new("Main").with_id("w"));
+        // The second subscriber sees each event before its reaction runs.
+        assert_eq!(
+            order.lock().unwrap()[..4],
+            ["mirror w", "log w", "mirror w+", "log w+"]
+        );
+        assert_eq!(
+            store.workflows.keys().collect::<Vec<_>>(),
+            ["w", "w+", "w++", "w+++", "w++++"]
+        );
+        assert_eq!(store.problems.len(), 1);
+        assert_eq!(
+            store.problems[0].code,
+            crate::error::ErrorCode::EventDepthExceeded
+        );
+    }
+}
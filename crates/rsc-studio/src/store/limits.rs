@@ -0,0 +1,356 @@
+//! Entity count and memory guardrails.
+//!
+//! Every committed change is measured against [`StoreLimits`]. Going past
+//! a soft limit adds a warning to the problems panel; going past a hard
+//! limit rejects the change. Changes that shrink a measure are always
+//! allowed, so an oversized project can still be cleaned up.
+
+use std::io;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::{StoreChange, StudioStore};
+use crate::designer::css::ValidationSeverity;
+use crate::designer::flow_history::FlowCommand;
+use crate::entity::Workflow;
+use crate::error::{ErrorCode, StudioError};
+
+/// A soft and a hard limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limit {
+    pub soft: usize,
+    pub hard: usize,
+}
+
+impl Limit {
+    pub const fn new(soft: usize, hard: usize) -> Self {
+        Self { soft, hard }
+    }
+}
+
+/// Store size limits, part of the studio configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoreLimits {
+    /// Nodes (workflow, contexts and presets) in one workflow.
+    pub nodes_per_workflow: Limit,
+    /// Workflows in the project.
+    pub workflows: Limit,
+    /// Design tokens in all categories.
+    pub tokens: Limit,
+    /// Estimated bytes held by the undo/redo history.
+    pub history_bytes: Limit,
+}
+
+impl Default for StoreLimits {
+    fn default() -> Self {
+        Self {
+            nodes_per_workflow: Limit::new(5_000, 50_000),
+            workflows: Limit::new(500, 5_000),
+            tokens: Limit::new(5_000, 50_000),
+            history_bytes: Limit::new(64 << 20, 512 << 20),
+        }
+    }
+}
+
+/// What a limit applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    NodesPerWorkflow,
+    Workflows,
+    Tokens,
+    HistoryBytes,
+}
+
+impl Measure {
+    pub fn all() -> &'static [Measure] {
+        &[
+            Measure::NodesPerWorkflow,
+            Measure::Workflows,
+            Measure::Tokens,
+            Measure::HistoryBytes,
+        ]
+    }
+}
+
+/// Current value of each measure.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoreCounts {
+    /// Nodes in the largest workflow.
+    pub largest_workflow: usize,
+    /// Name of the largest workflow.
+    pub largest_workflow_name: Option<String>,
+    pub workflows: usize,
+    pub tokens: usize,
+    pub history_bytes: usize,
+}
+
+impl StoreCounts {
+    pub fn get(&self, measure: Measure) -> usize {
+        match measure {
+            Measure::NodesPerWorkflow => self.largest_workflow,
+            Measure::Workflows => self.workflows,
+            Measure::Tokens => self.tokens,
+            Measure::HistoryBytes => self.history_bytes,
+        }
+    }
+
+    fn describe(&self, measure: Measure) -> String {
+        match measure {
+            Measure::NodesPerWorkflow => format!(
+                "Workflow '{}' has {} nodes",
+                self.largest_workflow_name.as_deref().unwrap_or_default(),
+                self.largest_workflow
+            ),
+            Measure::Workflows => format!("The project has {} workflows", self.workflows),
+            Measure::Tokens => format!("The project has {} tokens", self.tokens),
+            Measure::HistoryBytes => {
+                format!("Undo history holds about {} KB", self.history_bytes / 1024)
+            }
+        }
+    }
+}
+
+impl StoreLimits {
+    pub fn get(&self, measure: Measure) -> Limit {
+        match measure {
+            Measure::NodesPerWorkflow => self.nodes_per_workflow,
+            Measure::Workflows => self.workflows,
+            Measure::Tokens => self.tokens,
+            Measure::HistoryBytes => self.history_bytes,
+        }
+    }
+
+    /// The first measure that grew past its hard limit.
+    pub fn check_hard(&self, before: &StoreCounts, after: &StoreCounts) -> Result<(), StudioError> {
+        for &measure in Measure::all() {
+            let (old, new, limit) = (before.get(measure), after.get(measure), self.get(measure));
+            if new > limit.hard && new > old {
+                return Err(StudioError::new(
+                    ErrorCode::LimitExceeded,
+                    format!("{}; the limit is {}", after.describe(measure), limit.hard),
+                )
+                .with_hint("Split the project or raise the limit in the studio configuration"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Warnings for measures that just crossed their soft limit.
+    pub fn soft_warnings(&self, before: &StoreCounts, after: &StoreCounts) -> Vec<StudioError> {
+        Measure::all()
+            .iter()
+            .filter(|&&m| before.get(m) <= self.get(m).soft && after.get(m) > self.get(m).soft)
+            .map(|&m| {
+                StudioError::new(
+                    ErrorCode::LimitExceeded,
+                    format!(
+                        "{}; above the recommended {}",
+                        after.describe(m),
+                        self.get(m).soft
+                    ),
+                )
+                .with_severity(ValidationSeverity::Warning)
+            })
+            .collect()
+    }
+}
+
+/// Estimated memory per store slice, for the devtools inspector.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryReport {
+    /// Slice name to estimated bytes.
+    pub slices: IndexMap<&'static str, usize>,
+}
+
+impl MemoryReport {
+    pub fn total(&self) -> usize {
+        self.slices.values().sum()
+    }
+}
+
+/// Counts bytes written to it.
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Size of a value as compact JSON, without building the string.
+pub fn serde_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, value).map_or(0, |_| counter.0)
+}
+
+fn node_count(workflow: &Workflow) -> usize {
+    1 + workflow
+        .contexts
+        .values()
+        .map(|c| 1 + c.presets.len())
+        .sum::<usize>()
+}
+
+impl StoreChange {
+    /// Estimated bytes held by this change.
+    pub fn estimated_bytes(&self) -> usize {
+        match self {
+            StoreChange::Workflow { before, after, .. } => serde_size(before) + serde_size(after),
+            StoreChange::Tokens { before, after } => serde_size(before) + serde_size(after),
+            StoreChange::ComponentStyle { before, after, .. } => {
+                serde_size(before) + serde_size(after)
+            }
+            StoreChange::ComponentStyleOrder { before, after } => {
+                serde_size(before) + serde_size(after)
+            }
+            StoreChange::Flow { commands, entities } => {
+                commands.len() * std::mem::size_of::<FlowCommand>()
+                    + entities.iter().map(Self::estimated_bytes).sum::<usize>()
+            }
+            StoreChange::Batch(changes) => changes.iter().map(Self::estimated_bytes).sum(),
+        }
+    }
+}
+
+impl StudioStore {
+    /// Estimated bytes held by the undo/redo history.
+    pub fn history_bytes(&self) -> usize {
+        self.history
+            .entries()
+            .map(|e| e.change.estimated_bytes())
+            .sum()
+    }
+
+    /// Current counts, with `history_bytes` as given.
+    pub(super) fn counts(&self, history_bytes: usize) -> StoreCounts {
+        let largest = self.workflows.values().max_by_key(|w| node_count(w));
+        StoreCounts {
+            largest_workflow: largest.map_or(0, node_count),
+            largest_workflow_name: largest.map(|w| w.name.clone()),
+            workflows: self.workflows.len(),
+            tokens: self.tokens.count(),
+            history_bytes,
+        }
+    }
+
+    /// Estimated bytes per store slice.
+    pub fn memory_report(&self) -> MemoryReport {
+        let mut slices = IndexMap::new();
+        slices.insert("workflows", serde_size(&self.workflows));
+        slices.insert("tokens", serde_size(&self.tokens));
+        slices.insert("component_styles", serde_size(&self.component_styles));
+        slices.insert("history", self.history_bytes());
+        slices.insert("edge_weights", serde_size(&self.edge_weights));
+        slices.insert(
+            "category_presentation",
+            serde_size(&self.category_presentation),
+        );
+        slices.insert("layers", serde_size(&self.layers));
+        slices.insert("statuses", serde_size(&self.statuses));
+        slices.insert("assets", self.assets.total_bytes());
+        MemoryReport { slices }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::{DesignTokens, TokenValue};
+    use crate::entity::{Context, Preset};
+    use crate::history::HistoryScope;
+
+    fn workflow(id: &str, contexts: usize) -> Workflow {
+        let mut workflow = Workflow::new(id).with_id(id);
+        for i in 0..contexts {
+            let mut context = Context::new(format!("c{}", i)).with_id(format!("{}-c{}", id, i));
+            context.add_preset(Preset::new("p").with_id(format!("{}-p{}", id, i)));
+            workflow.add_context(context);
+        }
+        workflow
+    }
+
+    #[test]
+    fn test_soft_limit_warns_once() {
+        let mut store = StudioStore::new();
+        store.limits.nodes_per_workflow = Limit::new(4, 100);
+        store.add_workflow(workflow("small", 1));
+        assert!(store.problems.is_empty());
+
+        // 1 + 2 * 2 = 5 nodes, past the soft limit of 4.
+        store.add_workflow(workflow("big", 2));
+        assert_eq!(store.workflows.len(), 2);
+        assert_eq!(store.problems.len(), 1);
+        assert_eq!(store.problems[0].severity, ValidationSeverity::Warning);
+        assert_eq!(
+            store.problems[0].message,
+            "Workflow 'big' has 5 nodes; above the recommended 4"
+        );
+        store.add_workflow(workflow("bigger", 3));
+        assert_eq!(store.problems.len(), 1);
+    }
+
+    #[test]
+    fn test_hard_limit_rejects() {
+        let mut store = StudioStore::new();
+        store.limits.workflows = Limit::new(1, 2);
+        store.limits.tokens = Limit::new(10, 1);
+        store.add_workflow(workflow("a", 0));
+        store.add_workflow(workflow("b", 0));
+        store.add_workflow(workflow("c", 0));
+        assert_eq!(store.workflows.keys().collect::<Vec<_>>(), ["a", "b"]);
+        let rejected = store.problems.last().unwrap();
+        assert_eq!(rejected.code, ErrorCode::LimitExceeded);
+        assert_eq!(rejected.severity, ValidationSeverity::Error);
+        assert_eq!(
+            rejected.message,
+            "The project has 3 workflows; the limit is 2"
+        );
+        // The rejected change isn't in the history.
+        assert!(store.undo_scope(HistoryScope::NavDesigner));
+        assert_eq!(store.workflows.len(), 1);
+
+        let json = r##"{"colors": {"a": "#000", "b": "#fff"}}"##;
+        let error = store.import_tokens(json).unwrap_err();
+        assert_eq!(error.code, ErrorCode::LimitExceeded);
+        assert_eq!(store.tokens.count(), 0);
+
+        // Shrinking is allowed even when over the limit.
+        store.limits.workflows = Limit::new(0, 0);
+        assert!(store.remove_workflow("a").is_some());
+    }
+
+    #[test]
+    fn test_memory_report_slices() {
+        let mut store = StudioStore::new();
+        let empty = store.memory_report();
+        assert_eq!(empty.slices["history"], 0);
+
+        store.add_workflow(workflow("a", 3));
+        let mut tokens = store.tokens.clone();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#3b82f6".to_string()),
+        );
+        store.set_tokens(tokens);
+
+        let report = store.memory_report();
+        assert_eq!(report.slices["workflows"], serde_size(&store.workflows));
+        assert_eq!(report.slices["tokens"], serde_size(&store.tokens));
+        // The history holds the added workflow and both token sets.
+        let workflow_bytes = serde_size(&store.workflows["a"]);
+        let tokens_bytes = serde_size(&store.tokens) + serde_size(&DesignTokens::default());
+        assert_eq!(
+            report.slices["history"],
+            workflow_bytes + serde_size(&None::<Workflow>) + tokens_bytes
+        );
+        assert_eq!(report.total(), report.slices.values().sum::<usize>());
+        assert!(report.slices["workflows"] > empty.slices["workflows"]);
+    }
+}
@@ -0,0 +1,61 @@
+//! Hooks around store actions.
+//!
+//! Middleware sees every committed change and every panel action, before
+//! it runs and after it succeeded, e.g. to log or audit actions. A
+//! middleware can veto an action by returning an error from
+//! [`StoreMiddleware::before`]; the rejection is recorded in `problems`
+//! like any other.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use super::StudioStore;
+use crate::error::StudioError;
+use crate::history::HistoryScope;
+
+/// An action passing through the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreAction<'a> {
+    /// A change committed to the history.
+    Commit { scope: HistoryScope, label: &'a str },
+    /// An action dispatched to a panel.
+    Panel { panel: &'a str, action: &'a str },
+}
+
+/// Hooks run around every store action.
+pub trait StoreMiddleware: Debug + Send + Sync {
+    /// Runs before the action. An error rejects it.
+    fn before(&self, _action: &StoreAction, _store: &StudioStore) -> Result<(), StudioError> {
+        Ok(())
+    }
+
+    /// Runs after the action succeeded.
+    fn after(&self, _action: &StoreAction, _store: &StudioStore) {}
+}
+
+impl StudioStore {
+    /// Run `middleware` around every following action, after the
+    /// middleware added before it.
+    pub fn add_middleware(&mut self, middleware: impl StoreMiddleware + 'static) {
+        self.middleware.push(Arc::new(middleware));
+    }
+
+    /// Ask every middleware whether `action` may run. The first rejection
+    /// is recorded in `problems` and returned.
+    pub(crate) fn before_action(&mut self, action: &StoreAction) -> Result<(), StudioError> {
+        let rejected = self
+            .middleware
+            .iter()
+            .find_map(|m| m.before(action, self).err());
+        match rejected {
+            Some(error) => self.checked(Err(error)),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn after_action(&self, action: &StoreAction) {
+        for middleware in &self.middleware {
+            middleware.after(action, self);
+        }
+    }
+}
@@ -0,0 +1,534 @@
+//! Auto-save snapshots and crash recovery.
+//!
+//! With [`Persistence`] enabled, each committed change marks the store
+//! dirty. The host calls [`StudioStore::autosave_tick`] on a timer; once
+//! no edit has come in for the configured interval, the store is written
+//! as a [`StoreSnapshot`] and the oldest snapshots beyond the limit are
+//! dropped. An explicit save records which snapshot it covered, so on the
+//! next start [`StudioStore::restore_latest`] can tell whether there is
+//! newer work to recover.
+//!
+//! Configuration settings are written to the same backend whenever they
+//! change, and read back with [`StudioStore::restore_settings`].
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::StudioStore;
+use crate::designer::css::{ComponentStyles, DesignTokens, ThemeManager};
+use crate::entity::Workflow;
+use crate::error::StudioError;
+use crate::settings::{FlaggedSetting, SettingStorage};
+
+/// Current snapshot format version.
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// Default key prefix for snapshots.
+pub const DEFAULT_KEY_PREFIX: &str = "rsc-studio";
+
+/// Default quiet period before a snapshot is taken.
+pub const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default number of snapshots kept.
+pub const DEFAULT_MAX_SNAPSHOTS: usize = 10;
+
+/// Persistence error types.
+#[derive(Debug, Error)]
+pub enum PersistenceError {
+    /// The storage backend failed.
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    /// A snapshot could not be written or read.
+    #[error("Snapshot error: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    /// `localStorage` was chosen but the host didn't provide it.
+    #[error("localStorage is not available")]
+    Unavailable,
+}
+
+/// Key-value storage for snapshots.
+pub trait StorageBackend: Debug + Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<String>, PersistenceError>;
+
+    fn set(&self, key: &str, value: &str) -> Result<(), PersistenceError>;
+
+    fn remove(&self, key: &str) -> Result<(), PersistenceError>;
+
+    /// Keys starting with `prefix`, in any order.
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, PersistenceError>;
+}
+
+/// Storage held in memory, for tests and sessions without a disk.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, PersistenceError> {
+        Ok(self.entries().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), PersistenceError> {
+        self.entries().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), PersistenceError> {
+        self.entries().remove(key);
+        Ok(())
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, PersistenceError> {
+        Ok(self
+            .entries()
+            .keys()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Where snapshots go.
+#[derive(Debug, Clone)]
+pub enum PersistenceTarget {
+    /// Browser `localStorage`, with keys under `key_prefix`. The web shell
+    /// binds it with [`Persistence::with_local_storage`].
+    LocalStorage { key_prefix: String },
+    /// An injected backend, e.g. files for native builds.
+    Backend(Arc<dyn StorageBackend>),
+}
+
+/// Auto-save settings.
+#[derive(Debug, Clone)]
+pub struct PersistenceConfig {
+    /// Quiet period after the last edit before a snapshot is taken.
+    pub interval: Duration,
+    /// Snapshots kept; older ones are removed.
+    pub max_snapshots: usize,
+    pub target: PersistenceTarget,
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_SNAPSHOT_INTERVAL,
+            max_snapshots: DEFAULT_MAX_SNAPSHOTS,
+            target: PersistenceTarget::LocalStorage {
+                key_prefix: DEFAULT_KEY_PREFIX.to_string(),
+            },
+        }
+    }
+}
+
+impl PersistenceConfig {
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_max_snapshots(mut self, max_snapshots: usize) -> Self {
+        self.max_snapshots = max_snapshots;
+        self
+    }
+
+    pub fn with_target(mut self, target: PersistenceTarget) -> Self {
+        self.target = target;
+        self
+    }
+}
+
+/// The store state a snapshot restores.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreSnapshot {
+    pub schema_version: u32,
+    /// Position in the snapshot sequence, starting at 1.
+    #[serde(default)]
+    pub sequence: u64,
+    /// When the snapshot was taken (Unix seconds).
+    pub taken_at: u64,
+    #[serde(default)]
+    pub workflows: Vec<Workflow>,
+    #[serde(default)]
+    pub tokens: DesignTokens,
+    #[serde(default)]
+    pub themes: ThemeManager,
+    #[serde(default)]
+    pub component_styles: ComponentStyles,
+    /// UI preference settings that differ from their defaults.
+    #[serde(default)]
+    pub ui_settings: IndexMap<String, serde_json::Value>,
+}
+
+/// The snapshot an explicit save covered.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct SavedMarker {
+    sequence: u64,
+    saved_at: u64,
+}
+
+/// Auto-save state of a store.
+#[derive(Debug, Clone)]
+pub struct Persistence {
+    pub config: PersistenceConfig,
+    local_storage: Option<Arc<dyn StorageBackend>>,
+    /// Edits noted so far, and as of the last tick.
+    edits: u64,
+    seen_edits: u64,
+    /// Start of the current quiet period; `None` when nothing is unsaved.
+    quiet_since: Option<Instant>,
+}
+
+impl Persistence {
+    pub fn new(config: PersistenceConfig) -> Self {
+        Self {
+            config,
+            local_storage: None,
+            edits: 0,
+            seen_edits: 0,
+            quiet_since: None,
+        }
+    }
+
+    /// Provide the browser's `localStorage` for the `LocalStorage` target.
+    pub fn with_local_storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.local_storage = Some(storage);
+        self
+    }
+
+    /// Whether there are edits no snapshot holds yet.
+    pub fn is_dirty(&self) -> bool {
+        self.edits != self.seen_edits || self.quiet_since.is_some()
+    }
+
+    pub(crate) fn note_edit(&mut self) {
+        self.edits += 1;
+    }
+
+    /// Whether a snapshot is due at `now`. Edits since the last tick
+    /// restart the quiet period.
+    fn due(&mut self, now: Instant) -> bool {
+        if self.edits != self.seen_edits {
+            self.seen_edits = self.edits;
+            self.quiet_since = Some(now);
+            return false;
+        }
+        self.quiet_since
+            .is_some_and(|since| now.duration_since(since) >= self.config.interval)
+    }
+
+    fn backend(&self) -> Result<(&dyn StorageBackend, &str), PersistenceError> {
+        match &self.config.target {
+            PersistenceTarget::LocalStorage { key_prefix } => {
+                let storage = self
+                    .local_storage
+                    .as_deref()
+                    .ok_or(PersistenceError::Unavailable)?;
+                Ok((storage, key_prefix))
+            }
+            PersistenceTarget::Backend(backend) => Ok((backend.as_ref(), DEFAULT_KEY_PREFIX)),
+        }
+    }
+
+    /// Snapshot keys, oldest first.
+    fn snapshot_keys(&self) -> Result<Vec<String>, PersistenceError> {
+        let (backend, prefix) = self.backend()?;
+        let mut keys = backend.keys(&format!("{}/snapshots/", prefix))?;
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn latest_sequence(&self) -> Result<u64, PersistenceError> {
+        Ok(self
+            .snapshot_keys()?
+            .last()
+            .and_then(|key| key.rsplit('/').next())
+            .and_then(|seq| seq.parse().ok())
+            .unwrap_or(0))
+    }
+
+    fn saved_marker(&self) -> Result<SavedMarker, PersistenceError> {
+        let (backend, prefix) = self.backend()?;
+        match backend.get(&format!("{}/saved", prefix))? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(SavedMarker::default()),
+        }
+    }
+
+    /// Write `snapshot` as the next in sequence and drop the oldest beyond
+    /// the limit. Returns its sequence number.
+    fn write(&self, mut snapshot: StoreSnapshot) -> Result<u64, PersistenceError> {
+        snapshot.sequence = self.latest_sequence()? + 1;
+        let (backend, prefix) = self.backend()?;
+        let key = format!("{}/snapshots/{:020}", prefix, snapshot.sequence);
+        backend.set(&key, &serde_json::to_string(&snapshot)?)?;
+
+        let keys = self.snapshot_keys()?;
+        let excess = keys.len().saturating_sub(self.config.max_snapshots.max(1));
+        for key in &keys[..excess] {
+            backend.remove(key)?;
+        }
+        Ok(snapshot.sequence)
+    }
+
+    fn write_settings(
+        &self,
+        settings: &IndexMap<String, serde_json::Value>,
+    ) -> Result<(), PersistenceError> {
+        let (backend, prefix) = self.backend()?;
+        backend.set(
+            &format!("{}/settings", prefix),
+            &serde_json::to_string(settings)?,
+        )
+    }
+
+    fn read_settings(&self) -> Result<IndexMap<String, serde_json::Value>, PersistenceError> {
+        let (backend, prefix) = self.backend()?;
+        match backend.get(&format!("{}/settings", prefix))? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(IndexMap::new()),
+        }
+    }
+
+    /// The latest snapshot, if it is newer than the last explicit save.
+    fn recovery(&self) -> Result<Option<StoreSnapshot>, PersistenceError> {
+        let saved = self.saved_marker()?;
+        let Some(key) = self.snapshot_keys()?.pop() else {
+            return Ok(None);
+        };
+        let (backend, _) = self.backend()?;
+        let Some(json) = backend.get(&key)? else {
+            return Ok(None);
+        };
+        let snapshot: StoreSnapshot = serde_json::from_str(&json)?;
+        Ok((snapshot.sequence > saved.sequence).then_some(snapshot))
+    }
+}
+
+impl StudioStore {
+    /// Start auto-saving.
+    pub fn enable_persistence(&mut self, persistence: Persistence) {
+        self.persistence = Some(persistence);
+    }
+
+    /// The persisted state of the store, stamped with the store clock.
+    pub fn snapshot(&self) -> StoreSnapshot {
+        StoreSnapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            sequence: 0,
+            taken_at: self.clock.now(),
+            workflows: self.workflows.values().cloned().collect(),
+            tokens: self.tokens.clone(),
+            themes: self.themes_snapshot(),
+            component_styles: self.component_styles.clone(),
+            ui_settings: self.settings.persisted(SettingStorage::Ui),
+        }
+    }
+
+    /// Take a snapshot if edits have settled for the configured interval.
+    /// Call on a timer; returns whether a snapshot was written. Does
+    /// nothing while auto-save is switched off in the settings.
+    pub fn autosave_tick(&mut self, now: Instant) -> Result<bool, StudioError> {
+        let Some(persistence) = self.persistence.as_mut() else {
+            return Ok(false);
+        };
+        if !self.ui.autosave.running || !persistence.due(now) {
+            return Ok(false);
+        }
+        persistence.quiet_since = None;
+        let snapshot = self.snapshot();
+        let result = self.persistence.as_ref().map(|p| p.write(snapshot));
+        match result {
+            Some(result) => self.checked(result).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Record an explicit save, covering every snapshot taken so far.
+    pub(crate) fn mark_snapshots_saved(&mut self) -> Result<(), StudioError> {
+        let Some(persistence) = self.persistence.as_ref() else {
+            return Ok(());
+        };
+        let result = persistence.latest_sequence().and_then(|sequence| {
+            let (backend, prefix) = persistence.backend()?;
+            let marker = SavedMarker {
+                sequence,
+                saved_at: self.clock.now(),
+            };
+            backend.set(
+                &format!("{}/saved", prefix),
+                &serde_json::to_string(&marker)?,
+            )
+        });
+        self.checked(result)
+    }
+
+    /// Call on startup. Returns the latest snapshot if it holds work newer
+    /// than the last explicit save, so the UI can offer to
+    /// [`recover`](StudioStore::recover) it.
+    pub fn restore_latest(&mut self) -> Result<Option<StoreSnapshot>, StudioError> {
+        let result = match self.persistence.as_ref() {
+            Some(persistence) => persistence.recovery(),
+            None => Ok(None),
+        };
+        self.checked(result)
+    }
+
+    /// Write the configuration settings to the auto-save backend. Called
+    /// whenever a setting changes.
+    pub fn save_settings(&mut self) -> Result<(), StudioError> {
+        let Some(persistence) = self.persistence.as_ref() else {
+            return Ok(());
+        };
+        let result = persistence.write_settings(&self.settings.persisted(SettingStorage::Config));
+        self.checked(result)
+    }
+
+    /// Call on startup. Applies the configuration settings last written
+    /// to the backend and returns the entries that could not be applied.
+    pub fn restore_settings(&mut self) -> Result<Vec<FlaggedSetting>, StudioError> {
+        let result = match self.persistence.as_ref() {
+            Some(persistence) => persistence.read_settings(),
+            None => Ok(IndexMap::new()),
+        };
+        let values = self.checked(result)?;
+        Ok(self.load_settings(SettingStorage::Config, values))
+    }
+
+    /// Load a recovery snapshot into the store, as one undoable change.
+    pub fn recover(&mut self, snapshot: StoreSnapshot) {
+        self.load_project(
+            snapshot.workflows,
+            snapshot.tokens,
+            snapshot.component_styles,
+        );
+        self.themes = snapshot.themes;
+        self.themes.set_active_tokens(self.tokens.clone());
+        self.load_settings(SettingStorage::Ui, snapshot.ui_settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Context;
+    use crate::store::dirty::ChangeCategory;
+    use crate::timestamp::Clock;
+
+    fn store(storage: &Arc<MemoryStorage>, max_snapshots: usize) -> StudioStore {
+        let config = PersistenceConfig::default()
+            .with_interval(Duration::from_secs(2))
+            .with_max_snapshots(max_snapshots)
+            .with_target(PersistenceTarget::Backend(storage.clone()));
+        let mut store = StudioStore::new();
+        store.clock = Clock::Fixed(1_000);
+        store.enable_persistence(Persistence::new(config));
+        store
+    }
+
+    fn secs(start: Instant, s: u64) -> Instant {
+        start + Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_debounced_snapshots_rotate() {
+        let storage = Arc::new(MemoryStorage::new());
+        let mut store = store(&storage, 3);
+        let start = Instant::now();
+        assert!(!store.autosave_tick(start).unwrap());
+
+        // Typing keeps restarting the quiet period.
+        for i in 0..3 {
+            store.add_workflow(Workflow::new("Draft").with_id(format!("w{}", i)));
+            assert!(!store.autosave_tick(secs(start, i)).unwrap());
+        }
+        assert!(!store.autosave_tick(secs(start, 3)).unwrap());
+        assert!(store.autosave_tick(secs(start, 4)).unwrap());
+        assert!(!store.autosave_tick(secs(start, 10)).unwrap());
+        assert!(!store.persistence.as_ref().unwrap().is_dirty());
+
+        for i in 0..4 {
+            store.add_workflow(Workflow::new("More").with_id(format!("m{}", i)));
+            let t = 20 + i * 10;
+            store.autosave_tick(secs(start, t)).unwrap();
+            assert!(store.autosave_tick(secs(start, t + 2)).unwrap());
+        }
+        let keys = storage.keys("rsc-studio/snapshots/").unwrap();
+        let sequences: Vec<&str> = keys
+            .iter()
+            .map(|k| k.rsplit('/').next().unwrap().trim_start_matches('0'))
+            .collect();
+        assert_eq!(sequences, ["3", "4", "5"]);
+    }
+
+    #[test]
+    fn test_recovery_after_crash() {
+        let storage = Arc::new(MemoryStorage::new());
+        let mut store = store(&storage, 5);
+        let start = Instant::now();
+        store.add_workflow(Workflow::new("Saved").with_id("w1"));
+        store.autosave_tick(start).unwrap();
+        assert!(store.autosave_tick(secs(start, 2)).unwrap());
+        store.mark_saved(ChangeCategory::all()).unwrap();
+        assert!(store.restore_latest().unwrap().is_none());
+
+        let mut workflow = Workflow::new("Checkout").with_id("w2");
+        workflow.add_context(Context::new("Cart").with_id("c1"));
+        store.add_workflow(workflow);
+        store.set_setting("canvas.show_minimap", true).unwrap();
+        store.autosave_tick(secs(start, 5)).unwrap();
+        assert!(store.autosave_tick(secs(start, 8)).unwrap());
+        // The edit in progress when the server restarts is lost.
+        store.add_workflow(Workflow::new("Unsaved").with_id("w3"));
+        drop(store);
+
+        let mut restarted = self::store(&storage, 5);
+        let snapshot = restarted.restore_latest().unwrap().unwrap();
+        assert_eq!(snapshot.schema_version, SNAPSHOT_SCHEMA_VERSION);
+        assert_eq!((snapshot.sequence, snapshot.taken_at), (2, 1_000));
+        restarted.recover(snapshot);
+        assert_eq!(restarted.workflows.keys().collect::<Vec<_>>(), ["w1", "w2"]);
+        assert!(restarted.workflows["w2"].contexts.contains_key("c1"));
+        assert!(restarted.ui.canvas.show_minimap);
+
+        restarted.mark_saved(ChangeCategory::all()).unwrap();
+        assert!(restarted.restore_latest().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_local_storage_needs_binding() {
+        let mut store = StudioStore::new();
+        store.enable_persistence(Persistence::new(PersistenceConfig::default()));
+        let error = store.restore_latest().unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::PersistenceFailed);
+        assert_eq!(store.problems.len(), 1);
+
+        let storage = Arc::new(MemoryStorage::new());
+        let persistence =
+            Persistence::new(PersistenceConfig::default()).with_local_storage(storage.clone());
+        store.enable_persistence(persistence);
+        store.add_workflow(Workflow::new("Main"));
+        let start = Instant::now();
+        store.autosave_tick(start).unwrap();
+        assert!(store.autosave_tick(secs(start, 2)).unwrap());
+        assert_eq!(storage.keys("rsc-studio/snapshots/").unwrap().len(), 1);
+    }
+}
@@ -1,5 +1,9 @@
 //! Built-in templates for presets.
 
+pub mod component;
+pub mod scaffold;
+pub mod workflows;
+
 use crate::entity::{
     ActivityBarConfig, ActivityItem, BottomPanelConfig, LayoutConfig, LayoutVariant,
     PanelConfig, Position, Preset, SidebarConfig, TabConfig,
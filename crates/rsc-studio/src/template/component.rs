@@ -0,0 +1,684 @@
+//! Component scaffolds for the built-in component types.
+//!
+//! A [`ComponentScaffold`] plans a new component of a [`ComponentType`]:
+//! its RSX source, a test stub, optionally a story, and a
+//! [`ComponentStyle`] whose defaults refer to the design tokens through
+//! `var()`. Tokens the project doesn't define fall back to literal values.
+//! The files are rendered from the `component` and `component-story`
+//! scaffolding templates, so user templates of those names replace them.
+//! Planning only reads the disk, so the UI can preview the files before
+//! [`StudioStore::scaffold_component`] writes them.
+
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::designer::css::{
+    ComponentStyle, ComponentType, DesignTokens, StyleProperties, TokenValue, path_to_css_var,
+};
+use crate::error::StudioError;
+use crate::history::HistoryScope;
+use crate::store::{StoreChange, StudioStore};
+use crate::template::scaffold::{
+    ScaffoldTemplate, TemplateError, TemplateLibrary, builtin_scaffolds,
+};
+
+/// Scaffold error.
+#[derive(Debug, Error)]
+pub enum ScaffoldError {
+    #[error("`{0}` is not a PascalCase identifier")]
+    InvalidName(String),
+
+    #[error("{} already exists", .0.display())]
+    FileExists(PathBuf),
+
+    #[error("Component style `{0}` already exists")]
+    StyleExists(String),
+
+    #[error("No scaffolding template `{0}`")]
+    MissingTemplate(String),
+
+    #[error("Template error: {0}")]
+    Template(#[from] TemplateError),
+
+    #[error("Write error: {0}")]
+    Write(#[from] std::io::Error),
+}
+
+/// What to generate and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaffoldOptions {
+    /// Render from a props struct rather than sample content.
+    pub with_props: bool,
+    /// Add a story for the component catalog.
+    pub with_story: bool,
+    /// Output directory, relative to the project.
+    pub directory: PathBuf,
+    /// Overwrite existing files and styles.
+    pub force: bool,
+}
+
+impl Default for ScaffoldOptions {
+    fn default() -> Self {
+        Self {
+            with_props: true,
+            with_story: false,
+            directory: PathBuf::from("components"),
+            force: false,
+        }
+    }
+}
+
+/// A file a scaffold writes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaffoldFile {
+    /// Path relative to the project.
+    pub path: PathBuf,
+    pub contents: String,
+    /// A file is already there; writing it needs `force`.
+    pub exists: bool,
+}
+
+/// Planned output of a scaffold.
+#[derive(Debug, Clone)]
+pub struct ScaffoldResult {
+    pub files: Vec<ScaffoldFile>,
+    /// Style name, which is also the component's CSS class.
+    pub style_name: String,
+    pub style: ComponentStyle,
+}
+
+impl ScaffoldResult {
+    /// Files that writing would overwrite.
+    pub fn conflicts(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|f| f.exists)
+            .map(|f| f.path.as_path())
+    }
+}
+
+/// Markup and default style of a component type.
+struct Blueprint {
+    tag: &'static str,
+    role: Option<&'static str>,
+    /// Prop holding the content, or the attribute for void elements.
+    prop: &'static str,
+    sample: &'static str,
+    /// Property, token path (empty for a fixed value) and fallback value.
+    style: &'static [(&'static str, &'static str, &'static str)],
+}
+
+impl Blueprint {
+    fn is_void(&self) -> bool {
+        self.tag == "input"
+    }
+}
+
+fn blueprint(component: ComponentType) -> Blueprint {
+    let (tag, role, prop, sample, style): (_, _, _, _, &[_]) = match component {
+        ComponentType::Button => (
+            "button",
+            None,
+            "label",
+            "Button",
+            &[
+                ("display", "", "inline-flex"),
+                ("align-items", "", "center"),
+                ("padding", "spacing.sm", "0.5rem"),
+                ("background-color", "colors.primary", "#3b82f6"),
+                ("color", "colors.bg", "#ffffff"),
+                ("border", "", "none"),
+                ("border-radius", "radius.md", "0.375rem"),
+                ("cursor", "", "pointer"),
+            ],
+        ),
+        ComponentType::Input => (
+            "input",
+            None,
+            "placeholder",
+            "Enter a value",
+            &[
+                ("display", "", "block"),
+                ("padding", "spacing.sm", "0.5rem"),
+                ("border", "", "1px solid"),
+                ("border-color", "colors.secondary", "#64748b"),
+                ("border-radius", "radius.sm", "0.25rem"),
+                ("font-size", "typography.sizes.md", "1rem"),
+            ],
+        ),
+        ComponentType::Card => (
+            "div",
+            None,
+            "title",
+            "Card",
+            &[
+                ("padding", "spacing.md", "1rem"),
+                ("background-color", "colors.bg", "#ffffff"),
+                ("border-radius", "radius.lg", "0.5rem"),
+                ("box-shadow", "shadows.md", "0 4px 6px rgba(0,0,0,0.1)"),
+            ],
+        ),
+        ComponentType::Modal => (
+            "div",
+            Some("dialog"),
+            "title",
+            "Dialog",
+            &[
+                ("position", "", "fixed"),
+                ("padding", "spacing.lg", "1.5rem"),
+                ("background-color", "colors.bg", "#ffffff"),
+                ("border-radius", "radius.lg", "0.5rem"),
+                ("box-shadow", "shadows.lg", "0 10px 15px rgba(0,0,0,0.1)"),
+                ("z-index", "z-index.modal", "1000"),
+            ],
+        ),
+        ComponentType::Badge => (
+            "span",
+            None,
+            "label",
+            "New",
+            &[
+                ("display", "", "inline-block"),
+                ("padding", "spacing.xs", "0.25rem"),
+                ("font-size", "typography.sizes.sm", "0.875rem"),
+                ("background-color", "colors.secondary", "#64748b"),
+                ("color", "colors.bg", "#ffffff"),
+                ("border-radius", "radius.full", "9999px"),
+            ],
+        ),
+        ComponentType::Alert => (
+            "div",
+            Some("alert"),
+            "message",
+            "Something happened",
+            &[
+                ("padding", "spacing.md", "1rem"),
+                ("background-color", "colors.warning", "#f59e0b"),
+                ("border-radius", "radius.md", "0.375rem"),
+            ],
+        ),
+        ComponentType::Tooltip => (
+            "div",
+            Some("tooltip"),
+            "text",
+            "Tooltip",
+            &[
+                ("position", "", "absolute"),
+                ("padding", "spacing.xs", "0.25rem"),
+                ("font-size", "typography.sizes.sm", "0.875rem"),
+                ("background-color", "colors.secondary", "#64748b"),
+                ("color", "colors.bg", "#ffffff"),
+                ("border-radius", "radius.sm", "0.25rem"),
+                ("z-index", "z-index.tooltip", "1100"),
+            ],
+        ),
+        ComponentType::Dropdown => (
+            "div",
+            Some("listbox"),
+            "label",
+            "Choose",
+            &[
+                ("position", "", "relative"),
+                ("display", "", "inline-block"),
+                ("padding", "spacing.sm", "0.5rem"),
+                ("border", "", "1px solid"),
+                ("border-color", "colors.secondary", "#64748b"),
+                ("border-radius", "radius.md", "0.375rem"),
+                ("box-shadow", "shadows.sm", "0 1px 2px rgba(0,0,0,0.05)"),
+            ],
+        ),
+        ComponentType::Tabs => (
+            "div",
+            Some("tablist"),
+            "label",
+            "Tab",
+            &[
+                ("display", "", "flex"),
+                ("gap", "spacing.sm", "0.5rem"),
+                ("padding", "spacing.xs", "0.25rem"),
+            ],
+        ),
+        ComponentType::Panel => (
+            "section",
+            None,
+            "title",
+            "Panel",
+            &[
+                ("display", "", "flex"),
+                ("flex-direction", "", "column"),
+                ("gap", "spacing.sm", "0.5rem"),
+                ("padding", "spacing.md", "1rem"),
+                ("background-color", "colors.bg", "#ffffff"),
+                ("border-radius", "radius.md", "0.375rem"),
+            ],
+        ),
+    };
+    Blueprint {
+        tag,
+        role,
+        prop,
+        sample,
+        style,
+    }
+}
+
+/// `var()` reference to the token at `path`, if `tokens` defines it.
+/// Scale tokens have no single variable.
+fn token_var(tokens: &DesignTokens, path: &str) -> Option<String> {
+    let (prefix, name) = path.rsplit_once('.')?;
+    let (_, map) = tokens
+        .categories()
+        .into_iter()
+        .find(|(p, _)| *p == prefix)?;
+    match map.get(name)? {
+        TokenValue::Scale(_) => None,
+        _ => path_to_css_var(path).map(|var| format!("var({})", var)),
+    }
+}
+
+/// `PrimaryButton`, but not `primaryButton`, `Primary_Button` or `Self`.
+fn is_pascal_case(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_alphanumeric())
+        && name != "Self"
+}
+
+/// `PrimaryButton` becomes `primary-button`, `HTTPButton` `http-button`.
+fn kebab_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let after_word = !chars[i - 1].is_ascii_uppercase();
+            let starts_word = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if after_word || starts_word {
+                out.push('-');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// A new component of a built-in type.
+#[derive(Debug, Clone)]
+pub struct ComponentScaffold {
+    pub component: ComponentType,
+    /// PascalCase component name.
+    pub name: String,
+    pub options: ScaffoldOptions,
+    templates: Vec<ScaffoldTemplate>,
+}
+
+impl ComponentScaffold {
+    pub fn new(component: ComponentType, name: impl Into<String>) -> Self {
+        Self {
+            component,
+            name: name.into(),
+            options: ScaffoldOptions::default(),
+            templates: builtin_scaffolds(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ScaffoldOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Render from the templates of `library` rather than the built-ins.
+    pub fn with_library(mut self, library: &TemplateLibrary) -> Self {
+        self.templates = library.templates().to_vec();
+        self
+    }
+
+    /// The name in kebab case: file stem, CSS class and style name.
+    pub fn class_name(&self) -> String {
+        kebab_case(&self.name)
+    }
+
+    /// Plan the files under `project_dir`, styled with `tokens`.
+    pub fn plan(
+        &self,
+        project_dir: &Path,
+        tokens: &DesignTokens,
+    ) -> Result<ScaffoldResult, ScaffoldError> {
+        if !is_pascal_case(&self.name) {
+            return Err(ScaffoldError::InvalidName(self.name.clone()));
+        }
+        let blueprint = blueprint(self.component);
+        let class = self.class_name();
+        let values = self.values(&blueprint, &class);
+        let mut names = vec!["component"];
+        if self.options.with_story {
+            names.push("component-story");
+        }
+        let mut files = Vec::new();
+        for name in names {
+            let template = self
+                .templates
+                .iter()
+                .find(|t| t.name == name)
+                .ok_or_else(|| ScaffoldError::MissingTemplate(name.to_string()))?;
+            for (path, contents) in template.render(&values)? {
+                let path = PathBuf::from(path);
+                files.push(ScaffoldFile {
+                    exists: project_dir.join(&path).exists(),
+                    path,
+                    contents,
+                });
+            }
+        }
+
+        let mut base = StyleProperties::default();
+        for (property, path, fallback) in blueprint.style {
+            let value = token_var(tokens, path).unwrap_or_else(|| fallback.to_string());
+            base.set(property, Some(value));
+        }
+        Ok(ScaffoldResult {
+            files,
+            style_name: class,
+            style: ComponentStyle {
+                base,
+                ..ComponentStyle::default()
+            },
+        })
+    }
+
+    /// Write the files of `plan` under `project_dir`. Nothing is written if
+    /// a file exists, unless `force` is set.
+    pub fn write(&self, project_dir: &Path, plan: &ScaffoldResult) -> Result<(), ScaffoldError> {
+        if !self.options.force
+            && let Some(file) = plan
+                .files
+                .iter()
+                .find(|f| project_dir.join(&f.path).exists())
+        {
+            return Err(ScaffoldError::FileExists(file.path.clone()));
+        }
+        for file in &plan.files {
+            let path = project_dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, &file.contents)?;
+        }
+        Ok(())
+    }
+
+    /// Values for the component templates.
+    fn values(&self, blueprint: &Blueprint, class: &str) -> IndexMap<String, String> {
+        let name = &self.name;
+        let mut directory = self.options.directory.to_string_lossy().into_owned();
+        if directory.is_empty() {
+            directory.push('.');
+        }
+        let (props, params, content, usage) = if self.options.with_props {
+            (
+                format!(
+                    "struct {}Props {{\n    {}: String,\n}}\n\n",
+                    name, blueprint.prop
+                ),
+                format!("(props: {}Props)", name),
+                format!("{{props.{}}}", blueprint.prop),
+                format!(" {}=\"{}\"", blueprint.prop, blueprint.sample),
+            )
+        } else {
+            let sample = format!("\"{}\"", blueprint.sample);
+            (String::new(), String::new(), sample, String::new())
+        };
+        let body = if blueprint.is_void() {
+            format!(" {}={} />", blueprint.prop, content)
+        } else {
+            format!(">\n            {}\n        </{}>", content, blueprint.tag)
+        };
+        let attrs = blueprint
+            .role
+            .map(|role| format!(" role=\"{}\"", role))
+            .unwrap_or_default();
+        [
+            ("name", name.clone()),
+            ("class", class.to_string()),
+            ("directory", directory),
+            ("type", self.component.label().to_string()),
+            ("props", props),
+            ("params", params),
+            ("tag", blueprint.tag.to_string()),
+            ("attrs", attrs),
+            ("body", body),
+            ("usage", usage),
+            ("test_name", format!("{}_renders", class.replace('-', "_"))),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+    }
+}
+
+impl StudioStore {
+    /// Scaffold a component under `project_dir` with the current tokens:
+    /// write its files and add its style as one undoable edit. Nothing
+    /// changes if a file or the style exists, unless `force` is set.
+    pub fn scaffold_component(
+        &mut self,
+        scaffold: &ComponentScaffold,
+        project_dir: &Path,
+    ) -> Result<ScaffoldResult, StudioError> {
+        let result = scaffold.plan(project_dir, &self.tokens).and_then(|plan| {
+            let before = self.component_styles.get(&plan.style_name);
+            if before.is_some() && !scaffold.options.force {
+                return Err(ScaffoldError::StyleExists(plan.style_name));
+            }
+            scaffold.write(project_dir, &plan)?;
+            Ok(plan)
+        });
+        let plan = self.checked(result)?;
+        let before = self
+            .component_styles
+            .get(&plan.style_name)
+            .cloned()
+            .map(Box::new);
+        self.try_commit(
+            HistoryScope::CssDesigner,
+            "Scaffold component",
+            StoreChange::ComponentStyle {
+                name: plan.style_name.clone(),
+                before,
+                after: Some(Box::new(plan.style.clone())),
+            },
+        )?;
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    fn project_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rsc-scaffold-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn tokens() -> DesignTokens {
+        let mut tokens = DesignTokens::default();
+        for (name, value) in [("primary", "#3b82f6"), ("bg", "#ffffff")] {
+            tokens
+                .colors
+                .insert(name.to_string(), TokenValue::Simple(value.to_string()));
+        }
+        tokens
+            .spacing
+            .insert("sm".to_string(), TokenValue::Simple("0.5rem".to_string()));
+        tokens
+    }
+
+    const BUTTON: &str = r#"//! PrimaryButton component, scaffolded from the Button type.
+
+use rsc::prelude::*;
+
+struct PrimaryButtonProps {
+    label: String,
+}
+
+component PrimaryButton(props: PrimaryButtonProps) {
+    render {
+        <button class="primary-button" data-testid="primary-button">
+            {props.label}
+        </button>
+    }
+}
+"#;
+
+    const BUTTON_TEST: &str = r#"//! Tests for PrimaryButton.
+
+use rsc_test::prelude::*;
+
+/// Tests that PrimaryButton renders.
+#[test]
+fn primary_button_renders() {
+    let view = render(<PrimaryButton label="Button" />);
+    assert!(view.query("[data-testid='primary-button']").exists(), "PrimaryButton should render");
+}
+"#;
+
+    #[test]
+    fn test_plan_snapshots() {
+        let dir = project_dir("plan");
+        let plan = ComponentScaffold::new(ComponentType::Button, "PrimaryButton")
+            .plan(&dir, &tokens())
+            .unwrap();
+
+        let paths: Vec<_> = plan.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("components/primary-button.rsx"),
+                Path::new("components/primary-button.test.rsx"),
+            ]
+        );
+        assert_eq!(plan.files[0].contents, BUTTON);
+        assert_eq!(plan.files[1].contents, BUTTON_TEST);
+        assert_eq!(plan.conflicts().count(), 0);
+
+        // Defined tokens are referenced; the rest fall back to literals.
+        assert_eq!(plan.style_name, "primary-button");
+        let base = &plan.style.base;
+        assert_eq!(
+            base.background_color.as_deref(),
+            Some("var(--color-primary)")
+        );
+        assert_eq!(base.padding.as_deref(), Some("var(--spacing-sm)"));
+        assert_eq!(base.border_radius.as_deref(), Some("0.375rem"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_options_and_names() {
+        let dir = project_dir("options");
+        let options = ScaffoldOptions {
+            with_props: false,
+            with_story: true,
+            directory: PathBuf::from("ui/forms"),
+            force: false,
+        };
+        let plan = ComponentScaffold::new(ComponentType::Input, "HTTPField")
+            .with_options(options)
+            .plan(&dir, &DesignTokens::default())
+            .unwrap();
+        assert_eq!(plan.files.len(), 3);
+        assert_eq!(
+            plan.files[2].path,
+            Path::new("ui/forms/http-field.story.rsx")
+        );
+        assert!(plan.files[0].contents.contains(
+            "<input class=\"http-field\" data-testid=\"http-field\" placeholder=\"Enter a value\" />"
+        ));
+        assert!(plan.files[2].contents.contains("<HTTPField />"));
+
+        for name in ["primaryButton", "Primary_Button", "Self", "9Lives", ""] {
+            let scaffold = ComponentScaffold::new(ComponentType::Button, name);
+            assert!(matches!(
+                scaffold.plan(&dir, &DesignTokens::default()),
+                Err(ScaffoldError::InvalidName(_))
+            ));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_user_template_replaces_component_files() {
+        let dir = project_dir("user-template");
+        let templates = dir.join("templates");
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(
+            templates.join("component.yaml"),
+            "name: component\ntarget: component\nvariables:\n  - name: name\n  - name: class\nfiles:\n  \"src/{{ class }}.rsx\": \"component {{ name }} {}\"\n",
+        )
+        .unwrap();
+        let library = TemplateLibrary::new(&templates);
+        let options = ScaffoldOptions {
+            with_story: true,
+            ..ScaffoldOptions::default()
+        };
+        let plan = ComponentScaffold::new(ComponentType::Badge, "StatusBadge")
+            .with_options(options)
+            .with_library(&library)
+            .plan(&dir, &DesignTokens::default())
+            .unwrap();
+
+        // The user `component` replaces the source and test; the built-in
+        // story is still used.
+        let files: Vec<_> = plan
+            .files
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.contents.as_str()))
+            .collect();
+        assert_eq!(
+            files[0],
+            ("src/status-badge.rsx", "component StatusBadge {}")
+        );
+        assert_eq!(files[1].0, "components/status-badge.story.rsx");
+        assert!(files[1].1.contains("<StatusBadge label=\"New\" />"));
+        assert_eq!(files.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_store_refuses_overwrite_without_force() {
+        let dir = project_dir("store");
+        let mut store = StudioStore::new();
+        let scaffold = ComponentScaffold::new(ComponentType::Card, "ProfileCard");
+        let plan = store.scaffold_component(&scaffold, &dir).unwrap();
+        for file in &plan.files {
+            let written = std::fs::read_to_string(dir.join(&file.path)).unwrap();
+            assert_eq!(written, file.contents);
+        }
+        assert!(store.component_styles.get("profile-card").is_some());
+        let css_history = store.history.scope(HistoryScope::CssDesigner).unwrap();
+        assert_eq!(css_history.undo_label(), Some("Scaffold component"));
+
+        let error = store.scaffold_component(&scaffold, &dir).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ScaffoldExists);
+        store.component_styles.styles.shift_remove("profile-card");
+        let error = store.scaffold_component(&scaffold, &dir).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ScaffoldExists);
+        let replan = scaffold.plan(&dir, &store.tokens).unwrap();
+        assert_eq!(replan.conflicts().count(), 2);
+
+        let forced = scaffold.with_options(ScaffoldOptions {
+            force: true,
+            with_story: true,
+            ..ScaffoldOptions::default()
+        });
+        store.scaffold_component(&forced, &dir).unwrap();
+        assert!(dir.join("components/profile-card.story.rsx").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,908 @@
+//! Scaffolding templates: built-ins plus user templates from a directory.
+//!
+//! A user template is one JSON or YAML manifest in the templates directory
+//! declaring a name, what it scaffolds, typed variables and the files it
+//! writes. File paths and contents use `{{ variable }}` placeholders. Each
+//! manifest is parsed and checked on its own, so a broken one is reported
+//! and skipped without failing the scan. A user template shadows the
+//! built-in of the same name.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::config::ScaffoldConfig;
+use crate::designer::css::ComponentType;
+use crate::watch::{ChangeNotifier, PollingNotifier, WaitResult};
+
+/// What a template scaffolds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Component,
+    Screen,
+    Workflow,
+}
+
+/// Type of a template variable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableType {
+    #[default]
+    String,
+    Number,
+    Bool,
+    /// One of the variable's `options`.
+    Choice,
+}
+
+/// Template error.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TemplateError {
+    #[error("{file}:{line}: unclosed placeholder")]
+    Unclosed { file: String, line: usize },
+
+    #[error("{file}:{line}: empty placeholder")]
+    EmptyPlaceholder { file: String, line: usize },
+
+    #[error("{file}:{line}: undeclared variable `{name}`")]
+    Undeclared {
+        file: String,
+        line: usize,
+        name: String,
+    },
+
+    #[error("Variable `{0}` is declared twice")]
+    DuplicateVariable(String),
+
+    #[error("Variable `{name}`: {message}")]
+    InvalidVariable { name: String, message: String },
+
+    #[error("Template has no files")]
+    NoFiles,
+
+    #[error("Missing value for `{0}`")]
+    MissingValue(String),
+}
+
+/// Why a manifest in the templates directory was skipped.
+#[derive(Debug, Error)]
+pub enum TemplateLoadError {
+    #[error("Read error: {0}")]
+    Read(#[from] std::io::Error),
+
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    #[error(transparent)]
+    Invalid(#[from] TemplateError),
+
+    #[error("Template `{name}` is already defined in {}", .other.display())]
+    Duplicate { name: String, other: PathBuf },
+}
+
+/// A skipped manifest.
+#[derive(Debug)]
+pub struct TemplateFileError {
+    pub path: PathBuf,
+    pub error: TemplateLoadError,
+}
+
+/// A user template replacing a built-in of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowWarning {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for ShadowWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "User template `{}` in {} shadows the built-in one",
+            self.name,
+            self.path.display()
+        )
+    }
+}
+
+/// A variable a template declares.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub ty: VariableType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Allowed values of a `choice` variable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+impl TemplateVariable {
+    pub fn new(name: impl Into<String>, ty: VariableType) -> Self {
+        Self {
+            name: name.into(),
+            ty,
+            default: None,
+            description: None,
+            options: Vec::new(),
+        }
+    }
+
+    pub fn with_default(mut self, default: impl Into<Value>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_options<S: Into<String>>(mut self, options: impl IntoIterator<Item = S>) -> Self {
+        self.options = options.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The default as it is substituted.
+    pub fn default_text(&self) -> Option<String> {
+        self.default.as_ref().map(|value| match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+
+    fn invalid(&self, message: impl Into<String>) -> TemplateError {
+        TemplateError::InvalidVariable {
+            name: self.name.clone(),
+            message: message.into(),
+        }
+    }
+
+    /// Check a value against the variable's type.
+    pub fn check_value(&self, value: &str) -> Result<(), TemplateError> {
+        match self.ty {
+            VariableType::String => Ok(()),
+            VariableType::Number if value.trim().parse::<f64>().is_ok() => Ok(()),
+            VariableType::Number => Err(self.invalid(format!("`{}` is not a number", value))),
+            VariableType::Bool if matches!(value, "true" | "false") => Ok(()),
+            VariableType::Bool => Err(self.invalid(format!("`{}` is not true or false", value))),
+            VariableType::Choice if self.options.iter().any(|o| o == value) => Ok(()),
+            VariableType::Choice => {
+                Err(self.invalid(format!("`{}` is not one of the options", value)))
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), TemplateError> {
+        if self.name.trim().is_empty() {
+            return Err(self.invalid("name is empty"));
+        }
+        if self.ty == VariableType::Choice && self.options.is_empty() {
+            return Err(self.invalid("a choice needs options"));
+        }
+        let type_matches = matches!(
+            (&self.default, self.ty),
+            (None, _)
+                | (
+                    Some(Value::String(_)),
+                    VariableType::String | VariableType::Choice
+                )
+                | (Some(Value::Number(_)), VariableType::Number)
+                | (Some(Value::Bool(_)), VariableType::Bool)
+        );
+        if !type_matches {
+            return Err(self.invalid("default doesn't match the type"));
+        }
+        match self.default_text() {
+            Some(default) => self.check_value(&default),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Where a template came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TemplateSource {
+    #[default]
+    BuiltIn,
+    User(PathBuf),
+}
+
+/// A scaffolding template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScaffoldTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub target: TargetKind,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    /// Output paths to contents; both may contain placeholders.
+    pub files: IndexMap<String, String>,
+    #[serde(skip)]
+    pub source: TemplateSource,
+}
+
+impl ScaffoldTemplate {
+    pub fn new(name: impl Into<String>, target: TargetKind) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            target,
+            variables: Vec::new(),
+            files: IndexMap::new(),
+            source: TemplateSource::BuiltIn,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_variable(mut self, variable: TemplateVariable) -> Self {
+        self.variables.push(variable);
+        self
+    }
+
+    pub fn with_file(mut self, path: impl Into<String>, contents: impl Into<String>) -> Self {
+        self.files.insert(path.into(), contents.into());
+        self
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&TemplateVariable> {
+        self.variables.iter().find(|v| v.name == name)
+    }
+
+    /// Manifest of a user template.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.source {
+            TemplateSource::User(path) => Some(path),
+            TemplateSource::BuiltIn => None,
+        }
+    }
+
+    pub fn is_user(&self) -> bool {
+        self.path().is_some()
+    }
+
+    /// Check the variables and that every placeholder is well formed and
+    /// declared.
+    pub fn validate(&self) -> Result<(), TemplateError> {
+        if self.files.is_empty() {
+            return Err(TemplateError::NoFiles);
+        }
+        for (i, variable) in self.variables.iter().enumerate() {
+            variable.validate()?;
+            if self.variables[..i].iter().any(|v| v.name == variable.name) {
+                return Err(TemplateError::DuplicateVariable(variable.name.clone()));
+            }
+        }
+        for (path, contents) in &self.files {
+            for text in [path, contents] {
+                for placeholder in placeholders(text, path)? {
+                    if self.variable(placeholder.name).is_none() {
+                        return Err(TemplateError::Undeclared {
+                            file: path.clone(),
+                            line: placeholder.line,
+                            name: placeholder.name.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Output paths and contents with placeholders filled from `values`,
+    /// falling back to variable defaults.
+    pub fn render(
+        &self,
+        values: &IndexMap<String, String>,
+    ) -> Result<IndexMap<String, String>, TemplateError> {
+        let mut resolved = IndexMap::new();
+        for variable in &self.variables {
+            let value = values
+                .get(&variable.name)
+                .cloned()
+                .or_else(|| variable.default_text())
+                .ok_or_else(|| TemplateError::MissingValue(variable.name.clone()))?;
+            variable.check_value(&value)?;
+            resolved.insert(variable.name.as_str(), value);
+        }
+        let fill = |text: &str, file: &str| -> Result<String, TemplateError> {
+            let mut out = String::with_capacity(text.len());
+            let mut last = 0;
+            for placeholder in placeholders(text, file)? {
+                let value =
+                    resolved
+                        .get(placeholder.name)
+                        .ok_or_else(|| TemplateError::Undeclared {
+                            file: file.to_string(),
+                            line: placeholder.line,
+                            name: placeholder.name.to_string(),
+                        })?;
+                out.push_str(&text[last..placeholder.span.start]);
+                out.push_str(value);
+                last = placeholder.span.end;
+            }
+            out.push_str(&text[last..]);
+            Ok(out)
+        };
+        self.files
+            .iter()
+            .map(|(path, contents)| Ok((fill(path, path)?, fill(contents, path)?)))
+            .collect()
+    }
+
+    /// Form fields for the template's variables, in declaration order.
+    pub fn form(&self) -> Vec<VariableField> {
+        self.variables.iter().map(VariableField::from).collect()
+    }
+}
+
+struct Placeholder<'a> {
+    name: &'a str,
+    span: std::ops::Range<usize>,
+    line: usize,
+}
+
+/// `{{ name }}` placeholders in `text`, in order.
+fn placeholders<'a>(text: &'a str, file: &str) -> Result<Vec<Placeholder<'a>>, TemplateError> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = text[from..].find("{{") {
+        let start = from + offset;
+        let line = text[..start].matches('\n').count() + 1;
+        let Some(len) = text[start + 2..].find("}}") else {
+            return Err(TemplateError::Unclosed {
+                file: file.to_string(),
+                line,
+            });
+        };
+        let end = start + 2 + len + 2;
+        let name = text[start + 2..end - 2].trim();
+        if name.is_empty() {
+            return Err(TemplateError::EmptyPlaceholder {
+                file: file.to_string(),
+                line,
+            });
+        }
+        found.push(Placeholder {
+            name,
+            span: start..end,
+            line,
+        });
+        from = end;
+    }
+    Ok(found)
+}
+
+/// Input widget of a variable field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldInput {
+    Text,
+    Number,
+    Checkbox,
+    Select(Vec<String>),
+}
+
+/// One field of a scaffold form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableField {
+    pub name: String,
+    pub label: String,
+    pub input: FieldInput,
+    /// Initial value: the default, or empty.
+    pub value: String,
+    /// No default, so the user must fill it in.
+    pub required: bool,
+    pub help: Option<String>,
+}
+
+impl From<&TemplateVariable> for VariableField {
+    fn from(variable: &TemplateVariable) -> Self {
+        let input = match variable.ty {
+            VariableType::String => FieldInput::Text,
+            VariableType::Number => FieldInput::Number,
+            VariableType::Bool => FieldInput::Checkbox,
+            VariableType::Choice => FieldInput::Select(variable.options.clone()),
+        };
+        let default = variable.default_text();
+        Self {
+            name: variable.name.clone(),
+            label: field_label(&variable.name),
+            input,
+            required: default.is_none(),
+            value: default.unwrap_or_default(),
+            help: variable.description.clone(),
+        }
+    }
+}
+
+/// `component_name` and `component-name` become "Component name".
+fn field_label(name: &str) -> String {
+    let words = name.replace(['_', '-'], " ");
+    let mut chars = words.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+const COMPONENT_RSX: &str = r#"//! {{ name }} component, scaffolded from the {{ type }} type.
+
+use rsc::prelude::*;
+
+{{ props }}component {{ name }}{{ params }} {
+    render {
+        <{{ tag }} class="{{ class }}" data-testid="{{ class }}"{{ attrs }}{{ body }}
+    }
+}
+"#;
+
+const COMPONENT_TEST_RSX: &str = r#"//! Tests for {{ name }}.
+
+use rsc_test::prelude::*;
+
+/// Tests that {{ name }} renders.
+#[test]
+fn {{ test_name }}() {
+    let view = render(<{{ name }}{{ usage }} />);
+    assert!(view.query("[data-testid='{{ class }}']").exists(), "{{ name }} should render");
+}
+"#;
+
+const COMPONENT_STORY_RSX: &str = r#"//! Stories for {{ name }}.
+
+use rsc::prelude::*;
+
+story Default {
+    render {
+        <{{ name }}{{ usage }} />
+    }
+}
+"#;
+
+/// Built-in scaffolding templates. `component` and `component-story` are
+/// the ones [`ComponentScaffold`](super::component::ComponentScaffold)
+/// renders; it fills every variable from the component type.
+pub fn builtin_scaffolds() -> Vec<ScaffoldTemplate> {
+    let name =
+        || TemplateVariable::new("name", VariableType::String).with_description("PascalCase name");
+    let class = || {
+        TemplateVariable::new("class", VariableType::String)
+            .with_description("kebab-case file stem and CSS class")
+    };
+    let directory =
+        || TemplateVariable::new("directory", VariableType::String).with_default("components");
+    let usage = || {
+        TemplateVariable::new("usage", VariableType::String)
+            .with_default("")
+            .with_description("Props set where the component is used")
+    };
+    let text = |name: &str, default: &str, description: &str| {
+        TemplateVariable::new(name, VariableType::String)
+            .with_default(default)
+            .with_description(description)
+    };
+    vec![
+        ScaffoldTemplate::new("component", TargetKind::Component)
+            .with_description("RSX component with a test")
+            .with_variable(name())
+            .with_variable(class())
+            .with_variable(directory())
+            .with_variable(
+                TemplateVariable::new("type", VariableType::Choice)
+                    .with_options(ComponentType::all().iter().map(ComponentType::label))
+                    .with_default("Button"),
+            )
+            .with_variable(text("props", "", "Props struct declaration"))
+            .with_variable(text("params", "", "Component parameters"))
+            .with_variable(text("tag", "div", "Root element"))
+            .with_variable(text("attrs", "", "Extra root attributes"))
+            .with_variable(text(
+                "body",
+                ">\n        </div>",
+                "Root element content and closing tag",
+            ))
+            .with_variable(usage())
+            .with_variable(text("test_name", "renders", "Test function name"))
+            .with_file("{{ directory }}/{{ class }}.rsx", COMPONENT_RSX)
+            .with_file("{{ directory }}/{{ class }}.test.rsx", COMPONENT_TEST_RSX),
+        ScaffoldTemplate::new("component-story", TargetKind::Component)
+            .with_description("Story for the component catalog")
+            .with_variable(name())
+            .with_variable(class())
+            .with_variable(directory())
+            .with_variable(usage())
+            .with_file("{{ directory }}/{{ class }}.story.rsx", COMPONENT_STORY_RSX),
+        ScaffoldTemplate::new("screen", TargetKind::Screen)
+            .with_description("Screen with a route")
+            .with_variable(name())
+            .with_variable(TemplateVariable::new("route", VariableType::String).with_default("/"))
+            .with_file(
+                "screens/{{ name }}.rs",
+                "/// Route: {{ route }}\npub fn {{ name }}Screen() {}\n",
+            ),
+        ScaffoldTemplate::new("workflow", TargetKind::Workflow)
+            .with_description("Workflow with one context")
+            .with_variable(name())
+            .with_file(
+                "workflows/{{ name }}.yaml",
+                "name: {{ name }}\ncontexts:\n  - name: Start\n",
+            ),
+    ]
+}
+
+/// Read and check one manifest.
+pub fn load_template(path: &Path) -> Result<ScaffoldTemplate, TemplateLoadError> {
+    let text = std::fs::read_to_string(path)?;
+    let is_json = path.extension().is_some_and(|e| e == "json");
+    let mut template: ScaffoldTemplate = if is_json {
+        serde_json::from_str(&text).map_err(|e| TemplateLoadError::Parse(e.to_string()))?
+    } else {
+        serde_yaml::from_str(&text).map_err(|e| TemplateLoadError::Parse(e.to_string()))?
+    };
+    template.validate()?;
+    template.source = TemplateSource::User(path.to_path_buf());
+    Ok(template)
+}
+
+fn is_manifest(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|e| e == "json" || e == "yaml" || e == "yml")
+}
+
+/// Scaffolding templates: built-ins merged with the user templates of a
+/// directory.
+#[derive(Debug)]
+pub struct TemplateLibrary {
+    dir: PathBuf,
+    templates: Vec<ScaffoldTemplate>,
+    errors: Vec<TemplateFileError>,
+    warnings: Vec<ShadowWarning>,
+}
+
+impl TemplateLibrary {
+    /// Scan `dir` for user templates. A missing directory just means none.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let mut library = Self {
+            dir: dir.into(),
+            templates: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+        library.rescan();
+        library
+    }
+
+    /// Library of the configured templates directory of a project.
+    pub fn for_project(project_dir: &Path, config: &ScaffoldConfig) -> Self {
+        Self::new(project_dir.join(&config.templates_dir))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Merged templates: built-ins in order, user templates replacing
+    /// same-named ones in place, then the remaining user templates.
+    pub fn templates(&self) -> &[ScaffoldTemplate] {
+        &self.templates
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ScaffoldTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Manifests skipped by the last scan.
+    pub fn errors(&self) -> &[TemplateFileError] {
+        &self.errors
+    }
+
+    /// Built-ins shadowed in the last scan.
+    pub fn warnings(&self) -> &[ShadowWarning] {
+        &self.warnings
+    }
+
+    /// Read the directory again. Manifests are loaded in file name order;
+    /// of two with the same name the first wins.
+    pub fn rescan(&mut self) {
+        self.templates = builtin_scaffolds();
+        self.errors.clear();
+        self.warnings.clear();
+
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|e| Some(e.ok()?.path()))
+                .filter(|p| is_manifest(p))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+
+        let mut user: IndexMap<String, ScaffoldTemplate> = IndexMap::new();
+        for path in paths {
+            let loaded = load_template(&path).and_then(|template| match user.get(&template.name) {
+                Some(first) => Err(TemplateLoadError::Duplicate {
+                    name: template.name,
+                    other: first.path().map(Path::to_path_buf).unwrap_or_default(),
+                }),
+                None => Ok(template),
+            });
+            match loaded {
+                Ok(template) => {
+                    user.insert(template.name.clone(), template);
+                }
+                Err(error) => self.errors.push(TemplateFileError { path, error }),
+            }
+        }
+
+        for builtin in &mut self.templates {
+            if let Some(template) = user.shift_remove(&builtin.name) {
+                self.warnings.push(ShadowWarning {
+                    name: template.name.clone(),
+                    path: template.path().map(Path::to_path_buf).unwrap_or_default(),
+                });
+                *builtin = template;
+            }
+        }
+        self.templates.extend(user.into_values());
+    }
+
+    /// A polling notifier for the templates directory, for use with
+    /// [`reload_if_changed`](Self::reload_if_changed).
+    pub fn notifier(&self, interval: Duration) -> PollingNotifier {
+        PollingNotifier::new(vec![self.dir.clone()], interval)
+    }
+
+    /// Rescan if `notifier` has pending changes, without waiting.
+    /// Returns whether it rescanned.
+    pub fn reload_if_changed(&mut self, notifier: &mut dyn ChangeNotifier) -> bool {
+        let mut changed = false;
+        while let WaitResult::Changed(_) = notifier.wait(Some(Duration::ZERO)) {
+            changed = true;
+        }
+        if changed {
+            self.rescan();
+        }
+        changed
+    }
+
+    /// Picker entries, optionally only those for one target kind.
+    pub fn picker(&self, target: Option<TargetKind>) -> Vec<ScaffoldPickerItem> {
+        self.templates
+            .iter()
+            .filter(|t| target.is_none_or(|target| t.target == target))
+            .map(|t| ScaffoldPickerItem {
+                name: t.name.clone(),
+                description: t.description.clone(),
+                target: t.target,
+                source: t.source.clone(),
+                fields: t.form(),
+            })
+            .collect()
+    }
+}
+
+/// One entry of the scaffold picker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaffoldPickerItem {
+    pub name: String,
+    pub description: String,
+    pub target: TargetKind,
+    pub source: TemplateSource,
+    pub fields: Vec<VariableField>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rsc-templates-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            std::fs::write(dir.join(file), contents).unwrap();
+        }
+        dir
+    }
+
+    const CARD: &str = r#"{
+        "name": "card",
+        "description": "Card component",
+        "target": "component",
+        "variables": [{"name": "name"}],
+        "files": {"components/{{ name }}.rs": "pub fn {{name}}() {}"}
+    }"#;
+
+    #[test]
+    fn test_user_templates_shadow_builtins() {
+        let dir = templates_dir(
+            "shadow",
+            &[
+                ("card.json", CARD),
+                (
+                    "component.yaml",
+                    "name: component\ntarget: component\nvariables:\n  - name: name\nfiles:\n  \"src/{{ name }}.rs\": \"// {{ name }}\"\n",
+                ),
+                ("README.md", "not a manifest"),
+            ],
+        );
+        let library = TemplateLibrary::new(&dir);
+
+        let names: Vec<_> = library
+            .templates()
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["component", "component-story", "screen", "workflow", "card"]
+        );
+        assert!(library.errors().is_empty());
+        assert_eq!(library.warnings().len(), 1);
+        assert_eq!(library.warnings()[0].name, "component");
+        assert!(library.get("component").unwrap().is_user());
+        assert!(!library.get("screen").unwrap().is_user());
+
+        let values = IndexMap::from([("name".to_string(), "Button".to_string())]);
+        let files = library.get("component").unwrap().render(&values).unwrap();
+        assert_eq!(
+            files.get("src/Button.rs").map(String::as_str),
+            Some("// Button")
+        );
+
+        let components: Vec<_> = library
+            .picker(Some(TargetKind::Component))
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+        assert_eq!(components, ["component", "component-story", "card"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_broken_templates_are_reported_and_skipped() {
+        let dir = templates_dir(
+            "broken",
+            &[
+                ("card.json", CARD),
+                (
+                    "unclosed.json",
+                    r#"{"name": "a", "target": "screen", "files": {"a.rs": "x\n{{ name"}}"#,
+                ),
+                (
+                    "undeclared.json",
+                    r#"{"name": "b", "target": "screen", "files": {"b.rs": "{{ title }}"}}"#,
+                ),
+                ("garbage.yaml", "name: [unterminated"),
+            ],
+        );
+        let mut library = TemplateLibrary::new(&dir);
+
+        assert!(library.get("card").is_some());
+        assert_eq!(library.templates().len(), 5);
+        let errors: Vec<_> = library
+            .errors()
+            .iter()
+            .map(|e| {
+                let file = e.path.file_name().unwrap().to_str().unwrap();
+                (file, e.error.to_string())
+            })
+            .collect();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], ("garbage.yaml", ref m) if m.starts_with("Parse error")));
+        assert_eq!(
+            errors[1],
+            ("unclosed.json", "a.rs:2: unclosed placeholder".to_string())
+        );
+        assert_eq!(
+            errors[2],
+            (
+                "undeclared.json",
+                "b.rs:1: undeclared variable `title`".to_string()
+            )
+        );
+
+        // Fixing a file and signalling a change picks it up.
+        std::fs::write(
+            dir.join("undeclared.json"),
+            r#"{"name": "b", "target": "screen", "variables": [{"name": "title", "default": "Home"}], "files": {"b.rs": "{{ title }}"}}"#,
+        )
+        .unwrap();
+        struct Once(bool);
+        impl ChangeNotifier for Once {
+            fn wait(&mut self, _timeout: Option<Duration>) -> WaitResult {
+                if std::mem::replace(&mut self.0, false) {
+                    WaitResult::Changed(PathBuf::from("undeclared.json"))
+                } else {
+                    WaitResult::Timeout
+                }
+            }
+        }
+        assert!(library.reload_if_changed(&mut Once(true)));
+        assert!(library.get("b").is_some());
+        assert_eq!(library.errors().len(), 2);
+        assert!(!library.reload_if_changed(&mut Once(false)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_variable_form_derivation() {
+        let template = ScaffoldTemplate::new("page", TargetKind::Screen)
+            .with_variable(TemplateVariable::new("page_name", VariableType::String))
+            .with_variable(TemplateVariable::new("columns", VariableType::Number).with_default(2))
+            .with_variable(
+                TemplateVariable::new("has-header", VariableType::Bool)
+                    .with_default(true)
+                    .with_description("Render a header bar"),
+            )
+            .with_variable(
+                TemplateVariable::new("layout", VariableType::Choice)
+                    .with_options(["grid", "list"])
+                    .with_default("grid"),
+            )
+            .with_file(
+                "{{ page_name }}.rs",
+                "{{ layout }} {{ columns }} {{ has-header }}",
+            );
+        assert_eq!(template.validate(), Ok(()));
+
+        let fields = template.form();
+        let summary: Vec<_> = fields
+            .iter()
+            .map(|f| (f.label.as_str(), &f.input, f.value.as_str(), f.required))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Page name", &FieldInput::Text, "", true),
+                ("Columns", &FieldInput::Number, "2", false),
+                ("Has header", &FieldInput::Checkbox, "true", false),
+                (
+                    "Layout",
+                    &FieldInput::Select(vec!["grid".into(), "list".into()]),
+                    "grid",
+                    false
+                ),
+            ]
+        );
+        assert_eq!(fields[2].help.as_deref(), Some("Render a header bar"));
+
+        assert_eq!(
+            template.render(&IndexMap::new()),
+            Err(TemplateError::MissingValue("page_name".into()))
+        );
+        let values = IndexMap::from([
+            ("page_name".to_string(), "Home".to_string()),
+            ("layout".to_string(), "table".to_string()),
+        ]);
+        assert!(matches!(
+            template.render(&values),
+            Err(TemplateError::InvalidVariable { name, .. }) if name == "layout"
+        ));
+
+        let bad_default = ScaffoldTemplate::new("x", TargetKind::Screen)
+            .with_variable(TemplateVariable::new("n", VariableType::Number).with_default("two"))
+            .with_file("x.rs", "{{ n }}");
+        assert!(matches!(
+            bad_default.validate(),
+            Err(TemplateError::InvalidVariable { .. })
+        ));
+    }
+}
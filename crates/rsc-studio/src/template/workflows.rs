@@ -0,0 +1,471 @@
+//! Workflow templates for the Add Workflow gallery.
+//!
+//! A [`WorkflowTemplate`] is data: contexts keyed by a short name and the
+//! transitions between them. Instantiating one loads it into a scratch
+//! navigation canvas, lays it out, and gives every entity and transition a
+//! fresh id, so the result renders placed and can be created any number of
+//! times. Projects add their own templates to a [`WorkflowGallery`] from
+//! JSON definitions; one with a built-in's id replaces it.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::entity::{Context, END_STATE_KEY, Workflow};
+use crate::export::workflow::TransitionExport;
+use crate::export::{WorkflowExport, WorkflowImport};
+
+/// Gallery id of the empty workflow.
+pub const BLANK_TEMPLATE: &str = "blank";
+
+/// Why a workflow template was rejected.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum WorkflowTemplateError {
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    #[error("Template `{0}` has no contexts")]
+    NoContexts(String),
+
+    #[error("Context `{0}` is declared twice")]
+    DuplicateContext(String),
+
+    #[error("Unknown context `{0}`")]
+    UnknownContext(String),
+
+    #[error("Template id `{BLANK_TEMPLATE}` is reserved")]
+    Reserved,
+}
+
+/// A context of a template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateContext {
+    /// Name transitions refer to the context by.
+    pub key: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Whether the flow may stop here without a way out.
+    #[serde(default)]
+    pub end_state: bool,
+}
+
+/// A transition between two contexts of a template, by key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateTransition {
+    pub source: String,
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A workflow template.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    pub contexts: Vec<TemplateContext>,
+    #[serde(default)]
+    pub transitions: Vec<TemplateTransition>,
+    /// Key of the start context; the first context when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+}
+
+impl WorkflowTemplate {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: String::new(),
+            icon: None,
+            contexts: Vec::new(),
+            transitions: Vec::new(),
+            start: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    pub fn with_context(mut self, key: impl Into<String>, name: impl Into<String>) -> Self {
+        self.contexts.push(TemplateContext {
+            key: key.into(),
+            name: name.into(),
+            description: None,
+            icon: None,
+            end_state: false,
+        });
+        self
+    }
+
+    /// Add a context the flow may stop at.
+    pub fn with_end_context(self, key: impl Into<String>, name: impl Into<String>) -> Self {
+        let mut template = self.with_context(key, name);
+        if let Some(context) = template.contexts.last_mut() {
+            context.end_state = true;
+        }
+        template
+    }
+
+    pub fn with_transition(
+        mut self,
+        source: impl Into<String>,
+        target: impl Into<String>,
+        label: impl Into<String>,
+    ) -> Self {
+        self.transitions.push(TemplateTransition {
+            source: source.into(),
+            target: target.into(),
+            label: Some(label.into()),
+        });
+        self
+    }
+
+    /// The built-in templates.
+    pub fn all() -> Vec<WorkflowTemplate> {
+        vec![auth_flow(), onboarding_wizard(), crud_master_detail()]
+    }
+
+    /// Read a template definition.
+    pub fn from_json(json: &str) -> Result<Self, WorkflowTemplateError> {
+        let template: Self =
+            serde_json::from_str(json).map_err(|e| WorkflowTemplateError::Parse(e.to_string()))?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Check that context keys are unique and that the start and every
+    /// transition name a context.
+    pub fn validate(&self) -> Result<(), WorkflowTemplateError> {
+        if self.id == BLANK_TEMPLATE {
+            return Err(WorkflowTemplateError::Reserved);
+        }
+        if self.contexts.is_empty() {
+            return Err(WorkflowTemplateError::NoContexts(self.id.clone()));
+        }
+        for (i, context) in self.contexts.iter().enumerate() {
+            if self.contexts[..i].iter().any(|c| c.key == context.key) {
+                return Err(WorkflowTemplateError::DuplicateContext(context.key.clone()));
+            }
+        }
+        let ends = self
+            .transitions
+            .iter()
+            .flat_map(|t| [&t.source, &t.target])
+            .chain(&self.start);
+        for key in ends {
+            if !self.contexts.iter().any(|c| &c.key == key) {
+                return Err(WorkflowTemplateError::UnknownContext(key.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// A new workflow from the template, named `name_override` or after
+    /// the template. Its transitions and positions come with
+    /// [`instantiate_with_canvas`](Self::instantiate_with_canvas).
+    pub fn instantiate(&self, name_override: Option<String>) -> Workflow {
+        self.instantiate_with_canvas(name_override).workflow
+    }
+
+    /// A new workflow from the template with its transitions and laid-out
+    /// positions, all with fresh ids. Load the workflow, then place it
+    /// with `NavigationDesigner::apply_import`.
+    pub fn instantiate_with_canvas(&self, name_override: Option<String>) -> WorkflowImport {
+        let mut workflow =
+            Workflow::new(name_override.unwrap_or_else(|| self.name.clone())).with_id(&self.id);
+        workflow.description = (!self.description.is_empty()).then(|| self.description.clone());
+        workflow.icon = self.icon.clone();
+        for template in &self.contexts {
+            let mut context = Context::new(&template.name).with_id(&template.key);
+            context.description = template.description.clone();
+            context.icon = template.icon.clone();
+            if template.end_state {
+                context
+                    .metadata
+                    .insert(END_STATE_KEY.to_string(), true.into());
+            }
+            workflow.add_context(context);
+        }
+        if let Some(start) = &self.start {
+            workflow.default_context = Some(start.clone());
+        }
+
+        let transitions: Vec<TransitionExport> = self
+            .transitions
+            .iter()
+            .enumerate()
+            .map(|(i, transition)| TransitionExport {
+                id: format!("t{}", i + 1),
+                source: transition.source.clone(),
+                target: transition.target.clone(),
+                label: transition.label.clone(),
+                layer: None,
+            })
+            .collect();
+        WorkflowExport::laid_out(workflow, &transitions).with_new_ids()
+    }
+}
+
+/// Login with registration and password recovery beside it.
+fn auth_flow() -> WorkflowTemplate {
+    WorkflowTemplate::new("auth-flow", "Auth flow")
+        .with_description("Sign in, sign up and password recovery")
+        .with_icon("lock")
+        .with_context("login", "Login")
+        .with_context("register", "Register")
+        .with_context("forgot-password", "Forgot password")
+        .with_end_context("signed-in", "Signed in")
+        .with_transition("login", "signed-in", "Sign in")
+        .with_transition("login", "register", "Create account")
+        .with_transition("login", "forgot-password", "Forgot password")
+        .with_transition("register", "signed-in", "Sign up")
+        .with_transition("register", "login", "Back")
+        .with_transition("forgot-password", "login", "Back")
+}
+
+/// Linear steps from a welcome screen to a finish.
+fn onboarding_wizard() -> WorkflowTemplate {
+    WorkflowTemplate::new("onboarding-wizard", "Onboarding wizard")
+        .with_description("Step-by-step setup for new users")
+        .with_icon("compass")
+        .with_context("welcome", "Welcome")
+        .with_context("profile", "Profile")
+        .with_context("preferences", "Preferences")
+        .with_end_context("done", "Done")
+        .with_transition("welcome", "profile", "Start")
+        .with_transition("profile", "preferences", "Next")
+        .with_transition("profile", "welcome", "Back")
+        .with_transition("preferences", "done", "Finish")
+        .with_transition("preferences", "profile", "Back")
+}
+
+/// A list with detail and edit screens.
+fn crud_master_detail() -> WorkflowTemplate {
+    WorkflowTemplate::new("crud-master-detail", "CRUD master-detail")
+        .with_description("List, view, create and edit records")
+        .with_icon("table")
+        .with_context("list", "List")
+        .with_context("detail", "Detail")
+        .with_context("edit", "Edit")
+        .with_context("create", "Create")
+        .with_transition("list", "detail", "Open")
+        .with_transition("list", "create", "New")
+        .with_transition("detail", "edit", "Edit")
+        .with_transition("detail", "list", "Back")
+        .with_transition("edit", "detail", "Save")
+        .with_transition("create", "list", "Save")
+}
+
+/// One entry of the Add Workflow gallery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GalleryEntry {
+    /// Template id, or [`BLANK_TEMPLATE`].
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub icon: Option<String>,
+}
+
+/// The templates offered when adding a workflow: the built-ins and any a
+/// project registered.
+#[derive(Debug, Clone)]
+pub struct WorkflowGallery {
+    templates: Vec<WorkflowTemplate>,
+}
+
+impl Default for WorkflowGallery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkflowGallery {
+    /// A gallery of the built-ins.
+    pub fn new() -> Self {
+        Self {
+            templates: WorkflowTemplate::all(),
+        }
+    }
+
+    pub fn templates(&self) -> &[WorkflowTemplate] {
+        &self.templates
+    }
+
+    pub fn get(&self, id: &str) -> Option<&WorkflowTemplate> {
+        self.templates.iter().find(|t| t.id == id)
+    }
+
+    /// Add a template, replacing the one with its id.
+    pub fn register(&mut self, template: WorkflowTemplate) -> Result<(), WorkflowTemplateError> {
+        template.validate()?;
+        match self.templates.iter_mut().find(|t| t.id == template.id) {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+        Ok(())
+    }
+
+    /// Add a template from its JSON definition.
+    pub fn register_json(&mut self, json: &str) -> Result<(), WorkflowTemplateError> {
+        self.register(WorkflowTemplate::from_json(json)?)
+    }
+
+    /// Gallery entries, "Blank" first.
+    pub fn entries(&self) -> Vec<GalleryEntry> {
+        let blank = GalleryEntry {
+            id: BLANK_TEMPLATE.to_string(),
+            name: "Blank".to_string(),
+            description: "An empty workflow".to_string(),
+            icon: None,
+        };
+        std::iter::once(blank)
+            .chain(self.templates.iter().map(|t| GalleryEntry {
+                id: t.id.clone(),
+                name: t.name.clone(),
+                description: t.description.clone(),
+                icon: t.icon.clone(),
+            }))
+            .collect()
+    }
+
+    /// Create the workflow an entry stands for. The blank one has no
+    /// contexts; `None` if there is no such entry.
+    pub fn create(&self, id: &str, name: Option<String>) -> Option<WorkflowImport> {
+        if id == BLANK_TEMPLATE {
+            let workflow = Workflow::new(name.unwrap_or_else(|| "Untitled".to_string()));
+            return Some(workflow.to_export().with_new_ids());
+        }
+        Some(self.get(id)?.instantiate_with_canvas(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::NavigationDesigner;
+
+    #[test]
+    fn test_instances_get_fresh_ids() {
+        let template = &WorkflowTemplate::all()[0];
+        let first = template.instantiate_with_canvas(None);
+        let second = template.instantiate_with_canvas(Some("Sign-in".to_string()));
+        assert_eq!(first.workflow.name, "Auth flow");
+        assert_eq!(second.workflow.name, "Sign-in");
+        assert_ne!(first.workflow.id, second.workflow.id);
+        for id in first.workflow.contexts.keys() {
+            assert!(!second.workflow.contexts.contains_key(id));
+        }
+        let names: Vec<&str> = first
+            .workflow
+            .contexts
+            .values()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, ["Login", "Register", "Forgot password", "Signed in"]);
+        // The hierarchy edges come along with the six transitions.
+        let labels = first.transitions.iter().filter(|t| t.label.is_some());
+        assert_eq!(labels.count(), 6);
+        assert_ne!(first.transitions[0].id, second.transitions[0].id);
+    }
+
+    #[test]
+    fn test_builtins_validate_and_are_laid_out() {
+        for template in WorkflowTemplate::all() {
+            let import = template.instantiate_with_canvas(None);
+            let mut designer = NavigationDesigner::new();
+            designer.load_workflows(&[&import.workflow]);
+            designer.apply_import(&import);
+            assert_eq!(
+                import.workflow.validate(&designer.canvas),
+                [],
+                "{}",
+                template.id
+            );
+
+            // Every context has its own spot.
+            let mut spots: Vec<(i64, i64)> = import
+                .workflow
+                .contexts
+                .keys()
+                .map(|id| {
+                    let p = import.positions[id];
+                    (p.x as i64, p.y as i64)
+                })
+                .collect();
+            spots.sort();
+            spots.dedup();
+            assert_eq!(spots.len(), import.workflow.contexts.len());
+        }
+    }
+
+    #[test]
+    fn test_register_custom_templates() {
+        let mut gallery = WorkflowGallery::new();
+        gallery
+            .register_json(
+                r#"{
+                    "id": "checkout",
+                    "name": "Checkout",
+                    "contexts": [
+                        { "key": "cart", "name": "Cart" },
+                        { "key": "paid", "name": "Paid", "end_state": true }
+                    ],
+                    "transitions": [{ "source": "cart", "target": "paid", "label": "Pay" }]
+                }"#,
+            )
+            .unwrap();
+        let ids: Vec<String> = gallery.entries().into_iter().map(|e| e.id).collect();
+        assert_eq!(
+            ids,
+            [
+                "blank",
+                "auth-flow",
+                "onboarding-wizard",
+                "crud-master-detail",
+                "checkout"
+            ]
+        );
+        let import = gallery.create("checkout", None).unwrap();
+        let pay = import
+            .transitions
+            .iter()
+            .find(|t| t.label.is_some())
+            .unwrap();
+        assert_eq!(pay.target, import.workflow.contexts[1].id);
+        assert_eq!(pay.label.as_deref(), Some("Pay"));
+        assert!(
+            gallery
+                .create("blank", None)
+                .unwrap()
+                .workflow
+                .contexts
+                .is_empty()
+        );
+        assert!(gallery.create("missing", None).is_none());
+
+        let broken = r#"{ "id": "x", "name": "X", "contexts": [{ "key": "a", "name": "A" }],
+            "transitions": [{ "source": "a", "target": "b" }] }"#;
+        assert_eq!(
+            gallery.register_json(broken),
+            Err(WorkflowTemplateError::UnknownContext("b".to_string()))
+        );
+        assert_eq!(
+            gallery.register(WorkflowTemplate::new("blank", "Mine")),
+            Err(WorkflowTemplateError::Reserved)
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Created/modified tracking for persisted entities.
+//!
+//! Workflows, contexts, presets, component styles and tokens carry
+//! [`Timestamps`]. The store stamps the entities touched by each change
+//! before applying it, so history entries hold the stamped values and
+//! undo restores the previous ones.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::designer::css::{ComponentStyle, DesignTokens};
+use crate::entity::{Context, Workflow};
+use crate::store::StoreChange;
+
+/// Creation and modification times (Unix seconds).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timestamps {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<u64>,
+    /// Who made the last change, from the studio config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+}
+
+impl Timestamps {
+    pub fn is_empty(&self) -> bool {
+        self.created_at.is_none() && self.modified_at.is_none() && self.modified_by.is_none()
+    }
+
+    /// Record a modification, setting `created_at` if unset.
+    pub fn touch(&mut self, now: u64, by: Option<&str>) {
+        self.created_at.get_or_insert(now);
+        self.modified_at = Some(now);
+        self.modified_by = by.map(str::to_string);
+    }
+
+    /// Fill missing times, e.g. from a legacy file's mtime.
+    pub fn backfill(&mut self, at: u64) {
+        self.created_at.get_or_insert(at);
+        self.modified_at.get_or_insert(at);
+    }
+}
+
+/// Time source for the store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Clock {
+    #[default]
+    System,
+    /// Fixed time, for tests and replays.
+    Fixed(u64),
+}
+
+impl Clock {
+    pub fn now(&self) -> u64 {
+        match self {
+            Clock::System => unix_time(SystemTime::now()),
+            Clock::Fixed(t) => *t,
+        }
+    }
+}
+
+/// Seconds since the Unix epoch; times before it map to 0.
+pub fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether two values differ, ignoring their timestamps.
+fn differs<T: Serialize>(a: &T, b: &T) -> bool {
+    let strip = |v: &T| {
+        let mut value = serde_json::to_value(v).unwrap_or_default();
+        if let Some(obj) = value.as_object_mut() {
+            for key in ["created_at", "modified_at", "modified_by"] {
+                obj.remove(key);
+            }
+        }
+        value
+    };
+    strip(a) != strip(b)
+}
+
+/// Carry `before`'s timestamps over, touching them if the entity changed.
+fn stamp_entity<T: Serialize>(
+    before: Option<&T>,
+    after: &mut T,
+    timestamps: impl Fn(&mut T) -> &mut Timestamps,
+    before_timestamps: impl Fn(&T) -> &Timestamps,
+    now: u64,
+    by: Option<&str>,
+) {
+    match before {
+        Some(before) if !differs(before, after) => {
+            *timestamps(after) = before_timestamps(before).clone();
+        }
+        Some(before) => {
+            let created = before_timestamps(before).created_at;
+            let ts = timestamps(after);
+            ts.created_at = created.or(ts.created_at);
+            ts.touch(now, by);
+        }
+        None => timestamps(after).touch(now, by),
+    }
+}
+
+fn stamp_workflow(before: Option<&Workflow>, after: &mut Workflow, now: u64, by: Option<&str>) {
+    for context in after.contexts.values_mut() {
+        let before_context = before.and_then(|w| w.contexts.get(&context.id));
+        stamp_context(before_context, context, now, by);
+    }
+    stamp_entity(
+        before,
+        after,
+        |w| &mut w.timestamps,
+        |w| &w.timestamps,
+        now,
+        by,
+    );
+}
+
+fn stamp_context(before: Option<&Context>, after: &mut Context, now: u64, by: Option<&str>) {
+    for preset in after.presets.values_mut() {
+        let before_preset = before.and_then(|c| c.presets.get(&preset.id));
+        stamp_entity(
+            before_preset,
+            preset,
+            |p| &mut p.timestamps,
+            |p| &p.timestamps,
+            now,
+            by,
+        );
+    }
+    stamp_entity(
+        before,
+        after,
+        |c| &mut c.timestamps,
+        |c| &c.timestamps,
+        now,
+        by,
+    );
+}
+
+fn stamp_tokens(before: &DesignTokens, after: &mut DesignTokens, now: u64, by: Option<&str>) {
+    let mut metadata = IndexMap::new();
+    for ((prefix, old), (_, new)) in before.categories().into_iter().zip(after.categories()) {
+        for (name, value) in new {
+            let path = format!("{}.{}", prefix, name);
+            let mut ts = before.metadata.get(&path).cloned().unwrap_or_default();
+            if old.get(name) != Some(value) {
+                ts.touch(now, by);
+            }
+            metadata.insert(path, ts);
+        }
+    }
+    after.metadata = metadata;
+}
+
+impl StoreChange {
+    /// Stamp the entities this change touches with one time for the whole
+    /// change, batches included.
+    pub fn stamp(&mut self, now: u64, by: Option<&str>) {
+        match self {
+            StoreChange::Workflow {
+                before,
+                after: Some(after),
+                ..
+            } => stamp_workflow(before.as_deref(), after, now, by),
+            StoreChange::Tokens { before, after } => stamp_tokens(before, after, now, by),
+            StoreChange::ComponentStyle {
+                before,
+                after: Some(after),
+                ..
+            } => stamp_entity(
+                before.as_deref(),
+                after.as_mut(),
+                |s: &mut ComponentStyle| &mut s.timestamps,
+                |s| &s.timestamps,
+                now,
+                by,
+            ),
+            StoreChange::Flow {
+                entities: changes, ..
+            }
+            | StoreChange::Batch(changes) => {
+                for change in changes {
+                    change.stamp(now, by);
+                }
+            }
+            StoreChange::Workflow { after: None, .. }
+            | StoreChange::ComponentStyle { after: None, .. }
+            | StoreChange::ComponentStyleOrder { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::designer::css::TokenValue;
+    use crate::entity::Preset;
+    use crate::history::HistoryScope;
+    use crate::store::StudioStore;
+
+    fn store_at(now: u64) -> StudioStore {
+        let mut store = StudioStore::new();
+        store.clock = Clock::Fixed(now);
+        store.modified_by = Some("ana".to_string());
+        store
+    }
+
+    #[test]
+    fn test_workflow_actions_stamp_touched_entities() {
+        let mut store = store_at(100);
+        let mut workflow = Workflow::new("Checkout").with_id("w1");
+        let mut cart = Context::new("Cart").with_id("c1");
+        cart.add_preset(Preset::new("Compact").with_id("p1"));
+        workflow.add_context(cart);
+        workflow.add_context(Context::new("Payment").with_id("c2"));
+        store.add_workflow(workflow);
+
+        let w = store.get_workflow("w1").unwrap();
+        assert_eq!(w.timestamps.created_at, Some(100));
+        assert_eq!(
+            w.contexts["c1"].presets["p1"].timestamps.modified_at,
+            Some(100)
+        );
+        assert_eq!(w.timestamps.modified_by.as_deref(), Some("ana"));
+
+        // Edit one context: it and its workflow change, its sibling doesn't.
+        store.clock = Clock::Fixed(200);
+        let mut edited = store.get_workflow("w1").unwrap().clone();
+        edited.contexts.get_mut("c2").unwrap().description = Some("Card entry".to_string());
+        store.add_workflow(edited);
+
+        let w = store.get_workflow("w1").unwrap();
+        assert_eq!(w.timestamps.created_at, Some(100));
+        assert_eq!(w.timestamps.modified_at, Some(200));
+        assert_eq!(w.contexts["c2"].timestamps.modified_at, Some(200));
+        assert_eq!(w.contexts["c2"].timestamps.created_at, Some(100));
+        assert_eq!(w.contexts["c1"].timestamps.modified_at, Some(100));
+
+        // Undo restores the previous stamps.
+        assert!(store.undo_scope(HistoryScope::NavDesigner));
+        let w = store.get_workflow("w1").unwrap();
+        assert_eq!(w.timestamps.modified_at, Some(100));
+    }
+
+    #[test]
+    fn test_token_and_style_actions() {
+        let mut store = store_at(10);
+        let mut tokens = DesignTokens::default();
+        tokens.colors.insert(
+            "primary".to_string(),
+            TokenValue::Simple("#000".to_string()),
+        );
+        tokens
+            .colors
+            .insert("accent".to_string(), TokenValue::Simple("#f00".to_string()));
+        store.set_tokens(tokens);
+        store
+            .set_component_style("button", ComponentStyle::default())
+            .unwrap();
+
+        store.clock = Clock::Fixed(20);
+        let mut tokens = store.tokens.clone();
+        tokens
+            .colors
+            .insert("accent".to_string(), TokenValue::Simple("#0f0".to_string()));
+        store.set_tokens(tokens);
+
+        let meta = &store.tokens.metadata;
+        assert_eq!(meta["colors.primary"].modified_at, Some(10));
+        assert_eq!(meta["colors.accent"].modified_at, Some(20));
+        assert_eq!(meta["colors.accent"].created_at, Some(10));
+        let button = store.component_styles.get("button").unwrap();
+        assert_eq!(button.timestamps.created_at, Some(10));
+    }
+
+    #[test]
+    fn test_batch_uses_one_time() {
+        let mut store = store_at(50);
+        let change = StoreChange::Batch(
+            (0..3)
+                .map(|i| StoreChange::Workflow {
+                    id: format!("w{}", i),
+                    index: i,
+                    before: None,
+                    after: Some(Box::new(Workflow::new("W").with_id(format!("w{}", i)))),
+                })
+                .collect(),
+        );
+        store.commit(HistoryScope::NavDesigner, "Import workflows", change);
+
+        let stamps: Vec<Option<u64>> = store
+            .workflows
+            .values()
+            .map(|w| w.timestamps.modified_at)
+            .collect();
+        assert_eq!(stamps, [Some(50); 3]);
+    }
+}
@@ -0,0 +1,417 @@
+//! Watch mode: re-run project actions when project files change.
+//!
+//! Change detection goes through the [`ChangeNotifier`] trait so the loop
+//! can be driven by a real watcher, the built-in [`PollingNotifier`], or a
+//! scripted notifier in tests.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::designer::css::ValidationSeverity;
+use crate::export::export_to_yaml;
+use crate::project::ProjectFile;
+use crate::store::StudioStore;
+
+/// Default quiet period before a burst of saves triggers a run.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Result of waiting for a change.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WaitResult {
+    /// A watched path changed.
+    Changed(PathBuf),
+    /// The timeout elapsed without changes.
+    Timeout,
+    /// The notifier was shut down; the watch loop ends.
+    Closed,
+}
+
+/// Source of file change notifications.
+pub trait ChangeNotifier {
+    /// Wait for the next change. `None` waits indefinitely.
+    fn wait(&mut self, timeout: Option<Duration>) -> WaitResult;
+}
+
+/// Action re-run after each change.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchAction {
+    /// Parse the project and report token errors.
+    Validate,
+    /// Report token warnings and style lints.
+    Lint,
+    /// Write the workflows as YAML to a path.
+    ExportYaml(PathBuf),
+}
+
+/// Outcome of one action.
+#[derive(Debug, Clone)]
+pub struct ActionOutcome {
+    pub action: WatchAction,
+    /// Messages produced by the action, or the error that stopped it.
+    pub result: Result<Vec<String>, String>,
+    pub elapsed: Duration,
+}
+
+/// Report for one watch run.
+#[derive(Debug, Clone)]
+pub struct WatchReport {
+    /// Run number, starting at 1.
+    pub run: usize,
+    /// Paths whose changes triggered the run.
+    pub changed: Vec<PathBuf>,
+    pub outcomes: Vec<ActionOutcome>,
+    pub elapsed: Duration,
+}
+
+/// Watch options.
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Quiet period that ends a burst of changes.
+    pub debounce: Duration,
+    /// Run once before waiting for changes.
+    pub run_on_start: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: DEFAULT_DEBOUNCE,
+            run_on_start: false,
+        }
+    }
+}
+
+/// Watch a project file and its split-workflow directory by polling.
+///
+/// Returns when the notifier closes; the polling notifier never does, so
+/// this runs until the process exits.
+pub fn watch_project(path: &Path, actions: &[WatchAction], callback: impl FnMut(&WatchReport)) {
+    let mut paths = vec![path.to_path_buf()];
+    let workflows_dir = path.with_extension("workflows");
+    if workflows_dir.is_dir() {
+        paths.push(workflows_dir);
+    }
+    let mut notifier = PollingNotifier::new(paths, Duration::from_millis(100));
+    watch_with(
+        &mut notifier,
+        path,
+        actions,
+        &WatchOptions::default(),
+        callback,
+    );
+}
+
+/// Run the watch loop with a given notifier.
+///
+/// Rapid changes are debounced into one run. Changes that arrive while a
+/// run is in progress queue exactly one follow-up run.
+pub fn watch_with(
+    notifier: &mut dyn ChangeNotifier,
+    path: &Path,
+    actions: &[WatchAction],
+    options: &WatchOptions,
+    mut callback: impl FnMut(&WatchReport),
+) {
+    let mut run = 0;
+    let mut pending: Vec<PathBuf> = Vec::new();
+
+    if options.run_on_start {
+        run += 1;
+        callback(&run_actions(run, Vec::new(), path, actions));
+    }
+
+    loop {
+        if pending.is_empty() {
+            match notifier.wait(None) {
+                WaitResult::Changed(p) => pending.push(p),
+                WaitResult::Timeout => continue,
+                WaitResult::Closed => return,
+            }
+        }
+
+        // Debounce: absorb changes until the notifier goes quiet.
+        let mut closed = false;
+        loop {
+            match notifier.wait(Some(options.debounce)) {
+                WaitResult::Changed(p) => pending.push(p),
+                WaitResult::Timeout => break,
+                WaitResult::Closed => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        run += 1;
+        let changed = dedup(std::mem::take(&mut pending));
+        callback(&run_actions(run, changed, path, actions));
+        if closed {
+            return;
+        }
+
+        // Changes made during the run queue one follow-up run.
+        loop {
+            match notifier.wait(Some(Duration::ZERO)) {
+                WaitResult::Changed(p) => pending.push(p),
+                WaitResult::Timeout => break,
+                WaitResult::Closed => return,
+            }
+        }
+    }
+}
+
+fn dedup(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = std::collections::HashSet::new();
+    paths.retain(|p| seen.insert(p.clone()));
+    paths
+}
+
+/// Run every action against the project at `path`.
+pub fn run_actions(
+    run: usize,
+    changed: Vec<PathBuf>,
+    path: &Path,
+    actions: &[WatchAction],
+) -> WatchReport {
+    let started = Instant::now();
+    let project = ProjectFile::read(path).map_err(|e| e.to_string());
+
+    let outcomes = actions
+        .iter()
+        .map(|action| {
+            let action_started = Instant::now();
+            let result = project
+                .as_ref()
+                .map_err(Clone::clone)
+                .and_then(|project| run_action(action, project));
+            ActionOutcome {
+                action: action.clone(),
+                result,
+                elapsed: action_started.elapsed(),
+            }
+        })
+        .collect();
+
+    WatchReport {
+        run,
+        changed,
+        outcomes,
+        elapsed: started.elapsed(),
+    }
+}
+
+fn run_action(action: &WatchAction, project: &ProjectFile) -> Result<Vec<String>, String> {
+    let token_messages = |severity: ValidationSeverity| {
+        project
+            .tokens
+            .validate()
+            .into_iter()
+            .filter(|e| e.severity == severity)
+            .map(|e| format!("{}: {}", e.path, e.message))
+            .collect::<Vec<_>>()
+    };
+
+    match action {
+        WatchAction::Validate => Ok(token_messages(ValidationSeverity::Error)),
+        WatchAction::Lint => {
+            let mut messages = token_messages(ValidationSeverity::Warning);
+            for (name, style) in &project.component_styles.styles {
+                let specs = std::iter::once(&style.base)
+                    .chain(style.states.values())
+                    .chain(style.breakpoints.values())
+                    .filter_map(|p| p.transitions.as_ref());
+                for spec in specs {
+                    for lint in spec.lint() {
+                        messages.push(format!("{}.{}: {}", name, lint.path, lint.message));
+                    }
+                }
+            }
+            Ok(messages)
+        }
+        WatchAction::ExportYaml(out) => {
+            let mut store = StudioStore::new();
+            project.clone().load_into(&mut store);
+            let yaml = export_to_yaml(&store).map_err(|e| e.to_string())?;
+            std::fs::write(out, yaml).map_err(|e| e.to_string())?;
+            Ok(vec![format!("Exported {}", out.display())])
+        }
+    }
+}
+
+/// Notifier that polls modification times.
+///
+/// Directories are compared by the newest modification time of their entries.
+#[derive(Debug)]
+pub struct PollingNotifier {
+    paths: Vec<(PathBuf, Option<SystemTime>)>,
+    interval: Duration,
+}
+
+impl PollingNotifier {
+    pub fn new(paths: Vec<PathBuf>, interval: Duration) -> Self {
+        let paths = paths
+            .into_iter()
+            .map(|p| {
+                let mtime = modified(&p);
+                (p, mtime)
+            })
+            .collect();
+        Self { paths, interval }
+    }
+
+    fn poll(&mut self) -> Option<PathBuf> {
+        for (path, last) in &mut self.paths {
+            let current = modified(path);
+            if current != *last {
+                *last = current;
+                return Some(path.clone());
+            }
+        }
+        None
+    }
+}
+
+impl ChangeNotifier for PollingNotifier {
+    fn wait(&mut self, timeout: Option<Duration>) -> WaitResult {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        loop {
+            if let Some(path) = self.poll() {
+                return WaitResult::Changed(path);
+            }
+            match deadline {
+                Some(d) if Instant::now() >= d => return WaitResult::Timeout,
+                _ => std::thread::sleep(self.interval),
+            }
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    let meta = std::fs::metadata(path).ok()?;
+    if meta.is_dir() {
+        std::fs::read_dir(path)
+            .ok()?
+            .filter_map(|e| e.ok()?.metadata().ok()?.modified().ok())
+            .max()
+    } else {
+        meta.modified().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Replays a fixed sequence of wait results, then closes.
+    struct ScriptedNotifier {
+        script: VecDeque<WaitResult>,
+    }
+
+    impl ScriptedNotifier {
+        fn new(script: Vec<WaitResult>) -> Self {
+            Self {
+                script: script.into(),
+            }
+        }
+    }
+
+    impl ChangeNotifier for ScriptedNotifier {
+        fn wait(&mut self, _timeout: Option<Duration>) -> WaitResult {
+            self.script.pop_front().unwrap_or(WaitResult::Closed)
+        }
+    }
+
+    fn changed(name: &str) -> WaitResult {
+        WaitResult::Changed(PathBuf::from(name))
+    }
+
+    fn project_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("rsc-watch-{}-{}.json", name, std::process::id()));
+        std::fs::write(&path, ProjectFile::default().to_json().unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_burst_and_change_during_run() {
+        let path = project_path("burst");
+        let mut notifier = ScriptedNotifier::new(vec![
+            // A burst of saves, then quiet: one run.
+            changed("project.json"),
+            changed("project.json"),
+            changed("workflows/a.yaml"),
+            WaitResult::Timeout,
+            // Two saves land while the run is in progress.
+            changed("project.json"),
+            changed("workflows/b.yaml"),
+            WaitResult::Timeout,
+            // Debounce for the follow-up run.
+            WaitResult::Timeout,
+            // Nothing during the follow-up run.
+            WaitResult::Timeout,
+        ]);
+
+        let mut reports = Vec::new();
+        watch_with(
+            &mut notifier,
+            &path,
+            &[WatchAction::Validate],
+            &WatchOptions::default(),
+            |r| reports.push(r.clone()),
+        );
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].run, 1);
+        assert_eq!(
+            reports[0].changed,
+            [
+                PathBuf::from("project.json"),
+                PathBuf::from("workflows/a.yaml")
+            ]
+        );
+        assert_eq!(reports[1].run, 2);
+        assert_eq!(
+            reports[1].changed,
+            [
+                PathBuf::from("project.json"),
+                PathBuf::from("workflows/b.yaml")
+            ]
+        );
+        assert!(reports[1].outcomes[0].result.is_ok());
+    }
+
+    #[test]
+    fn test_actions_report_errors() {
+        let path = project_path("actions");
+        let out = path.with_extension("yaml");
+        let mut notifier = ScriptedNotifier::new(vec![]);
+
+        let mut reports = Vec::new();
+        watch_with(
+            &mut notifier,
+            &path,
+            &[WatchAction::Lint, WatchAction::ExportYaml(out.clone())],
+            &WatchOptions {
+                run_on_start: true,
+                ..Default::default()
+            },
+            |r| reports.push(r.clone()),
+        );
+        assert!(out.exists());
+        std::fs::remove_file(&out).ok();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].outcomes.iter().all(|o| o.result.is_ok()));
+
+        let missing = run_actions(
+            1,
+            Vec::new(),
+            Path::new("/nonexistent/project.json"),
+            &[WatchAction::Validate],
+        );
+        assert!(missing.outcomes[0].result.is_err());
+    }
+}